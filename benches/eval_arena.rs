@@ -0,0 +1,41 @@
+#![feature(test)]
+
+///! Test evaluating with and without a scratch arena
+extern crate test;
+
+use rhai::{Engine, OptimizationLevel, INT};
+use test::Bencher;
+
+const SCRIPT: &str = r#"
+    fn sum(a, b, c, d, e, f, g) { a + b + c + d + e + f + g }
+
+    let total = 0;
+
+    for n in 0..20 {
+        let s = `the quick brown fox ${n} jumps over the lazy dog ${sum(n, 1, 2, 3, 4, 5, 6)}`;
+        total += s.len;
+    }
+
+    total
+"#;
+
+#[bench]
+fn bench_eval_arena_none(bench: &mut Bencher) {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(SCRIPT).unwrap();
+
+    bench.iter(|| engine.eval_ast::<INT>(&ast).unwrap());
+}
+
+#[bench]
+fn bench_eval_arena(bench: &mut Bencher) {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+    engine.with_arena(true);
+
+    let ast = engine.compile(SCRIPT).unwrap();
+
+    bench.iter(|| engine.eval_ast::<INT>(&ast).unwrap());
+}
@@ -0,0 +1,108 @@
+#![feature(test)]
+#![cfg(feature = "bytecode")]
+
+///! Test evaluating the same scripts by walking the syntax tree and as bytecode
+extern crate test;
+
+use rhai::{Dynamic, Engine, OptimizationLevel};
+use test::Bencher;
+
+const SCRIPT_LOOP: &str = "
+    let x = 1_000;
+
+    while x > 0 {
+        x -= 1;
+    }
+";
+
+const SCRIPT_VARIABLES: &str = "
+    let total = 0;
+
+    for i in 0..1_000 {
+        let x = i * 2;
+        let y = x + 1;
+        total += x * y - i;
+    }
+
+    total
+";
+
+const SCRIPT_FN_CALLS: &str = "
+    fn fib(n) {
+        if n < 2 {
+            n
+        } else {
+            fib(n - 1) + fib(n - 2)
+        }
+    }
+
+    fib(15)
+";
+
+const SCRIPT_NATIVE_CALLS: &str = "
+    let a = [];
+
+    for i in 0..1_000 {
+        push(a, abs(i - 500));
+    }
+
+    len(a)
+";
+
+fn bench_ast(bench: &mut Bencher, script: &str) {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let ast = engine.compile(script).unwrap();
+
+    bench.iter(|| engine.eval_ast::<Dynamic>(&ast).unwrap());
+}
+
+fn bench_bytecode(bench: &mut Bencher, script: &str) {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let bytecode = engine.compile_bytecode(&engine.compile(script).unwrap());
+
+    bench.iter(|| engine.eval_bytecode::<Dynamic>(&bytecode).unwrap());
+}
+
+#[bench]
+fn bench_eval_bytecode_loop_ast(bench: &mut Bencher) {
+    bench_ast(bench, SCRIPT_LOOP);
+}
+
+#[bench]
+fn bench_eval_bytecode_loop(bench: &mut Bencher) {
+    bench_bytecode(bench, SCRIPT_LOOP);
+}
+
+#[bench]
+fn bench_eval_bytecode_variables_ast(bench: &mut Bencher) {
+    bench_ast(bench, SCRIPT_VARIABLES);
+}
+
+#[bench]
+fn bench_eval_bytecode_variables(bench: &mut Bencher) {
+    bench_bytecode(bench, SCRIPT_VARIABLES);
+}
+
+#[bench]
+fn bench_eval_bytecode_fn_calls_ast(bench: &mut Bencher) {
+    bench_ast(bench, SCRIPT_FN_CALLS);
+}
+
+#[bench]
+fn bench_eval_bytecode_fn_calls(bench: &mut Bencher) {
+    bench_bytecode(bench, SCRIPT_FN_CALLS);
+}
+
+#[bench]
+fn bench_eval_bytecode_native_calls_ast(bench: &mut Bencher) {
+    bench_ast(bench, SCRIPT_NATIVE_CALLS);
+}
+
+#[bench]
+fn bench_eval_bytecode_native_calls(bench: &mut Bencher) {
+    bench_bytecode(bench, SCRIPT_NATIVE_CALLS);
+}
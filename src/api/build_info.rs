@@ -0,0 +1,180 @@
+//! Module that defines the build information API of [`Engine`].
+
+use crate::Engine;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Make a list of feature flags, each paired with whether it is compiled in.
+macro_rules! feature_flags {
+    ($($name:tt),* $(,)?) => {
+        &[$(($name, cfg!(feature = $name))),*]
+    };
+}
+
+/// All the feature flags in `Cargo.toml` (except `default`), each paired with whether it is
+/// compiled into this build.
+pub(crate) const ALL_FEATURES: &[(&str, bool)] = feature_flags![
+    "std",
+    "unchecked",
+    "sync",
+    "async",
+    "no_position",
+    "no_optimize",
+    "no_float",
+    "f32_float",
+    "only_i32",
+    "only_i64",
+    "decimal",
+    "no_index",
+    "no_object",
+    "no_function",
+    "no_closure",
+    "no_module",
+    "no_custom_syntax",
+    "no_pkg_string",
+    "no_pkg_array",
+    "no_pkg_blob",
+    "no_pkg_math",
+    "no_pkg_time",
+    "no_pkg_logic",
+    "minimal",
+    "unicode-xid-ident",
+    "metadata",
+    "internals",
+    "debugging",
+    "dap",
+    "serde",
+    "url_resolver",
+    "bytecode",
+    "lsp",
+    "tracing",
+    "profiling",
+    "coverage",
+    "gc",
+    "no_std",
+    "wasm-bindgen",
+    "stdweb",
+    "bin-features",
+];
+
+/// Number of feature flags compiled into this build.
+const NUM_FEATURES: usize = {
+    let mut n = 0;
+    let mut i = 0;
+
+    while i < ALL_FEATURES.len() {
+        if ALL_FEATURES[i].1 {
+            n += 1;
+        }
+        i += 1;
+    }
+
+    n
+};
+
+/// Feature flags compiled into this build.
+const FEATURES: &[&str] = &{
+    let mut list = [""; NUM_FEATURES];
+    let mut n = 0;
+    let mut i = 0;
+
+    while i < ALL_FEATURES.len() {
+        if ALL_FEATURES[i].1 {
+            list[n] = ALL_FEATURES[i].0;
+            n += 1;
+        }
+        i += 1;
+    }
+
+    list
+};
+
+/// Packages making up the [`StandardPackage`][crate::packages::StandardPackage] in this build.
+const PACKAGES: &[&str] = &[
+    "LanguageCorePackage",
+    "ArithmeticPackage",
+    "BasicStringPackage",
+    "BasicIteratorPackage",
+    "BasicFnPackage",
+    #[cfg(feature = "debugging")]
+    "DebuggingPackage",
+    "BitFieldPackage",
+    #[cfg(not(feature = "no_pkg_logic"))]
+    "LogicPackage",
+    #[cfg(not(feature = "no_pkg_math"))]
+    "BasicMathPackage",
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_pkg_array"))]
+    "BasicArrayPackage",
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_pkg_array"))]
+    "BasicPackedArrayPackage",
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_pkg_blob"))]
+    "BasicBlobPackage",
+    #[cfg(not(feature = "no_index"))]
+    "BasicSetPackage",
+    #[cfg(not(feature = "no_object"))]
+    "BasicMapPackage",
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(feature = "no_pkg_time"))]
+    "BasicTimePackage",
+    #[cfg(not(feature = "no_pkg_string"))]
+    "MoreStringPackage",
+];
+
+/// Information on how Rhai is compiled, returned by [`Engine::build_info`].
+///
+/// Useful for reasoning about the binary size contribution of Rhai, especially in embedded
+/// environments.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct BuildInfo {
+    /// Version of Rhai.
+    pub version: &'static str,
+    /// Feature flags compiled in.
+    pub features: &'static [&'static str],
+    /// Packages making up the [`StandardPackage`][crate::packages::StandardPackage], which is
+    /// registered by [`Engine::new`].
+    pub packages: &'static [&'static str],
+}
+
+impl BuildInfo {
+    /// Is a feature flag compiled in?
+    #[inline]
+    #[must_use]
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.contains(&feature)
+    }
+    /// Is a package part of the [`StandardPackage`][crate::packages::StandardPackage]?
+    #[inline]
+    #[must_use]
+    pub fn has_package(&self, package: &str) -> bool {
+        self.packages.contains(&package)
+    }
+}
+
+impl Engine {
+    /// Get information on how Rhai is compiled, including its version, the feature flags
+    /// compiled in and the packages making up the standard library.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let info = Engine::build_info();
+    ///
+    /// assert!(info.has_package("ArithmeticPackage"));
+    /// assert_eq!(info.has_feature("sync"), cfg!(feature = "sync"));
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub const fn build_info() -> BuildInfo {
+        BuildInfo {
+            version: env!("CARGO_PKG_VERSION"),
+            features: FEATURES,
+            packages: PACKAGES,
+        }
+    }
+}
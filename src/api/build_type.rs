@@ -1,10 +1,13 @@
 //! Trait to build a custom type for use with [`Engine`].
 #![allow(deprecated)]
 
-use crate::{types::dynamic::Variant, Engine, Identifier, RegisterNativeFunction};
-use std::marker::PhantomData;
+use crate::func::SendSync;
+use crate::{
+    types::dynamic::Variant, Dynamic, Engine, Identifier, RegisterNativeFunction, RhaiResult,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::{any::TypeId, marker::PhantomData};
 
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
 use crate::func::register::Mut;
@@ -103,6 +106,17 @@ impl Engine {
 pub struct TypeBuilder<'a, T: Variant + Clone> {
     engine: &'a mut Engine,
     name: Option<&'static str>,
+    /// Doc-comments of the type.
+    #[cfg(feature = "metadata")]
+    comments: crate::StaticVec<&'static str>,
+    /// Names of the fields with getters.
+    #[cfg(not(feature = "no_object"))]
+    #[cfg(not(feature = "no_index"))]
+    field_names: crate::StaticVec<Identifier>,
+    /// Iterate over the fields in `for` loops?
+    #[cfg(not(feature = "no_object"))]
+    #[cfg(not(feature = "no_index"))]
+    field_iter: bool,
     _marker: PhantomData<T>,
 }
 
@@ -113,6 +127,14 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
         Self {
             engine,
             name: None,
+            #[cfg(feature = "metadata")]
+            comments: crate::StaticVec::new_const(),
+            #[cfg(not(feature = "no_object"))]
+            #[cfg(not(feature = "no_index"))]
+            field_names: crate::StaticVec::new_const(),
+            #[cfg(not(feature = "no_object"))]
+            #[cfg(not(feature = "no_index"))]
+            field_iter: false,
             _marker: PhantomData::default(),
         }
     }
@@ -126,6 +148,16 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
         self
     }
 
+    /// _(metadata)_ Set doc-comments for the type, one string slice per line, each starting with
+    /// the doc-comment leader `///`.
+    /// Exported under the `metadata` feature only.
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    pub fn with_comments(&mut self, comments: &[&'static str]) -> &mut Self {
+        self.comments = comments.iter().copied().collect();
+        self
+    }
+
     /// Register a custom function.
     #[inline(always)]
     pub fn with_fn<A, R, S>(
@@ -136,6 +168,58 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
         self.engine.register_fn(name, method);
         self
     }
+
+    /// Register a fallback handler for calls to functions that are not registered, where the
+    /// first argument is of this type (e.g. method calls).
+    ///
+    /// The handler is called with the object, the name of the function and the rest of the
+    /// arguments.  It is only consulted for function names that are valid identifiers
+    /// (i.e. not operators, indexers, getters or setters).
+    ///
+    /// There can only be one such handler per type.  A new handler replaces any existing one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{CustomType, TypeBuilder, Engine};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Proxy;
+    ///
+    /// impl CustomType for Proxy {
+    ///     fn build(mut builder: TypeBuilder<Self>) {
+    ///         builder
+    ///             .with_fn("new_proxy", || Proxy)
+    ///             .on_unknown_method(|_, name, args| {
+    ///                 Ok(format!("{}/{}", name, args.len()).into())
+    ///             });
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.build_type::<Proxy>();
+    ///
+    /// # #[cfg(not(feature = "no_object"))]
+    /// assert_eq!(engine.eval::<String>("new_proxy().hello(1, 2)")?, "hello/2");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn on_unknown_method(
+        &mut self,
+        handler: impl Fn(&mut T, &str, &mut [&mut Dynamic]) -> RhaiResult + SendSync + 'static,
+    ) -> &mut Self {
+        self.engine.unknown_method_handlers.insert(
+            TypeId::of::<T>(),
+            Box::new(move |obj, name, args| {
+                let obj = &mut *obj.write_lock::<T>().expect("`T`");
+                handler(obj, name, args)
+            }),
+        );
+        self
+    }
 }
 
 impl<'a, T> TypeBuilder<'a, T>
@@ -165,6 +249,8 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
         name: impl AsRef<str>,
         get_fn: impl RegisterNativeFunction<(Mut<T>,), V, S> + crate::func::SendSync + 'static,
     ) -> &mut Self {
+        #[cfg(not(feature = "no_index"))]
+        self.add_field(name.as_ref());
         self.engine.register_get(name, get_fn);
         self
     }
@@ -182,6 +268,64 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
         self
     }
 
+    /// Register a fallback handler for properties that do not have a registered getter.
+    ///
+    /// The handler is called with the object and the name of the property.
+    /// It is consulted before falling back to an indexer.
+    ///
+    /// There can only be one such handler per type.  A new handler replaces any existing one.
+    ///
+    /// Not available under `no_object`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{CustomType, TypeBuilder, Engine, EvalAltResult, Map, Position, INT};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Row(Map);
+    ///
+    /// impl CustomType for Row {
+    ///     fn build(mut builder: TypeBuilder<Self>) {
+    ///         builder
+    ///             .with_fn("new_row", || {
+    ///                 let mut map = Map::new();
+    ///                 map.insert("id".into(), (42 as INT).into());
+    ///                 Row(map)
+    ///             })
+    ///             .on_unknown_property(|row, prop| {
+    ///                 row.0.get(prop).cloned().ok_or_else(|| {
+    ///                     EvalAltResult::ErrorPropertyNotFound(prop.into(), Position::NONE).into()
+    ///                 })
+    ///             });
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.build_type::<Row>();
+    ///
+    /// assert_eq!(engine.eval::<INT>("new_row().id")?, 42);
+    /// assert!(engine.eval::<INT>("new_row().name").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn on_unknown_property(
+        &mut self,
+        handler: impl Fn(&mut T, &str) -> RhaiResult + SendSync + 'static,
+    ) -> &mut Self {
+        self.engine.unknown_property_handlers.insert(
+            TypeId::of::<T>(),
+            Box::new(move |obj, prop| {
+                let obj = &mut *obj.write_lock::<T>().expect("`T`");
+                handler(obj, prop)
+            }),
+        );
+        self
+    }
+
     /// Short-hand for registering both getter and setter functions.
     ///
     /// All function signatures must start with `&mut self` and not `&self`.
@@ -194,9 +338,77 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
         get_fn: impl RegisterNativeFunction<(Mut<T>,), V, S1> + crate::func::SendSync + 'static,
         set_fn: impl RegisterNativeFunction<(Mut<T>, V), (), S2> + crate::func::SendSync + 'static,
     ) -> &mut Self {
+        #[cfg(not(feature = "no_index"))]
+        self.add_field(name.as_ref());
         self.engine.register_get_set(name, get_fn, set_fn);
         self
     }
+
+    /// Iterate over the fields of this type in `for` loops.
+    ///
+    /// Each item is an array of the name and the value of a field with a getter registered via
+    /// [`with_get`][TypeBuilder::with_get] or [`with_get_set`][TypeBuilder::with_get_set],
+    /// in the order they are registered.
+    ///
+    /// This is only used when no type iterator is registered for this type
+    /// (e.g. via [`is_iterable`][TypeBuilder::is_iterable]).
+    ///
+    /// Not available under `no_object` or `no_index`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{CustomType, TypeBuilder, Engine, INT};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Point {
+    ///     x: INT,
+    ///     y: INT,
+    /// }
+    ///
+    /// impl CustomType for Point {
+    ///     fn build(mut builder: TypeBuilder<Self>) {
+    ///         builder
+    ///             .with_fn("new_point", |x: INT, y: INT| Point { x, y })
+    ///             .with_get("x", |p: &mut Self| p.x)
+    ///             .with_get("y", |p: &mut Self| p.y)
+    ///             .with_field_iter();
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.build_type::<Point>();
+    ///
+    /// let result = engine.eval::<String>(
+    ///     r#"
+    ///         let s = "";
+    ///         for field in new_point(1, 2) {
+    ///             s += `${field[0]}=${field[1]};`;
+    ///         }
+    ///         s
+    ///     "#,
+    /// )?;
+    ///
+    /// assert_eq!(result, "x=1;y=2;");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    pub fn with_field_iter(&mut self) -> &mut Self {
+        self.field_iter = true;
+        self
+    }
+
+    /// Record the name of a field with a getter, if it is not already recorded.
+    #[cfg(not(feature = "no_index"))]
+    fn add_field(&mut self, name: &str) {
+        if !self.field_names.iter().any(|f| f == name) {
+            self.field_names.push(name.into());
+        }
+    }
 }
 
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
@@ -244,6 +456,32 @@ impl<'a, T: Variant + Clone> TypeBuilder<'a, T> {
 impl<'a, T: Variant + Clone> Drop for TypeBuilder<'a, T> {
     #[inline]
     fn drop(&mut self) {
+        #[cfg(not(feature = "no_object"))]
+        #[cfg(not(feature = "no_index"))]
+        if self.field_iter {
+            let fields = std::mem::take(&mut self.field_names);
+            self.engine
+                .field_iterators
+                .insert(TypeId::of::<T>(), fields);
+        }
+
+        #[cfg(feature = "metadata")]
+        if !self.comments.is_empty() {
+            let name = self.name.map_or_else(
+                || {
+                    self.engine
+                        .map_type_name(std::any::type_name::<T>())
+                        .as_ref()
+                        .into()
+                },
+                crate::SmartString::from,
+            );
+            let comments = std::mem::take(&mut self.comments);
+            self.engine
+                .register_type_with_name_and_comments::<T, _>(&name, comments);
+            return;
+        }
+
         if let Some(name) = self.name {
             self.engine.register_type_with_name::<T>(name);
         } else {
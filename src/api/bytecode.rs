@@ -0,0 +1,110 @@
+//! Module that defines the bytecode API of [`Engine`].
+#![cfg(feature = "bytecode")]
+
+use crate::eval::{Bytecode, GlobalRuntimeState};
+use crate::types::dynamic::Variant;
+use crate::{Engine, RhaiResultOf, Scope, AST};
+use std::any::TypeId;
+
+impl Engine {
+    /// Compile an [`AST`] into [`Bytecode`] for faster evaluation.
+    /// Exported under the `bytecode` feature only.
+    ///
+    /// Statements at global level and the bodies of script-defined functions are compiled into a
+    /// compact bytecode, which avoids walking the syntax tree for loops, conditions, blocks,
+    /// variables, `let`/`const` statements, assignments to variables, `return`/`throw`, built-in
+    /// operators and function calls.
+    /// Everything else is still evaluated by walking the syntax tree, with identical semantics.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("let x = 0; for i in 0..10 { x += i; } x")?;
+    ///
+    /// let bytecode = engine.compile_bytecode(&ast);
+    ///
+    /// assert_eq!(engine.eval_bytecode::<i64>(&bytecode)?, 45);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub fn compile_bytecode(&self, ast: &AST) -> Bytecode {
+        Bytecode::new(ast)
+    }
+    /// Evaluate [`Bytecode`], returning the result value or an error.
+    /// Exported under the `bytecode` feature only.
+    #[inline(always)]
+    pub fn eval_bytecode<T: Variant + Clone>(&self, bytecode: &Bytecode) -> RhaiResultOf<T> {
+        self.eval_bytecode_with_scope(&mut Scope::new(), bytecode)
+    }
+    /// Evaluate [`Bytecode`] with own scope, returning the result value or an error.
+    /// Exported under the `bytecode` feature only.
+    ///
+    /// If a debugger or an [operator trace][Engine::on_operator] is registered, if coverage is
+    /// being tracked (under the `coverage` feature) or profiling is on (under the `profiling`
+    /// feature), or if [fast operators][Engine::fast_operators] mode is turned off, the original
+    /// [`AST`] is evaluated instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut scope = Scope::new();
+    /// scope.push("x", 40_i64);
+    ///
+    /// let bytecode = engine.compile_bytecode(&engine.compile("x += 2; x")?);
+    ///
+    /// assert_eq!(engine.eval_bytecode_with_scope::<i64>(&mut scope, &bytecode)?, 42);
+    /// assert_eq!(engine.eval_bytecode_with_scope::<i64>(&mut scope, &bytecode)?, 44);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_bytecode_with_scope<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        bytecode: &Bytecode,
+    ) -> RhaiResultOf<T> {
+        #[cfg(feature = "debugging")]
+        if self.debugger.is_some() {
+            return self.eval_ast_with_scope(scope, bytecode.ast());
+        }
+
+        // Coverage is only recorded by evaluating the AST
+        #[cfg(feature = "coverage")]
+        if self.coverage.is_some() {
+            return self.eval_ast_with_scope(scope, bytecode.ast());
+        }
+
+        // Statements are only profiled by evaluating the AST
+        #[cfg(feature = "profiling")]
+        if self.profile.is_some() {
+            return self.eval_ast_with_scope(scope, bytecode.ast());
+        }
+
+        if self.operator_trace.is_some() || !self.fast_operators() {
+            return self.eval_ast_with_scope(scope, bytecode.ast());
+        }
+
+        let global = &mut GlobalRuntimeState::new(self);
+
+        let result = self.eval_bytecode_raw(scope, global, bytecode, 0);
+        self.report_eval_end(global, result.as_ref().err().map(|err| &**err));
+        let result = result?.flatten();
+
+        if TypeId::of::<T>() == TypeId::of::<crate::Dynamic>() || result.is::<T>() {
+            Ok(result.cast::<T>())
+        } else {
+            Err(self.make_output_type_error::<T>(&result))
+        }
+    }
+}
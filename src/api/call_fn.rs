@@ -4,13 +4,60 @@
 use crate::eval::{Caches, GlobalRuntimeState};
 use crate::types::dynamic::Variant;
 use crate::{
-    reify, Dynamic, Engine, FuncArgs, Position, RhaiResult, RhaiResultOf, Scope, StaticVec, AST,
-    ERR,
+    reify, Dynamic, Engine, FuncArgs, Module, Position, RhaiResult, RhaiResultOf, Scope, Shared,
+    StaticVec, AST, ERR,
 };
 use std::any::{type_name, TypeId};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// A reusable session for repeatedly calling script-defined functions in an [`AST`] via
+/// [`Engine::call_fn_with_session`].
+///
+/// Not available under `no_function`.
+///
+/// The session keeps the function resolution caches and the global runtime state (including
+/// modules imported by the [`AST`] and the tag) intact across calls, making hot callback paths
+/// much cheaper than calling [`Engine::call_fn`] each time.
+///
+/// Using the session with a different [`AST`] starts afresh.
+#[derive(Debug, Clone)]
+pub struct EvalSession {
+    /// Global runtime state.
+    global: GlobalRuntimeState<'static>,
+    /// Function resolution caches.
+    caches: Caches<'static>,
+    /// Functions of the [`AST`] the session is used with, if any.
+    lib: Option<Shared<Module>>,
+}
+
+impl EvalSession {
+    /// Create a new [`EvalSession`] based on an [`Engine`].
+    #[inline]
+    #[must_use]
+    pub fn new(engine: &Engine) -> Self {
+        Self {
+            global: GlobalRuntimeState::new(engine),
+            caches: Caches::new(),
+            lib: None,
+        }
+    }
+    /// Get the custom state of the session, which is kept across calls.
+    ///
+    /// It is initialized to the [default tag][Engine::default_tag] of the [`Engine`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn tag(&self) -> &Dynamic {
+        &self.global.tag
+    }
+    /// Get a mutable reference to the custom state of the session, which is kept across calls.
+    #[inline(always)]
+    #[must_use]
+    pub fn tag_mut(&mut self) -> &mut Dynamic {
+        &mut self.global.tag
+    }
+}
+
 impl Engine {
     /// Call a script function defined in an [`AST`] with multiple arguments.
     ///
@@ -67,6 +114,132 @@ impl Engine {
 
         let result = self.call_fn_raw(scope, ast, true, true, name, None, arg_values)?;
 
+        self.cast_fn_result(result)
+    }
+    /// Call a script function defined in an [`AST`] with multiple arguments, re-using an
+    /// [`EvalSession`] across calls.
+    ///
+    /// Not available under `no_function`.
+    ///
+    /// This works like [`call_fn`][Engine::call_fn], except that the function resolution caches and
+    /// the global runtime state are kept in the [`EvalSession`] instead of being built anew for
+    /// each call.  The [`AST`] is evaluated (to load the necessary modules) only on the first call
+    /// with each [`AST`], or again after an error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, EvalSession, Scope};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("fn on_tick(n) { n * 2 + 1 }")?;
+    ///
+    /// let mut session = EvalSession::new(&engine);
+    /// let mut scope = Scope::new();
+    ///
+    /// for n in 0..1000_i64 {
+    ///     let result = engine.call_fn_with_session::<i64>(&mut session, &mut scope, &ast, "on_tick", (n,))?;
+    ///     assert_eq!(result, n * 2 + 1);
+    /// }
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn call_fn_with_session<T: Variant + Clone>(
+        &self,
+        session: &mut EvalSession,
+        scope: &mut Scope,
+        ast: &AST,
+        name: impl AsRef<str>,
+        args: impl FuncArgs,
+    ) -> RhaiResultOf<T> {
+        let mut arg_values = StaticVec::new_const();
+        args.parse(&mut arg_values);
+
+        let EvalSession {
+            global,
+            caches,
+            lib,
+        } = session;
+
+        let eval_ast = !lib
+            .as_ref()
+            .map_or(false, |lib| Shared::ptr_eq(lib, ast.shared_lib()));
+
+        if eval_ast {
+            // Start afresh, as imported modules and cached functions may belong to another AST
+            let tag = std::mem::take(&mut global.tag);
+            *global = GlobalRuntimeState::new(self);
+            global.tag = tag;
+            *caches = Caches::new();
+            *lib = Some(ast.shared_lib().clone());
+        } else {
+            global.start_new_run(self);
+        }
+
+        let result = self._call_fn(
+            scope,
+            global,
+            caches,
+            ast,
+            eval_ast,
+            true,
+            name.as_ref(),
+            None,
+            arg_values.as_mut(),
+        );
+
+        self.report_eval_end(global, result.as_ref().err().map(|err| &**err));
+
+        if result.is_err() {
+            // The global runtime state may be left inconsistent
+            *lib = None;
+        }
+
+        self.cast_fn_result(result?)
+    }
+    /// Call a script function defined in an [`AST`] with multiple arguments, using a particular
+    /// global runtime state.
+    #[cfg(feature = "async")]
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) fn call_fn_with_global<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        ast: &AST,
+        name: &str,
+        args: impl FuncArgs,
+    ) -> RhaiResultOf<T> {
+        let mut arg_values = StaticVec::new_const();
+        args.parse(&mut arg_values);
+
+        let caches = &mut self.new_caches();
+
+        let result = self._call_fn(
+            scope,
+            global,
+            caches,
+            ast,
+            true,
+            true,
+            name,
+            None,
+            arg_values.as_mut(),
+        );
+
+        self.recycle_caches(caches);
+
+        self.report_eval_end(global, result.as_ref().err().map(|err| &**err));
+
+        self.cast_fn_result(result?)
+    }
+    /// Cast the result of calling a script function into a particular type.
+    fn cast_fn_result<T: Variant + Clone>(&self, result: Dynamic) -> RhaiResultOf<T> {
         // Bail out early if the return type needs no cast
         if TypeId::of::<T>() == TypeId::of::<Dynamic>() {
             return Ok(reify!(result => T));
@@ -161,18 +334,27 @@ impl Engine {
         arg_values: impl AsMut<[Dynamic]>,
     ) -> RhaiResult {
         let mut arg_values = arg_values;
+        let caches = &mut self.new_caches();
 
-        self._call_fn(
+        let global = &mut GlobalRuntimeState::new(self);
+
+        let result = self._call_fn(
             scope,
-            &mut GlobalRuntimeState::new(self),
-            &mut Caches::new(),
+            global,
+            caches,
             ast,
             eval_ast,
             rewind_scope,
             name.as_ref(),
             this_ptr,
             arg_values.as_mut(),
-        )
+        );
+
+        self.recycle_caches(caches);
+
+        self.report_eval_end(global, result.as_ref().err().map(|err| &**err));
+
+        result
     }
     /// _(internals)_ Call a script function defined in an [`AST`] with multiple [`Dynamic`] arguments.
     /// Exported under the `internals` feature only.
@@ -274,6 +456,7 @@ impl Engine {
         let fn_def = ast
             .shared_lib()
             .get_script_fn(name, args.len())
+            .filter(|f| f.visibility.is_host_callable())
             .ok_or_else(|| ERR::ErrorFunctionNotFound(name.into(), Position::NONE))?;
 
         let result = self.call_script_fn(
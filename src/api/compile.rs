@@ -1,9 +1,37 @@
 //! Module that defines the public compilation API of [`Engine`].
 
 use crate::parser::{ParseResult, ParseState};
-use crate::{Engine, OptimizationLevel, Scope, AST};
+use crate::tokenizer::{InputChars, TokenStream};
+use crate::{
+    Diagnostic, Engine, OptimizationLevel, ParseError, ParseWarning, Position, Scope, AST,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::time::Duration;
+
+/// Statistics of a compilation, returned by [`Engine::compile_with_stats`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct CompileStats {
+    /// Number of tokens in the script.
+    pub tokens: usize,
+    /// Number of nodes (statements and expressions) in the compiled [`AST`], including function
+    /// bodies.
+    pub nodes: usize,
+    /// Number of functions defined in the script, including anonymous functions and closures.
+    pub functions: usize,
+    /// Number of passes made by the optimizer over blocks of statements.
+    ///
+    /// This is zero if optimization is turned off.
+    pub optimizer_passes: usize,
+    /// Time spent tokenizing and parsing the script.
+    ///
+    /// Always zero under `no_std`.
+    pub parse_time: Duration,
+    /// Time spent optimizing the [`AST`].
+    ///
+    /// Always zero under `no_std`.
+    pub optimize_time: Duration,
+}
 
 impl Engine {
     /// Compile a string into an [`AST`], which can be used later for evaluation.
@@ -70,6 +98,198 @@ impl Engine {
     pub fn compile_with_scope(&self, scope: &Scope, script: impl AsRef<str>) -> ParseResult<AST> {
         self.compile_scripts_with_scope(scope, &[script])
     }
+    /// Compile a string into an [`AST`], also returning [statistics][CompileStats] of the
+    /// compilation (e.g. for tracking the growth in complexity of scripts over time).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let (ast, stats) = engine.compile_with_stats("fn add(x, y) { x + y } add(40, 2)")?;
+    ///
+    /// assert_eq!(stats.functions, 1);
+    /// assert!(stats.tokens > 10);
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile_with_stats(&self, script: impl AsRef<str>) -> ParseResult<(AST, CompileStats)> {
+        let scope = &Scope::new();
+        let scripts = [script];
+        let (stream, tokenizer_control) =
+            self.lex_raw(&scripts, self.token_mapper.as_ref().map(<_>::as_ref));
+        let mut state = ParseState::new_with_strings_pool(self, scope, tokenizer_control);
+        let mut stats = CompileStats::default();
+        let result = self.parse_with_stats(
+            &mut TokenStream::new(stream),
+            &mut state,
+            self.optimization_level,
+            &mut stats,
+        );
+        state.return_strings_pool(self);
+        let mut _ast = result?;
+        #[cfg(feature = "metadata")]
+        _ast.set_doc(state.tokenizer_control.borrow().global_comments.join("\n"));
+
+        _ast.walk(&mut |_| {
+            stats.nodes += 1;
+            true
+        });
+
+        Ok((_ast, stats))
+    }
+    /// Compile a string into an [`AST`], recovering from parse errors in order to return all of
+    /// them instead of stopping at the first error.
+    ///
+    /// When a statement fails to parse, the rest of the statement (up to the next `;` or `}`) is
+    /// skipped and parsing continues with the next statement, within blocks and function bodies
+    /// as well as at global level.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let errors = engine.compile_with_recovery("
+    ///     let x = 40 + ;
+    ///     if x > 0 {
+    ///         let y = ;
+    ///         x = x + 2;
+    ///     }
+    ///     x +
+    /// ").unwrap_err();
+    ///
+    /// assert_eq!(errors.len(), 3);
+    /// ```
+    #[inline(always)]
+    pub fn compile_with_recovery(&self, script: impl AsRef<str>) -> Result<AST, Vec<ParseError>> {
+        self.compile_with_scope_and_recovery(&Scope::new(), script)
+    }
+    /// Compile a string into an [`AST`] using own scope, recovering from parse errors in order to
+    /// return all of them instead of stopping at the first error.
+    ///
+    /// See [`compile_with_recovery`][Engine::compile_with_recovery] for details.
+    #[inline]
+    pub fn compile_with_scope_and_recovery(
+        &self,
+        scope: &Scope,
+        script: impl AsRef<str>,
+    ) -> Result<AST, Vec<ParseError>> {
+        match self.compile_collecting_errors(scope, script, self.optimization_level) {
+            (Some(ast), errors, ..) if errors.is_empty() => Ok(ast),
+            (_, errors, _) => Err(errors),
+        }
+    }
+    /// Compile a string into an [`AST`], collecting all parse errors and warnings as
+    /// [diagnostics][Diagnostic] instead of stopping at the first error.
+    ///
+    /// Parse errors are recovered from in the same way as
+    /// [`compile_with_recovery`][Engine::compile_with_recovery].
+    ///
+    /// Returns the [`AST`] (or [`None`] if there are errors) together with all
+    /// [diagnostics][Diagnostic] in the order of their positions in the script.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let (ast, diagnostics) = engine.compile_with_diagnostics("
+    ///     let x = 40 let y = 2;
+    ///     let z = x + ;
+    ///     x + y
+    /// ");
+    ///
+    /// assert!(ast.is_none());
+    /// assert_eq!(diagnostics.len(), 2);
+    /// assert_eq!(diagnostics[0].span().start().line(), Some(2));
+    /// assert_eq!(diagnostics[1].span().start().line(), Some(3));
+    /// ```
+    #[inline(always)]
+    pub fn compile_with_diagnostics(
+        &self,
+        script: impl AsRef<str>,
+    ) -> (Option<AST>, Vec<Diagnostic>) {
+        self.compile_with_scope_and_diagnostics(&Scope::new(), script)
+    }
+    /// Compile a string into an [`AST`] using own scope, collecting all parse errors and warnings
+    /// as [diagnostics][Diagnostic] instead of stopping at the first error.
+    ///
+    /// See [`compile_with_diagnostics`][Engine::compile_with_diagnostics] for details.
+    pub fn compile_with_scope_and_diagnostics(
+        &self,
+        scope: &Scope,
+        script: impl AsRef<str>,
+    ) -> (Option<AST>, Vec<Diagnostic>) {
+        let (ast, errors, warnings) =
+            self.compile_collecting_errors(scope, script, self.optimization_level);
+        let ast = ast.filter(|_| errors.is_empty());
+
+        let mut diagnostics: Vec<_> = errors
+            .iter()
+            .map(Diagnostic::from)
+            .chain(
+                warnings
+                    .iter()
+                    .map(|(warning, pos)| Diagnostic::from_warning(warning, *pos)),
+            )
+            .collect();
+
+        diagnostics.sort_by_key(|d| d.span().start());
+
+        (ast, diagnostics)
+    }
+    /// Compile a string into an [`AST`] using own scope, collecting all parse errors and warnings
+    /// instead of stopping at the first error.
+    ///
+    /// If there are errors, the [`AST`] (if any) only contains the statements that are parsed
+    /// successfully.
+    pub(crate) fn compile_collecting_errors(
+        &self,
+        scope: &Scope,
+        script: impl AsRef<str>,
+        optimization_level: OptimizationLevel,
+    ) -> (Option<AST>, Vec<ParseError>, Vec<(ParseWarning, Position)>) {
+        let scripts = [script];
+        let (stream, tokenizer_control) =
+            self.lex_raw(&scripts, self.token_mapper.as_ref().map(<_>::as_ref));
+        tokenizer_control.borrow_mut().collect_errors();
+
+        let mut state = ParseState::new_with_strings_pool(self, scope, tokenizer_control);
+        let result = self.parse(
+            &mut TokenStream::new(stream),
+            &mut state,
+            optimization_level,
+        );
+        state.return_strings_pool(self);
+
+        let (mut errors, warnings) = state.tokenizer_control.borrow_mut().take_errors();
+
+        match result {
+            Ok(_ast) => {
+                #[cfg(feature = "metadata")]
+                let mut _ast = _ast;
+                #[cfg(feature = "metadata")]
+                _ast.set_doc(state.tokenizer_control.borrow().global_comments.join("\n"));
+                (Some(_ast), errors, warnings)
+            }
+            Err(err) => {
+                if errors.last() != Some(&err) {
+                    errors.push(err);
+                }
+                (None, errors, warnings)
+            }
+        }
+    }
     /// Compile a string into an [`AST`] using own scope, which can be used later for evaluation,
     /// embedding all imported modules.
     ///
@@ -115,7 +335,10 @@ impl Engine {
 
         let mut ast = self.compile_with_scope(scope, script)?;
 
-        let mut resolver = StaticModuleResolver::new();
+        // Keep modules already embedded (e.g. those of `enum` definitions)
+        let mut resolver = ast
+            .resolver()
+            .map_or_else(StaticModuleResolver::new, |r| r.as_ref().clone());
         let mut imports = BTreeSet::new();
 
         collect_imports(&ast, &resolver, &mut imports);
@@ -203,6 +426,99 @@ impl Engine {
     ) -> ParseResult<AST> {
         self.compile_with_scope_and_optimization_level(scope, scripts, self.optimization_level)
     }
+    /// Compile a list of named sources into a single [`AST`] whose functions all share one
+    /// namespace, which can be used later for evaluation.
+    ///
+    /// Each source is compiled separately (so a syntax error never spills over into the next
+    /// source) and the statements are run in the order of the list.  A function defined in one
+    /// source can be called from any other, without `import`.
+    ///
+    /// # Errors
+    ///
+    /// Errors are wrapped in [`ErrorInModule`][crate::EvalAltResult::ErrorInModule] carrying the
+    /// name of the offending source.
+    ///
+    /// Defining a function with the same name and number of parameters in more than one source
+    /// is an error ([`FnDuplicatedDefinition`][crate::ParseErrorType::FnDuplicatedDefinition]),
+    /// reported against the later source.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile_sources(&[
+    ///     ("main.rhai", "let x = 40; add(x, 2)"),
+    ///     ("util.rhai", "fn add(a, b) { a + b }"),
+    /// ])?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    ///
+    /// let err = engine.compile_sources(&[
+    ///     ("a.rhai", "fn foo(x) { x }"),
+    ///     ("b.rhai", "fn foo(y) { y + 1 }"),
+    /// ]).unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Error in module 'b.rhai' > Syntax error: Function foo with 1 parameter already exists"
+    /// );
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn compile_sources<N: AsRef<str>, S: AsRef<str>>(
+        &self,
+        sources: impl AsRef<[(N, S)]>,
+    ) -> crate::RhaiResultOf<AST> {
+        self.compile_sources_with_scope(&Scope::new(), sources)
+    }
+    /// Compile a list of named sources into a single [`AST`] using own scope, whose functions all
+    /// share one namespace.
+    ///
+    /// See [`compile_sources`][Engine::compile_sources] for details.
+    pub fn compile_sources_with_scope<N: AsRef<str>, S: AsRef<str>>(
+        &self,
+        scope: &Scope,
+        sources: impl AsRef<[(N, S)]>,
+    ) -> crate::RhaiResultOf<AST> {
+        use crate::{Position, ERR};
+
+        let mut ast = AST::empty();
+
+        for (name, script) in sources.as_ref() {
+            let name = name.as_ref();
+
+            let source_ast = self
+                .compile_with_scope(scope, script)
+                .map_err(|err| ERR::ErrorInModule(name.into(), err.into(), Position::NONE))?;
+
+            #[cfg(not(feature = "no_function"))]
+            for fn_def in source_ast.iter_fn_def() {
+                let (fn_name, num_params) = (&fn_def.name, fn_def.params.len());
+
+                if ast
+                    .shared_lib()
+                    .get_script_fn(fn_name, num_params)
+                    .is_some()
+                {
+                    let err = crate::PERR::FnDuplicatedDefinition(fn_name.to_string(), num_params)
+                        .into_err(Position::NONE);
+                    return Err(ERR::ErrorInModule(name.into(), err.into(), Position::NONE).into());
+                }
+            }
+
+            ast.combine(source_ast);
+        }
+
+        Ok(ast)
+    }
     /// Join a list of strings and compile into an [`AST`] using own scope at a specific optimization level.
     ///
     /// ## Constants Propagation
@@ -221,12 +537,129 @@ impl Engine {
             scripts.as_ref(),
             self.token_mapper.as_ref().map(<_>::as_ref),
         );
-        let mut state = ParseState::new(self, scope, Default::default(), tokenizer_control);
-        let mut _ast = self.parse(&mut stream.peekable(), &mut state, optimization_level)?;
+        let mut state = ParseState::new_with_strings_pool(self, scope, tokenizer_control);
+        let result = self.parse(
+            &mut TokenStream::new(stream),
+            &mut state,
+            optimization_level,
+        );
+        state.return_strings_pool(self);
+        let mut _ast = result?;
         #[cfg(feature = "metadata")]
         _ast.set_doc(state.tokenizer_control.borrow().global_comments.join("\n"));
         Ok(_ast)
     }
+    /// Compile a stream of characters into an [`AST`], which can be used later for evaluation.
+    ///
+    /// The script is tokenized as the characters are consumed, so it never needs to be held in
+    /// memory in full.  This is useful for very large generated scripts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// // Generate `let x = 0; x += 1; ... x += 1; x` on the fly
+    /// let script = "let x = 0;"
+    ///     .chars()
+    ///     .chain((0..42).flat_map(|_| " x += 1;".chars()))
+    ///     .chain(" x".chars());
+    ///
+    /// let ast = engine.compile_stream(script)?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn compile_stream(&self, script: impl IntoIterator<Item = char>) -> ParseResult<AST> {
+        self.compile_stream_with_scope(&Scope::new(), script)
+    }
+    /// Compile a stream of characters into an [`AST`] using own scope, which can be used later
+    /// for evaluation.
+    ///
+    /// See [`compile_stream`][Engine::compile_stream] for details.
+    #[inline]
+    pub fn compile_stream_with_scope(
+        &self,
+        scope: &Scope,
+        script: impl IntoIterator<Item = char>,
+    ) -> ParseResult<AST> {
+        self.compile_input_with_scope(scope, InputChars::Stream(Box::new(script.into_iter())))
+    }
+    /// Compile a character source into an [`AST`] using own scope.
+    fn compile_input_with_scope(&self, scope: &Scope, input: InputChars) -> ParseResult<AST> {
+        let (stream, tokenizer_control) =
+            self.lex_streams([input], self.token_mapper.as_ref().map(<_>::as_ref));
+        let mut state = ParseState::new_with_strings_pool(self, scope, tokenizer_control);
+        let result = self.parse(
+            &mut TokenStream::new(stream),
+            &mut state,
+            self.optimization_level,
+        );
+        state.return_strings_pool(self);
+        let mut _ast = result?;
+        #[cfg(feature = "metadata")]
+        _ast.set_doc(state.tokenizer_control.borrow().global_comments.join("\n"));
+        Ok(_ast)
+    }
+    /// Compile a script read from a UTF-8 [`Read`][std::io::Read] source (e.g. a network stream)
+    /// into an [`AST`], which can be used later for evaluation.
+    ///
+    /// Not available under `no_std`.
+    ///
+    /// The script is tokenized as it is read, so it never needs to be held in memory in full.
+    /// An error is returned if the source cannot be read or is not valid UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile_from_reader("let x = 40; x + 2".as_bytes())?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_std"))]
+    #[inline(always)]
+    pub fn compile_from_reader(&self, reader: impl std::io::Read) -> crate::RhaiResultOf<AST> {
+        self.compile_from_reader_with_scope(&Scope::new(), reader)
+    }
+    /// Compile a script read from a UTF-8 [`Read`][std::io::Read] source into an [`AST`] using
+    /// own scope, which can be used later for evaluation.
+    ///
+    /// Not available under `no_std`.
+    ///
+    /// See [`compile_from_reader`][Engine::compile_from_reader] for details.
+    #[cfg(not(feature = "no_std"))]
+    pub fn compile_from_reader_with_scope(
+        &self,
+        scope: &Scope,
+        reader: impl std::io::Read,
+    ) -> crate::RhaiResultOf<AST> {
+        let mut error = None;
+
+        let result = self.compile_input_with_scope(
+            scope,
+            InputChars::Reader(crate::tokenizer::ReadChars::new(reader, &mut error)),
+        );
+
+        // A read error truncates the script, so it takes precedence over any parse error
+        match error {
+            Some(err) => {
+                Err(crate::ERR::ErrorSystem("Cannot read script".into(), err.into()).into())
+            }
+            None => Ok(result?),
+        }
+    }
     /// Compile a string containing an expression into an [`AST`],
     /// which can be used later for evaluation.
     ///
@@ -293,8 +726,10 @@ impl Engine {
         let (stream, tokenizer_control) =
             self.lex_raw(&scripts, self.token_mapper.as_ref().map(<_>::as_ref));
 
-        let mut peekable = stream.peekable();
-        let mut state = ParseState::new(self, scope, Default::default(), tokenizer_control);
-        self.parse_global_expr(&mut peekable, &mut state, self.optimization_level)
+        let mut stream = TokenStream::new(stream);
+        let mut state = ParseState::new_with_strings_pool(self, scope, tokenizer_control);
+        let result = self.parse_global_expr(&mut stream, &mut state, self.optimization_level);
+        state.return_strings_pool(self);
+        result
     }
 }
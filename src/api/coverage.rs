@@ -0,0 +1,280 @@
+//! Module that defines the coverage API of [`Engine`].
+#![cfg(feature = "coverage")]
+
+use crate::ast::{ASTNode, Stmt};
+use crate::eval::GlobalRuntimeState;
+use crate::func::locked_write;
+use crate::{Engine, Identifier, Position, Span, AST};
+use std::collections::BTreeMap;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A statement or a branch in a coverage report, with the number of times it is run.
+///
+/// Exported under the `coverage` feature only.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct CoverageItem {
+    /// [Span] of the statement or branch.
+    ///
+    /// The ending [position][Position] is [`Position::NONE`] if not known.
+    pub span: Span,
+    /// Number of times the statement or branch is run.
+    pub hits: u64,
+}
+
+/// Coverage of the statements and branches in an [`AST`].
+///
+/// Exported under the `coverage` feature only.
+///
+/// Branches are the `then` and `else` blocks of each `if` statement (a missing `else` block
+/// being at the position of the `if` statement), and the cases of each `switch` statement (a
+/// missing default case being at the position of the `switch` statement).
+#[derive(Debug, Clone, Default)]
+pub struct CoverageReport {
+    /// Statements, in order of position.
+    statements: Vec<CoverageItem>,
+    /// Branches, in order of the position of the `if` or `switch` statement.
+    branches: Vec<CoverageItem>,
+}
+
+impl CoverageReport {
+    /// Get all statements, in order of position.
+    #[inline(always)]
+    #[must_use]
+    pub fn statements(&self) -> &[CoverageItem] {
+        &self.statements
+    }
+    /// Get all branches, in order of the position of the `if` or `switch` statement.
+    #[inline(always)]
+    #[must_use]
+    pub fn branches(&self) -> &[CoverageItem] {
+        &self.branches
+    }
+    /// Iterate through the [spans][Span] of all statements that are never run.
+    #[inline]
+    pub fn uncovered_statements(&self) -> impl Iterator<Item = Span> + '_ {
+        self.statements
+            .iter()
+            .filter(|item| item.hits == 0)
+            .map(|item| item.span)
+    }
+    /// Iterate through the [spans][Span] of all branches that are never taken.
+    #[inline]
+    pub fn uncovered_branches(&self) -> impl Iterator<Item = Span> + '_ {
+        self.branches
+            .iter()
+            .filter(|item| item.hits == 0)
+            .map(|item| item.span)
+    }
+}
+
+/// Statements run and branches taken during evaluation runs.
+///
+/// Exported under the `coverage` feature only.
+///
+/// Statements and branches are identified by source and [position][Position], so nothing can be
+/// told apart under the `no_position` feature.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    /// Number of times each statement is run, by source and position.
+    statements: BTreeMap<(Identifier, Position), u64>,
+    /// Number of times each branch is taken, by source, position of the `if` or `switch`
+    /// statement, and branch number.
+    branches: BTreeMap<(Identifier, Position, usize), u64>,
+}
+
+impl Coverage {
+    /// Is this [`Coverage`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+    /// Iterate through the sources of all statements run, in order.
+    ///
+    /// The source is empty if none.
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        let mut sources: Vec<_> = self.statements.keys().map(|(s, ..)| s.as_str()).collect();
+        sources.dedup();
+        sources.into_iter()
+    }
+    /// Get the number of times the statement at a particular [position][Position] in a source is
+    /// run.
+    ///
+    /// The source is empty if none.
+    #[inline]
+    #[must_use]
+    pub fn statement_hits(&self, source: &str, pos: Position) -> u64 {
+        self.statements
+            .get(&(source.into(), pos))
+            .copied()
+            .unwrap_or(0)
+    }
+    /// Report the coverage of all statements and branches in an [`AST`], including function
+    /// bodies, matching them by the source of the [`AST`].
+    #[must_use]
+    pub fn report(&self, ast: &AST) -> CoverageReport {
+        let source = ast.source_raw();
+        let mut statements = BTreeMap::new();
+        let mut branches = BTreeMap::new();
+
+        ast.walk(&mut |path| {
+            let stmt = match path.last() {
+                Some(ASTNode::Stmt(stmt)) => *stmt,
+                _ => return true,
+            };
+
+            let pos = stmt.position();
+
+            if !pos.is_none() {
+                let span = match stmt {
+                    Stmt::Block(block) => block.span_or_else(pos, Position::NONE),
+                    _ => Span::new(pos, Position::NONE),
+                };
+                statements.entry(pos).or_insert(span);
+            }
+
+            match stmt {
+                Stmt::If(x, pos) => {
+                    let (.., then_block, else_block) = &**x;
+                    let then_span = then_block.span_or_else(*pos, Position::NONE);
+                    let else_span = else_block.span_or_else(*pos, Position::NONE);
+                    branches.entry((*pos, 0)).or_insert(then_span);
+                    branches.entry((*pos, 1)).or_insert(else_span);
+                }
+                Stmt::Switch(x, pos) => {
+                    let (.., cases) = &**x;
+
+                    let indices = cases
+                        .cases
+                        .values()
+                        .flat_map(|list| list.iter().copied())
+                        .chain(cases.ranges.iter().map(|r| r.index()))
+                        .chain(cases.patterns.iter().map(|p| p.index))
+                        .chain(cases.def_case);
+
+                    for index in indices {
+                        let span =
+                            Span::new(cases.expressions[index].expr.position(), Position::NONE);
+                        branches.entry((*pos, index)).or_insert(span);
+                    }
+
+                    if cases.def_case.is_none() {
+                        let span = Span::new(*pos, Position::NONE);
+                        branches
+                            .entry((*pos, cases.expressions.len()))
+                            .or_insert(span);
+                    }
+                }
+                _ => (),
+            }
+
+            true
+        });
+
+        CoverageReport {
+            statements: statements
+                .into_iter()
+                .map(|(pos, span)| CoverageItem {
+                    span,
+                    hits: self
+                        .statements
+                        .get(&(source.clone(), pos))
+                        .copied()
+                        .unwrap_or(0),
+                })
+                .collect(),
+            branches: branches
+                .into_iter()
+                .map(|((pos, index), span)| CoverageItem {
+                    span,
+                    hits: self
+                        .branches
+                        .get(&(source.clone(), pos, index))
+                        .copied()
+                        .unwrap_or(0),
+                })
+                .collect(),
+        }
+    }
+    /// Record a run of a statement.
+    #[inline]
+    pub(crate) fn record_stmt(&mut self, source: &str, pos: Position) {
+        *self.statements.entry((source.into(), pos)).or_default() += 1;
+    }
+    /// Record a branch taken by an `if` or `switch` statement.
+    #[inline]
+    pub(crate) fn record_branch(&mut self, source: &str, pos: Position, branch: usize) {
+        *self
+            .branches
+            .entry((source.into(), pos, branch))
+            .or_default() += 1;
+    }
+    /// Merge the [`Coverage`] of an evaluation run.
+    fn merge(&mut self, run: &Self) {
+        for (key, hits) in &run.statements {
+            *self.statements.entry(key.clone()).or_default() += hits;
+        }
+        for (key, hits) in &run.branches {
+            *self.branches.entry(key.clone()).or_default() += hits;
+        }
+    }
+}
+
+impl Engine {
+    /// Start tracking the statements run and branches taken in all evaluation runs, replacing any
+    /// [`Coverage`] currently being tracked.
+    /// Exported under the `coverage` feature only.
+    ///
+    /// Coverage is accumulated across evaluation runs (see [`Engine::on_eval_end`] for the runs
+    /// covered) until [`take_coverage`][Engine::take_coverage] is called.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let ast = engine.compile(
+    /// "
+    ///     let x = 42;
+    ///     if x > 0 {
+    ///         print(x);
+    ///     } else {
+    ///         print(-x);
+    ///     }
+    /// ")?;
+    ///
+    /// engine.start_coverage();
+    /// engine.run_ast(&ast)?;
+    ///
+    /// let report = engine.take_coverage().unwrap().report(&ast);
+    ///
+    /// // The `else` block is never run
+    /// assert_eq!(report.uncovered_statements().count(), 1);
+    /// assert_eq!(report.uncovered_branches().count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn start_coverage(&mut self) -> &mut Self {
+        self.coverage = Some(Coverage::default().into());
+        self
+    }
+    /// Stop tracking coverage, returning the [`Coverage`] recorded, if any.
+    /// Exported under the `coverage` feature only.
+    #[inline(always)]
+    pub fn take_coverage(&mut self) -> Option<Coverage> {
+        self.coverage
+            .take()
+            .map(|coverage| std::mem::take(&mut *locked_write(&coverage)))
+    }
+    /// Merge the [`Coverage`] of an evaluation run into the [`Coverage`] being tracked, if any.
+    pub(crate) fn report_coverage(&self, global: &GlobalRuntimeState) {
+        if let (Some(coverage), Some(run)) = (&self.coverage, &global.coverage) {
+            locked_write(coverage).merge(run);
+        }
+    }
+}
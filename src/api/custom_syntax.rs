@@ -7,8 +7,8 @@ use crate::parser::ParseResult;
 use crate::tokenizer::{is_valid_identifier, Token};
 use crate::types::dynamic::Variant;
 use crate::{
-    reify, Engine, EvalContext, Identifier, ImmutableString, LexError, Position, RhaiResult,
-    StaticVec,
+    reify, Dynamic, Engine, EvalContext, Identifier, ImmutableString, LexError, Position,
+    RhaiResult, StaticVec,
 };
 use std::ops::Deref;
 #[cfg(feature = "no_std")]
@@ -18,6 +18,30 @@ use std::prelude::v1::*;
 pub mod markers {
     /// Special marker for matching an expression.
     pub const CUSTOM_SYNTAX_MARKER_EXPR: &str = "$expr$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// an integer number.
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_INT: &str = "$expr:int$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// a floating-point number.
+    #[cfg(not(feature = "no_float"))]
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_FLOAT: &str = "$expr:float$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// a boolean value.
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_BOOL: &str = "$expr:bool$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// a character.
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_CHAR: &str = "$expr:char$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// a string.
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_STRING: &str = "$expr:string$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// an array.
+    #[cfg(not(feature = "no_index"))]
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_ARRAY: &str = "$expr:array$";
+    /// Special marker for matching an expression that must not be a literal of a type other than
+    /// an object map.
+    #[cfg(not(feature = "no_object"))]
+    pub const CUSTOM_SYNTAX_MARKER_EXPR_MAP: &str = "$expr:map$";
     /// Special marker for matching a statements block.
     pub const CUSTOM_SYNTAX_MARKER_BLOCK: &str = "$block$";
     /// Special marker for matching an identifier.
@@ -37,6 +61,31 @@ pub mod markers {
     pub const CUSTOM_SYNTAX_MARKER_SYNTAX_VARIANT: &str = "$$";
 }
 
+/// Kind of a custom operator, registered via
+/// [`Engine::register_custom_operator_with_precedence`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum CustomOperatorKind {
+    /// Binary operator binding to the left, i.e. `a # b # c` is `(a # b) # c`.
+    Binary,
+    /// Binary operator binding to the right, i.e. `a # b # c` is `a # (b # c)`.
+    BinaryRight,
+    /// Unary operator before its operand, e.g. `# a`.
+    ///
+    /// The operand extends over all following operators with higher precedence,
+    /// i.e. `# a + b` is `# (a + b)` if `#` has a lower precedence than `+`.
+    Prefix,
+    /// Unary operator after its operand, e.g. `a #`.
+    Postfix,
+}
+
+impl Default for CustomOperatorKind {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::Binary
+    }
+}
+
 /// A general expression evaluation trait object.
 #[cfg(not(feature = "sync"))]
 pub type FnCustomSyntaxEval = dyn Fn(&mut EvalContext, &[Expression]) -> RhaiResult;
@@ -168,6 +217,8 @@ pub struct CustomSyntax {
     pub func: Box<FnCustomSyntaxEval>,
     /// Any variables added/removed in the scope?
     pub scope_may_be_changed: bool,
+    /// Template of a custom syntax macro, expanded at compile time instead of calling `func`.
+    pub template: Option<ImmutableString>,
 }
 
 impl Engine {
@@ -185,6 +236,10 @@ impl Engine {
     /// * Symbols that are all-whitespace or empty are ignored.
     /// * If `symbols` does not contain at least one valid token, then the custom syntax registration
     ///   is simply ignored.
+    /// * Typed expression markers (e.g. `$expr:int$`, `$expr:string$`) match an expression like
+    ///   `$expr$`, but raise a parse error if the expression is a literal of another type.
+    ///   Other expressions (e.g. variables or function calls) are only known at runtime and are
+    ///   not checked.
     ///
     /// ## Note on `scope_may_be_changed`
     ///
@@ -235,6 +290,21 @@ impl Engine {
                 // Markers not in first position
                 #[cfg(not(feature = "no_float"))]
                 CUSTOM_SYNTAX_MARKER_FLOAT if !segments.is_empty() => s.into(),
+                // Typed expression markers not in first position
+                CUSTOM_SYNTAX_MARKER_EXPR_INT
+                | CUSTOM_SYNTAX_MARKER_EXPR_BOOL
+                | CUSTOM_SYNTAX_MARKER_EXPR_CHAR
+                | CUSTOM_SYNTAX_MARKER_EXPR_STRING
+                    if !segments.is_empty() =>
+                {
+                    s.into()
+                }
+                #[cfg(not(feature = "no_float"))]
+                CUSTOM_SYNTAX_MARKER_EXPR_FLOAT if !segments.is_empty() => s.into(),
+                #[cfg(not(feature = "no_index"))]
+                CUSTOM_SYNTAX_MARKER_EXPR_ARRAY if !segments.is_empty() => s.into(),
+                #[cfg(not(feature = "no_object"))]
+                CUSTOM_SYNTAX_MARKER_EXPR_MAP if !segments.is_empty() => s.into(),
                 // Standard or reserved keyword/symbol not in first position
                 _ if !segments.is_empty() && token.is_some() => {
                     // Make it a custom keyword/symbol if it is disabled or reserved
@@ -313,6 +383,70 @@ impl Engine {
 
         Ok(self)
     }
+    /// Register a custom syntax macro with the [`Engine`].
+    ///
+    /// Not available under `no_custom_syntax`.
+    ///
+    /// A macro is expanded at compile time: each use of the custom syntax is replaced by the
+    /// expression in `template`, so it costs nothing more at runtime than writing out the template
+    /// by hand.
+    ///
+    /// * `symbols` holds a slice of strings that define the custom syntax, the same as for
+    ///   [`register_custom_syntax`][Engine::register_custom_syntax].
+    /// * `template` is a script expression, in which `$0`, `$1` etc. are replaced by the
+    ///   expressions, statements blocks, identifiers and literals matched by the markers in
+    ///   `symbols`, in order. An input used more than once is also evaluated more than once.
+    ///
+    /// Variables declared inside a block in `template` are not visible to the inputs of the
+    /// macro. Syntax errors in `template` are reported when the macro is used.
+    ///
+    /// Errors in `template`, whether at compile time or at runtime, are reported at the position
+    /// where the macro is used, as the template does not appear in the script. Errors in the
+    /// inputs keep their own positions.
+    ///
+    /// The expanded template is an expression, so a macro used as a statement must be followed
+    /// by `;` unless it is the last statement in a block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, INT};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_custom_syntax_macro(
+    ///     ["unless", "$expr$", "$block$"],
+    ///     "if !($0) { $1 }",
+    /// )?;
+    ///
+    /// assert_eq!(engine.eval::<INT>("let x = 1; unless x > 10 { x + 41 }")?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_custom_syntax_macro<S: AsRef<str> + Into<Identifier>>(
+        &mut self,
+        symbols: impl AsRef<[S]>,
+        template: impl Into<ImmutableString>,
+    ) -> ParseResult<&mut Self> {
+        let key = match symbols
+            .as_ref()
+            .iter()
+            .map(|s| s.as_ref().trim())
+            .find(|s| !s.is_empty())
+        {
+            Some(key) => Identifier::from(key),
+            None => return Ok(self),
+        };
+
+        self.register_custom_syntax(symbols, false, |_, _| Ok(Dynamic::UNIT))?;
+
+        if let Some(syntax) = self.custom_syntax.get_mut(&key) {
+            syntax.template = Some(template.into());
+        }
+
+        Ok(self)
+    }
     /// Register a custom syntax with the [`Engine`].
     ///
     /// Not available under `no_custom_syntax`.
@@ -359,6 +493,7 @@ impl Engine {
                 parse: Box::new(parse),
                 func: Box::new(func),
                 scope_may_be_changed,
+                template: None,
             },
         );
         self
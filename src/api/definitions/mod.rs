@@ -395,6 +395,13 @@ impl Module {
     fn write_definition(&self, writer: &mut dyn fmt::Write, def: &Definitions) -> fmt::Result {
         let mut first = true;
 
+        // Module-level documentation, e.g. of a package
+        // (standard packages are not documented to keep their definitions compact)
+        if !self.standard && !self.doc().is_empty() {
+            writer.write_str(self.doc())?;
+            first = false;
+        }
+
         let mut submodules = self.iter_sub_modules().collect::<Vec<_>>();
         submodules.sort_by(|(a, _), (b, _)| a.cmp(b));
 
@@ -409,6 +416,22 @@ impl Module {
             writer.write_str("}")?;
         }
 
+        let mut custom_types = self.iter_custom_types().collect::<Vec<_>>();
+        custom_types.sort_by_key(|&(_, name, _)| name);
+
+        for (_, display_name, comments) in custom_types {
+            if !first {
+                writer.write_str("\n\n")?;
+            }
+            first = false;
+
+            for comment in comments {
+                writeln!(writer, "{comment}")?;
+            }
+
+            write!(writer, "type {display_name};")?;
+        }
+
         let mut vars = self.iter_var().collect::<Vec<_>>();
         vars.sort_by(|(a, _), (b, _)| a.cmp(b));
 
@@ -520,13 +543,13 @@ impl FuncInfo {
 ///
 /// This is highly inefficient and is currently based on trial and error with the core packages.
 ///
-/// It tries to flatten types, removing `&` and `&mut`, and paths, while keeping generics.
+/// It tries to flatten types, removing `&` and `&mut`, while keeping generics.
+/// Paths are already removed by [`Engine::format_type_name`].
 ///
 /// Associated generic types are also rewritten into regular generic type parameters.
 fn def_type_name<'a>(ty: &'a str, engine: &'a Engine) -> Cow<'a, str> {
-    let ty = engine.format_type_name(ty).replace("crate::", "");
+    let ty = engine.format_type_name(ty);
     let ty = ty.strip_prefix("&mut").unwrap_or(&*ty).trim();
-    let ty = ty.split("::").last().unwrap();
 
     let ty = ty
         .strip_prefix("RhaiResultOf<")
@@ -2,11 +2,13 @@
 
 use crate::eval::{Caches, GlobalRuntimeState};
 use crate::parser::ParseState;
+use crate::tokenizer::TokenStream;
 use crate::types::dynamic::Variant;
 use crate::{
-    Dynamic, Engine, OptimizationLevel, Position, RhaiResult, RhaiResultOf, Scope, AST, ERR,
+    Dynamic, Engine, OptimizationLevel, Position, RhaiResult, RhaiResultOf, Scope, TypedEvalError,
+    AST, ERR,
 };
-use std::any::type_name;
+use std::any::{type_name, TypeId};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -120,7 +122,7 @@ impl Engine {
 
         // No need to optimize a lone expression
         let ast = self.parse_global_expr(
-            &mut stream.peekable(),
+            &mut TokenStream::new(stream),
             &mut state,
             #[cfg(not(feature = "no_optimize"))]
             OptimizationLevel::None,
@@ -183,10 +185,245 @@ impl Engine {
         &self,
         scope: &mut Scope,
         ast: &AST,
+    ) -> RhaiResultOf<T> {
+        self.eval_ast_typed_with_scope(scope, ast)
+            .map_err(Into::into)
+    }
+    /// Evaluate a string as a script with a piece of custom data for the host, returning the
+    /// result value or an error.
+    ///
+    /// The data is kept separately from the [tag][Engine::default_tag] (which is left untouched)
+    /// and is stored as is, without conversion into a [`Dynamic`].
+    /// Native Rust functions can get it via [`NativeCallContext::data`][crate::NativeCallContext::data].
+    ///
+    /// This is useful for passing per-request information (e.g. a user ID or a database handle)
+    /// to native Rust functions without exposing it to scripts as variables.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, NativeCallContext};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Request {
+    ///     user: String,
+    /// }
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("current_user", |ctx: NativeCallContext| {
+    ///     ctx.data::<Request>().map_or_else(String::new, |r| r.user.clone())
+    /// });
+    ///
+    /// let request = Request { user: "alice".into() };
+    ///
+    /// let user = engine.eval_with_context_data::<String>("current_user()", request)?;
+    ///
+    /// assert_eq!(user, "alice");
+    /// assert_eq!(engine.eval::<String>("current_user()")?, "");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_with_context_data<T: Variant + Clone>(
+        &self,
+        script: &str,
+        data: impl Variant + Clone,
+    ) -> RhaiResultOf<T> {
+        let scope = &mut Scope::new();
+        let ast = self.compile_with_scope_and_optimization_level(
+            scope,
+            &[script],
+            self.optimization_level,
+        )?;
+        self.eval_ast_with_context_data(scope, &ast, data)
+    }
+    /// Evaluate an [`AST`] with own scope and a piece of custom data for the host, returning the
+    /// result value or an error.
+    ///
+    /// The data is kept separately from the [tag][Engine::default_tag] (which is left untouched)
+    /// and is stored as is, without conversion into a [`Dynamic`].
+    /// Native Rust functions can get it via [`NativeCallContext::data`][crate::NativeCallContext::data].
+    #[inline]
+    pub fn eval_ast_with_context_data<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+        data: impl Variant + Clone,
     ) -> RhaiResultOf<T> {
         let global = &mut GlobalRuntimeState::new(self);
+        global.context_data = Some(crate::Shared::new(data));
 
-        let result = self.eval_ast_with_scope_raw(scope, global, ast, 0)?;
+        self.eval_ast_typed_with_global(scope, global, None, ast)?
+            .map_err(|value| self.make_output_type_error::<T>(&value))
+    }
+    /// Make an error for a result value that is not of the required type.
+    #[cold]
+    #[inline(never)]
+    #[must_use]
+    pub(crate) fn make_output_type_error<T>(&self, value: &Dynamic) -> crate::RhaiError {
+        let typ = self.map_type_name(value.type_name());
+        let t = self.map_type_name(type_name::<T>()).into();
+        ERR::ErrorMismatchOutputType(t, typ.into(), Position::NONE).into()
+    }
+    /// Call the callback registered via [`Engine::on_eval_end`], if any, and report metrics to
+    /// the [`MetricsSink`][crate::MetricsSink], if any.
+    #[inline]
+    pub(crate) fn report_eval_end(&self, global: &GlobalRuntimeState, error: Option<&ERR>) {
+        if let Some(ref callback) = self.eval_end {
+            callback(global.into());
+        }
+        self.report_metrics(global, error);
+        #[cfg(feature = "profiling")]
+        self.report_profile(global);
+        #[cfg(feature = "coverage")]
+        self.report_coverage(global);
+    }
+    /// Evaluate a string as a script, returning the result value or an error.
+    ///
+    /// If the result value is not of the required type, it is returned in a
+    /// [`TypedEvalError::MismatchOutputType`] error, together with the names of the required and
+    /// actual types, so that it can be coerced without evaluating the script again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, TypedEvalError};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// assert_eq!(engine.eval_typed::<i64>("40 + 2")?, 42);
+    ///
+    /// match engine.eval_typed::<i64>(r#""42""#) {
+    ///     Err(TypedEvalError::MismatchOutputType(expected, actual, value)) => {
+    ///         assert_eq!(expected, "i64");
+    ///         assert_eq!(actual, "string");
+    ///         assert_eq!(value.into_string().unwrap(), "42");
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn eval_typed<T: Variant + Clone>(&self, script: &str) -> Result<T, TypedEvalError> {
+        let scope = &mut Scope::new();
+        let ast = self
+            .compile_with_scope(scope, script)
+            .map_err(|err| TypedEvalError::Eval(err.into()))?;
+        self.eval_ast_typed_with_scope(scope, &ast)
+    }
+    /// Evaluate a string containing an expression with own scope, returning the result value or an
+    /// error.
+    ///
+    /// If the result value is not of the required type, it is returned in a
+    /// [`TypedEvalError::MismatchOutputType`] error, together with the names of the required and
+    /// actual types, so that it can be coerced without evaluating the expression again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope, TypedEvalError};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut scope = Scope::new();
+    /// scope.push("x", 40_i64);
+    ///
+    /// match engine.eval_expression_typed_with_scope::<bool>(&mut scope, "x + 2") {
+    ///     Err(TypedEvalError::MismatchOutputType(.., value)) => {
+    ///         assert_eq!(value.as_int().unwrap(), 42)
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_expression_typed_with_scope<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+    ) -> Result<T, TypedEvalError> {
+        let scripts = [script];
+        let (stream, tokenizer_control) =
+            self.lex_raw(&scripts, self.token_mapper.as_ref().map(<_>::as_ref));
+        let mut state = ParseState::new(self, scope, Default::default(), tokenizer_control);
+
+        // No need to optimize a lone expression
+        let ast = self
+            .parse_global_expr(
+                &mut TokenStream::new(stream),
+                &mut state,
+                #[cfg(not(feature = "no_optimize"))]
+                OptimizationLevel::None,
+                #[cfg(feature = "no_optimize")]
+                OptimizationLevel::default(),
+            )
+            .map_err(|err| TypedEvalError::Eval(err.into()))?;
+
+        self.eval_ast_typed_with_scope(scope, &ast)
+    }
+    /// Evaluate an [`AST`] with own scope, returning the result value or an error.
+    ///
+    /// If the result value is not of the required type, it is returned in a
+    /// [`TypedEvalError::MismatchOutputType`] error, together with the names of the required and
+    /// actual types, so that it can be coerced without evaluating the [`AST`] again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope, TypedEvalError};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("40 + 2")?;
+    ///
+    /// // Fall back to converting the result into a string
+    /// let value = match engine.eval_ast_typed_with_scope::<String>(&mut Scope::new(), &ast) {
+    ///     Ok(s) => s,
+    ///     Err(TypedEvalError::MismatchOutputType(.., value)) => value.to_string(),
+    ///     Err(err) => return Err(err.into()),
+    /// };
+    ///
+    /// assert_eq!(value, "42");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_ast_typed_with_scope<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+    ) -> Result<T, TypedEvalError> {
+        let global = &mut GlobalRuntimeState::new(self);
+
+        self.eval_ast_typed_with_global(scope, global, None, ast)?
+            .map_err(|value| {
+                let typ = self.map_type_name(value.type_name()).into();
+                let t = self.map_type_name(type_name::<T>()).into();
+                TypedEvalError::MismatchOutputType(t, typ, value)
+            })
+    }
+    /// Evaluate an [`AST`] with own scope, global runtime state and optional [`Caches`], returning
+    /// the result value or an error.
+    pub(crate) fn eval_ast_typed_with_global<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: Option<&mut Caches>,
+        ast: &AST,
+    ) -> RhaiResultOf<Result<T, Dynamic>> {
+        let result = match caches {
+            Some(caches) => self.eval_ast_with_caches_raw(scope, global, caches, ast, 0),
+            None => self.eval_ast_with_scope_raw(scope, global, ast, 0),
+        };
+        self.report_eval_end(global, result.as_ref().err().map(|err| &**err));
+        let result = result?;
 
         #[cfg(feature = "debugging")]
         if self.debugger.is_some() {
@@ -199,12 +436,13 @@ impl Engine {
             self.run_debugger(scope, global, lib, &mut None, node, 0)?;
         }
 
-        let typ = self.map_type_name(result.type_name());
+        let result = result.flatten();
 
-        result.try_cast::<T>().ok_or_else(|| {
-            let t = self.map_type_name(type_name::<T>()).into();
-            ERR::ErrorMismatchOutputType(t, typ.into(), Position::NONE).into()
-        })
+        if TypeId::of::<T>() == TypeId::of::<Dynamic>() || result.is::<T>() {
+            Ok(Ok(result.cast::<T>()))
+        } else {
+            Ok(Err(result))
+        }
     }
     /// Evaluate an [`AST`] with own scope, returning the result value or an error.
     #[inline]
@@ -215,7 +453,20 @@ impl Engine {
         ast: &'a AST,
         level: usize,
     ) -> RhaiResult {
-        let mut caches = Caches::new();
+        let mut caches = self.new_caches();
+        let result = self.eval_ast_with_caches_raw(scope, global, &mut caches, ast, level);
+        self.recycle_caches(&mut caches);
+        result
+    }
+    /// Evaluate an [`AST`] with own scope and [`Caches`], returning the result value or an error.
+    pub(crate) fn eval_ast_with_caches_raw(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        ast: &AST,
+        level: usize,
+    ) -> RhaiResult {
         global.source = ast.source_raw().clone();
 
         #[cfg(not(feature = "no_module"))]
@@ -239,8 +490,16 @@ impl Engine {
             _lib = &[];
         }
 
-        let result =
-            self.eval_global_statements(scope, global, &mut caches, statements, _lib, level);
+        let result = self.eval_global_statements(scope, global, caches, statements, _lib, level);
+
+        // Run any tasks still pending at the end of the evaluation
+        #[cfg(not(feature = "no_function"))]
+        let result = match result {
+            Ok(value) if level == 0 => self
+                .run_pending_tasks(global, caches, _lib, level)
+                .map(|_| value),
+            result => result,
+        };
 
         #[cfg(not(feature = "no_module"))]
         {
@@ -263,7 +522,7 @@ impl Engine {
         &self,
         scope: &mut Scope,
         global: &mut GlobalRuntimeState,
-        caches: &mut Caches,
+        caches: &mut crate::eval::Caches,
         statements: &[crate::ast::Stmt],
         lib: &[&crate::Module],
         level: usize,
@@ -0,0 +1,418 @@
+//! Module that defines the asynchronous evaluation API of [`Engine`].
+#![cfg(feature = "async")]
+#![cfg(not(feature = "no_std"))]
+#![cfg(not(target_family = "wasm"))]
+
+use crate::eval::GlobalRuntimeState;
+use crate::func::SendSync;
+use crate::types::dynamic::Variant;
+use crate::{
+    Dynamic, Engine, Identifier, NativeCallContext, Position, RhaiResult, RhaiResultOf, Scope,
+    Shared, ERR,
+};
+use std::{
+    any::TypeId,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+/// A boxed [`Future`] returned by an async native function.
+type BoxedFuture = Pin<Box<dyn Future<Output = RhaiResult> + Send>>;
+
+/// A job running an asynchronous evaluation, handed to the spawner registered via
+/// [`Engine::on_async_spawn`].
+pub type AsyncJob = Box<dyn FnOnce() + Send>;
+
+/// Callback function for spawning the job running an asynchronous evaluation.
+pub type OnAsyncSpawnCallback = dyn Fn(AsyncJob) + Send + Sync;
+
+/// An evaluation to run in a separate job, keeping the [`Bridge`] in its global runtime state.
+type BoxedEval<T> = Box<dyn FnOnce(&Engine, Bridge) -> RhaiResultOf<T> + Send>;
+
+/// Request from an evaluation to the [`EvalFuture`] driving it to await a [`Future`] and send
+/// back its result.
+type AwaitRequest = (BoxedFuture, Sender<RhaiResult>);
+
+/// Connection from an evaluation to the [`EvalFuture`] driving it, kept in the global runtime
+/// state of the evaluation.
+#[derive(Clone)]
+pub(crate) struct Bridge {
+    sender: Sender<AwaitRequest>,
+    waker: Arc<Mutex<Option<Waker>>>,
+    /// Set when the [`EvalFuture`] is dropped.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Bridge {
+    /// Has the [`EvalFuture`] driving the evaluation been dropped?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Wake up the task polling an [`EvalFuture`], if any.
+fn wake(waker: &Mutex<Option<Waker>>) {
+    if let Ok(mut waker) = waker.lock() {
+        if let Some(waker) = waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Guard that wakes up the [`EvalFuture`] when its evaluation job finishes, even by panicking,
+/// so that it notices the disconnection.
+///
+/// It must be dropped after all the senders connected to the [`EvalFuture`].
+struct Disconnect(Arc<Mutex<Option<Waker>>>);
+
+impl Drop for Disconnect {
+    #[inline(always)]
+    fn drop(&mut self) {
+        wake(&self.0);
+    }
+}
+
+/// Await a [`Future`] returned by an async native function.
+///
+/// The [`Future`] is handed over to the [`EvalFuture`] driving the evaluation, to be awaited by
+/// the async executor while the evaluation job is blocked.
+///
+/// It is an error to call an async native function outside of an evaluation driven by an
+/// [`EvalFuture`], because awaiting the [`Future`] would otherwise block the current thread,
+/// which may be a worker thread of the async executor.
+fn await_future(ctx: &NativeCallContext, future: BoxedFuture) -> RhaiResult {
+    match ctx
+        .global_runtime_state()
+        .and_then(|global| global.async_bridge.as_ref())
+    {
+        Some(bridge) if bridge.is_cancelled() => {
+            Err(ERR::ErrorTerminated("cancelled".into(), ctx.position()).into())
+        }
+        Some(bridge) => {
+            let (sender, receiver) = channel();
+            // The receiver is gone if the evaluation is cancelled, which is reported below
+            let _ = bridge.sender.send((future, sender));
+            wake(&bridge.waker);
+
+            receiver.recv().unwrap_or_else(|_| {
+                Err(ERR::ErrorTerminated("cancelled".into(), ctx.position()).into())
+            })
+        }
+        None => Err(ERR::ErrorRuntime(
+            format!(
+                "async function {} can only be called via eval_async or call_fn_async",
+                ctx.fn_name()
+            )
+            .into(),
+            ctx.position(),
+        )
+        .into()),
+    }
+}
+
+/// A [`Future`] that drives an asynchronous evaluation started by [`Engine::eval_async`],
+/// [`Engine::eval_with_scope_async`] or [`Engine::call_fn_async`].
+///
+/// The script runs in a separate job, which is blocked whenever an async native function
+/// (registered via [`Engine::register_async_fn`]) is called.  The [`Future`] returned by that
+/// function is then awaited by this [`EvalFuture`] on the async executor, without blocking it.
+///
+/// The job is started on the first poll, via the spawner registered with
+/// [`Engine::on_async_spawn`] (e.g. `tokio::task::spawn_blocking`, which runs it on a bounded
+/// pool of blocking threads), or on a new OS thread if there is none.  Either way, a thread is
+/// taken up by every evaluation in flight, even while it is waiting for a [`Future`].
+///
+/// Dropping an [`EvalFuture`] cancels the evaluation: pending calls to async native functions
+/// fail with [`ErrorTerminated`][crate::EvalAltResult::ErrorTerminated], and a script that is
+/// still running is terminated at its next operation (unless the `unchecked` feature is used).
+/// If the evaluation job panics, the [`EvalFuture`] resolves to an error.
+///
+/// Only available under the `async` feature.  Not available under `no_std` or `WASM`.
+#[must_use = "futures do nothing unless polled"]
+pub struct EvalFuture<T> {
+    /// [`Engine`] evaluating the script.
+    engine: Shared<Engine>,
+    /// Evaluation to start on the first poll, plus the connection to hand to it and the channel
+    /// to send back its result.
+    start: Option<(BoxedEval<T>, Bridge, Sender<RhaiResultOf<T>>)>,
+    /// Requests from the evaluation to await a [`Future`].
+    requests: Receiver<AwaitRequest>,
+    /// Result of the evaluation.
+    result: Receiver<RhaiResultOf<T>>,
+    /// [`Future`] currently being awaited, plus the channel to send back its result.
+    pending: Option<AwaitRequest>,
+    /// Waker of the task polling this [`EvalFuture`].
+    waker: Arc<Mutex<Option<Waker>>>,
+    /// Set when this [`EvalFuture`] is dropped.
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> EvalFuture<T> {
+    /// Create a new [`EvalFuture`] that runs an evaluation in a separate job.
+    ///
+    /// The evaluation must keep the [`Bridge`] in its global runtime state.
+    fn new(
+        engine: Shared<Engine>,
+        eval: impl FnOnce(&Engine, Bridge) -> RhaiResultOf<T> + Send + 'static,
+    ) -> Self {
+        let (sender, requests) = channel();
+        let (result_sender, result) = channel();
+        let waker = Arc::new(Mutex::new(None));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let bridge = Bridge {
+            sender,
+            waker: waker.clone(),
+            cancelled: cancelled.clone(),
+        };
+
+        Self {
+            engine,
+            start: Some((Box::new(eval), bridge, result_sender)),
+            requests,
+            result,
+            pending: None,
+            waker,
+            cancelled,
+        }
+    }
+}
+
+impl<T> Drop for EvalFuture<T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl<T: Send + 'static> Future for EvalFuture<T> {
+    type Output = RhaiResultOf<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        // Start the evaluation job on the first poll
+        // (the only senders are moved into the job, so a panic there disconnects the channels)
+        if let Some((eval, bridge, result_sender)) = this.start.take() {
+            let engine = this.engine.clone();
+
+            let job: AsyncJob = Box::new(move || {
+                let _disconnect = Disconnect(bridge.waker.clone());
+                // Captured variables are dropped last, so move the sender to be dropped first
+                let result_sender = result_sender;
+                let result = eval(&engine, bridge);
+                let _ = result_sender.send(result);
+            });
+
+            match this.engine.async_spawn {
+                Some(ref spawn) => spawn(job),
+                None => drop(thread::spawn(job)),
+            }
+        }
+
+        loop {
+            // Await the pending future, if any
+            if let Some((ref mut future, ..)) = this.pending {
+                match future.as_mut().poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(result) => {
+                        let (.., sender) = this.pending.take().unwrap();
+                        // The evaluation is waiting for this result
+                        let _ = sender.send(result);
+                    }
+                }
+            }
+
+            // Register the waker before checking for messages to avoid missing any wake-up
+            *this.waker.lock().unwrap() = Some(cx.waker().clone());
+
+            if let Ok(request) = this.requests.try_recv() {
+                this.pending = Some(request);
+                continue;
+            }
+
+            return match this.result.try_recv() {
+                Ok(result) => Poll::Ready(result),
+                Err(TryRecvError::Empty) => Poll::Pending,
+                Err(TryRecvError::Disconnected) => Poll::Ready(Err(ERR::ErrorRuntime(
+                    "evaluation job panicked".into(),
+                    Position::NONE,
+                )
+                .into())),
+            };
+        }
+    }
+}
+
+impl Engine {
+    /// Register an async native function with the [`Engine`].
+    ///
+    /// Only available under the `async` feature.  Not available under `no_std` or `WASM`.
+    ///
+    /// The function takes a list of [`TypeId`]'s indicating the actual types of the parameters,
+    /// similar to [`register_raw_fn`][Engine::register_raw_fn].  It is called with clones of the
+    /// arguments and returns a [`Future`].
+    ///
+    /// The function can only be called by scripts evaluated via [`eval_async`][Engine::eval_async]
+    /// or [`call_fn_async`][Engine::call_fn_async], which suspend the script while the [`Future`]
+    /// is awaited by the async executor.  Calling it in any other evaluation is an error, instead
+    /// of blocking the current thread on the [`Future`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Dynamic, Engine, INT};
+    /// use std::any::TypeId;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_async_fn("double", [TypeId::of::<INT>()], |args| async move {
+    ///     Ok(Dynamic::from(args[0].as_int().unwrap() * 2))
+    /// });
+    /// ```
+    #[inline]
+    pub fn register_async_fn<F>(
+        &mut self,
+        name: impl AsRef<str> + Into<Identifier>,
+        arg_types: impl AsRef<[TypeId]>,
+        func: impl Fn(Vec<Dynamic>) -> F + SendSync + 'static,
+    ) -> &mut Self
+    where
+        F: Future<Output = RhaiResult> + Send + 'static,
+    {
+        self.register_raw_fn(name, arg_types, move |ctx, args| {
+            let args = args.iter().map(|arg| arg.flatten_clone()).collect();
+            await_future(&ctx, Box::pin(func(args)))
+        })
+    }
+    /// Set the spawner of the jobs running asynchronous evaluations.
+    ///
+    /// Only available under the `async` feature.  Not available under `no_std` or `WASM`.
+    ///
+    /// Each [`EvalFuture`] hands its evaluation, as a job that blocks while [`Future`]'s are
+    /// being awaited, to the spawner when it is first polled.  Without a spawner, each job runs
+    /// on a new OS thread.
+    ///
+    /// The spawner must eventually run the job, on a thread that may be blocked.  With `tokio`,
+    /// for example, use `tokio::task::spawn_blocking` to run the jobs on its bounded pool of
+    /// blocking threads.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    /// use std::thread;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.on_async_spawn(|job| {
+    ///     thread::Builder::new()
+    ///         .name("rhai".into())
+    ///         .spawn(job)
+    ///         .expect("thread spawned");
+    /// });
+    /// ```
+    #[inline(always)]
+    pub fn on_async_spawn(
+        &mut self,
+        callback: impl Fn(AsyncJob) + SendSync + 'static,
+    ) -> &mut Self {
+        self.async_spawn = Some(Box::new(callback));
+        self
+    }
+    /// Evaluate a string as a script asynchronously, returning a [`Future`] of the result value.
+    ///
+    /// Only available under the `async` feature.  Not available under `no_std` or `WASM`.
+    ///
+    /// See [`EvalFuture`] for details.
+    #[inline]
+    pub fn eval_async<T: Variant + Clone>(
+        self: Shared<Self>,
+        script: impl Into<String>,
+    ) -> EvalFuture<T> {
+        let script = script.into();
+
+        EvalFuture::new(self, move |engine, bridge| {
+            engine.eval_with_bridge(&mut Scope::new(), &script, bridge)
+        })
+    }
+    /// Evaluate a string as a script asynchronously with own scope, returning a [`Future`] of the
+    /// result value together with the [`Scope`].
+    ///
+    /// Only available under the `async` feature.  Not available under `no_std` or `WASM`.
+    ///
+    /// The [`Scope`] is moved into the evaluation and handed back, with any new variables, when
+    /// the evaluation succeeds.  See [`EvalFuture`] for details.
+    #[inline]
+    pub fn eval_with_scope_async<T: Variant + Clone>(
+        self: Shared<Self>,
+        scope: Scope<'static>,
+        script: impl Into<String>,
+    ) -> EvalFuture<(T, Scope<'static>)> {
+        let script = script.into();
+        let mut scope = scope;
+
+        EvalFuture::new(self, move |engine, bridge| {
+            engine
+                .eval_with_bridge(&mut scope, &script, bridge)
+                .map(|value| (value, scope))
+        })
+    }
+    /// Evaluate a string as a script with own scope, keeping a [`Bridge`] to an [`EvalFuture`] in
+    /// the global runtime state.
+    fn eval_with_bridge<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        script: &str,
+        bridge: Bridge,
+    ) -> RhaiResultOf<T> {
+        let ast = self.compile_with_scope_and_optimization_level(
+            scope,
+            &[script],
+            self.optimization_level,
+        )?;
+
+        let global = &mut GlobalRuntimeState::new(self);
+        global.async_bridge = Some(bridge);
+
+        self.eval_ast_typed_with_global(scope, global, None, &ast)?
+            .map_err(|value| self.make_output_type_error::<T>(&value))
+    }
+    /// Call a script function defined in an [`AST`] asynchronously, returning a [`Future`] of
+    /// the result value together with the [`Scope`].
+    ///
+    /// Only available under the `async` feature.  Not available under `no_std` or `WASM`.
+    /// Not available under `no_function`.
+    ///
+    /// The [`Scope`] is moved into the call and handed back when the call succeeds.
+    /// See [`EvalFuture`] for details.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    pub fn call_fn_async<T: Variant + Clone>(
+        self: Shared<Self>,
+        scope: Scope<'static>,
+        ast: crate::AST,
+        name: impl Into<String>,
+        args: impl crate::FuncArgs + Send + 'static,
+    ) -> EvalFuture<(T, Scope<'static>)> {
+        let name = name.into();
+        let mut scope = scope;
+
+        EvalFuture::new(self, move |engine, bridge| {
+            let global = &mut GlobalRuntimeState::new(engine);
+            global.async_bridge = Some(bridge);
+
+            engine
+                .call_fn_with_global(&mut scope, global, &ast, &name, args)
+                .map(|value| (value, scope))
+        })
+    }
+}
@@ -0,0 +1,90 @@
+//! Module that defines the concurrent evaluation API of [`Engine`].
+#![cfg(feature = "sync")]
+#![cfg(not(feature = "no_std"))]
+
+use crate::eval::{Caches, GlobalRuntimeState, ScratchArena};
+use crate::types::dynamic::Variant;
+use crate::{Engine, RhaiResultOf, Scope, AST};
+use std::cell::RefCell;
+
+thread_local! {
+    /// [`Caches`] of the current thread, kept between concurrent evaluations.
+    static THREAD_CACHES: RefCell<Caches<'static>> = const { RefCell::new(Caches::new()) };
+}
+
+impl Engine {
+    /// Evaluate an [`AST`] with own scope, re-using [`Caches`] local to the current thread,
+    /// returning the result value or an error.
+    ///
+    /// This is intended for an [`Engine`] shared among many threads via [`Arc`][std::sync::Arc].
+    /// Strings created during evaluation are interned, and scratch buffers (if the
+    /// [scratch arena][Engine::with_arena] is enabled) are kept, per thread instead of in the
+    /// [`Engine`], so concurrent evaluations do not contend on its internal state.
+    ///
+    /// Function resolution caches are reset for each evaluation, so the same thread can evaluate
+    /// different [`AST`]'s, or use different [`Engine`]'s.
+    ///
+    /// Only available under `sync`.  Not available under `no_std`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope, INT};
+    /// use std::{sync::Arc, thread};
+    ///
+    /// let engine = Arc::new(Engine::new());
+    /// let ast = Arc::new(engine.compile("let x = 0; for n in 0..k { x += n; } x")?);
+    ///
+    /// let handles = (0..4 as INT)
+    ///     .map(|k| {
+    ///         let engine = engine.clone();
+    ///         let ast = ast.clone();
+    ///
+    ///         thread::spawn(move || {
+    ///             let mut scope = Scope::new();
+    ///             scope.push("k", k * 10);
+    ///             engine.eval_concurrent::<INT>(&mut scope, &ast)
+    ///         })
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let results = handles
+    ///     .into_iter()
+    ///     .map(|h| h.join().unwrap())
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// assert_eq!(results, [0, 45, 190, 435]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_concurrent<T: Variant + Clone>(
+        &self,
+        scope: &mut Scope,
+        ast: &AST,
+    ) -> RhaiResultOf<T> {
+        let global = &mut GlobalRuntimeState::new(self);
+
+        let result = THREAD_CACHES.with(|caches| match caches.try_borrow_mut() {
+            Ok(mut caches) => {
+                caches.rewind_fn_resolution_caches(0);
+
+                if self.has_arena() && caches.arena_mut().is_none() {
+                    caches.set_arena(ScratchArena::new());
+                }
+
+                let result = self.eval_ast_typed_with_global(scope, global, Some(&mut caches), ast);
+
+                if let Some(arena) = caches.arena_mut() {
+                    arena.reset();
+                }
+
+                result
+            }
+            // Re-entrant call (e.g. from within a native function) - the thread's caches are in use
+            Err(_) => self.eval_ast_typed_with_global(scope, global, None, ast),
+        })?;
+
+        result.map_err(|value| self.make_output_type_error::<T>(&value))
+    }
+}
@@ -0,0 +1,452 @@
+//! Module that defines the durable evaluation API of [`Engine`].
+#![cfg(not(feature = "no_object"))]
+
+use crate::ast::{ASTNode, Expr, Stmt};
+use crate::eval::GlobalRuntimeState;
+use crate::func::{locked_read, locked_write, SendSync};
+use crate::types::dynamic::AccessMode;
+use crate::{
+    Dynamic, Engine, Identifier, ImmutableString, Locked, Position, RhaiResultOf, Scope, AST, ERR,
+};
+use std::collections::BTreeMap;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Name of the function that checkpoints a durable evaluation.
+const FN_CHECKPOINT: &str = "checkpoint";
+
+/// A variable saved at a [`Checkpoint`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct SavedVariable {
+    /// Name of the variable.
+    name: Identifier,
+    /// Is the variable a constant?
+    #[cfg_attr(feature = "serde", serde(default))]
+    is_constant: bool,
+    /// Value of the variable.
+    value: Dynamic,
+    /// Aliases of the variable.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    aliases: Vec<Identifier>,
+}
+
+/// A saved state of a durable evaluation started by [`Engine::eval_durable`], from which it can be
+/// resumed later, possibly in another process.
+///
+/// It records the top-level statement to resume from, together with a snapshot of all the
+/// variables in the [`Scope`], in order (so shadowed variables are kept).
+///
+/// Under the `serde` feature, a [`Checkpoint`] can be serialized for persistence, provided that
+/// the values of all variables are serializable.
+///
+/// Not available under `no_object`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    /// Label of the checkpoint.
+    label: Identifier,
+    /// Index of the top-level statement to resume from.
+    index: usize,
+    /// Variables, in the order of the [`Scope`].
+    variables: Vec<SavedVariable>,
+}
+
+impl Checkpoint {
+    /// Create a new [`Checkpoint`] from the variables in a [`Scope`].
+    fn new(label: Identifier, index: usize, scope: &Scope) -> Self {
+        #[cfg(not(feature = "no_module"))]
+        let mut aliases = scope.iter_aliases();
+
+        let variables = scope
+            .iter_raw()
+            .map(|(name, is_constant, value)| {
+                let mut value = value.flatten_clone();
+                value.set_access_mode(AccessMode::ReadWrite);
+
+                SavedVariable {
+                    name: name.into(),
+                    is_constant,
+                    value,
+                    #[cfg(not(feature = "no_module"))]
+                    aliases: aliases.next().map_or_else(Vec::new, <[_]>::to_vec),
+                }
+            })
+            .collect();
+
+        Self {
+            label,
+            index,
+            variables,
+        }
+    }
+    /// Get the label of the checkpoint, which is empty if `checkpoint` is called without one.
+    #[inline(always)]
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+    /// Get an iterator over the variables saved at the checkpoint, in order.
+    ///
+    /// Each item is a tuple of the name of the variable, whether it is a constant, and its value.
+    /// A later variable shadows an earlier one of the same name.
+    #[inline]
+    pub fn iter_variables(&self) -> impl Iterator<Item = (&str, bool, &Dynamic)> {
+        self.variables
+            .iter()
+            .map(|v| (v.name.as_str(), v.is_constant, &v.value))
+    }
+    /// Get the value of a variable saved at the checkpoint.
+    ///
+    /// If there are multiple variables of the same name, the last one is returned.
+    #[inline]
+    #[must_use]
+    pub fn get_variable(&self, name: &str) -> Option<&Dynamic> {
+        self.variables
+            .iter()
+            .rev()
+            .find(|v| v.name == name)
+            .map(|v| &v.value)
+    }
+}
+
+/// Trait that encapsulates the persistence of [checkpoints][Checkpoint] of durable evaluations.
+///
+/// Each durable evaluation is identified by an ID chosen by the host, and keeps at most one
+/// [`Checkpoint`] (the latest).
+///
+/// Not available under `no_object`.
+pub trait CheckpointStore: SendSync {
+    /// Load the [`Checkpoint`] of a durable evaluation, or [`None`] if it has not been started or
+    /// it has completed.
+    fn load(&self, id: &str) -> RhaiResultOf<Option<Checkpoint>>;
+
+    /// Save the [`Checkpoint`] of a durable evaluation, replacing any previous one.
+    fn save(&self, id: &str, checkpoint: Checkpoint) -> RhaiResultOf<()>;
+
+    /// Remove the [`Checkpoint`] of a durable evaluation that has completed.
+    fn remove(&self, id: &str) -> RhaiResultOf<()>;
+}
+
+/// A [`CheckpointStore`] that keeps [checkpoints][Checkpoint] in memory.
+///
+/// Not available under `no_object`.
+#[derive(Debug, Default)]
+pub struct MemoryCheckpointStore(Locked<BTreeMap<Identifier, Checkpoint>>);
+
+impl MemoryCheckpointStore {
+    /// Create a new [`MemoryCheckpointStore`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Get the number of [checkpoints][Checkpoint] kept.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        locked_read(&self.0).len()
+    }
+    /// Is this store empty?
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        locked_read(&self.0).is_empty()
+    }
+}
+
+impl CheckpointStore for MemoryCheckpointStore {
+    #[inline]
+    fn load(&self, id: &str) -> RhaiResultOf<Option<Checkpoint>> {
+        Ok(locked_read(&self.0).get(id).cloned())
+    }
+    #[inline]
+    fn save(&self, id: &str, checkpoint: Checkpoint) -> RhaiResultOf<()> {
+        locked_write(&self.0).insert(id.into(), checkpoint);
+        Ok(())
+    }
+    #[inline]
+    fn remove(&self, id: &str) -> RhaiResultOf<()> {
+        locked_write(&self.0).remove(id);
+        Ok(())
+    }
+}
+
+/// Status of a durable evaluation returned by [`Engine::eval_durable`].
+///
+/// Not available under `no_object`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub enum Durable {
+    /// The evaluation is suspended at a checkpoint with a label.
+    Suspended(ImmutableString),
+    /// The evaluation has completed with a result value.
+    Completed(Dynamic),
+}
+
+impl Durable {
+    /// Is the evaluation suspended at a checkpoint?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_suspended(&self) -> bool {
+        matches!(self, Self::Suspended(..))
+    }
+    /// Is the evaluation completed?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_completed(&self) -> bool {
+        matches!(self, Self::Completed(..))
+    }
+}
+
+/// Get the label of a top-level statement that is a checkpoint, if it is one.
+fn checkpoint_label(stmt: &Stmt) -> Option<RhaiResultOf<Identifier>> {
+    // Without optimization, the call is not turned into `Stmt::FnCall`
+    let x = match stmt {
+        Stmt::FnCall(x, ..) if x.name == FN_CHECKPOINT => x,
+        Stmt::Expr(expr) => match **expr {
+            Expr::FnCall(ref x, ..) if x.name == FN_CHECKPOINT => x,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    #[cfg(not(feature = "no_module"))]
+    if !x.namespace.is_empty() {
+        return None;
+    }
+
+    Some(match x.args.as_slice() {
+        [] => Ok(Identifier::new_const()),
+        [Expr::StringConstant(s, ..)] => Ok(s.as_str().into()),
+        _ => {
+            Err(ERR::ErrorRuntime("checkpoint label must be a string literal".into(), x.pos).into())
+        }
+    })
+}
+
+/// Find a call to `checkpoint` within a statement, returning its position.
+fn find_nested_checkpoint(stmt: &Stmt) -> Option<Position> {
+    let mut found = None;
+
+    stmt.walk(&mut Vec::new(), &mut |path| match path.last().unwrap() {
+        ASTNode::Stmt(Stmt::FnCall(x, ..)) | ASTNode::Expr(Expr::FnCall(x, ..))
+            if x.name == FN_CHECKPOINT =>
+        {
+            found = Some(x.pos);
+            false
+        }
+        _ => true,
+    });
+
+    found
+}
+
+impl Engine {
+    /// Evaluate an [`AST`] durably, so that it can be suspended at checkpoints and resumed later,
+    /// possibly in another process and with another [`Engine`].
+    ///
+    /// The script calls `checkpoint()` or `checkpoint("label")` as a top-level statement to save
+    /// its state into a [`CheckpointStore`] under `id`, and the evaluation is suspended with
+    /// [`Durable::Suspended`].
+    ///
+    /// Calling this method again with the same `id` resumes the evaluation after the last
+    /// checkpoint, with its variables restored into `scope` in their original order.  Variables
+    /// already in `scope` can be used to pass in new information (e.g. the outcome of an
+    /// approval) when resuming, but restored variables of the same names shadow them.  Top-level
+    /// `import` statements before the checkpoint are run again.
+    ///
+    /// When the evaluation completes, its checkpoint is removed from the store and
+    /// [`Durable::Completed`] is returned with the result value.
+    ///
+    /// The script must not be changed between checkpoints.
+    ///
+    /// Not available under `no_object`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `checkpoint` is called anywhere other than as a top-level statement, if
+    /// the checkpoint does not match the script, or if the store fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Durable, Engine, MemoryCheckpointStore, Scope};
+    ///
+    /// let engine = Engine::new();
+    /// let store = MemoryCheckpointStore::new();
+    ///
+    /// let ast = engine.compile(r#"
+    ///     let amount = 500;
+    ///     checkpoint("approval");
+    ///     if approved { amount } else { 0 }
+    /// "#)?;
+    ///
+    /// let status = engine.eval_durable("order-42", &mut Scope::new(), &ast, &store)?;
+    /// assert!(matches!(status, Durable::Suspended(label) if label.as_str() == "approval"));
+    ///
+    /// // ... later, possibly in another process ...
+    ///
+    /// let mut scope = Scope::new();
+    /// scope.push("approved", true);
+    ///
+    /// let status = engine.eval_durable("order-42", &mut scope, &ast, &store)?;
+    /// assert!(matches!(status, Durable::Completed(value) if value.as_int().unwrap() == 500));
+    /// assert!(store.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval_durable(
+        &self,
+        id: &str,
+        scope: &mut Scope,
+        ast: &AST,
+        store: &impl CheckpointStore,
+    ) -> RhaiResultOf<Durable> {
+        let statements = ast.statements();
+
+        // Find all the checkpoints
+        let mut checkpoints = Vec::new();
+
+        for (index, stmt) in statements.iter().enumerate() {
+            match checkpoint_label(stmt) {
+                Some(label) => checkpoints.push((index, label?)),
+                None => {
+                    if let Some(pos) = find_nested_checkpoint(stmt) {
+                        return Err(ERR::ErrorRuntime(
+                            "checkpoint can only be called as a top-level statement".into(),
+                            pos,
+                        )
+                        .into());
+                    }
+                }
+            }
+        }
+        #[cfg(not(feature = "no_function"))]
+        for stmt in ast.iter_fn_def().flat_map(|f| f.body.iter()) {
+            if let Some(pos) = find_nested_checkpoint(stmt) {
+                return Err(ERR::ErrorRuntime(
+                    "checkpoint can only be called as a top-level statement".into(),
+                    pos,
+                )
+                .into());
+            }
+        }
+
+        let global = &mut GlobalRuntimeState::new(self);
+        global.source = ast.source_raw().clone();
+
+        #[cfg(not(feature = "no_module"))]
+        {
+            global.embedded_module_resolver = ast.resolver().cloned();
+        }
+
+        let mut _lib = &[
+            #[cfg(not(feature = "no_function"))]
+            ast.as_ref(),
+        ][..];
+        #[cfg(not(feature = "no_function"))]
+        if !ast.has_functions() {
+            _lib = &[];
+        }
+
+        let mut caches = self.new_caches();
+
+        // Restore the last checkpoint, if any
+        let start = match store.load(id)? {
+            Some(checkpoint) => {
+                let valid = checkpoints.iter().any(|(index, label)| {
+                    index + 1 == checkpoint.index && *label == checkpoint.label
+                });
+
+                if !valid {
+                    return Err(ERR::ErrorRuntime(
+                        format!(
+                            "checkpoint '{}' does not match the script",
+                            checkpoint.label
+                        )
+                        .into(),
+                        Position::NONE,
+                    )
+                    .into());
+                }
+
+                // Restore the variables in their original order after those passed in by the
+                // host, so they shadow any host variables of the same names
+                for var in checkpoint.variables {
+                    if var.is_constant {
+                        scope.push_constant_dynamic(var.name, var.value);
+                    } else {
+                        scope.push_dynamic(var.name, var.value);
+                    }
+                    #[cfg(not(feature = "no_module"))]
+                    for alias in var.aliases {
+                        scope.add_alias_by_index(scope.len() - 1, alias);
+                    }
+                }
+
+                // Imported modules are not saved, so import them again
+                #[cfg(not(feature = "no_module"))]
+                for stmt in statements[..checkpoint.index]
+                    .iter()
+                    .filter(|stmt| matches!(stmt, Stmt::Import(..)))
+                {
+                    self.eval_global_statements(
+                        scope,
+                        global,
+                        &mut caches,
+                        std::slice::from_ref(stmt),
+                        _lib,
+                        0,
+                    )?;
+                }
+
+                checkpoint.index
+            }
+            None => 0,
+        };
+
+        // Run until the next checkpoint
+        let (end, label) = match checkpoints.into_iter().find(|&(index, ..)| index >= start) {
+            Some((index, label)) => (index, Some(label)),
+            None => (statements.len(), None),
+        };
+
+        let result = self.eval_global_statements(
+            scope,
+            global,
+            &mut caches,
+            &statements[start..end],
+            _lib,
+            0,
+        );
+
+        // Run any tasks still pending at the end of the evaluation
+        #[cfg(not(feature = "no_function"))]
+        let result = result.and_then(|value| {
+            self.run_pending_tasks(global, &mut caches, _lib, 0)
+                .map(|_| value)
+        });
+
+        self.recycle_caches(&mut caches);
+
+        let value = result?;
+
+        match label {
+            Some(label) => {
+                store.save(id, Checkpoint::new(label.clone(), end + 1, scope))?;
+
+                Ok(Durable::Suspended(label.as_str().into()))
+            }
+            None => {
+                store.remove(id)?;
+                Ok(Durable::Completed(value.flatten()))
+            }
+        }
+    }
+}
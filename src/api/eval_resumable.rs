@@ -0,0 +1,262 @@
+//! Module that defines the resumable evaluation API of [`Engine`].
+#![cfg(not(feature = "unchecked"))]
+#![cfg(not(feature = "no_function"))]
+
+use crate::eval::{Caches, Continuation, GlobalRuntimeState, Step};
+use crate::types::dynamic::Variant;
+use crate::{Engine, Position, RhaiResultOf, Scope, Shared, AST, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{any::type_name, marker::PhantomData, task::Poll};
+
+/// Handle to a resumable evaluation started by [`Engine::eval_resumable`] or
+/// [`Engine::eval_ast_resumable`].
+///
+/// The evaluation only runs while [`resume`][Resumable::resume] is called, for a budget of
+/// operations at a time, so long scripts can be time-sliced (e.g. across frames in a game loop).
+/// Between calls, the evaluation is suspended with its [`Scope`] and global runtime state intact,
+/// all on the calling thread.
+///
+/// Dropping a [`Resumable`] before it completes cancels the evaluation.
+///
+/// # Limitations
+///
+/// * The budget is checked before each statement and each loop iteration within blocks, `if`
+///   statements and loops, at global level as well as inside calls to script-defined functions,
+///   so the evaluation is only suspended between such statements and may run past its budget.
+///
+/// * A call to a script-defined function is suspended half-way only when it is a statement by
+///   itself, initializes a variable (e.g. `let x = foo(42);`) or is returned (e.g.
+///   `return foo(42);`), and is neither qualified with a namespace nor captures the parent scope
+///   (e.g. `foo!(42)`).  All other statements run to completion once started.
+///
+/// * When a debugger (`debugging` feature), coverage tracking (`coverage` feature) or a profiler
+///   (`profiling` feature) is active on the [`Engine`], their hooks would miss statements, so
+///   [`resume`][Resumable::resume] returns an error instead.
+///
+/// Not available under `unchecked` or `no_function`.
+#[must_use]
+pub struct Resumable<T> {
+    /// [`Engine`] evaluating the script.
+    engine: Shared<Engine>,
+    /// Script being evaluated.
+    ast: AST,
+    /// Variables of the evaluation.
+    scope: Scope<'static>,
+    /// Global runtime state of the evaluation.
+    global: GlobalRuntimeState<'static>,
+    /// Caches of the evaluation.
+    caches: Caches<'static>,
+    /// Statement where the evaluation is suspended.
+    cont: Continuation,
+    /// Has the evaluation completed?
+    done: bool,
+    /// Take care of the output type.
+    dummy: PhantomData<fn() -> T>,
+}
+
+impl<T: Variant + Clone> Resumable<T> {
+    /// Resume the evaluation for a budget of (at least one) operations.
+    ///
+    /// Returns [`Poll::Pending`] if the evaluation is suspended again after exhausting the budget,
+    /// or [`Poll::Ready`] with the result if it has completed.
+    ///
+    /// An error is returned if a debugger, coverage tracking or a profiler is active on the
+    /// [`Engine`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the evaluation has already completed.
+    pub fn resume(&mut self, num_operations: u64) -> Poll<RhaiResultOf<T>> {
+        assert!(!self.done, "evaluation already completed");
+
+        if self.engine.has_stmt_hooks() {
+            self.done = true;
+
+            return Poll::Ready(Err(ERR::ErrorSystem(
+                "Cannot evaluate in a resumable manner".into(),
+                "a debugger, coverage tracking or a profiler is active".into(),
+            )
+            .into()));
+        }
+
+        let Self {
+            engine,
+            ast,
+            scope,
+            global,
+            caches,
+            cont,
+            ..
+        } = self;
+
+        cont.suspend_at = global.num_operations.saturating_add(num_operations.max(1));
+
+        let lib = &[ast.as_ref()][..];
+        let lib = if ast.has_functions() { lib } else { &[] };
+        let resuming = cont.is_started();
+
+        let result = engine.exec_generator_block(
+            scope,
+            global,
+            caches,
+            lib,
+            &mut None,
+            cont,
+            0,
+            0,
+            ast.statements(),
+            resuming,
+            0,
+        );
+
+        let result = match result {
+            Ok(Step::Suspend) => return Poll::Pending,
+            Ok(Step::Done(value) | Step::Yield(value)) => Ok(value),
+            Err(err) => match *err {
+                ERR::Return(out, ..) => Ok(out),
+                mut err => {
+                    Engine::add_limit_context(&mut err, scope, "", Position::NONE);
+                    Err(err.into())
+                }
+            },
+        };
+
+        // Run any tasks still pending at the end of the evaluation
+        let result = result.and_then(|value| {
+            engine
+                .run_pending_tasks(global, caches, lib, 0)
+                .map(|_| value)
+        });
+
+        self.done = true;
+
+        self.engine
+            .report_eval_end(&self.global, result.as_ref().err().map(|err| &**err));
+
+        Poll::Ready(result.and_then(|value| {
+            let value = value.flatten();
+            let typ = self.engine.map_type_name(value.type_name());
+
+            value.try_cast::<T>().ok_or_else(|| {
+                let t = self.engine.map_type_name(type_name::<T>()).into();
+                ERR::ErrorMismatchOutputType(t, typ.into(), Position::NONE).into()
+            })
+        }))
+    }
+    /// Has the evaluation completed?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_done(&self) -> bool {
+        self.done
+    }
+    /// Number of operations performed so far.
+    #[inline(always)]
+    #[must_use]
+    pub const fn num_operations(&self) -> u64 {
+        self.global.num_operations
+    }
+    /// Get the [`Scope`] after the evaluation has completed, or [`None`] if it is not yet
+    /// completed.
+    #[inline(always)]
+    #[must_use]
+    pub fn scope(&self) -> Option<&Scope<'static>> {
+        if self.done {
+            Some(&self.scope)
+        } else {
+            None
+        }
+    }
+    /// Consume this [`Resumable`], returning the [`Scope`] after the evaluation has completed,
+    /// or [`None`] if it is not yet completed.
+    #[inline(always)]
+    #[must_use]
+    pub fn into_scope(self) -> Option<Scope<'static>> {
+        if self.done {
+            Some(self.scope)
+        } else {
+            None
+        }
+    }
+}
+
+impl Engine {
+    /// Compile a string as a script and start a resumable evaluation of it with a [`Scope`].
+    ///
+    /// Nothing is evaluated until [`Resumable::resume`] is called.
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    ///
+    /// See [`Resumable`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope, Shared, INT};
+    /// use std::task::Poll;
+    ///
+    /// let engine = Shared::new(Engine::new());
+    ///
+    /// let mut eval = engine.eval_resumable::<INT>(Scope::new(), "
+    ///     let x = 0;
+    ///     for n in 0..1000 { x += n; }
+    ///     x
+    /// ")?;
+    ///
+    /// let mut frames = 1;
+    ///
+    /// let result = loop {
+    ///     match eval.resume(100) {
+    ///         Poll::Ready(result) => break result?,
+    ///         Poll::Pending => frames += 1,
+    ///     }
+    /// };
+    ///
+    /// assert_eq!(result, 499500);
+    /// assert!(frames > 10);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_resumable<T: Variant + Clone>(
+        self: Shared<Self>,
+        scope: Scope<'static>,
+        script: &str,
+    ) -> RhaiResultOf<Resumable<T>> {
+        let ast = self.compile_with_scope(&scope, script)?;
+        Ok(self.eval_ast_resumable(scope, ast))
+    }
+    /// Start a resumable evaluation of an [`AST`] with a [`Scope`].
+    ///
+    /// Nothing is evaluated until [`Resumable::resume`] is called.
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    ///
+    /// See [`Resumable`] for details.
+    #[inline]
+    pub fn eval_ast_resumable<T: Variant + Clone>(
+        self: Shared<Self>,
+        scope: Scope<'static>,
+        ast: AST,
+    ) -> Resumable<T> {
+        let mut global = GlobalRuntimeState::new(&self);
+        global.source = ast.source_raw().clone();
+
+        #[cfg(not(feature = "no_module"))]
+        {
+            global.embedded_module_resolver = ast.resolver().cloned();
+        }
+
+        Resumable {
+            engine: self,
+            ast,
+            scope,
+            global,
+            caches: Caches::new(),
+            cont: Continuation::new_global(),
+            done: false,
+            dummy: PhantomData,
+        }
+    }
+}
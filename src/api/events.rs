@@ -1,6 +1,6 @@
 //! Module that defines public event handlers for [`Engine`].
 
-use crate::func::SendSync;
+use crate::func::{CallableFunction, SendSync};
 use crate::{Dynamic, Engine, EvalContext, Position, RhaiResultOf};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -18,6 +18,23 @@ pub struct VarDefInfo<'a> {
     pub will_shadow: bool,
 }
 
+/// Information on the progress of an evaluation run, passed to a callback registered via
+/// [`Engine::on_progress_sampled`].
+///
+/// Not available under `unchecked`.
+#[cfg(not(feature = "unchecked"))]
+#[non_exhaustive]
+pub struct ProgressInfo<'a> {
+    /// Number of operations performed so far.
+    pub num_operations: u64,
+    /// [Position] of the current operation.
+    pub position: Position,
+    /// Name of the script-defined function currently being evaluated, empty if at global level.
+    pub fn_name: &'a str,
+    /// Source of the script currently being evaluated, if any.
+    pub source: Option<&'a str>,
+}
+
 impl Engine {
     /// Provide a callback that will be invoked before each variable access.
     ///
@@ -204,6 +221,50 @@ impl Engine {
         self.token_mapper = Some(Box::new(callback));
         self
     }
+    /// Register a callback that will be invoked for each warning raised during compilation.
+    ///
+    /// Warnings do not stop compilation.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// `Fn(warning: ParseWarning, pos: Position)`
+    ///
+    /// where:
+    /// * [`warning`][crate::ParseWarning]: the warning
+    /// * [`pos`][`Position`]: location of the code that triggers the warning
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::{Engine, ParseWarning};
+    ///
+    /// let warnings = Arc::new(RwLock::new(Vec::new()));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let log = warnings.clone();
+    /// engine.on_parse_warning(move |warning, _| log.write().unwrap().push(warning));
+    ///
+    /// engine.compile("fn add(x, y) { let z = x + y; }")?;
+    ///
+    /// assert_eq!(
+    ///     *warnings.read().unwrap(),
+    ///     vec![ParseWarning::FnValueDiscarded("add".into())]
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_parse_warning(
+        &mut self,
+        callback: impl Fn(crate::ParseWarning, Position) + SendSync + 'static,
+    ) -> &mut Self {
+        self.parse_warning = Some(Box::new(callback));
+        self
+    }
     /// Register a callback for script evaluation progress.
     ///
     /// Not available under `unchecked`.
@@ -258,6 +319,103 @@ impl Engine {
         self.progress = Some(Box::new(callback));
         self
     }
+    /// Register a callback for progress reporting at a sampling interval, which receives the current
+    /// position and function name in addition to the number of operations performed.
+    ///
+    /// The callback is called once every `interval` operations (an interval of zero is treated as
+    /// one). It is called in addition to any callback registered via
+    /// [`on_progress`][Engine::on_progress].
+    ///
+    /// This also turns on [call stack tracking][Engine::set_track_call_stack] in order to report
+    /// the current function.
+    ///
+    /// Not available under `unchecked`.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// `Fn(info: &ProgressInfo) -> Option<Dynamic>`
+    ///
+    /// ## Return value
+    ///
+    /// * `None`: continue running the script.
+    /// * `Some(Dynamic)`: terminate the script with the specified exception value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::Engine;
+    ///
+    /// let hot_spots = Arc::new(RwLock::new(Vec::new()));
+    /// let logger = hot_spots.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.on_progress_sampled(100, move |info| {
+    ///     logger.write().unwrap().push(info.fn_name.to_string());
+    ///     None
+    /// });
+    ///
+    /// engine.run("fn busy() { let n = 0; for x in 0..1000 { n += x; } } busy();")?;
+    ///
+    /// assert!(hot_spots.read().unwrap().iter().any(|f| f == "busy"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub fn on_progress_sampled(
+        &mut self,
+        interval: u64,
+        callback: impl Fn(&ProgressInfo) -> Option<Dynamic> + SendSync + 'static,
+    ) -> &mut Self {
+        self.progress_sampled = Some((interval.max(1), Box::new(callback)));
+        #[cfg(not(feature = "no_function"))]
+        self.set_track_call_stack(true);
+        self
+    }
+    /// Register a callback that is called at the end of each evaluation run, successful or not,
+    /// with a read-only view of its global runtime state.
+    ///
+    /// This is useful for collecting telemetry, such as the number of operations performed.
+    ///
+    /// The callback is called by the `eval`, `run` and `call_fn` families of methods.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// `Fn(state: GlobalStateView)`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::Engine;
+    ///
+    /// let result = Arc::new(RwLock::new(0_u64));
+    /// let logger = result.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.on_eval_end(move |state| *logger.write().unwrap() = state.num_operations());
+    ///
+    /// engine.run("let x = 40; x + 2;")?;
+    ///
+    /// assert!(*result.read().unwrap() > 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_eval_end(
+        &mut self,
+        callback: impl Fn(crate::GlobalStateView) + SendSync + 'static,
+    ) -> &mut Self {
+        self.eval_end = Some(Box::new(callback));
+        self
+    }
     /// Override default action of `print` (print to stdout using [`println!`])
     ///
     /// # Example
@@ -337,6 +495,230 @@ impl Engine {
         self.debug = Box::new(callback);
         self
     }
+    /// Register a callback that is invoked after every evaluation of an operator (e.g. `+`, `-`,
+    /// `==`, `!`) with the names of the types of its operands and its result.
+    ///
+    /// This is useful for building expression auditors that trace how a result is calculated.
+    ///
+    /// Operators that short-circuit (i.e. `&&`, `||` and `??`) and op-assignments (e.g. `+=`) are
+    /// not traced.
+    ///
+    /// When no callback is registered, operators are evaluated without any overhead.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// The callback function signature passed takes the following form:
+    ///
+    /// `Fn(op: &str, operand_types: &[&str], result: &Dynamic, pos: Position)`
+    ///
+    /// where:
+    /// * `op`: the operator
+    /// * `operand_types`: names of the types of the operands
+    /// * `result`: result of the operator
+    /// * [`pos`][`Position`]: location of the operator
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::Engine;
+    ///
+    /// let result = Arc::new(RwLock::new(Vec::new()));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let logger = result.clone();
+    /// engine.on_operator(move |op, types, value, _| logger.write().unwrap().push(
+    ///                     format!("{} {} => {}", op, types.join(","), value)
+    ///                    ));
+    ///
+    /// engine.run("let x = 40; let y = x + 2; let z = y > 0;")?;
+    ///
+    /// assert_eq!(*result.read().unwrap(), ["+ i64,i64 => 42", "> i64,i64 => true"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_operator(
+        &mut self,
+        callback: impl Fn(&str, &[&str], &Dynamic, Position) + SendSync + 'static,
+    ) -> &mut Self {
+        self.operator_trace = Some(Box::new(callback));
+        self
+    }
+    /// Register a callback that is invoked when a function call cannot be resolved, with the name
+    /// of the function, the names of the types of its arguments and the position of the call.
+    ///
+    /// This is useful for proxy or RPC-style dynamic APIs, where it is not possible to register
+    /// every function beforehand.
+    ///
+    /// Property getters/setters and indexers are not covered.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// The callback function signature passed takes the following form:
+    ///
+    /// `Fn(name: &str, arg_types: &[&str], pos: Position) -> Result<Option<CallableFunction>, Box<EvalAltResult>>`
+    ///
+    /// where:
+    /// * `name`: name of the function
+    /// * `arg_types`: names of the types of the arguments
+    /// * [`pos`][`Position`]: location of the function call
+    ///
+    /// ## Return value
+    ///
+    /// * `Ok(None)`: the function is not found and the normal error is raised.
+    /// * `Ok(Some(func))`: the native function `func` is called in place of the missing function.
+    ///   A script-defined function is ignored.
+    /// * `Err(Box<EvalAltResult>)`: the error is raised instead of the normal error.
+    ///
+    /// The callback is invoked for each call to a missing function; synthesized functions are not
+    /// registered with the [`Engine`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    /// use rhai::plugin::CallableFunction;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.on_missing_function(|name, arg_types, _| match name.strip_prefix("rpc_") {
+    ///     Some(method) => {
+    ///         let call = format!("{}({})", method, arg_types.join(", "));
+    ///         Ok(Some(CallableFunction::from_native_fn(move |_, _| Ok(call.clone().into()))))
+    ///     }
+    ///     None => Ok(None),
+    /// });
+    ///
+    /// assert_eq!(engine.eval::<String>("rpc_get_user(42)")?, "get_user(i64)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_missing_function(
+        &mut self,
+        callback: impl Fn(&str, &[&str], Position) -> RhaiResultOf<Option<CallableFunction>>
+            + SendSync
+            + 'static,
+    ) -> &mut Self {
+        self.missing_fn = Some(Box::new(callback));
+        self
+    }
+    /// Register a callback that is invoked when a script attempts to assign to a read-only target,
+    /// such as a constant, a read-only value or a variable in the [frozen][crate::Scope::freeze]
+    /// base layer of a [`Scope`][crate::Scope].
+    ///
+    /// This is useful for logging such attempts, customizing the error raised or, when migrating
+    /// scripts, permitting the write.
+    ///
+    /// Property setters and indexers called on read-only objects are also covered, with an empty
+    /// name for indexers, but not other native functions that modify their first argument.
+    ///
+    /// While this callback is registered, constants in the [`Scope`][crate::Scope] are no longer
+    /// propagated during optimization, as they may be written to.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// The callback function signature passed takes the following form:
+    ///
+    /// `Fn(name: &str, value: &Dynamic, pos: Position) -> Result<bool, Box<EvalAltResult>>`
+    ///
+    /// where:
+    /// * `name`: name of the variable or property
+    /// * `value`: new value (for an op-assignment to a variable, the right-hand side of the operator)
+    /// * [`pos`][`Position`]: location of the assignment
+    ///
+    /// ## Return value
+    ///
+    /// * `Ok(false)`: the normal [`ErrorAssignmentToConstant`][crate::EvalAltResult::ErrorAssignmentToConstant]
+    ///   error is raised.
+    /// * `Ok(true)`: the write is permitted and the target stays read-only.  Variables in the
+    ///   frozen base layer of a [`Scope`][crate::Scope] can never be written to, so the normal
+    ///   error is still raised for them.
+    /// * `Err(Box<EvalAltResult>)`: the error is raised instead of the normal error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, EvalAltResult, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.on_invalid_assignment(|name, _, pos| match name {
+    ///     "LIMIT" => Ok(true),
+    ///     _ => Err(EvalAltResult::ErrorRuntime(format!("{} is read-only", name).into(), pos).into()),
+    /// });
+    ///
+    /// let mut scope = Scope::new();
+    /// scope.push_constant("LIMIT", 42_i64);
+    /// scope.push_constant("RATE", 2_i64);
+    ///
+    /// engine.run_with_scope(&mut scope, "LIMIT = 100;")?;
+    /// assert_eq!(scope.get_value::<i64>("LIMIT").unwrap(), 100);
+    /// assert!(scope.is_constant("LIMIT").unwrap());
+    ///
+    /// let err = engine.run_with_scope(&mut scope, "RATE += 1;").unwrap_err();
+    /// assert_eq!(err.to_string(), "Runtime error: RATE is read-only (line 1, position 1)");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn on_invalid_assignment(
+        &mut self,
+        callback: impl Fn(&str, &Dynamic, Position) -> RhaiResultOf<bool> + SendSync + 'static,
+    ) -> &mut Self {
+        self.invalid_assignment = Some(Box::new(callback));
+        self
+    }
+    /// Register a callback to be notified whenever a module is invalidated via
+    /// [`invalidate_module`][Engine::invalidate_module].
+    ///
+    /// Long-running hosts can use this to re-run scripts that import the module, so that they pick
+    /// up the new version.
+    ///
+    /// # Callback Function Signature
+    ///
+    /// The callback function signature takes the following form:
+    ///
+    /// `Fn(path: &str)`
+    ///
+    /// where:
+    /// * `path`: path of the invalidated module
+    ///
+    /// Not available under `no_module`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::sync::RwLock;
+    /// # use std::sync::Arc;
+    /// use rhai::Engine;
+    ///
+    /// let reloaded = Arc::new(RwLock::new(Vec::new()));
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// let log = reloaded.clone();
+    /// engine.on_module_invalidated(move |path| log.write().unwrap().push(path.to_string()));
+    ///
+    /// engine.invalidate_module("utils");
+    ///
+    /// assert_eq!(*reloaded.read().unwrap(), vec!["utils".to_string()]);
+    /// ```
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn on_module_invalidated(
+        &mut self,
+        callback: impl Fn(&str) + SendSync + 'static,
+    ) -> &mut Self {
+        self.module_invalidated = Some(Box::new(callback));
+        self
+    }
     /// _(debugging)_ Register a callback for debugging.
     /// Exported under the `debugging` feature only.
     ///
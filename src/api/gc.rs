@@ -0,0 +1,199 @@
+//! Module that defines the garbage collection API of [`Engine`].
+#![cfg(feature = "gc")]
+#![cfg(not(feature = "no_closure"))]
+#![cfg(not(feature = "no_std"))]
+
+use crate::func::locked_write;
+use crate::types::dynamic::Union;
+use crate::{Dynamic, Engine, Locked, Scope, Shared};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// A shared value.
+type SharedValue = Shared<Locked<Dynamic>>;
+
+/// A weak reference to a shared value.
+#[cfg(not(feature = "sync"))]
+type WeakValue = std::rc::Weak<Locked<Dynamic>>;
+/// A weak reference to a shared value.
+#[cfg(feature = "sync")]
+type WeakValue = std::sync::Weak<Locked<Dynamic>>;
+
+thread_local! {
+    /// Shared values created on the current thread, tracked for [`Engine::collect_garbage`].
+    static SHARED_VALUES: RefCell<Vec<WeakValue>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Track a newly-created shared value for [`Engine::collect_garbage`].
+#[inline]
+pub(crate) fn track_shared_value(value: &SharedValue) {
+    let _ = SHARED_VALUES.try_with(|values| {
+        if let Ok(mut values) = values.try_borrow_mut() {
+            // Drop dead references before growing the list
+            if values.len() == values.capacity() {
+                values.retain(|v| v.strong_count() > 0);
+                let len = values.len();
+                values.reserve(len);
+            }
+            values.push(Shared::downgrade(value));
+        }
+    });
+}
+
+/// Lock a shared value for reading, or [`None`] if it is currently locked for writing.
+#[inline(always)]
+fn try_read(value: &SharedValue) -> Option<crate::func::native::LockGuard<'_, Dynamic>> {
+    #[cfg(not(feature = "sync"))]
+    return value.try_borrow().ok();
+
+    #[cfg(feature = "sync")]
+    return value.try_read().ok();
+}
+
+/// Call a function for each shared value directly held by a [`Dynamic`] value (i.e. not via
+/// another shared value).
+fn for_each_shared(value: &Dynamic, f: &mut impl FnMut(&SharedValue)) {
+    match value.0 {
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(ref a, ..) => a.iter().for_each(|v| for_each_shared(v, f)),
+        #[cfg(not(feature = "no_object"))]
+        Union::Map(ref m, ..) => m.values().for_each(|v| for_each_shared(v, f)),
+        Union::Tuple(ref t, ..) => t.iter().for_each(|v| for_each_shared(v, f)),
+        Union::FnPtr(ref p, ..) => p.curry().iter().for_each(|v| for_each_shared(v, f)),
+        Union::Shared(ref cell, ..) => f(cell),
+        _ => (),
+    }
+}
+
+/// Statistics of a garbage collection pass, returned by [`Engine::collect_garbage`].
+/// Exported under the `gc` feature only.
+///
+/// Not available under `no_closure` or `no_std`.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct GarbageStats {
+    /// Number of live shared values scanned.
+    pub scanned: usize,
+    /// Number of shared values freed by breaking unreachable reference cycles.
+    pub freed: usize,
+}
+
+impl Engine {
+    /// Free shared values that are kept alive only by reference cycles among themselves.
+    /// Exported under the `gc` feature only.
+    ///
+    /// Not available under `no_closure` or `no_std`.
+    ///
+    /// Closures capture variables by turning them into shared values, so a closure stored inside
+    /// the value it captures (e.g. a method in an object map) forms a reference cycle that is
+    /// never freed, even when no variable refers to it any more. This is a problem for
+    /// long-lived sessions (e.g. a REPL) that evaluate many scripts.
+    ///
+    /// All shared values created on the current thread are scanned. Tracking them has a small
+    /// cost whenever a shared value is created, which is why this requires the `gc` feature.
+    /// Shared values that are referenced from the [`Scope`], or from anywhere else outside of
+    /// shared values (e.g. by the host or inside custom types), are kept together with everything
+    /// reachable from them. The rest are unreachable, and their contents are cleared to break
+    /// the cycles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Scope};
+    ///
+    /// let engine = Engine::new();
+    /// let mut scope = Scope::new();
+    ///
+    /// engine.run_with_scope(&mut scope, "
+    ///     let obj = #{ value: 42 };
+    ///     obj.get = || obj.value;     // 'obj' refers to itself via the closure
+    /// ")?;
+    ///
+    /// // 'obj' is still in the scope, so nothing is freed
+    /// assert_eq!(engine.collect_garbage(&mut scope).freed, 0);
+    ///
+    /// scope.clear();
+    ///
+    /// // 'obj' is now unreachable
+    /// assert_eq!(engine.collect_garbage(&mut scope).freed, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn collect_garbage(&self, scope: &mut Scope) -> GarbageStats {
+        let values: Vec<SharedValue> = SHARED_VALUES.with(|values| {
+            let mut values = values.borrow_mut();
+            values.retain(|v| v.strong_count() > 0);
+            values.iter().filter_map(WeakValue::upgrade).collect()
+        });
+
+        let index: HashMap<_, _> = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (Shared::as_ptr(v), i))
+            .collect();
+
+        // Count the references to each shared value from outside of shared values, by
+        // subtracting references held by shared values (and the one held here) from the total
+        let mut external: Vec<_> = values.iter().map(|v| Shared::strong_count(v) - 1).collect();
+        let mut reachable = vec![false; values.len()];
+        let mut pending = Vec::new();
+
+        for (i, value) in values.iter().enumerate() {
+            match try_read(value) {
+                Some(v) => for_each_shared(&v, &mut |cell| {
+                    if let Some(&j) = index.get(&Shared::as_ptr(cell)) {
+                        external[j] = external[j].saturating_sub(1);
+                    }
+                }),
+                // The value is in use, so it is reachable
+                None => {
+                    reachable[i] = true;
+                    pending.push(i);
+                }
+            }
+        }
+
+        let mut mark = |cell: &SharedValue, pending: &mut Vec<usize>| {
+            if let Some(&i) = index.get(&Shared::as_ptr(cell)) {
+                if !reachable[i] {
+                    reachable[i] = true;
+                    pending.push(i);
+                }
+            }
+        };
+
+        for (i, &count) in external.iter().enumerate() {
+            if count > 0 {
+                mark(&values[i], &mut pending);
+            }
+        }
+        for (.., value) in scope.iter_raw() {
+            for_each_shared(value, &mut |cell| mark(cell, &mut pending));
+        }
+
+        // Mark everything reachable from the roots
+        while let Some(i) = pending.pop() {
+            if let Some(v) = try_read(&values[i]) {
+                for_each_shared(&v, &mut |cell| mark(cell, &mut pending));
+            }
+        }
+
+        // Break the unreachable cycles, dropping the contents only after all are cleared
+        let garbage: Vec<_> = values
+            .iter()
+            .zip(reachable)
+            .filter(|(.., reachable)| !reachable)
+            .map(|(value, ..)| std::mem::take(&mut *locked_write(value)))
+            .collect();
+
+        let stats = GarbageStats {
+            scanned: values.len(),
+            freed: garbage.len(),
+        };
+
+        drop(garbage);
+        drop(values);
+
+        stats
+    }
+}
@@ -0,0 +1,113 @@
+//! Module that defines the API of [`Engine`]'s interned strings.
+
+use crate::func::{locked_read, locked_write};
+use crate::{Engine, ImmutableString};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Statistics of the strings interned by an [`Engine`], returned by
+/// [`Engine::interner_stats`].
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct InternerStats {
+    /// Number of strings currently interned.
+    pub len: usize,
+    /// Maximum number of strings interned.
+    pub capacity: usize,
+    /// Maximum length of strings interned.
+    pub max_string_len: usize,
+    /// Number of lookups that found an interned string.
+    pub hits: u64,
+    /// Number of lookups that did not find an interned string.
+    pub misses: u64,
+}
+
+impl Engine {
+    /// Intern a set of strings that are known to be used often (e.g. common property names), so
+    /// that they are shared instead of being allocated again when used by scripts.
+    ///
+    /// Interned strings are still evicted (longest first) when the interner is full, and strings
+    /// longer than [`max_interned_string_len`][Engine::max_interned_string_len] are not interned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.intern_strings(["id", "name", "timestamp"]);
+    ///
+    /// assert!(engine.interner_stats().len >= 3);
+    /// ```
+    pub fn intern_strings<S: AsRef<str> + Into<ImmutableString>>(
+        &mut self,
+        strings: impl IntoIterator<Item = S>,
+    ) -> &mut Self {
+        let mut interner = locked_write(&self.interned_strings);
+
+        for s in strings {
+            let _ = interner.get(s);
+        }
+
+        drop(interner);
+        self
+    }
+    /// Get [statistics][InternerStats] of the strings interned by this [`Engine`].
+    #[inline]
+    #[must_use]
+    pub fn interner_stats(&self) -> InternerStats {
+        let interner = &*locked_read(&self.interned_strings);
+
+        InternerStats {
+            len: interner.len(),
+            capacity: interner.capacity,
+            max_string_len: interner.max_string_len,
+            hits: interner.hits(),
+            misses: interner.misses(),
+        }
+    }
+    /// Remove all strings interned by this [`Engine`].
+    #[inline]
+    pub fn clear_interned_strings(&mut self) -> &mut Self {
+        locked_write(&self.interned_strings).clear();
+        self
+    }
+    /// The maximum number of strings interned by this [`Engine`].
+    #[inline]
+    #[must_use]
+    pub fn max_interned_strings(&self) -> usize {
+        locked_read(&self.interned_strings).capacity
+    }
+    /// Set the maximum number of strings interned by this [`Engine`].
+    /// Set to zero to stop interning strings.
+    ///
+    /// The default is 256.  When the interner is full, the longest strings are evicted first.
+    ///
+    /// Interned strings are kept for the lifetime of the [`Engine`], so a long-running [`Engine`]
+    /// running many different scripts may want to limit this.
+    pub fn set_max_interned_strings(&mut self, capacity: usize) -> &mut Self {
+        let mut interner = locked_write(&self.interned_strings);
+        interner.capacity = capacity;
+
+        if capacity == 0 {
+            interner.clear();
+        }
+
+        drop(interner);
+        self
+    }
+    /// The maximum length of strings interned by this [`Engine`].
+    #[inline]
+    #[must_use]
+    pub fn max_interned_string_len(&self) -> usize {
+        locked_read(&self.interned_strings).max_string_len
+    }
+    /// Set the maximum length of strings interned by this [`Engine`].
+    ///
+    /// The default is 24.  Longer strings are never interned.
+    #[inline]
+    pub fn set_max_interned_string_len(&mut self, max_len: usize) -> &mut Self {
+        locked_write(&self.interned_strings).max_string_len = max_len;
+        self
+    }
+}
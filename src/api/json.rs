@@ -2,7 +2,7 @@
 #![cfg(not(feature = "no_object"))]
 
 use crate::parser::ParseState;
-use crate::tokenizer::Token;
+use crate::tokenizer::{Token, TokenStream};
 use crate::{Engine, LexError, Map, OptimizationLevel, RhaiResultOf, Scope};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -120,7 +120,7 @@ impl Engine {
         let mut state = ParseState::new(self, &scope, Default::default(), tokenizer_control);
 
         let ast = self.parse_global_expr(
-            &mut stream.peekable(),
+            &mut TokenStream::new(stream),
             &mut state,
             #[cfg(not(feature = "no_optimize"))]
             OptimizationLevel::None,
@@ -7,6 +7,51 @@ use std::num::{NonZeroU64, NonZeroUsize};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// A table of fuel costs charged for each kind of operation when fuel metering is enabled via
+/// [`Engine::set_fuel_limit`].
+///
+/// Fuel is charged deterministically - the same script with the same inputs always consumes the
+/// same amount of fuel.
+///
+/// Not available under `unchecked`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FuelCosts {
+    /// Fuel charged for every operation (the same operations counted towards
+    /// [`Engine::max_operations`]).
+    pub operation: u64,
+    /// Additional fuel charged for every function call (native or script-defined).
+    pub function_call: u64,
+    /// Additional fuel charged for every iteration of a loop.
+    pub loop_iteration: u64,
+    /// Additional fuel charged for every string concatenation, including each segment of an
+    /// interpolated string.
+    pub string_concat: u64,
+    /// Additional fuel charged for every [array][crate::Array] created by an array literal.
+    pub array_alloc: u64,
+}
+
+impl FuelCosts {
+    /// Create a new [`FuelCosts`] table where every operation costs one unit of fuel and there
+    /// are no additional costs.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            operation: 1,
+            function_call: 0,
+            loop_iteration: 0,
+            string_concat: 0,
+            array_alloc: 0,
+        }
+    }
+}
+
+impl Default for FuelCosts {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// A type containing all the limits imposed by the [`Engine`].
 ///
 /// Not available under `unchecked`.
@@ -47,6 +92,26 @@ pub struct Limits {
     /// Not available under `no_object`.
     #[cfg(not(feature = "no_object"))]
     pub max_map_size: Option<NonZeroUsize>,
+    /// Maximum number of segments in an interpolated string literal.
+    pub max_interpolation_pieces: Option<NonZeroUsize>,
+    /// Maximum size of a script, in bytes.
+    pub max_script_size: Option<NonZeroUsize>,
+    /// Maximum number of tokens in a script.
+    pub max_tokens: Option<NonZeroUsize>,
+    /// Maximum number of functions defined in a script, including anonymous functions.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    pub max_functions: Option<NonZeroUsize>,
+    /// Maximum number of parameters of a function defined in a script.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    pub max_fn_params: Option<NonZeroUsize>,
+    /// Maximum amount of fuel allowed to be consumed.
+    pub fuel_limit: Option<NonZeroU64>,
+    /// Fuel costs of operations.
+    pub fuel_costs: FuelCosts,
 }
 
 impl Limits {
@@ -69,6 +134,15 @@ impl Limits {
             max_array_size: None,
             #[cfg(not(feature = "no_object"))]
             max_map_size: None,
+            max_interpolation_pieces: None,
+            max_script_size: None,
+            max_tokens: None,
+            #[cfg(not(feature = "no_function"))]
+            max_functions: None,
+            #[cfg(not(feature = "no_function"))]
+            max_fn_params: None,
+            fuel_limit: None,
+            fuel_costs: FuelCosts::new(),
         }
     }
 }
@@ -244,4 +318,256 @@ impl Engine {
             0
         }
     }
+    /// Set the maximum number of segments in an interpolated string literal (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    pub fn set_max_interpolation_pieces(&mut self, max_pieces: usize) -> &mut Self {
+        self.limits.max_interpolation_pieces = NonZeroUsize::new(max_pieces);
+        self
+    }
+    /// The maximum number of segments in an interpolated string literal (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.
+    #[inline]
+    #[must_use]
+    pub const fn max_interpolation_pieces(&self) -> usize {
+        if let Some(n) = self.limits.max_interpolation_pieces {
+            n.get()
+        } else {
+            0
+        }
+    }
+    /// Set the maximum size of a script in bytes (0 for unlimited).
+    ///
+    /// This limit is checked while the script is being tokenized, so that a huge script is
+    /// rejected before it exhausts memory.  A script exceeding it fails to compile with
+    /// [`ParseErrorType::ScriptTooLarge`][crate::ParseErrorType::ScriptTooLarge].
+    ///
+    /// Not available under `unchecked`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, ParseErrorType};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_max_script_size(20);
+    ///
+    /// engine.compile("let x = 42;")?;
+    ///
+    /// let err = engine.compile("let x = 42; let y = x + 1; y * 2").unwrap_err();
+    /// assert_eq!(*err.err_type(), ParseErrorType::ScriptTooLarge(20));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_max_script_size(&mut self, max_size: usize) -> &mut Self {
+        self.limits.max_script_size = NonZeroUsize::new(max_size);
+        self
+    }
+    /// The maximum size of a script in bytes (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.
+    #[inline]
+    #[must_use]
+    pub const fn max_script_size(&self) -> usize {
+        if let Some(n) = self.limits.max_script_size {
+            n.get()
+        } else {
+            0
+        }
+    }
+    /// Set the maximum number of tokens in a script (0 for unlimited).
+    ///
+    /// A script exceeding it fails to compile with
+    /// [`ParseErrorType::TooManyTokens`][crate::ParseErrorType::TooManyTokens].
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    pub fn set_max_tokens(&mut self, max_tokens: usize) -> &mut Self {
+        self.limits.max_tokens = NonZeroUsize::new(max_tokens);
+        self
+    }
+    /// The maximum number of tokens in a script (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.
+    #[inline]
+    #[must_use]
+    pub const fn max_tokens(&self) -> usize {
+        if let Some(n) = self.limits.max_tokens {
+            n.get()
+        } else {
+            0
+        }
+    }
+    /// Set the maximum number of functions defined in a script, including anonymous functions
+    /// and closures (0 for unlimited).
+    ///
+    /// A script exceeding it fails to compile with
+    /// [`ParseErrorType::TooManyFunctions`][crate::ParseErrorType::TooManyFunctions].
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_max_functions(&mut self, max_functions: usize) -> &mut Self {
+        self.limits.max_functions = NonZeroUsize::new(max_functions);
+        self
+    }
+    /// The maximum number of functions defined in a script (0 for unlimited).
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    #[must_use]
+    pub const fn max_functions(&self) -> usize {
+        if let Some(n) = self.limits.max_functions {
+            n.get()
+        } else {
+            0
+        }
+    }
+    /// Set the maximum number of parameters of a function defined in a script (0 for unlimited).
+    ///
+    /// A script exceeding it fails to compile with
+    /// [`ParseErrorType::TooManyParameters`][crate::ParseErrorType::TooManyParameters].
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_max_fn_params(&mut self, max_params: usize) -> &mut Self {
+        self.limits.max_fn_params = NonZeroUsize::new(max_params);
+        self
+    }
+    /// The maximum number of parameters of a function defined in a script (0 for unlimited).
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    #[must_use]
+    pub const fn max_fn_params(&self) -> usize {
+        if let Some(n) = self.limits.max_fn_params {
+            n.get()
+        } else {
+            0
+        }
+    }
+    /// Set the maximum amount of fuel allowed to be consumed by a script (0 for unlimited).
+    ///
+    /// Fuel is charged according to the table set via [`set_fuel_costs`][Engine::set_fuel_costs].
+    /// When the limit is exceeded, evaluation terminates with
+    /// [`ErrorTooManyOperations`][crate::EvalAltResult::ErrorTooManyOperations].
+    ///
+    /// Not available under `unchecked`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, FuelCosts};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_fuel_limit(1000).set_fuel_costs(FuelCosts {
+    ///     loop_iteration: 10,
+    ///     ..FuelCosts::new()
+    /// });
+    ///
+    /// assert!(engine.run("let n = 0; for x in 0..1000 { n += x; }").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_fuel_limit(&mut self, fuel: u64) -> &mut Self {
+        self.limits.fuel_limit = NonZeroU64::new(fuel);
+        self
+    }
+    /// The maximum amount of fuel allowed to be consumed by a script (0 for unlimited).
+    ///
+    /// Not available under `unchecked`.
+    #[inline]
+    #[must_use]
+    pub const fn fuel_limit(&self) -> u64 {
+        if let Some(n) = self.limits.fuel_limit {
+            n.get()
+        } else {
+            0
+        }
+    }
+    /// Set the table of fuel costs charged for each kind of operation.
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    pub fn set_fuel_costs(&mut self, costs: FuelCosts) -> &mut Self {
+        self.limits.fuel_costs = costs;
+        self
+    }
+    /// The table of fuel costs charged for each kind of operation.
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    #[must_use]
+    pub const fn fuel_costs(&self) -> &FuelCosts {
+        &self.limits.fuel_costs
+    }
+    /// Set a separate, usually stricter, profile of [`Limits`] for _nested_ evaluations, i.e. the
+    /// evaluation of an interpolated string or of an expression in a custom syntax.
+    ///
+    /// Nested evaluations remain subject to all the limits of the [`Engine`]. In addition:
+    ///
+    /// * `max_operations` and `fuel_limit` are counted from the start of the outermost nested
+    ///   evaluation, instead of from the start of the script,
+    /// * `max_call_stack_depth` limits the levels of function calls made within a nested evaluation,
+    /// * `max_string_size`, `max_array_size` and `max_map_size` limit the size of the result of a
+    ///   nested evaluation.
+    ///
+    /// All other fields of [`Limits`] are ignored.
+    ///
+    /// Not available under `unchecked`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Limits};
+    /// use std::num::NonZeroU64;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_max_operations(10_000).set_nested_limits(Limits {
+    ///     max_operations: NonZeroU64::new(100),
+    ///     ..Limits::new()
+    /// });
+    ///
+    /// // The loop in the parent script is within its budget...
+    /// engine.run("let n = 0; for x in 0..100 { n += x; }")?;
+    ///
+    /// // ... but the same loop inside an interpolated string is not
+    /// assert!(engine.run("let s = `${ let n = 0; for x in 0..100 { n += x; } n }`;").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_nested_limits(&mut self, limits: Limits) -> &mut Self {
+        self.nested_limits = Some(limits.into());
+        self
+    }
+    /// Remove the profile of [`Limits`] for nested evaluations, if any.
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    pub fn clear_nested_limits(&mut self) -> &mut Self {
+        self.nested_limits = None;
+        self
+    }
+    /// The profile of [`Limits`] for nested evaluations, if any.
+    ///
+    /// Not available under `unchecked`.
+    #[inline(always)]
+    #[must_use]
+    pub fn nested_limits(&self) -> Option<&Limits> {
+        self.nested_limits.as_deref()
+    }
 }
@@ -0,0 +1,106 @@
+//! Module that defines the metrics API of [`Engine`].
+
+use crate::eval::GlobalRuntimeState;
+use crate::func::{locked_read, SendSync};
+use crate::{Engine, EvalAltResult};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Trait that encapsulates a sink for metrics about the internals of an [`Engine`], e.g. for
+/// exporting them to Prometheus.
+///
+/// The following metrics are reported at the end of each evaluation run (see
+/// [`Engine::on_eval_end`] for the runs covered):
+///
+/// | Name                          | Kind    | Label  | Description                                        |
+/// |-------------------------------|---------|--------|----------------------------------------------------|
+/// | `rhai_evaluations_total`      | counter |        | number of evaluation runs                          |
+/// | `rhai_errors_total`           | counter | `kind` | number of runs failed, by the kind of error        |
+/// | `rhai_operations_total`       | counter |        | number of operations performed                     |
+/// | `rhai_fn_cache_hits_total`    | counter |        | function calls resolved via the resolution cache   |
+/// | `rhai_fn_cache_misses_total`  | counter |        | function calls not found in the resolution cache   |
+/// | `rhai_scope_size_peak`        | gauge   |        | largest number of variables in scope during a run  |
+/// | `rhai_interned_strings`       | gauge   |        | number of strings interned by the [`Engine`]       |
+///
+/// The kind of error is the name of the [`EvalAltResult`] variant (e.g. `ErrorVariableNotFound`),
+/// looking through errors in function calls and modules.
+pub trait MetricsSink: SendSync {
+    /// Add a value to a counter, with an optional label as a `(name, value)` pair.
+    fn increment_counter(
+        &self,
+        name: &'static str,
+        label: Option<(&'static str, &str)>,
+        value: u64,
+    );
+
+    /// Set the value of a gauge.
+    fn set_gauge(&self, name: &'static str, value: u64);
+}
+
+impl Engine {
+    /// Set the [`MetricsSink`] to receive metrics about the internals of this [`Engine`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, MetricsSink};
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// struct Errors(Arc<AtomicU64>);
+    ///
+    /// impl MetricsSink for Errors {
+    ///     fn increment_counter(&self, name: &'static str, _: Option<(&'static str, &str)>, value: u64) {
+    ///         if name == "rhai_errors_total" {
+    ///             self.0.fetch_add(value, Ordering::Relaxed);
+    ///         }
+    ///     }
+    ///     fn set_gauge(&self, _: &'static str, _: u64) {}
+    /// }
+    ///
+    /// let errors = Arc::new(AtomicU64::new(0));
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.set_metrics_sink(Errors(errors.clone()));
+    ///
+    /// engine.run("let x = 42;")?;
+    /// assert!(engine.run("x").is_err());
+    ///
+    /// assert_eq!(errors.load(Ordering::Relaxed), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn set_metrics_sink(&mut self, sink: impl MetricsSink + 'static) -> &mut Self {
+        self.metrics = Some(Box::new(sink));
+        self
+    }
+    /// Report the metrics of an evaluation run to the [`MetricsSink`], if any.
+    pub(crate) fn report_metrics(
+        &self,
+        global: &GlobalRuntimeState,
+        error: Option<&EvalAltResult>,
+    ) {
+        let sink = match self.metrics {
+            Some(ref sink) => sink,
+            None => return,
+        };
+
+        sink.increment_counter("rhai_evaluations_total", None, 1);
+
+        if let Some(err) = error {
+            let kind = err.unwrap_inner().kind();
+            sink.increment_counter("rhai_errors_total", Some(("kind", kind)), 1);
+        }
+
+        sink.increment_counter("rhai_operations_total", None, global.num_operations);
+        sink.increment_counter("rhai_fn_cache_hits_total", None, global.fn_cache_hits);
+        sink.increment_counter("rhai_fn_cache_misses_total", None, global.fn_cache_misses);
+        sink.set_gauge("rhai_scope_size_peak", global.peak_scope_size as u64);
+        sink.set_gauge(
+            "rhai_interned_strings",
+            locked_read(&self.interned_strings).len() as u64,
+        );
+    }
+}
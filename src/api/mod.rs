@@ -12,6 +12,16 @@ pub mod json;
 
 pub mod files;
 
+pub mod eval_async;
+
+pub mod eval_resumable;
+
+pub mod eval_concurrent;
+
+pub mod eval_durable;
+
+pub mod snapshot;
+
 pub mod register;
 
 pub mod call_fn;
@@ -22,6 +32,18 @@ pub mod optimize;
 
 pub mod limits;
 
+pub mod interner;
+
+pub mod gc;
+
+pub mod metrics;
+
+pub mod tracing;
+
+pub mod profiling;
+
+pub mod coverage;
+
 pub mod events;
 
 pub mod custom_syntax;
@@ -30,6 +52,12 @@ pub mod deprecated;
 
 pub mod build_type;
 
+pub mod build_info;
+
+pub mod verify;
+
+pub mod bytecode;
+
 #[cfg(feature = "metadata")]
 pub mod definitions;
 
@@ -92,6 +120,30 @@ impl Engine {
         self
     }
 
+    /// Invalidate any cached [module][crate::Module] resolved from a path by the module resolution
+    /// service, so that the next `import` with that path resolves it afresh (e.g. reloads a modified
+    /// script file).
+    ///
+    /// The callback registered via [`on_module_invalidated`][Engine::on_module_invalidated], if any,
+    /// is then called with the path.
+    ///
+    /// Returns `true` if a cached [module][crate::Module] was removed.
+    ///
+    /// Modules that import the invalidated module are not invalidated automatically.
+    ///
+    /// Not available under `no_module`.
+    #[cfg(not(feature = "no_module"))]
+    #[inline]
+    pub fn invalidate_module(&self, path: &str) -> bool {
+        let invalidated = self.module_resolver.invalidate(path);
+
+        if let Some(ref callback) = self.module_invalidated {
+            callback(path);
+        }
+
+        invalidated
+    }
+
     /// Disable a particular keyword or operator in the language.
     ///
     /// # Examples
@@ -165,10 +217,62 @@ impl Engine {
     /// # }
     /// ```
     #[cfg(not(feature = "no_custom_syntax"))]
+    #[inline(always)]
     pub fn register_custom_operator(
         &mut self,
         keyword: impl AsRef<str>,
         precedence: u8,
+    ) -> Result<&mut Self, String> {
+        self.register_custom_operator_with_precedence(
+            keyword,
+            precedence,
+            crate::CustomOperatorKind::Binary,
+        )
+    }
+
+    /// Register a custom operator of a particular [kind][crate::CustomOperatorKind] with a
+    /// precedence into the language.
+    ///
+    /// Not available under `no_custom_syntax`.
+    ///
+    /// Binary operators call a function with two parameters, while prefix and postfix operators
+    /// call a function with one parameter.
+    ///
+    /// The operator can be a valid identifier, a reserved symbol, a disabled operator or a disabled keyword.
+    ///
+    /// The precedence cannot be zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{CustomOperatorKind, Engine};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// // Register a prefix operator called '~'
+    /// engine
+    ///     .register_custom_operator_with_precedence("~", 200, CustomOperatorKind::Prefix)
+    ///     .expect("should succeed");
+    /// engine.register_fn("~", |x: i64| !x);
+    ///
+    /// // Register a right-binding operator called 'pow'
+    /// engine
+    ///     .register_custom_operator_with_precedence("pow", 170, CustomOperatorKind::BinaryRight)
+    ///     .expect("should succeed");
+    /// engine.register_fn("pow", |x: i64, y: i64| x.pow(y as u32));
+    ///
+    /// assert_eq!(engine.eval_expression::<i64>("~0 + 1")?, 0);
+    /// assert_eq!(engine.eval_expression::<i64>("2 pow 3 pow 2")?, 512);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_custom_syntax"))]
+    pub fn register_custom_operator_with_precedence(
+        &mut self,
+        keyword: impl AsRef<str>,
+        precedence: u8,
+        kind: crate::CustomOperatorKind,
     ) -> Result<&mut Self, String> {
         let precedence = Precedence::new(precedence);
 
@@ -215,6 +319,12 @@ impl Engine {
         // Add to custom keywords
         self.custom_keywords.insert(keyword.into(), precedence);
 
+        if kind == crate::CustomOperatorKind::Binary {
+            self.custom_operator_kinds.remove(keyword);
+        } else {
+            self.custom_operator_kinds.insert(keyword.into(), kind);
+        }
+
         Ok(self)
     }
 
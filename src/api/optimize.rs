@@ -59,7 +59,7 @@ impl Engine {
             .map(|f| f.func.get_script_fn_def().unwrap().clone())
             .collect();
 
-        let mut _new_ast = crate::optimizer::optimize_into_ast(
+        let (mut _new_ast, ..) = crate::optimizer::optimize_into_ast(
             self,
             scope,
             ast.take_statements(),
@@ -1,5 +1,6 @@
 //! Settings for [`Engine`]'s language options.
 
+use crate::types::StringsInterner;
 use crate::Engine;
 use bitflags::bitflags;
 #[cfg(feature = "no_std")]
@@ -29,6 +30,19 @@ bitflags! {
         const FAIL_ON_INVALID_MAP_PROPERTY = 0b_0000_1000_0000;
         /// Fast operators mode?
         const FAST_OPS = 0b_0001_0000_0000;
+        /// Is shadowing a variable defined in the same block allowed?
+        const SHADOW_SAME_SCOPE = 0b_0010_0000_0000;
+        /// Strict constants mode?
+        const STRICT_CONST = 0b_0100_0000_0000;
+        /// Is an explicit `return` required to return a value from a function?
+        #[cfg(not(feature = "no_function"))]
+        const EXPLICIT_RETURN = 0b_1000_0000_0000;
+        /// Do tail calls of script-defined functions to themselves re-use their stack frames?
+        #[cfg(not(feature = "no_function"))]
+        const TAIL_CALLS = 0b_0001_0000_0000_0000;
+        /// Are active calls to script-defined functions tracked in a call stack?
+        #[cfg(not(feature = "no_function"))]
+        const CALL_STACK = 0b_0010_0000_0000_0000;
     }
 }
 
@@ -42,6 +56,7 @@ impl LangOptions {
             | Self::STMT_EXPR
             | Self::LOOPING
             | Self::SHADOW
+            | Self::SHADOW_SAME_SCOPE
             | Self::FAST_OPS
             | {
                 #[cfg(not(feature = "no_function"))]
@@ -135,6 +150,21 @@ impl Engine {
     pub fn set_allow_shadowing(&mut self, enable: bool) {
         self.options.set(LangOptions::SHADOW, enable);
     }
+    /// Is shadowing a variable defined in the same block allowed?
+    /// Default is `true`.
+    ///
+    /// When this is `false` but [`allow_shadowing`][Engine::allow_shadowing] is `true`, a variable
+    /// can only be shadowed inside a nested block. This is checked during compilation.
+    #[inline(always)]
+    #[must_use]
+    pub const fn allow_shadowing_in_same_scope(&self) -> bool {
+        self.options.contains(LangOptions::SHADOW_SAME_SCOPE)
+    }
+    /// Set whether shadowing a variable defined in the same block is allowed.
+    #[inline(always)]
+    pub fn set_allow_shadowing_in_same_scope(&mut self, enable: bool) {
+        self.options.set(LangOptions::SHADOW_SAME_SCOPE, enable);
+    }
     /// Is strict variables mode enabled?
     /// Default is `false`.
     #[inline(always)]
@@ -147,6 +177,46 @@ impl Engine {
     pub fn set_strict_variables(&mut self, enable: bool) {
         self.options.set(LangOptions::STRICT_VAR, enable);
     }
+    /// Is strict constants mode enabled?
+    /// Default is `false`.
+    ///
+    /// Assigning directly to a constant is always a compilation error. Under strict constants mode,
+    /// the following are also compilation errors instead of run-time errors:
+    /// * assigning to an element or property of a constant (e.g. `FOO[0] = 1` or `FOO.bar += 1`),
+    /// * assigning to a constant captured by a closure,
+    /// * assigning to a constant in the [`Scope`][crate::Scope] passed to the compiler.
+    #[inline(always)]
+    #[must_use]
+    pub const fn strict_constants(&self) -> bool {
+        self.options.contains(LangOptions::STRICT_CONST)
+    }
+    /// Set whether strict constants mode is enabled.
+    #[inline(always)]
+    pub fn set_strict_constants(&mut self, enable: bool) {
+        self.options.set(LangOptions::STRICT_CONST, enable);
+    }
+    /// Is an explicit `return` required to return a value from a function?
+    /// Default is `false`.
+    ///
+    /// When enabled, it is a compilation error for a function to end with an expression whose
+    /// value is returned implicitly (e.g. `fn foo(x) { x + 1 }`).
+    /// Function and method calls at the end of a function are still allowed.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn require_explicit_return(&self) -> bool {
+        self.options.contains(LangOptions::EXPLICIT_RETURN)
+    }
+    /// Set whether an explicit `return` is required to return a value from a function.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_require_explicit_return(&mut self, enable: bool) {
+        self.options.set(LangOptions::EXPLICIT_RETURN, enable);
+    }
     /// Raise error if an object map property does not exist?
     /// Default is `false`.
     ///
@@ -179,4 +249,160 @@ impl Engine {
     pub fn set_fast_operators(&mut self, enable: bool) {
         self.options.set(LangOptions::FAST_OPS, enable);
     }
+    /// Is tail-call optimization enabled?
+    /// Default is `false`.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn optimize_tail_calls(&self) -> bool {
+        self.options.contains(LangOptions::TAIL_CALLS)
+    }
+    /// Set whether tail-call optimization is enabled.
+    ///
+    /// When enabled, a call of a script-defined function to itself in tail position (i.e. the
+    /// value of `return` or the last expression of the function body, including in the last
+    /// `if` branches) re-uses the stack frame of the function instead of creating a new one, so
+    /// accumulator-style recursion runs in constant stack space and never exceeds the maximum
+    /// number of call levels.
+    ///
+    /// Tail calls within `try` blocks, and in functions called in method-call style (i.e. with
+    /// `this`), are not optimized.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_optimize_tail_calls(&mut self, enable: bool) {
+        self.options.set(LangOptions::TAIL_CALLS, enable);
+    }
+    /// Are active calls to script-defined functions tracked in a call stack?
+    /// Default is `false`.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn track_call_stack(&self) -> bool {
+        self.options.contains(LangOptions::CALL_STACK)
+    }
+    /// Set whether active calls to script-defined functions are tracked in a call stack, which
+    /// is returned by the `call_stack` function and by
+    /// [`NativeCallContext::call_stack`][crate::NativeCallContext::call_stack].
+    ///
+    /// Tracking adds a small cost to every function call, so it is off by default.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    pub fn set_track_call_stack(&mut self, enable: bool) -> &mut Self {
+        self.options.set(LangOptions::CALL_STACK, enable);
+        self
+    }
+    /// The maximum number of strings in the pool shared by all [`AST`][crate::AST]'s compiled by
+    /// this [`Engine`].
+    /// Zero (the default) means that no pool is used.
+    #[inline]
+    #[must_use]
+    pub fn strings_pool_capacity(&self) -> usize {
+        self.strings_pool
+            .as_ref()
+            .map_or(0, |pool| crate::func::locked_read(pool).capacity)
+    }
+    /// Set the maximum number of strings in the pool shared by all [`AST`][crate::AST]'s compiled
+    /// by this [`Engine`].
+    /// Set to zero to turn off the pool and release all pooled strings.
+    ///
+    /// When the pool is turned on, string constants and identifiers in all scripts compiled by
+    /// this [`Engine`] are deduplicated against the pool, so [`AST`][crate::AST]'s compiled from
+    /// similar scripts share a single copy of each string.  This reduces memory usage when a large
+    /// number of [`AST`][crate::AST]'s are kept around at the same time.
+    ///
+    /// When the pool is full, the longest strings are evicted first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_strings_pool_capacity(10_000);
+    ///
+    /// let ast1 = engine.compile(r#"let endpoint = "https://example.com/api/v1/status";"#)?;
+    /// let ast2 = engine.compile(r#"let endpoint = "https://example.com/api/v1/status";"#)?;
+    ///
+    /// let endpoint = |ast: &rhai::AST| {
+    ///     let (.., value) = ast.iter_literal_variables(false, true).next().unwrap();
+    ///     value.into_immutable_string().unwrap()
+    /// };
+    ///
+    /// // Both ASTs share the same copy of the string constant
+    /// assert!(endpoint(&ast1).ptr_eq(&endpoint(&ast2)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_strings_pool_capacity(&mut self, capacity: usize) -> &mut Self {
+        if capacity == 0 {
+            self.strings_pool = None;
+        } else if let Some(ref mut pool) = self.strings_pool {
+            crate::func::locked_write(pool).capacity = capacity;
+        } else {
+            let mut pool = StringsInterner::new();
+            pool.capacity = capacity;
+            pool.max_string_len = usize::MAX;
+            self.strings_pool = Some(pool.into());
+        }
+        self
+    }
+}
+
+/// Policy for operators between [`Decimal`][rust_decimal::Decimal] and floating-point numbers.
+///
+/// Only available under `decimal` and not under `no_float`.
+#[cfg(feature = "decimal")]
+#[cfg(not(feature = "no_float"))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DecimalFloatPolicy {
+    /// Raise an error when a decimal number is mixed with a floating-point number.
+    Error,
+    /// Convert the floating-point number into a decimal number.
+    ToDecimal,
+    /// Convert the decimal number into a floating-point number.
+    ToFloat,
+}
+
+#[cfg(feature = "decimal")]
+#[cfg(not(feature = "no_float"))]
+impl Default for DecimalFloatPolicy {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+#[cfg(feature = "decimal")]
+#[cfg(not(feature = "no_float"))]
+impl Engine {
+    /// The policy for arithmetic and comparison operators between
+    /// [`Decimal`][rust_decimal::Decimal] and floating-point numbers.
+    /// Default is [`DecimalFloatPolicy::Error`].
+    ///
+    /// Only available under `decimal` and not under `no_float`.
+    #[inline(always)]
+    #[must_use]
+    pub const fn decimal_float_policy(&self) -> DecimalFloatPolicy {
+        self.decimal_float_policy
+    }
+    /// Set the policy for arithmetic and comparison operators between
+    /// [`Decimal`][rust_decimal::Decimal] and floating-point numbers.
+    ///
+    /// Only available under `decimal` and not under `no_float`.
+    #[inline(always)]
+    pub fn set_decimal_float_policy(&mut self, policy: DecimalFloatPolicy) -> &mut Self {
+        self.decimal_float_policy = policy;
+        self
+    }
 }
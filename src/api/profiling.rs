@@ -0,0 +1,279 @@
+//! Module that defines the profiling API of [`Engine`].
+#![cfg(feature = "profiling")]
+
+use crate::eval::GlobalRuntimeState;
+use crate::func::locked_write;
+use crate::{Engine, Identifier, ImmutableString, Instant, Position};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Name of the root frame in folded stacks, standing for the top level of the script.
+pub const ROOT_FRAME_NAME: &str = "<script>";
+
+/// Profiling statistics of a script-defined function.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct FnProfile {
+    /// Number of calls.
+    pub calls: u64,
+    /// Wall time spent in the function, including calls to other script-defined functions.
+    pub total_time: Duration,
+    /// Wall time spent in the function itself, excluding calls to other script-defined functions.
+    pub self_time: Duration,
+    /// Number of operations performed in the function, including calls to other script-defined
+    /// functions.
+    pub operations: u64,
+}
+
+/// Profiling statistics of a statement.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct StmtProfile {
+    /// Number of times the statement is run.
+    pub hits: u64,
+    /// Wall time spent in the statement, including any nested statements and function calls.
+    pub time: Duration,
+    /// Number of operations performed in the statement, including any nested statements and
+    /// function calls.
+    pub operations: u64,
+}
+
+/// A profile of evaluation runs, recording the wall time and number of operations spent in each
+/// script-defined function and each statement.
+///
+/// Exported under the `profiling` feature only.
+///
+/// Functions are identified by name only, so overloaded functions (i.e. with the same name but
+/// different numbers of parameters) are combined.  Statements are identified by source and
+/// [position][Position].
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// Statistics of script-defined functions, by name.
+    functions: BTreeMap<ImmutableString, FnProfile>,
+    /// Statistics of statements, by source and position.
+    statements: BTreeMap<(Identifier, Position), StmtProfile>,
+    /// Self time of each stack of function calls, by folded stack.
+    stacks: BTreeMap<String, Duration>,
+}
+
+impl Profile {
+    /// Is this [`Profile`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty() && self.stacks.is_empty()
+    }
+    /// Get the statistics of a script-defined function, if it has been called.
+    #[inline(always)]
+    #[must_use]
+    pub fn function(&self, name: &str) -> Option<&FnProfile> {
+        self.functions.get(name)
+    }
+    /// Iterate through the statistics of all script-defined functions called, in order of name.
+    #[inline]
+    pub fn functions(&self) -> impl Iterator<Item = (&str, &FnProfile)> {
+        self.functions.iter().map(|(name, p)| (name.as_str(), p))
+    }
+    /// Iterate through the statistics of all statements run, in order of source and position.
+    ///
+    /// The source is empty if none.
+    #[inline]
+    pub fn statements(&self) -> impl Iterator<Item = (&str, Position, &StmtProfile)> {
+        self.statements
+            .iter()
+            .map(|((source, pos), p)| (source.as_str(), *pos, p))
+    }
+    /// Export the self time of each stack of function calls in _folded stacks_ format, one line
+    /// per stack, for rendering into a flame graph by tools such as
+    /// [`inferno`](https://crates.io/crates/inferno) or
+    /// [`flamegraph.pl`](https://github.com/brendangregg/FlameGraph).
+    ///
+    /// Each line contains the names of the functions in the stack, outermost first and separated
+    /// by `;`, followed by a space and the self time in microseconds.  The outermost frame is
+    /// always [`<script>`][ROOT_FRAME_NAME], standing for the top level of the script.
+    ///
+    /// ```text
+    /// <script> 150
+    /// <script>;foo 1200
+    /// <script>;foo;bar 800
+    /// ```
+    #[must_use]
+    pub fn to_folded_stacks(&self) -> String {
+        let mut output = String::new();
+
+        for (stack, time) in &self.stacks {
+            writeln!(output, "{stack} {}", time.as_micros()).expect("`String` never fails");
+        }
+
+        output
+    }
+    /// Merge the statistics recorded by a [`Profiler`] during an evaluation run.
+    fn merge(&mut self, profiler: &Profiler) {
+        let run = &profiler.profile;
+
+        for (name, p) in &run.functions {
+            let entry = self.functions.entry(name.clone()).or_default();
+            entry.calls += p.calls;
+            entry.total_time += p.total_time;
+            entry.self_time += p.self_time;
+            entry.operations += p.operations;
+        }
+        for (key, p) in &run.statements {
+            let entry = self.statements.entry(key.clone()).or_default();
+            entry.hits += p.hits;
+            entry.time += p.time;
+            entry.operations += p.operations;
+        }
+        for (stack, time) in &run.stacks {
+            *self.stacks.entry(stack.clone()).or_default() += *time;
+        }
+
+        // The top level of the script is still running
+        let root = &profiler.stack[0];
+        let self_time = root.start.elapsed().saturating_sub(root.child_time);
+        *self.stacks.entry(root.name.to_string()).or_default() += self_time;
+    }
+}
+
+/// A frame in the stack of function calls of a [`Profiler`].
+#[derive(Debug, Clone)]
+struct ProfilerFrame {
+    /// Function name.
+    name: ImmutableString,
+    /// Time when the call started.
+    start: Instant,
+    /// Number of operations performed when the call started.
+    #[cfg(not(feature = "no_function"))]
+    operations: u64,
+    /// Wall time spent in calls to other script-defined functions.
+    child_time: Duration,
+}
+
+/// Recorder of the [`Profile`] of a single evaluation run.
+#[derive(Debug, Clone)]
+pub(crate) struct Profiler {
+    /// Statistics recorded so far.
+    profile: Profile,
+    /// Stack of active function calls, starting with the top level of the script.
+    stack: Vec<ProfilerFrame>,
+}
+
+impl Profiler {
+    /// Create a new [`Profiler`], starting the clock on the top level of the script.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            profile: Profile::default(),
+            stack: vec![ProfilerFrame {
+                name: ROOT_FRAME_NAME.into(),
+                start: Instant::now(),
+                #[cfg(not(feature = "no_function"))]
+                operations: 0,
+                child_time: Duration::ZERO,
+            }],
+        }
+    }
+    /// Record the start of a call to a script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    pub fn enter_fn(&mut self, name: ImmutableString, operations: u64) {
+        self.stack.push(ProfilerFrame {
+            name,
+            start: Instant::now(),
+            operations,
+            child_time: Duration::ZERO,
+        });
+    }
+    /// Record the end of the current call to a script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    pub fn exit_fn(&mut self, operations: u64) {
+        let frame = self.stack.pop().expect("`enter_fn` called");
+        let total_time = frame.start.elapsed();
+        let self_time = total_time.saturating_sub(frame.child_time);
+
+        let entry = self
+            .profile
+            .functions
+            .entry(frame.name.clone())
+            .or_default();
+        entry.calls += 1;
+        entry.total_time += total_time;
+        entry.self_time += self_time;
+        entry.operations += operations.saturating_sub(frame.operations);
+
+        let mut stack = String::new();
+        self.stack.iter().for_each(|f| {
+            stack.push_str(&f.name);
+            stack.push(';');
+        });
+        stack.push_str(&frame.name);
+        *self.profile.stacks.entry(stack).or_default() += self_time;
+
+        self.stack.last_mut().expect("root frame").child_time += total_time;
+    }
+    /// Record a run of a statement.
+    pub fn record_stmt(&mut self, source: &str, pos: Position, time: Duration, operations: u64) {
+        let entry = self
+            .profile
+            .statements
+            .entry((source.into(), pos))
+            .or_default();
+        entry.hits += 1;
+        entry.time += time;
+        entry.operations += operations;
+    }
+}
+
+impl Engine {
+    /// Start profiling all evaluation runs, replacing any [`Profile`] currently being recorded.
+    /// Exported under the `profiling` feature only.
+    ///
+    /// The wall time and number of operations spent in each script-defined function and each
+    /// statement are accumulated across evaluation runs (see [`Engine::on_eval_end`] for the runs
+    /// covered) until [`take_profile`][Engine::take_profile] is called.
+    ///
+    /// Profiling adds a clock reading to every statement and function call, so evaluation is
+    /// noticeably slower while it is active, but much faster than profiling via a debugger.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.start_profiling();
+    ///
+    /// engine.run(
+    /// "
+    ///     fn foo(x) { x * 2 }
+    ///
+    ///     for i in 0..10 { foo(i); }
+    /// ")?;
+    ///
+    /// let profile = engine.take_profile().unwrap();
+    ///
+    /// assert_eq!(profile.function("foo").unwrap().calls, 10);
+    /// assert!(profile.to_folded_stacks().contains("<script>;foo "));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline(always)]
+    pub fn start_profiling(&mut self) -> &mut Self {
+        self.profile = Some(Profile::default().into());
+        self
+    }
+    /// Stop profiling evaluation runs, returning the [`Profile`] recorded, if any.
+    /// Exported under the `profiling` feature only.
+    #[inline(always)]
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile
+            .take()
+            .map(|profile| std::mem::take(&mut *locked_write(&profile)))
+    }
+    /// Merge the statistics recorded during an evaluation run into the [`Profile`], if any.
+    pub(crate) fn report_profile(&self, global: &GlobalRuntimeState) {
+        if let (Some(profile), Some(profiler)) = (&self.profile, &global.profiler) {
+            locked_write(profile).merge(profiler);
+        }
+    }
+}
@@ -3,7 +3,8 @@
 use crate::func::{FnCallArgs, RegisterNativeFunction, SendSync};
 use crate::types::dynamic::Variant;
 use crate::{
-    Engine, FnAccess, FnNamespace, Identifier, Module, NativeCallContext, RhaiResultOf, Shared,
+    Dynamic, Engine, FnAccess, FnNamespace, Identifier, Module, NativeCallContext, RhaiResultOf,
+    Shared,
 };
 use std::any::{type_name, TypeId};
 #[cfg(feature = "no_std")]
@@ -28,6 +29,14 @@ impl Engine {
     }
     /// Register a custom function with the [`Engine`].
     ///
+    /// # Return Value
+    ///
+    /// The return value is converted via [`Dynamic::from`], which does not look inside generic
+    /// types. A function returning an [`Option`], a [`Result`] (other than
+    /// `Result<T, Box<EvalAltResult>>`), a tuple, a [`HashMap`][std::collections::HashMap] etc.
+    /// hands a custom type to scripts. To pass the value as `()`, an object map or an array instead, convert it
+    /// via `into()` and return a [`Dynamic`].
+    ///
     /// # Example
     ///
     /// ```
@@ -127,6 +136,10 @@ impl Engine {
     /// Register a custom type for use with the [`Engine`].
     /// The type must implement [`Clone`].
     ///
+    /// The type is displayed by its Rust type name without the path (see
+    /// [`map_type_name`][Engine::map_type_name]), e.g. `TestStruct` instead of
+    /// `my_crate::TestStruct`.
+    ///
     /// # Example
     ///
     /// ```
@@ -166,7 +179,8 @@ impl Engine {
     /// ```
     #[inline(always)]
     pub fn register_type<T: Variant + Clone>(&mut self) -> &mut Self {
-        self.register_type_with_name::<T>(type_name::<T>())
+        let name = self.map_type_name(type_name::<T>()).into_owned();
+        self.register_type_with_name::<T>(&name)
     }
     /// Register a custom type for use with the [`Engine`], with a pretty-print name
     /// for the `type_of` function. The type must implement [`Clone`].
@@ -197,7 +211,7 @@ impl Engine {
     ///
     /// assert_eq!(
     ///     engine.eval::<String>("let x = new_ts(); type_of(x)")?,
-    ///     "rust_out::TestStruct"
+    ///     "TestStruct"
     /// );
     ///
     /// // Re-register the custom type with a name.
@@ -215,6 +229,40 @@ impl Engine {
         self.global_namespace_mut().set_custom_type::<T>(name);
         self
     }
+    /// _(metadata)_ Register a custom type for use with the [`Engine`], with a pretty-print name
+    /// for the `type_of` function and doc-comments (which may include examples) for generated
+    /// definitions and metadata. The type must implement [`Clone`].
+    /// Exported under the `metadata` feature only.
+    ///
+    /// Each string slice in `comments` is one line of doc-comment, starting with the doc-comment
+    /// leader `///` (or a single-line block doc-comment starting with `/**`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone)]
+    /// struct TestStruct;
+    ///
+    /// use rhai::Engine;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_type_with_name_and_comments::<TestStruct, _>(
+    ///     "Hello",
+    ///     ["/// A friendly greeting.", "///", "/// ```", "/// let x = hello();", "/// ```"],
+    /// );
+    /// ```
+    #[cfg(feature = "metadata")]
+    #[inline(always)]
+    pub fn register_type_with_name_and_comments<T: Variant + Clone, C: AsRef<str>>(
+        &mut self,
+        name: &str,
+        comments: impl AsRef<[C]>,
+    ) -> &mut Self {
+        self.global_namespace_mut()
+            .set_custom_type_with_comments::<T, C>(name, comments);
+        self
+    }
     /// Register a custom type for use with the [`Engine`], with a pretty-print name
     /// for the `type_of` function. The type must implement [`Clone`].
     ///
@@ -254,6 +302,106 @@ impl Engine {
         self.global_namespace_mut().set_iterable_result::<T, X>();
         self
     }
+    /// Register an iterator function for a custom type with the [`Engine`], so that it can be
+    /// iterated in `for` loops.
+    ///
+    /// Unlike [`register_iterator`][Engine::register_iterator], the type does not need to
+    /// implement [`IntoIterator`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone)]
+    /// struct Inventory {
+    ///     items: Vec<String>,
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Dynamic, Engine};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine
+    ///     .register_type::<Inventory>()
+    ///     .register_fn("new_inventory", || Inventory { items: vec!["apple".into(), "pear".into()] })
+    ///     .register_iterator_fn(|inv: Inventory| {
+    ///         Box::new(inv.items.into_iter().map(Dynamic::from))
+    ///     });
+    ///
+    /// let result = engine.eval::<String>(r#"
+    ///     let s = "";
+    ///     for item in new_inventory() { s += item; }
+    ///     s
+    /// "#)?;
+    ///
+    /// assert_eq!(result, "applepear");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn register_iterator_fn<T: Variant + Clone>(
+        &mut self,
+        func: impl Fn(T) -> Box<dyn Iterator<Item = Dynamic>> + SendSync + 'static,
+    ) -> &mut Self {
+        self.global_namespace_mut()
+            .set_iter(TypeId::of::<T>(), move |obj| func(obj.cast::<T>()));
+        self
+    }
+    /// Register an iterator function yielding index/value pairs for a custom type with the
+    /// [`Engine`], so that it can be iterated in `for` loops.
+    ///
+    /// In a `for` loop with a counter variable, i.e. `for (value, index) in ...`, the counter
+    /// variable holds the index of each pair (which can be of any type) instead of counting from
+    /// zero.
+    ///
+    /// It takes precedence over any other type iterator registered for the type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// #[derive(Clone)]
+    /// struct Scores {
+    ///     names: Vec<&'static str>,
+    ///     scores: Vec<i64>,
+    /// }
+    ///
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Dynamic, Engine};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine
+    ///     .register_type::<Scores>()
+    ///     .register_fn("new_scores", || Scores { names: vec!["a", "b"], scores: vec![40, 2] })
+    ///     .register_indexed_iterator_fn(|s: Scores| {
+    ///         Box::new(
+    ///             s.names.into_iter()
+    ///                 .zip(s.scores)
+    ///                 .map(|(name, score)| (name.into(), score.into())),
+    ///         )
+    ///     });
+    ///
+    /// let result = engine.eval::<String>(r#"
+    ///     let s = "";
+    ///     for (score, name) in new_scores() { s += `${name}=${score} `; }
+    ///     s
+    /// "#)?;
+    ///
+    /// assert_eq!(result, "a=40 b=2 ");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn register_indexed_iterator_fn<T: Variant + Clone>(
+        &mut self,
+        func: impl Fn(T) -> Box<dyn Iterator<Item = (Dynamic, Dynamic)>> + SendSync + 'static,
+    ) -> &mut Self {
+        self.indexed_iterators.insert(
+            TypeId::of::<T>(),
+            Shared::new(move |obj: Dynamic| func(obj.cast::<T>())),
+        );
+        self
+    }
     /// Register a getter function for a member of a registered type with the [`Engine`].
     ///
     /// The function signature must start with `&mut self` and not `&self`.
@@ -633,8 +781,14 @@ impl Engine {
     ///
     /// When searching for functions, modules loaded later are preferred. In other words, loaded
     /// modules are searched in reverse order.
-    #[inline(always)]
+    ///
+    /// Custom operators in the module (see [`Module::set_custom_operator`]) are registered into
+    /// the [`Engine`], unless they conflict with active keywords or symbols.
+    #[inline]
     pub fn register_global_module(&mut self, module: Shared<Module>) -> &mut Self {
+        #[cfg(not(feature = "no_custom_syntax"))]
+        self.register_module_custom_operators(&module);
+
         // Insert the module into the front.
         // The first module is always the global namespace.
         self.global_modules.insert(1, module);
@@ -645,6 +799,9 @@ impl Engine {
     /// Functions marked [`FnNamespace::Global`] and type iterators are exposed to scripts without
     /// namespace qualifications.
     ///
+    /// Custom operators in the module (see [`Module::set_custom_operator`]) are registered into
+    /// the [`Engine`], unless they conflict with active keywords or symbols.
+    ///
     /// Not available under `no_module`.
     ///
     /// # Example
@@ -715,9 +872,67 @@ impl Engine {
             }
         }
 
+        #[cfg(not(feature = "no_custom_syntax"))]
+        self.register_module_custom_operators(&module);
+
+        self.lazy_sub_modules.remove(name.as_ref());
         register_static_module_raw(&mut self.global_sub_modules, name.as_ref(), module);
         self
     }
+    /// Register all custom operators in a [`Module`] into the [`Engine`].
+    ///
+    /// Custom operators that conflict with active keywords or symbols are skipped.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    fn register_module_custom_operators(&mut self, module: &Module) {
+        for (name, precedence) in module.iter_custom_operators() {
+            let _ = self.register_custom_operator(name, precedence);
+        }
+    }
+    /// Register a static module namespace with the [`Engine`], constructing the [`Module`] lazily
+    /// via a closure on first use.
+    ///
+    /// The closure is called at most once per [`Engine`], when the module is first accessed by a
+    /// script via a namespace-qualified path. Under the `sync` feature, construction is guarded by
+    /// a lock so that it still happens only once even when the [`Engine`] is shared across threads.
+    ///
+    /// Unlike [`register_static_module`][Engine::register_static_module], `name` must be a simple
+    /// root-level name (i.e. it cannot contain `::`), and functions marked
+    /// [global][crate::FnNamespace::Global] in the module are _not_ exposed to the global namespace
+    /// (because that would require constructing the module up-front).
+    ///
+    /// Not available under `no_module`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Module};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_static_module_lazy("calc", || {
+    ///     let mut module = Module::new();
+    ///     module.set_native_fn("inc", |x: i64| Ok(x + 1));
+    ///     module
+    /// });
+    ///
+    /// assert_eq!(engine.eval::<i64>("calc::inc(41)")?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_module"))]
+    #[inline]
+    pub fn register_static_module_lazy(
+        &mut self,
+        name: impl Into<Identifier>,
+        init: impl Fn() -> Module + SendSync + 'static,
+    ) -> &mut Self {
+        let name = name.into();
+        self.global_sub_modules.remove(&name);
+        self.lazy_sub_modules
+            .insert(name, crate::module::LazyModule::new(Box::new(init)));
+        self
+    }
     /// _(metadata)_ Generate a list of all registered functions.
     /// Exported under the `metadata` feature only.
     ///
@@ -1,7 +1,8 @@
 //! Module that defines the public evaluation API of [`Engine`].
 
-use crate::eval::{Caches, GlobalRuntimeState};
+use crate::eval::GlobalRuntimeState;
 use crate::parser::ParseState;
+use crate::tokenizer::TokenStream;
 use crate::{Engine, Module, RhaiResultOf, Scope, AST};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -59,7 +60,11 @@ impl Engine {
         let (stream, tokenizer_control) =
             self.lex_raw(&scripts, self.token_mapper.as_ref().map(<_>::as_ref));
         let mut state = ParseState::new(self, scope, Default::default(), tokenizer_control);
-        let ast = self.parse(&mut stream.peekable(), &mut state, self.optimization_level)?;
+        let ast = self.parse(
+            &mut TokenStream::new(stream),
+            &mut state,
+            self.optimization_level,
+        )?;
         self.run_ast_with_scope(scope, &ast)
     }
     /// Evaluate an [`AST`].
@@ -111,7 +116,6 @@ impl Engine {
     /// ```
     #[inline]
     pub fn run_ast_with_scope(&self, scope: &mut Scope, ast: &AST) -> RhaiResultOf<()> {
-        let caches = &mut Caches::new();
         let global = &mut GlobalRuntimeState::new(self);
         global.source = ast.source_raw().clone();
 
@@ -131,9 +135,22 @@ impl Engine {
             } else {
                 &lib
             };
-            self.eval_global_statements(scope, global, caches, statements, lib, 0)?;
+            let caches = &mut self.new_caches();
+            let result = self.eval_global_statements(scope, global, caches, statements, lib, 0);
+
+            // Run any tasks still pending at the end of the evaluation
+            #[cfg(not(feature = "no_function"))]
+            let result = result.and_then(|_| self.run_pending_tasks(global, caches, lib, 0));
+
+            self.recycle_caches(caches);
+            if let Err(ref err) = result {
+                self.report_eval_end(global, Some(&**err));
+            }
+            result?;
         }
 
+        self.report_eval_end(global, None);
+
         #[cfg(feature = "debugging")]
         if self.debugger.is_some() {
             global.debugger.status = crate::eval::DebuggerStatus::Terminate;
@@ -0,0 +1,345 @@
+//! Module that defines the API to keep, snapshot and restore the state of an interpreter.
+
+use crate::eval::GlobalRuntimeState;
+use crate::types::dynamic::Variant;
+use crate::{Dynamic, Engine, RhaiResultOf, Scope, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(feature = "serde")]
+use crate::serde::PortableValue;
+#[cfg(feature = "serde")]
+use crate::Identifier;
+#[cfg(feature = "serde")]
+#[cfg(not(feature = "no_module"))]
+use crate::{Position, ERR};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::collections::BTreeMap;
+
+/// The state of an interpreter, made up of the variables in a [`Scope`] and the global runtime
+/// state (modules imported at global level and global constants), which is kept across
+/// evaluations via [`Engine::eval_with_state`] and [`Engine::eval_ast_with_state`].
+///
+/// Unlike evaluating with a [`Scope`] only, modules imported by one evaluation are available to
+/// subsequent ones.
+///
+/// Under the `serde` feature, the state can be saved into a `Snapshot` and restored later
+/// (possibly in another process) to continue evaluation.
+#[derive(Debug, Clone)]
+pub struct InterpreterState {
+    /// Variables.
+    scope: Scope<'static>,
+    /// Global runtime state.
+    global: GlobalRuntimeState<'static>,
+}
+
+impl InterpreterState {
+    /// Create a new, empty [`InterpreterState`] based on an [`Engine`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new(engine: &Engine) -> Self {
+        Self::with_scope(engine, Scope::new())
+    }
+    /// Create a new [`InterpreterState`] based on an [`Engine`], starting with the variables in a
+    /// [`Scope`].
+    #[inline(always)]
+    #[must_use]
+    pub fn with_scope(engine: &Engine, scope: Scope<'static>) -> Self {
+        Self {
+            scope,
+            global: GlobalRuntimeState::new(engine),
+        }
+    }
+    /// Get the variables of the state.
+    #[inline(always)]
+    #[must_use]
+    pub const fn scope(&self) -> &Scope<'static> {
+        &self.scope
+    }
+    /// Get a mutable reference to the variables of the state.
+    #[inline(always)]
+    #[must_use]
+    pub fn scope_mut(&mut self) -> &mut Scope<'static> {
+        &mut self.scope
+    }
+    /// Get the custom state of the interpreter, which is kept across evaluations.
+    ///
+    /// It is initialized to the [default tag][Engine::default_tag] of the [`Engine`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn tag(&self) -> &Dynamic {
+        &self.global.tag
+    }
+    /// Get a mutable reference to the custom state of the interpreter, which is kept across
+    /// evaluations.
+    #[inline(always)]
+    #[must_use]
+    pub fn tag_mut(&mut self) -> &mut Dynamic {
+        &mut self.global.tag
+    }
+    /// Get the names of the modules imported at global level, in the order they were imported.
+    ///
+    /// Not available under `no_module`.
+    #[cfg(not(feature = "no_module"))]
+    #[inline]
+    pub fn iter_imports(&self) -> impl Iterator<Item = &str> {
+        self.global
+            .scan_imports_raw()
+            .map(|(name, ..)| name.as_str())
+    }
+}
+
+/// A variable saved in a [`Snapshot`].
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotVariable {
+    /// Name of the variable.
+    name: Identifier,
+    /// Is the variable a constant?
+    #[serde(default)]
+    constant: bool,
+    /// Export aliases of the variable.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<Identifier>,
+    /// Value of the variable.
+    value: PortableValue,
+}
+
+/// _(serde)_ A saved [`InterpreterState`] that can be serialized, and restored later to continue
+/// evaluation.
+/// Exported under the `serde` feature only.
+///
+/// It records all the variables in the [`Scope`] (in order, including shadowed ones), the paths
+/// of modules imported at global level and the values of global constants.
+///
+/// Only the following types of values can be saved: `()`, `bool`, integers, floating-point
+/// numbers, decimals, characters, strings, arrays, BLOB's, object maps and function pointers
+/// (except to closures, which belong to the [`AST`] that defines them).
+///
+/// The [tag][InterpreterState::tag] and the frozen base layer of the [`Scope`] (if any) are not
+/// saved.  Script-defined functions are not part of the state, as they belong to the [`AST`]
+/// being evaluated.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Variables.
+    variables: Vec<SnapshotVariable>,
+    /// Names and paths of modules imported at global level, in order.
+    #[cfg(not(feature = "no_module"))]
+    #[serde(default)]
+    imports: Vec<(Identifier, Identifier)>,
+    /// Values of global constants.
+    #[cfg(not(feature = "no_module"))]
+    #[cfg(not(feature = "no_function"))]
+    #[serde(default)]
+    constants: BTreeMap<Identifier, PortableValue>,
+    /// Source of the last evaluation.
+    #[serde(default)]
+    source: Identifier,
+}
+
+#[cfg(feature = "serde")]
+impl InterpreterState {
+    /// Save the state into a [`Snapshot`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a variable or a global constant holds a value that cannot be saved,
+    /// or if a module imported at global level has no [ID][crate::Module::id] to resolve it again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, InterpreterState};
+    ///
+    /// let engine = Engine::new();
+    /// let mut state = InterpreterState::new(&engine);
+    ///
+    /// engine.eval_with_state::<()>(&mut state, "let x = 40; const Y = 2;")?;
+    ///
+    /// let json = serde_json::to_string(&state.snapshot(&engine)?).unwrap();
+    ///
+    /// // ... later, possibly in another process ...
+    ///
+    /// let snapshot = serde_json::from_str(&json).unwrap();
+    /// let mut state = InterpreterState::restore(&engine, snapshot)?;
+    ///
+    /// assert_eq!(engine.eval_with_state::<i64>(&mut state, "x + Y")?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn snapshot(&self, engine: &Engine) -> RhaiResultOf<Snapshot> {
+        let variables = self
+            .scope
+            .iter_raw()
+            .zip(self.scope.iter_aliases())
+            .map(|((name, constant, value), aliases)| {
+                Ok(SnapshotVariable {
+                    name: name.into(),
+                    constant,
+                    aliases: aliases.to_vec(),
+                    value: PortableValue::from_dynamic(engine, value)?,
+                })
+            })
+            .collect::<RhaiResultOf<_>>()?;
+
+        #[cfg(not(feature = "no_module"))]
+        let imports = self
+            .global
+            .scan_imports_raw()
+            .map(|(name, module)| match module.id() {
+                Some(path) => Ok((name.as_str().into(), path.into())),
+                None => Err(ERR::ErrorRuntime(
+                    format!("imported module '{}' has no ID and cannot be saved", name).into(),
+                    Position::NONE,
+                )
+                .into()),
+            })
+            .collect::<RhaiResultOf<_>>()?;
+
+        #[cfg(not(feature = "no_module"))]
+        #[cfg(not(feature = "no_function"))]
+        let constants = {
+            let mut constants = BTreeMap::new();
+            if let Some(ref values) = self.global.constants {
+                for (name, value) in crate::func::locked_read(values).iter() {
+                    let value = PortableValue::from_dynamic(engine, value)?;
+                    constants.insert(name.as_str().into(), value);
+                }
+            }
+            constants
+        };
+
+        Ok(Snapshot {
+            variables,
+            #[cfg(not(feature = "no_module"))]
+            imports,
+            #[cfg(not(feature = "no_module"))]
+            #[cfg(not(feature = "no_function"))]
+            constants,
+            source: self.global.source.clone(),
+        })
+    }
+    /// Restore an [`InterpreterState`] from a [`Snapshot`].
+    ///
+    /// Modules imported at global level are resolved again, via the
+    /// [module resolver][Engine::set_module_resolver] of the [`Engine`], in their original order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an imported module cannot be resolved, or if a value refers to a
+    /// closure.
+    pub fn restore(engine: &Engine, snapshot: Snapshot) -> RhaiResultOf<Self> {
+        let mut state = Self::new(engine);
+        let names = &BTreeMap::new();
+
+        for var in snapshot.variables {
+            let value = var.value.into_dynamic(names)?;
+
+            if var.constant {
+                state.scope.push_constant_dynamic(var.name, value);
+            } else {
+                state.scope.push_dynamic(var.name, value);
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            {
+                let index = state.scope.len() - 1;
+                for alias in var.aliases {
+                    state.scope.add_alias_by_index(index, alias);
+                }
+            }
+        }
+
+        state.global.source = snapshot.source;
+
+        #[cfg(not(feature = "no_module"))]
+        for (name, path) in snapshot.imports {
+            let module = engine.module_resolver.resolve_raw(
+                engine,
+                &mut state.global,
+                &path,
+                Position::NONE,
+            )?;
+
+            if module.is_indexed() {
+                state.global.push_import(name, module);
+            } else {
+                let mut m = crate::func::shared_take_or_clone(module);
+                m.build_index();
+                state.global.push_import(name, m);
+            }
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        #[cfg(not(feature = "no_function"))]
+        if !snapshot.constants.is_empty() {
+            let constants = snapshot
+                .constants
+                .into_iter()
+                .map(|(name, value)| Ok((name.into(), value.into_dynamic(names)?)))
+                .collect::<RhaiResultOf<_>>()?;
+            state.global.constants = Some(crate::Shared::new(crate::Locked::new(constants)));
+        }
+
+        Ok(state)
+    }
+}
+
+impl Engine {
+    /// Evaluate a string as a script with an [`InterpreterState`], returning the result value or
+    /// an error.
+    ///
+    /// Variables, modules imported at global level and global constants are kept in the
+    /// [`InterpreterState`] for subsequent evaluations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, InterpreterState};
+    ///
+    /// let engine = Engine::new();
+    /// let mut state = InterpreterState::new(&engine);
+    ///
+    /// engine.eval_with_state::<()>(&mut state, "let x = 40;")?;
+    ///
+    /// assert_eq!(engine.eval_with_state::<i64>(&mut state, "x += 2; x")?, 42);
+    /// assert_eq!(state.scope().get_value::<i64>("x").unwrap(), 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_with_state<T: Variant + Clone>(
+        &self,
+        state: &mut InterpreterState,
+        script: &str,
+    ) -> RhaiResultOf<T> {
+        let ast = self.compile_with_scope_and_optimization_level(
+            &state.scope,
+            [script],
+            self.optimization_level,
+        )?;
+        self.eval_ast_with_state(state, &ast)
+    }
+    /// Evaluate an [`AST`] with an [`InterpreterState`], returning the result value or an error.
+    ///
+    /// Variables, modules imported at global level and global constants are kept in the
+    /// [`InterpreterState`] for subsequent evaluations.
+    pub fn eval_ast_with_state<T: Variant + Clone>(
+        &self,
+        state: &mut InterpreterState,
+        ast: &AST,
+    ) -> RhaiResultOf<T> {
+        let InterpreterState { scope, global } = state;
+
+        global.start_new_run(self);
+        global.always_search_scope = false;
+
+        self.eval_ast_typed_with_global(scope, global, None, ast)?
+            .map_err(|value| self.make_output_type_error::<T>(&value))
+    }
+}
@@ -0,0 +1,40 @@
+//! Settings for [`Engine`]'s integration with [`tracing`](https://crates.io/crates/tracing).
+#![cfg(feature = "tracing")]
+
+use crate::Engine;
+use std::time::Duration;
+
+/// Default minimum duration of a call to a script-defined function to be reported as slow.
+pub(crate) const DEFAULT_SLOW_FN_THRESHOLD: Duration = Duration::from_millis(10);
+
+impl Engine {
+    /// _(tracing)_ Set the minimum duration of a call to a script-defined function for it to be
+    /// reported as slow.
+    /// Exported under the `tracing` feature only.
+    ///
+    /// Under the `tracing` feature, the [`Engine`] emits the following to
+    /// [`tracing`](https://crates.io/crates/tracing), all with the target `rhai`:
+    ///
+    /// * an `INFO` span named `eval` for each evaluation, with the `source` of the script,
+    /// * a `TRACE` span named `call` for each call to a script-defined function, with the
+    ///   `function` name and the `source`,
+    /// * an `INFO` event for each call taking at least this duration, with the `function` name,
+    ///   the `source` and `duration_us` (the duration in microseconds),
+    /// * an `INFO` span named `import` for each module resolution, with the module `path` and the
+    ///   `source`.
+    ///
+    /// The default is 10 milliseconds.  Set it to [`Duration::ZERO`] to report all calls.
+    #[inline(always)]
+    pub fn set_slow_fn_threshold(&mut self, threshold: Duration) -> &mut Self {
+        self.slow_fn_threshold = threshold;
+        self
+    }
+    /// _(tracing)_ The minimum duration of a call to a script-defined function for it to be
+    /// reported as slow.
+    /// Exported under the `tracing` feature only.
+    #[inline(always)]
+    #[must_use]
+    pub const fn slow_fn_threshold(&self) -> Duration {
+        self.slow_fn_threshold
+    }
+}
@@ -1,10 +1,11 @@
-use crate::packages::iter_basic::{BitRange, CharsStream, StepRange};
+use crate::packages::iter_basic::{BitRange, CharsStream, LinesStream, StepRange};
 use crate::{
-    Engine, ExclusiveRange, FnPtr, ImmutableString, InclusiveRange, Position, RhaiError, ERR,
+    Engine, ExclusiveRange, FnPtr, ImmutableString, InclusiveRange, Position, RhaiError, StaticVec,
+    ERR,
 };
-use std::any::type_name;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::{any::type_name, borrow::Cow};
 
 /// Map the name of a standard type into a friendly form.
 #[inline]
@@ -32,6 +33,9 @@ fn map_std_type_name(name: &str, shorthands: bool) -> &str {
     if name == type_name::<FnPtr>() || name == "FnPtr" {
         return if shorthands { "Fn" } else { "FnPtr" };
     }
+    if name == type_name::<crate::Tuple>() || name == "Tuple" {
+        return if shorthands { "tuple" } else { "Tuple" };
+    }
     #[cfg(not(feature = "no_index"))]
     if name == type_name::<crate::Array>() || name == "Array" {
         return if shorthands { "array" } else { "Array" };
@@ -40,14 +44,67 @@ fn map_std_type_name(name: &str, shorthands: bool) -> &str {
     if name == type_name::<crate::Blob>() || name == "Blob" {
         return if shorthands { "blob" } else { "Blob" };
     }
+    #[cfg(not(feature = "no_index"))]
+    if name == type_name::<crate::Set>() || name == "Set" {
+        return if shorthands { "set" } else { "Set" };
+    }
+    #[cfg(not(feature = "no_index"))]
+    if name == type_name::<crate::SortedMap>() || name == "SortedMap" {
+        return if shorthands {
+            "sorted_map"
+        } else {
+            "SortedMap"
+        };
+    }
+    #[cfg(not(feature = "no_index"))]
+    if name == type_name::<crate::IntArray>() || name == "IntArray" {
+        return if shorthands { "int_array" } else { "IntArray" };
+    }
+    #[cfg(not(feature = "no_index"))]
+    #[cfg(not(feature = "no_float"))]
+    if name == type_name::<crate::FloatArray>() || name == "FloatArray" {
+        return if shorthands {
+            "float_array"
+        } else {
+            "FloatArray"
+        };
+    }
     #[cfg(not(feature = "no_object"))]
     if name == type_name::<crate::Map>() || name == "Map" {
         return if shorthands { "map" } else { "Map" };
     }
+    #[cfg(not(feature = "no_object"))]
+    if name == type_name::<crate::OrderedMap>() || name == "OrderedMap" {
+        return if shorthands {
+            "ordered_map"
+        } else {
+            "OrderedMap"
+        };
+    }
+    #[cfg(not(feature = "no_object"))]
+    if name == type_name::<crate::Proxy>() || name == "Proxy" {
+        return if shorthands { "proxy" } else { "Proxy" };
+    }
     #[cfg(not(feature = "no_std"))]
     if name == type_name::<crate::Instant>() || name == "Instant" {
         return if shorthands { "timestamp" } else { "Instant" };
     }
+    #[cfg(not(feature = "no_function"))]
+    if name == type_name::<crate::Task>() || name == "Task" {
+        return if shorthands { "task" } else { "Task" };
+    }
+    #[cfg(not(feature = "no_function"))]
+    if name == type_name::<crate::Generator>() || name == "Generator" {
+        return if shorthands { "generator" } else { "Generator" };
+    }
+    #[cfg(not(feature = "no_custom_syntax"))]
+    if name == type_name::<crate::StateMachine>() || name == "StateMachine" {
+        return if shorthands {
+            "state_machine"
+        } else {
+            "StateMachine"
+        };
+    }
     if name == type_name::<ExclusiveRange>() || name == "ExclusiveRange" {
         return if shorthands {
             "range"
@@ -72,6 +129,9 @@ fn map_std_type_name(name: &str, shorthands: bool) -> &str {
     if name == type_name::<CharsStream>() {
         return if shorthands { "range" } else { "CharStream" };
     }
+    if name == type_name::<LinesStream>() {
+        return if shorthands { "range" } else { "LinesStream" };
+    }
 
     let step_range_name = type_name::<StepRange<u8>>();
     let step_range_name = &step_range_name[..step_range_name.len() - 3];
@@ -106,6 +166,46 @@ fn map_std_type_name(name: &str, shorthands: bool) -> &str {
         .map_or(name, |s| map_std_type_name(s, shorthands))
 }
 
+/// Strip the path from a Rust type name, e.g. `alloc::vec::Vec` becomes `Vec`.
+///
+/// Names that are not simple paths (e.g. `dyn Trait`) are returned unchanged.
+#[inline]
+#[must_use]
+fn strip_type_path(name: &str) -> &str {
+    if name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == ':')
+    {
+        name.rsplit("::").next().unwrap_or(name)
+    } else {
+        name
+    }
+}
+
+/// Split a list of type names (e.g. the generic arguments `K, V` of `BTreeMap<K, V>`) at
+/// top-level separators, skipping those nested inside other generic types, tuples or arrays.
+#[must_use]
+fn split_type_list(list: &str, separator: char) -> StaticVec<&str> {
+    let mut types = StaticVec::new_const();
+    let mut level = 0_usize;
+    let mut start = 0;
+
+    for (i, ch) in list.char_indices() {
+        match ch {
+            '<' | '(' | '[' => level += 1,
+            '>' | ')' | ']' => level = level.saturating_sub(1),
+            c if c == separator && level == 0 => {
+                types.push(list[start..i].trim());
+                start = i + c.len_utf8();
+            }
+            _ => (),
+        }
+    }
+
+    types.push(list[start..].trim());
+    types
+}
+
 /// Format a Rust type to be display-friendly.
 ///
 /// * `()` is cleared.
@@ -172,17 +272,10 @@ pub fn format_type(typ: &str, is_return_type: bool) -> std::borrow::Cow<str> {
 }
 
 impl Engine {
-    /// Pretty-print a type name.
-    ///
-    /// If a type is registered via [`register_type_with_name`][Engine::register_type_with_name],
-    /// the type name provided for the registration will be used.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the type name is `&mut`.
-    #[inline]
+    /// Get the display name of a custom type registered in this [`Engine`] (including its global
+    /// sub-modules), if any.
     #[must_use]
-    pub fn map_type_name<'a>(&'a self, name: &'a str) -> &'a str {
+    fn get_custom_type_name(&self, name: &str) -> Option<&str> {
         self.global_modules
             .iter()
             .find_map(|m| m.get_custom_type(name))
@@ -195,46 +288,276 @@ impl Engine {
                 #[cfg(feature = "no_module")]
                 return None;
             })
-            .unwrap_or_else(|| map_std_type_name(name, true))
     }
 
-    /// Format a type name.
+    /// Get the namespace-qualified display name of a custom type registered in a global
+    /// sub-module (or one of its sub-modules) of this [`Engine`], e.g. `foo::bar::MyType`.
     ///
-    /// If a type is registered via [`register_type_with_name`][Engine::register_type_with_name],
-    /// the type name provided for the registration will be used.
-    #[cfg(feature = "metadata")]
-    #[inline]
+    /// Returns [`None`] if the custom type is not registered in any global sub-module, or if it is
+    /// also registered in the global namespace.
     #[must_use]
-    pub(crate) fn format_type_name<'a>(&'a self, name: &'a str) -> std::borrow::Cow<'a, str> {
-        if let Some(x) = name.strip_prefix("&mut ") {
-            let r = self.format_type_name(x);
+    fn get_qualified_custom_type_name(&self, name: &str) -> Option<String> {
+        #[cfg(not(feature = "no_module"))]
+        {
+            fn find(namespace: &str, module: &crate::Module, name: &str) -> Option<String> {
+                module
+                    .get_custom_type(name)
+                    .map(|display_name| format!("{namespace}::{display_name}"))
+                    .or_else(|| {
+                        module
+                            .iter_sub_modules()
+                            .find_map(|(sub_name, sub_module)| {
+                                find(&format!("{namespace}::{sub_name}"), sub_module, name)
+                            })
+                    })
+            }
+
+            if self
+                .global_modules
+                .iter()
+                .any(|m| m.get_custom_type(name).is_some())
+            {
+                return None;
+            }
+
+            self.global_sub_modules
+                .iter()
+                .find_map(|(namespace, m)| find(namespace, m, name))
+        }
+        #[cfg(feature = "no_module")]
+        {
+            let _ = name;
+            None
+        }
+    }
+
+    /// Is a type name the namespace-qualified display name of a custom type registered in a global
+    /// sub-module (or one of its sub-modules) of this [`Engine`]?
+    #[must_use]
+    fn is_qualified_custom_type_name(&self, name: &str) -> bool {
+        #[cfg(not(feature = "no_module"))]
+        if let Some((path, display_name)) = name.rsplit_once("::") {
+            let mut path = path.split("::");
 
-            return if x == r {
-                name.into()
-            } else {
-                format!("&mut {r}").into()
+            let mut module = match path.next().and_then(|ns| self.global_sub_modules.get(ns)) {
+                Some(m) => &**m,
+                None => return false,
             };
+            for ns in path {
+                module = match module.get_sub_module(ns) {
+                    Some(m) => m,
+                    None => return false,
+                };
+            }
+
+            return module.find_custom_type(display_name).is_some();
         }
 
+        let _ = name;
+        false
+    }
+
+    /// Find the Rust type name of a custom type from the display name it is registered with.
+    ///
+    /// This is the reverse of [`map_type_name`][Engine::map_type_name] for custom types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// #[derive(Clone)]
+    /// struct TestStruct;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_type_with_name::<TestStruct>("Hello");
+    ///
+    /// assert_eq!(
+    ///     engine.find_type_name("Hello"),
+    ///     Some(std::any::type_name::<TestStruct>())
+    /// );
+    /// assert_eq!(engine.find_type_name("World"), None);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn find_type_name(&self, display_name: &str) -> Option<&str> {
         self.global_modules
             .iter()
-            .find_map(|m| m.get_custom_type(name))
+            .find_map(|m| m.find_custom_type(display_name))
             .or_else(|| {
                 #[cfg(not(feature = "no_module"))]
                 return self
                     .global_sub_modules
                     .iter()
-                    .find_map(|(_, m)| m.get_custom_type(name));
+                    .find_map(|(_, m)| m.find_custom_type(display_name));
                 #[cfg(feature = "no_module")]
                 return None;
             })
-            .unwrap_or_else(|| match name {
-                "INT" => type_name::<crate::INT>(),
-                #[cfg(not(feature = "no_float"))]
-                "FLOAT" => type_name::<crate::FLOAT>(),
-                _ => map_std_type_name(name, false),
-            })
-            .into()
+    }
+
+    /// Pretty-print a type name.
+    ///
+    /// If a type is registered via [`register_type_with_name`][Engine::register_type_with_name],
+    /// the type name provided for the registration will be used.
+    ///
+    /// Generic types, references, tuples and arrays are mapped part by part, and paths are
+    /// stripped from all other Rust type names, so `alloc::vec::Vec<my_crate::MyType>` becomes
+    /// `Vec<MyType>` (with `MyType` replaced by its registered name, if any).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Engine;
+    ///
+    /// #[derive(Clone)]
+    /// struct TestStruct;
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_type_with_name::<TestStruct>("Hello");
+    ///
+    /// assert_eq!(
+    ///     engine.map_type_name(std::any::type_name::<Vec<TestStruct>>()),
+    ///     "Vec<Hello>"
+    /// );
+    /// assert_eq!(
+    ///     engine.map_type_name(std::any::type_name::<Option<(String, char)>>()),
+    ///     "Option<(string, char)>"
+    /// );
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub fn map_type_name<'a>(&'a self, name: &'a str) -> Cow<'a, str> {
+        self.map_type_name_with(name, true, false)
+    }
+
+    /// Map a type name into its display name, with or without shorthands for standard types.
+    ///
+    /// If `qualified` is `true`, custom types registered in global sub-modules are qualified by
+    /// the namespace of the sub-module, e.g. `foo::bar::MyType`.
+    #[must_use]
+    fn map_type_name_with<'a>(
+        &'a self,
+        name: &'a str,
+        shorthands: bool,
+        qualified: bool,
+    ) -> Cow<'a, str> {
+        let name = name.trim();
+
+        if qualified {
+            if let Some(qualified_name) = self.get_qualified_custom_type_name(name) {
+                return qualified_name.into();
+            }
+            if self.is_qualified_custom_type_name(name) {
+                return name.into();
+            }
+        }
+        if let Some(display_name) = self.get_custom_type_name(name) {
+            return display_name.into();
+        }
+
+        let mapped = map_std_type_name(name, shorthands);
+
+        // Standard types that are not simply stripped of the `rhai::` prefix are mapped in full
+        if mapped != name && !name.ends_with(mapped) {
+            return mapped.into();
+        }
+
+        let name = mapped;
+
+        // &mut T, &T
+        if let Some(inner) = name.strip_prefix("&mut ") {
+            return format!(
+                "&mut {}",
+                self.map_type_name_with(inner, shorthands, qualified)
+            )
+            .into();
+        }
+        if let Some(inner) = name.strip_prefix('&') {
+            return format!("&{}", self.map_type_name_with(inner, shorthands, qualified)).into();
+        }
+
+        // Associated types - Name = T
+        if let Some((assoc, typ)) = name.split_once('=') {
+            if !assoc.contains('<') {
+                let typ = self.map_type_name_with(typ, shorthands, qualified);
+                return format!("{}={typ}", assoc.trim_end()).into();
+            }
+        }
+
+        // Generic types - T<A, B, ...>
+        if let (Some(index), true) = (name.find('<'), name.ends_with('>')) {
+            let base = strip_type_path(name[..index].trim());
+            let args =
+                self.map_type_list(&name[index + 1..name.len() - 1], ',', shorthands, qualified);
+            return format!("{base}<{args}>").into();
+        }
+
+        // Tuples - (A, B, ...)
+        if name.len() > 2 && name.starts_with('(') && name.ends_with(')') {
+            let types = &name[1..name.len() - 1];
+
+            return match types.strip_suffix(',') {
+                Some(typ) => format!("({},)", self.map_type_name_with(typ, shorthands, qualified)),
+                None => format!(
+                    "({})",
+                    self.map_type_list(types, ',', shorthands, qualified)
+                ),
+            }
+            .into();
+        }
+
+        // Slices and arrays - [T], [T; N]
+        if name.starts_with('[') && name.ends_with(']') {
+            let types = &name[1..name.len() - 1];
+            return format!(
+                "[{}]",
+                self.map_type_list(types, ';', shorthands, qualified)
+            )
+            .into();
+        }
+
+        strip_type_path(name).into()
+    }
+
+    /// Map a list of type names separated by `separator`.
+    #[must_use]
+    fn map_type_list(
+        &self,
+        list: &str,
+        separator: char,
+        shorthands: bool,
+        qualified: bool,
+    ) -> String {
+        split_type_list(list, separator)
+            .into_iter()
+            .map(|typ| self.map_type_name_with(typ, shorthands, qualified))
+            .collect::<StaticVec<_>>()
+            .join(&format!("{separator} "))
+    }
+
+    /// Format a type name.
+    ///
+    /// If a type is registered via [`register_type_with_name`][Engine::register_type_with_name],
+    /// the type name provided for the registration will be used.
+    ///
+    /// Other types are mapped as in [`map_type_name`][Engine::map_type_name], but without the
+    /// shorthands used by scripts (e.g. `ImmutableString` instead of `string`).
+    #[cfg(feature = "metadata")]
+    #[inline]
+    #[must_use]
+    pub(crate) fn format_type_name<'a>(&'a self, name: &'a str) -> Cow<'a, str> {
+        if let Some(x) = name.strip_prefix("&mut ") {
+            return format!("&mut {}", self.format_type_name(x)).into();
+        }
+
+        match name.trim() {
+            "INT" => type_name::<crate::INT>().into(),
+            #[cfg(not(feature = "no_float"))]
+            "FLOAT" => type_name::<crate::FLOAT>().into(),
+            name => self.map_type_name_with(name, false, true),
+        }
     }
 
     /// Make a `Box<`[`EvalAltResult<ErrorMismatchDataType>`][ERR::ErrorMismatchDataType]`>`.
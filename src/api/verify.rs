@@ -0,0 +1,371 @@
+//! Module that defines the registration sanity checker of [`Engine`].
+
+use crate::func::{
+    get_builtin_binary_op_fn, get_builtin_op_assignment_fn, get_builtin_unary_op_fn,
+};
+use crate::module::FuncInfo;
+use crate::{Dynamic, Engine, FnNamespace, Identifier, ImmutableString, Module, INT};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{any::TypeId, collections::BTreeMap, fmt};
+
+/// A potential mistake found by [`Engine::verify`] among registered functions.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum VerifyIssue {
+    /// The same function signature is registered in more than one place.
+    /// Only the version in `shadowed_by` is ever called.
+    DuplicateFunction {
+        /// Signature of the function.
+        signature: String,
+        /// Where the unreachable version is registered.
+        location: Identifier,
+        /// Where the version that is actually called is registered.
+        shadowed_by: Identifier,
+    },
+    /// A property has a setter but no getter for the same type, so compound assignments and
+    /// chained updates on it (e.g. `obj.prop += 1`) fail.
+    SetterWithoutGetter {
+        /// Name of the property.
+        property: Identifier,
+        /// Signature of the setter.
+        signature: String,
+    },
+    /// A property has a getter but no setter for a type that has a string indexer setter,
+    /// so writes to the property are silently routed to the indexer instead.
+    GetterWithoutSetter {
+        /// Name of the property.
+        property: Identifier,
+        /// Signature of the getter.
+        signature: String,
+    },
+    /// A property getter and a string indexer are both registered for the same type,
+    /// so `obj.prop` and `obj["prop"]` may return different values.
+    IndexerPropertyConflict {
+        /// Name of the property.
+        property: Identifier,
+        /// Signature of the property getter.
+        signature: String,
+    },
+    /// A function overloads an operator for types that are already handled by a built-in
+    /// operator. It is never called while fast operators mode is turned on (the default).
+    ShadowedOperator {
+        /// Signature of the function.
+        signature: String,
+        /// Where the function is registered.
+        location: Identifier,
+    },
+}
+
+impl fmt::Display for VerifyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateFunction {
+                signature,
+                location,
+                shadowed_by,
+            } => write!(
+                f,
+                "Function {signature} in {location} is shadowed by the same function in {shadowed_by}"
+            ),
+            Self::SetterWithoutGetter {
+                property,
+                signature,
+            } => write!(
+                f,
+                "Property '{property}' has a setter {signature} but no getter"
+            ),
+            Self::GetterWithoutSetter {
+                property,
+                signature,
+            } => write!(
+                f,
+                "Property '{property}' has a getter {signature} but writes go to the indexer"
+            ),
+            Self::IndexerPropertyConflict {
+                property,
+                signature,
+            } => write!(
+                f,
+                "Property '{property}' has a getter {signature} that conflicts with a string indexer"
+            ),
+            Self::ShadowedOperator {
+                signature,
+                location,
+            } => write!(
+                f,
+                "Operator function {signature} in {location} shadows a built-in operator"
+            ),
+        }
+    }
+}
+
+/// Report returned by [`Engine::verify`].
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct VerifyReport {
+    /// Potential mistakes found.
+    issues: Vec<VerifyIssue>,
+}
+
+impl VerifyReport {
+    /// Are there no potential mistakes?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+    /// Get the potential mistakes found.
+    #[inline(always)]
+    #[must_use]
+    pub fn issues(&self) -> &[VerifyIssue] {
+        &self.issues
+    }
+    /// Get an iterator over the potential mistakes found.
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = &VerifyIssue> {
+        self.issues.iter()
+    }
+}
+
+impl IntoIterator for VerifyReport {
+    type Item = VerifyIssue;
+    type IntoIter = std::vec::IntoIter<VerifyIssue>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.into_iter()
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Generate a human-readable signature of a registered function.
+#[must_use]
+fn fn_signature(func: &FuncInfo) -> String {
+    #[cfg(feature = "metadata")]
+    return func.gen_signature();
+
+    #[cfg(not(feature = "metadata"))]
+    return format!("{}({})", func.name, vec!["_"; func.num_params].join(", "));
+}
+
+/// Create a value of a standard type, used to probe for built-in operators.
+#[must_use]
+fn sample_value(type_id: TypeId) -> Option<Dynamic> {
+    if type_id == TypeId::of::<INT>() {
+        return Some(Dynamic::ZERO);
+    }
+    if type_id == TypeId::of::<bool>() {
+        return Some(Dynamic::FALSE);
+    }
+    if type_id == TypeId::of::<char>() {
+        return Some(Dynamic::from_char(' '));
+    }
+    if type_id == TypeId::of::<ImmutableString>() {
+        return Some(ImmutableString::new().into());
+    }
+    if type_id == TypeId::of::<()>() {
+        return Some(Dynamic::UNIT);
+    }
+    #[cfg(not(feature = "no_float"))]
+    if type_id == TypeId::of::<crate::FLOAT>() {
+        return Some(Dynamic::from_float(0.0));
+    }
+    #[cfg(feature = "decimal")]
+    if type_id == TypeId::of::<rust_decimal::Decimal>() {
+        return Some(Dynamic::from_decimal(rust_decimal::Decimal::ZERO));
+    }
+    #[cfg(not(feature = "no_index"))]
+    if type_id == TypeId::of::<crate::Blob>() {
+        return Some(Dynamic::from_blob(crate::Blob::new()));
+    }
+    None
+}
+
+/// Is a registered function an overload of a built-in operator?
+#[must_use]
+fn is_builtin_operator(func: &FuncInfo) -> bool {
+    let name = func.name.as_str();
+    let mut args = func.param_types.iter().map(|&t| sample_value(t));
+
+    match (func.num_params, args.next(), args.next()) {
+        (1, Some(Some(x)), None) => get_builtin_unary_op_fn(name, &x).is_some(),
+        (2, Some(Some(x)), Some(Some(y))) => {
+            get_builtin_binary_op_fn(name, &x, &y).is_some()
+                || get_builtin_op_assignment_fn(name, &x, &y).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Property accessors and indexers registered for a particular type.
+#[cfg(not(feature = "no_object"))]
+#[derive(Default)]
+struct TypeAccessors<'a> {
+    getters: BTreeMap<&'a str, &'a FuncInfo>,
+    setters: BTreeMap<&'a str, &'a FuncInfo>,
+    has_str_indexer_get: bool,
+    has_str_indexer_set: bool,
+}
+
+impl Engine {
+    /// Scan all registered functions for common registration mistakes.
+    ///
+    /// The following are reported:
+    /// * the same function signature registered in more than one place
+    ///   (except between standard packages)
+    /// * property setters without getters
+    /// * property getters without setters when a string indexer setter would take the writes
+    /// * property getters conflicting with string indexers
+    /// * operator overloads shadowed by built-in operators
+    ///
+    /// Functions are searched in the global namespace, then in registered global modules,
+    /// then in registered static modules (functions exposed to the global namespace only).
+    /// Except for duplicates, functions in standard packages are not checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Engine, VerifyIssue, INT};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// assert!(engine.verify().is_ok());
+    ///
+    /// engine.register_fn("+", |x: INT, y: INT| x * y);
+    ///
+    /// let report = engine.verify();
+    ///
+    /// assert!(matches!(report.issues(), [VerifyIssue::ShadowedOperator { .. }]));
+    /// ```
+    #[must_use]
+    pub fn verify(&self) -> VerifyReport {
+        // (location, is standard?, only global functions exposed?, module)
+        let mut sources: Vec<(Identifier, bool, bool, &Module)> = Vec::new();
+
+        sources.push((
+            "global namespace".into(),
+            false,
+            false,
+            self.global_namespace(),
+        ));
+
+        for m in self.global_modules.iter().skip(1).filter(|m| !m.internal) {
+            let location = match m.id() {
+                Some(id) => id.into(),
+                None if m.standard => "standard library".into(),
+                None => "global module".into(),
+            };
+            sources.push((location, m.standard, false, &**m));
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        for (name, m) in &self.global_sub_modules {
+            sources.push((name.clone(), m.standard, true, &**m));
+        }
+
+        let mut issues = Vec::new();
+        let mut seen = BTreeMap::<(&str, &[TypeId]), (&Identifier, bool)>::new();
+        #[cfg(not(feature = "no_object"))]
+        let mut accessors = BTreeMap::<TypeId, TypeAccessors>::new();
+
+        for (location, standard, global_only, module) in &sources {
+            for func in module.iter_fn() {
+                if *global_only && func.namespace != FnNamespace::Global {
+                    continue;
+                }
+
+                let key = (func.name.as_str(), &func.param_types[..]);
+
+                match seen.get(&key) {
+                    Some((_, true)) if *standard => (),
+                    Some(&(shadowed_by, ..)) => issues.push(VerifyIssue::DuplicateFunction {
+                        signature: fn_signature(func),
+                        location: location.clone(),
+                        shadowed_by: shadowed_by.clone(),
+                    }),
+                    None => {
+                        seen.insert(key, (location, *standard));
+                    }
+                }
+
+                if *standard {
+                    continue;
+                }
+
+                if is_builtin_operator(func) {
+                    issues.push(VerifyIssue::ShadowedOperator {
+                        signature: fn_signature(func),
+                        location: location.clone(),
+                    });
+                    continue;
+                }
+
+                #[cfg(not(feature = "no_object"))]
+                if let Some(&type_id) = func.param_types.first() {
+                    let name = func.name.as_str();
+
+                    if let Some(prop) = name.strip_prefix(crate::engine::FN_GET) {
+                        if func.num_params == 1 {
+                            let entry = accessors.entry(type_id).or_default();
+                            entry.getters.entry(prop).or_insert(func);
+                        }
+                    } else if let Some(prop) = name.strip_prefix(crate::engine::FN_SET) {
+                        if func.num_params == 2 {
+                            let entry = accessors.entry(type_id).or_default();
+                            entry.setters.entry(prop).or_insert(func);
+                        }
+                    }
+
+                    #[cfg(not(feature = "no_index"))]
+                    if func.param_types.get(1) == Some(&TypeId::of::<ImmutableString>()) {
+                        match (name, func.num_params) {
+                            (crate::engine::FN_IDX_GET, 2) => {
+                                accessors.entry(type_id).or_default().has_str_indexer_get = true;
+                            }
+                            (crate::engine::FN_IDX_SET, 3) => {
+                                accessors.entry(type_id).or_default().has_str_indexer_set = true;
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(not(feature = "no_object"))]
+        for entry in accessors.values() {
+            for (&prop, &func) in &entry.setters {
+                if !entry.getters.contains_key(prop) {
+                    issues.push(VerifyIssue::SetterWithoutGetter {
+                        property: prop.into(),
+                        signature: fn_signature(func),
+                    });
+                }
+            }
+            for (&prop, &func) in &entry.getters {
+                if entry.has_str_indexer_get {
+                    issues.push(VerifyIssue::IndexerPropertyConflict {
+                        property: prop.into(),
+                        signature: fn_signature(func),
+                    });
+                }
+                if entry.has_str_indexer_set && !entry.setters.contains_key(prop) {
+                    issues.push(VerifyIssue::GetterWithoutSetter {
+                        property: prop.into(),
+                        signature: fn_signature(func),
+                    });
+                }
+            }
+        }
+
+        VerifyReport { issues }
+    }
+}
@@ -79,7 +79,7 @@ impl AST {
             doc: crate::SmartString::new_const(),
             body: StmtBlock::new(statements, Position::NONE, Position::NONE),
             #[cfg(not(feature = "no_function"))]
-            lib: functions.into(),
+            lib: Self::with_fn_patches(functions.into()),
             #[cfg(not(feature = "no_module"))]
             resolver: None,
         }
@@ -99,7 +99,7 @@ impl AST {
             doc: crate::SmartString::new_const(),
             body: StmtBlock::new(statements, Position::NONE, Position::NONE),
             #[cfg(not(feature = "no_function"))]
-            lib: functions.into(),
+            lib: Self::with_fn_patches(functions.into()),
             #[cfg(not(feature = "no_module"))]
             resolver: None,
         }
@@ -139,6 +139,16 @@ impl AST {
         ast.set_source(source);
         ast
     }
+    /// Make sure that script-defined functions can be hot-patched, so that all clones of the
+    /// [`AST`] share the replacements made via [`replace_fn`][AST::replace_fn].
+    #[cfg(not(feature = "no_function"))]
+    #[must_use]
+    fn with_fn_patches(mut lib: crate::Shared<crate::Module>) -> crate::Shared<crate::Module> {
+        if lib.fn_patches.is_none() && lib.iter_script_fn().next().is_some() {
+            crate::func::shared_make_mut(&mut lib).fn_patches = Some(Default::default());
+        }
+        lib
+    }
     /// Create an empty [`AST`].
     #[inline]
     #[must_use]
@@ -308,6 +318,60 @@ impl AST {
         self.resolver = Some(resolver.into());
         self
     }
+    /// Embed a [module][crate::Module] into the [`AST`] under a path, replacing any existing one.
+    ///
+    /// `import` statements with that path then resolve to the new [module][crate::Module] when the
+    /// [`AST`] is evaluated, without the need to recompile the [`AST`].
+    /// Embedded [modules][crate::Module] take precedence over the
+    /// [`Engine`][crate::Engine]'s [module resolver][crate::ModuleResolver].
+    ///
+    /// Returns the [module][crate::Module] previously embedded under the path, if any.
+    ///
+    /// Not available under `no_module`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Module, INT};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut ast = engine.compile(r#"import "config" as config; config::VERSION"#)?;
+    ///
+    /// let mut module = Module::new();
+    /// module.set_var("VERSION", 1 as INT);
+    /// ast.patch_module("config", module);
+    ///
+    /// assert_eq!(engine.eval_ast::<INT>(&ast)?, 1);
+    ///
+    /// let mut module = Module::new();
+    /// module.set_var("VERSION", 2 as INT);
+    /// ast.patch_module("config", module);
+    ///
+    /// assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_module"))]
+    pub fn patch_module(
+        &mut self,
+        path: impl Into<Identifier>,
+        module: crate::Module,
+    ) -> Option<crate::Shared<crate::Module>> {
+        let path = path.into();
+
+        let mut resolver = self.resolver.take().map_or_else(
+            crate::module::resolvers::StaticModuleResolver::new,
+            crate::func::shared_take_or_clone,
+        );
+
+        let old_module = resolver.remove(&path);
+        resolver.insert(path, module);
+        self.resolver = Some(resolver.into());
+
+        old_module
+    }
     /// Clone the [`AST`]'s functions into a new [`AST`].
     /// No statements are cloned.
     ///
@@ -739,6 +803,234 @@ impl AST {
         }
         self
     }
+    /// Remove all script-defined functions that cannot be reached from the statements at global
+    /// level or from any of the specified entry points (functions called via
+    /// [`Engine::call_fn`][crate::Engine::call_fn]).
+    ///
+    /// Not available under `no_function`.
+    ///
+    /// Functions are matched by name only, so all overloads of a reachable function are kept.
+    ///
+    /// A function is considered reachable if it is called by name, referenced by a function
+    /// pointer, or named in any string literal (e.g. `Fn("foo")` or `call("foo")`).
+    /// Function names constructed at runtime cannot be detected, and functions called this way
+    /// must be listed as entry points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut ast = engine.compile(r#"
+    ///     fn main(n) { helper(n) }
+    ///     fn helper(n) { n + 1 }
+    ///     fn unused() { print("hello"); }
+    /// "#)?;
+    ///
+    /// ast.retain_functions_used_by(["main"]);
+    ///
+    /// let names: Vec<_> = ast.iter_functions().map(|f| f.name).collect();
+    ///
+    /// assert_eq!(names.len(), 2);
+    /// assert!(names.contains(&"main"));
+    /// assert!(names.contains(&"helper"));
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    pub fn retain_functions_used_by<S: AsRef<str>>(
+        &mut self,
+        entry_points: impl IntoIterator<Item = S>,
+    ) -> &mut Self {
+        if self.lib.is_empty() {
+            return self;
+        }
+
+        let mut pending: Vec<Identifier> = entry_points
+            .into_iter()
+            .map(|s| s.as_ref().into())
+            .collect();
+        collect_fn_refs(self.statements(), &mut pending);
+
+        let mut used = std::collections::BTreeSet::new();
+
+        while let Some(name) = pending.pop() {
+            if used.contains(&name) {
+                continue;
+            }
+
+            for (.., fn_def) in self.lib.iter_script_fn() {
+                if fn_def.name.as_str() == name.as_str() {
+                    collect_fn_refs(fn_def.body.statements(), &mut pending);
+                }
+            }
+
+            used.insert(name);
+        }
+
+        self.retain_functions(|_, _, name, _| used.contains(name))
+    }
+    /// Replace a script-defined function in this [`AST`] with the function of the same name and
+    /// number of parameters defined in another [`AST`].
+    ///
+    /// Returns `false` (and nothing is replaced) if either [`AST`] does not contain the function.
+    ///
+    /// Not available under `no_function`.
+    ///
+    /// This is useful for hot-patching a single function in a long-running [`AST`] without
+    /// recompiling the entire script.
+    ///
+    /// The replacement is shared by all clones of this [`AST`] (e.g. those handed to worker
+    /// threads or kept in an [`EvalSession`][crate::EvalSession]). Each replacement bumps an epoch
+    /// counter, which engines check whenever they call a script-defined function, so evaluations
+    /// currently running on any clone pick up the new version at the next call, even if the old
+    /// version is in their function resolution caches. Calls already in progress finish with the
+    /// old version.
+    ///
+    /// Clones made before the [`AST`] contained any script-defined function (e.g. via
+    /// [`merge`][AST::merge]) do not share the replacements.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut ast = engine.compile("
+    ///     fn foo(x) { x + 1 }
+    ///     fn bar() { 0 }
+    ///     foo(41)
+    /// ")?;
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+    ///
+    /// let patch = engine.compile("fn foo(x) { x * 2 }")?;
+    ///
+    /// // Hot-patch 'foo(..)'
+    /// assert!(ast.replace_fn("foo", 1, &patch));
+    ///
+    /// assert_eq!(engine.eval_ast::<i64>(&ast)?, 82);
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    pub fn replace_fn(
+        &mut self,
+        name: impl AsRef<str>,
+        num_params: usize,
+        new_fn_ast: &Self,
+    ) -> bool {
+        let name = name.as_ref();
+
+        let orig = match self.lib.get_script_fn(name, num_params) {
+            Some(f) => f.clone(),
+            None => return false,
+        };
+
+        let mut fn_def = match new_fn_ast.lib.get_script_fn(name, num_params) {
+            Some(f) => f.clone(),
+            None => return false,
+        };
+
+        // Keep the visibility of the function being replaced
+        if fn_def.visibility != orig.visibility {
+            crate::func::shared_make_mut(&mut fn_def).visibility = orig.visibility;
+        }
+
+        let lib = crate::func::shared_make_mut(&mut self.lib);
+        lib.set_script_fn(fn_def.clone());
+
+        // Make evaluations running on clones of this AST pick up the replacement
+        lib.fn_patches
+            .get_or_insert_with(Default::default)
+            .replace(&orig, fn_def);
+
+        true
+    }
+    /// Set who may call a script-defined function in this [`AST`], based on its name and number
+    /// of parameters.
+    ///
+    /// Returns `false` if the [`AST`] does not contain the function.
+    ///
+    /// Not available under `no_function`.
+    ///
+    /// A [host-only][super::FnVisibility::HostOnly] function can be called via
+    /// [`Engine::call_fn`][crate::Engine::call_fn] but is invisible to scripts, making it suitable
+    /// for internal entry points.  A [script-only][super::FnVisibility::ScriptOnly] function cannot
+    /// be called via [`Engine::call_fn`][crate::Engine::call_fn].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, FnVisibility, Scope};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let mut ast = engine.compile("fn on_init() { 42 }")?;
+    ///
+    /// assert!(ast.set_fn_visibility("on_init", 0, FnVisibility::HostOnly));
+    ///
+    /// // The host can call 'on_init'...
+    /// let result = engine.call_fn::<i64>(&mut Scope::new(), &ast, "on_init", ())?;
+    /// assert_eq!(result, 42);
+    ///
+    /// // ... but scripts cannot
+    /// let ast = ast.merge(&engine.compile("on_init()")?);
+    /// assert!(engine.eval_ast::<i64>(&ast).is_err());
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_function"))]
+    pub fn set_fn_visibility(
+        &mut self,
+        name: impl AsRef<str>,
+        num_params: usize,
+        visibility: super::FnVisibility,
+    ) -> bool {
+        let mut fn_def = match self.lib.get_script_fn(name, num_params) {
+            Some(f) if f.visibility == visibility => return true,
+            Some(f) => f.clone(),
+            None => return false,
+        };
+
+        crate::func::shared_make_mut(&mut fn_def).visibility = visibility;
+        crate::func::shared_make_mut(&mut self.lib).set_script_fn(fn_def);
+
+        true
+    }
+    /// Get who may call a script-defined function in this [`AST`], based on its name and number
+    /// of parameters.
+    ///
+    /// Returns [`None`] if the [`AST`] does not contain the function.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    #[must_use]
+    pub fn fn_visibility(
+        &self,
+        name: impl AsRef<str>,
+        num_params: usize,
+    ) -> Option<super::FnVisibility> {
+        self.lib
+            .get_script_fn(name, num_params)
+            .map(|f| f.visibility)
+    }
     /// _(internals)_ Iterate through all function definitions.
     /// Exported under the `internals` feature only.
     ///
@@ -867,7 +1159,6 @@ impl AST {
     /// Recursively walk the [`AST`], including function bodies (if any).
     /// Return `false` from the callback to terminate the walk.
     #[cfg(not(feature = "internals"))]
-    #[cfg(not(feature = "no_module"))]
     #[inline(always)]
     pub(crate) fn walk(&self, on_node: &mut impl FnMut(&[ASTNode]) -> bool) -> bool {
         self._walk(on_node)
@@ -901,6 +1192,47 @@ impl AST {
     }
 }
 
+/// Collect the names of all functions possibly referred to in a list of statements.
+#[cfg(not(feature = "no_function"))]
+fn collect_fn_refs(statements: &[Stmt], refs: &mut Vec<Identifier>) {
+    let path = &mut Vec::new();
+
+    for stmt in statements {
+        stmt.walk(path, &mut |path: &[ASTNode]| {
+            match path.last() {
+                Some(ASTNode::Stmt(Stmt::FnCall(x, ..)))
+                | Some(ASTNode::Expr(Expr::FnCall(x, ..)))
+                | Some(ASTNode::Expr(Expr::MethodCall(x, ..))) => refs.push(x.name.as_str().into()),
+                Some(ASTNode::Expr(Expr::StringConstant(s, ..))) => refs.push(s.as_str().into()),
+                Some(ASTNode::Expr(Expr::DynamicConstant(v, ..))) => collect_value_fn_refs(v, refs),
+                _ => (),
+            }
+            true
+        });
+    }
+}
+
+/// Collect the names of all functions referred to by function pointers in a constant value.
+#[cfg(not(feature = "no_function"))]
+fn collect_value_fn_refs(value: &Dynamic, refs: &mut Vec<Identifier>) {
+    use crate::types::dynamic::Union;
+
+    match value.0 {
+        Union::Str(ref s, ..) => refs.push(s.as_str().into()),
+        Union::FnPtr(ref f, ..) => {
+            refs.push(f.fn_name().into());
+            f.curry()
+                .iter()
+                .for_each(|v| collect_value_fn_refs(v, refs));
+        }
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(ref a, ..) => a.iter().for_each(|v| collect_value_fn_refs(v, refs)),
+        #[cfg(not(feature = "no_object"))]
+        Union::Map(ref m, ..) => m.values().for_each(|v| collect_value_fn_refs(v, refs)),
+        _ => (),
+    }
+}
+
 impl<A: AsRef<AST>> Add<A> for &AST {
     type Output = AST;
 
@@ -375,6 +375,8 @@ pub enum Expr {
     InterpolatedString(Box<StaticVec<Expr>>, Position),
     /// [ expr, ... ]
     Array(Box<StaticVec<Expr>>, Position),
+    /// ( expr, ... )
+    Tuple(Box<StaticVec<Expr>>, Position),
     /// #{ name:expr, ... }
     Map(
         Box<(StaticVec<(Ident, Expr)>, BTreeMap<Identifier, Dynamic>)>,
@@ -394,12 +396,20 @@ pub enum Expr {
         Option<NonZeroU8>,
         Position,
     ),
-    /// Property access - ((getter, hash), (setter, hash), prop)
+    /// Property access - ((getter, hash), (setter, hash), prop, id)
+    ///
+    /// All property accesses of the same name within an [`AST`][crate::AST] share the same
+    /// data, which saves one allocation per access.
+    ///
+    /// `id` numbers the properties of an [`AST`][crate::AST] in order of first appearance.
+    /// Evaluation uses it to index a cache of the getters and setters found missing for a type,
+    /// so that such properties go straight to the indexer.
     Property(
-        Box<(
+        crate::Shared<(
             (ImmutableString, u64),
             (ImmutableString, u64),
             ImmutableString,
+            usize,
         )>,
         Position,
     ),
@@ -463,6 +473,10 @@ impl fmt::Debug for Expr {
                 f.write_str("Array")?;
                 f.debug_list().entries(x.iter()).finish()
             }
+            Self::Tuple(x, ..) => {
+                f.write_str("Tuple")?;
+                f.debug_list().entries(x.iter()).finish()
+            }
             Self::Map(x, ..) => {
                 f.write_str("Map")?;
                 f.debug_map()
@@ -572,6 +586,10 @@ impl Expr {
                 Dynamic::from_array(arr)
             }
 
+            Self::Tuple(x, ..) if self.is_constant() => Dynamic::from(crate::Tuple::new(
+                x.iter().map(|v| v.get_literal_value().unwrap()),
+            )),
+
             #[cfg(not(feature = "no_object"))]
             Self::Map(x, ..) if self.is_constant() => {
                 Dynamic::from_map(x.0.iter().fold(x.1.clone(), |mut map, (k, v)| {
@@ -718,6 +736,7 @@ impl Expr {
             | Self::Unit(pos)
             | Self::StringConstant(.., pos)
             | Self::Array(.., pos)
+            | Self::Tuple(.., pos)
             | Self::Map(.., pos)
             | Self::Variable(.., pos)
             | Self::And(.., pos)
@@ -776,6 +795,7 @@ impl Expr {
             | Self::Unit(pos)
             | Self::StringConstant(.., pos)
             | Self::Array(.., pos)
+            | Self::Tuple(.., pos)
             | Self::Map(.., pos)
             | Self::And(.., pos)
             | Self::Or(.., pos)
@@ -803,7 +823,9 @@ impl Expr {
     #[must_use]
     pub fn is_pure(&self) -> bool {
         match self {
-            Self::InterpolatedString(x, ..) | Self::Array(x, ..) => x.iter().all(Self::is_pure),
+            Self::InterpolatedString(x, ..) | Self::Array(x, ..) | Self::Tuple(x, ..) => {
+                x.iter().all(Self::is_pure)
+            }
 
             Self::Map(x, ..) => x.0.iter().map(|(.., v)| v).all(Self::is_pure),
 
@@ -839,7 +861,9 @@ impl Expr {
             | Self::StringConstant(..)
             | Self::Unit(..) => true,
 
-            Self::InterpolatedString(x, ..) | Self::Array(x, ..) => x.iter().all(Self::is_constant),
+            Self::InterpolatedString(x, ..) | Self::Array(x, ..) | Self::Tuple(x, ..) => {
+                x.iter().all(Self::is_constant)
+            }
 
             Self::Map(x, ..) => x.0.iter().map(|(.., expr)| expr).all(Self::is_constant),
 
@@ -879,6 +903,7 @@ impl Expr {
             | Self::Dot(..)
             | Self::Index(..)
             | Self::Array(..)
+            | Self::Tuple(..)
             | Self::Map(..) => false,
 
             #[cfg(not(feature = "no_custom_syntax"))]
@@ -920,7 +945,7 @@ impl Expr {
                     }
                 }
             }
-            Self::InterpolatedString(x, ..) | Self::Array(x, ..) => {
+            Self::InterpolatedString(x, ..) | Self::Array(x, ..) | Self::Tuple(x, ..) => {
                 for e in &**x {
                     if !e.walk(path, on_node) {
                         return false;
@@ -946,7 +971,7 @@ impl Expr {
                     return false;
                 }
             }
-            Self::FnCall(x, ..) => {
+            Self::FnCall(x, ..) | Self::MethodCall(x, ..) => {
                 for e in &x.args {
                     if !e.walk(path, on_node) {
                         return false;
@@ -37,6 +37,52 @@ impl FnAccess {
     }
 }
 
+/// A type representing who may call a script-defined function.
+///
+/// Not available under `no_function`.
+#[cfg(not(feature = "no_function"))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum FnVisibility {
+    /// Callable from both scripts and the host (the default).
+    All,
+    /// Callable only from the host (e.g. via [`Engine::call_fn`][crate::Engine::call_fn]), but not
+    /// from scripts.
+    HostOnly,
+    /// Callable only from scripts, but not from the host.
+    ScriptOnly,
+}
+
+#[cfg(not(feature = "no_function"))]
+impl Default for FnVisibility {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::All
+    }
+}
+
+#[cfg(not(feature = "no_function"))]
+impl FnVisibility {
+    /// Can this function be called from scripts?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_script_callable(self) -> bool {
+        match self {
+            Self::All | Self::ScriptOnly => true,
+            Self::HostOnly => false,
+        }
+    }
+    /// Can this function be called from the host?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_host_callable(self) -> bool {
+        match self {
+            Self::All | Self::HostOnly => true,
+            Self::ScriptOnly => false,
+        }
+    }
+}
+
 bitflags! {
     /// _(internals)_ Bit-flags containing [`AST`][crate::AST] node configuration options.
     /// Exported under the `internals` feature only.
@@ -5,6 +5,7 @@ pub mod expr;
 pub mod flags;
 pub mod ident;
 pub mod namespace;
+pub mod node_id;
 pub mod script_fn;
 pub mod stmt;
 
@@ -12,18 +13,23 @@ pub use ast::{ASTNode, AST};
 #[cfg(not(feature = "no_custom_syntax"))]
 pub use expr::CustomExpr;
 pub use expr::{BinaryExpr, Expr, FnCallExpr, FnCallHashes};
+#[cfg(not(feature = "no_function"))]
+pub use flags::FnVisibility;
 pub use flags::{ASTFlags, FnAccess};
 pub use ident::Ident;
 #[cfg(not(feature = "no_module"))]
 pub use namespace::Namespace;
+pub use node_id::NodeId;
 #[cfg(not(feature = "no_module"))]
 #[cfg(not(feature = "no_function"))]
 pub use script_fn::EncapsulatedEnviron;
 #[cfg(not(feature = "no_function"))]
 pub use script_fn::{ScriptFnDef, ScriptFnMetadata};
+#[cfg(not(feature = "no_function"))]
+pub(crate) use script_fn::FnPatches;
 pub use stmt::{
-    CaseBlocksList, ConditionalExpr, OpAssignment, RangeCase, Stmt, StmtBlock, StmtBlockContainer,
-    SwitchCasesCollection, TryCatchBlock,
+    CaseBlocksList, ConditionalExpr, OpAssignment, PatternCase, RangeCase, Stmt, StmtBlock,
+    StmtBlockContainer, SwitchCasesCollection, SwitchPattern, TryCatchBlock,
 };
 
 #[cfg(not(feature = "no_float"))]
@@ -0,0 +1,265 @@
+//! Module defining stable identifiers of AST nodes.
+
+use super::{ASTNode, Expr, Stmt, AST};
+use crate::Position;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+};
+
+/// A stable identifier of a statement or expression in an [`AST`].
+///
+/// Node ID's are derived from the structure of the [`AST`] instead of from positions: each node
+/// is identified by the function that contains it (or the global level), and the path to it from
+/// there, made up of the kind of each node, its name (for variables, properties and function
+/// calls) and its occurrence among the siblings of the same kind and name.
+///
+/// Therefore, a node keeps the same ID after the script is edited and parsed again, as long as
+/// the edit does not change the path to the node, no matter how positions shift.  For example,
+/// adding a function, or adding or removing a line that does not precede a statement of the
+/// same kind in the same block, does not affect other nodes.  This allows external tools
+/// (e.g. coverage, profilers or visual editors) to attach annotations to nodes and map them
+/// back to the new [`AST`].
+///
+/// Node ID's are only stable for the same version of Rhai and the same
+/// [optimization level][crate::OptimizationLevel], as the optimizer may change the [`AST`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct NodeId(u64);
+
+impl NodeId {
+    /// Create a [`NodeId`] from its raw value, e.g. one that is saved earlier.
+    #[inline(always)]
+    #[must_use]
+    pub const fn from_u64(value: u64) -> Self {
+        Self(value)
+    }
+    /// Get the raw value of this [`NodeId`], e.g. to save it.
+    #[inline(always)]
+    #[must_use]
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:016x}", self.0)
+    }
+}
+
+/// A hasher using FNV-1a, which is stable across runs.
+struct StableHasher(u64);
+
+impl StableHasher {
+    /// Create a new [`StableHasher`].
+    #[inline(always)]
+    #[must_use]
+    const fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    #[inline(always)]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Calculate the key of a node among its siblings, from its kind and name.
+#[must_use]
+fn node_key(node: &ASTNode) -> u64 {
+    let hasher = &mut StableHasher::new();
+
+    match node {
+        ASTNode::Stmt(stmt) => {
+            0_u8.hash(hasher);
+            mem::discriminant(*stmt).hash(hasher);
+
+            match stmt {
+                Stmt::Var(x, ..) => x.0.name.as_str().hash(hasher),
+                Stmt::FnCall(x, ..) => x.name.as_str().hash(hasher),
+                _ => (),
+            }
+        }
+        ASTNode::Expr(expr) => {
+            1_u8.hash(hasher);
+            mem::discriminant(*expr).hash(hasher);
+
+            match expr {
+                Expr::Variable(x, ..) => x.3.as_str().hash(hasher),
+                Expr::Property(x, ..) => x.2.as_str().hash(hasher),
+                Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) => x.name.as_str().hash(hasher),
+                _ => (),
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+/// Generator of [`NodeId`]'s during a walk of an [`AST`].
+struct NodeIdGenerator {
+    /// ID's of the nodes on the current path (starting with the root), with the number of
+    /// children seen so far for each key.
+    levels: Vec<(u64, Vec<(u64, usize)>)>,
+}
+
+impl NodeIdGenerator {
+    /// Create a [`NodeIdGenerator`] for the nodes under a root, identified by a name and a number
+    /// (e.g. a function name and its number of parameters).
+    #[must_use]
+    fn new(root: Option<(&str, usize)>) -> Self {
+        let hasher = &mut StableHasher::new();
+        root.hash(hasher);
+
+        Self {
+            levels: vec![(hasher.finish(), Vec::new())],
+        }
+    }
+    /// Get the [`NodeId`] of the last node on a walk path.
+    fn next(&mut self, path: &[ASTNode]) -> NodeId {
+        let node = path.last().expect("path is not empty");
+        let key = node_key(node);
+
+        // Nodes are walked in pre-order, so the parent is the last node on the path
+        self.levels.truncate(path.len());
+        let (parent, children) = self.levels.last_mut().expect("root level");
+
+        let index = match children.iter_mut().find(|(k, ..)| *k == key) {
+            Some((.., n)) => {
+                *n += 1;
+                *n
+            }
+            None => {
+                children.push((key, 0));
+                0
+            }
+        };
+
+        let hasher = &mut StableHasher::new();
+        parent.hash(hasher);
+        key.hash(hasher);
+        index.hash(hasher);
+        let id = hasher.finish();
+
+        self.levels.push((id, Vec::new()));
+
+        NodeId(id)
+    }
+}
+
+impl AST {
+    /// Recursively walk the [`AST`], including function bodies (if any), with the [`NodeId`] of
+    /// each node.
+    /// Return `false` from the callback to terminate the walk.
+    fn _walk_with_ids(&self, on_node: &mut impl FnMut(&[ASTNode], NodeId) -> bool) -> bool {
+        let path = &mut Vec::new();
+
+        let ids = &mut NodeIdGenerator::new(None);
+
+        for stmt in self.statements() {
+            if !stmt.walk(path, &mut |path| on_node(path, ids.next(path))) {
+                return false;
+            }
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        for fn_def in self.iter_fn_def() {
+            let ids = &mut NodeIdGenerator::new(Some((fn_def.name.as_str(), fn_def.params.len())));
+
+            for stmt in fn_def.body.iter() {
+                if !stmt.walk(path, &mut |path| on_node(path, ids.next(path))) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+    /// _(internals)_ Recursively walk the [`AST`], including function bodies (if any), with the
+    /// [`NodeId`] of each node.
+    /// Return `false` from the callback to terminate the walk.
+    /// Exported under the `internals` feature only.
+    #[cfg(feature = "internals")]
+    #[inline(always)]
+    pub fn walk_with_ids(&self, on_node: &mut impl FnMut(&[ASTNode], NodeId) -> bool) -> bool {
+        self._walk_with_ids(on_node)
+    }
+    /// Get the [`NodeId`]'s and [positions][Position] of all statements and expressions in the
+    /// [`AST`], including function bodies (if any), in the order they are walked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("let x = 40;\nprint(x + 2);")?;
+    /// let (id, ..) = ast.node_ids()[0];
+    ///
+    /// // Insert lines before the statement
+    /// let ast = engine.compile("// the answer\n\nlet x = 40;\nprint(x + 2);")?;
+    ///
+    /// assert_eq!(ast.node_position(id).unwrap().line(), Some(3));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn node_ids(&self) -> Vec<(NodeId, Position)> {
+        let mut ids = Vec::new();
+
+        self._walk_with_ids(&mut |path, id| {
+            ids.push((id, path.last().expect("path is not empty").position()));
+            true
+        });
+
+        ids
+    }
+    /// Get the [`NodeId`] of the outermost statement or expression at a [position][Position] in
+    /// the [`AST`], if any.
+    #[must_use]
+    pub fn node_id_at(&self, pos: Position) -> Option<NodeId> {
+        let mut found = None;
+
+        self._walk_with_ids(&mut |path, id| {
+            if path.last().expect("path is not empty").position() == pos {
+                found = Some(id);
+                false
+            } else {
+                true
+            }
+        });
+
+        found
+    }
+    /// Get the [position][Position] of a statement or expression in the [`AST`] by its
+    /// [`NodeId`], if it exists.
+    #[must_use]
+    pub fn node_position(&self, id: NodeId) -> Option<Position> {
+        let mut found = None;
+
+        self._walk_with_ids(&mut |path, node_id| {
+            if node_id == id {
+                found = Some(path.last().expect("path is not empty").position());
+                false
+            } else {
+                true
+            }
+        });
+
+        found
+    }
+}
@@ -1,10 +1,13 @@
 //! Module defining script-defined functions.
 #![cfg(not(feature = "no_function"))]
 
-use super::{FnAccess, StmtBlock};
-use crate::{ImmutableString, StaticVec};
+use super::{FnAccess, FnVisibility, StmtBlock};
+use crate::func::{locked_read, locked_write};
+use crate::{ImmutableString, Locked, Shared, StaticVec};
+use std::collections::BTreeMap;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{fmt, hash::Hash};
 
 /// _(internals)_ Encapsulated AST environment.
@@ -41,8 +44,15 @@ pub struct ScriptFnDef {
     pub name: ImmutableString,
     /// Function access mode.
     pub access: FnAccess,
+    /// Who may call the function.
+    pub visibility: FnVisibility,
     /// Names of function parameters.
     pub params: StaticVec<ImmutableString>,
+    /// Does the function body contain `yield`?
+    ///
+    /// Calling a generator function returns a [`Generator`][crate::Generator] instead of
+    /// evaluating the body.
+    pub is_generator: bool,
     /// _(metadata)_ Function doc-comments (if any).
     /// Exported under the `metadata` feature only.
     ///
@@ -59,6 +69,52 @@ pub struct ScriptFnDef {
     pub comments: Box<[Box<str>]>,
 }
 
+/// Script-defined functions hot-patched via [`AST::replace_fn`][crate::AST::replace_fn], shared by
+/// all clones of an [`AST`][crate::AST] so that evaluations running on any of them call the
+/// replacements.
+#[derive(Debug, Default)]
+pub(crate) struct FnPatches {
+    /// Epoch of the patches, bumped whenever a function is replaced.
+    epoch: AtomicUsize,
+    /// Replacement of each function, keyed by the address of the original function.
+    ///
+    /// The original is kept alive so that its address is never reused.
+    fns: Locked<BTreeMap<usize, (Shared<ScriptFnDef>, Shared<ScriptFnDef>)>>,
+}
+
+impl FnPatches {
+    /// Get the epoch of the patches, which is zero if no function has been replaced.
+    #[inline(always)]
+    #[must_use]
+    pub fn epoch(&self) -> usize {
+        self.epoch.load(Ordering::Acquire)
+    }
+    /// Get the replacement of a function, if any.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, fn_def: &ScriptFnDef) -> Option<Shared<ScriptFnDef>> {
+        if self.epoch() == 0 {
+            return None;
+        }
+
+        let addr = fn_def as *const ScriptFnDef as usize;
+        locked_read(&self.fns).get(&addr).map(|(.., f)| f.clone())
+    }
+    /// Replace a function, together with all functions it has replaced before, and bump the epoch.
+    pub fn replace(&self, orig: &Shared<ScriptFnDef>, fn_def: Shared<ScriptFnDef>) {
+        let mut fns = locked_write(&self.fns);
+
+        fns.values_mut()
+            .filter(|(.., f)| Shared::ptr_eq(f, orig))
+            .for_each(|(.., f)| *f = fn_def.clone());
+
+        let addr = Shared::as_ptr(orig) as usize;
+        fns.insert(addr, (orig.clone(), fn_def));
+
+        self.epoch.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
 impl fmt::Display for ScriptFnDef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
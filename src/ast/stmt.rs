@@ -3,7 +3,7 @@
 use super::{ASTFlags, ASTNode, BinaryExpr, Expr, FnCallExpr, Ident};
 use crate::engine::KEYWORD_EVAL;
 use crate::tokenizer::{Span, Token};
-use crate::{calc_fn_hash, Position, StaticVec, INT};
+use crate::{calc_fn_hash, Dynamic, ImmutableString, Position, StaticVec, INT};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
@@ -245,6 +245,37 @@ impl RangeCase {
             Self::InclusiveInt(r, ..) => r.contains(&n),
         }
     }
+    /// Is the specified floating-point number within this range?
+    ///
+    /// Not available under `no_float`.
+    #[cfg(not(feature = "no_float"))]
+    #[inline]
+    #[must_use]
+    pub fn contains_float(&self, f: crate::FLOAT) -> bool {
+        match self {
+            Self::ExclusiveInt(r, ..) => {
+                (r.start as crate::FLOAT) <= f && f < (r.end as crate::FLOAT)
+            }
+            Self::InclusiveInt(r, ..) => {
+                (*r.start() as crate::FLOAT) <= f && f <= (*r.end() as crate::FLOAT)
+            }
+        }
+    }
+    /// Is the specified value within this range?
+    ///
+    /// Only integers and floating-point numbers can be within a range.
+    #[inline]
+    #[must_use]
+    pub fn contains_value(&self, value: &Dynamic) -> bool {
+        if let Ok(n) = value.as_int() {
+            return self.contains(n);
+        }
+        #[cfg(not(feature = "no_float"))]
+        if let Ok(f) = value.as_float() {
+            return self.contains_float(f);
+        }
+        false
+    }
     /// Is the specified range inclusive?
     #[inline(always)]
     #[must_use]
@@ -271,6 +302,53 @@ impl RangeCase {
     }
 }
 
+/// _(internals)_ A pattern in a `switch` case.
+/// Exported under the `internals` feature only.
+#[derive(Debug, Clone, Hash)]
+pub enum SwitchPattern {
+    /// `is` _type_
+    ///
+    /// Matches values of a type with the specified name (as returned by `type_of`).
+    Type(ImmutableString),
+    /// `case [` _var_ `,` ... `]`
+    ///
+    /// Matches arrays of the same length, binding each element to a variable
+    /// (or skipping it if [`None`], i.e. `_`).
+    #[cfg(not(feature = "no_index"))]
+    Array(StaticVec<Option<Ident>>),
+    /// `case {` _property_ `,` ... `}`
+    ///
+    /// Matches object maps containing all the properties, binding each to a variable of the same
+    /// name.
+    #[cfg(not(feature = "no_object"))]
+    Map(StaticVec<Ident>),
+}
+
+impl SwitchPattern {
+    /// Get the variables bound by this pattern.
+    #[inline]
+    pub fn bindings(&self) -> impl Iterator<Item = &Ident> {
+        let bindings: Box<dyn Iterator<Item = &Ident>> = match self {
+            Self::Type(..) => Box::new(std::iter::empty()),
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(vars) => Box::new(vars.iter().flatten()),
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(props) => Box::new(props.iter()),
+        };
+        bindings
+    }
+}
+
+/// _(internals)_ A pattern case in a `switch` statement.
+/// Exported under the `internals` feature only.
+#[derive(Debug, Clone, Hash)]
+pub struct PatternCase {
+    /// The pattern.
+    pub pattern: SwitchPattern,
+    /// Index to the [`ConditionalExpr`].
+    pub index: usize,
+}
+
 pub type CaseBlocksList = smallvec::SmallVec<[usize; 1]>;
 
 /// _(internals)_ A type containing all cases for a `switch` statement.
@@ -283,8 +361,15 @@ pub struct SwitchCasesCollection {
     pub cases: BTreeMap<u64, CaseBlocksList>,
     /// List of range cases.
     pub ranges: StaticVec<RangeCase>,
+    /// List of pattern cases, tested in order.
+    pub patterns: StaticVec<PatternCase>,
     /// Statements block for the default case (there can be no condition for the default case).
     pub def_case: Option<usize>,
+    /// List of case values as written (with ranges not unrolled), each with the index to its
+    /// [`ConditionalExpr`].
+    ///
+    /// Not used in evaluation.  Cleared when the `switch` statement is optimized.
+    pub values: StaticVec<(Dynamic, usize)>,
 }
 
 /// _(internals)_ A `try-catch` block.
@@ -548,6 +633,16 @@ pub enum Stmt {
     /// * [`EXPORTED`][ASTFlags::EXPORTED] = `export`
     /// * [`CONSTANT`][ASTFlags::CONSTANT] = `const`
     Var(Box<(Ident, Expr, Option<NonZeroUsize>)>, ASTFlags, Position),
+    /// \[`export`\] `let`|`const` `(` id `,` ... `)` `=` expr
+    ///
+    /// Destructures a [tuple][crate::Tuple] with the same number of items, binding each item to a
+    /// variable (or skipping it if [`None`], i.e. `_`).
+    ///
+    /// ### Flags
+    ///
+    /// * [`EXPORTED`][ASTFlags::EXPORTED] = `export`
+    /// * [`CONSTANT`][ASTFlags::CONSTANT] = `const`
+    VarTuple(Box<(StaticVec<Option<Ident>>, Expr)>, ASTFlags, Position),
     /// expr op`=` expr
     Assignment(Box<(OpAssignment, BinaryExpr)>),
     /// func `(` expr `,` ... `)`
@@ -575,6 +670,14 @@ pub enum Stmt {
     /// * [`NONE`][ASTFlags::NONE] = `return`
     /// * [`BREAK`][ASTFlags::BREAK] = `throw`
     Return(Option<Box<Expr>>, ASTFlags, Position),
+    /// `yield` expr
+    ///
+    /// Only allowed in the body of a generator function, outside of `switch`, `try` and
+    /// statement expressions.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    Yield(Box<Expr>, Position),
     /// `import` expr `as` alias
     ///
     /// Not available under `no_module`.
@@ -646,6 +749,7 @@ impl Stmt {
             | Self::For(.., pos)
             | Self::Return(.., pos)
             | Self::Var(.., pos)
+            | Self::VarTuple(.., pos)
             | Self::TryCatch(.., pos) => *pos,
 
             Self::Assignment(x) => x.0.pos,
@@ -654,6 +758,9 @@ impl Stmt {
 
             Self::Expr(x) => x.start_position(),
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(.., pos) => *pos,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(.., pos) => *pos,
             #[cfg(not(feature = "no_module"))]
@@ -676,6 +783,7 @@ impl Stmt {
             | Self::For(.., pos)
             | Self::Return(.., pos)
             | Self::Var(.., pos)
+            | Self::VarTuple(.., pos)
             | Self::TryCatch(.., pos) => *pos = new_pos,
 
             Self::Assignment(x) => x.0.pos = new_pos,
@@ -686,6 +794,9 @@ impl Stmt {
                 x.set_position(new_pos);
             }
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(.., pos) => *pos = new_pos,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(.., pos) => *pos = new_pos,
             #[cfg(not(feature = "no_module"))]
@@ -713,7 +824,14 @@ impl Stmt {
             | Self::For(..)
             | Self::TryCatch(..) => false,
 
-            Self::Var(..) | Self::Assignment(..) | Self::BreakLoop(..) | Self::Return(..) => false,
+            Self::Var(..)
+            | Self::VarTuple(..)
+            | Self::Assignment(..)
+            | Self::BreakLoop(..)
+            | Self::Return(..) => false,
+
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => false,
 
             #[cfg(not(feature = "no_module"))]
             Self::Import(..) | Self::Export(..) => false,
@@ -743,12 +861,16 @@ impl Stmt {
             },
 
             Self::Var(..)
+            | Self::VarTuple(..)
             | Self::Assignment(..)
             | Self::FnCall(..)
             | Self::Do(..)
             | Self::BreakLoop(..)
             | Self::Return(..) => false,
 
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => false,
+
             #[cfg(not(feature = "no_module"))]
             Self::Import(..) | Self::Export(..) => false,
 
@@ -778,6 +900,10 @@ impl Stmt {
                         let block = &sw.expressions[r.index()];
                         block.condition.is_pure() && block.expr.is_pure()
                     })
+                    && sw.patterns.iter().all(|p| {
+                        let block = &sw.expressions[p.index];
+                        block.condition.is_pure() && block.expr.is_pure()
+                    })
                     && sw.def_case.is_some()
                     && sw.expressions[sw.def_case.unwrap()].expr.is_pure()
             }
@@ -798,9 +924,11 @@ impl Stmt {
             // so infinite loops can never occur.
             Self::For(x, ..) => x.2.is_pure() && x.3.iter().all(Self::is_pure),
 
-            Self::Var(..) | Self::Assignment(..) | Self::FnCall(..) => false,
+            Self::Var(..) | Self::VarTuple(..) | Self::Assignment(..) | Self::FnCall(..) => false,
             Self::Block(block, ..) => block.iter().all(Self::is_pure),
             Self::BreakLoop(..) | Self::Return(..) => false,
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(..) => false,
             Self::TryCatch(x, ..) => {
                 x.try_block.iter().all(Self::is_pure) && x.catch_block.iter().all(Self::is_pure)
             }
@@ -825,7 +953,7 @@ impl Stmt {
     #[must_use]
     pub fn is_block_dependent(&self) -> bool {
         match self {
-            Self::Var(..) => true,
+            Self::Var(..) | Self::VarTuple(..) => true,
 
             Self::Expr(e) => match &**e {
                 Expr::Stmt(s) => s.iter().all(Self::is_block_dependent),
@@ -852,6 +980,7 @@ impl Stmt {
     pub fn is_internally_pure(&self) -> bool {
         match self {
             Self::Var(x, ..) => x.1.is_pure(),
+            Self::VarTuple(x, ..) => x.1.is_pure(),
 
             Self::Expr(e) => match &**e {
                 Expr::Stmt(s) => s.iter().all(Self::is_internally_pure),
@@ -899,6 +1028,11 @@ impl Stmt {
                     return false;
                 }
             }
+            Self::VarTuple(x, ..) => {
+                if !x.1.walk(path, on_node) {
+                    return false;
+                }
+            }
             Self::If(x, ..) => {
                 if !x.0.walk(path, on_node) {
                     return false;
@@ -942,6 +1076,16 @@ impl Stmt {
                         return false;
                     }
                 }
+                for p in &sw.patterns {
+                    let block = &sw.expressions[p.index];
+
+                    if !block.condition.walk(path, on_node) {
+                        return false;
+                    }
+                    if !block.expr.walk(path, on_node) {
+                        return false;
+                    }
+                }
                 if let Some(index) = sw.def_case {
                     if !sw.expressions[index].expr.walk(path, on_node) {
                         return false;
@@ -1012,6 +1156,12 @@ impl Stmt {
                     return false;
                 }
             }
+            #[cfg(not(feature = "no_function"))]
+            Self::Yield(e, ..) => {
+                if !e.walk(path, on_node) {
+                    return false;
+                }
+            }
             #[cfg(not(feature = "no_module"))]
             Self::Import(x, ..) => {
                 if !x.0.walk(path, on_node) {
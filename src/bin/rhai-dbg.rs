@@ -147,6 +147,9 @@ fn print_debug_help() {
     println!(
         "break/b <func> <#args> => set a new break-point for a function call with #args arguments"
     );
+    println!("watch/w <variable>     => set a new break-point for changes to a variable");
+    println!("display <expr...>      => print the value of an expression at every stop");
+    println!("undisplay              => delete all displayed expressions");
     println!("throw                  => throw a runtime exception");
     println!("throw <message...>     => throw an exception with string data");
     println!("throw <#>              => throw an exception with numeric data");
@@ -254,6 +257,9 @@ fn debug_callback(
                 BreakPoint::AtProperty { ref name, .. } => {
                     println!("! Property {} accessed.", name)
                 }
+                BreakPoint::AtVariableChange { ref name, .. } => {
+                    println!("! Variable {} changed.", name)
+                }
                 _ => unreachable!(),
             }
         }
@@ -289,6 +295,11 @@ fn debug_callback(
     // Print current source line
     print_current_source(&mut context, source, pos, lines, (0, 0));
 
+    // Print watch expressions
+    for watch in context.global_runtime_state().debugger.watches() {
+        println!("\x1b[36m{}\x1b[39m", watch);
+    }
+
     // Read stdin for commands
     let mut input = String::new();
 
@@ -462,8 +473,7 @@ fn debug_callback(
                             context
                                 .global_runtime_state_mut()
                                 .debugger
-                                .break_points_mut()
-                                .remove(n - 1);
+                                .remove_break_point(n - 1);
                             println!("Break-point #{} deleted.", n)
                         } else {
                             eprintln!("\x1b[31mInvalid break-point: {}\x1b[39m", n);
@@ -476,8 +486,7 @@ fn debug_callback(
                     context
                         .global_runtime_state_mut()
                         .debugger
-                        .break_points_mut()
-                        .clear();
+                        .clear_break_points();
                     println!("All break-points deleted.");
                 }
                 ["break" | "b", fn_name, args] => {
@@ -564,6 +573,35 @@ fn debug_callback(
                         .break_points_mut()
                         .push(bp);
                 }
+                ["watch" | "w", var_name] => {
+                    let bp = rhai::debugger::BreakPoint::AtVariableChange {
+                        name: var_name.trim().into(),
+                        enabled: true,
+                    };
+                    println!("Break-point added for {}", bp);
+                    context
+                        .global_runtime_state_mut()
+                        .debugger
+                        .break_points_mut()
+                        .push(bp);
+                }
+                ["display", ..] => {
+                    let expr = input.trim().split_once(' ').map(|(_, x)| x).unwrap_or("");
+                    let engine = context.engine();
+
+                    match context
+                        .global_runtime_state_mut()
+                        .debugger
+                        .add_watch(engine, expr.trim())
+                    {
+                        Ok(..) => println!("Expression added: {}", expr.trim()),
+                        Err(err) => eprintln!("\x1b[31m{}\x1b[39m", err),
+                    }
+                }
+                ["undisplay"] => {
+                    context.global_runtime_state_mut().debugger.clear_watches();
+                    println!("All displayed expressions deleted.");
+                }
                 ["throw"] => break Err(EvalAltResult::ErrorRuntime(Dynamic::UNIT, pos).into()),
                 ["throw", num] if num.trim().parse::<INT>().is_ok() => {
                     let value = num.trim().parse::<INT>().unwrap().into();
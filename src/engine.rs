@@ -2,8 +2,8 @@
 
 use crate::api::options::LangOptions;
 use crate::func::native::{
-    locked_write, OnDebugCallback, OnDefVarCallback, OnParseTokenCallback, OnPrintCallback,
-    OnVarCallback,
+    locked_write, OnDebugCallback, OnDefVarCallback, OnMissingFnCallback, OnOperatorCallback,
+    OnParseTokenCallback, OnPrintCallback, OnVarCallback,
 };
 use crate::packages::{Package, StandardPackage};
 use crate::tokenizer::Token;
@@ -14,7 +14,7 @@ use crate::{
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{collections::BTreeSet, fmt, num::NonZeroU8};
+use std::{any::TypeId, collections::BTreeSet, fmt, num::NonZeroU8};
 
 pub type Precedence = NonZeroU8;
 
@@ -30,6 +30,9 @@ pub const KEYWORD_IS_SHARED: &str = "is_shared";
 pub const KEYWORD_IS_DEF_VAR: &str = "is_def_var";
 #[cfg(not(feature = "no_function"))]
 pub const KEYWORD_IS_DEF_FN: &str = "is_def_fn";
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_index"))]
+pub const KEYWORD_CALL_QUALIFIED: &str = "call_qualified";
 pub const KEYWORD_THIS: &str = "this";
 #[cfg(not(feature = "no_function"))]
 #[cfg(not(feature = "no_module"))]
@@ -51,6 +54,12 @@ pub const FN_ANONYMOUS: &str = "anon$";
 /// function to compare two [`Dynamic`] values.
 pub const OP_EQUALS: &str = Token::EqualsTo.literal_syntax();
 
+/// Standard less-than comparison operator.
+///
+/// Some standard functions (e.g. sorting or searching a sorted [`Array`][crate::Array]) implicitly
+/// call this function to order two [`Dynamic`] values.
+pub const OP_LESS_THAN: &str = Token::LessThan.literal_syntax();
+
 /// Standard concatenation operator.
 ///
 /// Used primarily to build up interpolated strings.
@@ -96,19 +105,33 @@ pub struct Engine {
     /// A collection of all sub-modules directly loaded into the Engine.
     #[cfg(not(feature = "no_module"))]
     pub(crate) global_sub_modules: std::collections::BTreeMap<Identifier, Shared<Module>>,
+    /// A collection of all sub-modules directly loaded into the Engine, constructed on first use.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) lazy_sub_modules: std::collections::BTreeMap<Identifier, crate::module::LazyModule>,
 
     /// A module resolution service.
     #[cfg(not(feature = "no_module"))]
     pub(crate) module_resolver: Box<dyn crate::ModuleResolver>,
+    /// Callback closure for notification of an invalidated module.
+    #[cfg(not(feature = "no_module"))]
+    pub(crate) module_invalidated: Option<Box<crate::func::native::OnModuleInvalidatedCallback>>,
 
     /// An empty [`ImmutableString`] for cloning purposes.
     pub(crate) interned_strings: Locked<StringsInterner<'static>>,
+    /// A shared empty [`ImmutableString`], which can be cloned without locking.
+    pub(crate) empty_string: ImmutableString,
+    /// Pool of strings shared by all [`AST`][crate::AST]'s compiled by this [`Engine`], if enabled.
+    pub(crate) strings_pool: Option<Locked<StringsInterner<'static>>>,
 
     /// A set of symbols to disable.
     pub(crate) disabled_symbols: BTreeSet<Identifier>,
     /// A map containing custom keywords and precedence to recognize.
     #[cfg(not(feature = "no_custom_syntax"))]
     pub(crate) custom_keywords: std::collections::BTreeMap<Identifier, Option<Precedence>>,
+    /// Kinds of custom operators that are not left-binding binary operators.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    pub(crate) custom_operator_kinds:
+        std::collections::BTreeMap<Identifier, crate::CustomOperatorKind>,
     /// Custom syntax.
     #[cfg(not(feature = "no_custom_syntax"))]
     pub(crate) custom_syntax:
@@ -119,17 +142,66 @@ pub struct Engine {
     pub(crate) resolve_var: Option<Box<OnVarCallback>>,
     /// Callback closure to remap tokens during parsing.
     pub(crate) token_mapper: Option<Box<OnParseTokenCallback>>,
+    /// Callback closure for parse warnings.
+    pub(crate) parse_warning: Option<Box<crate::func::native::OnParseWarningCallback>>,
+    /// Callback closure for tracing operator evaluations.
+    pub(crate) operator_trace: Option<Box<OnOperatorCallback>>,
+    /// Callback closure for resolving missing functions.
+    pub(crate) missing_fn: Option<Box<OnMissingFnCallback>>,
+    /// Callback closure for attempts to assign to read-only targets.
+    pub(crate) invalid_assignment: Option<Box<crate::func::native::OnInvalidAssignmentCallback>>,
+    /// Handlers for unknown properties of custom types.
+    #[cfg(not(feature = "no_object"))]
+    pub(crate) unknown_property_handlers:
+        std::collections::BTreeMap<TypeId, Box<crate::func::native::OnUnknownPropertyCallback>>,
+    /// Names of the fields of custom types to iterate over in `for` loops.
+    #[cfg(not(feature = "no_object"))]
+    #[cfg(not(feature = "no_index"))]
+    pub(crate) field_iterators: std::collections::BTreeMap<TypeId, StaticVec<Identifier>>,
+    /// Iterators of index/value pairs of custom types to iterate over in `for` loops.
+    pub(crate) indexed_iterators:
+        std::collections::BTreeMap<TypeId, Shared<crate::func::IndexedIteratorFn>>,
+    /// Handlers for unknown methods of custom types.
+    pub(crate) unknown_method_handlers:
+        std::collections::BTreeMap<TypeId, Box<crate::func::native::OnUnknownMethodCallback>>,
 
     /// Callback closure for implementing the `print` command.
     pub(crate) print: Box<OnPrintCallback>,
     /// Callback closure for implementing the `debug` command.
     pub(crate) debug: Box<OnDebugCallback>,
+    /// Callback closure for the end of an evaluation run.
+    pub(crate) eval_end: Option<Box<crate::func::native::OnEvalEndCallback>>,
     /// Callback closure for progress reporting.
     #[cfg(not(feature = "unchecked"))]
     pub(crate) progress: Option<Box<crate::func::native::OnProgressCallback>>,
+    /// Callback closure for progress reporting at a sampling interval.
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) progress_sampled: Option<(u64, Box<crate::func::native::OnProgressSampledCallback>)>,
+    /// Callback closure for spawning the jobs running asynchronous evaluations.
+    #[cfg(feature = "async")]
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) async_spawn: Option<Box<crate::api::eval_async::OnAsyncSpawnCallback>>,
+    /// Pool of scratch arenas for evaluation temporaries, retained between evaluations.
+    pub(crate) arenas: Option<Locked<Vec<crate::eval::ScratchArena>>>,
+    /// Minimum duration of a call to a script-defined function to be reported as slow.
+    #[cfg(feature = "tracing")]
+    pub(crate) slow_fn_threshold: std::time::Duration,
+    /// Profile of evaluation runs being recorded.
+    #[cfg(feature = "profiling")]
+    pub(crate) profile: Option<Locked<crate::api::profiling::Profile>>,
+    /// Coverage of evaluation runs being tracked.
+    #[cfg(feature = "coverage")]
+    pub(crate) coverage: Option<Locked<crate::api::coverage::Coverage>>,
+    /// Sink for metrics about the internals of the Engine.
+    pub(crate) metrics: Option<Box<dyn crate::api::metrics::MetricsSink>>,
 
     /// Language options.
     pub(crate) options: LangOptions,
+    /// Policy for operators between decimal and floating-point numbers.
+    #[cfg(feature = "decimal")]
+    #[cfg(not(feature = "no_float"))]
+    pub(crate) decimal_float_policy: crate::api::options::DecimalFloatPolicy,
 
     /// Default value for the custom state.
     pub(crate) def_tag: Dynamic,
@@ -140,6 +212,9 @@ pub struct Engine {
     /// Max limits.
     #[cfg(not(feature = "unchecked"))]
     pub(crate) limits: crate::api::limits::Limits,
+    /// Limits for nested evaluations.
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) nested_limits: Option<Box<crate::api::limits::Limits>>,
 
     /// Callback closure for debugging.
     #[cfg(feature = "debugging")]
@@ -158,6 +233,9 @@ impl fmt::Debug for Engine {
 
         #[cfg(not(feature = "no_module"))]
         f.field("global_sub_modules", &self.global_sub_modules);
+        #[cfg(not(feature = "no_module"))]
+        f.field("lazy_sub_modules", &self.lazy_sub_modules)
+            .field("module_invalidated", &self.module_invalidated.is_some());
 
         f.field("disabled_symbols", &self.disabled_symbols);
 
@@ -170,18 +248,63 @@ impl fmt::Debug for Engine {
                 .map(crate::SmartString::as_str)
                 .collect::<String>(),
         );
+        #[cfg(not(feature = "no_custom_syntax"))]
+        f.field("custom_operator_kinds", &self.custom_operator_kinds);
 
         f.field("def_var_filter", &self.def_var_filter.is_some())
             .field("resolve_var", &self.resolve_var.is_some())
-            .field("token_mapper", &self.token_mapper.is_some());
+            .field("token_mapper", &self.token_mapper.is_some())
+            .field("parse_warning", &self.parse_warning.is_some())
+            .field("operator_trace", &self.operator_trace.is_some())
+            .field("missing_fn", &self.missing_fn.is_some())
+            .field("invalid_assignment", &self.invalid_assignment.is_some());
+
+        #[cfg(not(feature = "no_object"))]
+        f.field(
+            "unknown_property_handlers",
+            &self.unknown_property_handlers.len(),
+        );
+        #[cfg(not(feature = "no_object"))]
+        #[cfg(not(feature = "no_index"))]
+        f.field("field_iterators", &self.field_iterators.len());
+        f.field("indexed_iterators", &self.indexed_iterators.len());
+        f.field(
+            "unknown_method_handlers",
+            &self.unknown_method_handlers.len(),
+        );
+
+        f.field("eval_end", &self.eval_end.is_some());
 
         #[cfg(not(feature = "unchecked"))]
-        f.field("progress", &self.progress.is_some());
+        f.field("progress", &self.progress.is_some())
+            .field("progress_sampled", &self.progress_sampled.is_some());
+
+        #[cfg(feature = "async")]
+        #[cfg(not(feature = "no_std"))]
+        #[cfg(not(target_family = "wasm"))]
+        f.field("async_spawn", &self.async_spawn.is_some());
+
+        f.field("arenas", &self.arenas.is_some());
+        f.field("metrics", &self.metrics.is_some());
+
+        #[cfg(feature = "tracing")]
+        f.field("slow_fn_threshold", &self.slow_fn_threshold);
+
+        #[cfg(feature = "profiling")]
+        f.field("profile", &self.profile.is_some());
+        #[cfg(feature = "coverage")]
+        f.field("coverage", &self.coverage.is_some());
 
         f.field("options", &self.options);
 
+        #[cfg(feature = "decimal")]
+        #[cfg(not(feature = "no_float"))]
+        f.field("decimal_float_policy", &self.decimal_float_policy);
+
         #[cfg(not(feature = "unchecked"))]
         f.field("limits", &self.limits);
+        #[cfg(not(feature = "unchecked"))]
+        f.field("nested_limits", &self.nested_limits);
 
         f.finish()
     }
@@ -234,6 +357,7 @@ impl Engine {
         // default print/debug implementations
         #[cfg(not(feature = "no_std"))]
         #[cfg(not(target_family = "wasm"))]
+        #[cfg(not(feature = "minimal"))]
         {
             engine.print = Box::new(|s| println!("{}", s));
             engine.debug = Box::new(|s, source, pos| {
@@ -255,6 +379,32 @@ impl Engine {
         engine
     }
 
+    /// Create a new [`Engine`] with only arithmetic and logic functions.
+    ///
+    /// Only the [`ArithmeticPackage`][crate::packages::ArithmeticPackage] and
+    /// [`LogicPackage`][crate::packages::LogicPackage] are registered. No module resolver and no
+    /// `print`/`debug` implementations are set up.
+    ///
+    /// This only affects which functions are registered at runtime. To also reduce binary size,
+    /// turn on the `minimal` feature, which excludes at compile time the number formatting
+    /// functions (e.g. `to_hex`), the default `print`/`debug` implementations writing to
+    /// `stdout` and all optional packages of the standard library (via the `no_pkg_XXX` feature
+    /// flags).
+    ///
+    /// Use [`Engine::build_info`] to find out which features and packages are compiled in.
+    #[inline]
+    #[must_use]
+    pub fn new_minimal() -> Self {
+        use crate::packages::{ArithmeticPackage, LogicPackage};
+
+        let mut engine = Self::new_raw();
+
+        engine.register_global_module(ArithmeticPackage::new().as_shared_module());
+        engine.register_global_module(LogicPackage::new().as_shared_module());
+
+        engine
+    }
+
     /// Create a new [`Engine`] with minimal built-in functions.
     ///
     /// Use [`register_global_module`][Engine::register_global_module] to add packages of functions.
@@ -266,28 +416,65 @@ impl Engine {
 
             #[cfg(not(feature = "no_module"))]
             global_sub_modules: std::collections::BTreeMap::new(),
+            #[cfg(not(feature = "no_module"))]
+            lazy_sub_modules: std::collections::BTreeMap::new(),
 
             #[cfg(not(feature = "no_module"))]
             module_resolver: Box::new(crate::module::resolvers::DummyModuleResolver::new()),
+            #[cfg(not(feature = "no_module"))]
+            module_invalidated: None,
 
             interned_strings: StringsInterner::new().into(),
+            empty_string: ImmutableString::new(),
+            strings_pool: None,
             disabled_symbols: BTreeSet::new(),
             #[cfg(not(feature = "no_custom_syntax"))]
             custom_keywords: std::collections::BTreeMap::new(),
             #[cfg(not(feature = "no_custom_syntax"))]
+            custom_operator_kinds: std::collections::BTreeMap::new(),
+            #[cfg(not(feature = "no_custom_syntax"))]
             custom_syntax: std::collections::BTreeMap::new(),
 
             def_var_filter: None,
             resolve_var: None,
             token_mapper: None,
+            parse_warning: None,
+            operator_trace: None,
+            missing_fn: None,
+            invalid_assignment: None,
+            #[cfg(not(feature = "no_object"))]
+            unknown_property_handlers: std::collections::BTreeMap::new(),
+            #[cfg(not(feature = "no_object"))]
+            #[cfg(not(feature = "no_index"))]
+            field_iterators: std::collections::BTreeMap::new(),
+            indexed_iterators: std::collections::BTreeMap::new(),
+            unknown_method_handlers: std::collections::BTreeMap::new(),
 
             print: Box::new(|_| {}),
             debug: Box::new(|_, _, _| {}),
 
+            eval_end: None,
             #[cfg(not(feature = "unchecked"))]
             progress: None,
+            #[cfg(not(feature = "unchecked"))]
+            progress_sampled: None,
+            #[cfg(feature = "async")]
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(target_family = "wasm"))]
+            async_spawn: None,
+            arenas: None,
+            #[cfg(feature = "tracing")]
+            slow_fn_threshold: crate::api::tracing::DEFAULT_SLOW_FN_THRESHOLD,
+            #[cfg(feature = "profiling")]
+            profile: None,
+            #[cfg(feature = "coverage")]
+            coverage: None,
+            metrics: None,
 
             options: LangOptions::new(),
+            #[cfg(feature = "decimal")]
+            #[cfg(not(feature = "no_float"))]
+            decimal_float_policy: crate::api::options::DecimalFloatPolicy::Error,
 
             def_tag: Dynamic::UNIT,
 
@@ -298,6 +485,8 @@ impl Engine {
 
             #[cfg(not(feature = "unchecked"))]
             limits: crate::api::limits::Limits::new(),
+            #[cfg(not(feature = "unchecked"))]
+            nested_limits: None,
 
             #[cfg(feature = "debugging")]
             debugger: None,
@@ -308,6 +497,27 @@ impl Engine {
         global_namespace.internal = true;
         engine.global_modules.push(global_namespace.into());
 
+        // Deliver all property accesses and method calls on proxies to their handlers
+        #[cfg(not(feature = "no_object"))]
+        {
+            use crate::types::Proxy;
+
+            engine.unknown_property_handlers.insert(
+                TypeId::of::<Proxy>(),
+                Box::new(|obj, prop| {
+                    let proxy = obj.read_lock::<Proxy>().expect("`Proxy`").clone();
+                    proxy.get_property(prop)
+                }),
+            );
+            engine.unknown_method_handlers.insert(
+                TypeId::of::<Proxy>(),
+                Box::new(|obj, name, args| {
+                    let proxy = obj.read_lock::<Proxy>().expect("`Proxy`").clone();
+                    proxy.call_method(name, args)
+                }),
+            );
+        }
+
         engine
     }
 
@@ -319,6 +529,9 @@ impl Engine {
         &self,
         string: impl AsRef<str> + Into<ImmutableString>,
     ) -> ImmutableString {
+        if string.as_ref().is_empty() {
+            return self.empty_string.clone();
+        }
         locked_write(&self.interned_strings).get(string)
     }
 
@@ -334,6 +547,9 @@ impl Engine {
         &self,
         string: impl AsRef<str> + Into<ImmutableString>,
     ) -> ImmutableString {
+        if string.as_ref().is_empty() {
+            return self.empty_string.clone();
+        }
         locked_write(&self.interned_strings).get(string)
     }
 
@@ -341,7 +557,7 @@ impl Engine {
     #[inline(always)]
     #[must_use]
     pub fn const_empty_string(&self) -> ImmutableString {
-        self.get_interned_string("")
+        self.empty_string.clone()
     }
 
     /// Check a result to ensure that it is valid.
@@ -0,0 +1,143 @@
+//! Module defining the scratch arena for evaluation temporaries.
+
+use super::Caches;
+use crate::func::native::locked_write;
+use crate::{Dynamic, Engine};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Maximum number of buffers of each kind retained in a [`ScratchArena`].
+const MAX_POOLED_BUFFERS: usize = 16;
+
+/// Maximum capacity of a buffer retained in a [`ScratchArena`] after it is reset.
+///
+/// Larger buffers are dropped to avoid holding onto memory after evaluating an unusual script.
+const MAX_POOLED_CAPACITY: usize = 4096;
+
+/// Maximum number of [`ScratchArena`]'s retained by an [`Engine`] for concurrent evaluations.
+const MAX_POOLED_ARENAS: usize = 16;
+
+/// _(internals)_ A scratch arena holding reusable buffers for evaluation temporaries.
+/// Exported under the `internals` feature only.
+///
+/// Buffers (for example for argument lists and interpolated strings) are taken from the arena,
+/// used during evaluation, and then put back in order to be reused, reducing the pressure on the
+/// allocator when evaluating many scripts in quick succession.
+///
+/// Iterator states of `for` loops over integer ranges are kept in the loop itself and need no
+/// buffer; those of other types are boxed by their iterator functions.
+#[derive(Debug, Clone, Default)]
+pub struct ScratchArena {
+    /// Pool of string buffers.
+    strings: Vec<String>,
+    /// Pool of value buffers.
+    values: Vec<Vec<Dynamic>>,
+}
+
+impl ScratchArena {
+    /// Create a new, empty [`ScratchArena`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            strings: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+    /// Take an empty string buffer from the arena.
+    #[inline]
+    #[must_use]
+    pub fn take_string(&mut self) -> String {
+        self.strings.pop().unwrap_or_default()
+    }
+    /// Put a string buffer back into the arena.
+    #[inline]
+    pub fn put_string(&mut self, mut s: String) {
+        if self.strings.len() < MAX_POOLED_BUFFERS {
+            s.clear();
+            self.strings.push(s);
+        }
+    }
+    /// Put an empty string buffer with a particular capacity into the arena, to replace one that
+    /// is kept by its user.
+    #[inline]
+    pub fn replace_string(&mut self, capacity: usize) {
+        if self.strings.len() < MAX_POOLED_BUFFERS && capacity <= MAX_POOLED_CAPACITY {
+            self.strings.push(String::with_capacity(capacity));
+        }
+    }
+    /// Take an empty value buffer with at least the specified capacity from the arena.
+    #[inline]
+    #[must_use]
+    pub fn take_values(&mut self, capacity: usize) -> Vec<Dynamic> {
+        let mut values = self.values.pop().unwrap_or_default();
+        values.reserve(capacity);
+        values
+    }
+    /// Put a value buffer back into the arena.
+    #[inline]
+    pub fn put_values(&mut self, mut values: Vec<Dynamic>) {
+        if self.values.len() < MAX_POOLED_BUFFERS {
+            values.clear();
+            self.values.push(values);
+        }
+    }
+    /// Reset the arena, dropping any buffer that has grown too large to keep.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.strings.retain(|s| s.capacity() <= MAX_POOLED_CAPACITY);
+        self.values.retain(|v| v.capacity() <= MAX_POOLED_CAPACITY);
+    }
+}
+
+impl Engine {
+    /// Enable or disable a per-evaluation scratch arena for temporaries such as
+    /// argument lists and interpolation buffers.
+    ///
+    /// Each evaluation takes an arena from a pool kept by this [`Engine`] and puts it back, reset,
+    /// when done, so its buffers are reused by the following evaluations, reducing allocator
+    /// pressure in scenarios with thousands of evaluations per second.  Concurrent evaluations
+    /// each take a separate arena.
+    ///
+    /// Disabled by default.
+    #[inline(always)]
+    pub fn with_arena(&mut self, enable: bool) -> &mut Self {
+        self.arenas = if enable {
+            Some(Vec::new().into())
+        } else {
+            None
+        };
+        self
+    }
+    /// Is the per-evaluation scratch arena enabled?
+    #[inline(always)]
+    #[must_use]
+    pub const fn has_arena(&self) -> bool {
+        self.arenas.is_some()
+    }
+    /// Create a new [`Caches`] for an evaluation, moving in a [scratch arena][ScratchArena]
+    /// from the pool retained by this [`Engine`], if enabled.
+    #[inline]
+    #[must_use]
+    pub(crate) fn new_caches<'a>(&self) -> Caches<'a> {
+        let mut caches = Caches::new();
+        if let Some(ref arenas) = self.arenas {
+            let arena = locked_write(arenas).pop().unwrap_or_default();
+            caches.set_arena(arena);
+        }
+        caches
+    }
+    /// Reset the [scratch arena][ScratchArena] of a [`Caches`] and put it back into the pool
+    /// of this [`Engine`] for the next evaluation.
+    #[inline]
+    pub(crate) fn recycle_caches(&self, caches: &mut Caches) {
+        if let (Some(arenas), Some(mut arena)) = (self.arenas.as_ref(), caches.take_arena()) {
+            arena.reset();
+
+            let mut arenas = locked_write(arenas);
+            if arenas.len() < MAX_POOLED_ARENAS {
+                arenas.push(arena);
+            }
+        }
+    }
+}
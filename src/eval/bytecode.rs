@@ -0,0 +1,1263 @@
+//! Module defining a compact bytecode for evaluating an [`AST`].
+#![cfg(feature = "bytecode")]
+
+use super::stmt::ForLoop;
+use super::{Caches, GlobalRuntimeState};
+use crate::ast::{ASTFlags, Expr, FnCallExpr, FnCallHashes, Ident, OpAssignment, Stmt, StmtBlock};
+use crate::tokenizer::is_keyword_function;
+use crate::{
+    Dynamic, Engine, FnArgsVec, ImmutableString, Module, Position, RhaiResult, RhaiResultOf, Scope,
+    AST, ERR,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{mem, num::NonZeroUsize};
+
+/// A bytecode instruction.
+///
+/// Every statement and expression pushes exactly one value onto the stack.
+/// Statements and expressions that are not compiled are evaluated by the tree-walking evaluator.
+#[derive(Debug, Clone)]
+pub(crate) enum Instr {
+    /// Count an operation, then push a constant value.
+    Const(Dynamic, Position),
+    /// Push a value without counting an operation.
+    Push(Dynamic),
+    /// Discard the value on top of the stack.
+    Pop,
+    /// Flatten the value on top of the stack.
+    Flatten,
+    /// Count an operation.
+    #[cfg(not(feature = "unchecked"))]
+    IncOps(Position),
+    /// Consume fuel for a loop iteration.
+    #[cfg(not(feature = "unchecked"))]
+    ConsumeFuel(Position),
+    /// Evaluate an expression via the tree-walking evaluator.
+    Expr(Box<Expr>),
+    /// Evaluate a statement via the tree-walking evaluator.
+    ///
+    /// The flag is passed on as `rewind_scope`.
+    Stmt(Box<Stmt>, bool),
+    /// Evaluate an `import` statement via the tree-walking evaluator, then update the function
+    /// resolution caches.
+    ///
+    /// The flag is passed on as `rewind_scope`.
+    #[cfg(not(feature = "no_module"))]
+    Import(Box<Stmt>, bool),
+    /// Call a native operator (name, hashes, position) with operands popped from the stack.
+    Operator(Box<(ImmutableString, FnCallHashes, Position)>, usize),
+    /// Check that the value on top of the stack is `bool`.
+    ToBool(Position),
+    /// Jump to the target, keeping the `bool` on top of the stack, if it is equal to the flag.
+    /// Otherwise discard it.
+    ShortCircuit(bool, usize),
+    /// Pop a `bool` from the stack and jump to the target if it is equal to the flag.
+    JumpIf(bool, usize, Position),
+    /// Jump to the target.
+    Jump(usize),
+    /// Enter a statements block, with or without restoring the original state on leaving.
+    EnterBlock(bool),
+    /// Leave a statements block.
+    LeaveBlock,
+    /// Enter a loop (continue target, break target, position of the loop statement).
+    EnterLoop(usize, usize, Position),
+    /// Enter a `for` loop, popping the value to iterate from the stack.
+    ForInit(Box<ForLoopInfo>),
+    /// Advance the innermost `for` loop, or jump to its break target when done.
+    ForNext,
+    /// Leave the innermost loop.
+    LeaveLoop,
+    /// Assign the value popped from the stack to a variable.
+    AssignVar(Box<(OpAssignment, Expr)>),
+    /// Count an operation, then push the value of a variable.
+    Var(Box<Expr>),
+    /// Count an operation, then push the value of a variable at an offset from the end of the
+    /// [`Scope`], unless the [`Scope`] must be searched.
+    LocalVar(Box<Expr>, usize),
+    /// Check that a variable can be defined by a `let`/`const` statement (name, flags, position).
+    CheckVar(Box<Ident>, ASTFlags, Position),
+    /// Define a variable by a `let`/`const` statement with the value popped from the stack.
+    ///
+    /// The flag is passed on as `rewind_scope`.
+    DefineVar(Box<(Ident, Option<NonZeroUsize>)>, ASTFlags, bool),
+    /// Call a function with arguments popped from the stack.
+    Call(Box<CallInfo>),
+    /// Call a function with a variable as the first argument, passed by reference if possible,
+    /// and the other arguments popped from the stack.
+    CallRef(Box<(CallInfo, Expr)>),
+    /// Return the value popped from the stack.
+    Return(Position),
+    /// Throw the value popped from the stack as an exception.
+    Throw(Position),
+}
+
+/// Information on a function call.
+#[derive(Debug, Clone)]
+pub(crate) struct CallInfo {
+    /// Function name.
+    name: ImmutableString,
+    /// Function call hashes.
+    hashes: FnCallHashes,
+    /// Number of arguments on the stack.
+    num_args: usize,
+    /// Address of the original [`FnCallExpr`], to find out whether it is a tail call.
+    #[cfg(not(feature = "no_function"))]
+    addr: usize,
+    /// Position of the function call.
+    pos: Position,
+}
+
+/// Information on a `for` loop.
+#[derive(Debug, Clone)]
+pub(crate) struct ForLoopInfo {
+    /// Loop variable, counter variable, expression to iterate and an empty loop body with the
+    /// position of the compiled one.
+    x: (Ident, Ident, Expr, StmtBlock),
+    /// Position of the loop statement.
+    pos: Position,
+    /// Continue target.
+    continue_at: usize,
+    /// Break target.
+    break_at: usize,
+}
+
+/// Bodies of script-defined functions compiled into bytecode, keyed by the addresses of their
+/// definitions.
+#[cfg(not(feature = "no_function"))]
+pub(crate) type CompiledFns = std::collections::BTreeMap<usize, Box<[Instr]>>;
+
+/// State of a statements block saved on entry.
+struct BlockState {
+    restore_orig_state: bool,
+    always_search_scope: bool,
+    scope_len: usize,
+    #[cfg(not(feature = "no_module"))]
+    imports_len: usize,
+    fn_resolution_caches_len: usize,
+}
+
+/// State of a running loop.
+#[cfg_attr(feature = "unchecked", allow(dead_code))]
+struct LoopState<'a> {
+    continue_at: usize,
+    break_at: usize,
+    stack_len: usize,
+    pos: Position,
+    for_loop: Option<(Box<ForLoop>, &'a ForLoopInfo)>,
+}
+
+/// Runtime state passed to the bytecode interpreter and its helpers.
+pub(crate) struct RunContext<'g, 'pg, 'c, 'pc, 'a> {
+    /// The global runtime state.
+    pub(crate) global: &'g mut GlobalRuntimeState<'pg>,
+    /// The caches.
+    pub(crate) caches: &'c mut Caches<'pc>,
+    /// The current stack of script-defined functions' libraries.
+    pub(crate) lib: &'a [&'a Module],
+    /// The current nesting level of function calls.
+    pub(crate) level: usize,
+}
+
+/// A frame on the bytecode interpreter's frames stack.
+enum Frame<'a> {
+    Block(BlockState),
+    Loop(LoopState<'a>),
+}
+
+/// An [`AST`] compiled into a compact bytecode for faster evaluation.
+/// Exported under the `bytecode` feature only.
+///
+/// Created via [`Engine::compile_bytecode`] and evaluated via [`Engine::eval_bytecode`].
+///
+/// Statements at global level and the bodies of script-defined functions are compiled.
+/// Statements and expressions without a bytecode equivalent are evaluated by the tree-walking
+/// evaluator with identical semantics.
+#[derive(Debug, Clone)]
+pub struct Bytecode {
+    /// The original [`AST`].
+    ast: AST,
+    /// Compiled instructions.
+    code: Box<[Instr]>,
+    /// Compiled bodies of script-defined functions.
+    #[cfg(not(feature = "no_function"))]
+    fns: crate::Shared<CompiledFns>,
+}
+
+impl Bytecode {
+    /// Compile an [`AST`] into [`Bytecode`].
+    #[must_use]
+    pub(crate) fn new(ast: &AST) -> Self {
+        let ast = ast.clone();
+
+        let mut compiler = Compiler::new(&ast);
+        compiler.compile_block(ast.statements(), false);
+        let code = mem::take(&mut compiler.code).into();
+
+        // Generators are evaluated lazily, and functions with an encapsulated environment resolve
+        // variables and functions differently
+        #[cfg(not(feature = "no_function"))]
+        let fns = ast
+            .shared_lib()
+            .iter_script_fn()
+            .filter(|(.., f)| {
+                #[cfg(not(feature = "no_module"))]
+                if f.environ.is_some() {
+                    return false;
+                }
+                !f.is_generator && !f.body.is_empty()
+            })
+            .map(|(.., f)| {
+                compiler.self_call = Some((f.name.clone(), f.params.len()));
+                compiler.compile_block(f.body.statements(), true);
+                let code = mem::take(&mut compiler.code).into();
+                (&**f as *const crate::ast::ScriptFnDef as usize, code)
+            })
+            .collect::<CompiledFns>()
+            .into();
+
+        Self {
+            ast,
+            code,
+            #[cfg(not(feature = "no_function"))]
+            fns,
+        }
+    }
+    /// Get the original [`AST`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn ast(&self) -> &AST {
+        &self.ast
+    }
+}
+
+/// Compiler from statements into bytecode instructions.
+struct Compiler<'a> {
+    code: Vec<Instr>,
+    /// Script-defined functions, which take precedence over variables without a short index.
+    #[cfg(not(feature = "no_function"))]
+    lib: &'a Module,
+    /// Name and number of parameters of the function being compiled, if any.
+    self_call: Option<(ImmutableString, usize)>,
+    #[cfg(feature = "no_function")]
+    dummy: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> Compiler<'a> {
+    /// Create a new [`Compiler`] for the statements and functions of an [`AST`].
+    #[inline(always)]
+    #[must_use]
+    fn new(_ast: &'a AST) -> Self {
+        Self {
+            code: Vec::new(),
+            #[cfg(not(feature = "no_function"))]
+            lib: _ast.shared_lib(),
+            self_call: None,
+            #[cfg(feature = "no_function")]
+            dummy: std::marker::PhantomData,
+        }
+    }
+    /// Add an instruction, returning its address.
+    #[inline(always)]
+    fn emit(&mut self, instr: Instr) -> usize {
+        self.code.push(instr);
+        self.code.len() - 1
+    }
+    /// Address of the next instruction.
+    #[inline(always)]
+    #[must_use]
+    fn here(&self) -> usize {
+        self.code.len()
+    }
+    /// Set the target of a jump instruction.
+    fn patch(&mut self, addr: usize, target: usize) {
+        match &mut self.code[addr] {
+            Instr::ShortCircuit(.., t) | Instr::JumpIf(.., t, _) | Instr::Jump(t) => *t = target,
+            instr => unreachable!("not a jump instruction: {:?}", instr),
+        }
+    }
+    /// Count an operation.
+    #[inline(always)]
+    fn inc_ops(&mut self, _pos: Position) {
+        #[cfg(not(feature = "unchecked"))]
+        self.emit(Instr::IncOps(_pos));
+    }
+    /// Consume fuel for a loop iteration.
+    #[inline(always)]
+    fn consume_fuel(&mut self, _pos: Position) {
+        #[cfg(not(feature = "unchecked"))]
+        self.emit(Instr::ConsumeFuel(_pos));
+    }
+    /// Compile a statements block.
+    fn compile_block(&mut self, statements: &[Stmt], restore_orig_state: bool) {
+        if statements.is_empty() {
+            self.emit(Instr::Push(Dynamic::UNIT));
+            return;
+        }
+
+        self.emit(Instr::EnterBlock(restore_orig_state));
+
+        for (i, stmt) in statements.iter().enumerate() {
+            if i > 0 {
+                self.emit(Instr::Pop);
+            }
+            self.compile_stmt(stmt, restore_orig_state);
+        }
+
+        self.emit(Instr::LeaveBlock);
+    }
+    /// Compile the body of a loop, discarding its value.
+    fn compile_loop_body(&mut self, statements: &[Stmt]) {
+        if !statements.is_empty() {
+            self.compile_block(statements, true);
+            self.emit(Instr::Pop);
+        }
+    }
+    /// Compile a statement.
+    fn compile_stmt(&mut self, stmt: &Stmt, rewind_scope: bool) {
+        match stmt {
+            Stmt::Noop(..) => {
+                self.inc_ops(stmt.position());
+                self.emit(Instr::Push(Dynamic::UNIT));
+            }
+
+            Stmt::Expr(expr) => {
+                self.inc_ops(stmt.position());
+                self.compile_expr(expr);
+                self.emit(Instr::Flatten);
+            }
+
+            Stmt::Block(statements) => {
+                self.inc_ops(stmt.position());
+                self.compile_block(statements.statements(), true);
+            }
+
+            Stmt::If(x, ..) => {
+                let (expr, if_block, else_block) = &**x;
+
+                self.inc_ops(stmt.position());
+                self.compile_expr(expr);
+                let jump_else = self.emit(Instr::JumpIf(false, 0, expr.position()));
+                self.compile_block(if_block.statements(), true);
+                let jump_end = self.emit(Instr::Jump(0));
+                self.patch(jump_else, self.here());
+                self.compile_block(else_block.statements(), true);
+                self.patch(jump_end, self.here());
+            }
+
+            // loop { ... }
+            Stmt::While(x, ..) if matches!(x.0, Expr::Unit(..)) => {
+                let (.., body) = &**x;
+
+                self.inc_ops(stmt.position());
+                let enter = self.emit(Instr::EnterLoop(0, 0, stmt.position()));
+                let top = self.here();
+                self.consume_fuel(body.position());
+                if body.is_empty() {
+                    self.inc_ops(body.position());
+                } else {
+                    self.compile_loop_body(body.statements());
+                }
+                self.emit(Instr::Jump(top));
+                self.code[enter] = Instr::EnterLoop(top, self.here(), stmt.position());
+                self.emit(Instr::LeaveLoop);
+                self.emit(Instr::Push(Dynamic::UNIT));
+            }
+
+            Stmt::While(x, ..) => {
+                let (expr, body) = &**x;
+
+                self.inc_ops(stmt.position());
+                let enter = self.emit(Instr::EnterLoop(0, 0, stmt.position()));
+                let top = self.here();
+                self.compile_expr(expr);
+                let jump_end = self.emit(Instr::JumpIf(false, 0, expr.position()));
+                self.consume_fuel(body.position());
+                self.compile_loop_body(body.statements());
+                self.emit(Instr::Jump(top));
+                self.patch(jump_end, self.here());
+                self.code[enter] = Instr::EnterLoop(top, self.here(), stmt.position());
+                self.emit(Instr::LeaveLoop);
+                self.emit(Instr::Push(Dynamic::UNIT));
+            }
+
+            // `continue` skips the condition, just like the tree-walking evaluator.
+            Stmt::Do(x, options, ..) => {
+                let (expr, body) = &**x;
+                let is_while = !options.contains(ASTFlags::NEGATED);
+
+                self.inc_ops(stmt.position());
+                let enter = self.emit(Instr::EnterLoop(0, 0, stmt.position()));
+                let top = self.here();
+                self.consume_fuel(body.position());
+                self.compile_loop_body(body.statements());
+                self.compile_expr(expr);
+                let jump_end = self.emit(Instr::JumpIf(!is_while, 0, expr.position()));
+                self.emit(Instr::Jump(top));
+                self.patch(jump_end, self.here());
+                self.code[enter] = Instr::EnterLoop(top, self.here(), stmt.position());
+                self.emit(Instr::LeaveLoop);
+                self.emit(Instr::Push(Dynamic::UNIT));
+            }
+
+            Stmt::For(x, ..) => {
+                let (var, counter, expr, body) = &**x;
+
+                self.inc_ops(stmt.position());
+                self.compile_expr(expr);
+                self.emit(Instr::Flatten);
+
+                let mut info = ForLoopInfo {
+                    x: (
+                        var.clone(),
+                        counter.clone(),
+                        expr.clone(),
+                        StmtBlock::empty(body.position()),
+                    ),
+                    pos: stmt.position(),
+                    continue_at: 0,
+                    break_at: 0,
+                };
+                let init = self.emit(Instr::Push(Dynamic::UNIT));
+                let top = self.emit(Instr::ForNext);
+                self.compile_loop_body(body.statements());
+                self.emit(Instr::Jump(top));
+
+                info.continue_at = top;
+                info.break_at = self.here();
+                self.code[init] = Instr::ForInit(info.into());
+                self.emit(Instr::LeaveLoop);
+                self.emit(Instr::Push(Dynamic::UNIT));
+            }
+
+            Stmt::Assignment(x) if matches!(x.1.lhs, Expr::Variable(..)) => {
+                let (op_info, ref expr) = **x;
+
+                self.inc_ops(stmt.position());
+                self.compile_expr(&expr.rhs);
+                self.emit(Instr::Flatten);
+                self.emit(Instr::AssignVar((op_info, expr.lhs.clone()).into()));
+            }
+
+            Stmt::Var(x, options, pos) => {
+                let (var_name, expr, index) = &**x;
+
+                self.inc_ops(stmt.position());
+                self.emit(Instr::CheckVar(var_name.clone().into(), *options, *pos));
+                self.compile_expr(expr);
+                self.emit(Instr::Flatten);
+                let var = (var_name.clone(), *index);
+                self.emit(Instr::DefineVar(var.into(), *options, rewind_scope));
+            }
+
+            Stmt::FnCall(x, ..) if Self::can_compile_call(x) => {
+                self.inc_ops(stmt.position());
+                self.compile_call(x);
+            }
+
+            // Return value of a call of a script-defined function to itself
+            Stmt::Return(Some(expr), options, ..)
+                if !options.contains(ASTFlags::BREAK)
+                    && matches!(**expr, Expr::FnCall(ref x, ..) if self.is_self_call(x)) =>
+            {
+                self.emit(Instr::Stmt(stmt.clone().into(), rewind_scope));
+            }
+
+            Stmt::Return(expr, options, pos) => {
+                self.inc_ops(stmt.position());
+                if let Some(expr) = expr {
+                    self.compile_expr(expr);
+                    self.emit(Instr::Flatten);
+                } else {
+                    self.emit(Instr::Push(Dynamic::UNIT));
+                }
+                if options.contains(ASTFlags::BREAK) {
+                    self.emit(Instr::Throw(*pos));
+                } else {
+                    self.emit(Instr::Return(*pos));
+                }
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            Stmt::Import(..) => {
+                self.emit(Instr::Import(stmt.clone().into(), rewind_scope));
+            }
+
+            _ => {
+                self.emit(Instr::Stmt(stmt.clone().into(), rewind_scope));
+            }
+        }
+    }
+    /// Compile an expression.
+    fn compile_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::DynamicConstant(..)
+            | Expr::IntegerConstant(..)
+            | Expr::CharConstant(..)
+            | Expr::StringConstant(..)
+            | Expr::BoolConstant(..)
+            | Expr::Unit(..) => {
+                let value = expr.get_literal_value().unwrap();
+                self.emit(Instr::Const(value, expr.position()));
+            }
+            #[cfg(not(feature = "no_float"))]
+            Expr::FloatConstant(..) => {
+                let value = expr.get_literal_value().unwrap();
+                self.emit(Instr::Const(value, expr.position()));
+            }
+
+            Expr::FnCall(x, ..) if x.is_native_operator && matches!(x.args.len(), 1 | 2) => {
+                self.inc_ops(expr.position());
+                for arg in &x.args {
+                    self.compile_expr(arg);
+                    self.emit(Instr::Flatten);
+                }
+                let op = (x.name.clone(), x.hashes, x.pos);
+                self.emit(Instr::Operator(op.into(), x.args.len()));
+            }
+
+            Expr::FnCall(x, ..) if Self::can_compile_call(x) => {
+                self.inc_ops(expr.position());
+                self.compile_call(x);
+            }
+
+            Expr::Variable(x, index, ..) => {
+                let offset = match index {
+                    Some(i) => i.get() as usize,
+                    // Script-defined functions with the same name take precedence
+                    None if self.has_script_fn(&x.3) => 0,
+                    None => x.0.map_or(0, NonZeroUsize::get),
+                };
+
+                if offset > 0 && expr.is_variable_access(true) {
+                    self.emit(Instr::LocalVar(expr.clone().into(), offset));
+                } else {
+                    self.emit(Instr::Var(expr.clone().into()));
+                }
+            }
+
+            Expr::And(x, ..) | Expr::Or(x, ..) => {
+                self.inc_ops(expr.position());
+                self.compile_expr(&x.lhs);
+                self.emit(Instr::ToBool(x.lhs.position()));
+                let is_or = matches!(expr, Expr::Or(..));
+                let jump_end = self.emit(Instr::ShortCircuit(is_or, 0));
+                self.compile_expr(&x.rhs);
+                self.emit(Instr::ToBool(x.rhs.position()));
+                self.patch(jump_end, self.here());
+            }
+
+            Expr::Stmt(x) => {
+                self.inc_ops(expr.position());
+                self.compile_block(x.statements(), true);
+            }
+
+            _ => {
+                self.emit(Instr::Expr(expr.clone().into()));
+            }
+        }
+    }
+    /// Can a function call be compiled?
+    ///
+    /// Namespace-qualified calls, calls capturing the parent scope and calls of keyword functions
+    /// are evaluated by the tree-walking evaluator.
+    #[must_use]
+    fn can_compile_call(x: &FnCallExpr) -> bool {
+        #[cfg(not(feature = "no_module"))]
+        if !x.namespace.is_empty() {
+            return false;
+        }
+        #[cfg(not(feature = "no_closure"))]
+        if x.name == crate::engine::KEYWORD_IS_SHARED {
+            return false;
+        }
+
+        !x.capture_parent_scope && !x.is_native_operator && !is_keyword_function(&x.name)
+    }
+    /// Is a function call a call of the function being compiled to itself?
+    #[must_use]
+    fn is_self_call(&self, x: &FnCallExpr) -> bool {
+        match self.self_call {
+            Some((ref name, num_params)) => {
+                Self::can_compile_call(x) && x.name == *name && x.args.len() == num_params
+            }
+            None => false,
+        }
+    }
+    /// Is there a script-defined function with a particular name?
+    #[must_use]
+    fn has_script_fn(&self, _name: &str) -> bool {
+        #[cfg(not(feature = "no_function"))]
+        if self.lib.iter_script_fn().any(|(_, _, f, ..)| f == _name) {
+            return true;
+        }
+
+        false
+    }
+    /// Compile a function call, without counting an operation.
+    ///
+    /// A variable as the first argument is passed by reference, except to the function being
+    /// compiled itself, whose arguments are all evaluated in order as for a tail call.
+    fn compile_call(&mut self, x: &FnCallExpr) {
+        let first = x
+            .args
+            .first()
+            .filter(|expr| expr.is_variable_access(false) && !self.is_self_call(x));
+        let args = if first.is_some() {
+            &x.args[1..]
+        } else {
+            &x.args[..]
+        };
+
+        for arg in args {
+            self.compile_expr(arg);
+            self.emit(Instr::Flatten);
+        }
+
+        let info = CallInfo {
+            name: x.name.clone(),
+            hashes: x.hashes,
+            num_args: args.len(),
+            #[cfg(not(feature = "no_function"))]
+            addr: x as *const FnCallExpr as usize,
+            pos: x.pos,
+        };
+
+        match first {
+            Some(expr) => self.emit(Instr::CallRef((info, expr.clone()).into())),
+            None => self.emit(Instr::Call(info.into())),
+        };
+    }
+}
+
+impl Engine {
+    /// Evaluate [`Bytecode`] with own scope.
+    ///
+    /// This mirrors `eval_ast_with_scope_raw` and `eval_global_statements`.
+    pub(crate) fn eval_bytecode_raw(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        bytecode: &Bytecode,
+        level: usize,
+    ) -> RhaiResult {
+        let ast = &bytecode.ast;
+
+        global.source = ast.source_raw().clone();
+
+        #[cfg(not(feature = "no_module"))]
+        let orig_embedded_module_resolver = std::mem::replace(
+            &mut global.embedded_module_resolver,
+            ast.resolver().cloned(),
+        );
+
+        if ast.statements().is_empty() {
+            return Ok(Dynamic::UNIT);
+        }
+
+        let mut _lib = &[
+            #[cfg(not(feature = "no_function"))]
+            ast.as_ref(),
+        ][..];
+        #[cfg(not(feature = "no_function"))]
+        if !ast.has_functions() {
+            _lib = &[];
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        let orig_compiled_fns = global.compiled_fns.replace(bytecode.fns.clone());
+
+        let mut caches = self.new_caches();
+        let this_ptr = &mut None;
+        let ctx = RunContext {
+            global,
+            caches: &mut caches,
+            lib: _lib,
+            level,
+        };
+        let result = self.run_bytecode(scope, this_ptr, ctx, &bytecode.code);
+
+        // Run any tasks still pending at the end of the evaluation
+        #[cfg(not(feature = "no_function"))]
+        let result = match result {
+            Ok(value) if level == 0 => self
+                .run_pending_tasks(global, &mut caches, _lib, level)
+                .map(|_| value),
+            result => result,
+        };
+
+        let result = result.or_else(|err| match *err {
+            ERR::Return(out, ..) => Ok(out),
+            ERR::LoopBreak(..) => {
+                unreachable!("no outer loop scope to break out of")
+            }
+            #[cfg(not(feature = "unchecked"))]
+            mut err => {
+                Self::add_limit_context(&mut err, scope, "", Position::NONE);
+                Err(err.into())
+            }
+            #[cfg(feature = "unchecked")]
+            _ => Err(err),
+        });
+        self.recycle_caches(&mut caches);
+
+        #[cfg(not(feature = "no_function"))]
+        {
+            global.compiled_fns = orig_compiled_fns;
+        }
+        #[cfg(not(feature = "no_module"))]
+        {
+            global.embedded_module_resolver = orig_embedded_module_resolver;
+        }
+
+        result
+    }
+
+    /// Restore the original state on leaving a statements block.
+    ///
+    /// This mirrors `eval_stmt_block`.
+    fn leave_bytecode_block(
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        state: BlockState,
+    ) {
+        caches.rewind_fn_resolution_caches(state.fn_resolution_caches_len);
+
+        if state.restore_orig_state {
+            scope.rewind(state.scope_len);
+            global.scope_level -= 1;
+            #[cfg(not(feature = "no_module"))]
+            global.truncate_imports(state.imports_len);
+            global.always_search_scope = state.always_search_scope;
+        }
+    }
+
+    /// Run bytecode instructions.
+    //
+    // # Implementation Notes
+    //
+    // Do not use the `?` operator within the main body as it makes this function return early,
+    // by-passing the cleanup of blocks and loops.
+    pub(crate) fn run_bytecode(
+        &self,
+        scope: &mut Scope,
+        this_ptr: &mut Option<&mut Dynamic>,
+        ctx: RunContext,
+        code: &[Instr],
+    ) -> RhaiResult {
+        let RunContext {
+            global,
+            caches,
+            lib,
+            level,
+        } = ctx;
+
+        let mut stack = Vec::<Dynamic>::new();
+        let mut frames = Vec::<Frame>::new();
+        let mut ip = 0;
+
+        while ip < code.len() {
+            let instr = &code[ip];
+            ip += 1;
+
+            let result = match instr {
+                Instr::Const(value, _pos) => {
+                    #[cfg(not(feature = "unchecked"))]
+                    let result = self.inc_operations(global, *_pos);
+                    #[cfg(feature = "unchecked")]
+                    let result = Ok(());
+
+                    result.map(|_| stack.push(value.clone()))
+                }
+                Instr::Push(value) => {
+                    stack.push(value.clone());
+                    Ok(())
+                }
+                Instr::Pop => {
+                    stack.pop();
+                    Ok(())
+                }
+                Instr::Flatten => {
+                    let value = stack.last_mut().unwrap();
+                    *value = std::mem::take(value).flatten();
+                    Ok(())
+                }
+                #[cfg(not(feature = "unchecked"))]
+                Instr::IncOps(pos) => self.inc_operations(global, *pos),
+                #[cfg(not(feature = "unchecked"))]
+                Instr::ConsumeFuel(pos) => {
+                    self.consume_fuel(global, self.fuel_costs().loop_iteration, *pos)
+                }
+
+                Instr::Expr(expr) => self
+                    .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
+                    .map(|v| stack.push(v)),
+                Instr::Stmt(stmt, rewind_scope) => self
+                    .eval_stmt(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        stmt,
+                        *rewind_scope,
+                        level,
+                    )
+                    .map(|v| stack.push(v)),
+
+                #[cfg(not(feature = "no_module"))]
+                Instr::Import(stmt, rewind_scope) => {
+                    let imports_len = global.num_imports();
+
+                    self.eval_stmt(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        stmt,
+                        *rewind_scope,
+                        level,
+                    )
+                    .map(|v| {
+                        stack.push(v);
+
+                        // Get the extra modules - see if any functions are marked global.
+                        // Without global functions, the extra modules never affect function resolution.
+                        if global
+                            .scan_imports_raw()
+                            .skip(imports_len)
+                            .any(|(.., m)| m.contains_indexed_global_functions())
+                        {
+                            let orig_fn_resolution_caches_len = match frames.last() {
+                                Some(Frame::Block(state)) => state.fn_resolution_caches_len,
+                                _ => unreachable!("`import` statement outside a block"),
+                            };
+
+                            if caches.fn_resolution_caches_len() > orig_fn_resolution_caches_len
+                                || !*rewind_scope
+                            {
+                                caches.clear_fn_resolution_cache();
+                            } else {
+                                caches.push_fn_resolution_cache();
+                            }
+                        }
+                    })
+                }
+
+                Instr::Operator(op, num_operands) => {
+                    let (name, hashes, pos) = &**op;
+
+                    let mut rhs = if *num_operands == 2 {
+                        stack.pop().unwrap()
+                    } else {
+                        Dynamic::UNIT
+                    };
+                    let mut lhs = stack.pop().unwrap();
+
+                    let mut operands = [&mut lhs, &mut rhs];
+                    let operands = &mut operands[..*num_operands];
+
+                    self.call_native_operator(
+                        global, caches, lib, name, *hashes, operands, *pos, level,
+                    )
+                    .map(|v| stack.push(v))
+                }
+
+                Instr::ToBool(pos) => {
+                    let value = stack.last_mut().unwrap();
+
+                    value
+                        .as_bool()
+                        .map(|b| *value = b.into())
+                        .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, *pos))
+                }
+                Instr::ShortCircuit(flag, target) => {
+                    if stack.last().unwrap().as_bool() == Ok(*flag) {
+                        ip = *target;
+                    } else {
+                        stack.pop();
+                    }
+                    Ok(())
+                }
+                Instr::JumpIf(flag, target, pos) => {
+                    let value = stack.pop().unwrap();
+
+                    value
+                        .as_bool()
+                        .map(|b| {
+                            if b == *flag {
+                                ip = *target;
+                            }
+                        })
+                        .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, *pos))
+                }
+                Instr::Jump(target) => {
+                    ip = *target;
+                    Ok(())
+                }
+
+                Instr::EnterBlock(restore_orig_state) => {
+                    frames.push(Frame::Block(BlockState {
+                        restore_orig_state: *restore_orig_state,
+                        always_search_scope: global.always_search_scope,
+                        scope_len: scope.len(),
+                        #[cfg(not(feature = "no_module"))]
+                        imports_len: global.num_imports(),
+                        fn_resolution_caches_len: caches.fn_resolution_caches_len(),
+                    }));
+
+                    if *restore_orig_state {
+                        global.scope_level += 1;
+                    }
+                    Ok(())
+                }
+                Instr::LeaveBlock => {
+                    match frames.pop() {
+                        Some(Frame::Block(state)) => {
+                            Self::leave_bytecode_block(scope, global, caches, state)
+                        }
+                        _ => unreachable!("not inside a block"),
+                    }
+                    Ok(())
+                }
+
+                Instr::EnterLoop(continue_at, break_at, pos) => {
+                    frames.push(Frame::Loop(LoopState {
+                        continue_at: *continue_at,
+                        break_at: *break_at,
+                        stack_len: stack.len(),
+                        pos: *pos,
+                        for_loop: None,
+                    }));
+                    Ok(())
+                }
+                Instr::ForInit(info) => {
+                    let iter_obj = stack.pop().unwrap();
+
+                    self.new_for_loop(scope, global, caches, lib, iter_obj, &info.x, level)
+                        .map(|state| {
+                            frames.push(Frame::Loop(LoopState {
+                                continue_at: info.continue_at,
+                                break_at: info.break_at,
+                                stack_len: stack.len(),
+                                pos: info.pos,
+                                for_loop: Some((state.into(), info)),
+                            }))
+                        })
+                }
+                Instr::ForNext => {
+                    let (state, info) = match frames.last_mut() {
+                        Some(Frame::Loop(LoopState {
+                            for_loop: Some((state, info)),
+                            ..
+                        })) => (state, *info),
+                        _ => unreachable!("not inside a `for` loop"),
+                    };
+
+                    self.next_for_iteration(scope, global, caches, lib, state, &info.x, level)
+                        .map(|has_next| {
+                            if !has_next {
+                                ip = info.break_at;
+                            }
+                        })
+                }
+                Instr::LeaveLoop => {
+                    match frames.pop() {
+                        Some(Frame::Loop(state)) => {
+                            if let Some((for_loop, ..)) = state.for_loop {
+                                scope.rewind(for_loop.orig_scope_len);
+                            }
+                        }
+                        _ => unreachable!("not inside a loop"),
+                    }
+                    Ok(())
+                }
+
+                Instr::AssignVar(x) => {
+                    let (op_info, lhs) = &**x;
+                    let rhs_val = stack.pop().unwrap();
+
+                    let ctx = RunContext {
+                        global,
+                        caches,
+                        lib,
+                        level,
+                    };
+                    self.assign_bytecode_var(scope, this_ptr, ctx, *op_info, lhs, rhs_val)
+                        .map(|_| stack.push(Dynamic::UNIT))
+                }
+
+                Instr::LocalVar(expr, offset)
+                    if !global.always_search_scope && self.resolve_var.is_none() =>
+                {
+                    #[cfg(not(feature = "unchecked"))]
+                    let result = self.inc_operations(global, expr.position());
+                    #[cfg(feature = "unchecked")]
+                    let result = Ok(());
+
+                    result.map(|_| {
+                        let value = scope.get_mut_by_index(scope.len() - offset).clone();
+                        stack.push(value);
+                    })
+                }
+                Instr::Var(expr) | Instr::LocalVar(expr, ..) => {
+                    #[cfg(not(feature = "unchecked"))]
+                    let result = self.inc_operations(global, expr.position());
+                    #[cfg(feature = "unchecked")]
+                    let result = Ok(());
+
+                    result
+                        .and_then(|_| {
+                            self.search_namespace(scope, global, lib, this_ptr, expr, level)
+                        })
+                        .map(|(value, ..)| stack.push(value.take_or_clone()))
+                }
+
+                Instr::CheckVar(var_name, options, pos) => self.check_new_var(
+                    scope, global, lib, this_ptr, var_name, *options, *pos, level,
+                ),
+                Instr::DefineVar(x, options, rewind_scope) => {
+                    let (var_name, index) = &**x;
+                    let value = stack.pop().unwrap();
+
+                    self.define_var(
+                        scope,
+                        global,
+                        lib,
+                        var_name,
+                        *index,
+                        *options,
+                        value,
+                        *rewind_scope,
+                    );
+                    stack.push(Dynamic::UNIT);
+                    Ok(())
+                }
+
+                Instr::Call(info) => {
+                    let args_at = stack.len() - info.num_args;
+                    let args = &mut stack[args_at..];
+
+                    let ctx = RunContext {
+                        global,
+                        caches,
+                        lib,
+                        level,
+                    };
+                    let result = self.call_bytecode_fn(ctx, info, None, args);
+                    stack.truncate(args_at);
+                    result.map(|v| stack.push(v))
+                }
+                Instr::CallRef(x) => {
+                    let (info, first_expr) = &**x;
+                    let args_at = stack.len() - info.num_args;
+                    let args = &mut stack[args_at..];
+
+                    let result = self
+                        .search_namespace(scope, global, lib, this_ptr, first_expr, level)
+                        .and_then(|(mut target, _pos)| {
+                            if target.is_read_only() {
+                                target = target.into_owned();
+                            }
+
+                            #[cfg(not(feature = "unchecked"))]
+                            self.inc_operations(global, _pos)?;
+
+                            #[cfg(not(feature = "no_closure"))]
+                            let target_is_shared = target.is_shared();
+                            #[cfg(feature = "no_closure")]
+                            let target_is_shared = false;
+
+                            let ctx = RunContext {
+                                global,
+                                caches,
+                                lib,
+                                level,
+                            };
+
+                            if target_is_shared || target.is_temp_value() {
+                                let first = &mut target.take_or_clone().flatten();
+                                self.call_bytecode_fn(ctx, info, Some((first, false)), args)
+                            } else {
+                                let first = target.take_ref().expect("ref");
+                                self.call_bytecode_fn(ctx, info, Some((first, true)), args)
+                            }
+                        });
+                    stack.truncate(args_at);
+                    result.map(|v| stack.push(v))
+                }
+
+                Instr::Return(pos) => Err(ERR::Return(stack.pop().unwrap(), *pos).into()),
+                Instr::Throw(pos) => Err(ERR::ErrorRuntime(stack.pop().unwrap(), *pos).into()),
+            };
+
+            let err = match result {
+                Ok(()) => continue,
+                Err(err) => err,
+            };
+
+            // Break out of, or continue, the innermost loop
+            if let ERR::LoopBreak(is_break, ..) = *err {
+                if frames.iter().any(|f| matches!(f, Frame::Loop(..))) {
+                    while let Some(Frame::Block(..)) = frames.last() {
+                        if let Some(Frame::Block(state)) = frames.pop() {
+                            Self::leave_bytecode_block(scope, global, caches, state);
+                        }
+                    }
+
+                    if let Some(Frame::Loop(state)) = frames.last() {
+                        stack.truncate(state.stack_len);
+                        ip = if is_break {
+                            state.break_at
+                        } else {
+                            state.continue_at
+                        };
+                    }
+                    continue;
+                }
+            }
+
+            // Unwind all blocks and loops
+            #[cfg(not(feature = "unchecked"))]
+            let mut err = err;
+
+            while let Some(frame) = frames.pop() {
+                match frame {
+                    Frame::Block(state) => {
+                        Self::leave_bytecode_block(scope, global, caches, state);
+                    }
+                    Frame::Loop(state) => {
+                        if let Some((for_loop, ..)) = state.for_loop {
+                            scope.rewind(for_loop.orig_scope_len);
+                        }
+                        #[cfg(not(feature = "unchecked"))]
+                        Self::add_limit_context(&mut err, scope, "", state.pos);
+                    }
+                }
+            }
+
+            return Err(err);
+        }
+
+        Ok(stack.pop().unwrap_or(Dynamic::UNIT))
+    }
+
+    /// Call a function with the first argument, if any, followed by the arguments from the stack.
+    ///
+    /// The flag of the first argument indicates whether it is passed by reference.
+    ///
+    /// This mirrors `eval_fn_call_expr` and `make_function_call`.
+    fn call_bytecode_fn(
+        &self,
+        ctx: RunContext,
+        info: &CallInfo,
+        first_arg: Option<(&mut Dynamic, bool)>,
+        arg_values: &mut [Dynamic],
+    ) -> RhaiResult {
+        let RunContext {
+            global,
+            caches,
+            lib,
+            level,
+        } = ctx;
+
+        // Call of a script-defined function to itself in tail position
+        #[cfg(not(feature = "no_function"))]
+        if let (None, Some(ref mut tail_call)) = (&first_arg, &mut global.tail_call) {
+            if tail_call.is_tail_addr(info.addr, level) {
+                tail_call.set_args(arg_values.iter_mut().map(mem::take).collect());
+                return Err(ERR::Return(Dynamic::UNIT, info.pos).into());
+            }
+        }
+
+        let mut args = FnArgsVec::with_capacity(arg_values.len() + 1);
+        let mut is_ref_mut = false;
+
+        if let Some((first, is_ref)) = first_arg {
+            args.push(first);
+            is_ref_mut = is_ref;
+        }
+        args.extend(arg_values.iter_mut());
+
+        self.exec_fn_call(
+            None,
+            global,
+            caches,
+            lib,
+            &info.name,
+            info.hashes,
+            &mut args,
+            is_ref_mut,
+            false,
+            0,
+            info.pos,
+            level,
+        )
+        .map(|(v, ..)| v)
+    }
+
+    /// Assign a value to a variable.
+    ///
+    /// This mirrors `eval_stmt`.
+    fn assign_bytecode_var(
+        &self,
+        scope: &mut Scope,
+        this_ptr: &mut Option<&mut Dynamic>,
+        ctx: RunContext,
+        op_info: OpAssignment,
+        lhs: &Expr,
+        rhs_val: Dynamic,
+    ) -> RhaiResultOf<()> {
+        let RunContext {
+            global,
+            caches,
+            lib,
+            level,
+        } = ctx;
+
+        let var_name = match lhs {
+            Expr::Variable(x, ..) => x.3.as_str(),
+            _ => unreachable!("`Expr::Variable` expected but gets {:?}", lhs),
+        };
+
+        // Variables in the frozen base layer of the scope are never written to
+        if lhs.is_variable_access(true) && scope.is_frozen(var_name) {
+            return Err(
+                ERR::ErrorAssignmentToConstant(var_name.to_string(), lhs.position()).into(),
+            );
+        }
+
+        let (mut lhs_ptr, pos) = self.search_namespace(scope, global, lib, this_ptr, lhs, level)?;
+
+        #[cfg(not(feature = "no_closure"))]
+        // Also handle case where target is a `Dynamic` shared value
+        // (returned by a variable resolver, for example)
+        let is_temp_result = !lhs_ptr.is_ref() && !lhs_ptr.is_shared();
+        #[cfg(feature = "no_closure")]
+        let is_temp_result = !lhs_ptr.is_ref();
+
+        // Cannot assign to temp result from expression, even if permitted
+        if is_temp_result {
+            self.allow_invalid_assignment(var_name, &rhs_val, pos)?;
+            return Err(ERR::ErrorAssignmentToConstant(var_name.to_string(), pos).into());
+        }
+
+        #[cfg(not(feature = "unchecked"))]
+        self.inc_operations(global, pos)?;
+
+        let root = (var_name, pos);
+
+        self.eval_op_assignment(
+            global,
+            caches,
+            lib,
+            op_info,
+            &mut lhs_ptr,
+            root,
+            rhs_val,
+            level,
+        )
+    }
+}
@@ -1,7 +1,11 @@
 //! System caches.
 
-use crate::func::{CallableFunction, StraightHashMap};
-use crate::{Identifier, StaticVec};
+use super::ScratchArena;
+use crate::func::{locked_read, CallableFunction, StraightHashMap};
+use crate::types::StringsInterner;
+use crate::{Dynamic, Engine, Identifier, ImmutableString, StaticVec};
+#[cfg(not(feature = "no_object"))]
+use std::any::TypeId;
 use std::marker::PhantomData;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -14,6 +18,9 @@ pub struct FnResolutionCacheEntry {
     pub func: CallableFunction,
     /// Optional source.
     pub source: Option<Box<Identifier>>,
+    /// Bit-mask of parameters (bit 0 = first parameter) borrowed by the function instead of being
+    /// taken by value.
+    pub borrowed_params: usize,
 }
 
 /// _(internals)_ A function resolution cache.
@@ -23,15 +30,47 @@ pub struct FnResolutionCacheEntry {
 /// level as possible.
 pub type FnResolutionCache = StraightHashMap<u64, Option<FnResolutionCacheEntry>>;
 
+/// An entry in the property access cache, recording the accessors found missing when a property
+/// is accessed on a particular type.
+#[cfg(not(feature = "no_object"))]
+#[derive(Debug, Clone)]
+struct PropertyCacheEntry {
+    /// Name of the property.
+    ///
+    /// Property ids are only unique within an [`AST`][crate::AST], so the name is compared (by
+    /// pointer) to make sure that the entry belongs to the same property.
+    name: ImmutableString,
+    /// Type of the object.
+    type_id: TypeId,
+    /// Is the getter missing?
+    no_getter: bool,
+    /// Is the setter missing?
+    no_setter: bool,
+}
+
 /// _(internals)_ A type containing system-wide caches.
 /// Exported under the `internals` feature only.
 ///
 /// The following caches are contained inside this type:
 /// * A stack of [function resolution caches][FnResolutionCache]
+/// * A cache of property getters and setters found missing, indexed by property id
+/// * An optional [scratch arena][ScratchArena] for evaluation temporaries
+/// * A [strings interner][StringsInterner] for strings created during evaluation, so that the
+///   [`Engine`]'s own interner is not locked
 #[derive(Debug, Clone)]
 pub struct Caches<'a> {
     /// Stack of [function resolution caches][FnResolutionCache].
     fn_resolution: StaticVec<FnResolutionCache>,
+    /// Getters and setters found missing, indexed by property id.
+    ///
+    /// Cleared whenever function resolution changes.
+    #[cfg(not(feature = "no_object"))]
+    properties: Vec<Option<PropertyCacheEntry>>,
+    /// Optional [scratch arena][ScratchArena] for evaluation temporaries.
+    arena: Option<ScratchArena>,
+    /// [Strings interner][StringsInterner] for strings created during evaluation, created on
+    /// first use.
+    strings: Option<StringsInterner<'static>>,
     /// Take care of the lifetime parameter.
     dummy: PhantomData<&'a ()>,
 }
@@ -43,6 +82,10 @@ impl Caches<'_> {
     pub const fn new() -> Self {
         Self {
             fn_resolution: StaticVec::new_const(),
+            #[cfg(not(feature = "no_object"))]
+            properties: Vec::new(),
+            arena: None,
+            strings: None,
             dummy: PhantomData,
         }
     }
@@ -67,10 +110,202 @@ impl Caches<'_> {
     #[inline(always)]
     pub fn push_fn_resolution_cache(&mut self) {
         self.fn_resolution.push(StraightHashMap::default());
+        #[cfg(not(feature = "no_object"))]
+        self.properties.clear();
+    }
+    /// Clear the current function resolution cache.
+    #[inline]
+    pub fn clear_fn_resolution_cache(&mut self) {
+        self.fn_resolution_cache_mut().clear();
+        #[cfg(not(feature = "no_object"))]
+        self.properties.clear();
     }
     /// Rewind the function resolution caches stack to a particular size.
-    #[inline(always)]
+    #[inline]
     pub fn rewind_fn_resolution_caches(&mut self, len: usize) {
-        self.fn_resolution.truncate(len);
+        if len < self.fn_resolution.len() {
+            self.fn_resolution.truncate(len);
+            #[cfg(not(feature = "no_object"))]
+            self.properties.clear();
+        }
+    }
+    /// Get the cache entry of a property accessed on a particular type.
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    fn property_entry(
+        &self,
+        id: usize,
+        name: &ImmutableString,
+        type_id: TypeId,
+    ) -> Option<&PropertyCacheEntry> {
+        match self.properties.get(id) {
+            Some(Some(entry)) if entry.type_id == type_id && entry.name.ptr_eq(name) => Some(entry),
+            _ => None,
+        }
+    }
+    /// Get a mutable reference to the cache entry of a property accessed on a particular type,
+    /// replacing any entry for another property or type with the same id.
+    #[cfg(not(feature = "no_object"))]
+    #[must_use]
+    fn property_entry_mut(
+        &mut self,
+        id: usize,
+        name: &ImmutableString,
+        type_id: TypeId,
+    ) -> &mut PropertyCacheEntry {
+        if self.properties.len() <= id {
+            self.properties.resize(id + 1, None);
+        }
+
+        let slot = &mut self.properties[id];
+
+        match slot {
+            Some(entry) if entry.type_id == type_id && entry.name.ptr_eq(name) => (),
+            _ => {
+                *slot = Some(PropertyCacheEntry {
+                    name: name.clone(),
+                    type_id,
+                    no_getter: false,
+                    no_setter: false,
+                })
+            }
+        }
+
+        slot.as_mut().unwrap()
+    }
+    /// Is the getter of a property known to be missing for a particular type?
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_getter_missing(
+        &self,
+        id: usize,
+        name: &ImmutableString,
+        type_id: TypeId,
+    ) -> bool {
+        self.property_entry(id, name, type_id)
+            .map_or(false, |e| e.no_getter)
+    }
+    /// Is the setter of a property known to be missing for a particular type?
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_setter_missing(
+        &self,
+        id: usize,
+        name: &ImmutableString,
+        type_id: TypeId,
+    ) -> bool {
+        self.property_entry(id, name, type_id)
+            .map_or(false, |e| e.no_setter)
+    }
+    /// Record that the getter of a property is missing for a particular type.
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    pub(crate) fn set_getter_missing(
+        &mut self,
+        id: usize,
+        name: &ImmutableString,
+        type_id: TypeId,
+    ) {
+        self.property_entry_mut(id, name, type_id).no_getter = true;
+    }
+    /// Record that the setter of a property is missing for a particular type.
+    #[cfg(not(feature = "no_object"))]
+    #[inline]
+    pub(crate) fn set_setter_missing(
+        &mut self,
+        id: usize,
+        name: &ImmutableString,
+        type_id: TypeId,
+    ) {
+        self.property_entry_mut(id, name, type_id).no_setter = true;
+    }
+    /// Get a mutable reference to the [scratch arena][ScratchArena], if any.
+    #[inline(always)]
+    #[must_use]
+    pub fn arena_mut(&mut self) -> Option<&mut ScratchArena> {
+        self.arena.as_mut()
+    }
+    /// Set the [scratch arena][ScratchArena].
+    #[inline(always)]
+    pub(crate) fn set_arena(&mut self, arena: ScratchArena) {
+        self.arena = Some(arena);
+    }
+    /// Take the [scratch arena][ScratchArena], if any.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn take_arena(&mut self) -> Option<ScratchArena> {
+        self.arena.take()
+    }
+    /// Take an empty string buffer, from the [scratch arena][ScratchArena] if there is one.
+    #[inline]
+    #[must_use]
+    pub(crate) fn take_string(&mut self) -> String {
+        self.arena
+            .as_mut()
+            .map_or_else(String::new, ScratchArena::take_string)
+    }
+    /// Convert a string buffer into an [`ImmutableString`], putting the buffer back into the
+    /// [scratch arena][ScratchArena] if there is one.
+    ///
+    /// With an arena, a long string keeps the buffer and a replacement with the same capacity is
+    /// put back, so the next string is built without reallocating.  A short string is stored
+    /// inline, so the buffer itself is put back.
+    #[inline]
+    #[must_use]
+    pub(crate) fn finish_string(&mut self, s: String) -> ImmutableString {
+        match self.arena {
+            Some(ref mut arena) if s.len() <= smartstring::MAX_INLINE => {
+                let result = s.as_str().into();
+                arena.put_string(s);
+                result
+            }
+            Some(ref mut arena) => {
+                arena.replace_string(s.capacity());
+                s.into()
+            }
+            None => s.into(),
+        }
+    }
+    /// Get an interned [string][ImmutableString] created during evaluation.
+    ///
+    /// The strings interner is local to this [`Caches`] and takes its settings from the
+    /// [`Engine`] when first used, so the [`Engine`] can be shared among threads without
+    /// contention.
+    #[inline]
+    #[must_use]
+    pub fn get_interned_string(
+        &mut self,
+        engine: &Engine,
+        text: impl AsRef<str> + Into<ImmutableString>,
+    ) -> ImmutableString {
+        if text.as_ref().is_empty() {
+            return engine.const_empty_string();
+        }
+
+        self.strings
+            .get_or_insert_with(|| {
+                let source = locked_read(&engine.interned_strings);
+                let mut strings = StringsInterner::new();
+                strings.capacity = source.capacity;
+                strings.max_string_len = source.max_string_len;
+                strings
+            })
+            .get(text)
+    }
+    /// Take an empty value buffer with at least the specified capacity, from the
+    /// [scratch arena][ScratchArena] if there is one.
+    #[inline]
+    #[must_use]
+    pub(crate) fn take_values(&mut self, capacity: usize) -> Option<Vec<Dynamic>> {
+        self.arena.as_mut().map(|arena| arena.take_values(capacity))
+    }
+    /// Put a value buffer back into the [scratch arena][ScratchArena], if there is one.
+    #[inline]
+    pub(crate) fn put_values(&mut self, values: Vec<Dynamic>) {
+        if let Some(ref mut arena) = self.arena {
+            arena.put_values(values);
+        }
     }
 }
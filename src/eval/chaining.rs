@@ -9,6 +9,15 @@ use std::hash::Hash;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// Property access - ((getter, hash), (setter, hash), prop, id)
+#[cfg(not(feature = "no_object"))]
+type PropertyAccess = (
+    (crate::ImmutableString, u64),
+    (crate::ImmutableString, u64),
+    crate::ImmutableString,
+    usize,
+);
+
 /// Method of chaining.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum ChainType {
@@ -266,33 +275,12 @@ impl Engine {
                         #[cfg(feature = "debugging")]
                         self.run_debugger(scope, global, lib, this_ptr, rhs, level)?;
 
-                        let ((getter, hash_get), (setter, hash_set), name) = &**x;
                         let (mut new_val, op_info) = new_val.expect("`Some`");
 
                         if op_info.is_op_assignment() {
-                            let args = &mut [target.as_mut()];
-                            let (mut orig_val, ..) = self
-                                .call_native_fn(
-                                    global, caches, lib, getter, *hash_get, args, is_ref_mut,
-                                    false, *pos, level,
-                                )
-                                .or_else(|err| match *err {
-                                    // Try an indexer if property does not exist
-                                    ERR::ErrorDotExpr(..) => {
-                                        let mut prop = name.into();
-                                        self.call_indexer_get(
-                                            global, caches, lib, target, &mut prop, level,
-                                        )
-                                        .map(|r| (r, false))
-                                        .map_err(|e| {
-                                            match *e {
-                                                ERR::ErrorIndexingType(..) => err,
-                                                _ => e,
-                                            }
-                                        })
-                                    }
-                                    _ => Err(err),
-                                })?;
+                            let mut orig_val = self.call_property_getter(
+                                global, caches, lib, target, x, is_ref_mut, *pos, level,
+                            )?;
 
                             {
                                 let orig_val = &mut (&mut orig_val).into();
@@ -305,57 +293,29 @@ impl Engine {
                             new_val = orig_val;
                         }
 
-                        let args = &mut [target.as_mut(), &mut new_val];
-                        self.call_native_fn(
-                            global, caches, lib, setter, *hash_set, args, is_ref_mut, false, *pos,
+                        self.call_property_setter(
+                            global,
+                            caches,
+                            lib,
+                            target,
+                            x,
+                            &mut new_val,
+                            is_ref_mut,
+                            *pos,
                             level,
+                            Err,
                         )
-                        .or_else(|err| match *err {
-                            // Try an indexer if property does not exist
-                            ERR::ErrorDotExpr(..) => {
-                                let idx = &mut name.into();
-                                let new_val = &mut new_val;
-                                self.call_indexer_set(
-                                    global, caches, lib, target, idx, new_val, is_ref_mut, level,
-                                )
-                                .map_err(|e| match *e {
-                                    ERR::ErrorIndexingType(..) => err,
-                                    _ => e,
-                                })
-                            }
-                            _ => Err(err),
-                        })
                     }
                     // xxx.id
                     Expr::Property(x, pos) => {
                         #[cfg(feature = "debugging")]
                         self.run_debugger(scope, global, lib, this_ptr, rhs, level)?;
 
-                        let ((getter, hash_get), _, name) = &**x;
-                        let args = &mut [target.as_mut()];
-                        self.call_native_fn(
-                            global, caches, lib, getter, *hash_get, args, is_ref_mut, false, *pos,
-                            level,
-                        )
-                        .map_or_else(
-                            |err| match *err {
-                                // Try an indexer if property does not exist
-                                ERR::ErrorDotExpr(..) => {
-                                    let mut prop = name.into();
-                                    self.call_indexer_get(
-                                        global, caches, lib, target, &mut prop, level,
-                                    )
-                                    .map(|r| (r, false))
-                                    .map_err(|e| match *e {
-                                        ERR::ErrorIndexingType(..) => err,
-                                        _ => e,
-                                    })
-                                }
-                                _ => Err(err),
-                            },
-                            // Assume getters are always pure
-                            |(v, ..)| Ok((v, false)),
+                        // Assume getters are always pure
+                        self.call_property_getter(
+                            global, caches, lib, target, x, is_ref_mut, *pos, level,
                         )
+                        .map(|v| (v, false))
                     }
                     // {xxx:map}.sub_lhs[expr] | {xxx:map}.sub_lhs.expr
                     Expr::Index(x, options, x_pos) | Expr::Dot(x, options, x_pos)
@@ -425,34 +385,12 @@ impl Engine {
                                 #[cfg(feature = "debugging")]
                                 self.run_debugger(scope, global, lib, this_ptr, _node, level)?;
 
-                                let ((getter, hash_get), (setter, hash_set), name) = &**p;
                                 let rhs_chain = rhs.into();
-                                let mut arg_values = [target.as_mut(), &mut Dynamic::UNIT.clone()];
-                                let args = &mut arg_values[..1];
 
                                 // Assume getters are always pure
-                                let (mut val, ..) = self
-                                    .call_native_fn(
-                                        global, caches, lib, getter, *hash_get, args, is_ref_mut,
-                                        false, pos, level,
-                                    )
-                                    .or_else(|err| match *err {
-                                        // Try an indexer if property does not exist
-                                        ERR::ErrorDotExpr(..) => {
-                                            let mut prop = name.into();
-                                            self.call_indexer_get(
-                                                global, caches, lib, target, &mut prop, level,
-                                            )
-                                            .map(|r| (r, false))
-                                            .map_err(
-                                                |e| match *e {
-                                                    ERR::ErrorIndexingType(..) => err,
-                                                    _ => e,
-                                                },
-                                            )
-                                        }
-                                        _ => Err(err),
-                                    })?;
+                                let mut val = self.call_property_getter(
+                                    global, caches, lib, target, p, is_ref_mut, pos, level,
+                                )?;
 
                                 let val = &mut (&mut val).into();
 
@@ -465,34 +403,19 @@ impl Engine {
 
                                 // Feed the value back via a setter just in case it has been updated
                                 if may_be_changed {
-                                    // Re-use args because the first &mut parameter will not be consumed
-                                    let mut arg_values = [target.as_mut(), val.as_mut()];
-                                    let args = &mut arg_values;
-                                    self.call_native_fn(
-                                        global, caches, lib, setter, *hash_set, args, is_ref_mut,
-                                        false, pos, level,
-                                    )
-                                    .or_else(
-                                        |err| match *err {
-                                            // Try an indexer if property does not exist
-                                            ERR::ErrorDotExpr(..) => {
-                                                let idx = &mut name.into();
-                                                let new_val = val;
-                                                self.call_indexer_set(
-                                                    global, caches, lib, target, idx, new_val,
-                                                    is_ref_mut, level,
-                                                )
-                                                .or_else(|e| match *e {
-                                                    // If there is no setter, no need to feed it
-                                                    // back because the property is read-only
-                                                    ERR::ErrorIndexingType(..) => {
-                                                        Ok((Dynamic::UNIT, false))
-                                                    }
-                                                    _ => Err(e),
-                                                })
-                                            }
-                                            _ => Err(err),
-                                        },
+                                    self.call_property_setter(
+                                        global,
+                                        caches,
+                                        lib,
+                                        target,
+                                        p,
+                                        val.as_mut(),
+                                        is_ref_mut,
+                                        pos,
+                                        level,
+                                        // If there is no setter, no need to feed it back because
+                                        // the property is read-only
+                                        |_| Ok((Dynamic::UNIT, false)),
                                     )?;
                                 }
 
@@ -608,18 +531,45 @@ impl Engine {
                 self.run_debugger(scope, global, lib, this_ptr, lhs, level)?;
 
                 #[cfg(not(feature = "unchecked"))]
-                self.inc_operations(&mut global.num_operations, *var_pos)?;
+                self.inc_operations(global, *var_pos)?;
+
+                // Variables in the frozen base layer of the scope are never written to
+                if new_val.is_some() && lhs.is_variable_access(true) && scope.is_frozen(&x.3) {
+                    return Err(ERR::ErrorAssignmentToConstant(x.3.to_string(), *var_pos).into());
+                }
 
-                let (mut target, ..) =
+                let (target, ..) =
                     self.search_namespace(scope, global, lib, this_ptr, lhs, level)?;
 
+                // Variables in the frozen base layer of the scope are returned as temporary
+                // shared values, so lock them in place instead of indexing into a copy
+                #[cfg(not(feature = "no_closure"))]
+                let mut frozen;
+                #[cfg(not(feature = "no_closure"))]
+                let mut target = if target.is_temp_value() && target.is_shared() {
+                    frozen = target.take_or_clone();
+                    Target::from(&mut frozen)
+                } else {
+                    target
+                };
+                #[cfg(feature = "no_closure")]
+                let mut target = target;
+
                 let obj_ptr = &mut target;
                 let root = (x.3.as_str(), *var_pos);
 
-                self.eval_dot_index_chain_helper(
+                let result = self.eval_dot_index_chain_helper(
                     global, caches, lib, &mut None, obj_ptr, root, expr, rhs, options, idx_values,
                     chain_type, level, new_val,
-                )
+                );
+
+                #[cfg(feature = "debugging")]
+                if let Ok((.., true)) = result {
+                    drop(target);
+                    global.debugger.trace_var(scope, lhs);
+                }
+
+                result
             }
             // {expr}.??? = ??? or {expr}[???] = ???
             _ if new_val.is_some() => unreachable!("cannot assign to an expression"),
@@ -657,7 +607,7 @@ impl Engine {
         level: usize,
     ) -> RhaiResultOf<()> {
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, expr.position())?;
+        self.inc_operations(global, expr.position())?;
 
         match expr {
             #[cfg(not(feature = "no_object"))]
@@ -772,7 +722,7 @@ impl Engine {
         let level = level + 1;
 
         self.call_native_fn(
-            global, caches, lib, fn_name, hash, args, true, false, pos, level,
+            global, caches, lib, fn_name, hash, args, true, false, 0, pos, level,
         )
         .map(|(r, ..)| r)
     }
@@ -797,10 +747,168 @@ impl Engine {
         let level = level + 1;
 
         self.call_native_fn(
-            global, caches, lib, fn_name, hash, args, is_ref_mut, false, pos, level,
+            global, caches, lib, fn_name, hash, args, is_ref_mut, false, 0, pos, level,
         )
     }
 
+    /// Call the getter of a property, falling back to the get indexer if there is no getter.
+    ///
+    /// A getter found missing for the type of the object is recorded under the property id, so
+    /// that later accesses go straight to the indexer.
+    #[cfg(not(feature = "no_object"))]
+    fn call_property_getter(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        target: &mut Dynamic,
+        prop: &PropertyAccess,
+        is_ref_mut: bool,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<Dynamic> {
+        let ((getter, hash_get), _, name, id) = prop;
+        let type_id = target.type_id();
+
+        if caches.is_getter_missing(*id, name, type_id) {
+            #[cfg(not(feature = "unchecked"))]
+            self.inc_operations(global, pos)?;
+
+            let mut idx = name.into();
+
+            match self.call_indexer_get(global, caches, lib, target, &mut idx, level) {
+                Err(err) if matches!(*err, ERR::ErrorIndexingType(..)) => (),
+                result => return result,
+            }
+
+            // No indexer either, so raise the error from the getter
+            let args = &mut [target];
+            return self
+                .call_native_fn(
+                    global, caches, lib, getter, *hash_get, args, is_ref_mut, false, 0, pos, level,
+                )
+                .map(|(v, ..)| v);
+        }
+
+        let args = &mut [target];
+
+        // Assume getters are always pure
+        let err = match self.call_native_fn(
+            global, caches, lib, getter, *hash_get, args, is_ref_mut, false, 0, pos, level,
+        ) {
+            Ok((v, ..)) => return Ok(v),
+            Err(err) if matches!(*err, ERR::ErrorDotExpr(..)) => err,
+            Err(err) => return Err(err),
+        };
+
+        // Try an indexer if property does not exist
+        if !self.unknown_property_handlers.contains_key(&type_id)
+            && self
+                .resolve_fn(
+                    global,
+                    caches,
+                    lib,
+                    getter,
+                    *hash_get,
+                    Some(args),
+                    true,
+                    false,
+                )
+                .is_none()
+        {
+            caches.set_getter_missing(*id, name, type_id);
+        }
+
+        let mut idx = name.into();
+
+        self.call_indexer_get(global, caches, lib, args[0], &mut idx, level)
+            .map_err(|e| match *e {
+                ERR::ErrorIndexingType(..) => err,
+                _ => e,
+            })
+    }
+
+    /// Call the setter of a property, falling back to the set indexer if there is no setter.
+    ///
+    /// If there is no indexer either, `if_not_found` is called with the error from the setter.
+    ///
+    /// A setter found missing for the type of the object is recorded under the property id, so
+    /// that later accesses go straight to the indexer.
+    #[cfg(not(feature = "no_object"))]
+    fn call_property_setter(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        target: &mut Dynamic,
+        prop: &PropertyAccess,
+        new_val: &mut Dynamic,
+        is_ref_mut: bool,
+        pos: Position,
+        level: usize,
+        if_not_found: impl FnOnce(crate::RhaiError) -> RhaiResultOf<(Dynamic, bool)>,
+    ) -> RhaiResultOf<(Dynamic, bool)> {
+        let (_, (setter, hash_set), name, id) = prop;
+        let type_id = target.type_id();
+
+        if caches.is_setter_missing(*id, name, type_id) {
+            #[cfg(not(feature = "unchecked"))]
+            self.inc_operations(global, pos)?;
+
+            let idx = &mut name.into();
+
+            match self
+                .call_indexer_set(global, caches, lib, target, idx, new_val, is_ref_mut, level)
+            {
+                Err(err) if matches!(*err, ERR::ErrorIndexingType(..)) => (),
+                result => return result,
+            }
+
+            // No indexer either, so raise the error from the setter
+            let args = &mut [target, new_val];
+            return match self.call_native_fn(
+                global, caches, lib, setter, *hash_set, args, is_ref_mut, false, 0, pos, level,
+            ) {
+                Err(err) if matches!(*err, ERR::ErrorDotExpr(..)) => if_not_found(err),
+                result => result,
+            };
+        }
+
+        let args = &mut [target, new_val];
+
+        let err = match self.call_native_fn(
+            global, caches, lib, setter, *hash_set, args, is_ref_mut, false, 0, pos, level,
+        ) {
+            Err(err) if matches!(*err, ERR::ErrorDotExpr(..)) => err,
+            result => return result,
+        };
+
+        // Try an indexer if property does not exist
+        if self
+            .resolve_fn(
+                global,
+                caches,
+                lib,
+                setter,
+                *hash_set,
+                Some(args),
+                true,
+                false,
+            )
+            .is_none()
+        {
+            caches.set_setter_missing(*id, name, type_id);
+        }
+
+        let [target, new_val] = args;
+        let idx = &mut name.into();
+
+        match self.call_indexer_set(global, caches, lib, target, idx, new_val, is_ref_mut, level) {
+            Err(e) if matches!(*e, ERR::ErrorIndexingType(..)) => if_not_found(err),
+            result => result,
+        }
+    }
+
     /// Get the value at the indexed position of a base type.
     /// [`Position`] in [`EvalAltResult`][crate::EvalAltResult] may be [`NONE`][Position::NONE] and should be set afterwards.
     fn get_indexed_mut<'t>(
@@ -816,7 +924,7 @@ impl Engine {
         level: usize,
     ) -> RhaiResultOf<Target<'t>> {
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, Position::NONE)?;
+        self.inc_operations(global, Position::NONE)?;
 
         match target {
             #[cfg(not(feature = "no_index"))]
@@ -876,6 +984,20 @@ impl Engine {
                 )
             }
 
+            #[cfg(not(feature = "no_index"))]
+            Dynamic(Union::Tuple(tuple, ..)) => {
+                // val_tuple[idx]
+                let index = idx
+                    .as_int()
+                    .map_err(|typ| self.make_type_mismatch_err::<crate::INT>(typ, idx_pos))?;
+                let len = tuple.len();
+                let tuple_idx = super::calc_index(len, index, true, || {
+                    ERR::ErrorArrayBounds(len, index, idx_pos).into()
+                })?;
+
+                Ok(tuple.get_mut(tuple_idx).map(Target::from).unwrap())
+            }
+
             #[cfg(not(feature = "no_index"))]
             Dynamic(Union::Int(value, ..))
                 if idx.is::<crate::ExclusiveRange>() || idx.is::<crate::InclusiveRange>() =>
@@ -0,0 +1,830 @@
+//! _(dap)_ Module implementing a [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/)
+//! server on top of the debugging interface.
+//! Exported under the `dap` feature only.
+//!
+//! A [`DebugAdapter`] registered into an [`Engine`] (via [`Engine::register_debug_adapter`]) maps
+//! the debugger hooks of the [`Engine`] to DAP requests and events, so that a DAP client (e.g.
+//! VS Code) can debug scripts run by the host application.  Messages are exchanged over a
+//! [`Transport`]: a [`StreamTransport`] (e.g. over a TCP stream) or an in-process
+//! [`ChannelTransport`].
+//!
+//! The host runs scripts as usual; the `launch` and `attach` requests do nothing other than
+//! recording the `stopOnEntry` argument.  Requests are processed when evaluation starts (until
+//! the `configurationDone` request) and whenever evaluation is stopped.  Requests sent while a
+//! script is running are processed at the next stop.
+//!
+//! Supported requests: `initialize`, `launch`, `attach`, `setBreakpoints` (with conditions and
+//! hit conditions), `setFunctionBreakpoints`, `setExceptionBreakpoints`, `configurationDone`,
+//! `threads`, `stackTrace`, `scopes`, `variables`, `evaluate`, `continue`, `next`, `stepIn`,
+//! `stepOut`, `disconnect` and `terminate`.  The `pause` request fails, as a running script
+//! cannot be interrupted.
+//!
+//! Not available under `no_position`.
+#![cfg(feature = "dap")]
+#![cfg(not(feature = "no_position"))]
+
+use super::{BreakPoint, Debugger, DebuggerCommand, DebuggerEvent, EvalContext, HitCondition};
+use crate::ast::ASTNode;
+use crate::func::Shared;
+use crate::{Dynamic, Engine, EvalAltResult, Position, RhaiResultOf, Scope};
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, PoisonError};
+
+/// The only thread reported to the client.
+const THREAD_ID: i64 = 1;
+
+/// Variables reference of the local variables in scope.
+const LOCALS_REF: usize = 1;
+
+/// Maximum size of the content of a message read by a [`StreamTransport`], in bytes.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Trait that encapsulates a transport for DAP messages.
+pub trait Transport: Send {
+    /// Receive the next message from the other end, blocking until one is available.
+    ///
+    /// Returns [`None`] if the other end is disconnected.
+    fn receive(&mut self) -> Option<Value>;
+
+    /// Send a message to the other end.
+    ///
+    /// Errors are ignored; a disconnected other end is detected when receiving.
+    fn send(&mut self, message: Value);
+}
+
+/// A [`Transport`] over a byte stream (e.g. a [`TcpStream`]), with messages framed by
+/// `Content-Length` headers as specified by the DAP.
+///
+/// Messages larger than 16MB are rejected, as if the other end disconnected.
+#[derive(Debug)]
+pub struct StreamTransport<R: Read, W: Write> {
+    /// Stream to read messages from.
+    reader: BufReader<R>,
+    /// Stream to write messages to.
+    writer: W,
+}
+
+impl<R: Read, W: Write> StreamTransport<R, W> {
+    /// Create a new [`StreamTransport`] reading from one stream and writing to another.
+    #[inline(always)]
+    #[must_use]
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+        }
+    }
+    /// Read the next message.
+    fn read_message(&mut self) -> io::Result<Value> {
+        let mut len = None;
+        let mut line = String::new();
+
+        // Headers, ending with an empty line
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let header = line.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("Content-Length") {
+                    len = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+
+        let len = len.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+        })?;
+        if len > MAX_MESSAGE_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "message too large",
+            ));
+        }
+        let mut content = vec![0; len];
+        self.reader.read_exact(&mut content)?;
+
+        serde_json::from_slice(&content)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl StreamTransport<TcpStream, TcpStream> {
+    /// Create a new [`StreamTransport`] over a [`TcpStream`].
+    #[inline]
+    pub fn from_tcp(stream: TcpStream) -> io::Result<Self> {
+        let writer = stream.try_clone()?;
+        Ok(Self::new(stream, writer))
+    }
+}
+
+impl<R: Read + Send, W: Write + Send> Transport for StreamTransport<R, W> {
+    fn receive(&mut self) -> Option<Value> {
+        self.read_message().ok()
+    }
+    fn send(&mut self, message: Value) {
+        let content = message.to_string();
+        let _ = write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        );
+        let _ = self.writer.flush();
+    }
+}
+
+/// An in-process [`Transport`], created in connected pairs by [`channel`].
+#[derive(Debug)]
+pub struct ChannelTransport {
+    /// Channel to send messages to the other end.
+    sender: Sender<Value>,
+    /// Channel to receive messages from the other end.
+    receiver: Receiver<Value>,
+}
+
+impl Transport for ChannelTransport {
+    #[inline(always)]
+    fn receive(&mut self) -> Option<Value> {
+        self.receiver.recv().ok()
+    }
+    #[inline(always)]
+    fn send(&mut self, message: Value) {
+        let _ = self.sender.send(message);
+    }
+}
+
+/// Create a pair of connected in-process [`ChannelTransport`]'s: one for the [`DebugAdapter`]
+/// and one for the client.
+#[must_use]
+pub fn channel() -> (ChannelTransport, ChannelTransport) {
+    let (tx1, rx1) = mpsc::channel();
+    let (tx2, rx2) = mpsc::channel();
+
+    (
+        ChannelTransport {
+            sender: tx1,
+            receiver: rx2,
+        },
+        ChannelTransport {
+            sender: tx2,
+            receiver: rx1,
+        },
+    )
+}
+
+/// A break-point set by the client, kept to be set again on each evaluation run.
+#[derive(Debug, Clone)]
+struct ClientBreakPoint {
+    /// The break-point.
+    bp: BreakPoint,
+    /// Condition expression, if any.
+    condition: Option<String>,
+    /// Hit condition, if any.
+    hit_condition: Option<String>,
+}
+
+/// What to do after processing a request.
+enum Next {
+    /// Wait for the next request.
+    Wait,
+    /// Resume evaluation.
+    Resume(DebuggerCommand),
+    /// Terminate evaluation.
+    Terminate,
+}
+
+/// State of a [`DebugAdapter`].
+struct State {
+    /// Transport to the client.
+    transport: Box<dyn Transport>,
+    /// Sequence number of the next message sent.
+    seq: i64,
+    /// Path of the main script, if any.
+    main_source: Option<String>,
+    /// Has the client finished configuring?
+    configured: bool,
+    /// Stop when evaluation starts?
+    stop_on_entry: bool,
+    /// Has the client disconnected?
+    detached: bool,
+    /// Break-points set by the client.
+    break_points: Vec<ClientBreakPoint>,
+    /// Values of expandable variables, by variables reference (less two) - reset at each stop.
+    handles: Vec<Dynamic>,
+}
+
+/// A [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/) server,
+/// serving a DAP client over a [`Transport`].
+///
+/// # Example
+///
+/// ```no_run
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use rhai::debugger::dap::{DebugAdapter, StreamTransport};
+/// use rhai::Engine;
+/// use std::net::TcpListener;
+///
+/// // Wait for the DAP client to connect
+/// let (stream, ..) = TcpListener::bind("127.0.0.1:4711")?.accept()?;
+///
+/// let mut engine = Engine::new();
+///
+/// engine.register_debug_adapter(
+///     DebugAdapter::new(StreamTransport::from_tcp(stream)?).with_main_source("script.rhai"),
+/// );
+///
+/// engine.run_file("script.rhai".into())?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DebugAdapter {
+    /// State of the adapter.
+    state: Shared<Mutex<State>>,
+    /// Is the adapter evaluating an expression (in which case the debugger must not stop)?
+    evaluating: Shared<AtomicBool>,
+}
+
+impl DebugAdapter {
+    /// Create a new [`DebugAdapter`] serving a client over a [`Transport`].
+    #[must_use]
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Self {
+            state: Shared::new(Mutex::new(State {
+                transport: Box::new(transport),
+                seq: 1,
+                main_source: None,
+                configured: false,
+                stop_on_entry: false,
+                detached: false,
+                break_points: Vec::new(),
+                handles: Vec::new(),
+            })),
+            evaluating: Shared::new(AtomicBool::new(false)),
+        }
+    }
+    /// Set the path of the main script (i.e. scripts with no source).
+    ///
+    /// Break-points that the client sets in this file are set in scripts with no source.  If this
+    /// is not set, all break-points at source lines are set in scripts with no source.
+    #[must_use]
+    pub fn with_main_source(self, path: impl Into<String>) -> Self {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .main_source = Some(path.into());
+        self
+    }
+}
+
+impl Engine {
+    /// _(dap)_ Register a [`DebugAdapter`] as the debugger of this [`Engine`], serving the Debug
+    /// Adapter Protocol to a client.
+    /// Exported under the `dap` feature only.
+    ///
+    /// This replaces any debugger registered via `register_debugger`.
+    #[allow(deprecated)]
+    pub fn register_debug_adapter(&mut self, adapter: DebugAdapter) -> &mut Self {
+        let DebugAdapter { state, evaluating } = adapter;
+
+        self.register_debugger(
+            |_| Dynamic::UNIT,
+            move |mut context, event, node, source, pos| {
+                // Do not stop while evaluating an expression for the client
+                if evaluating.load(Ordering::SeqCst) {
+                    return Ok(DebuggerCommand::Continue);
+                }
+
+                let state = &mut *state.lock().map_err(|err| {
+                    EvalAltResult::ErrorSystem("Debug adapter".into(), err.to_string().into())
+                })?;
+
+                if state.detached {
+                    return Ok(DebuggerCommand::Continue);
+                }
+
+                let source = source.unwrap_or("");
+                state.on_event(&mut context, &evaluating, event, node, source, pos)
+            },
+        )
+    }
+}
+
+impl State {
+    /// Send a message to the client.
+    fn send(&mut self, mut message: Value) {
+        message["seq"] = self.seq.into();
+        self.seq += 1;
+        self.transport.send(message);
+    }
+    /// Send an event to the client.
+    fn send_event(&mut self, event: &str, body: Value) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+    /// Send a response to a request.
+    fn respond(&mut self, request: &Value, result: Result<Value, String>) {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Value::Null) => (),
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = message.into(),
+        }
+        self.send(response);
+    }
+    /// Stop serving the client, letting evaluation run to the end.
+    fn detach(&mut self, debugger: &mut Debugger) {
+        self.detached = true;
+        debugger.clear_break_points();
+        debugger.clear_watches();
+    }
+    /// Handle an event of the debugger.
+    fn on_event(
+        &mut self,
+        context: &mut EvalContext,
+        evaluating: &AtomicBool,
+        event: DebuggerEvent,
+        node: ASTNode,
+        source: &str,
+        pos: Position,
+    ) -> RhaiResultOf<DebuggerCommand> {
+        let reason = match event {
+            DebuggerEvent::Start => {
+                if self.configured {
+                    self.apply_break_points(context);
+                } else {
+                    // Configuration phase
+                    match self.serve(context, evaluating, source, pos) {
+                        Ok(DebuggerCommand::Continue) if !self.detached && self.stop_on_entry => (),
+                        r => return r,
+                    }
+                }
+                if !self.stop_on_entry {
+                    return Ok(DebuggerCommand::Continue);
+                }
+                "entry"
+            }
+            DebuggerEvent::End => {
+                self.send_event("terminated", json!({}));
+                return Ok(DebuggerCommand::Continue);
+            }
+            DebuggerEvent::BreakPoint(index) => {
+                match context.global_runtime_state().debugger.break_points()[index] {
+                    // Stop only once at a line, at the statement
+                    BreakPoint::AtPosition { .. } if !matches!(node, ASTNode::Stmt(..)) => {
+                        return Ok(DebuggerCommand::Continue)
+                    }
+                    BreakPoint::AtFunctionName { .. } => "function breakpoint",
+                    BreakPoint::AtVariableChange { .. } => "data breakpoint",
+                    _ => "breakpoint",
+                }
+            }
+            _ => "step",
+        };
+
+        self.send_event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+
+        self.serve(context, evaluating, source, pos)
+    }
+    /// Process requests until evaluation is resumed.
+    ///
+    /// Before the client finishes configuring, evaluation is resumed by the `configurationDone`
+    /// request.
+    fn serve(
+        &mut self,
+        context: &mut EvalContext,
+        evaluating: &AtomicBool,
+        source: &str,
+        pos: Position,
+    ) -> RhaiResultOf<DebuggerCommand> {
+        self.handles.clear();
+
+        loop {
+            let request = match self.transport.receive() {
+                Some(request) => request,
+                None => {
+                    self.detach(&mut context.global_runtime_state_mut().debugger);
+                    return Ok(DebuggerCommand::Continue);
+                }
+            };
+
+            if request["type"] != "request" {
+                continue;
+            }
+
+            let (result, next) = self.handle(context, evaluating, &request, source, pos);
+            self.respond(&request, result);
+
+            if request["command"] == "initialize" {
+                self.send_event("initialized", json!({}));
+            }
+
+            match next {
+                Next::Wait => (),
+                Next::Resume(command) => return Ok(command),
+                Next::Terminate => {
+                    self.send_event("terminated", json!({}));
+                    self.detached = true;
+                    return Err(EvalAltResult::ErrorTerminated(Dynamic::UNIT, pos).into());
+                }
+            }
+        }
+    }
+    /// Handle a request.
+    fn handle(
+        &mut self,
+        context: &mut EvalContext,
+        evaluating: &AtomicBool,
+        request: &Value,
+        source: &str,
+        pos: Position,
+    ) -> (Result<Value, String>, Next) {
+        let args = &request["arguments"];
+
+        let result = match request["command"].as_str().unwrap_or("") {
+            "initialize" => {
+                let capabilities = json!({
+                    "supportsConfigurationDoneRequest": true,
+                    "supportsFunctionBreakpoints": true,
+                    "supportsConditionalBreakpoints": true,
+                    "supportsHitConditionalBreakpoints": true,
+                    "supportsEvaluateForHovers": true,
+                    "supportsTerminateRequest": true,
+                });
+                Ok(capabilities)
+            }
+            "launch" | "attach" => {
+                self.stop_on_entry = args["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(Value::Null)
+            }
+            "setBreakpoints" => Ok(self.set_break_points(context, args)),
+            "setFunctionBreakpoints" => Ok(self.set_function_break_points(context, args)),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "configurationDone" => {
+                self.configured = true;
+                return (Ok(Value::Null), Next::Resume(DebuggerCommand::Continue));
+            }
+            "threads" => Ok(json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" => Ok(self.stack_trace(context, source, pos)),
+            "scopes" => Ok(match args["frameId"].as_i64() {
+                Some(0) => json!({
+                    "scopes": [{ "name": "Locals", "variablesReference": LOCALS_REF, "expensive": false }]
+                }),
+                _ => json!({ "scopes": [] }),
+            }),
+            "variables" => Ok(self.variables(context, args)),
+            "evaluate" => self.evaluate(context, evaluating, args),
+            "pause" => Err("Cannot pause a running script".into()),
+            "continue" => {
+                return (
+                    Ok(json!({ "allThreadsContinued": true })),
+                    Next::Resume(DebuggerCommand::Continue),
+                )
+            }
+            "next" => return (Ok(Value::Null), Next::Resume(DebuggerCommand::Next)),
+            "stepIn" => return (Ok(Value::Null), Next::Resume(DebuggerCommand::StepInto)),
+            "stepOut" => return (Ok(Value::Null), Next::Resume(DebuggerCommand::FunctionExit)),
+            "terminate" => return (Ok(Value::Null), Next::Terminate),
+            "disconnect" => {
+                if args["terminateDebuggee"].as_bool().unwrap_or(false) {
+                    return (Ok(Value::Null), Next::Terminate);
+                }
+                self.detach(&mut context.global_runtime_state_mut().debugger);
+                return (Ok(Value::Null), Next::Resume(DebuggerCommand::Continue));
+            }
+            command => Err(format!("Unsupported request: {}", command)),
+        };
+
+        (result, Next::Wait)
+    }
+    /// Get the DAP `Source` of a script source (empty for the main script).
+    fn source_json(&self, source: &str) -> Value {
+        match (source, &self.main_source) {
+            ("", Some(path)) => json!({ "name": file_name(path), "path": path }),
+            ("", None) => json!({ "name": "main" }),
+            (path, ..) => json!({ "name": file_name(path), "path": path }),
+        }
+    }
+    /// Map the path of a DAP `Source` to a script source (empty for the main script).
+    fn map_source(&self, path: &str) -> String {
+        match self.main_source {
+            Some(ref main) if main != path => path.into(),
+            _ => String::new(),
+        }
+    }
+    /// Handle the `setBreakpoints` request.
+    fn set_break_points(&mut self, context: &mut EvalContext, args: &Value) -> Value {
+        let source = self.map_source(args["source"]["path"].as_str().unwrap_or(""));
+
+        self.break_points.retain(|b| match b.bp {
+            BreakPoint::AtPosition { source: ref s, .. } => *s != source,
+            _ => true,
+        });
+
+        let empty = Vec::new();
+        let requested = args["breakpoints"].as_array().unwrap_or(&empty);
+        let engine = context.engine();
+        let mut results = Vec::new();
+
+        for b in requested {
+            let line = b["line"].as_u64().unwrap_or(0);
+            let result = ClientBreakPoint::new(
+                BreakPoint::AtPosition {
+                    source: source.as_str().into(),
+                    pos: Position::new(line.min(u16::MAX as u64) as u16, 0),
+                    enabled: true,
+                },
+                b,
+            );
+            results.push(breakpoint_json(engine, &result, json!({ "line": line })));
+            self.break_points.push(result);
+        }
+
+        self.apply_break_points(context);
+
+        json!({ "breakpoints": results })
+    }
+    /// Handle the `setFunctionBreakpoints` request.
+    fn set_function_break_points(&mut self, context: &mut EvalContext, args: &Value) -> Value {
+        self.break_points
+            .retain(|b| !matches!(b.bp, BreakPoint::AtFunctionName { .. }));
+
+        let empty = Vec::new();
+        let requested = args["breakpoints"].as_array().unwrap_or(&empty);
+        let engine = context.engine();
+        let mut results = Vec::new();
+
+        for b in requested {
+            let result = ClientBreakPoint::new(
+                BreakPoint::AtFunctionName {
+                    name: b["name"].as_str().unwrap_or("").into(),
+                    enabled: true,
+                },
+                b,
+            );
+            results.push(breakpoint_json(engine, &result, json!({})));
+            self.break_points.push(result);
+        }
+
+        self.apply_break_points(context);
+
+        json!({ "breakpoints": results })
+    }
+    /// Set all the break-points set by the client into the debugger, replacing all others.
+    ///
+    /// Break-points with invalid conditions are disabled.
+    fn apply_break_points(&self, context: &mut EvalContext) {
+        let engine = context.engine();
+        let debugger = &mut context.global_runtime_state_mut().debugger;
+
+        debugger.clear_break_points();
+
+        for (index, b) in self.break_points.iter().enumerate() {
+            debugger.break_points_mut().push(b.bp.clone());
+
+            let valid = match b.condition {
+                Some(ref expr) => debugger
+                    .set_break_point_condition(engine, index, expr)
+                    .is_ok(),
+                None => true,
+            };
+            let valid = valid
+                && match b.hit_condition {
+                    Some(ref hits) => match parse_hit_condition(hits) {
+                        Some(h) => {
+                            debugger.set_break_point_hit_condition(index, h);
+                            true
+                        }
+                        None => false,
+                    },
+                    None => true,
+                };
+
+            if !valid {
+                debugger.break_points_mut()[index].enable(false);
+            }
+        }
+    }
+    /// Handle the `stackTrace` request.
+    fn stack_trace(&self, context: &EvalContext, source: &str, pos: Position) -> Value {
+        let call_stack = context.global_runtime_state().debugger.call_stack();
+        let mut frames = Vec::with_capacity(call_stack.len() + 1);
+
+        let fn_name = |level: usize| match level {
+            0 => "main".to_string(),
+            n => fn_display_name(&call_stack[n - 1].fn_name),
+        };
+
+        // The current frame
+        frames.push(json!({
+            "id": 0,
+            "name": fn_name(call_stack.len()),
+            "source": self.source_json(source),
+            "line": pos.line().unwrap_or(0),
+            "column": pos.position().unwrap_or(0),
+        }));
+
+        // Callers, at the positions of their calls
+        for level in (0..call_stack.len()).rev() {
+            let frame = &call_stack[level];
+
+            frames.push(json!({
+                "id": call_stack.len() - level,
+                "name": fn_name(level),
+                "source": self.source_json(&frame.source),
+                "line": frame.pos.line().unwrap_or(0),
+                "column": frame.pos.position().unwrap_or(0),
+            }));
+        }
+
+        json!({ "stackFrames": frames, "totalFrames": frames.len() })
+    }
+    /// Get the DAP `Variable` for a value, keeping it if it can be expanded.
+    fn variable_json(&mut self, engine: &Engine, name: &str, value: Dynamic) -> Value {
+        let expandable = match value.0 {
+            #[cfg(not(feature = "no_index"))]
+            crate::types::dynamic::Union::Array(ref a, ..) => !a.is_empty(),
+            #[cfg(not(feature = "no_object"))]
+            crate::types::dynamic::Union::Map(ref m, ..) => !m.is_empty(),
+            _ => false,
+        };
+
+        let json = json!({
+            "name": name,
+            "value": format!("{:?}", value),
+            "type": engine.map_type_name(value.type_name()),
+            "variablesReference": if expandable { self.handles.len() + LOCALS_REF + 1 } else { 0 },
+        });
+
+        if expandable {
+            self.handles.push(value);
+        }
+
+        json
+    }
+    /// Handle the `variables` request.
+    fn variables(&mut self, context: &EvalContext, args: &Value) -> Value {
+        let engine = context.engine();
+        let reference = args["variablesReference"].as_u64().unwrap_or(0) as usize;
+
+        let mut variables = Vec::new();
+
+        if reference == LOCALS_REF {
+            // Later variables shadow earlier ones with the same name
+            let scope: &Scope = context.scope();
+            let mut seen = BTreeSet::new();
+            let mut vars: Vec<_> = scope
+                .iter()
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .filter(|(name, ..)| seen.insert(name.to_string()))
+                .collect();
+            vars.reverse();
+
+            for (name, .., value) in vars {
+                variables.push(self.variable_json(engine, name, value));
+            }
+        } else if let Some(value) = reference
+            .checked_sub(LOCALS_REF + 1)
+            .and_then(|i| self.handles.get(i))
+            .cloned()
+        {
+            #[cfg(not(feature = "no_index"))]
+            if value.is::<crate::Array>() {
+                for (i, item) in value.into_array().unwrap().into_iter().enumerate() {
+                    variables.push(self.variable_json(engine, &format!("[{}]", i), item));
+                }
+                return json!({ "variables": variables });
+            }
+            #[cfg(not(feature = "no_object"))]
+            if value.is::<crate::Map>() {
+                for (key, item) in value.cast::<crate::Map>() {
+                    variables.push(self.variable_json(engine, &key, item));
+                }
+            }
+            #[cfg(feature = "no_index")]
+            #[cfg(feature = "no_object")]
+            let _ = value;
+        }
+
+        json!({ "variables": variables })
+    }
+    /// Handle the `evaluate` request, evaluating an expression in the current scope.
+    fn evaluate(
+        &mut self,
+        context: &mut EvalContext,
+        evaluating: &AtomicBool,
+        args: &Value,
+    ) -> Result<Value, String> {
+        let expr = args["expression"].as_str().unwrap_or("");
+
+        if args["frameId"].as_i64().map_or(false, |id| id != 0) {
+            return Err("Expressions can only be evaluated in the current frame".into());
+        }
+
+        let engine = context.engine();
+
+        evaluating.store(true, Ordering::SeqCst);
+        let result = engine.eval_expression_with_scope::<Dynamic>(context.scope_mut(), expr);
+        evaluating.store(false, Ordering::SeqCst);
+
+        let value = result.map_err(|err| err.to_string())?;
+        let variable = self.variable_json(engine, "", value);
+
+        Ok(json!({
+            "result": variable["value"],
+            "type": variable["type"],
+            "variablesReference": variable["variablesReference"],
+        }))
+    }
+}
+
+impl ClientBreakPoint {
+    /// Create a new [`ClientBreakPoint`] with the conditions in a DAP break-point request.
+    fn new(bp: BreakPoint, request: &Value) -> Self {
+        let text = |name: &str| {
+            request[name]
+                .as_str()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(Into::into)
+        };
+
+        Self {
+            bp,
+            condition: text("condition"),
+            hit_condition: text("hitCondition"),
+        }
+    }
+}
+
+/// Get the DAP `Breakpoint` for a [`ClientBreakPoint`], verifying its conditions.
+fn breakpoint_json(engine: &Engine, b: &ClientBreakPoint, mut json: Value) -> Value {
+    let error = match (&b.condition, &b.hit_condition) {
+        (Some(expr), ..) if engine.compile_expression(expr).is_err() => {
+            Some(format!("Invalid condition: {}", expr))
+        }
+        (.., Some(hits)) if parse_hit_condition(hits).is_none() => {
+            Some(format!("Invalid hit condition: {}", hits))
+        }
+        _ => None,
+    };
+
+    json["verified"] = error.is_none().into();
+    if let Some(message) = error {
+        json["message"] = message.into();
+    }
+    json
+}
+
+/// Parse a DAP hit condition: `N` or `== N`, `> N`, `>= N` or `% N`.
+#[must_use]
+fn parse_hit_condition(text: &str) -> Option<HitCondition> {
+    let text = text.trim();
+
+    let (op, n) = match text.find(|c: char| c.is_ascii_digit()) {
+        Some(i) => (text[..i].trim(), text[i..].trim().parse::<u64>().ok()?),
+        None => return None,
+    };
+
+    match op {
+        "" | "=" | "==" => Some(HitCondition::Equals(n)),
+        ">" => Some(HitCondition::AtLeast(n + 1)),
+        ">=" => Some(HitCondition::AtLeast(n)),
+        "%" => Some(HitCondition::Multiple(n)),
+        _ => None,
+    }
+}
+
+/// Get the file name of a path.
+#[must_use]
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Get the name of a function for display.
+#[must_use]
+fn fn_display_name(fn_name: &str) -> String {
+    #[cfg(not(feature = "no_function"))]
+    if let Some(name) = crate::parser::anonymous_fn_display_name(fn_name) {
+        return name;
+    }
+    fn_name.to_string()
+}
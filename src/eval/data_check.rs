@@ -1,8 +1,14 @@
 //! Data size checks during evaluation.
 #![cfg(not(feature = "unchecked"))]
 
+use super::GlobalRuntimeState;
+use crate::api::limits::Limits;
+use crate::engine::OP_CONCAT;
+use crate::tokenizer::is_valid_function_name;
 use crate::types::dynamic::Union;
-use crate::{Dynamic, Engine, Position, RhaiResultOf, ERR};
+use crate::{
+    Dynamic, Engine, EvalAltResult, ImmutableString, Position, RhaiResult, RhaiResultOf, Scope, ERR,
+};
 use std::num::NonZeroUsize;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -22,7 +28,7 @@ impl Engine {
             Union::Array(ref arr, ..) => {
                 arr.iter()
                     .fold((0, 0, 0), |(arrays, maps, strings), value| match value.0 {
-                        Union::Array(..) => {
+                        Union::Array(..) | Union::Tuple(..) => {
                             let (a, m, s) = Self::calc_data_sizes(value, false);
                             (arrays + a + 1, maps + m, strings + s)
                         }
@@ -38,6 +44,30 @@ impl Engine {
             }
             #[cfg(not(feature = "no_index"))]
             Union::Blob(ref arr, ..) => (arr.len(), 0, 0),
+            Union::Tuple(ref tuple, ..) => {
+                tuple
+                    .iter()
+                    .fold((0, 0, 0), |(arrays, maps, strings), value| match value.0 {
+                        #[cfg(not(feature = "no_index"))]
+                        Union::Array(..) => {
+                            let (a, m, s) = Self::calc_data_sizes(value, false);
+                            (arrays + a + 1, maps + m, strings + s)
+                        }
+                        #[cfg(not(feature = "no_index"))]
+                        Union::Blob(ref a, ..) => (arrays + 1 + a.len(), maps, strings),
+                        #[cfg(not(feature = "no_object"))]
+                        Union::Map(..) => {
+                            let (a, m, s) = Self::calc_data_sizes(value, false);
+                            (arrays + a + 1, maps + m, strings + s)
+                        }
+                        Union::Tuple(..) => {
+                            let (a, m, s) = Self::calc_data_sizes(value, false);
+                            (arrays + a + 1, maps + m, strings + s)
+                        }
+                        Union::Str(ref s, ..) => (arrays + 1, maps, strings + s.len()),
+                        _ => (arrays + 1, maps, strings),
+                    })
+            }
             #[cfg(not(feature = "no_object"))]
             Union::Map(ref map, ..) => {
                 map.values()
@@ -49,7 +79,7 @@ impl Engine {
                         }
                         #[cfg(not(feature = "no_index"))]
                         Union::Blob(ref a, ..) => (arrays + a.len(), maps, strings),
-                        Union::Map(..) => {
+                        Union::Map(..) | Union::Tuple(..) => {
                             let (a, m, s) = Self::calc_data_sizes(value, false);
                             (arrays + a, maps + m + 1, strings + s)
                         }
@@ -93,35 +123,46 @@ impl Engine {
         &self,
         sizes: (usize, usize, usize),
         pos: Position,
+    ) -> RhaiResultOf<()> {
+        Self::raise_err_if_over_size_limits(&self.limits, sizes, pos)
+    }
+
+    /// Raise an error if any data size exceeds a set of [`Limits`].
+    #[cfg(not(feature = "unchecked"))]
+    fn raise_err_if_over_size_limits(
+        limits: &Limits,
+        sizes: (usize, usize, usize),
+        pos: Position,
     ) -> RhaiResultOf<()> {
         let (_arr, _map, s) = sizes;
 
-        if s > self
-            .limits
-            .max_string_size
-            .map_or(usize::MAX, NonZeroUsize::get)
-        {
-            return Err(ERR::ErrorDataTooLarge("Length of string".to_string(), pos).into());
+        if s > limits.max_string_size.map_or(usize::MAX, NonZeroUsize::get) {
+            return Err(ERR::ErrorDataTooLarge(
+                "Length of string".to_string(),
+                Default::default(),
+                pos,
+            )
+            .into());
         }
 
         #[cfg(not(feature = "no_index"))]
-        if _arr
-            > self
-                .limits
-                .max_array_size
-                .map_or(usize::MAX, NonZeroUsize::get)
-        {
-            return Err(ERR::ErrorDataTooLarge("Size of array".to_string(), pos).into());
+        if _arr > limits.max_array_size.map_or(usize::MAX, NonZeroUsize::get) {
+            return Err(ERR::ErrorDataTooLarge(
+                "Size of array".to_string(),
+                Default::default(),
+                pos,
+            )
+            .into());
         }
 
         #[cfg(not(feature = "no_object"))]
-        if _map
-            > self
-                .limits
-                .max_map_size
-                .map_or(usize::MAX, NonZeroUsize::get)
-        {
-            return Err(ERR::ErrorDataTooLarge("Size of object map".to_string(), pos).into());
+        if _map > limits.max_map_size.map_or(usize::MAX, NonZeroUsize::get) {
+            return Err(ERR::ErrorDataTooLarge(
+                "Size of object map".to_string(),
+                Default::default(),
+                pos,
+            )
+            .into());
         }
 
         Ok(())
@@ -149,28 +190,242 @@ impl Engine {
         self.check_data_size(value, Position::NONE)
     }
 
+    /// Add [context][crate::LimitContext] to an error raised when a limit is exceeded.
+    ///
+    /// Context is added from the innermost loop or function outwards, so existing information is
+    /// never overwritten and nothing more is added after leaving a script-defined function.
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) fn add_limit_context(
+        err: &mut EvalAltResult,
+        scope: &Scope,
+        fn_name: &str,
+        loop_pos: Position,
+    ) {
+        /// Maximum number of variables to include in the context.
+        const MAX_VARIABLES: usize = 5;
+
+        let ctx = match err.limit_context_mut() {
+            Some(ctx) if ctx.fn_name.is_empty() => ctx,
+            _ => return,
+        };
+
+        if ctx.loop_pos.is_none() {
+            ctx.loop_pos = loop_pos;
+        }
+
+        if ctx.variables.is_empty() {
+            let mut variables: Vec<_> = scope
+                .iter_raw()
+                .map(|(name, _, value)| {
+                    let (a, m, s) = Self::calc_data_sizes(value, true);
+                    (name.to_string(), a + m + s)
+                })
+                .filter(|&(_, size)| size > 0)
+                .collect();
+
+            variables.sort_by(|(_, a), (_, b)| b.cmp(a));
+            variables.truncate(MAX_VARIABLES);
+            ctx.variables = variables;
+        }
+
+        ctx.fn_name = fn_name.to_string();
+    }
+
+    /// Consume an amount of fuel and check that the fuel limit is not exceeded.
+    ///
+    /// Does nothing if there is no fuel limit.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline]
+    pub(crate) fn consume_fuel(
+        &self,
+        global: &mut GlobalRuntimeState,
+        fuel: u64,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        if fuel == 0 || (self.fuel_limit() == 0 && global.nested_eval.is_none()) {
+            return Ok(());
+        }
+
+        global.fuel_consumed = global.fuel_consumed.saturating_add(fuel);
+
+        // Guard against running out of fuel
+        if self.fuel_limit() > 0 && global.fuel_consumed > self.fuel_limit() {
+            return Err(ERR::ErrorTooManyOperations(Default::default(), pos).into());
+        }
+
+        self.check_nested_budget(global, pos)
+    }
+
+    /// Start a nested evaluation (i.e. of an interpolated string or of an expression in a custom
+    /// syntax), which is subject to the [nested limits][Engine::set_nested_limits], if any.
+    ///
+    /// Returns `true` if this is the outermost nested evaluation, which must then be ended via
+    /// [`end_nested_eval`][Engine::end_nested_eval].
+    #[cfg(not(feature = "unchecked"))]
+    #[inline]
+    pub(crate) fn start_nested_eval(&self, global: &mut GlobalRuntimeState, level: usize) -> bool {
+        if self.nested_limits.is_none() || global.nested_eval.is_some() {
+            return false;
+        }
+
+        global.nested_eval = Some((global.num_operations, global.fuel_consumed, level));
+        true
+    }
+
+    /// End a nested evaluation started via [`start_nested_eval`][Engine::start_nested_eval],
+    /// checking the size of its result against the [nested limits][Engine::set_nested_limits].
+    #[cfg(not(feature = "unchecked"))]
+    #[inline]
+    pub(crate) fn end_nested_eval(
+        &self,
+        global: &mut GlobalRuntimeState,
+        outermost: bool,
+        result: RhaiResult,
+        pos: Position,
+    ) -> RhaiResult {
+        if !outermost {
+            return result;
+        }
+
+        global.nested_eval = None;
+
+        let value = result?;
+
+        if let Some(ref limits) = self.nested_limits {
+            let sizes = Self::calc_data_sizes(&value, true);
+            Self::raise_err_if_over_size_limits(limits, sizes, pos)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Check that the operations and fuel consumed by the nested evaluation in progress, if any,
+    /// stay within the [nested limits][Engine::set_nested_limits].
+    #[cfg(not(feature = "unchecked"))]
+    fn check_nested_budget(&self, global: &GlobalRuntimeState, pos: Position) -> RhaiResultOf<()> {
+        if let (Some((operations, fuel, ..)), Some(limits)) =
+            (global.nested_eval, &self.nested_limits)
+        {
+            let operations = global.num_operations.saturating_sub(operations);
+            let fuel = global.fuel_consumed.saturating_sub(fuel);
+
+            if limits
+                .max_operations
+                .map_or(false, |max| operations > max.get())
+                || limits.fuel_limit.map_or(false, |max| fuel > max.get())
+            {
+                return Err(ERR::ErrorTooManyOperations(Default::default(), pos).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the level of function calls within the nested evaluation in progress, if any,
+    /// stays within the [nested limits][Engine::set_nested_limits].
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn check_nested_call_level(
+        &self,
+        global: &GlobalRuntimeState,
+        level: usize,
+        pos: Position,
+    ) -> RhaiResultOf<()> {
+        if let (Some((.., base)), Some(limits)) = (global.nested_eval, &self.nested_limits) {
+            if level.saturating_sub(base) > limits.max_call_stack_depth {
+                return Err(ERR::ErrorStackOverflow(Default::default(), pos).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Calculate the additional fuel cost of a native function call, on top of the cost of the
+    /// operation itself.
+    ///
+    /// `first_arg` is the first argument after the call, which is checked for op-assignments.
+    #[cfg(not(feature = "unchecked"))]
+    #[must_use]
+    pub(crate) fn calc_native_call_fuel(
+        &self,
+        name: &str,
+        result: &Dynamic,
+        first_arg: Option<&Dynamic>,
+    ) -> u64 {
+        let costs = self.fuel_costs();
+
+        match name {
+            "+" if result.is::<ImmutableString>() => costs.string_concat,
+            OP_CONCAT if first_arg.map_or(false, Dynamic::is::<ImmutableString>) => {
+                costs.string_concat
+            }
+            _ if is_valid_function_name(name) => costs.function_call,
+            _ => 0,
+        }
+    }
+
     /// Check if the number of operations stay within limit.
     #[cfg(not(feature = "unchecked"))]
     pub(crate) fn inc_operations(
         &self,
-        num_operations: &mut u64,
+        global: &mut GlobalRuntimeState,
         pos: Position,
     ) -> RhaiResultOf<()> {
-        *num_operations += 1;
+        global.num_operations += 1;
 
         // Guard against too many operations
-        if self.max_operations() > 0 && *num_operations > self.max_operations() {
-            return Err(ERR::ErrorTooManyOperations(pos).into());
+        if self.max_operations() > 0 && global.num_operations > self.max_operations() {
+            return Err(ERR::ErrorTooManyOperations(Default::default(), pos).into());
+        }
+
+        self.check_nested_budget(global, pos)?;
+
+        self.consume_fuel(global, self.fuel_costs().operation, pos)?;
+
+        // Stop if the asynchronous evaluation has been cancelled
+        #[cfg(feature = "async")]
+        #[cfg(not(feature = "no_std"))]
+        #[cfg(not(target_family = "wasm"))]
+        if global
+            .async_bridge
+            .as_ref()
+            .map_or(false, |bridge| bridge.is_cancelled())
+        {
+            return Err(ERR::ErrorTerminated("cancelled".into(), pos).into());
         }
 
         // Report progress - only in steps
         if let Some(ref progress) = self.progress {
-            if let Some(token) = progress(*num_operations) {
+            if let Some(token) = progress(global.num_operations) {
                 // Terminate script if progress returns a termination token
                 return Err(ERR::ErrorTerminated(token, pos).into());
             }
         }
 
+        // Report progress with information - only at the sampling interval
+        if let Some((interval, ref progress)) = self.progress_sampled {
+            if global.num_operations % interval == 0 {
+                let info = crate::ProgressInfo {
+                    num_operations: global.num_operations,
+                    position: pos,
+                    #[cfg(not(feature = "no_function"))]
+                    fn_name: global.call_stack.last().map_or("", |f| f.fn_name.as_str()),
+                    #[cfg(feature = "no_function")]
+                    fn_name: "",
+                    source: if global.source.is_empty() {
+                        None
+                    } else {
+                        Some(global.source.as_str())
+                    },
+                };
+
+                if let Some(token) = progress(&info) {
+                    // Terminate script if progress returns a termination token
+                    return Err(ERR::ErrorTerminated(token, pos).into());
+                }
+            }
+        }
+
         Ok(())
     }
 }
@@ -1,12 +1,20 @@
 //! Module defining the debugging interface.
 #![cfg(feature = "debugging")]
 
-use super::{EvalContext, GlobalRuntimeState};
-use crate::ast::{ASTNode, Expr, Stmt};
-use crate::{Dynamic, Engine, EvalAltResult, Identifier, Module, Position, RhaiResultOf, Scope};
+use super::{Caches, EvalContext, GlobalRuntimeState};
+use crate::ast::{ASTNode, Expr, Stmt, StmtBlockContainer};
+use crate::{
+    Dynamic, Engine, EvalAltResult, Identifier, Module, ParseError, Position, RhaiResultOf, Scope,
+    StaticVec,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{fmt, iter::repeat, mem};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::repeat,
+    mem,
+};
 
 /// Callback function to initialize the debugger.
 #[cfg(not(feature = "sync"))]
@@ -136,6 +144,16 @@ pub enum BreakPoint {
         /// Is the break-point enabled?
         enabled: bool,
     },
+    /// Break at the next statement after the value of a particular variable in scope changes
+    /// (i.e. a watch-point).
+    ///
+    /// Changes are only detected while the variable stays in scope.
+    AtVariableChange {
+        /// Variable name.
+        name: Identifier,
+        /// Is the break-point enabled?
+        enabled: bool,
+    },
 }
 
 impl fmt::Display for BreakPoint {
@@ -194,6 +212,13 @@ impl fmt::Display for BreakPoint {
                 }
                 Ok(())
             }
+            Self::AtVariableChange { name: var, enabled } => {
+                write!(f, "watch {}", var)?;
+                if !*enabled {
+                    f.write_str(" (disabled)")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -209,6 +234,7 @@ impl BreakPoint {
             Self::AtFunctionName { enabled, .. } | Self::AtFunctionCall { enabled, .. } => *enabled,
             #[cfg(not(feature = "no_object"))]
             Self::AtProperty { enabled, .. } => *enabled,
+            Self::AtVariableChange { enabled, .. } => *enabled,
         }
     }
     /// Enable/disable this [`BreakPoint`].
@@ -222,6 +248,159 @@ impl BreakPoint {
             }
             #[cfg(not(feature = "no_object"))]
             Self::AtProperty { enabled, .. } => *enabled = value,
+            Self::AtVariableChange { enabled, .. } => *enabled = value,
+        }
+    }
+    /// Is this [`BreakPoint`] triggered by a particular [`AST` Node][ASTNode] under a particular
+    /// source, regardless of whether it is enabled?
+    #[must_use]
+    fn is_triggered_by(&self, src: &str, node: ASTNode) -> bool {
+        let _src = src;
+
+        match self {
+            #[cfg(not(feature = "no_position"))]
+            Self::AtPosition { pos, .. } if pos.is_none() => false,
+            #[cfg(not(feature = "no_position"))]
+            Self::AtPosition { source, pos, .. } if pos.is_beginning_of_line() => {
+                node.position().line().unwrap_or(0) == pos.line().unwrap() && _src == source
+            }
+            #[cfg(not(feature = "no_position"))]
+            Self::AtPosition { source, pos, .. } => node.position() == *pos && _src == source,
+            Self::AtFunctionName { name, .. } => match node {
+                ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
+                    x.name == *name
+                }
+                ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
+                    Expr::FnCall(x, ..) => x.name == *name,
+                    _ => false,
+                },
+                _ => false,
+            },
+            Self::AtFunctionCall { name, args, .. } => match node {
+                ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
+                    x.args.len() == *args && x.name == *name
+                }
+                ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
+                    Expr::FnCall(x, ..) => x.args.len() == *args && x.name == *name,
+                    _ => false,
+                },
+                _ => false,
+            },
+            #[cfg(not(feature = "no_object"))]
+            Self::AtProperty { name, .. } => match node {
+                ASTNode::Expr(Expr::Property(x, ..)) => x.2 == *name,
+                _ => false,
+            },
+            // Changes to variables are checked at every statement
+            Self::AtVariableChange { .. } => matches!(node, ASTNode::Stmt(..)),
+        }
+    }
+}
+
+/// A condition on the number of hits of a [`BreakPoint`] for it to trigger.
+///
+/// A hit is counted each time the break-point is reached and its condition expression (if any)
+/// evaluates to `true`.  Hits are counted from 1.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HitCondition {
+    /// Trigger only on a particular hit.
+    Equals(u64),
+    /// Trigger on a particular hit and all hits after it.
+    AtLeast(u64),
+    /// Trigger on every hit that is a multiple of a number.
+    Multiple(u64),
+}
+
+impl fmt::Display for HitCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equals(n) => write!(f, "hits == {}", n),
+            Self::AtLeast(n) => write!(f, "hits >= {}", n),
+            Self::Multiple(n) => write!(f, "hits % {} == 0", n),
+        }
+    }
+}
+
+impl HitCondition {
+    /// Is this [`HitCondition`] satisfied by a particular number of hits?
+    #[inline]
+    #[must_use]
+    pub const fn is_satisfied(self, hits: u64) -> bool {
+        match self {
+            Self::Equals(n) => hits == n,
+            Self::AtLeast(n) => hits >= n,
+            Self::Multiple(0) => false,
+            Self::Multiple(n) => hits % n == 0,
+        }
+    }
+}
+
+/// Conditions and hit count of a [`BreakPoint`].
+#[derive(Debug, Clone, Default)]
+struct BreakPointConditions {
+    /// Source text and compiled statements of the condition expression, if any.
+    condition: Option<(Identifier, StmtBlockContainer)>,
+    /// Condition on the number of hits, if any.
+    hit_condition: Option<HitCondition>,
+    /// Number of hits so far.
+    hits: u64,
+    /// Last value of the watched variable, if any.
+    value: Option<Dynamic>,
+}
+
+impl Hash for BreakPointConditions {
+    /// The last value of the watched variable is not hashed, because it may be a custom type.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.condition.hash(state);
+        self.hit_condition.hash(state);
+        self.hits.hash(state);
+    }
+}
+
+/// A watch expression, re-evaluated each time the debugger callback is run.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    /// Source text of the expression.
+    expr: Identifier,
+    /// Compiled statements of the expression.
+    statements: StmtBlockContainer,
+    /// Result (or error message) of the last evaluation, if any.
+    value: Option<Result<Dynamic, String>>,
+}
+
+impl Hash for Watch {
+    /// The result of the last evaluation is not hashed, because it may be a custom type.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.expr.hash(state);
+        self.statements.hash(state);
+    }
+}
+
+impl Watch {
+    /// Get the source text of the watch expression.
+    #[inline(always)]
+    #[must_use]
+    pub fn expr(&self) -> &str {
+        &self.expr
+    }
+    /// Get the result of the last evaluation of the watch expression (or the error message if it
+    /// failed), or [`None`] if it has not been evaluated yet.
+    #[inline]
+    #[must_use]
+    pub fn value(&self) -> Option<Result<&Dynamic, &str>> {
+        self.value
+            .as_ref()
+            .map(|r| r.as_ref().map_err(String::as_str))
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value {
+            Some(Ok(ref value)) => write!(f, "{} = {:?}", self.expr, value),
+            Some(Err(ref err)) => write!(f, "{} = <{}>", self.expr, err),
+            None => write!(f, "{} = ?", self.expr),
         }
     }
 }
@@ -241,7 +420,12 @@ pub struct CallStackFrame {
 
 impl fmt::Display for CallStackFrame {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut fp = f.debug_tuple(&self.fn_name);
+        #[cfg(not(feature = "no_function"))]
+        let name = crate::parser::anonymous_fn_display_name(&self.fn_name);
+        #[cfg(feature = "no_function")]
+        let name: Option<String> = None;
+
+        let mut fp = f.debug_tuple(name.as_deref().unwrap_or(&self.fn_name));
 
         for arg in &self.args {
             fp.field(arg);
@@ -262,16 +446,35 @@ impl fmt::Display for CallStackFrame {
 }
 
 /// A type providing debugging facilities.
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone)]
 pub struct Debugger {
     /// The current status command.
     pub(crate) status: DebuggerStatus,
     /// The current set of break-points.
     break_points: Vec<BreakPoint>,
+    /// Conditions of break-points, by position in `break_points`.
+    conditions: Vec<BreakPointConditions>,
+    /// The current set of watch expressions.
+    watches: Vec<Watch>,
     /// The current function call stack.
     call_stack: Vec<CallStackFrame>,
     /// The current state.
     state: Dynamic,
+    /// The evaluation [`Trace`][super::Trace] being recorded, if any.
+    trace: Option<Box<super::Trace>>,
+}
+
+impl Hash for Debugger {
+    /// The evaluation [`Trace`][super::Trace] being recorded is not hashed, because the values of
+    /// variables may be custom types.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.status.hash(state);
+        self.break_points.hash(state);
+        self.conditions.hash(state);
+        self.watches.hash(state);
+        self.call_stack.hash(state);
+        self.state.hash(state);
+    }
 }
 
 impl Debugger {
@@ -282,8 +485,11 @@ impl Debugger {
         Self {
             status,
             break_points: Vec::new(),
+            conditions: Vec::new(),
+            watches: Vec::new(),
             call_stack: Vec::new(),
             state,
+            trace: None,
         }
     }
     /// Get the current call stack.
@@ -334,53 +540,13 @@ impl Debugger {
             }
         }
     }
-    /// Returns the first break-point triggered by a particular [`AST` Node][ASTNode].
+    /// Returns the first enabled break-point triggered by a particular [`AST` Node][ASTNode],
+    /// ignoring conditions.
     #[must_use]
     pub fn is_break_point(&self, src: &str, node: ASTNode) -> Option<usize> {
-        let _src = src;
-
         self.break_points()
             .iter()
-            .enumerate()
-            .filter(|&(.., bp)| bp.is_enabled())
-            .find(|&(.., bp)| match bp {
-                #[cfg(not(feature = "no_position"))]
-                BreakPoint::AtPosition { pos, .. } if pos.is_none() => false,
-                #[cfg(not(feature = "no_position"))]
-                BreakPoint::AtPosition { source, pos, .. } if pos.is_beginning_of_line() => {
-                    node.position().line().unwrap_or(0) == pos.line().unwrap() && _src == source
-                }
-                #[cfg(not(feature = "no_position"))]
-                BreakPoint::AtPosition { source, pos, .. } => {
-                    node.position() == *pos && _src == source
-                }
-                BreakPoint::AtFunctionName { name, .. } => match node {
-                    ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
-                        x.name == *name
-                    }
-                    ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
-                        Expr::FnCall(x, ..) => x.name == *name,
-                        _ => false,
-                    },
-                    _ => false,
-                },
-                BreakPoint::AtFunctionCall { name, args, .. } => match node {
-                    ASTNode::Expr(Expr::FnCall(x, ..)) | ASTNode::Stmt(Stmt::FnCall(x, ..)) => {
-                        x.args.len() == *args && x.name == *name
-                    }
-                    ASTNode::Stmt(Stmt::Expr(e)) => match &**e {
-                        Expr::FnCall(x, ..) => x.args.len() == *args && x.name == *name,
-                        _ => false,
-                    },
-                    _ => false,
-                },
-                #[cfg(not(feature = "no_object"))]
-                BreakPoint::AtProperty { name, .. } => match node {
-                    ASTNode::Expr(Expr::Property(x, ..)) => x.2 == *name,
-                    _ => false,
-                },
-            })
-            .map(|(i, ..)| i)
+            .position(|bp| bp.is_enabled() && bp.is_triggered_by(src, node))
     }
     /// Get a slice of all [`BreakPoint`]'s.
     #[inline(always)]
@@ -389,11 +555,248 @@ impl Debugger {
         &self.break_points
     }
     /// Get the underlying [`Vec`] holding all [`BreakPoint`]'s.
+    ///
+    /// Conditions of break-points are kept by position, so use
+    /// [`remove_break_point`][Debugger::remove_break_point] and
+    /// [`clear_break_points`][Debugger::clear_break_points] to remove break-points together with
+    /// their conditions.
     #[inline(always)]
     #[must_use]
     pub fn break_points_mut(&mut self) -> &mut Vec<BreakPoint> {
         &mut self.break_points
     }
+    /// Remove the [`BreakPoint`] at a particular index, together with its conditions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn remove_break_point(&mut self, index: usize) -> BreakPoint {
+        if index < self.conditions.len() {
+            self.conditions.remove(index);
+        }
+        self.break_points.remove(index)
+    }
+    /// Remove all [`BreakPoint`]'s, together with their conditions.
+    #[inline]
+    pub fn clear_break_points(&mut self) {
+        self.break_points.clear();
+        self.conditions.clear();
+    }
+    /// Get the conditions of the [`BreakPoint`] at a particular index, creating them if necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn conditions_mut(&mut self, index: usize) -> &mut BreakPointConditions {
+        assert!(
+            index < self.break_points.len(),
+            "break-point index out of bounds: {}",
+            index
+        );
+        if index >= self.conditions.len() {
+            self.conditions
+                .resize_with(self.break_points.len(), Default::default);
+        }
+        &mut self.conditions[index]
+    }
+    /// Get the condition expression of the [`BreakPoint`] at a particular index, if any.
+    #[inline]
+    #[must_use]
+    pub fn break_point_condition(&self, index: usize) -> Option<&str> {
+        self.conditions
+            .get(index)
+            .and_then(|c| c.condition.as_ref())
+            .map(|(script, ..)| script.as_str())
+    }
+    /// Set a condition expression on the [`BreakPoint`] at a particular index.
+    ///
+    /// The expression is evaluated in the current scope whenever the break-point is reached, and
+    /// the break-point only triggers if it evaluates to `true`.  It is an error if it does not
+    /// evaluate to a boolean value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::debugger::{BreakPoint, DebuggerCommand, DebuggerEvent};
+    /// use rhai::Engine;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let stops = Arc::new(Mutex::new(Vec::new()));
+    /// let stops2 = stops.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_debugger(
+    ///     |_| ().into(),
+    ///     move |mut context, event, _, _, _| {
+    ///         let engine = context.engine();
+    ///         let debugger = &mut context.global_runtime_state_mut().debugger;
+    ///
+    ///         match event {
+    ///             DebuggerEvent::Start => {
+    ///                 debugger.break_points_mut().push(BreakPoint::AtFunctionName {
+    ///                     name: "print".into(),
+    ///                     enabled: true,
+    ///                 });
+    ///                 debugger.set_break_point_condition(engine, 0, "x == 3")?;
+    ///             }
+    ///             DebuggerEvent::BreakPoint(..) => {
+    ///                 let x = context.scope().get_value::<i64>("x").unwrap();
+    ///                 stops2.lock().unwrap().push(x);
+    ///             }
+    ///             _ => (),
+    ///         }
+    ///
+    ///         Ok(DebuggerCommand::Continue)
+    ///     },
+    /// );
+    ///
+    /// engine.run("for x in 0..5 { print(x); }")?;
+    ///
+    /// assert_eq!(*stops.lock().unwrap(), [3]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_break_point_condition(
+        &mut self,
+        engine: &Engine,
+        index: usize,
+        condition: &str,
+    ) -> Result<(), ParseError> {
+        let mut ast = engine.compile_expression(condition)?;
+        self.conditions_mut(index).condition = Some((condition.into(), ast.take_statements()));
+        Ok(())
+    }
+    /// Remove the condition expression from the [`BreakPoint`] at a particular index, if any.
+    #[inline]
+    pub fn clear_break_point_condition(&mut self, index: usize) {
+        if let Some(c) = self.conditions.get_mut(index) {
+            c.condition = None;
+        }
+    }
+    /// Get the [`HitCondition`] of the [`BreakPoint`] at a particular index, if any.
+    #[inline]
+    #[must_use]
+    pub fn break_point_hit_condition(&self, index: usize) -> Option<HitCondition> {
+        self.conditions.get(index).and_then(|c| c.hit_condition)
+    }
+    /// Set (or remove, with [`None`]) the [`HitCondition`] of the [`BreakPoint`] at a particular
+    /// index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn set_break_point_hit_condition(
+        &mut self,
+        index: usize,
+        hit_condition: impl Into<Option<HitCondition>>,
+    ) {
+        self.conditions_mut(index).hit_condition = hit_condition.into();
+    }
+    /// Get the number of hits of the [`BreakPoint`] at a particular index.
+    #[inline]
+    #[must_use]
+    pub fn break_point_hits(&self, index: usize) -> u64 {
+        self.conditions.get(index).map_or(0, |c| c.hits)
+    }
+    /// Reset the number of hits of the [`BreakPoint`] at a particular index to zero.
+    #[inline]
+    pub fn reset_break_point_hits(&mut self, index: usize) {
+        if let Some(c) = self.conditions.get_mut(index) {
+            c.hits = 0;
+        }
+    }
+    /// Add a watch expression, returning its index.
+    ///
+    /// The expression is evaluated in the current scope each time the debugger callback is run,
+    /// and its result is available via [`watches`][Debugger::watches].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::debugger::{DebuggerCommand, DebuggerEvent};
+    /// use rhai::Engine;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let seen2 = seen.clone();
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_debugger(
+    ///     |_| ().into(),
+    ///     move |mut context, event, _, _, _| {
+    ///         let engine = context.engine();
+    ///         let debugger = &mut context.global_runtime_state_mut().debugger;
+    ///
+    ///         match event {
+    ///             DebuggerEvent::Start => {
+    ///                 debugger.add_watch(engine, "x * 2")?;
+    ///             }
+    ///             DebuggerEvent::Step => match debugger.watches()[0].value() {
+    ///                 Some(Ok(value)) => seen2.lock().unwrap().push(value.as_int().unwrap()),
+    ///                 // 'x' is not yet defined at the first statement
+    ///                 _ => (),
+    ///             },
+    ///             _ => (),
+    ///         }
+    ///
+    ///         Ok(DebuggerCommand::Next)
+    ///     },
+    /// );
+    ///
+    /// engine.run("let x = 1; x = 21; x += 0;")?;
+    ///
+    /// assert_eq!(*seen.lock().unwrap(), [2, 42]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_watch(&mut self, engine: &Engine, expr: &str) -> Result<usize, ParseError> {
+        let mut ast = engine.compile_expression(expr)?;
+
+        self.watches.push(Watch {
+            expr: expr.into(),
+            statements: ast.take_statements(),
+            value: None,
+        });
+
+        Ok(self.watches.len() - 1)
+    }
+    /// Get a slice of all [`Watch`] expressions.
+    #[inline(always)]
+    #[must_use]
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+    /// Remove the [`Watch`] expression at a particular index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline(always)]
+    pub fn remove_watch(&mut self, index: usize) -> Watch {
+        self.watches.remove(index)
+    }
+    /// Remove all [`Watch`] expressions.
+    #[inline(always)]
+    pub fn clear_watches(&mut self) {
+        self.watches.clear();
+    }
+    /// Replace this [`Debugger`] with an inactive one while evaluating expressions (e.g.
+    /// conditions of break-points), returning it.
+    #[inline]
+    #[must_use]
+    fn suspend(&mut self) -> Self {
+        mem::replace(self, Self::new(DebuggerStatus::CONTINUE, Dynamic::UNIT))
+    }
     /// Get the custom state.
     #[inline(always)]
     #[must_use]
@@ -411,6 +814,66 @@ impl Debugger {
     pub fn set_state(&mut self, state: impl Into<Dynamic>) {
         self.state = state.into();
     }
+    /// Start recording an evaluation [`Trace`][super::Trace], discarding any trace already
+    /// being recorded.
+    ///
+    /// A step is recorded before each statement is evaluated, containing its position together
+    /// with all changes to variables since the previous step.
+    #[inline(always)]
+    pub fn start_trace(&mut self) {
+        self.trace = Some(Default::default());
+    }
+    /// Get the evaluation [`Trace`][super::Trace] being recorded, if any.
+    #[inline(always)]
+    #[must_use]
+    pub fn trace(&self) -> Option<&super::Trace> {
+        self.trace.as_deref()
+    }
+    /// Stop recording and return the evaluation [`Trace`][super::Trace], if any.
+    #[inline(always)]
+    pub fn take_trace(&mut self) -> Option<super::Trace> {
+        self.trace.take().map(|t| *t)
+    }
+    /// Record variables pushed onto, or removed from, the [`Scope`] in the
+    /// [`Trace`][super::Trace] being recorded, if any.
+    #[inline(always)]
+    pub(crate) fn trace_sync(&mut self, scope: &Scope) {
+        if let Some(ref mut trace) = self.trace {
+            trace.sync(scope);
+        }
+    }
+    /// Record a new value assigned to the variable at a particular index in the [`Scope`] in the
+    /// [`Trace`][super::Trace] being recorded, if any.
+    #[inline(always)]
+    pub(crate) fn trace_set(&mut self, scope: &Scope, index: usize) {
+        if let Some(ref mut trace) = self.trace {
+            trace.set(scope, index);
+        }
+    }
+    /// Record a new value assigned to a variable in the [`Trace`][super::Trace] being recorded,
+    /// if any.
+    #[inline(always)]
+    pub(crate) fn trace_var(&mut self, scope: &Scope, expr: &Expr) {
+        if let Some(ref mut trace) = self.trace {
+            trace.set_var(scope, expr);
+        }
+    }
+    /// Record the call of a script-defined function with a new, empty [`Scope`] in the
+    /// [`Trace`][super::Trace] being recorded, if any.
+    #[inline(always)]
+    pub(crate) fn trace_enter(&mut self) {
+        if let Some(ref mut trace) = self.trace {
+            trace.enter();
+        }
+    }
+    /// Record the return from a script-defined function called with a new, empty [`Scope`] in
+    /// the [`Trace`][super::Trace] being recorded, if any.
+    #[inline(always)]
+    pub(crate) fn trace_exit(&mut self) {
+        if let Some(ref mut trace) = self.trace {
+            trace.exit();
+        }
+    }
 }
 
 impl Engine {
@@ -494,17 +957,167 @@ impl Engine {
         };
 
         let event = match event {
-            Some(e) => e,
-            None => {
-                if let Some(bp) = global.debugger.is_break_point(&global.source, node) {
-                    DebuggerEvent::BreakPoint(bp)
-                } else {
-                    return Ok(None);
-                }
+            Some(e) => Some(e),
+            None => self
+                .find_break_point(scope, global, lib, this_ptr, node, level)?
+                .map(DebuggerEvent::BreakPoint),
+        };
+
+        // Record the step before running the debugger callback, which may stop the trace...
+        let traced = match (node, global.debugger.trace.as_deref_mut()) {
+            (ASTNode::Stmt(stmt), Some(trace)) => {
+                trace.step(&global.source, stmt.position(), level, scope);
+                true
             }
+            _ => false,
         };
 
-        self.run_debugger_raw(scope, global, lib, this_ptr, node, event, level)
+        let result = match event {
+            Some(e) => self.run_debugger_raw(scope, global, lib, this_ptr, node, e, level)?,
+            None => None,
+        };
+
+        // ... or start it
+        match (node, global.debugger.trace.as_deref_mut()) {
+            (ASTNode::Stmt(stmt), Some(trace)) if !traced => {
+                trace.step(&global.source, stmt.position(), level, scope);
+            }
+            _ => (),
+        }
+
+        Ok(result)
+    }
+    /// Find the first enabled break-point triggered by a particular [`AST` Node][ASTNode],
+    /// evaluating its condition expression and counting its hits.
+    ///
+    /// Break-points are not checked (and the debugger is not run) while evaluating conditions.
+    fn find_break_point(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        node: ASTNode,
+        level: usize,
+    ) -> RhaiResultOf<Option<usize>> {
+        let candidates = global
+            .debugger
+            .break_points
+            .iter()
+            .enumerate()
+            .filter(|&(.., bp)| bp.is_enabled() && bp.is_triggered_by(&global.source, node))
+            .map(|(i, ..)| i)
+            .collect::<StaticVec<_>>();
+
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let mut debugger = global.debugger.suspend();
+
+        if debugger.conditions.len() < debugger.break_points.len() {
+            let len = debugger.break_points.len();
+            debugger.conditions.resize_with(len, Default::default);
+        }
+
+        let Debugger {
+            ref break_points,
+            ref mut conditions,
+            ..
+        } = debugger;
+
+        // Keep track of the values of watched variables
+        let mut candidates = candidates;
+
+        candidates.retain(|&mut index| match break_points[index] {
+            BreakPoint::AtVariableChange { ref name, .. } => {
+                let value = scope.get(name).map(Dynamic::flatten_clone);
+                let c = &mut conditions[index];
+
+                let changed = match (&c.value, &value) {
+                    (Some(old), Some(new)) => !is_same_value(old, new),
+                    _ => false,
+                };
+                c.value = value;
+                changed
+            }
+            _ => true,
+        });
+
+        let mut result = Ok(None);
+
+        for index in candidates {
+            let c = &mut conditions[index];
+
+            let satisfied = match c.condition {
+                Some((.., ref statements)) => match self
+                    .eval_stmt_block(
+                        scope,
+                        global,
+                        &mut Caches::new(),
+                        lib,
+                        this_ptr,
+                        statements,
+                        false,
+                        level,
+                    )
+                    .and_then(|v| {
+                        v.as_bool().map_err(|typ| {
+                            self.make_type_mismatch_err::<bool>(typ, node.position())
+                        })
+                    }) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        result = Err(err);
+                        break;
+                    }
+                },
+                None => true,
+            };
+
+            if satisfied {
+                c.hits += 1;
+
+                if c.hit_condition.map_or(true, |h| h.is_satisfied(c.hits)) {
+                    result = Ok(Some(index));
+                    break;
+                }
+            }
+        }
+
+        global.debugger = debugger;
+
+        result
+    }
+    /// Evaluate all watch expressions, storing their results.
+    ///
+    /// Break-points are not checked (and the debugger is not run) while evaluating watch
+    /// expressions.
+    fn eval_watches(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        level: usize,
+    ) {
+        let mut debugger = global.debugger.suspend();
+
+        for watch in &mut debugger.watches {
+            let result = self.eval_stmt_block(
+                scope,
+                global,
+                &mut Caches::new(),
+                lib,
+                this_ptr,
+                &watch.statements,
+                false,
+                level,
+            );
+            watch.value = Some(result.map_err(|err| err.to_string()));
+        }
+
+        global.debugger = debugger;
     }
     /// Run the debugger callback unconditionally.
     ///
@@ -530,6 +1143,10 @@ impl Engine {
             Some(source.as_str())
         };
 
+        if !global.debugger.watches.is_empty() {
+            self.eval_watches(scope, global, lib, this_ptr, level);
+        }
+
         let context = crate::EvalContext::new(self, scope, global, None, lib, this_ptr, level);
 
         if let Some((.., ref on_debugger)) = self.debugger {
@@ -572,3 +1189,15 @@ impl Engine {
         }
     }
 }
+
+/// Are two values the same, for the purpose of detecting variable changes?
+///
+/// Values are compared structurally.  Custom types, which cannot be compared that way, fall back
+/// to comparing their debug representations.
+#[must_use]
+fn is_same_value(old: &Dynamic, new: &Dynamic) -> bool {
+    old.structural_eq(new).unwrap_or_else(|| {
+        let new = &new.flatten_clone();
+        old.type_id() == new.type_id() && format!("{old:?}") == format!("{new:?}")
+    })
+}
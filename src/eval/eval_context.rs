@@ -95,6 +95,16 @@ impl<'a, 's, 'ps, 'g, 'pg, 'c, 'pc, 't, 'pt> EvalContext<'a, 's, 'ps, 'g, 'pg, '
     pub fn tag_mut(&mut self) -> &mut Dynamic {
         &mut self.global.tag
     }
+    /// Custom state of a specific type, passed in via
+    /// [`Engine::eval_with_context_data`][crate::Engine::eval_with_context_data] or set as the
+    /// [default tag][crate::Engine::set_default_tag].
+    ///
+    /// Returns [`None`] if there is no custom state of this type.
+    #[inline]
+    #[must_use]
+    pub fn data<T: crate::types::dynamic::Variant + Clone>(&self) -> Option<&T> {
+        self.global.context_data::<T>()
+    }
     /// _(internals)_ The current [`GlobalRuntimeState`].
     /// Exported under the `internals` feature only.
     #[cfg(feature = "internals")]
@@ -111,6 +121,12 @@ impl<'a, 's, 'ps, 'g, 'pg, 'c, 'pc, 't, 'pt> EvalContext<'a, 's, 'ps, 'g, 'pg, '
     pub fn global_runtime_state_mut(&mut self) -> &mut &'g mut GlobalRuntimeState<'pg> {
         &mut self.global
     }
+    /// A read-only view of the current global runtime state.
+    #[inline(always)]
+    #[must_use]
+    pub fn global_state(&self) -> crate::GlobalStateView {
+        (&*self.global).into()
+    }
     /// Get an iterator over the namespaces containing definition of all script-defined functions.
     #[inline]
     pub fn iter_namespaces(&self) -> impl Iterator<Item = &Module> {
@@ -142,6 +158,20 @@ impl<'a, 's, 'ps, 'g, 'pg, 'c, 'pc, 't, 'pt> EvalContext<'a, 's, 'ps, 'g, 'pg, '
     pub const fn call_level(&self) -> usize {
         self.level
     }
+    /// The number of further nesting levels of function calls allowed before the maximum call
+    /// stack depth (set via [`Engine::set_max_call_levels`]) is exceeded.
+    ///
+    /// When this is zero, calling a script-defined function raises
+    /// [`ErrorStackOverflow`][crate::EvalAltResult::ErrorStackOverflow].
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn remaining_call_depth(&self) -> usize {
+        self.engine.max_call_levels().saturating_sub(self.level)
+    }
 
     /// Evaluate an [expression tree][crate::Expression] within this [evaluation context][`EvalContext`].
     ///
@@ -184,7 +214,10 @@ impl<'a, 's, 'ps, 'g, 'pg, 'c, 'pc, 't, 'pt> EvalContext<'a, 's, 'ps, 'g, 'pg, '
             None => &mut new_caches,
         };
 
-        match expr {
+        #[cfg(not(feature = "unchecked"))]
+        let nested = self.engine.start_nested_eval(self.global, self.level);
+
+        let result = match expr {
             crate::ast::Expr::Stmt(statements) => self.engine.eval_stmt_block(
                 self.scope,
                 self.global,
@@ -204,6 +237,13 @@ impl<'a, 's, 'ps, 'g, 'pg, 'c, 'pc, 't, 'pt> EvalContext<'a, 's, 'ps, 'g, 'pg, '
                 expr,
                 self.level,
             ),
-        }
+        };
+
+        #[cfg(not(feature = "unchecked"))]
+        let result =
+            self.engine
+                .end_nested_eval(self.global, nested, result, expr.start_position());
+
+        result
     }
 }
@@ -1,15 +1,19 @@
 //! Module defining functions for evaluating an expression.
 
 use super::{Caches, EvalContext, GlobalRuntimeState, Target};
-use crate::ast::{Expr, FnCallExpr, OpAssignment};
+use crate::ast::{Expr, FnCallExpr, FnCallHashes, OpAssignment};
 use crate::engine::{KEYWORD_THIS, OP_CONCAT};
 use crate::eval::FnResolutionCacheEntry;
+use crate::func::builtin::is_overridable_binary_op;
 use crate::func::{
     calc_fn_params_hash, combine_hashes, gen_fn_call_signature, get_builtin_binary_op_fn,
-    CallableFunction,
+    get_builtin_unary_op_fn, CallableFunction, FnCallArgs,
 };
 use crate::types::dynamic::AccessMode;
-use crate::{Dynamic, Engine, Module, Position, RhaiResult, RhaiResultOf, Scope, ERR};
+use crate::{
+    Dynamic, Engine, FnArgsVec, ImmutableString, Module, Position, RhaiResult, RhaiResultOf, Scope,
+    ERR,
+};
 #[cfg(feature = "no_std")]
 use hashbrown::hash_map::Entry;
 #[cfg(not(feature = "no_std"))]
@@ -49,7 +53,12 @@ impl Engine {
 
         // Do a text-match search if the index doesn't work
         global.find_import(root).map_or_else(
-            || self.global_sub_modules.get(root).cloned(),
+            || {
+                self.global_sub_modules
+                    .get(root)
+                    .cloned()
+                    .or_else(|| self.lazy_sub_modules.get(root).map(|m| m.get()))
+            },
             |offset| global.get_shared_import(offset),
         )
     }
@@ -199,12 +208,18 @@ impl Engine {
             match scope.get_index(var_name) {
                 Some((index, _)) => index,
                 None => {
+                    // Variables in the frozen base layer of the scope are read-only shared
+                    // values, so cloning them is cheap
+                    if let Some(val) = scope.get_frozen(var_name) {
+                        return Ok((val.clone().into(), var_pos));
+                    }
+
                     return match self.global_modules.iter().find_map(|m| m.get_var(var_name)) {
                         Some(val) => Ok((val.into(), var_pos)),
                         None => {
                             Err(ERR::ErrorVariableNotFound(var_name.to_string(), var_pos).into())
                         }
-                    }
+                    };
                 }
             }
         };
@@ -230,6 +245,22 @@ impl Engine {
             name, hashes, args, ..
         } = expr;
 
+        // Call of a script-defined function to itself in tail position
+        #[cfg(not(feature = "no_function"))]
+        if global
+            .tail_call
+            .as_ref()
+            .map_or(false, |t| t.is_tail_expr(expr, level))
+        {
+            return self.eval_tail_call(scope, global, caches, lib, this_ptr, expr, pos, level);
+        }
+
+        // Trace operator call if a callback is registered
+        if expr.is_native_operator && self.operator_trace.is_some() {
+            return self
+                .eval_traced_operator(scope, global, caches, lib, this_ptr, expr, pos, level);
+        }
+
         // Short-circuit native binary operator call if under Fast Operators mode
         if expr.is_native_operator && self.fast_operators() && (args.len() == 1 || args.len() == 2)
         {
@@ -253,53 +284,8 @@ impl Engine {
                 &mut operands[0..1]
             };
 
-            let hash = calc_fn_params_hash(operands.iter().map(|a| a.type_id()));
-            let hash = combine_hashes(hashes.native, hash);
-
-            let cache = caches.fn_resolution_cache_mut();
-
-            let func = match cache.entry(hash) {
-                Entry::Vacant(entry) => {
-                    let func = if args.len() == 2 {
-                        get_builtin_binary_op_fn(name, operands[0], operands[1])
-                    } else {
-                        None
-                    };
-
-                    if let Some(f) = func {
-                        &entry
-                            .insert(Some(FnResolutionCacheEntry {
-                                func: CallableFunction::from_fn_builtin(f),
-                                source: None,
-                            }))
-                            .as_ref()
-                            .unwrap()
-                            .func
-                    } else {
-                        let result = self.exec_fn_call(
-                            None, global, caches, lib, name, *hashes, operands, false, false, pos,
-                            level,
-                        );
-                        return result.map(|(v, ..)| v);
-                    }
-                }
-                Entry::Occupied(entry) => {
-                    if let Some(entry) = entry.into_mut() {
-                        &entry.func
-                    } else {
-                        let sig = gen_fn_call_signature(self, name, operands);
-                        return Err(ERR::ErrorFunctionNotFound(sig, pos).into());
-                    }
-                }
-            };
-
-            let context = (self, name, None, &*global, lib, pos, level).into();
-            let result = if func.is_plugin_fn() {
-                func.get_plugin_fn().unwrap().call(context, operands)
-            } else {
-                func.get_native_fn().unwrap()(context, operands)
-            };
-            return self.check_return_value(result, pos);
+            return self
+                .call_native_operator(global, caches, lib, name, *hashes, operands, pos, level);
         }
 
         #[cfg(not(feature = "no_module"))]
@@ -336,6 +322,134 @@ impl Engine {
         )
     }
 
+    /// Call a native operator with evaluated operands under Fast Operators mode, short-circuiting
+    /// to a built-in implementation if there is one.
+    pub(crate) fn call_native_operator(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        name: &str,
+        hashes: FnCallHashes,
+        operands: &mut FnCallArgs,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResult {
+        let hash = calc_fn_params_hash(operands.iter().map(|a| a.type_id()));
+        let hash = combine_hashes(hashes.native, hash);
+
+        let cache = caches.fn_resolution_cache_mut();
+
+        let func = match cache.entry(hash) {
+            Entry::Vacant(entry) => {
+                let func = if operands.len() == 2 {
+                    let types = [operands[0].type_id(), operands[1].type_id()];
+
+                    // Some built-in operators give way to user-registered overloads
+                    if is_overridable_binary_op(types[0], types[1])
+                        && self.has_native_fn_override(hashes.native, types)
+                    {
+                        None
+                    } else {
+                        get_builtin_binary_op_fn(name, operands[0], operands[1])
+                    }
+                } else {
+                    get_builtin_unary_op_fn(name, operands[0])
+                };
+
+                if let Some(f) = func {
+                    &entry
+                        .insert(Some(FnResolutionCacheEntry {
+                            func: CallableFunction::from_fn_builtin(f),
+                            source: None,
+                            borrowed_params: 0,
+                        }))
+                        .as_ref()
+                        .unwrap()
+                        .func
+                } else {
+                    let result = self.exec_fn_call(
+                        None, global, caches, lib, name, hashes, operands, false, false, 0, pos,
+                        level,
+                    );
+                    return result.map(|(v, ..)| v);
+                }
+            }
+            Entry::Occupied(entry) => {
+                if let Some(entry) = entry.into_mut() {
+                    &entry.func
+                } else {
+                    let sig = gen_fn_call_signature(self, name, operands);
+                    return Err(ERR::ErrorFunctionNotFound(sig, pos).into());
+                }
+            }
+        };
+
+        let context = (self, name, None, &*global, lib, pos, level).into();
+        let result = if func.is_plugin_fn() {
+            func.get_plugin_fn().unwrap().call(context, operands)
+        } else {
+            func.get_native_fn().unwrap()(context, operands)
+        };
+
+        #[cfg(not(feature = "unchecked"))]
+        if let Ok(ref r) = result {
+            if self.fuel_limit() > 0 {
+                let fuel = self.calc_native_call_fuel(name, r, None);
+                self.consume_fuel(global, fuel, pos)?;
+            }
+        }
+
+        self.check_return_value(result, pos)
+    }
+
+    /// Evaluate an operator call expression, passing its operand types and result to the
+    /// registered operator trace callback.
+    fn eval_traced_operator(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        expr: &FnCallExpr,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResult {
+        let FnCallExpr {
+            name, hashes, args, ..
+        } = expr;
+
+        let mut operands = args
+            .iter()
+            .map(|arg| {
+                self.get_arg_value(scope, global, caches, lib, this_ptr, arg, level)
+                    .map(|(v, ..)| v.flatten())
+            })
+            .collect::<RhaiResultOf<FnArgsVec<_>>>()?;
+
+        let type_names = operands
+            .iter()
+            .map(|v| self.map_type_name(v.type_name()))
+            .collect::<FnArgsVec<_>>();
+        let types = type_names
+            .iter()
+            .map(|t| t.as_ref())
+            .collect::<FnArgsVec<_>>();
+
+        let mut args = operands.iter_mut().collect::<FnArgsVec<_>>();
+
+        let (result, ..) = self.exec_fn_call(
+            None, global, caches, lib, name, *hashes, &mut args, false, false, 0, pos, level,
+        )?;
+
+        if let Some(ref trace) = self.operator_trace {
+            trace(name, &types, &result, pos);
+        }
+
+        Ok(result)
+    }
+
     /// Evaluate an expression.
     //
     // # Implementation Notes
@@ -365,7 +479,7 @@ impl Engine {
                 self.run_debugger_with_reset(scope, global, lib, this_ptr, expr, level)?;
 
             #[cfg(not(feature = "unchecked"))]
-            self.inc_operations(&mut global.num_operations, expr.position())?;
+            self.inc_operations(global, expr.position())?;
 
             let result =
                 self.eval_fn_call_expr(scope, global, caches, lib, this_ptr, x, x.pos, level);
@@ -384,7 +498,7 @@ impl Engine {
             self.run_debugger(scope, global, lib, this_ptr, expr, level)?;
 
             #[cfg(not(feature = "unchecked"))]
-            self.inc_operations(&mut global.num_operations, expr.position())?;
+            self.inc_operations(global, expr.position())?;
 
             return if index.is_none() && x.0.is_none() && x.3 == KEYWORD_THIS {
                 this_ptr
@@ -402,7 +516,7 @@ impl Engine {
             self.run_debugger_with_reset(scope, global, lib, this_ptr, expr, level)?;
 
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, expr.position())?;
+        self.inc_operations(global, expr.position())?;
 
         let result = match expr {
             // Constants
@@ -417,41 +531,108 @@ impl Engine {
 
             // `... ${...} ...`
             Expr::InterpolatedString(x, _) => {
-                let mut concat = self.get_interned_string("").into();
-                let target = &mut concat;
-                let mut result = Ok(Dynamic::UNIT);
+                /// Estimated size of a segment that is not a literal string.
+                const SEGMENT_SIZE_HINT: usize = 8;
+
+                #[cfg(not(feature = "unchecked"))]
+                let nested = self.start_nested_eval(global, level);
+
+                let mut buf = caches.take_string();
+                let mut result = Ok(());
+
+                // Reserve the total capacity up-front to concatenate into a single buffer
+                buf.reserve(
+                    x.iter()
+                        .map(|expr| match expr {
+                            Expr::StringConstant(s, ..) => s.len(),
+                            _ => SEGMENT_SIZE_HINT,
+                        })
+                        .sum(),
+                );
 
                 let mut op_info = OpAssignment::new_op_assignment(OP_CONCAT, Position::NONE);
                 let root = ("", Position::NONE);
 
                 for expr in &**x {
-                    let item =
+                    let mut item =
                         match self.eval_expr(scope, global, caches, lib, this_ptr, expr, level) {
-                            Ok(r) => r,
-                            err => {
-                                result = err;
+                            Ok(r) => r.flatten(),
+                            Err(err) => {
+                                result = Err(err);
                                 break;
                             }
                         };
 
-                    op_info.pos = expr.start_position();
+                    if !item.is::<ImmutableString>() {
+                        // Convert the item via string concatenation
+                        let mut concat = self.const_empty_string().into();
+                        op_info.pos = expr.start_position();
+
+                        if let Err(err) = self.eval_op_assignment(
+                            global,
+                            caches,
+                            lib,
+                            op_info,
+                            &mut concat,
+                            root,
+                            item,
+                            level,
+                        ) {
+                            result = Err(err);
+                            break;
+                        }
 
-                    if let Err(err) = self
-                        .eval_op_assignment(global, caches, lib, op_info, target, root, item, level)
-                    {
-                        result = Err(err);
+                        item = concat.take_or_clone().flatten();
+                    } else {
+                        #[cfg(not(feature = "unchecked"))]
+                        if let Err(err) = self.consume_fuel(
+                            global,
+                            self.fuel_costs().string_concat,
+                            expr.start_position(),
+                        ) {
+                            result = Err(err);
+                            break;
+                        }
+                    }
+
+                    let text;
+                    let s = match item.as_str_ref() {
+                        Ok(s) => s,
+                        Err(_) => {
+                            text = item.to_string();
+                            &text
+                        }
+                    };
+
+                    #[cfg(not(feature = "unchecked"))]
+                    if self.max_string_size() > 0 && buf.len() + s.len() > self.max_string_size() {
+                        result = Err(ERR::ErrorDataTooLarge(
+                            "Length of string".to_string(),
+                            Default::default(),
+                            expr.start_position(),
+                        )
+                        .into());
                         break;
                     }
+
+                    buf.push_str(s);
                 }
 
-                self.check_return_value(
-                    result.map(|_| concat.take_or_clone()),
-                    expr.start_position(),
-                )
+                let s = caches.finish_string(buf);
+                let result =
+                    self.check_return_value(result.map(|_| s.into()), expr.start_position());
+
+                #[cfg(not(feature = "unchecked"))]
+                let result = self.end_nested_eval(global, nested, result, expr.start_position());
+
+                result
             }
 
             #[cfg(not(feature = "no_index"))]
-            Expr::Array(x, ..) => {
+            Expr::Array(x, _pos) => {
+                #[cfg(not(feature = "unchecked"))]
+                self.consume_fuel(global, self.fuel_costs().array_alloc, *_pos)?;
+
                 let mut array = crate::Array::with_capacity(x.len());
                 let mut result = Ok(Dynamic::UNIT);
 
@@ -488,6 +669,22 @@ impl Engine {
                 result.map(|_| array.into())
             }
 
+            Expr::Tuple(x, _pos) => {
+                let tuple = x
+                    .iter()
+                    .map(|item_expr| {
+                        self.eval_expr(scope, global, caches, lib, this_ptr, item_expr, level)
+                            .map(Dynamic::flatten)
+                    })
+                    .collect::<RhaiResultOf<crate::Tuple>>()?
+                    .into();
+
+                #[cfg(not(feature = "unchecked"))]
+                self.check_data_size(&tuple, *_pos)?;
+
+                Ok(tuple)
+            }
+
             #[cfg(not(feature = "no_object"))]
             Expr::Map(x, ..) => {
                 let mut map = x.1.clone();
@@ -0,0 +1,1021 @@
+//! Generators created by calling script-defined functions that contain `yield`, and the
+//! suspendable evaluation of blocks of statements shared with resumable evaluations.
+#![cfg(not(feature = "no_function"))]
+
+use super::stmt::ForLoop;
+use super::{Caches, CallFrame, GlobalRuntimeState};
+use crate::ast::{ASTFlags, Expr, FnCallExpr, ScriptFnDef, Stmt};
+use crate::func::{locked_read, locked_write, FnCallArgs};
+use crate::{
+    calc_fn_hash, Dynamic, Engine, Locked, Module, Position, RhaiResultOf, Scope, Shared, ERR,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{fmt, mem, ptr};
+
+#[cfg(not(feature = "sync"))]
+type WeakToken = std::rc::Weak<()>;
+#[cfg(feature = "sync")]
+type WeakToken = std::sync::Weak<()>;
+
+/// A lazy iterator returned by calling a script-defined function that contains `yield`.
+///
+/// Not available under `no_function`.
+///
+/// The function body only runs when the next value is requested, up to the next `yield`
+/// statement, and is then suspended with its local variables intact.  The generator finishes
+/// when the function body ends or returns (the return value is ignored).
+///
+/// A generator can be iterated by a `for` loop, or turned into an array via `collect`.
+/// Clones of a generator share the same state.
+///
+/// The state of a generator is kept by the evaluation that creates it, so a generator cannot be
+/// resumed after that evaluation ends.
+#[derive(Clone)]
+pub struct Generator(Shared<()>);
+
+impl fmt::Debug for Generator {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Generator")
+    }
+}
+
+/// State of the statement where a generator is suspended.
+enum StmtState {
+    /// Nothing to keep.
+    None,
+    /// The statement has not started, because the budget of operations is exhausted.
+    Pending,
+    /// `if` statement, with the branch taken.
+    If(bool),
+    /// `for` loop.
+    For(Box<ForLoop>),
+    /// Call to a script-defined function.
+    Call(Box<SuspendedFn>),
+}
+
+/// Position of a suspended generator within a block of statements.
+struct BlockFrame {
+    /// Index of the current statement.
+    index: usize,
+    /// Number of variables in the scope when the block is entered.
+    scope_len: usize,
+    /// Number of modules imported by the generator when the block is entered.
+    #[cfg(not(feature = "no_module"))]
+    imports_len: usize,
+    /// State of the current statement.
+    stmt: StmtState,
+}
+
+/// Path to the statement where a generator or a resumable evaluation is suspended.
+pub(crate) struct Continuation {
+    /// Blocks being evaluated, outermost first.
+    ///
+    /// Empty if the evaluation has not started.
+    frames: Vec<BlockFrame>,
+    /// Number of operations after which the evaluation is suspended before the next statement.
+    pub(crate) suspend_at: u64,
+    /// Is the outermost block evaluated at global level, keeping its variables at the end?
+    is_global: bool,
+}
+
+impl Continuation {
+    /// Create a [`Continuation`] for the body of a generator, which is only suspended by `yield`.
+    #[inline(always)]
+    #[must_use]
+    const fn new() -> Self {
+        Self {
+            frames: Vec::new(),
+            suspend_at: u64::MAX,
+            is_global: false,
+        }
+    }
+    /// Create a [`Continuation`] for the statements of a script evaluated at global level.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) const fn new_global() -> Self {
+        Self {
+            frames: Vec::new(),
+            suspend_at: u64::MAX,
+            is_global: true,
+        }
+    }
+    /// Has the evaluation started?
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn is_started(&self) -> bool {
+        !self.frames.is_empty()
+    }
+}
+
+/// Outcome of evaluating a statement or a block of statements with a [`Continuation`].
+pub(crate) enum Step {
+    /// Finished, with the value of the statement or block.
+    Done(Dynamic),
+    /// Suspended at a `yield` statement, with the yielded value.
+    Yield(Dynamic),
+    /// Suspended before a statement, because the budget of operations is exhausted.
+    Suspend,
+}
+
+/// State of a suspended call to a script-defined function: a [`Generator`], a
+/// [task][crate::Task], or a function call evaluated with a budget of operations.
+pub(crate) struct SuspendedFn {
+    /// The function.
+    fn_def: Shared<ScriptFnDef>,
+    /// Copy of the `this` pointer, if any.
+    this_ptr: Option<Dynamic>,
+    /// Local variables, starting with the parameters.
+    scope: Scope<'static>,
+    /// Modules imported by the function.
+    #[cfg(not(feature = "no_module"))]
+    imports: Vec<(crate::ImmutableString, Shared<Module>)>,
+    /// Path to the statement where the function is suspended.
+    cont: Continuation,
+}
+
+impl SuspendedFn {
+    /// Create a new [`SuspendedFn`] that has not yet started evaluating the function body.
+    #[must_use]
+    fn new(
+        fn_def: Shared<ScriptFnDef>,
+        this_ptr: Option<Dynamic>,
+        args: impl Iterator<Item = Dynamic>,
+    ) -> Self {
+        let mut scope = Scope::new();
+        scope.extend(fn_def.params.iter().cloned().zip(args));
+
+        Self {
+            fn_def,
+            this_ptr,
+            scope,
+            #[cfg(not(feature = "no_module"))]
+            imports: Vec::new(),
+            cont: Continuation::new(),
+        }
+    }
+    /// Create a new [`SuspendedFn`] that calls a script-defined function in `lib` with
+    /// arguments.
+    ///
+    /// Returns [`None`] if there is no such function, or if it is a generator function.
+    #[must_use]
+    pub(crate) fn new_call(lib: &[&Module], fn_name: &str, args: &[Dynamic]) -> Option<Self> {
+        let fn_def = Self::find_fn(lib, calc_fn_hash(fn_name, args.len()))?;
+        Some(Self::new(fn_def, None, args.iter().cloned()))
+    }
+    /// Find a script-defined function in `lib` by its hash, if it is not a generator function.
+    #[must_use]
+    fn find_fn(lib: &[&Module], hash: u64) -> Option<Shared<ScriptFnDef>> {
+        lib.iter()
+            .find_map(|m| m.get_fn(hash))
+            .and_then(|f| f.get_script_fn_def())
+            .filter(|f| !f.is_generator)
+            .cloned()
+    }
+}
+
+/// Entry of a [`Generator`] in a [`GeneratorTable`].
+enum Slot {
+    /// Suspended, or not yet started.
+    Suspended(Box<SuspendedFn>),
+    /// Currently running.
+    Running,
+    /// Finished.
+    Done,
+}
+
+/// List of [generators][Generator] with their states.
+type GeneratorList = Vec<(WeakToken, Slot)>;
+
+/// Table of the states of [generators][Generator] created during an evaluation.
+///
+/// The table is only allocated when needed. Clones of a table share the same states.
+///
+/// States are dropped together with the last clone of their generators.
+#[derive(Default)]
+pub struct GeneratorTable(Locked<Option<Shared<Locked<GeneratorList>>>>);
+
+impl fmt::Debug for GeneratorTable {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = locked_read(&self.0)
+            .as_ref()
+            .map_or(0, |list| locked_read(list).len());
+        write!(f, "GeneratorTable({len})")
+    }
+}
+
+impl Clone for GeneratorTable {
+    #[inline]
+    fn clone(&self) -> Self {
+        let list = locked_write(&self.0)
+            .get_or_insert_with(Default::default)
+            .clone();
+        Self(Locked::new(Some(list)))
+    }
+}
+
+impl GeneratorTable {
+    /// Add the state of a new [`Generator`].
+    #[must_use]
+    fn insert(&self, state: SuspendedFn) -> Generator {
+        let list = locked_write(&self.0)
+            .get_or_insert_with(Default::default)
+            .clone();
+        let mut list = locked_write(&list);
+
+        // Drop the states of generators that are gone
+        list.retain(|(token, ..)| token.strong_count() > 0);
+
+        let token = Shared::new(());
+        list.push((Shared::downgrade(&token), Slot::Suspended(state.into())));
+
+        Generator(token)
+    }
+    /// Replace the [`Slot`] of a [`Generator`], returning the original.
+    ///
+    /// Returns [`None`] if the generator is not in the table.
+    #[must_use]
+    fn replace(&self, gen: &Generator, slot: Slot) -> Option<Slot> {
+        let list = locked_read(&self.0).as_ref()?.clone();
+        let mut list = locked_write(&list);
+
+        list.iter_mut()
+            .find(|(token, ..)| ptr::eq(token.as_ptr(), Shared::as_ptr(&gen.0)))
+            .map(|(.., s)| mem::replace(s, slot))
+    }
+}
+
+impl Engine {
+    /// Does this [`Engine`] have a debugger, coverage tracking or a profiler active, which must
+    /// see every statement evaluated?
+    ///
+    /// Statements evaluated with a [`Continuation`] skip their hooks, so tasks run to completion
+    /// instead of being suspended, and resumable evaluations cannot be started, when this is
+    /// `true`.
+    #[must_use]
+    pub(crate) fn has_stmt_hooks(&self) -> bool {
+        #[cfg(feature = "debugging")]
+        if self.debugger.is_some() {
+            return true;
+        }
+        #[cfg(feature = "coverage")]
+        if self.coverage.is_some() {
+            return true;
+        }
+        #[cfg(feature = "profiling")]
+        if self.profile.is_some() {
+            return true;
+        }
+        false
+    }
+
+    /// Create a [`Generator`] that evaluates the body of a script-defined function.
+    ///
+    /// The arguments are consumed.
+    pub(crate) fn make_generator(
+        &self,
+        global: &GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &Option<&mut Dynamic>,
+        fn_def: &ScriptFnDef,
+        args: &mut FnCallArgs,
+    ) -> Generator {
+        let hash = calc_fn_hash(fn_def.name.as_str(), fn_def.params.len());
+
+        // Avoid copying the function body if it can be found
+        let fn_def = lib
+            .iter()
+            .find_map(|m| m.get_fn(hash))
+            .and_then(|f| f.get_script_fn_def())
+            .filter(|f| ptr::eq(&***f, fn_def))
+            .cloned()
+            .unwrap_or_else(|| fn_def.clone().into());
+
+        let args = args.iter_mut().map(|v| mem::take(*v));
+        let state = SuspendedFn::new(fn_def, this_ptr.as_deref().cloned(), args);
+
+        global.generators.insert(state)
+    }
+
+    /// Resume a [`Generator`] until it yields the next value.
+    ///
+    /// Returns [`None`] if the generator has finished.
+    pub(crate) fn resume_generator(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        gen: &Generator,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<Option<Dynamic>> {
+        let mut state = match global.generators.replace(gen, Slot::Running) {
+            Some(Slot::Suspended(state)) => state,
+            Some(Slot::Done) => {
+                let _ = global.generators.replace(gen, Slot::Done);
+                return Ok(None);
+            }
+            Some(Slot::Running) => {
+                return Err(ERR::ErrorRuntime(
+                    "Cannot resume a generator that is running".into(),
+                    pos,
+                )
+                .into())
+            }
+            None => {
+                return Err(ERR::ErrorRuntime(
+                    "Cannot resume a generator outside the evaluation that created it".into(),
+                    pos,
+                )
+                .into())
+            }
+        };
+
+        let result = self
+            .resume_suspended_fn(global, caches, lib, &mut state, u64::MAX, pos, level)
+            .map(|step| match step {
+                Step::Yield(value) => Some(value),
+                Step::Done(..) | Step::Suspend => None,
+            });
+
+        let slot = match result {
+            Ok(Some(..)) => Slot::Suspended(state),
+            _ => Slot::Done,
+        };
+        let _ = global.generators.replace(gen, slot);
+
+        result
+    }
+
+    /// Resume a [`SuspendedFn`] until it yields a value, until it finishes, or until the number of
+    /// operations reaches `suspend_at`.
+    ///
+    /// The return value of the function is returned with [`Step::Done`].
+    pub(crate) fn resume_suspended_fn(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        state: &mut SuspendedFn,
+        suspend_at: u64,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<Step> {
+        let level = level + 1;
+
+        #[cfg(not(feature = "unchecked"))]
+        self.inc_operations(global, pos)?;
+        #[cfg(not(feature = "unchecked"))]
+        self.consume_fuel(global, self.fuel_costs().function_call, pos)?;
+
+        // Check for stack overflow
+        #[cfg(not(feature = "unchecked"))]
+        if level > self.max_call_levels() {
+            return Err(ERR::ErrorStackOverflow(Default::default(), pos).into());
+        }
+        #[cfg(not(feature = "unchecked"))]
+        self.check_nested_call_level(global, level, pos)?;
+
+        let fn_def = state.fn_def.clone();
+
+        #[cfg(not(feature = "no_module"))]
+        let orig_imports_len = global.num_imports();
+        let orig_fn_resolution_caches_len = caches.fn_resolution_caches_len();
+        let orig_always_search_scope = global.always_search_scope;
+
+        // Merge in encapsulated environment, if any
+        #[cfg(not(feature = "no_module"))]
+        let mut lib_merged = crate::StaticVec::with_capacity(lib.len() + 1);
+
+        #[cfg(not(feature = "no_module"))]
+        let (lib, constants) = if let Some(ref environ) = fn_def.environ {
+            let crate::ast::EncapsulatedEnviron {
+                lib: fn_lib,
+                imports,
+                constants,
+            } = environ.as_ref();
+
+            imports
+                .iter()
+                .cloned()
+                .for_each(|(n, m)| global.push_import(n, m));
+
+            (
+                if fn_lib.is_empty() {
+                    lib
+                } else {
+                    caches.push_fn_resolution_cache();
+                    lib_merged.push(&**fn_lib);
+                    lib_merged.extend(lib.iter().copied());
+                    &lib_merged
+                },
+                Some(mem::replace(&mut global.constants, constants.clone())),
+            )
+        } else {
+            (lib, None)
+        };
+
+        // Restore modules imported by the function
+        #[cfg(not(feature = "no_module"))]
+        let imports_base = global.num_imports();
+        #[cfg(feature = "no_module")]
+        let imports_base = 0;
+        #[cfg(not(feature = "no_module"))]
+        if !state.imports.is_empty() {
+            caches.push_fn_resolution_cache();
+            state
+                .imports
+                .drain(..)
+                .for_each(|(n, m)| global.push_import(n, m));
+        }
+
+        // Push a new call frame
+        let orig_call_frames_len = global.call_stack.len();
+        global.call_stack.push(CallFrame {
+            fn_name: fn_def.name.clone(),
+            source: global.source.clone(),
+            pos,
+        });
+
+        // Calls in a suspendable function are never tail calls
+        let orig_tail_call = global.tail_call.take();
+
+        let SuspendedFn {
+            this_ptr,
+            scope,
+            cont,
+            ..
+        } = state;
+
+        cont.suspend_at = suspend_at;
+        let resuming = cont.is_started();
+
+        let result = self.exec_generator_block(
+            scope,
+            global,
+            caches,
+            lib,
+            &mut this_ptr.as_mut(),
+            cont,
+            imports_base,
+            0,
+            fn_def.body.statements(),
+            resuming,
+            level,
+        );
+
+        let result = match result {
+            Ok(step @ (Step::Yield(..) | Step::Suspend)) => {
+                // Keep the modules imported by the function
+                #[cfg(not(feature = "no_module"))]
+                state.imports.extend(
+                    global
+                        .scan_imports_raw()
+                        .skip(imports_base)
+                        .map(|(n, m)| (n.clone(), m.clone())),
+                );
+                Ok(step)
+            }
+            Ok(step) => Ok(step),
+            Err(err) => match *err {
+                ERR::Return(value, ..) => Ok(Step::Done(value)),
+                // System errors are passed straight-through
+                mut err if err.is_system_exception() => {
+                    err.set_position(pos);
+                    Err(err.into())
+                }
+                // Other errors are wrapped in `ErrorInFunctionCall`
+                _ => Err(ERR::ErrorInFunctionCall(
+                    fn_def.name.to_string(),
+                    global.source.to_string(),
+                    err,
+                    pos,
+                )
+                .into()),
+            },
+        };
+
+        global.tail_call = orig_tail_call;
+        global.call_stack.truncate(orig_call_frames_len);
+        global.always_search_scope = orig_always_search_scope;
+
+        #[cfg(not(feature = "no_module"))]
+        global.truncate_imports(orig_imports_len);
+
+        // Restore constants
+        #[cfg(not(feature = "no_module"))]
+        if let Some(constants) = constants {
+            global.constants = constants;
+        }
+
+        caches.rewind_fn_resolution_caches(orig_fn_resolution_caches_len);
+
+        result
+    }
+
+    /// Evaluate a block of statements with a [`Continuation`], until the next `yield` statement
+    /// or until the budget of operations is exhausted.
+    ///
+    /// If `resuming` is `true`, evaluation resumes at the statement recorded in `cont`.
+    ///
+    /// The budget is only checked before each statement (so an empty loop body is also checked on
+    /// every iteration), and at least one statement is evaluated after resuming.  Statements other
+    /// than blocks, `if` statements, loops and calls to script-defined functions always run to
+    /// completion.
+    pub(crate) fn exec_generator_block(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        cont: &mut Continuation,
+        imports_base: usize,
+        depth: usize,
+        statements: &[Stmt],
+        resuming: bool,
+        level: usize,
+    ) -> RhaiResultOf<Step> {
+        if !resuming {
+            cont.frames.truncate(depth);
+            cont.frames.push(BlockFrame {
+                index: 0,
+                scope_len: scope.len(),
+                #[cfg(not(feature = "no_module"))]
+                imports_len: global.num_imports() - imports_base,
+                stmt: StmtState::None,
+            });
+        }
+
+        // Variables at global level are kept
+        let is_global = cont.is_global && depth == 0;
+
+        if !is_global {
+            global.scope_level += 1;
+        }
+
+        let mut resuming = resuming;
+        let mut value = Dynamic::UNIT;
+
+        let result = loop {
+            let frame = &mut cont.frames[depth];
+
+            // Always evaluate a statement after resuming, even if the budget is already exhausted
+            // by resuming suspended function calls
+            let was_pending = resuming && matches!(frame.stmt, StmtState::Pending);
+
+            if was_pending {
+                frame.stmt = StmtState::None;
+                resuming = false;
+            }
+
+            // Never suspend after the last statement, so its value is not lost
+            if !resuming
+                && !was_pending
+                && global.num_operations >= cont.suspend_at
+                && (frame.index < statements.len() || statements.is_empty())
+            {
+                frame.stmt = StmtState::Pending;
+                break Ok(Step::Suspend);
+            }
+
+            let stmt = match statements.get(frame.index) {
+                Some(stmt) => stmt,
+                None => break Ok(Step::Done(value)),
+            };
+
+            match self.exec_generator_stmt(
+                scope,
+                global,
+                caches,
+                lib,
+                this_ptr,
+                cont,
+                imports_base,
+                depth,
+                stmt,
+                resuming,
+                level,
+            ) {
+                Ok(Step::Done(v)) => {
+                    value = v;
+                    let frame = &mut cont.frames[depth];
+                    frame.index += 1;
+                    frame.stmt = StmtState::None;
+                }
+                result => break result,
+            }
+
+            resuming = false;
+        };
+
+        if !is_global {
+            global.scope_level -= 1;
+        }
+
+        // Remove all local variables and imported modules at the end of the block
+        if !matches!(result, Ok(Step::Yield(..) | Step::Suspend)) {
+            let frame = cont.frames.pop().expect("block frame");
+
+            if !is_global {
+                scope.rewind(frame.scope_len);
+                #[cfg(not(feature = "no_module"))]
+                global.truncate_imports(imports_base + frame.imports_len);
+            }
+        }
+        #[cfg(feature = "no_module")]
+        let _ = imports_base;
+
+        result
+    }
+
+    /// Evaluate a statement with a [`Continuation`], until the next `yield` statement or until
+    /// the budget of operations is exhausted.
+    ///
+    /// Statements that may be suspended share their logic with `eval_stmt`.
+    fn exec_generator_stmt(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        cont: &mut Continuation,
+        imports_base: usize,
+        depth: usize,
+        stmt: &Stmt,
+        resuming: bool,
+        level: usize,
+    ) -> RhaiResultOf<Step> {
+        match stmt {
+            // Yield value
+            Stmt::Yield(..) if resuming => Ok(Step::Done(Dynamic::UNIT)),
+            Stmt::Yield(expr, pos) => {
+                #[cfg(not(feature = "unchecked"))]
+                self.inc_operations(global, *pos)?;
+                #[cfg(feature = "unchecked")]
+                let _ = pos;
+
+                self.eval_expr(scope, global, caches, lib, this_ptr, expr, level)
+                    .map(|v| Step::Yield(v.flatten()))
+            }
+
+            // Block scope
+            Stmt::Block(statements) => self.exec_generator_block(
+                scope,
+                global,
+                caches,
+                lib,
+                this_ptr,
+                cont,
+                imports_base,
+                depth + 1,
+                statements.statements(),
+                resuming,
+                level,
+            ),
+
+            // If statement
+            Stmt::If(x, ..) => {
+                let (expr, if_block, else_block) = &**x;
+
+                let guard_val = if resuming {
+                    match cont.frames[depth].stmt {
+                        StmtState::If(guard_val) => guard_val,
+                        _ => unreachable!("`if` statement expected"),
+                    }
+                } else {
+                    #[cfg(not(feature = "unchecked"))]
+                    self.inc_operations(global, stmt.position())?;
+
+                    let guard_val = self.eval_if_condition(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        expr,
+                        stmt.position(),
+                        level,
+                    )?;
+
+                    cont.frames[depth].stmt = StmtState::If(guard_val);
+                    guard_val
+                };
+
+                let block = if guard_val { if_block } else { else_block };
+
+                self.exec_generator_block(
+                    scope,
+                    global,
+                    caches,
+                    lib,
+                    this_ptr,
+                    cont,
+                    imports_base,
+                    depth + 1,
+                    block.statements(),
+                    resuming,
+                    level,
+                )
+            }
+
+            // Loops
+            Stmt::While(x, ..) | Stmt::Do(x, ..) => {
+                let (expr, body) = &**x;
+
+                // `loop` has no condition, and `do` loops check the condition after the body
+                let (is_do, is_while) = match stmt {
+                    Stmt::Do(.., options, _) => (true, !options.contains(ASTFlags::NEGATED)),
+                    _ => (false, true),
+                };
+                let is_loop = matches!(expr, Expr::Unit(..));
+
+                #[cfg(not(feature = "unchecked"))]
+                if !resuming {
+                    self.inc_operations(global, stmt.position())?;
+                }
+
+                let mut resuming = resuming;
+
+                loop {
+                    if !resuming {
+                        if !is_do
+                            && !is_loop
+                            && !self
+                                .eval_condition(scope, global, caches, lib, this_ptr, expr, level)?
+                        {
+                            return Ok(Step::Done(Dynamic::UNIT));
+                        }
+
+                        self.start_loop_iteration(global, caches, lib, body, !is_loop, level)?;
+                    }
+
+                    match self.exec_generator_block(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        cont,
+                        imports_base,
+                        depth + 1,
+                        body.statements(),
+                        resuming,
+                        level,
+                    ) {
+                        Ok(Step::Done(..)) => (),
+                        Ok(step) => return Ok(step),
+                        Err(err) => match *err {
+                            // `continue` in a `do` loop skips the condition, as in `eval_stmt`
+                            ERR::LoopBreak(false, ..) if is_do => {
+                                resuming = false;
+                                continue;
+                            }
+                            ERR::LoopBreak(false, ..) => (),
+                            ERR::LoopBreak(true, ..) => return Ok(Step::Done(Dynamic::UNIT)),
+                            _ => return Err(err),
+                        },
+                    }
+
+                    resuming = false;
+
+                    if is_do
+                        && self.eval_condition(scope, global, caches, lib, this_ptr, expr, level)?
+                            != is_while
+                    {
+                        return Ok(Step::Done(Dynamic::UNIT));
+                    }
+                }
+            }
+
+            // For loop
+            Stmt::For(x, ..) => {
+                let body = &x.3;
+
+                let mut resuming = resuming;
+
+                let mut state = if resuming {
+                    match mem::replace(&mut cont.frames[depth].stmt, StmtState::None) {
+                        StmtState::For(state) => state,
+                        _ => unreachable!("`for` loop expected"),
+                    }
+                } else {
+                    #[cfg(not(feature = "unchecked"))]
+                    self.inc_operations(global, stmt.position())?;
+
+                    self.start_for_loop(scope, global, caches, lib, this_ptr, x, level)?
+                        .into()
+                };
+
+                let result = loop {
+                    if !resuming {
+                        match self
+                            .next_for_iteration(scope, global, caches, lib, &mut state, x, level)
+                        {
+                            Ok(true) => (),
+                            Ok(false) => break Ok(Step::Done(Dynamic::UNIT)),
+                            Err(err) => break Err(err),
+                        }
+                    }
+
+                    match self.exec_generator_block(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        cont,
+                        imports_base,
+                        depth + 1,
+                        body.statements(),
+                        resuming,
+                        level,
+                    ) {
+                        Ok(Step::Done(..)) => (),
+                        Ok(step) => {
+                            cont.frames[depth].stmt = StmtState::For(state);
+                            return Ok(step);
+                        }
+                        Err(err) => match *err {
+                            ERR::LoopBreak(false, ..) => (),
+                            ERR::LoopBreak(true, ..) => break Ok(Step::Done(Dynamic::UNIT)),
+                            _ => break Err(err),
+                        },
+                    }
+
+                    resuming = false;
+                };
+
+                scope.rewind(state.orig_scope_len);
+
+                result
+            }
+
+            // Other statements, which are never suspended unless calling a script-defined function
+            _ => {
+                let call = if resuming {
+                    match mem::replace(&mut cont.frames[depth].stmt, StmtState::None) {
+                        StmtState::Call(state) => Some(state),
+                        _ => unreachable!("function call expected"),
+                    }
+                } else if cont.suspend_at < u64::MAX {
+                    self.start_suspendable_call(scope, global, caches, lib, this_ptr, stmt, level)?
+                } else {
+                    None
+                };
+
+                if let Some(state) = call {
+                    return self.resume_suspendable_call(
+                        scope, global, caches, lib, cont, depth, stmt, state, level,
+                    );
+                }
+
+                #[cfg(not(feature = "no_module"))]
+                let imports_len = global.num_imports();
+
+                let rewind_scope = !cont.is_global || depth > 0;
+
+                let value = self.eval_stmt(
+                    scope,
+                    global,
+                    caches,
+                    lib,
+                    this_ptr,
+                    stmt,
+                    rewind_scope,
+                    level,
+                )?;
+
+                // New modules with global functions affect function resolution
+                #[cfg(not(feature = "no_module"))]
+                if matches!(stmt, Stmt::Import(..))
+                    && global
+                        .scan_imports_raw()
+                        .skip(imports_len)
+                        .any(|(.., m)| m.contains_indexed_global_functions())
+                {
+                    caches.clear_fn_resolution_cache();
+                }
+
+                Ok(Step::Done(value))
+            }
+        }
+    }
+
+    /// Start a statement that calls a script-defined function with a [`SuspendedFn`], so that the
+    /// call can be suspended half-way.
+    ///
+    /// The statement must be a function call, a `let`/`const` statement initialized by a function
+    /// call, or a `return` statement returning a function call.  The function must be found in
+    /// `lib` by a call that is not qualified and does not capture the parent scope, and must not
+    /// be a generator function.
+    ///
+    /// Returns [`None`] (without evaluating anything) if the statement is not such a call.
+    fn start_suspendable_call(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        stmt: &Stmt,
+        level: usize,
+    ) -> RhaiResultOf<Option<Box<SuspendedFn>>> {
+        let x = match suspendable_call_expr(stmt) {
+            Some(x) if !x.is_qualified() && !x.capture_parent_scope => x,
+            _ => return Ok(None),
+        };
+
+        let fn_def = match SuspendedFn::find_fn(lib, x.hashes.script) {
+            Some(fn_def) if !x.hashes.is_native_only() => fn_def,
+            _ => return Ok(None),
+        };
+
+        #[cfg(not(feature = "unchecked"))]
+        self.inc_operations(global, stmt.position())?;
+
+        if let Stmt::Var(v, options, pos) = stmt {
+            self.check_new_var(scope, global, lib, this_ptr, &v.0, *options, *pos, level)?;
+        }
+
+        let args = x
+            .args
+            .iter()
+            .map(|expr| {
+                self.get_arg_value(scope, global, caches, lib, this_ptr, expr, level)
+                    .map(|(value, ..)| value.flatten())
+            })
+            .collect::<RhaiResultOf<crate::StaticVec<_>>>()?;
+
+        Ok(Some(
+            SuspendedFn::new(fn_def, None, args.into_iter()).into(),
+        ))
+    }
+
+    /// Resume the call of a statement started by `start_suspendable_call`, until it finishes or
+    /// until the budget of operations is exhausted.
+    fn resume_suspendable_call(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        cont: &mut Continuation,
+        depth: usize,
+        stmt: &Stmt,
+        mut state: Box<SuspendedFn>,
+        level: usize,
+    ) -> RhaiResultOf<Step> {
+        let pos = suspendable_call_expr(stmt).expect("function call").pos;
+
+        let value = match self.resume_suspended_fn(
+            global,
+            caches,
+            lib,
+            &mut state,
+            cont.suspend_at,
+            pos,
+            level,
+        )? {
+            Step::Done(value) => value.flatten(),
+            Step::Suspend => {
+                cont.frames[depth].stmt = StmtState::Call(state);
+                return Ok(Step::Suspend);
+            }
+            Step::Yield(..) => unreachable!("generator function called"),
+        };
+
+        match stmt {
+            Stmt::Var(x, options, ..) => {
+                let rewind_scope = !cont.is_global || depth > 0;
+                self.define_var(scope, global, lib, &x.0, x.2, *options, value, rewind_scope);
+                Ok(Step::Done(Dynamic::UNIT))
+            }
+            Stmt::Return(.., pos) => Err(ERR::Return(value, *pos).into()),
+            _ => Ok(Step::Done(value)),
+        }
+    }
+}
+
+/// Get the function call made by a statement that may call a script-defined function with a
+/// [`SuspendedFn`].
+#[must_use]
+fn suspendable_call_expr(stmt: &Stmt) -> Option<&FnCallExpr> {
+    let expr = match stmt {
+        Stmt::FnCall(x, ..) => return Some(x),
+        Stmt::Expr(expr) => expr,
+        Stmt::Var(x, ..) => &x.1,
+        Stmt::Return(Some(expr), options, ..) if !options.contains(ASTFlags::BREAK) => expr,
+        _ => return None,
+    };
+
+    match expr {
+        Expr::FnCall(x, ..) => Some(x),
+        _ => None,
+    }
+}
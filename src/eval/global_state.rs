@@ -11,6 +11,40 @@ use std::{fmt, marker::PhantomData};
 pub type GlobalConstants =
     crate::Shared<crate::Locked<std::collections::BTreeMap<crate::ImmutableString, Dynamic>>>;
 
+/// An active call to a script-defined function.
+///
+/// Not available under `no_function`.
+#[cfg(not(feature = "no_function"))]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct CallFrame {
+    /// Function name.
+    pub fn_name: crate::ImmutableString,
+    /// Source of the function, empty if none.
+    pub source: Identifier,
+    /// [Position][`crate::Position`] of the function call.
+    pub pos: crate::Position,
+}
+
+#[cfg(not(feature = "no_function"))]
+impl fmt::Display for CallFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match crate::parser::anonymous_fn_display_name(&self.fn_name) {
+            Some(name) => f.write_str(&name)?,
+            None => f.write_str(&self.fn_name)?,
+        }
+
+        if !self.pos.is_none() {
+            if self.source.is_empty() {
+                write!(f, " @ {:?}", self.pos)?;
+            } else {
+                write!(f, ": {} @ {:?}", self.source, self.pos)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// _(internals)_ Global runtime states.
 /// Exported under the `internals` feature only.
 //
@@ -35,8 +69,26 @@ pub struct GlobalRuntimeState<'a> {
     pub source: Identifier,
     /// Number of operations performed.
     pub num_operations: u64,
+    /// Amount of fuel consumed.
+    ///
+    /// Not available under `unchecked`.
+    #[cfg(not(feature = "unchecked"))]
+    pub fuel_consumed: u64,
+    /// Maximum amount of fuel allowed to be consumed (0 for unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    fuel_limit: u64,
+    /// Number of operations, amount of fuel consumed and function call level at the start of the
+    /// outermost nested evaluation in progress, if [nested limits][Engine::set_nested_limits] are set.
+    #[cfg(not(feature = "unchecked"))]
+    pub(crate) nested_eval: Option<(u64, u64, usize)>,
     /// Number of modules loaded.
     pub num_modules_loaded: usize,
+    /// Number of function calls resolved via the function resolution cache.
+    pub(crate) fn_cache_hits: u64,
+    /// Number of function calls not found in the function resolution cache.
+    pub(crate) fn_cache_misses: u64,
+    /// Largest number of variables in the [`Scope`][crate::Scope] seen so far.
+    pub(crate) peak_scope_size: usize,
     /// Level of the current scope.
     ///
     /// The global (root) level is zero, a new block (or function call) is one level higher, and so on.
@@ -66,9 +118,42 @@ pub struct GlobalRuntimeState<'a> {
     pub constants: Option<GlobalConstants>,
     /// Custom state that can be used by the external host.
     pub tag: Dynamic,
+    /// Custom data passed in for this evaluation run only, if any.
+    pub(crate) context_data: Option<crate::Shared<dyn crate::types::dynamic::Variant>>,
+    /// Log for recording or replaying native function call results in this evaluation run, if any.
+    ///
+    /// It is shared so that native calls nested within native calls are logged in the same place.
+    pub(crate) native_call_log: Option<crate::Shared<crate::Locked<crate::func::NativeCallLog>>>,
+    /// Connection to the [`EvalFuture`][crate::EvalFuture] driving this evaluation run, if any.
+    #[cfg(feature = "async")]
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(target_family = "wasm"))]
+    pub(crate) async_bridge: Option<crate::api::eval_async::Bridge>,
+    /// Active calls to script-defined functions, outermost first.
+    #[cfg(not(feature = "no_function"))]
+    pub call_stack: Vec<CallFrame>,
+    /// Queue of cooperative tasks spawned during the evaluation.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) tasks: super::TaskQueue,
+    /// Suspended generators created during the evaluation.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) generators: super::GeneratorTable,
+    /// Tail calls of the innermost script-defined function being optimized, if any.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) tail_call: Option<Box<crate::func::script::TailCall>>,
+    /// Bodies of script-defined functions compiled into bytecode, if evaluating bytecode.
+    #[cfg(feature = "bytecode")]
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) compiled_fns: Option<crate::Shared<super::bytecode::CompiledFns>>,
     /// Debugging interface.
     #[cfg(feature = "debugging")]
     pub debugger: super::Debugger,
+    /// Recorder of the profile of this evaluation run, if profiling.
+    #[cfg(feature = "profiling")]
+    pub(crate) profiler: Option<Box<crate::api::profiling::Profiler>>,
+    /// Coverage of this evaluation run, if tracking coverage.
+    #[cfg(feature = "coverage")]
+    pub(crate) coverage: Option<Box<crate::api::coverage::Coverage>>,
     /// Take care of the lifetime parameter.
     dummy: PhantomData<&'a ()>,
 }
@@ -85,7 +170,16 @@ impl GlobalRuntimeState<'_> {
             modules: crate::StaticVec::new_const(),
             source: Identifier::new_const(),
             num_operations: 0,
+            #[cfg(not(feature = "unchecked"))]
+            fuel_consumed: 0,
+            #[cfg(not(feature = "unchecked"))]
+            fuel_limit: engine.fuel_limit(),
+            #[cfg(not(feature = "unchecked"))]
+            nested_eval: None,
             num_modules_loaded: 0,
+            fn_cache_hits: 0,
+            fn_cache_misses: 0,
+            peak_scope_size: 0,
             scope_level: 0,
             always_search_scope: false,
             #[cfg(not(feature = "no_module"))]
@@ -97,6 +191,23 @@ impl GlobalRuntimeState<'_> {
             constants: None,
 
             tag: engine.default_tag().clone(),
+            context_data: None,
+            native_call_log: None,
+            #[cfg(feature = "async")]
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(target_family = "wasm"))]
+            async_bridge: None,
+            #[cfg(not(feature = "no_function"))]
+            call_stack: Vec::new(),
+            #[cfg(not(feature = "no_function"))]
+            tasks: super::TaskQueue::default(),
+            #[cfg(not(feature = "no_function"))]
+            generators: super::GeneratorTable::default(),
+            #[cfg(not(feature = "no_function"))]
+            tail_call: None,
+            #[cfg(feature = "bytecode")]
+            #[cfg(not(feature = "no_function"))]
+            compiled_fns: None,
 
             #[cfg(feature = "debugging")]
             debugger: crate::eval::Debugger::new(
@@ -111,10 +222,76 @@ impl GlobalRuntimeState<'_> {
                     Dynamic::UNIT
                 },
             ),
+            #[cfg(feature = "profiling")]
+            profiler: engine
+                .profile
+                .as_ref()
+                .map(|_| crate::api::profiling::Profiler::new().into()),
+            #[cfg(feature = "coverage")]
+            coverage: engine.coverage.as_ref().map(|_| Default::default()),
 
             dummy: PhantomData::default(),
         }
     }
+    /// Get the custom data of a specific type for this evaluation run.
+    ///
+    /// The data passed in via [`Engine::eval_with_context_data`] takes precedence; otherwise the
+    /// [tag][GlobalRuntimeState::tag] is used.
+    #[inline]
+    #[must_use]
+    pub(crate) fn context_data<T: crate::types::dynamic::Variant + Clone>(&self) -> Option<&T> {
+        match self.context_data {
+            Some(ref data) => data.as_any().downcast_ref::<T>(),
+            None => self.tag.downcast_ref::<T>(),
+        }
+    }
+    /// Reset the statistics and recorders of this [`GlobalRuntimeState`] for a new evaluation run,
+    /// keeping imported [modules][crate::Module] and the tag.
+    pub(crate) fn start_new_run(&mut self, engine: &Engine) {
+        self.num_operations = 0;
+        #[cfg(not(feature = "unchecked"))]
+        {
+            self.fuel_consumed = 0;
+            self.fuel_limit = engine.fuel_limit();
+            self.nested_eval = None;
+        }
+        self.num_modules_loaded = 0;
+        self.fn_cache_hits = 0;
+        self.fn_cache_misses = 0;
+        self.peak_scope_size = 0;
+
+        #[cfg(feature = "debugging")]
+        if engine.debugger.is_some() {
+            self.debugger.status = crate::eval::DebuggerStatus::Init;
+        }
+        #[cfg(feature = "profiling")]
+        {
+            self.profiler = engine
+                .profile
+                .as_ref()
+                .map(|_| crate::api::profiling::Profiler::new().into());
+        }
+        #[cfg(feature = "coverage")]
+        {
+            self.coverage = engine.coverage.as_ref().map(|_| Default::default());
+        }
+
+        let _ = engine;
+    }
+    /// Get the amount of fuel remaining, or [`None`] if fuel is not metered
+    /// (see [`Engine::set_fuel_limit`]).
+    ///
+    /// Not available under `unchecked`.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline]
+    #[must_use]
+    pub const fn fuel_remaining(&self) -> Option<u64> {
+        if self.fuel_limit == 0 {
+            None
+        } else {
+            Some(self.fuel_limit.saturating_sub(self.fuel_consumed))
+        }
+    }
     /// Get the length of the stack of globally-imported [modules][crate::Module].
     ///
     /// Not available under `no_module`.
@@ -363,7 +540,13 @@ impl fmt::Debug for GlobalRuntimeState<'_> {
 
         f.field("source", &self.source)
             .field("num_operations", &self.num_operations)
-            .field("num_modules_loaded", &self.num_modules_loaded);
+            .field("num_modules_loaded", &self.num_modules_loaded)
+            .field("fn_cache_hits", &self.fn_cache_hits)
+            .field("fn_cache_misses", &self.fn_cache_misses)
+            .field("peak_scope_size", &self.peak_scope_size);
+
+        #[cfg(not(feature = "unchecked"))]
+        f.field("fuel_consumed", &self.fuel_consumed);
 
         #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
         f.field("fn_hash_indexing", &self.fn_hash_indexing);
@@ -375,6 +558,140 @@ impl fmt::Debug for GlobalRuntimeState<'_> {
         #[cfg(not(feature = "no_function"))]
         f.field("constants", &self.constants);
 
+        #[cfg(not(feature = "no_function"))]
+        f.field("call_stack", &self.call_stack);
+
+        f.finish()
+    }
+}
+
+/// A read-only view of the global runtime state of an evaluation run.
+///
+/// This is a stable alternative to `GlobalRuntimeState` (which is only exported under
+/// `internals`), for native Rust functions and for inspection after an evaluation run.
+///
+/// Obtain it via [`NativeCallContext::global_state`][crate::NativeCallContext::global_state],
+/// [`EvalContext::global_state`][crate::EvalContext::global_state] or a callback registered via
+/// [`Engine::on_eval_end`].
+#[derive(Clone, Copy)]
+pub struct GlobalStateView<'a> {
+    /// The global runtime state.
+    global: &'a GlobalRuntimeState<'a>,
+}
+
+impl<'a> From<&'a GlobalRuntimeState<'a>> for GlobalStateView<'a> {
+    #[inline(always)]
+    fn from(global: &'a GlobalRuntimeState<'a>) -> Self {
+        Self { global }
+    }
+}
+
+impl<'a> GlobalStateView<'a> {
+    /// Get the current source, if any.
+    #[inline]
+    #[must_use]
+    pub fn source(&self) -> Option<&'a str> {
+        if self.global.source.is_empty() {
+            None
+        } else {
+            Some(self.global.source.as_str())
+        }
+    }
+    /// Get the number of operations performed so far.
+    #[inline(always)]
+    #[must_use]
+    pub const fn num_operations(&self) -> u64 {
+        self.global.num_operations
+    }
+    /// Get the amount of fuel consumed so far.
+    ///
+    /// Not available under `unchecked`.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn fuel_consumed(&self) -> u64 {
+        self.global.fuel_consumed
+    }
+    /// Get the amount of fuel remaining, or [`None`] if fuel is not metered
+    /// (see [`Engine::set_fuel_limit`]).
+    ///
+    /// Not available under `unchecked`.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn fuel_remaining(&self) -> Option<u64> {
+        self.global.fuel_remaining()
+    }
+    /// Get the number of [modules][crate::Module] loaded so far.
+    #[inline(always)]
+    #[must_use]
+    pub const fn num_modules_loaded(&self) -> usize {
+        self.global.num_modules_loaded
+    }
+    /// Get the number of globally-imported [modules][crate::Module].
+    ///
+    /// Not available under `no_module`.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn num_imports(&self) -> usize {
+        self.global.num_imports()
+    }
+    /// Get an iterator over the globally-imported [modules][crate::Module] in reverse order,
+    /// i.e. the most recently imported first.
+    ///
+    /// Not available under `no_module`.
+    #[cfg(not(feature = "no_module"))]
+    #[inline(always)]
+    pub fn iter_imports(&self) -> impl Iterator<Item = (&'a str, &'a crate::Module)> {
+        self.global.iter_imports()
+    }
+    /// Get the active calls to script-defined functions, outermost first.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn call_stack(&self) -> &'a [CallFrame] {
+        &self.global.call_stack
+    }
+    /// Get the custom state (the tag) of the evaluation run.
+    #[inline(always)]
+    #[must_use]
+    pub const fn tag(&self) -> &'a Dynamic {
+        &self.global.tag
+    }
+    /// _(debugging)_ Get the current status of the debugger.
+    /// Exported under the `debugging` feature only.
+    #[cfg(feature = "debugging")]
+    #[inline(always)]
+    #[must_use]
+    pub const fn debugger_status(&self) -> super::DebuggerStatus {
+        self.global.debugger.status
+    }
+}
+
+impl fmt::Debug for GlobalStateView<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("GlobalStateView");
+
+        #[cfg(not(feature = "no_module"))]
+        f.field(
+            "imports",
+            &self.iter_imports().map(|(k, ..)| k).collect::<Vec<_>>(),
+        );
+
+        f.field("source", &self.source())
+            .field("num_operations", &self.num_operations())
+            .field("num_modules_loaded", &self.num_modules_loaded());
+
+        #[cfg(not(feature = "unchecked"))]
+        f.field("fuel_consumed", &self.fuel_consumed());
+
+        #[cfg(not(feature = "no_function"))]
+        f.field("call_stack", &self.call_stack());
+
         f.finish()
     }
 }
@@ -1,24 +1,48 @@
+mod arena;
+mod bytecode;
 mod cache;
 mod chaining;
+pub mod dap;
 mod data_check;
 mod debugger;
 mod eval_context;
 mod expr;
+mod generator;
 mod global_state;
 mod stmt;
 mod target;
+mod tasks;
+mod trace;
 
+pub use arena::ScratchArena;
+#[cfg(feature = "bytecode")]
+pub use bytecode::Bytecode;
+#[cfg(feature = "bytecode")]
+pub(crate) use bytecode::RunContext;
 pub use cache::{Caches, FnResolutionCache, FnResolutionCacheEntry};
 #[cfg(any(not(feature = "no_index"), not(feature = "no_object")))]
 pub use chaining::ChainType;
 #[cfg(feature = "debugging")]
 pub use debugger::{
     BreakPoint, CallStackFrame, Debugger, DebuggerCommand, DebuggerEvent, DebuggerStatus,
-    OnDebuggerCallback, OnDebuggingInit,
+    HitCondition, OnDebuggerCallback, OnDebuggingInit, Watch,
 };
 pub use eval_context::EvalContext;
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "unchecked"))]
+pub(crate) use generator::Continuation;
+#[cfg(not(feature = "no_function"))]
+pub use generator::{Generator, GeneratorTable};
+#[cfg(not(feature = "no_function"))]
+pub(crate) use generator::{Step, SuspendedFn};
+#[cfg(not(feature = "no_function"))]
+pub use global_state::CallFrame;
 #[cfg(not(feature = "no_module"))]
 #[cfg(not(feature = "no_function"))]
 pub use global_state::GlobalConstants;
-pub use global_state::GlobalRuntimeState;
+pub use global_state::{GlobalRuntimeState, GlobalStateView};
 pub use target::{calc_index, calc_offset_len, Target};
+#[cfg(not(feature = "no_function"))]
+pub use tasks::{Task, TaskQueue};
+#[cfg(feature = "debugging")]
+pub use trace::{ScopeChange, Trace, TraceReplayer, TraceStep};
@@ -3,18 +3,168 @@
 use super::{Caches, EvalContext, GlobalRuntimeState, Target};
 use crate::api::events::VarDefInfo;
 use crate::ast::{
-    ASTFlags, BinaryExpr, Expr, Ident, OpAssignment, Stmt, SwitchCasesCollection, TryCatchBlock,
+    ASTFlags, BinaryExpr, Expr, Ident, OpAssignment, Stmt, StmtBlock, SwitchCasesCollection,
+    SwitchPattern, TryCatchBlock,
 };
 use crate::func::get_hasher;
 use crate::types::dynamic::{AccessMode, Union};
 use crate::{
     Dynamic, Engine, ImmutableString, Module, Position, RhaiResult, RhaiResultOf, Scope, ERR, INT,
 };
+#[cfg(not(feature = "unchecked"))]
+use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// Iterator of a `for` loop.
+pub(crate) enum ForIterator {
+    /// Values, with the counter variable counting from zero.
+    Values(Box<dyn Iterator<Item = RhaiResultOf<Dynamic>>>),
+    /// Index/value pairs, with the counter variable holding the index.
+    Indexed(Box<dyn Iterator<Item = (Dynamic, Dynamic)>>),
+    /// Integers in an exclusive range, iterated in place.
+    Range(crate::ExclusiveRange),
+    /// Integers in an inclusive range, iterated in place.
+    InclusiveRange(crate::InclusiveRange),
+    /// Values yielded by a [generator][crate::Generator], with the counter variable counting
+    /// from zero.
+    #[cfg(not(feature = "no_function"))]
+    Generator(crate::Generator),
+}
+
+impl ForIterator {
+    /// Get the next item, with its index (if any).
+    ///
+    /// Generators are resumed with the [`Engine`] until they yield the next value.
+    #[inline]
+    pub(crate) fn next_item(
+        &mut self,
+        engine: &Engine,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        pos: Position,
+        level: usize,
+    ) -> Option<RhaiResultOf<(Option<Dynamic>, Dynamic)>> {
+        #[cfg(feature = "no_function")]
+        let _ = (engine, global, caches, lib, pos, level);
+
+        match self {
+            Self::Values(iter) => iter.next().map(|v| v.map(|v| (None, v))),
+            Self::Indexed(iter) => iter.next().map(|(index, v)| Ok((Some(index), v))),
+            Self::Range(range) => range.next().map(|n| Ok((None, Dynamic::from_int(n)))),
+            Self::InclusiveRange(range) => range.next().map(|n| Ok((None, Dynamic::from_int(n)))),
+            #[cfg(not(feature = "no_function"))]
+            Self::Generator(gen) => engine
+                .resume_generator(global, caches, lib, gen, pos, level)
+                .transpose()
+                .map(|v| v.map(|v| (None, v))),
+        }
+    }
+}
+
+/// State of a running `for` loop.
+pub(crate) struct ForLoop {
+    /// Iterator of the loop.
+    iter: ForIterator,
+    /// Index of the counter variable in the [`Scope`], or `usize::MAX` if there is none.
+    counter_index: usize,
+    /// Index of the loop variable in the [`Scope`].
+    index: usize,
+    /// Number of variables in the [`Scope`] before the loop variables are added.
+    pub orig_scope_len: usize,
+    /// Number of items iterated so far.
+    count: usize,
+}
+
 impl Engine {
+    /// Get the iterator of a value in a `for` loop from the type iterators, or give the value
+    /// back if there is none.
+    pub(crate) fn get_type_iter(
+        &self,
+        global: &GlobalRuntimeState,
+        iter_obj: Dynamic,
+    ) -> Result<ForIterator, Dynamic> {
+        let iter_type = iter_obj.type_id();
+
+        // lib should only contain scripts, so technically they cannot have iterators
+
+        // Search order:
+        // 1) Iterators of index/value pairs - registered via Engine::register_indexed_iterator_fn
+        // 2) Global namespace - functions registered via Engine::register_XXX
+        // 3) Global modules - packages
+        // 4) Imported modules - functions marked with global namespace
+        // 5) Global sub-modules - functions marked with global namespace
+        if let Some(func) = self.indexed_iterators.get(&iter_type) {
+            return Ok(ForIterator::Indexed(func(iter_obj)));
+        }
+
+        #[cfg(not(feature = "no_function"))]
+        if iter_obj.is::<crate::Generator>() {
+            return Ok(ForIterator::Generator(iter_obj.cast()));
+        }
+
+        // Integer ranges are iterated in place instead of being boxed
+        if iter_obj.is::<crate::ExclusiveRange>() {
+            return Ok(ForIterator::Range(iter_obj.cast()));
+        }
+        if iter_obj.is::<crate::InclusiveRange>() {
+            return Ok(ForIterator::InclusiveRange(iter_obj.cast()));
+        }
+
+        let func = self
+            .global_modules
+            .iter()
+            .find_map(|m| m.get_iter(iter_type));
+
+        #[cfg(not(feature = "no_module"))]
+        let func = func.or_else(|| global.get_iter(iter_type)).or_else(|| {
+            self.global_sub_modules
+                .values()
+                .find_map(|m| m.get_qualified_iter(iter_type))
+        });
+        #[cfg(feature = "no_module")]
+        let _ = global;
+
+        match func {
+            Some(func) => Ok(ForIterator::Values(func(iter_obj))),
+            None => Err(iter_obj),
+        }
+    }
+    /// Get the iterator of a value in a `for` loop, including the fields of custom types.
+    pub(crate) fn get_for_iter(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        iter_obj: Dynamic,
+        pos: Position,
+        level: usize,
+    ) -> Option<ForIterator> {
+        match self.get_type_iter(global, iter_obj) {
+            Ok(iter) => Some(iter),
+            // Fields of custom types - built with `TypeBuilder::with_field_iter`
+            #[cfg(not(feature = "no_object"))]
+            #[cfg(not(feature = "no_index"))]
+            Err(iter_obj) => {
+                let iter_type = iter_obj.type_id();
+
+                self.field_iterators.get(&iter_type).map(|fields| {
+                    let pairs =
+                        self.get_field_pairs(global, caches, lib, iter_obj, fields, pos, level);
+                    ForIterator::Values(Box::new(pairs.into_iter()))
+                })
+            }
+            #[cfg(any(feature = "no_object", feature = "no_index"))]
+            Err(..) => {
+                let _ = (caches, lib, pos, level);
+                None
+            }
+        }
+    }
+
     /// Evaluate a statements block.
     //
     // # Implementation Notes
@@ -82,13 +232,13 @@ impl Engine {
                         // When new module is imported with global functions and there is already
                         // a new cache, clear it - notice that this is expensive as all function
                         // resolutions must start again
-                        caches.fn_resolution_cache_mut().clear();
+                        caches.clear_fn_resolution_cache();
                     } else if restore_orig_state {
                         // When new module is imported with global functions, push a new cache
                         caches.push_fn_resolution_cache();
                     } else {
                         // When the block is to be evaluated in-place, just clear the current cache
-                        caches.fn_resolution_cache_mut().clear();
+                        caches.clear_fn_resolution_cache();
                     }
                 }
             }
@@ -111,6 +261,19 @@ impl Engine {
         result
     }
 
+    /// Ask the [`on_invalid_assignment`][Engine::on_invalid_assignment] callback, if any, whether
+    /// an attempt to assign to a read-only target is permitted.
+    #[inline]
+    pub(crate) fn allow_invalid_assignment(
+        &self,
+        name: &str,
+        value: &Dynamic,
+        pos: Position,
+    ) -> RhaiResultOf<bool> {
+        self.invalid_assignment
+            .as_ref()
+            .map_or(Ok(false), |callback| callback(name, value, pos))
+    }
     /// Evaluate an op-assignment statement.
     pub(crate) fn eval_op_assignment(
         &self,
@@ -124,8 +287,26 @@ impl Engine {
         level: usize,
     ) -> RhaiResultOf<()> {
         if target.is_read_only() {
-            // Assignment to constant variable
-            return Err(ERR::ErrorAssignmentToConstant(root.0.to_string(), root.1).into());
+            if !self.allow_invalid_assignment(root.0, &new_val, root.1)? {
+                // Assignment to constant variable
+                return Err(ERR::ErrorAssignmentToConstant(root.0.to_string(), root.1).into());
+            }
+
+            // Write to the target as if it were not read-only, then make it read-only again
+            target
+                .write_lock::<Dynamic>()
+                .unwrap()
+                .set_access_mode(AccessMode::ReadWrite);
+
+            let result =
+                self.eval_op_assignment(global, caches, lib, op_info, target, root, new_val, level);
+
+            target
+                .write_lock::<Dynamic>()
+                .unwrap()
+                .set_access_mode(AccessMode::ReadOnly);
+
+            return result;
         }
 
         let mut new_val = new_val;
@@ -146,7 +327,7 @@ impl Engine {
             let level = level + 1;
 
             match self.call_native_fn(
-                global, caches, lib, op_assign, hash, args, true, true, op_pos, level,
+                global, caches, lib, op_assign, hash, args, true, true, 0, op_pos, level,
             ) {
                 Ok(_) => {
                     #[cfg(not(feature = "unchecked"))]
@@ -157,7 +338,7 @@ impl Engine {
                     // Expand to `var = var op rhs`
                     let (value, ..) = self
                         .call_native_fn(
-                            global, caches, lib, op, hash_op, args, true, false, op_pos, level,
+                            global, caches, lib, op, hash_op, args, true, false, 0, op_pos, level,
                         )
                         .map_err(|err| err.fill_position(op_info.pos))?;
 
@@ -193,6 +374,410 @@ impl Engine {
     // possibly by-passing important cleanup tasks at the end.
     //
     // Errors that are not recoverable, such as system errors or safety errors, can use `?`.
+    /// Match a value against a pattern in a `switch` case.
+    ///
+    /// If it matches, the variables bound by the pattern are pushed into the [`Scope`].
+    fn match_switch_pattern(
+        &self,
+        _scope: &mut Scope,
+        pattern: &SwitchPattern,
+        value: &Dynamic,
+    ) -> bool {
+        match pattern {
+            SwitchPattern::Type(name) => {
+                let type_name = value.type_name();
+                type_name == name.as_str() || self.map_type_name(type_name) == name.as_str()
+            }
+            #[cfg(not(feature = "no_index"))]
+            SwitchPattern::Array(vars) => match value.read_lock::<crate::Array>() {
+                Some(array) if array.len() == vars.len() => {
+                    for (var, item) in vars.iter().zip(array.iter()) {
+                        if let Some(var) = var {
+                            _scope.push(var.name.clone(), item.clone());
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            },
+            #[cfg(not(feature = "no_object"))]
+            SwitchPattern::Map(props) => match value.read_lock::<crate::Map>() {
+                Some(map) if props.iter().all(|p| map.contains_key(p.name.as_str())) => {
+                    for p in props {
+                        _scope.push(p.name.clone(), map[p.name.as_str()].clone());
+                    }
+                    true
+                }
+                _ => false,
+            },
+        }
+    }
+
+    /// Get the `[name, value]` pairs of the fields of a custom type, by calling their getters.
+    ///
+    /// Iteration stops at the first getter that fails, which is returned as the last item.
+    #[cfg(not(feature = "no_object"))]
+    #[cfg(not(feature = "no_index"))]
+    fn get_field_pairs(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        mut obj: Dynamic,
+        fields: &[crate::Identifier],
+        pos: Position,
+        level: usize,
+    ) -> Vec<RhaiResultOf<Dynamic>> {
+        let mut pairs = Vec::with_capacity(fields.len());
+
+        for name in fields {
+            let getter = crate::engine::make_getter(name);
+            let hash = crate::calc_fn_hash(&getter, 1);
+            let args = &mut [&mut obj];
+
+            match self.call_native_fn(
+                global, caches, lib, &getter, hash, args, false, false, 0, pos, level,
+            ) {
+                Ok((value, ..)) => {
+                    let pair: crate::Array = vec![name.into(), value];
+                    pairs.push(Ok(pair.into()));
+                }
+                Err(err) => {
+                    pairs.push(Err(err));
+                    break;
+                }
+            }
+        }
+
+        pairs
+    }
+    /// Evaluate the condition of an `if` statement or a loop.
+    #[inline]
+    pub(crate) fn eval_condition(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        expr: &Expr,
+        level: usize,
+    ) -> RhaiResultOf<bool> {
+        self.eval_expr(scope, global, caches, lib, this_ptr, expr, level)?
+            .as_bool()
+            .map_err(|typ| self.make_type_mismatch_err::<bool>(typ, expr.position()))
+    }
+    /// Evaluate the condition of an `if` statement, recording the branch taken into the coverage
+    /// of the evaluation run, if tracking coverage.
+    #[inline]
+    pub(crate) fn eval_if_condition(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        expr: &Expr,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<bool> {
+        let guard_val = self.eval_condition(scope, global, caches, lib, this_ptr, expr, level);
+
+        #[cfg(feature = "coverage")]
+        if let (Some(ref mut coverage), Ok(b)) = (&mut global.coverage, &guard_val) {
+            let branch = if *b { 0 } else { 1 };
+            coverage.record_branch(&global.source, pos, branch);
+        }
+        #[cfg(not(feature = "coverage"))]
+        let _ = pos;
+
+        guard_val
+    }
+    /// Start an iteration of a loop, before its body is evaluated.
+    ///
+    /// Fuel is consumed and pending tasks get a chance to run even if the loop body is empty.
+    /// An empty body of a loop without a condition also counts as an operation, so that it cannot
+    /// spin forever.
+    #[inline]
+    pub(crate) fn start_loop_iteration(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        body: &StmtBlock,
+        has_condition: bool,
+        level: usize,
+    ) -> RhaiResultOf<()> {
+        #[cfg(not(feature = "unchecked"))]
+        self.consume_fuel(global, self.fuel_costs().loop_iteration, body.position())?;
+
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "unchecked"))]
+        self.run_due_task(global, caches, lib, body.position(), level)?;
+
+        #[cfg(not(feature = "unchecked"))]
+        if !has_condition && body.is_empty() {
+            self.inc_operations(global, body.position())?;
+        }
+
+        #[cfg(feature = "unchecked")]
+        let _ = (global, body, has_condition);
+        #[cfg(any(feature = "no_function", feature = "unchecked"))]
+        let _ = (caches, lib, level);
+
+        Ok(())
+    }
+    /// Start a `for` loop: evaluate the value to iterate and add the loop variables to the
+    /// [`Scope`].
+    ///
+    /// The loop variables are only added if there is no error.
+    pub(crate) fn start_for_loop(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        x: &(Ident, Ident, Expr, StmtBlock),
+        level: usize,
+    ) -> RhaiResultOf<ForLoop> {
+        let iter_obj = self
+            .eval_expr(scope, global, caches, lib, this_ptr, &x.2, level)?
+            .flatten();
+
+        self.new_for_loop(scope, global, caches, lib, iter_obj, x, level)
+    }
+    /// Start a `for` loop over an evaluated value: add the loop variables to the [`Scope`].
+    ///
+    /// The loop variables are only added if the value can be iterated.
+    pub(crate) fn new_for_loop(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        iter_obj: Dynamic,
+        x: &(Ident, Ident, Expr, StmtBlock),
+        level: usize,
+    ) -> RhaiResultOf<ForLoop> {
+        let (var_name, counter, expr, ..) = x;
+
+        let iter = self
+            .get_for_iter(global, caches, lib, iter_obj, expr.position(), level)
+            .ok_or_else(|| ERR::ErrorFor(expr.start_position()))?;
+
+        // Add the loop variables
+        let orig_scope_len = scope.len();
+
+        #[cfg(feature = "debugging")]
+        global.debugger.trace_sync(scope);
+
+        let counter_index = if counter.is_empty() {
+            usize::MAX
+        } else {
+            scope.push(counter.name.clone(), 0 as INT);
+            scope.len() - 1
+        };
+
+        scope.push(var_name.name.clone(), ());
+        let index = scope.len() - 1;
+
+        Ok(ForLoop {
+            iter,
+            counter_index,
+            index,
+            orig_scope_len,
+            count: 0,
+        })
+    }
+    /// Set the loop variables of a `for` loop to the next item, before the loop body is
+    /// evaluated.
+    ///
+    /// Returns `false` if there are no more items.
+    pub(crate) fn next_for_iteration(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        state: &mut ForLoop,
+        x: &(Ident, Ident, Expr, StmtBlock),
+        level: usize,
+    ) -> RhaiResultOf<bool> {
+        let (.., counter, expr, statements) = x;
+
+        let (index_value, value) =
+            match state
+                .iter
+                .next_item(self, global, caches, lib, expr.position(), level)
+            {
+                Some(Ok(item)) => item,
+                Some(Err(err)) => return Err(err.fill_position(expr.position())),
+                None => return Ok(false),
+            };
+
+        let x = state.count;
+        state.count += 1;
+
+        // Increment counter
+        if state.counter_index < usize::MAX {
+            let index_value = match index_value {
+                Some(index_value) => index_value.flatten(),
+                None => {
+                    #[cfg(not(feature = "unchecked"))]
+                    let index_value = INT::try_from(x).map_err(|_| {
+                        ERR::ErrorArithmetic(format!("for-loop counter overflow: {x}"), counter.pos)
+                    })?;
+                    #[cfg(feature = "unchecked")]
+                    let index_value = x as INT;
+
+                    Dynamic::from_int(index_value)
+                }
+            };
+
+            *scope
+                .get_mut_by_index(state.counter_index)
+                .write_lock()
+                .unwrap() = index_value;
+
+            #[cfg(feature = "debugging")]
+            global.debugger.trace_set(scope, state.counter_index);
+        }
+
+        *scope.get_mut_by_index(state.index).write_lock().unwrap() = value.flatten();
+
+        #[cfg(feature = "debugging")]
+        global.debugger.trace_set(scope, state.index);
+
+        #[cfg(not(feature = "unchecked"))]
+        self.inc_operations(global, statements.position())?;
+
+        #[cfg(not(feature = "unchecked"))]
+        self.consume_fuel(
+            global,
+            self.fuel_costs().loop_iteration,
+            statements.position(),
+        )?;
+
+        #[cfg(feature = "unchecked")]
+        let _ = (counter, statements);
+
+        Ok(true)
+    }
+    /// Check that a variable can be defined by a `let`/`const` statement, via the variable
+    /// definition filter if any.
+    pub(crate) fn check_new_var(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        var_name: &Ident,
+        options: ASTFlags,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<()> {
+        // Shadowing disallowed
+        if !self.allow_shadowing() && scope.contains(var_name) {
+            return Err(ERR::ErrorVariableExists(var_name.to_string(), pos).into());
+        }
+
+        // Check variable definition filter
+        if let Some(ref filter) = self.def_var_filter {
+            let will_shadow = scope.contains(var_name);
+            let nesting_level = global.scope_level;
+            let is_const = options.contains(ASTFlags::CONSTANT);
+            let info = VarDefInfo {
+                name: var_name,
+                is_const,
+                nesting_level,
+                will_shadow,
+            };
+            let context = EvalContext::new(self, scope, global, None, lib, this_ptr, level);
+
+            if !filter(true, info, context)? {
+                return Err(ERR::ErrorForbiddenVariable(var_name.to_string(), pos).into());
+            }
+        }
+
+        Ok(())
+    }
+    /// Define a variable by a `let`/`const` statement with its initial value.
+    pub(crate) fn define_var(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        var_name: &Ident,
+        index: Option<NonZeroUsize>,
+        options: ASTFlags,
+        mut value: Dynamic,
+        rewind_scope: bool,
+    ) {
+        let access = if options.contains(ASTFlags::CONSTANT) {
+            AccessMode::ReadOnly
+        } else {
+            AccessMode::ReadWrite
+        };
+        let export = options.contains(ASTFlags::EXPORTED);
+
+        let _alias = if !rewind_scope {
+            // Put global constants into global module
+            #[cfg(not(feature = "no_function"))]
+            #[cfg(not(feature = "no_module"))]
+            if global.scope_level == 0
+                && access == AccessMode::ReadOnly
+                && lib.iter().any(|&m| !m.is_empty())
+            {
+                if global.constants.is_none() {
+                    global.constants = Some(crate::Shared::new(crate::Locked::new(
+                        std::collections::BTreeMap::new(),
+                    )));
+                }
+                crate::func::locked_write(global.constants.as_ref().unwrap())
+                    .insert(var_name.name.clone(), value.clone());
+            }
+
+            if export {
+                Some(var_name)
+            } else {
+                None
+            }
+        } else if export {
+            unreachable!("exported variable not on global level");
+        } else {
+            None
+        };
+
+        #[cfg(feature = "debugging")]
+        global.debugger.trace_sync(scope);
+
+        if let Some(index) = index {
+            value.set_access_mode(access);
+            *scope.get_mut_by_index(scope.len() - index.get()) = value;
+
+            #[cfg(feature = "debugging")]
+            global.debugger.trace_set(scope, scope.len() - index.get());
+        } else {
+            scope.push_entry(var_name.name.clone(), access, value);
+        }
+
+        #[cfg(not(feature = "no_module"))]
+        if let Some(alias) = _alias {
+            scope.add_alias_by_index(scope.len() - 1, alias.name.as_str().into());
+        }
+
+        #[cfg(any(feature = "no_function", feature = "no_module"))]
+        let _ = lib;
+        #[cfg(all(
+            not(feature = "debugging"),
+            any(feature = "no_function", feature = "no_module")
+        ))]
+        let _ = global;
+    }
+    /// Evaluate a statement, recording it into the coverage and the profile of the evaluation run,
+    /// if tracking coverage or profiling.
+    #[inline]
     pub(crate) fn eval_stmt(
         &self,
         scope: &mut Scope,
@@ -203,18 +788,81 @@ impl Engine {
         stmt: &Stmt,
         rewind_scope: bool,
         level: usize,
+    ) -> RhaiResult {
+        #[cfg(feature = "coverage")]
+        if let Some(ref mut coverage) = global.coverage {
+            coverage.record_stmt(&global.source, stmt.position());
+        }
+
+        #[cfg(feature = "profiling")]
+        if global.profiler.is_some() {
+            let start = crate::Instant::now();
+            let operations = global.num_operations;
+
+            let result = self.eval_stmt_raw(
+                scope,
+                global,
+                caches,
+                lib,
+                this_ptr,
+                stmt,
+                rewind_scope,
+                level,
+            );
+
+            if let Some(ref mut profiler) = global.profiler {
+                profiler.record_stmt(
+                    &global.source,
+                    stmt.position(),
+                    start.elapsed(),
+                    global.num_operations - operations,
+                );
+            }
+
+            return result;
+        }
+
+        self.eval_stmt_raw(
+            scope,
+            global,
+            caches,
+            lib,
+            this_ptr,
+            stmt,
+            rewind_scope,
+            level,
+        )
+    }
+    /// Evaluate a statement.
+    fn eval_stmt_raw(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        stmt: &Stmt,
+        rewind_scope: bool,
+        level: usize,
     ) -> RhaiResult {
         #[cfg(feature = "debugging")]
         let reset_debugger =
             self.run_debugger_with_reset(scope, global, lib, this_ptr, stmt, level)?;
 
+        global.peak_scope_size = global.peak_scope_size.max(scope.len());
+
+        // Give pending tasks a chance to run
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "unchecked"))]
+        self.run_due_task(global, caches, lib, stmt.position(), level)?;
+
         // Coded this way for better branch prediction.
         // Popular branches are lifted out of the `match` statement into their own branches.
 
         // Function calls should account for a relatively larger portion of statements.
         if let Stmt::FnCall(x, ..) = stmt {
             #[cfg(not(feature = "unchecked"))]
-            self.inc_operations(&mut global.num_operations, stmt.position())?;
+            self.inc_operations(global, stmt.position())?;
 
             let result =
                 self.eval_fn_call_expr(scope, global, caches, lib, this_ptr, x, x.pos, level);
@@ -232,7 +880,7 @@ impl Engine {
             let (op_info, BinaryExpr { lhs, rhs }) = &**x;
 
             #[cfg(not(feature = "unchecked"))]
-            self.inc_operations(&mut global.num_operations, stmt.position())?;
+            self.inc_operations(global, stmt.position())?;
 
             let result = if let Expr::Variable(x, ..) = lhs {
                 let rhs_result = self
@@ -240,6 +888,15 @@ impl Engine {
                     .map(Dynamic::flatten);
 
                 if let Ok(rhs_val) = rhs_result {
+                    // Variables in the frozen base layer of the scope are never written to
+                    if lhs.is_variable_access(true) && scope.is_frozen(x.3.as_str()) {
+                        return Err(ERR::ErrorAssignmentToConstant(
+                            x.3.to_string(),
+                            lhs.position(),
+                        )
+                        .into());
+                    }
+
                     let search_result =
                         self.search_namespace(scope, global, lib, this_ptr, lhs, level);
 
@@ -255,15 +912,19 @@ impl Engine {
                         #[cfg(feature = "no_closure")]
                         let is_temp_result = !lhs_ptr.is_ref();
 
-                        // Cannot assign to temp result from expression
+                        // Cannot assign to temp result from expression, even if permitted
                         if is_temp_result {
-                            return Err(
-                                ERR::ErrorAssignmentToConstant(var_name.to_string(), pos).into()
-                            );
+                            return match self.allow_invalid_assignment(var_name, &rhs_val, pos) {
+                                Err(err) => Err(err),
+                                Ok(..) => {
+                                    Err(ERR::ErrorAssignmentToConstant(var_name.to_string(), pos)
+                                        .into())
+                                }
+                            };
                         }
 
                         #[cfg(not(feature = "unchecked"))]
-                        self.inc_operations(&mut global.num_operations, pos)?;
+                        self.inc_operations(global, pos)?;
 
                         let root = (var_name, pos);
                         let lhs_ptr = &mut lhs_ptr;
@@ -287,7 +948,8 @@ impl Engine {
 
                 if let Ok(rhs_val) = rhs_result {
                     let rhs_val = if rhs_val.is::<ImmutableString>() {
-                        self.get_interned_string(rhs_val.cast::<ImmutableString>())
+                        caches
+                            .get_interned_string(self, rhs_val.cast::<ImmutableString>())
                             .into()
                     } else {
                         rhs_val
@@ -295,7 +957,7 @@ impl Engine {
 
                     let _new_val = Some((rhs_val, *op_info));
 
-                    // Must be either `var[index] op= val` or `var.prop op= val`
+                    // Must be either `var[index] op= val`, `var.prop op= val` or `(var, ...) = val`
                     match lhs {
                         // name op= rhs (handled above)
                         Expr::Variable(..) => {
@@ -315,6 +977,20 @@ impl Engine {
                                 scope, global, caches, lib, this_ptr, lhs, level, _new_val,
                             )
                             .map(|_| Dynamic::UNIT),
+                        // (xxx, ...) = rhs
+                        Expr::Tuple(x, ..) => self
+                            .eval_tuple_assignment(
+                                scope,
+                                global,
+                                caches,
+                                lib,
+                                this_ptr,
+                                x,
+                                rhs.position(),
+                                level,
+                                _new_val,
+                            )
+                            .map(|_| Dynamic::UNIT),
                         _ => unreachable!("cannot assign to expression: {:?}", lhs),
                     }
                 } else {
@@ -322,6 +998,11 @@ impl Engine {
                 }
             };
 
+            #[cfg(feature = "debugging")]
+            if result.is_ok() && matches!(lhs, Expr::Variable(..)) {
+                global.debugger.trace_var(scope, lhs);
+            }
+
             #[cfg(feature = "debugging")]
             global.debugger.reset_status(reset_debugger);
 
@@ -329,7 +1010,7 @@ impl Engine {
         }
 
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, stmt.position())?;
+        self.inc_operations(global, stmt.position())?;
 
         let result = match stmt {
             // No-op
@@ -350,13 +1031,16 @@ impl Engine {
             Stmt::If(x, ..) => {
                 let (expr, if_block, else_block) = &**x;
 
-                let guard_val = self
-                    .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
-                    .and_then(|v| {
-                        v.as_bool().map_err(|typ| {
-                            self.make_type_mismatch_err::<bool>(typ, expr.position())
-                        })
-                    });
+                let guard_val = self.eval_if_condition(
+                    scope,
+                    global,
+                    caches,
+                    lib,
+                    this_ptr,
+                    expr,
+                    stmt.position(),
+                    level,
+                );
 
                 match guard_val {
                     Ok(true) => {
@@ -390,9 +1074,13 @@ impl Engine {
                         cases,
                         def_case,
                         ranges,
+                        patterns,
+                        ..
                     },
                 ) = &**x;
 
+                let orig_scope_len = scope.len();
+
                 let value_result =
                     self.eval_expr(scope, global, caches, lib, this_ptr, expr, level);
 
@@ -439,12 +1127,11 @@ impl Engine {
                             }
 
                             result
-                        } else if value.is::<INT>() && !ranges.is_empty() {
-                            // Then check integer ranges
-                            let value = value.as_int().expect("`INT`");
+                        } else if !ranges.is_empty() {
+                            // Then check numeric ranges
                             let mut result = Ok(None);
 
-                            for r in ranges.iter().filter(|r| r.contains(value)) {
+                            for r in ranges.iter().filter(|r| r.contains_value(&value)) {
                                 let block = &expressions[r.index()];
 
                                 let cond_result = match block.condition {
@@ -480,7 +1167,65 @@ impl Engine {
                         Ok(None)
                     };
 
-                    if let Ok(Some(expr)) = expr_result {
+                    let expr_result = match expr_result {
+                        Ok(None) if !patterns.is_empty() => {
+                            // Then check patterns
+                            let mut result = Ok(None);
+
+                            for p in patterns {
+                                if !self.match_switch_pattern(scope, &p.pattern, &value) {
+                                    continue;
+                                }
+
+                                let block = &expressions[p.index];
+
+                                let cond_result = match block.condition {
+                                    Expr::BoolConstant(b, ..) => Ok(b),
+                                    ref c => self
+                                        .eval_expr(scope, global, caches, lib, this_ptr, c, level)
+                                        .and_then(|v| {
+                                            v.as_bool().map_err(|typ| {
+                                                self.make_type_mismatch_err::<bool>(
+                                                    typ,
+                                                    c.position(),
+                                                )
+                                            })
+                                        }),
+                                };
+
+                                match cond_result {
+                                    Ok(true) => result = Ok(Some(&block.expr)),
+                                    Ok(false) => {
+                                        // Remove the variables bound by the pattern
+                                        scope.rewind(orig_scope_len);
+                                        continue;
+                                    }
+                                    _ => result = cond_result.map(|_| None),
+                                }
+
+                                break;
+                            }
+
+                            result
+                        }
+                        result => result,
+                    };
+
+                    #[cfg(feature = "coverage")]
+                    if let Some(ref mut coverage) = global.coverage {
+                        let branch = match expr_result {
+                            Ok(Some(expr)) => expressions
+                                .iter()
+                                .position(|block| std::ptr::eq(&block.expr, expr)),
+                            Ok(None) => Some(def_case.unwrap_or(expressions.len())),
+                            Err(..) => None,
+                        };
+                        if let Some(branch) = branch {
+                            coverage.record_branch(&global.source, stmt.position(), branch);
+                        }
+                    }
+
+                    let result = if let Ok(Some(expr)) = expr_result {
                         self.eval_expr(scope, global, caches, lib, this_ptr, expr, level)
                     } else if let Ok(None) = expr_result {
                         // Default match clause
@@ -490,7 +1235,12 @@ impl Engine {
                         })
                     } else {
                         expr_result.map(|_| Dynamic::UNIT)
-                    }
+                    };
+
+                    // Remove the variables bound by a matched pattern
+                    scope.rewind(orig_scope_len);
+
+                    result
                 } else {
                     value_result
                 }
@@ -500,10 +1250,9 @@ impl Engine {
             Stmt::While(x, ..) if matches!(x.0, Expr::Unit(..)) => loop {
                 let (.., body) = &**x;
 
-                if body.is_empty() {
-                    #[cfg(not(feature = "unchecked"))]
-                    self.inc_operations(&mut global.num_operations, body.position())?;
-                } else {
+                self.start_loop_iteration(global, caches, lib, body, false, level)?;
+
+                if !body.is_empty() {
                     match self
                         .eval_stmt_block(scope, global, caches, lib, this_ptr, body, true, level)
                     {
@@ -521,13 +1270,12 @@ impl Engine {
             Stmt::While(x, ..) => loop {
                 let (expr, body) = &**x;
 
-                let condition = self
-                    .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
-                    .and_then(|v| {
-                        v.as_bool().map_err(|typ| {
-                            self.make_type_mismatch_err::<bool>(typ, expr.position())
-                        })
-                    });
+                let condition =
+                    self.eval_condition(scope, global, caches, lib, this_ptr, expr, level);
+
+                if let Ok(true) = condition {
+                    self.start_loop_iteration(global, caches, lib, body, true, level)?;
+                }
 
                 match condition {
                     Ok(false) => break Ok(Dynamic::UNIT),
@@ -553,6 +1301,8 @@ impl Engine {
                 let (expr, body) = &**x;
                 let is_while = !options.contains(ASTFlags::NEGATED);
 
+                self.start_loop_iteration(global, caches, lib, body, true, level)?;
+
                 if !body.is_empty() {
                     match self
                         .eval_stmt_block(scope, global, caches, lib, this_ptr, body, true, level)
@@ -566,13 +1316,8 @@ impl Engine {
                     }
                 }
 
-                let condition = self
-                    .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
-                    .and_then(|v| {
-                        v.as_bool().map_err(|typ| {
-                            self.make_type_mismatch_err::<bool>(typ, expr.position())
-                        })
-                    });
+                let condition =
+                    self.eval_condition(scope, global, caches, lib, this_ptr, expr, level);
 
                 match condition {
                     Ok(condition) if condition ^ is_while => break Ok(Dynamic::UNIT),
@@ -583,86 +1328,17 @@ impl Engine {
 
             // For loop
             Stmt::For(x, ..) => {
-                let (var_name, counter, expr, statements) = &**x;
-
-                let iter_result = self
-                    .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
-                    .map(Dynamic::flatten);
-
-                if let Ok(iter_obj) = iter_result {
-                    let iter_type = iter_obj.type_id();
-
-                    // lib should only contain scripts, so technically they cannot have iterators
-
-                    // Search order:
-                    // 1) Global namespace - functions registered via Engine::register_XXX
-                    // 2) Global modules - packages
-                    // 3) Imported modules - functions marked with global namespace
-                    // 4) Global sub-modules - functions marked with global namespace
-                    let func = self
-                        .global_modules
-                        .iter()
-                        .find_map(|m| m.get_iter(iter_type));
-
-                    #[cfg(not(feature = "no_module"))]
-                    let func = func.or_else(|| global.get_iter(iter_type)).or_else(|| {
-                        self.global_sub_modules
-                            .values()
-                            .find_map(|m| m.get_qualified_iter(iter_type))
-                    });
-
-                    if let Some(func) = func {
-                        // Add the loop variables
-                        let orig_scope_len = scope.len();
-                        let counter_index = if counter.is_empty() {
-                            usize::MAX
-                        } else {
-                            scope.push(counter.name.clone(), 0 as INT);
-                            scope.len() - 1
-                        };
-
-                        scope.push(var_name.name.clone(), ());
-                        let index = scope.len() - 1;
-
-                        let mut loop_result = Ok(Dynamic::UNIT);
-
-                        for (x, iter_value) in func(iter_obj).enumerate() {
-                            // Increment counter
-                            if counter_index < usize::MAX {
-                                // As the variable increments from 0, this should always work
-                                // since any overflow will first be caught below.
-                                let index_value = x as INT;
-
-                                #[cfg(not(feature = "unchecked"))]
-                                if index_value > crate::MAX_USIZE_INT {
-                                    loop_result = Err(ERR::ErrorArithmetic(
-                                        format!("for-loop counter overflow: {x}"),
-                                        counter.pos,
-                                    )
-                                    .into());
-                                    break;
-                                }
-
-                                *scope.get_mut_by_index(counter_index).write_lock().unwrap() =
-                                    Dynamic::from_int(index_value);
-                            }
-
-                            let value = match iter_value {
-                                Ok(v) => v.flatten(),
-                                Err(err) => {
-                                    loop_result = Err(err.fill_position(expr.position()));
-                                    break;
-                                }
-                            };
-
-                            *scope.get_mut_by_index(index).write_lock().unwrap() = value;
-
-                            #[cfg(not(feature = "unchecked"))]
-                            if let Err(err) = self
-                                .inc_operations(&mut global.num_operations, statements.position())
-                            {
-                                loop_result = Err(err);
-                                break;
+                let statements = &x.3;
+
+                match self.start_for_loop(scope, global, caches, lib, this_ptr, x, level) {
+                    Ok(mut state) => {
+                        let loop_result = loop {
+                            match self.next_for_iteration(
+                                scope, global, caches, lib, &mut state, x, level,
+                            ) {
+                                Ok(true) => (),
+                                Ok(false) => break Ok(Dynamic::UNIT),
+                                Err(err) => break Err(err),
                             }
 
                             if statements.is_empty() {
@@ -677,23 +1353,17 @@ impl Engine {
                                 Ok(_) => (),
                                 Err(err) => match *err {
                                     ERR::LoopBreak(false, ..) => (),
-                                    ERR::LoopBreak(true, ..) => break,
-                                    _ => {
-                                        loop_result = Err(err);
-                                        break;
-                                    }
+                                    ERR::LoopBreak(true, ..) => break Ok(Dynamic::UNIT),
+                                    _ => break Err(err),
                                 },
                             }
-                        }
+                        };
 
-                        scope.rewind(orig_scope_len);
+                        scope.rewind(state.orig_scope_len);
 
                         loop_result
-                    } else {
-                        Err(ERR::ErrorFor(expr.start_position()).into())
                     }
-                } else {
-                    iter_result
+                    Err(err) => Err(err),
                 }
             }
 
@@ -713,10 +1383,19 @@ impl Engine {
                     catch_block,
                 } = &**x;
 
+                // Calls in the try block are not in tail position
+                #[cfg(not(feature = "no_function"))]
+                let orig_tail_call = global.tail_call.take();
+
                 let result = self
                     .eval_stmt_block(scope, global, caches, lib, this_ptr, try_block, true, level)
                     .map(|_| Dynamic::UNIT);
 
+                #[cfg(not(feature = "no_function"))]
+                {
+                    global.tail_call = orig_tail_call;
+                }
+
                 match result {
                     Ok(_) => result,
                     Err(err) if err.is_pseudo_error() => Err(err),
@@ -759,6 +1438,9 @@ impl Engine {
 
                         let orig_scope_len = scope.len();
 
+                        #[cfg(feature = "debugging")]
+                        global.debugger.trace_sync(scope);
+
                         if !catch_var.is_empty() {
                             scope.push(catch_var.clone(), err_value);
                         }
@@ -802,109 +1484,54 @@ impl Engine {
             }
 
             // Return value
-            Stmt::Return(Some(expr), .., pos) => self
-                .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
-                .and_then(|v| Err(ERR::Return(v.flatten(), *pos).into())),
+            Stmt::Return(Some(expr), .., pos) => match **expr {
+                // Return value of a call of a script-defined function to itself
+                #[cfg(not(feature = "no_function"))]
+                Expr::FnCall(ref x, ..)
+                    if global
+                        .tail_call
+                        .as_ref()
+                        .map_or(false, |t| t.is_tail_return(x, level)) =>
+                {
+                    self.eval_tail_call(scope, global, caches, lib, this_ptr, x, *pos, level)
+                }
+                _ => self
+                    .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
+                    .and_then(|v| Err(ERR::Return(v.flatten(), *pos).into())),
+            },
 
             // Empty return
             Stmt::Return(None, .., pos) => Err(ERR::Return(Dynamic::UNIT, *pos).into()),
 
-            // Let/const statement - shadowing disallowed
-            Stmt::Var(x, .., pos) if !self.allow_shadowing() && scope.contains(&x.0) => {
-                Err(ERR::ErrorVariableExists(x.0.to_string(), *pos).into())
+            // Yield outside a generator
+            #[cfg(not(feature = "no_function"))]
+            Stmt::Yield(.., pos) => {
+                Err(ERR::ErrorParsing(crate::ParseErrorType::WrongYield, *pos).into())
             }
-            // Let/const statement
-            Stmt::Var(x, options, pos) => {
-                let (var_name, expr, index) = &**x;
-
-                let access = if options.contains(ASTFlags::CONSTANT) {
-                    AccessMode::ReadOnly
-                } else {
-                    AccessMode::ReadWrite
-                };
-                let export = options.contains(ASTFlags::EXPORTED);
-
-                // Check variable definition filter
-                let result = if let Some(ref filter) = self.def_var_filter {
-                    let will_shadow = scope.contains(var_name);
-                    let nesting_level = global.scope_level;
-                    let is_const = access == AccessMode::ReadOnly;
-                    let info = VarDefInfo {
-                        name: var_name,
-                        is_const,
-                        nesting_level,
-                        will_shadow,
-                    };
-                    let context = EvalContext::new(self, scope, global, None, lib, this_ptr, level);
-
-                    match filter(true, info, context) {
-                        Ok(true) => None,
-                        Ok(false) => {
-                            Some(Err(
-                                ERR::ErrorForbiddenVariable(var_name.to_string(), *pos).into()
-                            ))
-                        }
-                        err @ Err(_) => Some(err),
-                    }
-                } else {
-                    None
-                };
-
-                if let Some(result) = result {
-                    result.map(|_| Dynamic::UNIT)
-                } else {
-                    // Evaluate initial value
-                    let value_result = self
-                        .eval_expr(scope, global, caches, lib, this_ptr, expr, level)
-                        .map(Dynamic::flatten);
-
-                    if let Ok(mut value) = value_result {
-                        let _alias = if !rewind_scope {
-                            // Put global constants into global module
-                            #[cfg(not(feature = "no_function"))]
-                            #[cfg(not(feature = "no_module"))]
-                            if global.scope_level == 0
-                                && access == AccessMode::ReadOnly
-                                && lib.iter().any(|&m| !m.is_empty())
-                            {
-                                if global.constants.is_none() {
-                                    global.constants = Some(crate::Shared::new(
-                                        crate::Locked::new(std::collections::BTreeMap::new()),
-                                    ));
-                                }
-                                crate::func::locked_write(global.constants.as_ref().unwrap())
-                                    .insert(var_name.name.clone(), value.clone());
-                            }
-
-                            if export {
-                                Some(var_name)
-                            } else {
-                                None
-                            }
-                        } else if export {
-                            unreachable!("exported variable not on global level");
-                        } else {
-                            None
-                        };
-
-                        if let Some(index) = index {
-                            value.set_access_mode(access);
-                            *scope.get_mut_by_index(scope.len() - index.get()) = value;
-                        } else {
-                            scope.push_entry(var_name.name.clone(), access, value);
-                        }
 
-                        #[cfg(not(feature = "no_module"))]
-                        if let Some(alias) = _alias {
-                            scope.add_alias_by_index(scope.len() - 1, alias.name.as_str().into());
-                        }
-
-                        Ok(Dynamic::UNIT)
-                    } else {
-                        value_result
-                    }
-                }
-            }
+            // Let/const statement
+            Stmt::Var(x, options, pos) => self
+                .check_new_var(scope, global, lib, this_ptr, &x.0, *options, *pos, level)
+                .and_then(|_| self.eval_expr(scope, global, caches, lib, this_ptr, &x.1, level))
+                .map(|value| {
+                    let value = value.flatten();
+                    self.define_var(scope, global, lib, &x.0, x.2, *options, value, rewind_scope);
+                    Dynamic::UNIT
+                }),
+
+            // Let/const tuple destructuring statement
+            Stmt::VarTuple(x, options, pos) => self.eval_var_tuple(
+                scope,
+                global,
+                caches,
+                lib,
+                this_ptr,
+                x,
+                *options,
+                *pos,
+                rewind_scope,
+                level,
+            ),
 
             // Import statement
             #[cfg(not(feature = "no_module"))]
@@ -914,7 +1541,7 @@ impl Engine {
                 // Guard against too many modules
                 #[cfg(not(feature = "unchecked"))]
                 if global.num_modules_loaded >= self.max_modules() {
-                    return Err(ERR::ErrorTooManyModules(*_pos).into());
+                    return Err(ERR::ErrorTooManyModules(Default::default(), *_pos).into());
                 }
 
                 let path_result = self
@@ -934,6 +1561,15 @@ impl Engine {
 
                     let path_pos = expr.start_position();
 
+                    #[cfg(feature = "tracing")]
+                    let _span = tracing::info_span!(
+                        target: "rhai",
+                        "import",
+                        path = %path,
+                        source = %global.source
+                    )
+                    .entered();
+
                     let resolver = global.embedded_module_resolver.clone();
 
                     let module_result = resolver
@@ -1000,6 +1636,9 @@ impl Engine {
                         *val = std::mem::take(val).into_shared();
                     }
                     Ok(Dynamic::UNIT)
+                } else if scope.get_frozen(name).is_some() {
+                    // Variables in the frozen base layer of the scope never change
+                    Ok(Dynamic::UNIT)
                 } else {
                     Err(ERR::ErrorVariableNotFound(name.to_string(), *pos).into())
                 }
@@ -1008,9 +1647,213 @@ impl Engine {
             _ => unreachable!("statement cannot be evaluated: {:?}", stmt),
         };
 
+        #[cfg(not(feature = "unchecked"))]
+        let result = match stmt {
+            Stmt::While(..) | Stmt::Do(..) | Stmt::For(..) => result.map_err(|mut err| {
+                Self::add_limit_context(&mut err, scope, "", stmt.position());
+                err
+            }),
+            _ => result,
+        };
+
         #[cfg(feature = "debugging")]
         global.debugger.reset_status(reset_debugger);
 
         result
     }
+
+    /// Check that a value is a [tuple][crate::Tuple] with a certain number of items.
+    fn destructure_tuple(
+        &self,
+        value: Dynamic,
+        len: usize,
+        pos: Position,
+    ) -> RhaiResultOf<crate::Tuple> {
+        let describe = |n: usize| format!("tuple of {} items", n);
+
+        if !value.is::<crate::Tuple>() {
+            let actual = self.map_type_name(value.type_name()).into();
+            return Err(ERR::ErrorMismatchDataType(describe(len), actual, pos).into());
+        }
+
+        let tuple = value.cast::<crate::Tuple>();
+
+        if tuple.len() != len {
+            let actual = describe(tuple.len());
+            return Err(ERR::ErrorMismatchDataType(describe(len), actual, pos).into());
+        }
+
+        Ok(tuple)
+    }
+
+    /// Evaluate a `let`/`const` statement destructuring a tuple.
+    fn eval_var_tuple(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        x: &(crate::StaticVec<Option<Ident>>, Expr),
+        options: ASTFlags,
+        pos: Position,
+        rewind_scope: bool,
+        level: usize,
+    ) -> RhaiResult {
+        let (vars, expr) = x;
+
+        let access = if options.contains(ASTFlags::CONSTANT) {
+            AccessMode::ReadOnly
+        } else {
+            AccessMode::ReadWrite
+        };
+        let export = options.contains(ASTFlags::EXPORTED);
+
+        for var in vars.iter().flatten() {
+            self.check_new_var(scope, global, lib, this_ptr, var, options, pos, level)?;
+        }
+
+        let value = self
+            .eval_expr(scope, global, caches, lib, this_ptr, expr, level)?
+            .flatten();
+        let tuple = self.destructure_tuple(value, vars.len(), expr.position())?;
+
+        #[cfg(feature = "debugging")]
+        global.debugger.trace_sync(scope);
+
+        for (var, mut value) in vars.iter().zip(tuple) {
+            let var = match var {
+                Some(var) => var,
+                None => continue,
+            };
+
+            if !rewind_scope {
+                // Put global constants into global module
+                #[cfg(not(feature = "no_function"))]
+                #[cfg(not(feature = "no_module"))]
+                if global.scope_level == 0
+                    && access == AccessMode::ReadOnly
+                    && lib.iter().any(|&m| !m.is_empty())
+                {
+                    if global.constants.is_none() {
+                        global.constants = Some(crate::Shared::new(crate::Locked::new(
+                            std::collections::BTreeMap::new(),
+                        )));
+                    }
+                    crate::func::locked_write(global.constants.as_ref().unwrap())
+                        .insert(var.name.clone(), value.clone());
+                }
+            } else if export {
+                unreachable!("exported variable not on global level");
+            }
+
+            value.set_access_mode(access);
+            scope.push_entry(var.name.clone(), access, value);
+
+            #[cfg(not(feature = "no_module"))]
+            if export {
+                scope.add_alias_by_index(scope.len() - 1, var.name.as_str().into());
+            }
+        }
+
+        Ok(Dynamic::UNIT)
+    }
+
+    /// Evaluate an assignment to a tuple of targets, i.e. `(xxx, ...) = rhs`.
+    fn eval_tuple_assignment(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        targets: &[Expr],
+        rhs_pos: Position,
+        level: usize,
+        new_val: Option<(Dynamic, OpAssignment)>,
+    ) -> RhaiResultOf<()> {
+        let (rhs_val, op_info) = new_val.expect("`Some`");
+        let tuple = self.destructure_tuple(rhs_val, targets.len(), rhs_pos)?;
+
+        for (target, value) in targets.iter().zip(tuple) {
+            match target {
+                // var = value
+                Expr::Variable(x, ..) => {
+                    // Variables in the frozen base layer of the scope are never written to
+                    if target.is_variable_access(true) && scope.is_frozen(x.3.as_str()) {
+                        return Err(ERR::ErrorAssignmentToConstant(
+                            x.3.to_string(),
+                            target.position(),
+                        )
+                        .into());
+                    }
+
+                    let (mut lhs_ptr, pos) =
+                        self.search_namespace(scope, global, lib, this_ptr, target, level)?;
+                    let var_name = x.3.as_str();
+
+                    #[cfg(not(feature = "no_closure"))]
+                    let is_temp_result = !lhs_ptr.is_ref() && !lhs_ptr.is_shared();
+                    #[cfg(feature = "no_closure")]
+                    let is_temp_result = !lhs_ptr.is_ref();
+
+                    // Cannot assign to temp result from expression, even if permitted
+                    if is_temp_result {
+                        self.allow_invalid_assignment(var_name, &value, pos)?;
+                        return Err(
+                            ERR::ErrorAssignmentToConstant(var_name.to_string(), pos).into()
+                        );
+                    }
+
+                    let root = (var_name, pos);
+                    self.eval_op_assignment(
+                        global,
+                        caches,
+                        lib,
+                        op_info,
+                        &mut lhs_ptr,
+                        root,
+                        value,
+                        level,
+                    )?;
+
+                    #[cfg(feature = "debugging")]
+                    {
+                        drop(lhs_ptr);
+                        global.debugger.trace_var(scope, target);
+                    }
+                }
+                // var[index] = value, var.prop = value
+                #[cfg(not(feature = "no_index"))]
+                Expr::Index(..) => {
+                    self.eval_dot_index_chain(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        target,
+                        level,
+                        Some((value, op_info)),
+                    )?;
+                }
+                #[cfg(not(feature = "no_object"))]
+                Expr::Dot(..) => {
+                    self.eval_dot_index_chain(
+                        scope,
+                        global,
+                        caches,
+                        lib,
+                        this_ptr,
+                        target,
+                        level,
+                        Some((value, op_info)),
+                    )?;
+                }
+                _ => unreachable!("cannot assign to expression: {:?}", target),
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,449 @@
+//! Cooperative tasks scheduled within a single evaluation.
+#![cfg(not(feature = "no_function"))]
+
+use super::{Caches, GlobalRuntimeState, Step, SuspendedFn};
+use crate::func::{locked_read, locked_write};
+use crate::{
+    calc_fn_hash, Engine, FnPtr, Locked, Module, Position, RhaiResult, RhaiResultOf, Shared,
+    StaticVec,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Number of operations in a time slice of the scheduler.
+///
+/// The scheduler lets a task run for a time slice after every time slice of the rest of the
+/// evaluation.
+pub const TASK_TIME_SLICE: u64 = 64;
+
+/// State of a [`Task`].
+#[derive(Debug)]
+enum TaskState {
+    /// Not yet started.
+    Pending(FnPtr),
+    /// Started, and suspended at the end of a time slice.
+    ///
+    /// The state of the suspended function is kept by the [`TaskQueue`].
+    Suspended,
+    /// Currently running.
+    Running,
+    /// Finished with a result.
+    Done(RhaiResult),
+}
+
+/// A cooperative task spawned by a script.
+///
+/// Not available under `no_function`.
+///
+/// A task is a function pointer that is run by the [`Engine`] in time slices, in between
+/// statements of the evaluation that spawned it (see the
+/// [`TasksPackage`][crate::packages::TasksPackage]). Tasks are not threads: they take turns
+/// running on the thread of the evaluation.
+#[derive(Clone)]
+pub struct Task(Shared<Locked<TaskState>>);
+
+impl fmt::Debug for Task {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *locked_read(&self.0) {
+            TaskState::Pending(..) => "Task(pending)",
+            TaskState::Suspended => "Task(suspended)",
+            TaskState::Running => "Task(running)",
+            TaskState::Done(..) => "Task(done)",
+        })
+    }
+}
+
+impl Task {
+    /// Create a new pending [`Task`] that calls a function pointer.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn new(fn_ptr: FnPtr) -> Self {
+        Self(Locked::new(TaskState::Pending(fn_ptr)).into())
+    }
+    /// Has this task finished?
+    #[inline]
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        matches!(*locked_read(&self.0), TaskState::Done(..))
+    }
+    /// Is this task currently running?
+    ///
+    /// A task that has started but is waiting for its next time slice is not running.
+    #[inline]
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        matches!(*locked_read(&self.0), TaskState::Running)
+    }
+    /// Is this the same task as another?
+    #[inline(always)]
+    #[must_use]
+    fn is(&self, other: &Self) -> bool {
+        Shared::ptr_eq(&self.0, &other.0)
+    }
+    /// Mark this task as running, if it has not been started or is suspended.
+    ///
+    /// Returns the function pointer of a task that has not been started, or [`None`] for a
+    /// suspended task.
+    ///
+    /// Returns [`Err`] if the task is running or has finished.
+    #[inline]
+    fn take(&self) -> Result<Option<FnPtr>, ()> {
+        let mut guard = locked_write(&self.0);
+        let state = &mut *guard;
+
+        match state {
+            TaskState::Pending(..) => match std::mem::replace(state, TaskState::Running) {
+                TaskState::Pending(fn_ptr) => Ok(Some(fn_ptr)),
+                _ => unreachable!("task is pending"),
+            },
+            TaskState::Suspended => {
+                *state = TaskState::Running;
+                Ok(None)
+            }
+            _ => Err(()),
+        }
+    }
+    /// Suspend this task at the end of a time slice.
+    #[inline(always)]
+    fn suspend(&self) {
+        *locked_write(&self.0) = TaskState::Suspended;
+    }
+    /// Record the result of this task.
+    #[inline(always)]
+    pub(crate) fn finish(&self, result: RhaiResult) {
+        *locked_write(&self.0) = TaskState::Done(result);
+    }
+    /// Get the result of this task, if it has finished.
+    ///
+    /// Errors cannot be cloned, so the original error is only returned once.
+    /// Later calls return a copy of it.
+    #[inline]
+    #[must_use]
+    pub(crate) fn result(&self) -> Option<RhaiResult> {
+        match *locked_write(&self.0) {
+            TaskState::Done(Ok(ref value)) => Some(Ok(value.clone())),
+            TaskState::Done(Err(ref mut err)) => {
+                let copy = err.copy().into();
+                Some(Err(std::mem::replace(err, copy)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How to run a [`Task`] taken from a [`TaskQueue`].
+pub(crate) enum TaskRun {
+    /// Start calling a function pointer.
+    Start(FnPtr),
+    /// Resume a suspended function.
+    Resume(Box<SuspendedFn>),
+}
+
+/// List of tasks waiting to be run.
+#[derive(Default)]
+struct TaskList {
+    /// Tasks that have not finished, in the order they are run, with the states of those that
+    /// are suspended.
+    pending: VecDeque<(Task, Option<Box<SuspendedFn>>)>,
+    /// Is a task currently being run by the scheduler?
+    running: bool,
+    /// Number of operations performed when the scheduler last ran a task.
+    last_run: u64,
+}
+
+impl fmt::Debug for TaskList {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskList")
+            .field("pending", &self.pending.len())
+            .field("running", &self.running)
+            .field("last_run", &self.last_run)
+            .finish()
+    }
+}
+
+/// Queue of cooperative [tasks][Task] spawned during an evaluation.
+///
+/// The queue is only allocated when needed. Clones of a queue share the same list of tasks.
+#[derive(Debug, Default)]
+pub struct TaskQueue {
+    /// Shared list of tasks.
+    list: Locked<Option<Shared<Locked<TaskList>>>>,
+    /// Has the list been allocated?
+    ///
+    /// Checked before every statement, so that evaluations which never spawn a task do not
+    /// need to borrow the list.
+    active: AtomicBool,
+}
+
+impl Clone for TaskQueue {
+    #[inline]
+    fn clone(&self) -> Self {
+        let list = self.list();
+        Self {
+            list: Locked::new(Some(list)),
+            active: AtomicBool::new(true),
+        }
+    }
+}
+
+impl TaskQueue {
+    /// Get the shared list of tasks, allocating it if necessary.
+    #[inline]
+    #[must_use]
+    fn list(&self) -> Shared<Locked<TaskList>> {
+        self.active.store(true, Ordering::Relaxed);
+        locked_write(&self.list)
+            .get_or_insert_with(Default::default)
+            .clone()
+    }
+    /// Add a [`Task`] to the end of the queue.
+    #[inline]
+    pub(crate) fn push(&self, task: Task) {
+        locked_write(&self.list()).pending.push_back((task, None));
+    }
+    /// Is it time for the scheduler to run a pending task?
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_due(&self, num_operations: u64) -> bool {
+        if !self.active.load(Ordering::Relaxed) {
+            return false;
+        }
+
+        locked_read(&self.list).as_ref().map_or(false, |list| {
+            let list = locked_read(list);
+            !list.running
+                && !list.pending.is_empty()
+                && num_operations.abs_diff(list.last_run) >= TASK_TIME_SLICE
+        })
+    }
+    /// Take the next task that has not finished, marking it as running.
+    ///
+    /// Returns [`None`] if there is no such task or if the scheduler is already running a task.
+    #[must_use]
+    fn start_next(&self) -> Option<(Shared<Locked<TaskList>>, Task, TaskRun)> {
+        if !self.active.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let list = locked_read(&self.list).as_ref()?.clone();
+        let mut tasks = locked_write(&list);
+
+        if tasks.running {
+            return None;
+        }
+
+        // Tasks already finished (e.g. by being joined) are dropped
+        while let Some((task, state)) = tasks.pending.pop_front() {
+            let run = match (task.take(), state) {
+                (Ok(Some(fn_ptr)), ..) => TaskRun::Start(fn_ptr),
+                (Ok(None), Some(state)) => TaskRun::Resume(state),
+                _ => continue,
+            };
+            tasks.running = true;
+            drop(tasks);
+            return Some((list, task, run));
+        }
+
+        None
+    }
+    /// Take a [`Task`] out of the queue, marking it as running.
+    ///
+    /// Returns [`None`] if the task is running or has finished.
+    #[must_use]
+    pub(crate) fn take(&self, task: &Task) -> Option<TaskRun> {
+        match task.take() {
+            Ok(Some(fn_ptr)) => Some(TaskRun::Start(fn_ptr)),
+            Ok(None) => {
+                let list = self.list();
+                let mut list = locked_write(&list);
+                let index = list.pending.iter().position(|(t, ..)| t.is(task))?;
+                let (.., state) = list.pending.remove(index).expect("task in queue");
+                Some(TaskRun::Resume(state.expect("task is suspended")))
+            }
+            Err(()) => None,
+        }
+    }
+    /// Put a suspended [`Task`] back at the end of the queue.
+    fn suspend(list: &mut TaskList, task: Task, state: Box<SuspendedFn>) {
+        task.suspend();
+        list.pending.push_back((task, Some(state)));
+    }
+    /// Mark the scheduler as running a task or not, returning the previous setting.
+    ///
+    /// No other task is started while the scheduler is running a task.
+    fn set_running(&self, running: bool) -> bool {
+        if !running && !self.active.load(Ordering::Relaxed) {
+            return false;
+        }
+        let list = self.list();
+        let mut list = locked_write(&list);
+        std::mem::replace(&mut list.running, running)
+    }
+}
+
+impl Engine {
+    /// Run the next pending task for a time slice, if it is time to.
+    #[cfg(not(feature = "unchecked"))]
+    #[inline(always)]
+    pub(crate) fn run_due_task(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<()> {
+        if global.tasks.is_due(global.num_operations) {
+            self.run_next_task(global, caches, lib, pos, level)?;
+        }
+        Ok(())
+    }
+    /// Run the next pending task for a time slice, if any.
+    ///
+    /// Returns `false` if there is no task to run.
+    ///
+    /// A task that does not finish within its time slice is put back at the end of the queue.
+    /// The result of a finished task is recorded in the task itself, except for system exceptions
+    /// (e.g. exceeding the limit on operations) which abort the entire evaluation.
+    pub(crate) fn run_next_task(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<bool> {
+        let (list, task, run) = match global.tasks.start_next() {
+            Some(next) => next,
+            None => return Ok(false),
+        };
+
+        // Statement hooks would miss the statements of suspended tasks
+        let suspend_at = if self.has_stmt_hooks() {
+            u64::MAX
+        } else {
+            global.num_operations.saturating_add(TASK_TIME_SLICE)
+        };
+
+        let result = self.run_task(global, caches, lib, &task, run, suspend_at, pos, level);
+
+        let mut tasks = locked_write(&list);
+        tasks.running = false;
+        tasks.last_run = global.num_operations;
+        let result = match result {
+            Ok(Some(state)) => {
+                TaskQueue::suspend(&mut tasks, task, state);
+                Ok(true)
+            }
+            result => result.map(|_| true),
+        };
+        drop(tasks);
+
+        result
+    }
+    /// Run a [`Task`] taken via [`TaskQueue::take`] to completion.
+    ///
+    /// Other tasks are run in time slices in between, as the task may be waiting for them.
+    pub(crate) fn join_task(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        task: &Task,
+        run: TaskRun,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<()> {
+        let running = global.tasks.set_running(false);
+        let result = self.run_task(global, caches, lib, task, run, u64::MAX, pos, level);
+        global.tasks.set_running(running);
+        result.map(|_| ())
+    }
+    /// Run a [`Task`] until it finishes or until the number of operations reaches `suspend_at`.
+    ///
+    /// Returns the state of the task if it is suspended.
+    ///
+    /// Tasks calling script-defined functions are suspended at the end of a time slice in
+    /// between statements, like [`Generator`][crate::Generator]'s. Other tasks (e.g. calling
+    /// native functions) always run to completion.
+    fn run_task(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        task: &Task,
+        run: TaskRun,
+        suspend_at: u64,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResultOf<Option<Box<SuspendedFn>>> {
+        let mut state = match run {
+            TaskRun::Resume(state) => state,
+            TaskRun::Start(fn_ptr) => {
+                match SuspendedFn::new_call(lib, fn_ptr.fn_name(), fn_ptr.curry()) {
+                    Some(state) => state.into(),
+                    None => {
+                        let mut args: StaticVec<_> = fn_ptr.curry().iter().cloned().collect();
+                        let mut args: StaticVec<_> = args.iter_mut().collect();
+                        let fn_name = fn_ptr.fn_name();
+                        let hash = calc_fn_hash(fn_name, args.len()).into();
+
+                        let result = self
+                            .exec_fn_call(
+                                None, global, caches, lib, fn_name, hash, &mut args, false, false,
+                                0, pos, level,
+                            )
+                            .map(|(r, ..)| r);
+
+                        return finish_task(task, result).map(|_| None);
+                    }
+                }
+            }
+        };
+
+        match self.resume_suspended_fn(global, caches, lib, &mut state, suspend_at, pos, level) {
+            Ok(Step::Suspend) => Ok(Some(state)),
+            Ok(Step::Done(value) | Step::Yield(value)) => {
+                finish_task(task, Ok(value)).map(|_| None)
+            }
+            Err(err) => finish_task(task, Err(err)).map(|_| None),
+        }
+    }
+    /// Run all pending tasks until none is left.
+    #[inline]
+    pub(crate) fn run_pending_tasks(
+        &self,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        level: usize,
+    ) -> RhaiResultOf<()> {
+        while self.run_next_task(global, caches, lib, Position::NONE, level)? {}
+        Ok(())
+    }
+}
+
+/// Record the result of a finished [`Task`].
+///
+/// System exceptions are returned, to abort the entire evaluation.
+fn finish_task(task: &Task, result: RhaiResult) -> RhaiResultOf<()> {
+    match result {
+        Err(err) if err.is_system_exception() => {
+            task.finish(Err(err.copy().into()));
+            Err(err)
+        }
+        result => {
+            task.finish(result);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Module defining the recording and replaying of evaluation traces.
+#![cfg(feature = "debugging")]
+
+use crate::ast::Expr;
+use crate::{Dynamic, Identifier, Position, Scope};
+use std::mem;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A variable in a reconstructed [`Scope`]: name, whether it is a constant, and its value.
+type TraceVar = (Identifier, bool, Dynamic);
+
+/// A change to the variables in a [`Scope`], recorded in a [`TraceStep`].
+///
+/// Only new values are recorded.  Values replaced by a change are reconstructed by the
+/// [`TraceReplayer`] while replaying.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum ScopeChange {
+    /// Variables were removed from the end of the [`Scope`].
+    Rewind {
+        /// Number of variables remaining in the [`Scope`].
+        len: usize,
+    },
+    /// A new value was assigned to the variable at a particular index.
+    Set {
+        /// Index of the variable in the [`Scope`].
+        index: usize,
+        /// The new value.
+        value: Dynamic,
+    },
+    /// A new variable was pushed onto the [`Scope`].
+    Push {
+        /// Name of the variable.
+        name: Identifier,
+        /// Is the variable a constant?
+        constant: bool,
+        /// Value of the variable.
+        value: Dynamic,
+    },
+    /// A script-defined function was called with a new, empty [`Scope`].
+    Enter,
+    /// A script-defined function returned, restoring the [`Scope`] of its caller.
+    Exit,
+}
+
+/// Information needed to undo a [`ScopeChange`], reconstructed while replaying.
+#[derive(Debug, Clone)]
+enum Undo {
+    /// Push back the variables removed.
+    Rewind(Vec<TraceVar>),
+    /// Restore the value replaced at a particular index.
+    Set(usize, Dynamic),
+    /// Pop the variable pushed.
+    Push,
+    /// Restore the variables of the caller.
+    Enter,
+    /// Restore the variables of the returning function, and whether the caller was recorded.
+    Exit(Vec<TraceVar>, bool),
+}
+
+/// A single step in a [`Trace`], recorded just before a statement is evaluated.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// Source of the statement, empty if none.
+    pub source: Identifier,
+    /// [Position][`Position`] of the statement.
+    pub pos: Position,
+    /// Function call nesting level.
+    pub level: usize,
+    /// Changes to the [`Scope`] since the previous step, in the order they must be applied.
+    pub changes: Vec<ScopeChange>,
+}
+
+/// A compact log of evaluation, recording the position of each statement together with
+/// the changes made to variables since the previous statement.
+///
+/// Changes are recorded where they are made: variable definitions, assignments (including to
+/// indexed elements and properties), method calls on variables, `for` loops, `catch` blocks and
+/// calls to script-defined functions.  Changes made to a shared variable through a different
+/// name (e.g. inside a closure) appear only under that name.
+///
+/// Use [`Trace::replay`] to step forwards and backwards through the recorded steps.
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// Recorded steps.
+    steps: Vec<TraceStep>,
+    /// Changes since the last recorded step.
+    changes: Vec<ScopeChange>,
+    /// Number of variables in the current [`Scope`] as of the last change.
+    len: usize,
+    /// Number of variables in the [`Scope`]s of calling functions.
+    frames: Vec<usize>,
+}
+
+impl Trace {
+    /// Create a new, empty [`Trace`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Get all the recorded steps.
+    #[inline(always)]
+    #[must_use]
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+    /// Get the number of recorded steps.
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+    /// Is this [`Trace`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+    /// Create a [`TraceReplayer`] positioned before the first step.
+    #[inline(always)]
+    #[must_use]
+    pub fn replay(&self) -> TraceReplayer<'_> {
+        TraceReplayer {
+            trace: self,
+            index: 0,
+            vars: Vec::new(),
+            frames: Vec::new(),
+            undo: Vec::new(),
+        }
+    }
+    /// Record a step, together with all changes since the previous step.
+    pub(crate) fn step(&mut self, source: &str, pos: Position, level: usize, scope: &Scope) {
+        self.sync(scope);
+
+        self.steps.push(TraceStep {
+            source: source.into(),
+            pos,
+            level,
+            changes: mem::take(&mut self.changes),
+        });
+    }
+    /// Record variables pushed onto, or removed from, the [`Scope`] since the last change.
+    ///
+    /// Only the number of variables is compared, so this must be called before a variable is
+    /// pushed if others may have been removed since the last change.
+    pub(crate) fn sync(&mut self, scope: &Scope) {
+        if scope.len() < self.len {
+            self.len = scope.len();
+            self.changes.push(ScopeChange::Rewind { len: self.len });
+        }
+
+        for (name, constant, value) in scope.iter_raw().skip(self.len) {
+            self.changes.push(ScopeChange::Push {
+                name: name.into(),
+                constant,
+                value: value.flatten_clone(),
+            });
+        }
+
+        self.len = scope.len();
+    }
+    /// Record a new value assigned to the variable at a particular index in the [`Scope`].
+    pub(crate) fn set(&mut self, scope: &Scope, index: usize) {
+        let unchanged = self.len.min(scope.len());
+
+        self.sync(scope);
+
+        // Variables pushed since the last change are already recorded with their current values
+        if index >= unchanged {
+            return;
+        }
+
+        if let Some((.., value)) = scope.iter_raw().nth(index) {
+            self.changes.push(ScopeChange::Set {
+                index,
+                value: value.flatten_clone(),
+            });
+        }
+    }
+    /// Record a new value assigned to the variable referred to by an [`Expr::Variable`].
+    pub(crate) fn set_var(&mut self, scope: &Scope, expr: &Expr) {
+        if let Some((index, ..)) = expr
+            .get_variable_name(true)
+            .and_then(|name| scope.get_index(name))
+        {
+            self.set(scope, index);
+        }
+    }
+    /// Record the call of a script-defined function with a new, empty [`Scope`].
+    pub(crate) fn enter(&mut self) {
+        self.frames.push(mem::take(&mut self.len));
+        self.changes.push(ScopeChange::Enter);
+    }
+    /// Record the return from a script-defined function called with a new, empty [`Scope`].
+    ///
+    /// If the call was not recorded, the variables of the caller are recorded as pushed at the
+    /// next change.
+    pub(crate) fn exit(&mut self) {
+        self.len = self.frames.pop().unwrap_or(0);
+        self.changes.push(ScopeChange::Exit);
+    }
+}
+
+/// A cursor replaying a [`Trace`], able to step both forwards and backwards while
+/// reconstructing the [`Scope`] at each step.
+#[derive(Debug, Clone)]
+pub struct TraceReplayer<'t> {
+    /// The [`Trace`] being replayed.
+    trace: &'t Trace,
+    /// Number of steps applied.
+    index: usize,
+    /// Reconstructed variables.
+    vars: Vec<TraceVar>,
+    /// Reconstructed variables of calling functions.
+    frames: Vec<Vec<TraceVar>>,
+    /// Information needed to undo each change applied, in order.
+    undo: Vec<Undo>,
+}
+
+impl<'t> TraceReplayer<'t> {
+    /// Get the index of the current step, or [`None`] if positioned before the first step.
+    #[inline(always)]
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        self.index.checked_sub(1)
+    }
+    /// Get the current step, or [`None`] if positioned before the first step.
+    #[inline]
+    #[must_use]
+    pub fn current(&self) -> Option<&'t TraceStep> {
+        self.index().map(|i| &self.trace.steps[i])
+    }
+    /// Apply a change, keeping the information needed to undo it.
+    fn apply(&mut self, change: &ScopeChange) {
+        let undo = match change {
+            ScopeChange::Rewind { len } => Undo::Rewind(self.vars.split_off(*len)),
+            ScopeChange::Set { index, value } => Undo::Set(
+                *index,
+                mem::replace(&mut self.vars[*index].2, value.clone()),
+            ),
+            ScopeChange::Push {
+                name,
+                constant,
+                value,
+            } => {
+                self.vars.push((name.clone(), *constant, value.clone()));
+                Undo::Push
+            }
+            ScopeChange::Enter => {
+                self.frames.push(mem::take(&mut self.vars));
+                Undo::Enter
+            }
+            ScopeChange::Exit => {
+                let has_caller = !self.frames.is_empty();
+                let caller = self.frames.pop().unwrap_or_default();
+                Undo::Exit(mem::replace(&mut self.vars, caller), has_caller)
+            }
+        };
+
+        self.undo.push(undo);
+    }
+    /// Undo the last change applied.
+    fn undo(&mut self) {
+        match self.undo.pop().expect("change applied") {
+            Undo::Rewind(removed) => self.vars.extend(removed),
+            Undo::Set(index, value) => self.vars[index].2 = value,
+            Undo::Push => {
+                self.vars.pop();
+            }
+            Undo::Enter => self.vars = self.frames.pop().expect("caller variables"),
+            Undo::Exit(vars, has_caller) => {
+                let caller = mem::replace(&mut self.vars, vars);
+                if has_caller {
+                    self.frames.push(caller);
+                }
+            }
+        }
+    }
+    /// Move to the next step, returning it, or [`None`] if already at the last step.
+    pub fn step_forward(&mut self) -> Option<&'t TraceStep> {
+        let step = self.trace.steps.get(self.index)?;
+        step.changes.iter().for_each(|c| self.apply(c));
+        self.index += 1;
+        Some(step)
+    }
+    /// Move to the previous step, returning it, or [`None`] if already at the first step
+    /// (in which case the replayer is positioned before the first step).
+    pub fn step_back(&mut self) -> Option<&'t TraceStep> {
+        let step = self.current()?;
+        (0..step.changes.len()).for_each(|_| self.undo());
+        self.index -= 1;
+        self.current()
+    }
+    /// Move to a particular step, returning it, or [`None`] if the index is out of bounds
+    /// (in which case the position is unchanged).
+    pub fn seek(&mut self, index: usize) -> Option<&'t TraceStep> {
+        if index >= self.trace.len() {
+            return None;
+        }
+        while self.index > index + 1 {
+            self.step_back();
+        }
+        while self.index < index + 1 {
+            self.step_forward();
+        }
+        self.current()
+    }
+    /// Get the value of a variable at the current step.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Dynamic> {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(n, ..)| n == name)
+            .map(|(.., v)| v)
+    }
+    /// Reconstruct the [`Scope`] as it was at the current step.
+    #[must_use]
+    pub fn scope(&self) -> Scope<'static> {
+        let mut scope = Scope::new();
+
+        for (name, constant, value) in self.vars.iter().cloned() {
+            if constant {
+                scope.push_constant_dynamic(name, value);
+            } else {
+                scope.push_dynamic(name, value);
+            }
+        }
+
+        scope
+    }
+}
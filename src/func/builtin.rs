@@ -4,6 +4,10 @@ use super::call::FnCallArgs;
 use super::native::FnBuiltin;
 use crate::engine::OP_CONTAINS;
 use crate::{Dynamic, ExclusiveRange, ImmutableString, InclusiveRange, INT};
+
+#[cfg(feature = "decimal")]
+#[cfg(not(feature = "no_float"))]
+use crate::{NativeCallContext, Position, RhaiResult, ERR};
 use std::any::TypeId;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -53,6 +57,156 @@ fn is_numeric(type_id: TypeId) -> bool {
     result
 }
 
+/// Does the built-in implementation of a binary operator between these two types give way to
+/// user-registered overloads, even under [Fast Operators][crate::Engine::fast_operators] mode?
+///
+/// This is the case for operators between a [`Decimal`] and a floating-point number, which are
+/// commonly overloaded with application-specific semantics.
+#[inline]
+#[must_use]
+pub(crate) fn is_overridable_binary_op(type1: TypeId, type2: TypeId) -> bool {
+    #[cfg(feature = "decimal")]
+    #[cfg(not(feature = "no_float"))]
+    if (type1, type2) == (TypeId::of::<Decimal>(), TypeId::of::<FLOAT>())
+        || (type1, type2) == (TypeId::of::<FLOAT>(), TypeId::of::<Decimal>())
+    {
+        return true;
+    }
+
+    let _ = (type1, type2);
+    false
+}
+
+/// Implement an operator between a [`Decimal`] and a floating-point number according to the
+/// [policy][crate::DecimalFloatPolicy] set in the [`Engine`][crate::Engine].
+#[cfg(feature = "decimal")]
+#[cfg(not(feature = "no_float"))]
+fn decimal_float_op(ctx: NativeCallContext, args: &mut FnCallArgs) -> RhaiResult {
+    use crate::DecimalFloatPolicy;
+    use std::convert::TryFrom;
+
+    let (mut x, mut y) = match ctx.engine().decimal_float_policy() {
+        DecimalFloatPolicy::Error => {
+            return Err(ERR::ErrorArithmetic(
+                format!(
+                    "Cannot mix decimal and floating-point numbers: {} {} {}",
+                    args[0],
+                    ctx.fn_name(),
+                    args[1]
+                ),
+                Position::NONE,
+            )
+            .into())
+        }
+        DecimalFloatPolicy::ToDecimal => {
+            let to_decimal = |v: &Dynamic| {
+                v.as_decimal().or_else(|_| {
+                    let f = v.as_float().expect(BUILTIN);
+                    Decimal::try_from(f).map_err(|_| {
+                        ERR::ErrorArithmetic(
+                            format!("Cannot convert to Decimal: {f}"),
+                            Position::NONE,
+                        )
+                    })
+                })
+            };
+            (
+                Dynamic::from_decimal(to_decimal(args[0])?),
+                Dynamic::from_decimal(to_decimal(args[1])?),
+            )
+        }
+        DecimalFloatPolicy::ToFloat => {
+            let to_float = |v: &Dynamic| {
+                v.as_float().or_else(|_| {
+                    let d = v.as_decimal().expect(BUILTIN);
+                    FLOAT::try_from(d).map_err(|_| {
+                        ERR::ErrorArithmetic(
+                            format!("Cannot convert to floating-point: {d}"),
+                            Position::NONE,
+                        )
+                    })
+                })
+            };
+            (
+                Dynamic::from_float(to_float(args[0])?),
+                Dynamic::from_float(to_float(args[1])?),
+            )
+        }
+    };
+
+    let func = get_builtin_binary_op_fn(ctx.fn_name(), &x, &y).expect(BUILTIN);
+    func(ctx, &mut [&mut x, &mut y])
+}
+
+/// Build in common unary operator implementations to avoid the cost of calling a registered function.
+///
+/// The return function will be registered as a _method_, so the parameter cannot be consumed.
+#[must_use]
+pub fn get_builtin_unary_op_fn(op: &str, x: &Dynamic) -> Option<FnBuiltin> {
+    let type1 = x.type_id();
+
+    macro_rules! impl_neg {
+        ($x:ty) => {
+            |_, args| {
+                let x = *args[0].read_lock::<$x>().expect(BUILTIN);
+
+                #[cfg(not(feature = "unchecked"))]
+                return x.checked_neg().map(Dynamic::from).ok_or_else(|| {
+                    crate::packages::arithmetic::make_err(format!("Negation overflow: -{x}"))
+                });
+                #[cfg(feature = "unchecked")]
+                return Ok(Dynamic::from(-x));
+            }
+        };
+    }
+
+    if type1 == TypeId::of::<INT>() {
+        return match op {
+            "-" => Some(impl_neg!(INT)),
+            "+" => Some(|_, args| Ok(args[0].as_int().expect(BUILTIN).into())),
+            _ => None,
+        };
+    }
+
+    if type1 == TypeId::of::<bool>() {
+        return match op {
+            "!" => Some(|_, args| Ok((!args[0].as_bool().expect(BUILTIN)).into())),
+            _ => None,
+        };
+    }
+
+    #[cfg(not(feature = "no_float"))]
+    if type1 == TypeId::of::<FLOAT>() {
+        return match op {
+            "-" => Some(|_, args| Ok((-args[0].as_float().expect(BUILTIN)).into())),
+            "+" => Some(|_, args| Ok(args[0].as_float().expect(BUILTIN).into())),
+            _ => None,
+        };
+    }
+
+    #[cfg(feature = "decimal")]
+    if type1 == TypeId::of::<Decimal>() {
+        return match op {
+            "-" => Some(|_, args| Ok((-args[0].as_decimal().expect(BUILTIN)).into())),
+            "+" => Some(|_, args| Ok(args[0].as_decimal().expect(BUILTIN).into())),
+            _ => None,
+        };
+    }
+
+    #[cfg(not(feature = "only_i32"))]
+    #[cfg(not(feature = "only_i64"))]
+    #[cfg(not(target_family = "wasm"))]
+    if type1 == TypeId::of::<i128>() {
+        return match op {
+            "-" => Some(impl_neg!(i128)),
+            "+" => Some(|_, args| Ok(Dynamic::from(*args[0].read_lock::<i128>().expect(BUILTIN)))),
+            _ => None,
+        };
+    }
+
+    None
+}
+
 /// Build in common binary operator implementations to avoid the cost of calling a registered function.
 ///
 /// The return function will be registered as a _method_, so the first parameter cannot be consumed.
@@ -334,6 +488,19 @@ pub fn get_builtin_binary_op_fn(op: &str, x: &Dynamic, y: &Dynamic) -> Option<Fn
         impl_decimal!(INT, as_int, Decimal, as_decimal);
     }
 
+    #[cfg(feature = "decimal")]
+    #[cfg(not(feature = "no_float"))]
+    if types_pair == (TypeId::of::<Decimal>(), TypeId::of::<FLOAT>())
+        || types_pair == (TypeId::of::<FLOAT>(), TypeId::of::<Decimal>())
+    {
+        return match op {
+            "+" | "-" | "*" | "/" | "%" | "**" | "==" | "!=" | ">" | ">=" | "<" | "<=" => {
+                Some(decimal_float_op)
+            }
+            _ => None,
+        };
+    }
+
     // char op string
     if types_pair == (TypeId::of::<char>(), TypeId::of::<ImmutableString>()) {
         fn get_s1s2(args: &FnCallArgs) -> ([char; 2], [char; 2]) {
@@ -455,6 +622,50 @@ pub fn get_builtin_binary_op_fn(op: &str, x: &Dynamic, y: &Dynamic) -> Option<Fn
         };
     }
 
+    // ordered map op string
+    #[cfg(not(feature = "no_object"))]
+    if types_pair
+        == (
+            TypeId::of::<crate::OrderedMap>(),
+            TypeId::of::<ImmutableString>(),
+        )
+    {
+        use crate::OrderedMap;
+
+        return match op {
+            OP_CONTAINS => Some(impl_op!(OrderedMap.contains_key(ImmutableString.as_str()))),
+            _ => None,
+        };
+    }
+
+    // set op any
+    #[cfg(not(feature = "no_index"))]
+    if type1 == TypeId::of::<crate::Set>() {
+        use crate::Set;
+
+        return match op {
+            OP_CONTAINS => Some(|_, args| {
+                let set = &*args[0].read_lock::<Set>().expect(BUILTIN);
+                Ok(set.contains(&*args[1]).into())
+            }),
+            _ => None,
+        };
+    }
+
+    // sorted map op any
+    #[cfg(not(feature = "no_index"))]
+    if type1 == TypeId::of::<crate::SortedMap>() {
+        use crate::SortedMap;
+
+        return match op {
+            OP_CONTAINS => Some(|_, args| {
+                let map = &*args[0].read_lock::<SortedMap>().expect(BUILTIN);
+                Ok(map.contains_key(&*args[1]).into())
+            }),
+            _ => None,
+        };
+    }
+
     // Non-compatible ranges
     if types_pair
         == (
@@ -760,6 +971,41 @@ pub fn get_builtin_op_assignment_fn(op: &str, x: &Dynamic, y: &Dynamic) -> Optio
             _ => None,
         };
     }
+    // string op= int
+    if types_pair == (TypeId::of::<ImmutableString>(), TypeId::of::<INT>()) {
+        return match op {
+            "*=" => Some(|_ctx, args| {
+                let n = args[1].as_int().expect(BUILTIN);
+                let x = &mut *args[0].write_lock::<ImmutableString>().expect(BUILTIN);
+
+                if n <= 0 {
+                    *x = ImmutableString::new();
+                    return Ok(Dynamic::UNIT);
+                }
+
+                let n = std::convert::TryFrom::try_from(n).unwrap_or(usize::MAX);
+
+                // Check if string will be over max size limit before building it
+                #[cfg(not(feature = "unchecked"))]
+                if _ctx.engine().max_string_size() > 0
+                    && x.len().saturating_mul(n) > _ctx.engine().max_string_size()
+                {
+                    return Err(crate::ERR::ErrorDataTooLarge(
+                        "Length of string".to_string(),
+                        Default::default(),
+                        crate::Position::NONE,
+                    )
+                    .into());
+                }
+
+                if n > 1 && !x.is_empty() {
+                    *x = x.repeat(n).into();
+                }
+                Ok(Dynamic::UNIT)
+            }),
+            _ => None,
+        };
+    }
     // char op= string
     if types_pair == (TypeId::of::<char>(), TypeId::of::<ImmutableString>()) {
         return match op {
@@ -801,6 +1047,32 @@ pub fn get_builtin_op_assignment_fn(op: &str, x: &Dynamic, y: &Dynamic) -> Optio
                 let array = &mut *args[0].write_lock::<Array>().expect(BUILTIN);
                 Ok(push(array, x).into())
             }),
+            "-=" => Some(|ctx, args| {
+                let x = std::mem::take(args[1]);
+                let array = &mut *args[0].write_lock::<Array>().expect(BUILTIN);
+
+                // Remove the first element that equals the value
+                let index = index_of(ctx, array, x)?;
+                if index >= 0 {
+                    array.remove(index as usize);
+                }
+                Ok(Dynamic::UNIT)
+            }),
+            _ => None,
+        };
+    }
+
+    // map op= map
+    #[cfg(not(feature = "no_object"))]
+    if types_pair == (TypeId::of::<crate::Map>(), TypeId::of::<crate::Map>()) {
+        use crate::Map;
+
+        return match op {
+            "+=" => Some(|_, args| {
+                let map2 = std::mem::take(args[1]).cast::<Map>();
+                let map1 = &mut *args[0].write_lock::<Map>().expect(BUILTIN);
+                Ok(map1.extend(map2).into())
+            }),
             _ => None,
         };
     }
@@ -1,14 +1,15 @@
 //! Implement function-calling mechanism for [`Engine`].
 
 use super::callable_function::CallableFunction;
-use super::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn};
+use super::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn, get_builtin_unary_op_fn};
 use crate::api::default_limits::MAX_DYNAMIC_PARAMETERS;
 use crate::ast::{Expr, FnCallHashes, Stmt};
 use crate::engine::{
     KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_FN_PTR_CALL, KEYWORD_FN_PTR_CURRY,
-    KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_TYPE_OF,
+    KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_THIS, KEYWORD_TYPE_OF,
 };
 use crate::eval::{Caches, FnResolutionCacheEntry, GlobalRuntimeState};
+use crate::tokenizer::is_valid_function_name;
 use crate::{
     calc_fn_hash, calc_fn_params_hash, combine_hashes, Dynamic, Engine, FnArgsVec, FnPtr,
     ImmutableString, Module, OptimizationLevel, Position, RhaiError, RhaiResult, RhaiResultOf,
@@ -107,6 +108,38 @@ impl Drop for ArgBackup<'_> {
     }
 }
 
+/// A type that temporarily replaces arguments holding values lent from variables with copies,
+/// for functions that take them by value instead of borrowing them.
+#[derive(Debug)]
+struct LentArgBackup(FnArgsVec<(usize, Dynamic)>);
+
+impl LentArgBackup {
+    /// Replace the arguments marked in a bit-mask (bit 0 = first argument) with copies, keeping
+    /// the original values.
+    #[inline]
+    pub fn copy_args(args: &mut FnCallArgs, mask: usize) -> Self {
+        let mut orig = FnArgsVec::new_const();
+
+        if mask != 0 {
+            for (i, arg) in args.iter_mut().enumerate().take(usize::BITS as usize) {
+                if mask & (1 << i) != 0 {
+                    let value = arg.clone();
+                    orig.push((i, mem::replace(&mut **arg, value)));
+                }
+            }
+        }
+
+        Self(orig)
+    }
+    /// Put the original values back into the arguments.
+    #[inline]
+    pub fn restore_args(self, args: &mut FnCallArgs) {
+        for (i, value) in self.0 {
+            *args[i] = value;
+        }
+    }
+}
+
 #[cfg(not(feature = "no_closure"))]
 #[inline]
 pub fn ensure_no_data_race(
@@ -138,7 +171,7 @@ pub fn gen_fn_call_signature(engine: &Engine, fn_name: &str, args: &[&mut Dynami
         "{fn_name} ({})",
         args.iter()
             .map(|a| if a.is::<ImmutableString>() {
-                "&str | ImmutableString | String"
+                "&str | ImmutableString | String".into()
             } else {
                 engine.map_type_name(a.type_name())
             })
@@ -172,6 +205,34 @@ pub fn gen_qualified_fn_call_signature(
 }
 
 impl Engine {
+    /// Has a system function a Rust-native override?
+    #[must_use]
+    pub(crate) fn has_native_fn_override(
+        &self,
+        hash_script: u64,
+        arg_types: impl AsRef<[TypeId]>,
+    ) -> bool {
+        let hash_params = calc_fn_params_hash(arg_types.as_ref().iter().copied());
+        let hash = combine_hashes(hash_script, hash_params);
+
+        // First check the global namespace and packages, but skip modules that are standard because
+        // they should never conflict with system functions.
+        let result = self
+            .global_modules
+            .iter()
+            .filter(|m| !m.standard)
+            .any(|m| m.contains_fn(hash));
+
+        #[cfg(not(feature = "no_module"))]
+        // Then check sub-modules
+        let result = result
+            || self
+                .global_sub_modules
+                .values()
+                .any(|m| m.contains_qualified_fn(hash));
+
+        result
+    }
     /// Resolve a normal (non-qualified) function call.
     ///
     /// Search order:
@@ -181,9 +242,9 @@ impl Engine {
     /// 4) Imported modules - functions marked with global namespace
     /// 5) Static registered modules
     #[must_use]
-    fn resolve_fn<'s>(
+    pub(crate) fn resolve_fn<'s>(
         &self,
-        _global: &GlobalRuntimeState,
+        global: &mut GlobalRuntimeState,
         caches: &'s mut Caches,
         lib: &[&Module],
         fn_name: &str,
@@ -204,8 +265,12 @@ impl Engine {
         });
 
         match caches.fn_resolution_cache_mut().entry(hash) {
-            Entry::Occupied(entry) => entry.into_mut().as_ref(),
+            Entry::Occupied(entry) => {
+                global.fn_cache_hits += 1;
+                entry.into_mut().as_ref()
+            }
             Entry::Vacant(entry) => {
+                global.fn_cache_misses += 1;
                 let num_args = args.as_ref().map_or(0, |a| a.len());
                 let mut max_bitmask = 0; // One above maximum bitmask based on number of parameters.
                                          // Set later when a specific matching function is not found.
@@ -214,25 +279,35 @@ impl Engine {
                 loop {
                     let func = lib
                         .iter()
-                        .find_map(|&m| m.get_fn(hash).map(|f| (f, m.id())))
+                        .find_map(|&m| {
+                            m.get_fn_with_borrowed_params(hash)
+                                .map(|(f, b)| (f, b, m.id()))
+                        })
                         .or_else(|| {
-                            self.global_modules
-                                .iter()
-                                .find_map(|m| m.get_fn(hash).map(|f| (f, m.id())))
+                            self.global_modules.iter().find_map(|m| {
+                                m.get_fn_with_borrowed_params(hash)
+                                    .map(|(f, b)| (f, b, m.id()))
+                            })
                         });
 
                     #[cfg(not(feature = "no_module"))]
-                    let func = func.or_else(|| _global.get_qualified_fn(hash)).or_else(|| {
-                        self.global_sub_modules
-                            .values()
-                            .find_map(|m| m.get_qualified_fn(hash).map(|f| (f, m.id())))
-                    });
+                    let func = func
+                        .or_else(|| global.get_qualified_fn(hash).map(|(f, s)| (f, 0, s)))
+                        .or_else(|| {
+                            self.global_sub_modules
+                                .values()
+                                .find_map(|m| m.get_qualified_fn(hash).map(|f| (f, 0, m.id())))
+                        });
 
-                    if let Some((f, s)) = func {
+                    // Host-only functions are invisible to scripts
+                    let func = func.filter(|(f, ..)| f.is_script_callable());
+
+                    if let Some((f, b, s)) = func {
                         // Specific version found - insert into cache and return it
                         let new_entry = FnResolutionCacheEntry {
                             func: f.clone(),
                             source: s.map(|s| Box::new(s.into())),
+                            borrowed_params: b,
                         };
                         return entry.insert(Some(new_entry)).as_ref();
                     }
@@ -247,7 +322,7 @@ impl Engine {
 
                         #[cfg(not(feature = "no_module"))]
                         let is_dynamic = is_dynamic
-                            || _global
+                            || global
                                 .iter_imports_raw()
                                 .any(|(_, m)| m.may_contain_dynamic_fn(hash_base))
                             || self
@@ -263,19 +338,28 @@ impl Engine {
 
                     // Stop when all permutations are exhausted
                     if bitmask >= max_bitmask {
-                        if num_args != 2 {
+                        if num_args != 1 && num_args != 2 {
                             return None;
                         }
 
                         // Try to find a built-in version
                         let builtin = args.and_then(|args| {
-                            if is_op_assignment {
+                            if num_args == 1 {
+                                get_builtin_unary_op_fn(fn_name, args[0]).map(|f| {
+                                    FnResolutionCacheEntry {
+                                        func: CallableFunction::from_fn_builtin(f),
+                                        source: None,
+                                        borrowed_params: 0,
+                                    }
+                                })
+                            } else if is_op_assignment {
                                 let (first_arg, rest_args) = args.split_first().unwrap();
 
                                 get_builtin_op_assignment_fn(fn_name, *first_arg, rest_args[0]).map(
                                     |f| FnResolutionCacheEntry {
                                         func: CallableFunction::from_fn_builtin(f),
                                         source: None,
+                                        borrowed_params: 0,
                                     },
                                 )
                             } else {
@@ -283,6 +367,7 @@ impl Engine {
                                     FnResolutionCacheEntry {
                                         func: CallableFunction::from_fn_builtin(f),
                                         source: None,
+                                        borrowed_params: 0,
                                     }
                                 })
                             }
@@ -326,6 +411,10 @@ impl Engine {
     ///
     /// **DO NOT** reuse the argument values unless for the first `&mut` argument -
     /// all others are silently replaced by `()`!
+    ///
+    /// The exceptions are the arguments marked in `lent_args` (a bit-mask with bit 0 = first
+    /// argument), which hold values lent from variables: they are passed as is to the parameters
+    /// that the function borrows, and as copies to all others.
     pub(crate) fn call_native_fn(
         &self,
         global: &mut GlobalRuntimeState,
@@ -336,11 +425,12 @@ impl Engine {
         args: &mut FnCallArgs,
         is_ref_mut: bool,
         is_op_assign: bool,
+        lent_args: usize,
         pos: Position,
         level: usize,
     ) -> RhaiResultOf<(Dynamic, bool)> {
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, pos)?;
+        self.inc_operations(global, pos)?;
 
         let parent_source = global.source.clone();
 
@@ -363,7 +453,12 @@ impl Engine {
             #[cfg(feature = "debugging")]
             let orig_call_stack_len = global.debugger.call_stack().len();
 
-            let mut _result = if let Some(FnResolutionCacheEntry { func, source }) = func {
+            let mut _result = if let Some(FnResolutionCacheEntry {
+                func,
+                source,
+                borrowed_params,
+            }) = func
+            {
                 assert!(func.is_native());
 
                 let mut backup = ArgBackup::new();
@@ -374,6 +469,9 @@ impl Engine {
                     backup.change_first_arg_to_copy(args);
                 }
 
+                // Copy lent values not borrowed by the function
+                let lent_backup = LentArgBackup::copy_args(args, lent_args & !borrowed_params);
+
                 let source = match (source, parent_source.as_str()) {
                     (None, "") => None,
                     (None, s) => Some(s),
@@ -393,14 +491,18 @@ impl Engine {
                 // Run external function
                 let context = (self, name, source, &*global, lib, pos, level).into();
 
-                let result = if func.is_plugin_fn() {
-                    func.get_plugin_fn().unwrap().call(context, args)
-                } else {
-                    func.get_native_fn().unwrap()(context, args)
-                };
+                let result =
+                    self.run_native_fn_with_log(global, name, hash, args, is_ref_mut, |args| {
+                        if func.is_plugin_fn() {
+                            func.get_plugin_fn().unwrap().call(context, args)
+                        } else {
+                            func.get_native_fn().unwrap()(context, args)
+                        }
+                    });
 
                 // Restore the original reference
                 backup.restore_first_arg(args);
+                lent_backup.restore_args(args);
 
                 result
             } else {
@@ -441,6 +543,12 @@ impl Engine {
                 self.check_data_size(args[0], pos)?;
             }
 
+            #[cfg(not(feature = "unchecked"))]
+            if self.fuel_limit() > 0 {
+                let fuel = self.calc_native_call_fuel(name, &result, args.first().map(|a| &**a));
+                self.consume_fuel(global, fuel, pos)?;
+            }
+
             // See if the function match print/debug (which requires special processing)
             return Ok(match name {
                 KEYWORD_PRINT => {
@@ -498,6 +606,13 @@ impl Engine {
                 assert!(args.len() == 1);
 
                 let prop = &name[crate::engine::FN_GET.len()..];
+
+                // Ask the unknown property handler of the type
+                if let Some(handler) = self.unknown_property_handlers.get(&args[0].type_id()) {
+                    let result = self.check_return_value(handler(&mut *args[0], prop), pos)?;
+                    return Ok((result, false));
+                }
+
                 let t0 = self.map_type_name(args[0].type_name());
 
                 Err(ERR::ErrorDotExpr(
@@ -527,6 +642,87 @@ impl Engine {
                 .into())
             }
 
+            // Ask the unknown method handler of the type of the first argument
+            _ if !args.is_empty()
+                && is_valid_function_name(name)
+                && self
+                    .unknown_method_handlers
+                    .contains_key(&args[0].type_id()) =>
+            {
+                let handler = &self.unknown_method_handlers[&args[0].type_id()];
+                let lent_backup = LentArgBackup::copy_args(args, lent_args);
+                let (obj, rest) = args.split_first_mut().unwrap();
+                let result = handler(obj, name, rest);
+                lent_backup.restore_args(args);
+                let result = self.check_return_value(result, pos)?;
+
+                // Check the data size of the object, which may be changed.
+                #[cfg(not(feature = "unchecked"))]
+                self.check_data_size(args[0], pos)?;
+
+                Ok((result, true))
+            }
+
+            // Ask the missing function callback
+            _ if self.missing_fn.is_some() => {
+                let type_names = args
+                    .iter()
+                    .map(|a| self.map_type_name(a.type_name()))
+                    .collect::<FnArgsVec<_>>();
+                let arg_types = type_names
+                    .iter()
+                    .map(|t| t.as_ref())
+                    .collect::<FnArgsVec<_>>();
+
+                let func = match self.missing_fn.as_ref().unwrap()(name, &arg_types[..], pos)? {
+                    Some(func) if func.get_native_fn().is_some() || func.is_plugin_fn() => func,
+                    _ => {
+                        return Err(ERR::ErrorFunctionNotFound(
+                            gen_fn_call_signature(self, name, args),
+                            pos,
+                        )
+                        .into())
+                    }
+                };
+
+                let mut backup = ArgBackup::new();
+
+                // Calling pure function but the first argument is a reference?
+                if is_ref_mut && func.is_pure() && !args.is_empty() {
+                    // Clone the first argument
+                    backup.change_first_arg_to_copy(args);
+                }
+
+                let lent_backup = LentArgBackup::copy_args(args, lent_args);
+
+                let source = if parent_source.is_empty() {
+                    None
+                } else {
+                    Some(parent_source.as_str())
+                };
+                let context = (self, name, source, &*global, lib, pos, level).into();
+
+                let result = if func.is_plugin_fn() {
+                    func.get_plugin_fn().unwrap().call(context, args)
+                } else {
+                    func.get_native_fn().unwrap()(context, args)
+                };
+
+                // Restore the original reference
+                backup.restore_first_arg(args);
+                lent_backup.restore_args(args);
+
+                let result = self.check_return_value(result, pos)?;
+
+                // Check the data size of any `&mut` object, which may be changed.
+                #[cfg(not(feature = "unchecked"))]
+                if is_ref_mut && !args.is_empty() {
+                    self.check_data_size(args[0], pos)?;
+                }
+
+                Ok((result, func.is_method()))
+            }
+
             // Raise error
             _ => {
                 Err(ERR::ErrorFunctionNotFound(gen_fn_call_signature(self, name, args), pos).into())
@@ -545,6 +741,10 @@ impl Engine {
     ///
     /// **DO NOT** reuse the argument values unless for the first `&mut` argument -
     /// all others are silently replaced by `()`!
+    ///
+    /// The exceptions are the arguments marked in `lent_args` (a bit-mask with bit 0 = first
+    /// argument), which hold values lent from variables: they are passed as is to the parameters
+    /// that a native function borrows, and as copies to all others.
     pub(crate) fn exec_fn_call(
         &self,
         _scope: Option<&mut Scope>,
@@ -556,6 +756,7 @@ impl Engine {
         args: &mut FnCallArgs,
         is_ref_mut: bool,
         _is_method_call: bool,
+        lent_args: usize,
         pos: Position,
         level: usize,
     ) -> RhaiResultOf<(Dynamic, bool)> {
@@ -620,7 +821,9 @@ impl Engine {
 
         // Script-defined function call?
         #[cfg(not(feature = "no_function"))]
-        if let Some(FnResolutionCacheEntry { func, ref source }) = self
+        if let Some(FnResolutionCacheEntry {
+            func, ref source, ..
+        }) = self
             .resolve_fn(
                 global,
                 caches,
@@ -658,6 +861,9 @@ impl Engine {
                     .map_or(crate::Identifier::new_const(), |s| (**s).clone()),
             );
 
+            // Script-defined functions take all arguments by value
+            let lent_backup = LentArgBackup::copy_args(args, lent_args);
+
             let result = if _is_method_call {
                 // Method call of script function - map first argument to `this`
                 let (first_arg, rest_args) = args.split_first_mut().unwrap();
@@ -695,6 +901,7 @@ impl Engine {
 
             // Restore the original source
             global.source = orig_source;
+            lent_backup.restore_args(args);
 
             return Ok((result?, false));
         }
@@ -702,7 +909,7 @@ impl Engine {
         // Native function call
         let hash = hashes.native;
         self.call_native_fn(
-            global, caches, lib, fn_name, hash, args, is_ref_mut, false, pos, level,
+            global, caches, lib, fn_name, hash, args, is_ref_mut, false, lent_args, pos, level,
         )
     }
 
@@ -718,6 +925,10 @@ impl Engine {
         lib: &[&Module],
         level: usize,
     ) -> RhaiResult {
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::info_span!(target: "rhai", "eval", source = %global.source, level).entered();
+
         self.eval_stmt_block(
             scope, global, caches, lib, &mut None, statements, false, level,
         )
@@ -726,6 +937,12 @@ impl Engine {
             ERR::LoopBreak(..) => {
                 unreachable!("no outer loop scope to break out of")
             }
+            #[cfg(not(feature = "unchecked"))]
+            mut err => {
+                Self::add_limit_context(&mut err, scope, "", Position::NONE);
+                Err(err.into())
+            }
+            #[cfg(feature = "unchecked")]
             _ => Err(err),
         })
     }
@@ -766,6 +983,91 @@ impl Engine {
         Ok((result?, arg_expr.start_position()))
     }
 
+    /// Evaluate a variable argument that may be lent to a native function.
+    ///
+    /// Returns [`None`] if the variable holds an array, a BLOB or an object map, which is then left
+    /// in the [`Scope`] to be taken via [`take_lent_args`][Engine::take_lent_args] right before the
+    /// function call.
+    fn get_lendable_arg_value(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        arg_expr: &Expr,
+        level: usize,
+    ) -> RhaiResultOf<Option<Dynamic>> {
+        #[cfg(feature = "debugging")]
+        self.run_debugger(scope, global, lib, this_ptr, arg_expr, level)?;
+
+        let (target, _pos) =
+            self.search_namespace(scope, global, lib, this_ptr, arg_expr, level)?;
+
+        #[cfg(not(feature = "unchecked"))]
+        self.inc_operations(global, _pos)?;
+
+        #[cfg(not(feature = "no_closure"))]
+        let target_is_shared = target.is_shared();
+        #[cfg(feature = "no_closure")]
+        let target_is_shared = false;
+
+        let is_container = false;
+        #[cfg(not(feature = "no_index"))]
+        let is_container =
+            is_container || target.is::<crate::Array>() || target.is::<crate::Blob>();
+        #[cfg(not(feature = "no_object"))]
+        let is_container = is_container || target.is::<crate::Map>();
+
+        if target_is_shared || target.is_temp_value() || !is_container {
+            Ok(Some(target.take_or_clone().flatten()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Move the values of variables lent to a function call out of the [`Scope`] into their
+    /// arguments.
+    ///
+    /// Each lent argument is the index of its value and the variable expression, which must have
+    /// been evaluated via [`get_lendable_arg_value`][Engine::get_lendable_arg_value] with the
+    /// [`Scope`] unchanged since.
+    fn take_lent_args(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        lent: &[(usize, &Expr)],
+        arg_values: &mut [Dynamic],
+        level: usize,
+    ) {
+        for &(index, expr) in lent {
+            let (mut target, ..) = self
+                .search_namespace(scope, global, lib, this_ptr, expr, level)
+                .expect("variable found");
+            arg_values[index] = mem::take(&mut *target);
+        }
+    }
+
+    /// Put the values of variables lent to a function call back into the [`Scope`].
+    fn restore_lent_args(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        lent: &[(usize, &Expr)],
+        arg_values: &mut [Dynamic],
+        level: usize,
+    ) {
+        for &(index, expr) in lent {
+            let (mut target, ..) = self
+                .search_namespace(scope, global, lib, this_ptr, expr, level)
+                .expect("variable found");
+            *target = mem::take(&mut arg_values[index]);
+        }
+    }
+
     /// Call a dot method.
     #[cfg(not(feature = "no_object"))]
     pub(crate) fn make_method_call(
@@ -810,6 +1112,7 @@ impl Engine {
                     &mut args,
                     false,
                     false,
+                    0,
                     fn_call_pos,
                     level,
                 )
@@ -817,10 +1120,10 @@ impl Engine {
             KEYWORD_FN_PTR_CALL => {
                 if call_args.is_empty() {
                     let typ = self.map_type_name(target.type_name());
-                    return Err(self.make_type_mismatch_err::<FnPtr>(typ, fn_call_pos));
+                    return Err(self.make_type_mismatch_err::<FnPtr>(&typ, fn_call_pos));
                 } else if !call_args[0].is::<FnPtr>() {
                     let typ = self.map_type_name(call_args[0].type_name());
-                    return Err(self.make_type_mismatch_err::<FnPtr>(typ, first_arg_pos));
+                    return Err(self.make_type_mismatch_err::<FnPtr>(&typ, first_arg_pos));
                 }
 
                 // FnPtr call on object
@@ -855,6 +1158,7 @@ impl Engine {
                     &mut args,
                     is_ref_mut,
                     true,
+                    0,
                     fn_call_pos,
                     level,
                 )
@@ -862,7 +1166,7 @@ impl Engine {
             KEYWORD_FN_PTR_CURRY => {
                 if !target.is::<FnPtr>() {
                     let typ = self.map_type_name(target.type_name());
-                    return Err(self.make_type_mismatch_err::<FnPtr>(typ, fn_call_pos));
+                    return Err(self.make_type_mismatch_err::<FnPtr>(&typ, fn_call_pos));
                 }
 
                 let fn_ptr = target.read_lock::<FnPtr>().expect("`FnPtr`");
@@ -942,6 +1246,7 @@ impl Engine {
                     &mut args,
                     is_ref_mut,
                     true,
+                    0,
                     fn_call_pos,
                     level,
                 )
@@ -992,7 +1297,7 @@ impl Engine {
 
                 if !arg_value.is::<FnPtr>() {
                     let typ = self.map_type_name(arg_value.type_name());
-                    return Err(self.make_type_mismatch_err::<FnPtr>(typ, arg_pos));
+                    return Err(self.make_type_mismatch_err::<FnPtr>(&typ, arg_pos));
                 }
 
                 let fn_ptr = arg_value.cast::<FnPtr>();
@@ -1040,7 +1345,7 @@ impl Engine {
 
                 if !arg_value.is::<FnPtr>() {
                     let typ = self.map_type_name(arg_value.type_name());
-                    return Err(self.make_type_mismatch_err::<FnPtr>(typ, arg_pos));
+                    return Err(self.make_type_mismatch_err::<FnPtr>(&typ, arg_pos));
                 }
 
                 let (name, fn_curry) = arg_value.cast::<FnPtr>().take_data();
@@ -1103,6 +1408,52 @@ impl Engine {
                 return Ok(scope.contains(&var_name).into());
             }
 
+            // Handle call_qualified()
+            #[cfg(not(feature = "no_module"))]
+            #[cfg(not(feature = "no_index"))]
+            crate::engine::KEYWORD_CALL_QUALIFIED if total_args == 2 => {
+                let first = first_arg.unwrap();
+                let (arg_value, arg_pos) =
+                    self.get_arg_value(scope, global, caches, lib, this_ptr, first, level)?;
+
+                let path = arg_value
+                    .into_immutable_string()
+                    .map_err(|typ| self.make_type_mismatch_err::<ImmutableString>(typ, arg_pos))?;
+
+                let (arg_value, arg_pos) =
+                    self.get_arg_value(scope, global, caches, lib, this_ptr, &a_expr[0], level)?;
+
+                let mut arg_values = arg_value
+                    .into_array()
+                    .map_err(|typ| self.make_type_mismatch_err::<crate::Array>(typ, arg_pos))?;
+
+                // Split the path into the namespace and the function name
+                let separator = crate::tokenizer::Token::DoubleColon.literal_syntax();
+                let (namespace, fn_name) = match path.rsplit_once(separator) {
+                    Some((ns, name)) if !ns.is_empty() && !name.is_empty() => (ns, name),
+                    _ => return Err(ERR::ErrorFunctionNotFound(path.to_string(), pos).into()),
+                };
+                let namespace: crate::ast::Namespace = namespace
+                    .split(separator)
+                    .map(|m| crate::ast::Ident {
+                        name: caches.get_interned_string(self, m),
+                        pos,
+                    })
+                    .collect::<crate::StaticVec<_>>()
+                    .into();
+
+                let hash = crate::calc_qualified_fn_hash(
+                    namespace.iter().map(crate::ast::Ident::as_str),
+                    fn_name,
+                    arg_values.len(),
+                );
+                let mut args = arg_values.iter_mut().collect();
+
+                return self.call_qualified_fn_raw(
+                    global, caches, lib, &namespace, fn_name, hash, &mut args, None, pos, level,
+                );
+            }
+
             // Handle eval()
             KEYWORD_EVAL if total_args == 1 => {
                 // eval - only in function call style
@@ -1144,7 +1495,13 @@ impl Engine {
         }
 
         // Normal function call - except for Fn, curry, call and eval (handled above)
-        let mut arg_values = FnArgsVec::with_capacity(total_args);
+        let mut arg_values = FnArgsVec::new();
+        if total_args > arg_values.inline_size() {
+            // Take spilled argument lists from the scratch arena, if any
+            arg_values = caches
+                .take_values(total_args)
+                .map_or_else(|| FnArgsVec::with_capacity(total_args), FnArgsVec::from_vec);
+        }
         let mut args = FnArgsVec::with_capacity(total_args + curry.len());
         let mut is_ref_mut = false;
 
@@ -1167,14 +1524,50 @@ impl Engine {
             // Use parent scope
             let scope = Some(scope);
 
-            return self
+            let result = self
                 .exec_fn_call(
-                    scope, global, caches, lib, name, hashes, &mut args, is_ref_mut, false, pos,
+                    scope, global, caches, lib, name, hashes, &mut args, is_ref_mut, false, 0, pos,
                     level,
                 )
                 .map(|(v, ..)| v);
+
+            drop(args);
+            if arg_values.spilled() {
+                caches.put_values(arg_values.into_vec());
+            }
+
+            return result;
         }
 
+        // Variables holding arrays, BLOB's or object maps are lent to native functions that borrow
+        // them, instead of being cloned, if no other argument can observe the lent variables.
+        //
+        // As all arguments are then constants or variables, nothing can fail or change the scope
+        // once they are evaluated, so the lent values are only moved out of the scope afterwards,
+        // right before the call, and are always put back right after it.
+        let lend_args = curry.is_empty()
+            && self.resolve_var.is_none()
+            && total_args < usize::BITS as usize
+            && first_arg
+                .into_iter()
+                .chain(a_expr)
+                .all(|expr| expr.is_constant() || expr.is_variable_access(true));
+        let can_lend = |expr: &Expr| {
+            lend_args
+                && match expr.get_variable_name(true) {
+                    Some(name) if name != KEYWORD_THIS => {
+                        first_arg
+                            .into_iter()
+                            .chain(a_expr)
+                            .filter(|&e| e.get_variable_name(true) == Some(name))
+                            .count()
+                            == 1
+                    }
+                    _ => false,
+                }
+        };
+        let mut lent = FnArgsVec::new_const();
+
         // Call with blank scope
         if total_args == 0 && curry.is_empty() {
             // No arguments
@@ -1190,32 +1583,89 @@ impl Engine {
 
                 // func(x, ...) -> x.func(...)
                 a_expr.iter().try_for_each(|expr| {
-                    self.get_arg_value(scope, global, caches, lib, this_ptr, expr, level)
-                        .map(|(value, ..)| arg_values.push(value.flatten()))
+                    if can_lend(expr) {
+                        self.get_lendable_arg_value(scope, global, lib, this_ptr, expr, level)
+                            .map(|value| {
+                                if value.is_none() {
+                                    lent.push((arg_values.len(), expr));
+                                }
+                                arg_values.push(value.unwrap_or(Dynamic::UNIT));
+                            })
+                    } else {
+                        self.get_arg_value(scope, global, caches, lib, this_ptr, expr, level)
+                            .map(|(value, ..)| arg_values.push(value.flatten()))
+                    }
                 })?;
 
-                let (mut target, _pos) =
-                    self.search_namespace(scope, global, lib, this_ptr, first_expr, level)?;
+                if !lent.is_empty() {
+                    // Lent values must be taken before holding a reference into the scope
+                    let value = {
+                        let (mut target, _pos) =
+                            self.search_namespace(scope, global, lib, this_ptr, first_expr, level)?;
 
-                if target.is_read_only() {
-                    target = target.into_owned();
-                }
+                        if target.is_read_only() {
+                            target = target.into_owned();
+                        }
 
-                #[cfg(not(feature = "unchecked"))]
-                self.inc_operations(&mut global.num_operations, _pos)?;
+                        #[cfg(not(feature = "unchecked"))]
+                        self.inc_operations(global, _pos)?;
 
-                #[cfg(not(feature = "no_closure"))]
-                let target_is_shared = target.is_shared();
-                #[cfg(feature = "no_closure")]
-                let target_is_shared = false;
+                        #[cfg(not(feature = "no_closure"))]
+                        let target_is_shared = target.is_shared();
+                        #[cfg(feature = "no_closure")]
+                        let target_is_shared = false;
 
-                if target_is_shared || target.is_temp_value() {
-                    arg_values.insert(0, target.take_or_clone().flatten());
+                        if target_is_shared || target.is_temp_value() {
+                            Some(target.take_or_clone().flatten())
+                        } else {
+                            None
+                        }
+                    };
+
+                    self.take_lent_args(
+                        scope,
+                        global,
+                        lib,
+                        this_ptr,
+                        &lent,
+                        &mut arg_values,
+                        level,
+                    );
+
+                    if let Some(value) = value {
+                        arg_values.insert(0, value);
+                        lent.iter_mut().for_each(|(index, ..)| *index += 1);
+                    } else {
+                        let (target, ..) = self
+                            .search_namespace(scope, global, lib, this_ptr, first_expr, level)
+                            .expect("variable found");
+                        is_ref_mut = true;
+                        args.push(target.take_ref().expect("ref"));
+                    }
                 } else {
-                    // Turn it into a method call only if the object is not shared and not a simple value
-                    is_ref_mut = true;
-                    let obj_ref = target.take_ref().expect("ref");
-                    args.push(obj_ref);
+                    let (mut target, _pos) =
+                        self.search_namespace(scope, global, lib, this_ptr, first_expr, level)?;
+
+                    if target.is_read_only() {
+                        target = target.into_owned();
+                    }
+
+                    #[cfg(not(feature = "unchecked"))]
+                    self.inc_operations(global, _pos)?;
+
+                    #[cfg(not(feature = "no_closure"))]
+                    let target_is_shared = target.is_shared();
+                    #[cfg(feature = "no_closure")]
+                    let target_is_shared = false;
+
+                    if target_is_shared || target.is_temp_value() {
+                        arg_values.insert(0, target.take_or_clone().flatten());
+                    } else {
+                        // Turn it into a method call only if the object is not shared and not a simple value
+                        is_ref_mut = true;
+                        let obj_ref = target.take_ref().expect("ref");
+                        args.push(obj_ref);
+                    }
                 }
             } else {
                 // func(..., ...)
@@ -1223,19 +1673,53 @@ impl Engine {
                     .into_iter()
                     .chain(a_expr.iter())
                     .try_for_each(|expr| {
-                        self.get_arg_value(scope, global, caches, lib, this_ptr, expr, level)
-                            .map(|(value, ..)| arg_values.push(value.flatten()))
+                        if can_lend(expr) {
+                            self.get_lendable_arg_value(scope, global, lib, this_ptr, expr, level)
+                                .map(|value| {
+                                    if value.is_none() {
+                                        lent.push((arg_values.len(), expr));
+                                    }
+                                    arg_values.push(value.unwrap_or(Dynamic::UNIT));
+                                })
+                        } else {
+                            self.get_arg_value(scope, global, caches, lib, this_ptr, expr, level)
+                                .map(|(value, ..)| arg_values.push(value.flatten()))
+                        }
                     })?;
+
+                self.take_lent_args(scope, global, lib, this_ptr, &lent, &mut arg_values, level);
+
                 args.extend(curry.iter_mut());
             }
 
             args.extend(arg_values.iter_mut());
         }
 
-        self.exec_fn_call(
-            None, global, caches, lib, name, hashes, &mut args, is_ref_mut, false, pos, level,
-        )
-        .map(|(v, ..)| v)
+        let offset = if is_ref_mut { 1 } else { 0 };
+        let lent_args = lent
+            .iter()
+            .fold(0, |mask, &(index, ..)| mask | (1 << (index + offset)));
+
+        let result = self.exec_fn_call(
+            None, global, caches, lib, name, hashes, &mut args, is_ref_mut, false, lent_args, pos,
+            level,
+        );
+
+        drop(args);
+        self.restore_lent_args(scope, global, lib, this_ptr, &lent, &mut arg_values, level);
+
+        #[cfg(feature = "debugging")]
+        if let (Ok((.., true)), true, Some(expr)) = (&result, is_ref_mut, first_arg) {
+            global.debugger.trace_var(scope, expr);
+        }
+
+        let result = result.map(|(v, ..)| v);
+
+        if arg_values.spilled() {
+            caches.put_values(arg_values.into_vec());
+        }
+
+        result
     }
 
     /// Call a namespace-qualified function in normal function-call style.
@@ -1254,7 +1738,14 @@ impl Engine {
         pos: Position,
         level: usize,
     ) -> RhaiResult {
-        let mut arg_values = FnArgsVec::with_capacity(args_expr.len());
+        let mut arg_values = FnArgsVec::new();
+        if args_expr.len() > arg_values.inline_size() {
+            // Take spilled argument lists from the scratch arena, if any
+            arg_values = caches.take_values(args_expr.len()).map_or_else(
+                || FnArgsVec::with_capacity(args_expr.len()),
+                FnArgsVec::from_vec,
+            );
+        }
         let mut args = FnArgsVec::with_capacity(args_expr.len());
         let mut first_arg_value = None;
 
@@ -1282,7 +1773,7 @@ impl Engine {
                     self.search_scope_only(scope, global, lib, this_ptr, first_arg, level)?;
 
                 #[cfg(not(feature = "unchecked"))]
-                self.inc_operations(&mut global.num_operations, _pos)?;
+                self.inc_operations(global, _pos)?;
 
                 #[cfg(not(feature = "no_closure"))]
                 let target_is_shared = target.is_shared();
@@ -1310,17 +1801,68 @@ impl Engine {
             }
         }
 
+        #[cfg(feature = "debugging")]
+        let is_ref_mut = first_arg_value.is_some();
+
+        let result = self.call_qualified_fn_raw(
+            global,
+            caches,
+            lib,
+            namespace,
+            fn_name,
+            hash,
+            &mut args,
+            first_arg_value,
+            pos,
+            level,
+        );
+
+        drop(args);
+
+        #[cfg(feature = "debugging")]
+        if result.is_ok() && is_ref_mut {
+            global.debugger.trace_var(scope, &args_expr[0]);
+        }
+
+        if arg_values.spilled() {
+            caches.put_values(arg_values.into_vec());
+        }
+
+        result
+    }
+
+    /// Resolve and call a namespace-qualified function with a list of arguments.
+    ///
+    /// If `first_arg_value` is [`Some`], the first argument is passed by reference and the value is
+    /// used as storage for a copy of it if the function turns out not to be a method.
+    #[cfg(not(feature = "no_module"))]
+    fn call_qualified_fn_raw<'a>(
+        &self,
+        global: &mut GlobalRuntimeState,
+        _caches: &mut Caches,
+        lib: &[&Module],
+        namespace: &crate::ast::Namespace,
+        fn_name: &str,
+        hash: u64,
+        args: &mut FnArgsVec<&'a mut Dynamic>,
+        first_arg_value: Option<&'a mut Dynamic>,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResult {
         // Search for the root namespace
         let module = self
             .search_imports(global, namespace)
             .ok_or_else(|| ERR::ErrorModuleNotFound(namespace.to_string(), namespace.position()))?;
 
         // First search script-defined functions in namespace (can override built-in)
-        let mut func = match module.get_qualified_fn(hash) {
+        let mut func = match module
+            .get_qualified_fn(hash)
+            .filter(|f| f.is_script_callable())
+        {
             // Then search native Rust functions
             None => {
                 #[cfg(not(feature = "unchecked"))]
-                self.inc_operations(&mut global.num_operations, pos)?;
+                self.inc_operations(global, pos)?;
 
                 let hash_params = calc_fn_params_hash(args.iter().map(|a| a.type_id()));
                 let hash_qualified_fn = combine_hashes(hash, hash_params);
@@ -1354,7 +1896,7 @@ impl Engine {
                 let hash_qualified_fn = combine_hashes(hash, hash_params);
 
                 #[cfg(not(feature = "unchecked"))]
-                self.inc_operations(&mut global.num_operations, pos)?;
+                self.inc_operations(global, pos)?;
 
                 if let Some(f) = module.get_qualified_fn(hash_qualified_fn) {
                     func = Some(f);
@@ -1384,7 +1926,7 @@ impl Engine {
                 mem::swap(&mut global.source, &mut source);
 
                 let result = self.call_script_fn(
-                    new_scope, global, caches, lib, &mut None, fn_def, &mut args, true, pos, level,
+                    new_scope, global, _caches, lib, &mut None, fn_def, args, true, pos, level,
                 );
 
                 global.source = source;
@@ -1394,25 +1936,28 @@ impl Engine {
 
             Some(f) if f.is_plugin_fn() => {
                 let context = (self, fn_name, module.id(), &*global, lib, pos, level).into();
-                let result = f
-                    .get_plugin_fn()
-                    .expect("plugin function")
-                    .clone()
-                    .call(context, &mut args);
+                let func = f.get_plugin_fn().expect("plugin function").clone();
+                let result =
+                    self.run_native_fn_with_log(global, fn_name, hash, args, false, |args| {
+                        func.call(context, args)
+                    });
                 self.check_return_value(result, pos)
             }
 
             Some(f) if f.is_native() => {
                 let func = f.get_native_fn().expect("native function");
                 let context = (self, fn_name, module.id(), &*global, lib, pos, level).into();
-                let result = func(context, &mut args);
+                let result =
+                    self.run_native_fn_with_log(global, fn_name, hash, args, false, |args| {
+                        func(context, args)
+                    });
                 self.check_return_value(result, pos)
             }
 
             Some(f) => unreachable!("unknown function type: {:?}", f),
 
             None => Err(ERR::ErrorFunctionNotFound(
-                gen_qualified_fn_call_signature(self, namespace, fn_name, &args),
+                gen_qualified_fn_call_signature(self, namespace, fn_name, args),
                 pos,
             )
             .into()),
@@ -1431,7 +1976,7 @@ impl Engine {
         level: usize,
     ) -> RhaiResult {
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, _pos)?;
+        self.inc_operations(global, _pos)?;
 
         let script = script.trim();
 
@@ -1,9 +1,10 @@
 //! Module defining the standard Rhai function type.
 
+use super::call::FnCallArgs;
 use super::native::{FnAny, FnBuiltin, FnPlugin, IteratorFn, SendSync};
 use crate::ast::FnAccess;
 use crate::plugin::PluginFunction;
-use crate::Shared;
+use crate::{NativeCallContext, RhaiResult, Shared};
 use std::fmt;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -149,6 +150,20 @@ impl CallableFunction {
             Self::Script(f) => f.access,
         }
     }
+    /// Can this function be called from scripts?
+    #[inline]
+    #[must_use]
+    pub fn is_script_callable(&self) -> bool {
+        #[cfg(feature = "no_function")]
+        return true;
+
+        #[cfg(not(feature = "no_function"))]
+        match self {
+            Self::Plugin(..) => true,
+            Self::Pure(..) | Self::Method(..) | Self::Iterator(..) => true,
+            Self::Script(f) => f.visibility.is_script_callable(),
+        }
+    }
     /// Get a shared reference to a native Rust function.
     #[inline]
     #[must_use]
@@ -203,6 +218,24 @@ impl CallableFunction {
     pub fn from_fn_builtin(func: FnBuiltin) -> Self {
         Self::Method(Shared::new(func))
     }
+    /// Create a new [`CallableFunction::Pure`] from a native Rust closure, with all arguments
+    /// passed by value.
+    #[inline(always)]
+    #[must_use]
+    pub fn from_native_fn(
+        func: impl Fn(NativeCallContext, &mut FnCallArgs) -> RhaiResult + SendSync + 'static,
+    ) -> Self {
+        Self::Pure(Shared::new(func))
+    }
+    /// Create a new [`CallableFunction::Method`] from a native Rust closure, with the first
+    /// argument passed by reference and the rest passed by value.
+    #[inline(always)]
+    #[must_use]
+    pub fn from_native_method(
+        func: impl Fn(NativeCallContext, &mut FnCallArgs) -> RhaiResult + SendSync + 'static,
+    ) -> Self {
+        Self::Method(Shared::new(func))
+    }
     /// Create a new [`CallableFunction::Plugin`].
     #[inline(always)]
     #[must_use]
@@ -9,10 +9,13 @@ pub mod hashing;
 pub mod native;
 pub mod plugin;
 pub mod register;
+pub mod replay;
 pub mod script;
 
 pub use args::FuncArgs;
-pub use builtin::{get_builtin_binary_op_fn, get_builtin_op_assignment_fn};
+pub use builtin::{
+    get_builtin_binary_op_fn, get_builtin_op_assignment_fn, get_builtin_unary_op_fn,
+};
 #[cfg(not(feature = "no_module"))]
 pub use call::gen_qualified_fn_call_signature;
 pub use call::{gen_fn_call_signature, FnCallArgs};
@@ -25,7 +28,9 @@ pub use hashing::{
 };
 pub use native::{
     locked_read, locked_write, shared_get_mut, shared_make_mut, shared_take, shared_take_or_clone,
-    shared_try_take, FnAny, FnPlugin, IteratorFn, Locked, NativeCallContext, SendSync, Shared,
+    shared_try_take, FnAny, FnPlugin, IndexedIteratorFn, IteratorFn, Locked, NativeCallContext,
+    SendSync, Shared,
 };
 pub use plugin::PluginFunction;
 pub use register::RegisterNativeFunction;
+pub use replay::{NativeCallLog, NativeCallRecord};
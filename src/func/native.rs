@@ -1,6 +1,7 @@
 //! Module defining interfaces to native-Rust functions.
 
 use super::call::FnCallArgs;
+use super::CallableFunction;
 use crate::ast::FnCallHashes;
 use crate::eval::{Caches, GlobalRuntimeState};
 use crate::plugin::PluginFunction;
@@ -208,18 +209,54 @@ impl<'a> NativeCallContext<'a> {
     pub const fn call_level(&self) -> usize {
         self.level
     }
+    /// Number of further nesting levels of function calls allowed before the maximum call stack
+    /// depth (set via [`Engine::set_max_call_levels`]) is exceeded.
+    ///
+    /// When this is zero, calling a script-defined function raises
+    /// [`ErrorStackOverflow`][crate::EvalAltResult::ErrorStackOverflow].
+    ///
+    /// Not available under `unchecked` or `no_function`.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub const fn remaining_call_depth(&self) -> usize {
+        self.engine.max_call_levels().saturating_sub(self.level)
+    }
     /// The current source.
     #[inline(always)]
     #[must_use]
     pub const fn source(&self) -> Option<&str> {
         self.source
     }
+    /// Active calls to script-defined functions, outermost first.
+    ///
+    /// Empty if this [`NativeCallContext`] is not part of an evaluation, or if call stack tracking
+    /// is not turned on via [`Engine::set_track_call_stack`].
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline]
+    #[must_use]
+    pub fn call_stack(&self) -> &[crate::CallFrame] {
+        self.global.map_or(&[][..], |g| &g.call_stack[..])
+    }
     /// Custom state kept in a [`Dynamic`].
     #[inline(always)]
     #[must_use]
     pub fn tag(&self) -> Option<&Dynamic> {
         self.global.as_ref().map(|g| &g.tag)
     }
+    /// Custom state of a specific type, passed in via
+    /// [`Engine::eval_with_context_data`] or set as the [default tag][Engine::set_default_tag].
+    ///
+    /// Returns [`None`] if there is no custom state of this type, or if this
+    /// [`NativeCallContext`] is not part of an evaluation.
+    #[inline]
+    #[must_use]
+    pub fn data<T: Variant + Clone>(&self) -> Option<&T> {
+        self.global.and_then(|g| g.context_data::<T>())
+    }
     /// Get an iterator over the current set of modules imported via `import` statements
     /// in reverse order.
     ///
@@ -248,6 +285,21 @@ impl<'a> NativeCallContext<'a> {
     pub const fn global_runtime_state(&self) -> Option<&GlobalRuntimeState> {
         self.global
     }
+    /// The current [`GlobalRuntimeState`], if any.
+    #[cfg(not(feature = "internals"))]
+    #[inline(always)]
+    #[must_use]
+    pub(crate) const fn global_runtime_state(&self) -> Option<&GlobalRuntimeState> {
+        self.global
+    }
+    /// A read-only view of the current global runtime state.
+    ///
+    /// Returns [`None`] if this [`NativeCallContext`] is not part of an evaluation.
+    #[inline]
+    #[must_use]
+    pub fn global_state(&self) -> Option<crate::GlobalStateView> {
+        self.global.map(Into::into)
+    }
     /// Get an iterator over the namespaces containing definitions of all script-defined functions
     /// in reverse order (i.e. parent namespaces are iterated after child namespaces).
     #[inline]
@@ -339,6 +391,7 @@ impl<'a> NativeCallContext<'a> {
                 args,
                 is_ref_mut,
                 is_method_call,
+                0,
                 Position::NONE,
                 self.level + 1,
             )
@@ -432,6 +485,14 @@ pub type IteratorFn = dyn Fn(Dynamic) -> Box<dyn Iterator<Item = RhaiResultOf<Dy
 pub type IteratorFn =
     dyn Fn(Dynamic) -> Box<dyn Iterator<Item = RhaiResultOf<Dynamic>>> + Send + Sync;
 
+/// Function that gets an iterator of index/value pairs from a type.
+#[cfg(not(feature = "sync"))]
+pub type IndexedIteratorFn = dyn Fn(Dynamic) -> Box<dyn Iterator<Item = (Dynamic, Dynamic)>>;
+/// Function that gets an iterator of index/value pairs from a type.
+#[cfg(feature = "sync")]
+pub type IndexedIteratorFn =
+    dyn Fn(Dynamic) -> Box<dyn Iterator<Item = (Dynamic, Dynamic)>> + Send + Sync;
+
 #[cfg(not(feature = "sync"))]
 pub type FnPlugin = dyn PluginFunction;
 #[cfg(feature = "sync")]
@@ -482,3 +543,85 @@ pub type OnDefVarCallback = dyn Fn(bool, VarDefInfo, EvalContext) -> RhaiResultO
 #[cfg(feature = "sync")]
 pub type OnDefVarCallback =
     dyn Fn(bool, VarDefInfo, EvalContext) -> RhaiResultOf<bool> + Send + Sync;
+
+/// Callback function for tracing operator evaluations.
+#[cfg(not(feature = "sync"))]
+pub type OnOperatorCallback = dyn Fn(&str, &[&str], &Dynamic, Position);
+/// Callback function for tracing operator evaluations.
+#[cfg(feature = "sync")]
+pub type OnOperatorCallback = dyn Fn(&str, &[&str], &Dynamic, Position) + Send + Sync;
+
+/// Callback function for resolving missing functions.
+#[cfg(not(feature = "sync"))]
+pub type OnMissingFnCallback =
+    dyn Fn(&str, &[&str], Position) -> RhaiResultOf<Option<CallableFunction>>;
+/// Callback function for resolving missing functions.
+#[cfg(feature = "sync")]
+pub type OnMissingFnCallback =
+    dyn Fn(&str, &[&str], Position) -> RhaiResultOf<Option<CallableFunction>> + Send + Sync;
+
+/// Callback function for attempts to assign to read-only targets.
+#[cfg(not(feature = "sync"))]
+pub type OnInvalidAssignmentCallback = dyn Fn(&str, &Dynamic, Position) -> RhaiResultOf<bool>;
+/// Callback function for attempts to assign to read-only targets.
+#[cfg(feature = "sync")]
+pub type OnInvalidAssignmentCallback =
+    dyn Fn(&str, &Dynamic, Position) -> RhaiResultOf<bool> + Send + Sync;
+
+/// Callback function for handling unknown properties of a custom type.
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "sync"))]
+pub type OnUnknownPropertyCallback = dyn Fn(&mut Dynamic, &str) -> RhaiResult;
+/// Callback function for handling unknown properties of a custom type.
+#[cfg(not(feature = "no_object"))]
+#[cfg(feature = "sync")]
+pub type OnUnknownPropertyCallback = dyn Fn(&mut Dynamic, &str) -> RhaiResult + Send + Sync;
+
+/// Callback function for handling unknown methods of a custom type.
+#[cfg(not(feature = "sync"))]
+pub type OnUnknownMethodCallback = dyn Fn(&mut Dynamic, &str, &mut FnCallArgs) -> RhaiResult;
+/// Callback function for handling unknown methods of a custom type.
+#[cfg(feature = "sync")]
+pub type OnUnknownMethodCallback =
+    dyn Fn(&mut Dynamic, &str, &mut FnCallArgs) -> RhaiResult + Send + Sync;
+
+/// Callback function for constructing a lazily-registered static module.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "sync"))]
+pub type OnModuleInitCallback = dyn Fn() -> crate::Module;
+/// Callback function for constructing a lazily-registered static module.
+#[cfg(not(feature = "no_module"))]
+#[cfg(feature = "sync")]
+pub type OnModuleInitCallback = dyn Fn() -> crate::Module + Send + Sync;
+
+/// Callback function for notification of an invalidated module.
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "sync"))]
+pub type OnModuleInvalidatedCallback = dyn Fn(&str);
+/// Callback function for notification of an invalidated module.
+#[cfg(not(feature = "no_module"))]
+#[cfg(feature = "sync")]
+pub type OnModuleInvalidatedCallback = dyn Fn(&str) + Send + Sync;
+
+/// Callback function for progress reporting with information at a sampling interval.
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "sync"))]
+pub type OnProgressSampledCallback = dyn Fn(&crate::ProgressInfo) -> Option<Dynamic>;
+/// Callback function for progress reporting with information at a sampling interval.
+#[cfg(not(feature = "unchecked"))]
+#[cfg(feature = "sync")]
+pub type OnProgressSampledCallback = dyn Fn(&crate::ProgressInfo) -> Option<Dynamic> + Send + Sync;
+
+/// Callback function for the end of an evaluation run.
+#[cfg(not(feature = "sync"))]
+pub type OnEvalEndCallback = dyn Fn(crate::GlobalStateView);
+/// Callback function for the end of an evaluation run.
+#[cfg(feature = "sync")]
+pub type OnEvalEndCallback = dyn Fn(crate::GlobalStateView) + Send + Sync;
+
+/// Callback function for parse warnings.
+#[cfg(not(feature = "sync"))]
+pub type OnParseWarningCallback = dyn Fn(crate::ParseWarning, Position);
+/// Callback function for parse warnings.
+#[cfg(feature = "sync")]
+pub type OnParseWarningCallback = dyn Fn(crate::ParseWarning, Position) + Send + Sync;
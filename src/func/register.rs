@@ -6,7 +6,7 @@ use super::call::FnCallArgs;
 use super::callable_function::CallableFunction;
 use super::native::{SendSync, Shared};
 use crate::types::dynamic::{DynamicWriteLock, Variant};
-use crate::{reify, Dynamic, NativeCallContext, RhaiResultOf};
+use crate::{reify, Dynamic, ImmutableString, NativeCallContext, RhaiResultOf};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{any::TypeId, mem};
@@ -55,11 +55,53 @@ pub fn by_value<T: Variant + Clone>(data: &mut Dynamic) -> T {
         return reify!(mem::take(data).into_string().expect("`ImmutableString`") => T);
     }
 
+    // If T is a reference to a value held by the argument, borrow it instead of taking it.
+    // SAFETY: In each case, we already checked that `T` is the reference type, so it is safe to
+    //         cast here.  The argument outlives the function call, just like `&str` above.
+    if TypeId::of::<T>() == TypeId::of::<&ImmutableString>() {
+        return unsafe { borrow_as::<ImmutableString, T>(data) };
+    }
+    #[cfg(not(feature = "no_index"))]
+    if TypeId::of::<T>() == TypeId::of::<&crate::Array>() {
+        return unsafe { borrow_as::<crate::Array, T>(data) };
+    }
+    #[cfg(not(feature = "no_index"))]
+    if TypeId::of::<T>() == TypeId::of::<&[Dynamic]>() {
+        let slice = unsafe { borrow_as::<crate::Array, &crate::Array>(data) }.as_slice();
+        return unsafe { mem::transmute_copy::<_, T>(&slice) };
+    }
+    #[cfg(not(feature = "no_index"))]
+    if TypeId::of::<T>() == TypeId::of::<&crate::Blob>() {
+        return unsafe { borrow_as::<crate::Blob, T>(data) };
+    }
+    #[cfg(not(feature = "no_index"))]
+    if TypeId::of::<T>() == TypeId::of::<&[u8]>() {
+        let slice = unsafe { borrow_as::<crate::Blob, &crate::Blob>(data) }.as_slice();
+        return unsafe { mem::transmute_copy::<_, T>(&slice) };
+    }
+    #[cfg(not(feature = "no_object"))]
+    if TypeId::of::<T>() == TypeId::of::<&crate::Map>() {
+        return unsafe { borrow_as::<crate::Map, T>(data) };
+    }
+
     // We consume the argument and then replace it with () - the argument is not supposed to be used again.
     // This way, we avoid having to clone the argument again, because it is already a clone when passed here.
     mem::take(data).cast::<T>()
 }
 
+/// Borrow the value of type `U` held by an argument, as `T` (which must be `&U`).
+///
+/// # Safety
+///
+/// `T` must be `&U`, and the argument must outlive the returned reference.
+#[inline(always)]
+#[must_use]
+unsafe fn borrow_as<U: Variant + Clone, T>(data: &mut Dynamic) -> T {
+    data.flatten_in_place();
+    let value: &U = data.downcast_ref::<U>().expect("checked");
+    mem::transmute_copy::<&U, T>(&value)
+}
+
 /// Trait to register custom Rust functions.
 ///
 /// # Type Parameters
@@ -101,17 +143,26 @@ macro_rules! check_constant {
             if args_len > 0 && $args[0].is_read_only() {
                 let deny = match $ctx.fn_name() {
                     #[cfg(not(feature = "no_object"))]
-                    f if args_len == 2 && f.starts_with(crate::engine::FN_SET) => true,
+                    f if args_len == 2 && f.starts_with(crate::engine::FN_SET) => {
+                        Some(&f[crate::engine::FN_SET.len()..])
+                    }
                     #[cfg(not(feature = "no_index"))]
-                    crate::engine::FN_IDX_SET if args_len == 3 => true,
-                    _ => false,
+                    crate::engine::FN_IDX_SET if args_len == 3 => Some(""),
+                    _ => None,
                 };
-                if deny {
-                    return Err(crate::ERR::ErrorAssignmentToConstant(
-                        String::new(),
-                        crate::Position::NONE,
-                    )
-                    .into());
+                if let Some(name) = deny {
+                    let value = &*$args[args_len - 1];
+
+                    if !$ctx
+                        .engine()
+                        .allow_invalid_assignment(name, value, $ctx.position())?
+                    {
+                        return Err(crate::ERR::ErrorAssignmentToConstant(
+                            String::new(),
+                            crate::Position::NONE,
+                        )
+                        .into());
+                    }
                 }
             }
         }
@@ -0,0 +1,244 @@
+//! Module implementing the recording and replaying of native function call results.
+
+use super::call::FnCallArgs;
+use super::native::{locked_read, locked_write, Locked, Shared};
+use crate::eval::GlobalRuntimeState;
+use crate::types::dynamic::Variant;
+use crate::{Dynamic, Engine, Identifier, RhaiResult, RhaiResultOf, Scope, AST, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// The recorded result of a single native function call.
+#[derive(Debug)]
+pub struct NativeCallRecord {
+    /// Name of the function.
+    pub name: Identifier,
+    /// Call signature hash of the function, used to detect divergence during replay.
+    pub hash: u64,
+    /// Result of the call, including the original error raised.
+    ///
+    /// A [system error][ERR::ErrorSystem] is recorded with its message only, as the wrapped error
+    /// cannot be copied.
+    pub result: RhaiResult,
+    /// Value of the first argument after the call, if it is passed by reference.
+    pub this: Option<Dynamic>,
+    /// Number of native function calls nested within this call, recorded right after it.
+    pub num_nested: usize,
+}
+
+impl Clone for NativeCallRecord {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            hash: self.hash,
+            result: match self.result {
+                Ok(ref value) => Ok(value.clone()),
+                Err(ref err) => Err(err.copy().into()),
+            },
+            this: self.this.clone(),
+            num_nested: self.num_nested,
+        }
+    }
+}
+
+/// A log of the results of all native function calls made during evaluation.
+///
+/// When replaying, native functions are not called; instead, their results (together with any
+/// changes to the first argument passed by reference) are taken in order from the log, so that an
+/// evaluation can be reproduced exactly without the original host environment.
+#[derive(Debug, Clone, Default)]
+pub struct NativeCallLog {
+    /// Recorded native function calls.
+    records: Vec<NativeCallRecord>,
+    /// Position of the next record to replay, or [`None`] if recording.
+    replay_pos: Option<usize>,
+}
+
+impl NativeCallLog {
+    /// Create a new, empty [`NativeCallLog`] for recording.
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Create a [`NativeCallLog`] for replaying a list of recorded native function calls.
+    #[inline(always)]
+    #[must_use]
+    pub fn from_records(records: impl IntoIterator<Item = NativeCallRecord>) -> Self {
+        Self {
+            records: records.into_iter().collect(),
+            replay_pos: Some(0),
+        }
+    }
+    /// Get all the recorded native function calls.
+    #[inline(always)]
+    #[must_use]
+    pub fn records(&self) -> &[NativeCallRecord] {
+        &self.records
+    }
+    /// Get the number of recorded native function calls.
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    /// Is this [`NativeCallLog`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    /// Is this [`NativeCallLog`] being replayed?
+    #[inline(always)]
+    #[must_use]
+    pub const fn is_replaying(&self) -> bool {
+        self.replay_pos.is_some()
+    }
+    /// Get the number of native function calls already replayed.
+    #[inline(always)]
+    #[must_use]
+    pub fn num_replayed(&self) -> usize {
+        self.replay_pos.unwrap_or(0)
+    }
+    /// Rewind this [`NativeCallLog`] to replay from the beginning.
+    #[inline(always)]
+    pub fn rewind(&mut self) {
+        self.replay_pos = Some(0);
+    }
+}
+
+impl Engine {
+    /// Evaluate an [`AST`] with own scope, recording the results of all native function calls
+    /// into a [`NativeCallLog`], or replaying them from it.
+    ///
+    /// A new [`NativeCallLog`] records.  One created via [`NativeCallLog::from_records`] is
+    /// replayed: native functions are then no longer called; instead, each call takes the next
+    /// recorded result in order.  An error is raised if a call does not match the recorded call
+    /// signature or if the log is exhausted.
+    ///
+    /// The log only applies to this evaluation, so evaluations running concurrently on the same
+    /// [`Engine`] each keep their own.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, NativeCallLog, Scope};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.register_fn("sensor", || 42_i64);
+    ///
+    /// let ast = engine.compile("sensor()")?;
+    ///
+    /// let mut log = NativeCallLog::new();
+    /// let result = engine.eval_ast_with_native_call_log::<i64>(&mut log, &mut Scope::new(), &ast)?;
+    /// assert_eq!(result, 42);
+    ///
+    /// // Later, in a different environment...
+    /// engine.register_fn("sensor", || 0_i64);
+    ///
+    /// let mut log = NativeCallLog::from_records(log.records().iter().cloned());
+    /// let result = engine.eval_ast_with_native_call_log::<i64>(&mut log, &mut Scope::new(), &ast)?;
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn eval_ast_with_native_call_log<T: Variant + Clone>(
+        &self,
+        log: &mut NativeCallLog,
+        scope: &mut Scope,
+        ast: &AST,
+    ) -> RhaiResultOf<T> {
+        let shared_log = Shared::new(Locked::new(std::mem::take(log)));
+
+        let global = &mut GlobalRuntimeState::new(self);
+        global.native_call_log = Some(shared_log.clone());
+
+        let result = self.eval_ast_typed_with_global(scope, global, None, ast);
+
+        *log = std::mem::take(&mut *locked_write(&shared_log));
+
+        result?.map_err(|value| self.make_output_type_error::<T>(&value))
+    }
+    /// Run a native function call, recording its result into (or replaying it from) the
+    /// [`NativeCallLog`] of the evaluation, if any.
+    pub(crate) fn run_native_fn_with_log(
+        &self,
+        global: &GlobalRuntimeState,
+        name: &str,
+        hash: u64,
+        args: &mut FnCallArgs,
+        is_ref_mut: bool,
+        func: impl FnOnce(&mut FnCallArgs) -> RhaiResult,
+    ) -> RhaiResult {
+        let log = match global.native_call_log {
+            Some(ref log) => log,
+            None => return func(args),
+        };
+
+        // Replay the next recorded call
+        let replay_pos = locked_read(log).replay_pos;
+
+        if let Some(index) = replay_pos {
+            let log = &mut *locked_write(log);
+
+            let record = match log.records.get(index) {
+                Some(record) if record.hash == hash => record,
+                r => {
+                    let msg = match r {
+                        Some(record) => {
+                            format!("expecting call to '{}', not '{name}'", record.name)
+                        }
+                        None => format!("no recorded result for call to '{name}'"),
+                    };
+                    return Err(ERR::ErrorSystem("Replay diverged".into(), msg.into()).into());
+                }
+            };
+
+            // Skip over calls nested within this one, which are not made during replay
+            log.replay_pos = Some(index + 1 + record.num_nested);
+
+            if let (Some(this), Some(arg)) = (record.this.as_ref(), args.get_mut(0)) {
+                **arg = this.clone();
+            }
+
+            return match record.result {
+                Ok(ref value) => Ok(value.clone()),
+                Err(ref err) => Err(err.copy().into()),
+            };
+        }
+
+        // Reserve a record before the call, so nested calls are recorded after it
+        let index = {
+            let log = &mut *locked_write(log);
+            log.records.push(NativeCallRecord {
+                name: name.into(),
+                hash,
+                result: Ok(Dynamic::UNIT),
+                this: None,
+                num_nested: 0,
+            });
+            log.records.len() - 1
+        };
+
+        let result = func(args);
+
+        let log = &mut *locked_write(log);
+        let num_nested = log.records.len() - index - 1;
+        let record = &mut log.records[index];
+
+        record.num_nested = num_nested;
+        record.result = match result {
+            Ok(ref value) => Ok(value.flatten_clone()),
+            Err(ref err) => Err(err.copy().into()),
+        };
+        record.this = match args.first() {
+            Some(arg) if is_ref_mut => Some(arg.flatten_clone()),
+            _ => None,
+        };
+
+        result
+    }
+}
@@ -2,12 +2,99 @@
 #![cfg(not(feature = "no_function"))]
 
 use super::call::FnCallArgs;
-use crate::ast::ScriptFnDef;
-use crate::eval::{Caches, GlobalRuntimeState};
-use crate::{Dynamic, Engine, Module, Position, RhaiError, RhaiResult, Scope, ERR};
-use std::mem;
+use crate::ast::{Expr, FnCallExpr, ScriptFnDef, Stmt};
+use crate::eval::{Caches, CallFrame, GlobalRuntimeState};
+use crate::{
+    Dynamic, Engine, ImmutableString, Module, Position, RhaiError, RhaiResult, RhaiResultOf, Scope,
+    StaticVec, ERR,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
+use std::{mem, ptr};
+
+/// Calls of a script-defined function to itself in tail position, which re-use its stack frame
+/// when [tail-call optimization][Engine::set_optimize_tail_calls] is enabled.
+#[derive(Debug, Clone)]
+pub(crate) struct TailCall {
+    /// Function call level of the function.
+    level: usize,
+    /// Name of the function.
+    name: ImmutableString,
+    /// Number of parameters of the function.
+    num_params: usize,
+    /// Addresses of the calls at the end of the function body.
+    tail_exprs: StaticVec<usize>,
+    /// Argument values of the pending tail call, if any.
+    args: Option<StaticVec<Dynamic>>,
+}
+
+impl TailCall {
+    /// Is a function call a call to the function itself?
+    #[must_use]
+    fn is_self_call(&self, x: &FnCallExpr, level: usize) -> bool {
+        #[cfg(not(feature = "no_module"))]
+        if !x.namespace.is_empty() {
+            return false;
+        }
+
+        level == self.level
+            && !x.capture_parent_scope
+            && !x.is_native_operator
+            && x.args.len() == self.num_params
+            && x.name == self.name
+    }
+    /// Is a function call in a `return` statement a tail call?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_tail_return(&self, x: &FnCallExpr, level: usize) -> bool {
+        self.is_self_call(x, level)
+    }
+    /// Is a function call at the end of the function body a tail call?
+    #[inline]
+    #[must_use]
+    pub fn is_tail_expr(&self, x: &FnCallExpr, level: usize) -> bool {
+        self.tail_exprs.contains(&(x as *const FnCallExpr as usize)) && self.is_self_call(x, level)
+    }
+    /// Is the call at an address at the end of the function body a tail call?
+    ///
+    /// Calls are only recorded at their addresses if they are calls of the function to itself.
+    #[cfg(feature = "bytecode")]
+    #[inline(always)]
+    #[must_use]
+    pub fn is_tail_addr(&self, addr: usize, level: usize) -> bool {
+        level == self.level && self.tail_exprs.contains(&addr)
+    }
+    /// Set the argument values of the pending tail call.
+    #[cfg(feature = "bytecode")]
+    #[inline(always)]
+    pub fn set_args(&mut self, args: StaticVec<Dynamic>) {
+        self.args = Some(args);
+    }
+    /// Find calls of a function to itself at the end of a block of statements.
+    fn collect_tail_exprs(&mut self, statements: &[Stmt]) {
+        let x = match statements.last() {
+            Some(Stmt::FnCall(x, ..)) => &**x,
+            Some(Stmt::Expr(expr)) => match **expr {
+                Expr::FnCall(ref x, ..) => &**x,
+                _ => return,
+            },
+            Some(Stmt::If(x, ..)) => {
+                self.collect_tail_exprs(x.1.statements());
+                self.collect_tail_exprs(x.2.statements());
+                return;
+            }
+            Some(Stmt::Block(block)) => {
+                self.collect_tail_exprs(block.statements());
+                return;
+            }
+            _ => return,
+        };
+
+        if self.is_self_call(x, self.level) {
+            self.tail_exprs.push(x as *const FnCallExpr as usize);
+        }
+    }
+}
 
 impl Engine {
     /// # Main Entry-Point
@@ -62,13 +149,30 @@ impl Engine {
 
         assert!(fn_def.params.len() == args.len());
 
+        // Call the hot-patched replacement of the function, if any
+        let patched = lib
+            .iter()
+            .filter_map(|m| m.fn_patches.as_ref())
+            .find_map(|patches| patches.get(fn_def));
+        let fn_def = patched.as_deref().unwrap_or(fn_def);
+
         #[cfg(not(feature = "unchecked"))]
-        self.inc_operations(&mut global.num_operations, pos)?;
+        self.inc_operations(global, pos)?;
+        #[cfg(not(feature = "unchecked"))]
+        self.consume_fuel(global, self.fuel_costs().function_call, pos)?;
 
         // Check for stack overflow
         #[cfg(not(feature = "unchecked"))]
         if level > self.max_call_levels() {
-            return Err(ERR::ErrorStackOverflow(pos).into());
+            return Err(ERR::ErrorStackOverflow(Default::default(), pos).into());
+        }
+        #[cfg(not(feature = "unchecked"))]
+        self.check_nested_call_level(global, level, pos)?;
+
+        // Generator functions are evaluated lazily
+        if fn_def.is_generator {
+            let gen = self.make_generator(global, lib, this_ptr, fn_def, args);
+            return Ok(Dynamic::from(gen));
         }
 
         #[cfg(feature = "debugging")]
@@ -87,6 +191,12 @@ impl Engine {
         #[cfg(feature = "debugging")]
         let orig_call_stack_len = global.debugger.call_stack().len();
 
+        // A function called with a new scope is traced separately from its caller
+        #[cfg(feature = "debugging")]
+        if orig_scope_len == 0 {
+            global.debugger.trace_enter();
+        }
+
         // Put arguments into scope as variables
         scope.extend(fn_def.params.iter().cloned().zip(args.iter_mut().map(|v| {
             // Actually consume the arguments instead of cloning them
@@ -144,9 +254,78 @@ impl Engine {
             self.run_debugger(scope, global, lib, this_ptr, &node, level)?;
         }
 
+        // Push a new call frame
+        let orig_call_frames_len = global.call_stack.len();
+
+        if self.track_call_stack() {
+            global.call_stack.push(CallFrame {
+                fn_name: fn_def.name.clone(),
+                source: global.source.clone(),
+                pos,
+            });
+        }
+
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(
+            target: "rhai",
+            "call",
+            function = %fn_def.name,
+            source = %global.source
+        )
+        .entered();
+        #[cfg(feature = "tracing")]
+        let start = crate::Instant::now();
+
+        #[cfg(feature = "profiling")]
+        if let Some(ref mut profiler) = global.profiler {
+            profiler.enter_fn(fn_def.name.clone(), global.num_operations);
+        }
+
+        // Calls of the function to itself in tail position re-use this stack frame
+        let tail_call = if self.optimize_tail_calls() && rewind_scope && this_ptr.is_none() {
+            Self::make_tail_call(lib, fn_def, level)
+        } else {
+            None
+        };
+        let orig_tail_call = tail_call.map(|t| global.tail_call.replace(t));
+        #[cfg(not(feature = "no_module"))]
+        let body_imports_len = global.num_imports();
+
+        // Run the bytecode compiled from the function body, if any
+        #[cfg(feature = "bytecode")]
+        let compiled_fns = global.compiled_fns.clone();
+        #[cfg(feature = "bytecode")]
+        let code = compiled_fns
+            .as_ref()
+            .filter(|_| rewind_scope)
+            .and_then(|fns| fns.get(&(fn_def as *const ScriptFnDef as usize)));
+
         // Evaluate the function
-        let mut _result = self
-            .eval_stmt_block(
+        let result = loop {
+            #[cfg(feature = "bytecode")]
+            let result = match code {
+                Some(code) => {
+                    let ctx = crate::eval::RunContext {
+                        global,
+                        caches,
+                        lib,
+                        level,
+                    };
+                    self.run_bytecode(scope, this_ptr, ctx, code)
+                }
+                None => self.eval_stmt_block(
+                    scope,
+                    global,
+                    caches,
+                    lib,
+                    this_ptr,
+                    &fn_def.body,
+                    rewind_scope,
+                    level,
+                ),
+            };
+            #[cfg(not(feature = "bytecode"))]
+            let result = self.eval_stmt_block(
                 scope,
                 global,
                 caches,
@@ -155,27 +334,83 @@ impl Engine {
                 &fn_def.body,
                 rewind_scope,
                 level,
-            )
-            .or_else(|err| match *err {
-                // Convert return statement to return value
-                ERR::Return(x, ..) => Ok(x),
-                // Error in sub function call
-                ERR::ErrorInFunctionCall(name, src, err, ..) => {
-                    let fn_name = if src.is_empty() {
-                        format!("{name} < {}", fn_def.name)
-                    } else {
-                        format!("{name} @ '{src}' < {}", fn_def.name)
-                    };
-                    make_error(fn_name, fn_def, global, err, pos)
-                }
-                // System errors are passed straight-through
-                mut err if err.is_system_exception() => {
-                    err.set_position(pos);
-                    Err(err.into())
+            );
+
+            let args = match result {
+                Err(ref err) if orig_tail_call.is_some() && matches!(**err, ERR::Return(..)) => {
+                    match global.tail_call.as_mut().and_then(|t| t.args.take()) {
+                        Some(args) => args,
+                        None => break result,
+                    }
                 }
-                // Other errors are wrapped in `ErrorInFunctionCall`
-                _ => make_error(fn_def.name.to_string(), fn_def, global, err, pos),
-            });
+                _ => break result,
+            };
+
+            // Re-start the function with the new arguments
+            scope.rewind(orig_scope_len);
+            #[cfg(feature = "debugging")]
+            global.debugger.trace_sync(scope);
+            #[cfg(not(feature = "no_module"))]
+            global.truncate_imports(body_imports_len);
+
+            #[cfg(not(feature = "unchecked"))]
+            if let Err(err) = self
+                .inc_operations(global, pos)
+                .and_then(|_| self.consume_fuel(global, self.fuel_costs().function_call, pos))
+            {
+                break Err(err);
+            }
+
+            scope.extend(fn_def.params.iter().cloned().zip(args));
+        };
+        let mut _result = result.or_else(|err| match *err {
+            // Convert return statement to return value
+            ERR::Return(x, ..) => Ok(x),
+            // Error in sub function call
+            ERR::ErrorInFunctionCall(name, src, err, ..) => {
+                let fn_name = if src.is_empty() {
+                    format!("{name} < {}", fn_def.name)
+                } else {
+                    format!("{name} @ '{src}' < {}", fn_def.name)
+                };
+                make_error(fn_name, fn_def, global, err, pos)
+            }
+            // System errors are passed straight-through
+            mut err if err.is_system_exception() => {
+                #[cfg(not(feature = "unchecked"))]
+                Self::add_limit_context(&mut err, scope, &fn_def.name, Position::NONE);
+                err.set_position(pos);
+                Err(err.into())
+            }
+            // Other errors are wrapped in `ErrorInFunctionCall`
+            _ => make_error(fn_def.name.to_string(), fn_def, global, err, pos),
+        });
+
+        if let Some(tail_call) = orig_tail_call {
+            global.tail_call = tail_call;
+        }
+
+        global.call_stack.truncate(orig_call_frames_len);
+
+        #[cfg(feature = "profiling")]
+        if let Some(ref mut profiler) = global.profiler {
+            profiler.exit_fn(global.num_operations);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed = start.elapsed();
+
+            if elapsed >= self.slow_fn_threshold {
+                tracing::info!(
+                    target: "rhai",
+                    function = %fn_def.name,
+                    source = %global.source,
+                    duration_us = elapsed.as_micros() as u64,
+                    "slow function call"
+                );
+            }
+        }
 
         #[cfg(feature = "debugging")]
         {
@@ -208,6 +443,10 @@ impl Engine {
             // Remove arguments only, leaving new variables in the scope
             scope.remove_range(orig_scope_len, args.len());
         }
+        #[cfg(feature = "debugging")]
+        if orig_scope_len == 0 {
+            global.debugger.trace_exit();
+        }
         #[cfg(not(feature = "no_module"))]
         global.truncate_imports(orig_imports_len);
 
@@ -223,6 +462,70 @@ impl Engine {
         _result
     }
 
+    /// Find the calls of a script-defined function to itself in tail position, if any.
+    ///
+    /// Calls are only optimized when they resolve to the function itself, i.e. it is not
+    /// overridden by another script-defined function with the same name and arity.
+    #[must_use]
+    fn make_tail_call(
+        lib: &[&Module],
+        fn_def: &ScriptFnDef,
+        level: usize,
+    ) -> Option<Box<TailCall>> {
+        let num_params = fn_def.params.len();
+        let hash = crate::calc_fn_hash(fn_def.name.as_str(), num_params);
+
+        let target = lib
+            .iter()
+            .find_map(|m| m.get_fn(hash))
+            .and_then(|f| f.get_script_fn_def())?;
+
+        if !ptr::eq(&**target, fn_def) {
+            return None;
+        }
+
+        let mut tail_call = TailCall {
+            level,
+            name: fn_def.name.clone(),
+            num_params,
+            tail_exprs: StaticVec::new_const(),
+            args: None,
+        };
+
+        tail_call.collect_tail_exprs(fn_def.body.statements());
+
+        Some(tail_call.into())
+    }
+
+    /// Evaluate the arguments of a call of a script-defined function to itself in tail position,
+    /// then return from the function, which re-starts with them.
+    pub(crate) fn eval_tail_call(
+        &self,
+        scope: &mut Scope,
+        global: &mut GlobalRuntimeState,
+        caches: &mut Caches,
+        lib: &[&Module],
+        this_ptr: &mut Option<&mut Dynamic>,
+        expr: &FnCallExpr,
+        pos: Position,
+        level: usize,
+    ) -> RhaiResult {
+        let args = expr
+            .args
+            .iter()
+            .map(|arg| {
+                self.get_arg_value(scope, global, caches, lib, this_ptr, arg, level)
+                    .map(|(value, ..)| value.flatten())
+            })
+            .collect::<RhaiResultOf<_>>()?;
+
+        if let Some(ref mut tail_call) = global.tail_call {
+            tail_call.args = Some(args);
+        }
+
+        Err(ERR::Return(Dynamic::UNIT, pos).into())
+    }
+
     // Does a script-defined function exist?
     #[must_use]
     pub(crate) fn has_script_fn(
@@ -191,26 +191,84 @@ type ExclusiveRange = std::ops::Range<INT>;
 /// An inclusive integer range.
 type InclusiveRange = std::ops::RangeInclusive<INT>;
 
+pub use api::build_info::BuildInfo;
 #[allow(deprecated)]
 pub use api::build_type::{CustomType, TypeBuilder};
+#[cfg(not(feature = "no_function"))]
+pub use api::call_fn::EvalSession;
+pub use api::compile::CompileStats;
+#[cfg(feature = "coverage")]
+pub use api::coverage::{Coverage, CoverageItem, CoverageReport};
+#[cfg(feature = "async")]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_family = "wasm"))]
+pub use api::eval_async::{AsyncJob, EvalFuture};
+#[cfg(not(feature = "no_object"))]
+pub use api::eval_durable::{Checkpoint, CheckpointStore, Durable, MemoryCheckpointStore};
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_function"))]
+pub use api::eval_resumable::Resumable;
+#[cfg(not(feature = "unchecked"))]
+pub use api::events::ProgressInfo;
 #[cfg(not(feature = "no_std"))]
 #[cfg(not(target_family = "wasm"))]
 pub use api::files::{eval_file, run_file};
+#[cfg(feature = "gc")]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_std"))]
+pub use api::gc::GarbageStats;
+pub use api::interner::InternerStats;
+pub use api::metrics::MetricsSink;
+#[cfg(feature = "profiling")]
+pub use api::profiling::{FnProfile, Profile, StmtProfile};
+pub use api::snapshot::InterpreterState;
+#[cfg(feature = "serde")]
+pub use api::snapshot::Snapshot;
+pub use api::verify::{VerifyIssue, VerifyReport};
 pub use api::{eval::eval, events::VarDefInfo, run::run};
-pub use ast::{FnAccess, AST};
-pub use engine::{Engine, OP_CONTAINS, OP_EQUALS};
+#[cfg(not(feature = "no_function"))]
+pub use ast::FnVisibility;
+pub use ast::{FnAccess, NodeId, AST};
+pub use engine::{Engine, OP_CONTAINS, OP_EQUALS, OP_LESS_THAN};
+#[cfg(feature = "bytecode")]
+pub use eval::Bytecode;
+#[cfg(not(feature = "no_function"))]
+pub use eval::CallFrame;
 pub use eval::EvalContext;
-pub use func::{NativeCallContext, RegisterNativeFunction};
+#[cfg(not(feature = "no_function"))]
+pub use eval::Generator;
+pub use eval::GlobalStateView;
+#[cfg(not(feature = "no_function"))]
+pub use eval::Task;
+pub use func::{NativeCallContext, NativeCallLog, NativeCallRecord, RegisterNativeFunction};
 pub use module::{FnNamespace, Module};
-pub use tokenizer::Position;
+pub use tokenizer::{Position, Span};
 #[cfg(not(feature = "no_std"))]
 pub use types::Instant;
 pub use types::{
-    Dynamic, EvalAltResult, FnPtr, ImmutableString, LexError, ParseError, ParseErrorType, Scope,
+    Diagnostic, DiagnosticLabel, Dynamic, EvalAltResult, FnPtr, FrozenScope, ImmutableString,
+    LexError, LimitContext, ParseError, ParseErrorType, ParseWarning, Scope, Severity, Tuple,
+    TypedEvalError,
 };
 
+#[cfg(not(feature = "no_object"))]
+pub use types::ScopeMetadata;
+
+#[cfg(not(feature = "no_object"))]
+pub use types::{Proxy, ProxyHandler};
+
 #[cfg(not(feature = "no_custom_syntax"))]
-pub use api::custom_syntax::Expression;
+pub use types::StateMachine;
+
+#[cfg(not(feature = "unchecked"))]
+pub use api::limits::{FuelCosts, Limits};
+
+#[cfg(feature = "decimal")]
+#[cfg(not(feature = "no_float"))]
+pub use api::options::DecimalFloatPolicy;
+
+#[cfg(not(feature = "no_custom_syntax"))]
+pub use api::custom_syntax::{CustomOperatorKind, Expression};
 
 /// _(debugging)_ Module containing types for debugging.
 /// Exported under the `debugging` feature only.
@@ -218,7 +276,14 @@ pub use api::custom_syntax::Expression;
 pub mod debugger {
     #[cfg(not(feature = "no_function"))]
     pub use super::eval::CallStackFrame;
-    pub use super::eval::{BreakPoint, Debugger, DebuggerCommand, DebuggerEvent};
+    pub use super::eval::{
+        BreakPoint, Debugger, DebuggerCommand, DebuggerEvent, HitCondition, Watch,
+    };
+    pub use super::eval::{ScopeChange, Trace, TraceReplayer, TraceStep};
+
+    #[cfg(feature = "dap")]
+    #[cfg(not(feature = "no_position"))]
+    pub use super::eval::dap;
 }
 
 /// An identifier in Rhai. [`SmartString`](https://crates.io/crates/smartstring) is used because most
@@ -264,6 +329,19 @@ pub type Array = Vec<Dynamic>;
 #[cfg(not(feature = "no_index"))]
 pub type Blob = Vec<u8>;
 
+#[cfg(not(feature = "no_index"))]
+pub use types::Set;
+
+#[cfg(not(feature = "no_index"))]
+pub use types::SortedMap;
+
+#[cfg(not(feature = "no_index"))]
+pub use types::IntArray;
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_float"))]
+pub use types::FloatArray;
+
 /// A dictionary of [`Dynamic`] values with string keys.
 ///
 /// Not available under `no_object`.
@@ -273,6 +351,9 @@ pub type Blob = Vec<u8>;
 #[cfg(not(feature = "no_object"))]
 pub type Map = std::collections::BTreeMap<Identifier, Dynamic>;
 
+#[cfg(not(feature = "no_object"))]
+pub use types::OrderedMap;
+
 #[cfg(not(feature = "no_object"))]
 pub use api::json::format_map_as_json;
 
@@ -286,6 +367,9 @@ pub use module::resolvers as module_resolvers;
 #[cfg(feature = "serde")]
 pub mod serde;
 
+#[cfg(feature = "lsp")]
+pub mod lsp;
+
 #[cfg(not(feature = "no_optimize"))]
 pub use optimizer::OptimizationLevel;
 
@@ -303,10 +387,14 @@ pub use tokenizer::{get_next_token, parse_string_literal};
 
 #[cfg(feature = "internals")]
 pub use tokenizer::{
-    is_valid_function_name, is_valid_identifier, InputStream, MultiInputsStream, Span, Token,
+    is_valid_function_name, is_valid_identifier, InputChars, InputStream, MultiInputsStream, Token,
     TokenIterator, TokenizeState, TokenizerControl, TokenizerControlBlock,
 };
 
+#[cfg(feature = "internals")]
+#[cfg(not(feature = "no_std"))]
+pub use tokenizer::ReadChars;
+
 #[cfg(feature = "internals")]
 pub use types::StringsInterner;
 
@@ -316,7 +404,8 @@ pub use parser::ParseState;
 #[cfg(feature = "internals")]
 pub use ast::{
     ASTFlags, ASTNode, BinaryExpr, ConditionalExpr, Expr, FnCallExpr, FnCallHashes, Ident,
-    OpAssignment, RangeCase, ScriptFnDef, Stmt, StmtBlock, SwitchCasesCollection, TryCatchBlock,
+    OpAssignment, PatternCase, RangeCase, ScriptFnDef, Stmt, StmtBlock, SwitchCasesCollection,
+    SwitchPattern, TryCatchBlock,
 };
 
 #[cfg(feature = "internals")]
@@ -337,7 +426,9 @@ pub use ast::EncapsulatedEnviron;
 pub use ast::FloatWrapper;
 
 #[cfg(feature = "internals")]
-pub use eval::{Caches, FnResolutionCache, FnResolutionCacheEntry, GlobalRuntimeState};
+pub use eval::{
+    Caches, FnResolutionCache, FnResolutionCacheEntry, GlobalRuntimeState, ScratchArena,
+};
 
 #[cfg(feature = "internals")]
 #[cfg(feature = "metadata")]
@@ -0,0 +1,697 @@
+//! _(lsp)_ Support for language servers.
+//! Exported under the `lsp` feature only.
+//!
+//! [`analyze`] compiles a script without stopping at the first error and collects what a language
+//! server needs: the token stream with [spans][Span], the [diagnostics][Diagnostic], the
+//! [`AST`] and a table of the [symbols][Symbol] declared in each scope.  [`Analysis::completions`]
+//! then lists the candidates for completion at a [position][Position], drawing also on the
+//! functions registered into an [`Engine`].
+//!
+//! Not available under `no_position`.
+#![cfg(not(feature = "no_position"))]
+
+use crate::ast::{ASTFlags, ASTNode, Expr, Stmt};
+use crate::tokenizer::{is_valid_identifier, Token};
+use crate::{Diagnostic, Engine, ImmutableString, OptimizationLevel, Position, Scope, Span, AST};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Keywords offered for completion, if they are active in the [`Engine`].
+const KEYWORDS: &[&str] = &[
+    "let", "const", "if", "else", "switch", "do", "while", "until", "loop", "for", "in", "break",
+    "continue", "return", "throw", "try", "catch", "fn", "private", "import", "export", "as",
+    "yield", "true", "false",
+];
+
+/// Kind of a [`TokenInfo`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    /// Keyword, including custom keywords.
+    Keyword,
+    /// Identifier.
+    Identifier,
+    /// Operator or punctuation.
+    Symbol,
+    /// Number literal.
+    Number,
+    /// String literal, including segments of interpolated strings.
+    String,
+    /// Character literal.
+    Char,
+    /// Comment.
+    Comment,
+    /// Reserved keyword or symbol.
+    Reserved,
+    /// Invalid input.
+    Error,
+}
+
+/// A token in a script, together with its [`Span`].
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TokenInfo {
+    /// Kind of the token.
+    kind: TokenKind,
+    /// Text of the token (or the error message for [`TokenKind::Error`]).
+    text: String,
+    /// Span of the token.
+    span: Span,
+}
+
+impl TokenInfo {
+    /// Get the kind of the token.
+    #[inline(always)]
+    #[must_use]
+    pub const fn kind(&self) -> TokenKind {
+        self.kind
+    }
+    /// Get the text of the token.
+    ///
+    /// String literals are not included; the [span][TokenInfo::span] locates them in the script.
+    #[inline(always)]
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Get the [`Span`] of the token.
+    #[inline(always)]
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Kind of a [`Symbol`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SymbolKind {
+    /// Variable declared via `let`, or the variable of a `catch` block.
+    Variable,
+    /// Constant declared via `const`.
+    Constant,
+    /// Variable or counter of a `for` loop.
+    LoopVariable,
+    /// Parameter of a function.
+    Parameter,
+    /// Script-defined function.
+    Function,
+    /// Alias of an imported module.
+    Module,
+}
+
+/// A symbol declared in a script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Symbol {
+    /// Name of the symbol.
+    name: ImmutableString,
+    /// Kind of the symbol.
+    kind: SymbolKind,
+    /// Position where the symbol is declared.
+    pos: Position,
+    /// Extent of the scope of the symbol, or [`Span::NONE`] for the entire script.
+    scope: Span,
+}
+
+impl Symbol {
+    /// Get the name of the symbol.
+    #[inline(always)]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Get the kind of the symbol.
+    #[inline(always)]
+    #[must_use]
+    pub const fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+    /// Get the [position][Position] where the symbol is declared.
+    #[inline(always)]
+    #[must_use]
+    pub const fn position(&self) -> Position {
+        self.pos
+    }
+    /// Get the [`Span`] of the scope of the symbol, or [`Span::NONE`] for the entire script.
+    #[inline(always)]
+    #[must_use]
+    pub const fn scope(&self) -> Span {
+        self.scope
+    }
+    /// Is the symbol visible at a [position][Position]?
+    ///
+    /// Functions are visible throughout the script.  Other symbols are visible from their
+    /// declarations until the end of their scopes.
+    #[must_use]
+    pub fn is_visible_at(&self, pos: Position) -> bool {
+        if !self.scope.is_none() && !span_contains(self.scope, pos) {
+            return false;
+        }
+        match self.kind {
+            SymbolKind::Function | SymbolKind::Parameter => true,
+            _ => self.pos <= pos,
+        }
+    }
+}
+
+/// Kind of a [`Completion`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum CompletionKind {
+    /// Variable, loop variable or parameter.
+    Variable,
+    /// Constant.
+    Constant,
+    /// Script-defined or registered function.
+    Function,
+    /// Module.
+    Module,
+    /// Keyword.
+    Keyword,
+}
+
+/// A candidate for completion.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Completion {
+    /// Text to insert.
+    label: ImmutableString,
+    /// Kind of the candidate.
+    kind: CompletionKind,
+    /// Signature of a function, if any.
+    detail: Option<String>,
+    /// Doc-comments of a function, if any.
+    doc: Option<String>,
+}
+
+impl Completion {
+    /// Get the text to insert.
+    #[inline(always)]
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+    /// Get the kind of the candidate.
+    #[inline(always)]
+    #[must_use]
+    pub const fn kind(&self) -> CompletionKind {
+        self.kind
+    }
+    /// Get the signature of a function, if any.
+    #[inline(always)]
+    #[must_use]
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+    /// Get the doc-comments of a function, if any.
+    #[inline(always)]
+    #[must_use]
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+}
+
+/// Result of [analyzing][analyze] a script.
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    /// Tokens of the script.
+    tokens: Vec<TokenInfo>,
+    /// Diagnostics of the script.
+    diagnostics: Vec<Diagnostic>,
+    /// The [`AST`], containing only the statements without errors.
+    ast: Option<AST>,
+    /// Symbols declared in the script, in order of their positions.
+    symbols: Vec<Symbol>,
+}
+
+impl Analysis {
+    /// Get all the tokens of the script, including comments.
+    #[inline(always)]
+    #[must_use]
+    pub fn tokens(&self) -> &[TokenInfo] {
+        &self.tokens
+    }
+    /// Get all the [diagnostics][Diagnostic] of the script, in order of their positions.
+    #[inline(always)]
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+    /// Get the [`AST`] of the script, compiled without optimization.
+    ///
+    /// If the script has errors, the [`AST`] only contains the statements that are parsed
+    /// successfully.  It is [`None`] only if parsing cannot continue at all.
+    ///
+    /// Under the `internals` feature, use `AST::walk` to visit its nodes with their positions.
+    #[inline(always)]
+    #[must_use]
+    pub fn ast(&self) -> Option<&AST> {
+        self.ast.as_ref()
+    }
+    /// Get all the [symbols][Symbol] declared in the script, in order of their positions.
+    #[inline(always)]
+    #[must_use]
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+    /// Get the token at a [position][Position], if any.
+    #[must_use]
+    pub fn token_at(&self, pos: Position) -> Option<&TokenInfo> {
+        self.tokens
+            .iter()
+            .find(|t| t.span.start() <= pos && pos <= t.span.end())
+    }
+    /// Get an iterator over the [symbols][Symbol] visible at a [position][Position].
+    ///
+    /// Symbols shadowed by later symbols of the same name are skipped.
+    pub fn visible_symbols(&self, pos: Position) -> impl Iterator<Item = &Symbol> {
+        let visible: Vec<_> = self
+            .symbols
+            .iter()
+            .filter(|s| s.is_visible_at(pos))
+            .collect();
+
+        visible
+            .iter()
+            .enumerate()
+            .filter(|&(i, s)| !visible[i + 1..].iter().any(|t| t.name == s.name))
+            .map(|(.., &s)| s)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+    /// Find the [symbol][Symbol] referred to by the identifier at a [position][Position], if any.
+    #[must_use]
+    pub fn definition_at(&self, pos: Position) -> Option<&Symbol> {
+        let token = self
+            .token_at(pos)
+            .filter(|t| t.kind == TokenKind::Identifier)?;
+
+        self.visible_symbols(token.span.start())
+            .find(|s| s.name == token.text)
+    }
+    /// Get the candidates for completion at a [position][Position], sorted by label.
+    ///
+    /// If an identifier is being typed at the position, only candidates starting with it are
+    /// returned.  After a `.`, only functions are returned.
+    ///
+    /// Candidates include the [symbols][Symbol] visible at the position, the functions and
+    /// modules registered into the [`Engine`], and the active keywords.
+    #[must_use]
+    pub fn completions(&self, engine: &Engine, pos: Position) -> Vec<Completion> {
+        // Find the token just before the position
+        let index = self
+            .tokens
+            .iter()
+            .rposition(|t| t.span.start() < pos && t.kind != TokenKind::Comment);
+
+        let (prefix, before) = match index.map(|i| (&self.tokens[i], i)) {
+            Some((t, i)) if t.kind == TokenKind::Identifier && is_just_after(t.span, pos) => {
+                (&*t.text, i.checked_sub(1).map(|i| &self.tokens[i]))
+            }
+            Some((t, ..)) => ("", Some(t)),
+            None => ("", None),
+        };
+        let is_method = before.map_or(false, |t| t.text == ".");
+
+        let mut completions = Vec::new();
+
+        if !is_method {
+            // Script-defined functions are added below, with their signatures
+            for symbol in self
+                .visible_symbols(pos)
+                .filter(|s| s.kind != SymbolKind::Function)
+            {
+                let kind = match symbol.kind {
+                    SymbolKind::Constant => CompletionKind::Constant,
+                    SymbolKind::Module => CompletionKind::Module,
+                    _ => CompletionKind::Variable,
+                };
+                completions.push(Completion {
+                    label: symbol.name.clone(),
+                    kind,
+                    detail: None,
+                    doc: None,
+                });
+            }
+
+            for &keyword in KEYWORDS {
+                let active = !matches!(
+                    Token::lookup_from_syntax(keyword),
+                    None | Some(Token::Reserved(..))
+                ) && !engine.disabled_symbols.contains(keyword);
+
+                if active {
+                    completions.push(Completion {
+                        label: keyword.into(),
+                        kind: CompletionKind::Keyword,
+                        detail: None,
+                        doc: None,
+                    });
+                }
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            for name in engine
+                .global_sub_modules
+                .keys()
+                .chain(engine.lazy_sub_modules.keys())
+            {
+                completions.push(Completion {
+                    label: name.as_str().into(),
+                    kind: CompletionKind::Module,
+                    detail: None,
+                    doc: None,
+                });
+            }
+        }
+
+        // Script-defined functions
+        #[cfg(not(feature = "no_function"))]
+        if let Some(ref ast) = self.ast {
+            for f in ast.iter_functions() {
+                if !is_valid_identifier(f.name.chars()) {
+                    continue;
+                }
+                completions.push(Completion {
+                    label: f.name.into(),
+                    kind: CompletionKind::Function,
+                    detail: Some(f.to_string()),
+                    doc: Some(f.comments.join("\n")).filter(|s| !s.is_empty()),
+                });
+            }
+        }
+
+        // Registered functions
+        for f in engine.global_modules.iter().flat_map(|m| m.iter_fn()) {
+            if !is_valid_identifier(f.name.chars()) {
+                continue;
+            }
+            if completions.iter().any(|c| c.label == f.name.as_str()) {
+                continue;
+            }
+            completions.push(Completion {
+                label: f.name.as_str().into(),
+                kind: CompletionKind::Function,
+                detail: Some(f.gen_signature()),
+                doc: Some(f.comments.join("\n")).filter(|s| !s.is_empty()),
+            });
+        }
+
+        completions.retain(|c| c.label.starts_with(prefix));
+        completions.sort_by(|a, b| a.label.cmp(&b.label));
+        completions.dedup_by(|a, b| a.label == b.label);
+        completions
+    }
+}
+
+/// Analyze a script for a language server.
+///
+/// The script is compiled without optimization, recovering from errors (see
+/// [`Engine::compile_with_recovery`]), so that the [`Analysis`] is available even for scripts
+/// that are being edited.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Engine, Position};
+/// use rhai::lsp::{analyze, SymbolKind};
+///
+/// let engine = Engine::new();
+///
+/// let analysis = analyze(&engine, "
+///     fn add(x, y) { x + y }
+///     let total = add(1, 2);
+///     if total > 0 { let half = total / 2; }
+///     let z = tot +
+/// ");
+///
+/// assert_eq!(analysis.diagnostics().len(), 1);
+///
+/// let symbols: Vec<_> = analysis.symbols().iter().map(|s| (s.name(), s.kind())).collect();
+/// assert!(symbols.contains(&("add", SymbolKind::Function)));
+/// assert!(symbols.contains(&("x", SymbolKind::Parameter)));
+/// assert!(symbols.contains(&("half", SymbolKind::Variable)));
+///
+/// let completions = analysis.completions(&engine, Position::new(5, 16));
+/// assert_eq!(completions[0].label(), "total");
+/// ```
+#[must_use]
+pub fn analyze(engine: &Engine, script: impl AsRef<str>) -> Analysis {
+    let script = script.as_ref();
+    let tokens = tokenize(engine, script);
+
+    #[cfg(not(feature = "no_optimize"))]
+    let optimization_level = OptimizationLevel::None;
+    #[cfg(feature = "no_optimize")]
+    let optimization_level = OptimizationLevel::default();
+
+    let (ast, errors, warnings) =
+        engine.compile_collecting_errors(&Scope::new(), script, optimization_level);
+
+    let mut diagnostics: Vec<_> = errors
+        .iter()
+        .map(Diagnostic::from)
+        .chain(
+            warnings
+                .iter()
+                .map(|(warning, pos)| Diagnostic::from_warning(warning, *pos)),
+        )
+        .collect();
+    diagnostics.sort_by_key(|d| d.span().start());
+
+    let mut symbols = Vec::new();
+
+    if let Some(ref ast) = ast {
+        collect_symbols(ast, &tokens, &mut symbols);
+    }
+
+    symbols.sort_by_key(|s| s.pos);
+
+    Analysis {
+        tokens,
+        diagnostics,
+        ast,
+        symbols,
+    }
+}
+
+/// Tokenize a script, including comments.
+fn tokenize(engine: &Engine, script: &str) -> Vec<TokenInfo> {
+    let scripts = [script];
+    let (mut stream, ..) = engine.lex_raw(&scripts, engine.token_mapper.as_ref().map(<_>::as_ref));
+    stream.state.include_comments = true;
+
+    let mut tokens = Vec::new();
+
+    while let Some((token, pos)) = stream.next() {
+        let kind = match token {
+            Token::EOF => break,
+            Token::IntegerConstant(..) => TokenKind::Number,
+            #[cfg(not(feature = "no_float"))]
+            Token::FloatConstant(..) => TokenKind::Number,
+            #[cfg(feature = "decimal")]
+            Token::DecimalConstant(..) => TokenKind::Number,
+            Token::StringConstant(..) | Token::InterpolatedString(..) => TokenKind::String,
+            Token::CharConstant(..) => TokenKind::Char,
+            Token::Identifier(..) => TokenKind::Identifier,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Custom(..) => TokenKind::Keyword,
+            Token::Comment(..) => TokenKind::Comment,
+            Token::Reserved(..) => TokenKind::Reserved,
+            Token::LexError(..) => TokenKind::Error,
+            ref t if t.is_standard_keyword() => TokenKind::Keyword,
+            _ => TokenKind::Symbol,
+        };
+        let text = match token {
+            Token::StringConstant(..) | Token::InterpolatedString(..) => String::new(),
+            t => t.syntax().into(),
+        };
+
+        tokens.push(TokenInfo {
+            kind,
+            text,
+            span: Span::new(pos, stream.pos),
+        });
+    }
+
+    tokens
+}
+
+/// Is a [position][Position] within a [`Span`], or just after its end on the same line?
+#[inline]
+#[must_use]
+fn is_just_after(span: Span, pos: Position) -> bool {
+    let end = span.end();
+    end.line() == pos.line() && pos.position() <= end.position().map(|p| p + 1)
+}
+
+/// Does a [`Span`] contain a [position][Position]?
+#[inline]
+#[must_use]
+fn span_contains(span: Span, pos: Position) -> bool {
+    span.start() <= pos && (span.end().is_none() || pos <= span.end())
+}
+
+/// Collect all the [symbols][Symbol] declared in an [`AST`].
+fn collect_symbols(ast: &AST, _tokens: &[TokenInfo], symbols: &mut Vec<Symbol>) {
+    let path = &mut Vec::new();
+
+    for stmt in ast.statements() {
+        stmt.walk(path, &mut |path| {
+            collect_declarations(path, Span::NONE, symbols);
+            true
+        });
+    }
+
+    #[cfg(not(feature = "no_function"))]
+    for f in ast.iter_fn_def() {
+        let body = f.body.span();
+
+        for stmt in f.body.iter() {
+            stmt.walk(path, &mut |path| {
+                collect_declarations(path, body, symbols);
+                true
+            });
+        }
+
+        if crate::parser::is_anonymous_fn(&f.name) {
+            continue;
+        }
+
+        // Positions of the name and parameters are only found in the tokens
+        let (pos, params) = find_fn_declaration(_tokens, &f.name, f.params.len(), body.start());
+
+        symbols.push(Symbol {
+            name: f.name.clone(),
+            kind: SymbolKind::Function,
+            pos,
+            scope: Span::NONE,
+        });
+
+        for (name, pos) in f.params.iter().zip(params) {
+            symbols.push(Symbol {
+                name: name.clone(),
+                kind: SymbolKind::Parameter,
+                pos,
+                scope: body,
+            });
+        }
+    }
+}
+
+/// Find the [positions][Position] of the name and the parameters of a function declared before
+/// a position.
+#[cfg(not(feature = "no_function"))]
+fn find_fn_declaration(
+    tokens: &[TokenInfo],
+    name: &str,
+    num_params: usize,
+    before: Position,
+) -> (Position, Vec<Position>) {
+    let mut result = (Position::NONE, Vec::new());
+
+    for (i, t) in tokens.iter().enumerate() {
+        if t.span.start() >= before {
+            break;
+        }
+        if t.kind != TokenKind::Keyword || t.text != "fn" {
+            continue;
+        }
+
+        match tokens.get(i + 1) {
+            Some(n) if n.kind == TokenKind::Identifier && n.text == name => {
+                let params: Vec<_> = tokens[i + 2..]
+                    .iter()
+                    .take_while(|t| t.text != ")")
+                    .filter(|t| t.kind == TokenKind::Identifier)
+                    .map(|t| t.span.start())
+                    .collect();
+
+                if params.len() == num_params {
+                    // The last matching declaration is closest to the body
+                    result = (n.span.start(), params);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    result
+}
+
+/// Collect the [symbols][Symbol] declared by the last node of a path.
+///
+/// `outer` is the scope of symbols not declared within any block.
+fn collect_declarations(path: &[ASTNode], outer: Span, symbols: &mut Vec<Symbol>) {
+    let (node, ancestors) = path.split_last().unwrap();
+
+    let (name, kind, pos) = match node {
+        ASTNode::Stmt(Stmt::Var(x, options, ..)) => {
+            let kind = if options.contains(ASTFlags::CONSTANT) {
+                SymbolKind::Constant
+            } else {
+                SymbolKind::Variable
+            };
+            (&x.0.name, kind, x.0.pos)
+        }
+        ASTNode::Stmt(Stmt::For(x, ..)) => {
+            let (var, counter, .., body) = &**x;
+
+            for ident in [var, counter] {
+                if !ident.name.is_empty() {
+                    symbols.push(Symbol {
+                        name: ident.name.clone(),
+                        kind: SymbolKind::LoopVariable,
+                        pos: ident.pos,
+                        scope: body.span(),
+                    });
+                }
+            }
+            return;
+        }
+        ASTNode::Stmt(Stmt::TryCatch(x, ..)) => {
+            if !x.catch_var.name.is_empty() {
+                symbols.push(Symbol {
+                    name: x.catch_var.name.clone(),
+                    kind: SymbolKind::Variable,
+                    pos: x.catch_var.pos,
+                    scope: x.catch_block.span(),
+                });
+            }
+            return;
+        }
+        #[cfg(not(feature = "no_module"))]
+        ASTNode::Stmt(Stmt::Import(x, ..)) if !x.1.name.is_empty() => {
+            (&x.1.name, SymbolKind::Module, x.1.pos)
+        }
+        _ => return,
+    };
+
+    // The scope is the innermost block containing the declaration
+    let scope = ancestors
+        .iter()
+        .rev()
+        .find_map(|node| {
+            let blocks = match node {
+                ASTNode::Stmt(Stmt::Block(b)) => [b.span(), Span::NONE],
+                ASTNode::Stmt(Stmt::If(x, ..)) => [x.1.span(), x.2.span()],
+                ASTNode::Stmt(Stmt::While(x, ..)) | ASTNode::Stmt(Stmt::Do(x, ..)) => {
+                    [x.1.span(), Span::NONE]
+                }
+                ASTNode::Stmt(Stmt::For(x, ..)) => [x.3.span(), Span::NONE],
+                ASTNode::Stmt(Stmt::TryCatch(x, ..)) => [x.try_block.span(), x.catch_block.span()],
+                ASTNode::Expr(Expr::Stmt(b)) => [b.span(), Span::NONE],
+                _ => return None,
+            };
+            blocks
+                .iter()
+                .find(|&&span| !span.is_none() && span_contains(span, pos))
+                .copied()
+        })
+        .unwrap_or(outer);
+
+    symbols.push(Symbol {
+        name: name.clone(),
+        kind,
+        pos,
+        scope,
+    });
+}
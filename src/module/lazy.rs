@@ -0,0 +1,60 @@
+//! Module defining lazily-constructed static modules.
+
+use crate::func::native::{locked_read, locked_write, OnModuleInitCallback};
+use crate::{Locked, Module, Shared};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A static [`Module`] that is constructed on first use.
+///
+/// Under the `sync` feature, the constructor is run while holding a write lock, so it is only ever
+/// called once even when multiple threads race to access the module.
+pub(crate) struct LazyModule {
+    /// Constructor function for the module.
+    init: Box<OnModuleInitCallback>,
+    /// The module, if already constructed.
+    module: Locked<Option<Shared<Module>>>,
+}
+
+impl fmt::Debug for LazyModule {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyModule")
+            .field("module", &*locked_read(&self.module))
+            .finish()
+    }
+}
+
+impl LazyModule {
+    /// Create a new [`LazyModule`] with a constructor function.
+    #[inline(always)]
+    #[must_use]
+    pub fn new(init: Box<OnModuleInitCallback>) -> Self {
+        Self {
+            init,
+            module: Locked::new(None),
+        }
+    }
+    /// Get the [`Module`], constructing it if this is the first use.
+    #[must_use]
+    pub fn get(&self) -> Shared<Module> {
+        if let Some(ref module) = *locked_read(&self.module) {
+            return module.clone();
+        }
+
+        let mut guard = locked_write(&self.module);
+
+        // Another thread may have constructed the module while waiting for the lock
+        guard
+            .get_or_insert_with(|| {
+                let mut module = (self.init)();
+                if !module.is_indexed() {
+                    module.build_index();
+                }
+                module.into()
+            })
+            .clone()
+    }
+}
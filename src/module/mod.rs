@@ -77,6 +77,9 @@ pub struct FuncInfo {
     pub num_params: usize,
     /// Parameter types (if applicable).
     pub param_types: StaticVec<TypeId>,
+    /// Bit-mask of parameters (bit 0 = first parameter) borrowed by a native function instead of
+    /// being taken by value.
+    pub borrowed_params: usize,
     /// Parameter names and types (if available).
     #[cfg(feature = "metadata")]
     pub params_info: StaticVec<Identifier>,
@@ -188,10 +191,17 @@ pub struct Module {
     type_iterators: BTreeMap<TypeId, Shared<IteratorFn>>,
     /// Flattened collection of iterator functions, including those in sub-modules.
     all_type_iterators: BTreeMap<TypeId, Shared<IteratorFn>>,
+    /// Custom operators and their precedences.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    custom_operators: BTreeMap<Identifier, crate::engine::Precedence>,
     /// Is the [`Module`] indexed?
     indexed: bool,
     /// Does the [`Module`] contain indexed functions that have been exposed to the global namespace?
     contains_indexed_global_functions: bool,
+    /// Script-defined functions hot-patched into the [`AST`][crate::AST] holding this [`Module`],
+    /// if any.
+    #[cfg(not(feature = "no_function"))]
+    pub(crate) fn_patches: Option<Shared<crate::ast::FnPatches>>,
 }
 
 impl Default for Module {
@@ -287,8 +297,12 @@ impl Module {
             dynamic_functions: BloomFilterU64::new(),
             type_iterators: BTreeMap::new(),
             all_type_iterators: BTreeMap::new(),
+            #[cfg(not(feature = "no_custom_syntax"))]
+            custom_operators: BTreeMap::new(),
             indexed: true,
             contains_indexed_global_functions: false,
+            #[cfg(not(feature = "no_function"))]
+            fn_patches: None,
         }
     }
 
@@ -429,6 +443,8 @@ impl Module {
         self.dynamic_functions.clear();
         self.type_iterators.clear();
         self.all_type_iterators.clear();
+        #[cfg(not(feature = "no_custom_syntax"))]
+        self.custom_operators.clear();
         self.indexed = false;
         self.contains_indexed_global_functions = false;
     }
@@ -455,6 +471,57 @@ impl Module {
         self.custom_types.add_type::<T>(name);
         self
     }
+    /// _(metadata)_ Map a custom type to a friendly display name, with doc-comments.
+    /// Exported under the `metadata` feature only.
+    ///
+    /// ## Comments
+    ///
+    /// Block doc-comments should be kept in a single line.
+    ///
+    /// Line doc-comments should be kept in one string slice per line without the termination line-break.
+    ///
+    /// Leading white-spaces should be stripped, and each string slice always starts with the corresponding
+    /// doc-comment leader: `///` or `/**`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rhai::Module;
+    /// #[derive(Clone)]
+    /// struct TestStruct;
+    ///
+    /// let name = std::any::type_name::<TestStruct>();
+    ///
+    /// let mut module = Module::new();
+    ///
+    /// module.set_custom_type_with_comments::<TestStruct, _>(
+    ///     "MyType",
+    ///     ["/// A special type.", "///", "/// ```", "/// let x = new_my_type();", "/// ```"],
+    /// );
+    ///
+    /// assert_eq!(module.get_custom_type(name), Some("MyType"));
+    /// assert_eq!(module.get_custom_type_comments(name).unwrap().len(), 5);
+    /// ```
+    #[cfg(feature = "metadata")]
+    #[inline]
+    pub fn set_custom_type_with_comments<T, C: AsRef<str>>(
+        &mut self,
+        name: &str,
+        comments: impl AsRef<[C]>,
+    ) -> &mut Self {
+        self.custom_types.add_raw(
+            std::any::type_name::<T>(),
+            crate::types::CustomTypeInfo {
+                display_name: name.into(),
+                comments: comments
+                    .as_ref()
+                    .iter()
+                    .map(|s| s.as_ref().into())
+                    .collect(),
+            },
+        );
+        self
+    }
     /// Map a custom type to a friendly display name.
     ///
     /// ```
@@ -501,6 +568,45 @@ impl Module {
     pub fn get_custom_type(&self, key: &str) -> Option<&str> {
         self.custom_types.get(key).map(|t| t.display_name.as_str())
     }
+    /// _(metadata)_ Get the doc-comments of a registered custom type.
+    /// Exported under the `metadata` feature only.
+    #[cfg(feature = "metadata")]
+    #[inline]
+    #[must_use]
+    pub fn get_custom_type_comments(&self, key: &str) -> Option<&[Box<str>]> {
+        self.custom_types.get(key).map(|t| &*t.comments)
+    }
+    /// Iterate all the custom types registered in this [`Module`], as (type name, display name,
+    /// doc-comments) tuples.
+    #[cfg(feature = "metadata")]
+    #[inline]
+    pub(crate) fn iter_custom_types(&self) -> impl Iterator<Item = (&str, &str, &[Box<str>])> {
+        self.custom_types
+            .iter()
+            .map(|(type_name, t)| (type_name, t.display_name.as_str(), &*t.comments))
+    }
+    /// Find the type name of a registered custom type by its display name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use rhai::Module;
+    /// #[derive(Clone)]
+    /// struct TestStruct;
+    ///
+    /// let name = std::any::type_name::<TestStruct>();
+    ///
+    /// let mut module = Module::new();
+    ///
+    /// module.set_custom_type::<TestStruct>("MyType");
+    ///
+    /// assert_eq!(module.find_custom_type("MyType"), Some(name));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn find_custom_type(&self, name: &str) -> Option<&str> {
+        self.custom_types.find_by_display_name(name)
+    }
 
     /// Returns `true` if this [`Module`] contains no items.
     ///
@@ -523,6 +629,17 @@ impl Module {
             && self.modules.is_empty()
             && self.type_iterators.is_empty()
             && self.all_type_iterators.is_empty()
+            && self.custom_operators_is_empty()
+    }
+
+    /// Returns `true` if this [`Module`] contains no custom operators.
+    #[inline(always)]
+    #[must_use]
+    fn custom_operators_is_empty(&self) -> bool {
+        #[cfg(not(feature = "no_custom_syntax"))]
+        return self.custom_operators.is_empty();
+        #[cfg(feature = "no_custom_syntax")]
+        return true;
     }
 
     /// Is the [`Module`] indexed?
@@ -681,6 +798,7 @@ impl Module {
                 access: fn_def.access,
                 num_params,
                 param_types: StaticVec::new_const(),
+                borrowed_params: 0,
                 #[cfg(feature = "metadata")]
                 params_info,
                 #[cfg(feature = "metadata")]
@@ -937,10 +1055,52 @@ impl Module {
             // Map String to ImmutableString
             return TypeId::of::<ImmutableString>();
         }
+        if type_id == TypeId::of::<&ImmutableString>() {
+            // Map &ImmutableString to ImmutableString
+            return TypeId::of::<ImmutableString>();
+        }
+        #[cfg(not(feature = "no_index"))]
+        if type_id == TypeId::of::<&crate::Array>() || type_id == TypeId::of::<&[Dynamic]>() {
+            // Map &Array and &[Dynamic] to Array
+            return TypeId::of::<crate::Array>();
+        }
+        #[cfg(not(feature = "no_index"))]
+        if type_id == TypeId::of::<&crate::Blob>() || type_id == TypeId::of::<&[u8]>() {
+            // Map &Blob and &[u8] to Blob
+            return TypeId::of::<crate::Blob>();
+        }
+        #[cfg(not(feature = "no_object"))]
+        if type_id == TypeId::of::<&crate::Map>() {
+            // Map &Map to Map
+            return TypeId::of::<crate::Map>();
+        }
 
         type_id
     }
 
+    /// Is the type ID a reference type that a native function borrows from its argument?
+    #[inline]
+    #[must_use]
+    fn is_borrowed_type(type_id: TypeId) -> bool {
+        if type_id == TypeId::of::<&str>() || type_id == TypeId::of::<&ImmutableString>() {
+            return true;
+        }
+        #[cfg(not(feature = "no_index"))]
+        if type_id == TypeId::of::<&crate::Array>()
+            || type_id == TypeId::of::<&[Dynamic]>()
+            || type_id == TypeId::of::<&crate::Blob>()
+            || type_id == TypeId::of::<&[u8]>()
+        {
+            return true;
+        }
+        #[cfg(not(feature = "no_object"))]
+        if type_id == TypeId::of::<&crate::Map>() {
+            return true;
+        }
+
+        false
+    }
+
     /// Set a Rust function into the [`Module`], returning a non-zero hash key.
     ///
     /// If there is an existing Rust function of the same hash, it is replaced.
@@ -979,6 +1139,19 @@ impl Module {
             .collect();
         param_types.shrink_to_fit();
 
+        // Plugin functions convert their own arguments
+        let borrowed_params = if func.is_plugin_fn() {
+            0
+        } else {
+            arg_types
+                .as_ref()
+                .iter()
+                .enumerate()
+                .take(usize::BITS as usize)
+                .filter(|&(i, &type_id)| (!is_method || i > 0) && Self::is_borrowed_type(type_id))
+                .fold(0, |mask, (i, ..)| mask | (1 << i))
+        };
+
         let is_dynamic = param_types
             .iter()
             .any(|&type_id| type_id == TypeId::of::<Dynamic>());
@@ -1016,6 +1189,7 @@ impl Module {
                 access,
                 num_params: param_types.len(),
                 param_types,
+                borrowed_params,
                 #[cfg(feature = "metadata")]
                 params_info: param_names,
                 #[cfg(feature = "metadata")]
@@ -1212,6 +1386,82 @@ impl Module {
         )
     }
 
+    /// Set a custom operator with a precedence into the [`Module`], together with the Rust
+    /// function implementing it, returning a non-zero hash key.
+    /// This function is automatically exposed to the global namespace.
+    ///
+    /// Not available under `no_custom_syntax`.
+    ///
+    /// The custom operator is registered automatically into an [`Engine`][crate::Engine] when
+    /// this [`Module`] is registered via
+    /// [`register_global_module`][crate::Engine::register_global_module] or
+    /// [`register_static_module`][crate::Engine::register_static_module].  As parsing takes place
+    /// before any `import` statement is run, custom operators in imported modules are not usable.
+    ///
+    /// See [`Engine::register_custom_operator`][crate::Engine::register_custom_operator] for
+    /// details on custom operators.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the precedence is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::{Engine, Module};
+    ///
+    /// let mut module = Module::new();
+    /// module
+    ///     .set_custom_operator("#", 160, |x: i64, y: i64| Ok((x * y) - (x + y)))
+    ///     .expect("should succeed");
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_global_module(module.into());
+    ///
+    /// assert_eq!(engine.eval_expression::<i64>("1 + 2 * 3 # 4 - 5 / 6")?, 15);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(not(feature = "no_custom_syntax"))]
+    #[inline]
+    pub fn set_custom_operator<A, T, F, S>(
+        &mut self,
+        name: impl AsRef<str> + Into<Identifier>,
+        precedence: u8,
+        func: F,
+    ) -> Result<u64, String>
+    where
+        T: Variant + Clone,
+        F: RegisterNativeFunction<A, T, RhaiResultOf<S>>,
+    {
+        let precedence =
+            crate::engine::Precedence::new(precedence).ok_or("precedence cannot be zero")?;
+
+        self.custom_operators
+            .insert(name.as_ref().into(), precedence);
+
+        Ok(self.set_fn(
+            name,
+            FnNamespace::Global,
+            FnAccess::Public,
+            None,
+            &F::param_types(),
+            func.into_callable_function(),
+        ))
+    }
+
+    /// Get an iterator over the custom operators in this [`Module`] and their precedences.
+    ///
+    /// Not available under `no_custom_syntax`.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    #[inline]
+    pub fn iter_custom_operators(&self) -> impl Iterator<Item = (&str, u8)> {
+        self.custom_operators
+            .iter()
+            .map(|(name, precedence)| (name.as_str(), precedence.get()))
+    }
+
     /// Set a Rust getter function taking one mutable parameter, returning a non-zero hash key.
     /// This function is automatically exposed to the global namespace.
     ///
@@ -1520,6 +1770,22 @@ impl Module {
         }
     }
 
+    /// Look up a Rust function by hash, together with the bit-mask of parameters it borrows.
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_fn_with_borrowed_params(
+        &self,
+        hash_native: u64,
+    ) -> Option<(&CallableFunction, usize)> {
+        if self.functions.is_empty() {
+            None
+        } else {
+            self.functions
+                .get(&hash_native)
+                .map(|f| (&f.func, f.borrowed_params))
+        }
+    }
+
     /// Can the particular function with [`Dynamic`] parameter(s) exist in the [`Module`]?
     ///
     /// A `true` return value does not automatically imply that the function _must_ exist.
@@ -1565,6 +1831,9 @@ impl Module {
         self.functions.extend(other.functions.into_iter());
         self.dynamic_functions += &other.dynamic_functions;
         self.type_iterators.extend(other.type_iterators.into_iter());
+        #[cfg(not(feature = "no_custom_syntax"))]
+        self.custom_operators
+            .extend(other.custom_operators.into_iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1594,6 +1863,9 @@ impl Module {
         self.functions.extend(other.functions.into_iter());
         self.dynamic_functions += &other.dynamic_functions;
         self.type_iterators.extend(other.type_iterators.into_iter());
+        #[cfg(not(feature = "no_custom_syntax"))]
+        self.custom_operators
+            .extend(other.custom_operators.into_iter());
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1632,6 +1904,10 @@ impl Module {
         for (&k, v) in &other.type_iterators {
             self.type_iterators.entry(k).or_insert_with(|| v.clone());
         }
+        #[cfg(not(feature = "no_custom_syntax"))]
+        for (k, &v) in &other.custom_operators {
+            self.custom_operators.entry(k.clone()).or_insert(v);
+        }
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -1692,6 +1968,9 @@ impl Module {
 
         self.type_iterators
             .extend(other.type_iterators.iter().map(|(&k, v)| (k, v.clone())));
+        #[cfg(not(feature = "no_custom_syntax"))]
+        self.custom_operators
+            .extend(other.custom_operators.iter().map(|(k, &v)| (k.clone(), v)));
         self.all_functions.clear();
         self.all_variables.clear();
         self.all_type_iterators.clear();
@@ -2228,9 +2507,15 @@ impl Module {
     }
 }
 
+#[cfg(not(feature = "no_module"))]
+mod lazy;
+
 /// Module containing all built-in [module resolvers][ModuleResolver].
 #[cfg(not(feature = "no_module"))]
 pub mod resolvers;
 
+#[cfg(not(feature = "no_module"))]
+pub(crate) use lazy::LazyModule;
+
 #[cfg(not(feature = "no_module"))]
 pub use resolvers::ModuleResolver;
@@ -148,6 +148,20 @@ impl ModuleResolver for ModuleResolversCollection {
 
         Err(ERR::ErrorModuleNotFound(path.into(), pos).into())
     }
+    fn resolve_static(&self, path: &str) -> Option<Shared<Module>> {
+        // Only the first resolver can be trusted, because it may resolve the path at run-time
+        // even if it cannot do so statically
+        self.0.first()?.resolve_static(path)
+    }
+    fn invalidate(&self, path: &str) -> bool {
+        let mut invalidated = false;
+
+        for resolver in &self.0 {
+            invalidated |= resolver.invalidate(path);
+        }
+
+        invalidated
+    }
 }
 
 impl<M: ModuleResolver + 'static> AddAssign<M> for ModuleResolversCollection {
@@ -363,6 +363,22 @@ impl ModuleResolver for FileModuleResolver {
         self.impl_resolve(engine, None, source, path, pos)
     }
 
+    /// Remove all cached [modules][Module] loaded from a path string.
+    ///
+    /// Relative paths resolved against the paths of different source scripts are all removed.
+    fn invalidate(&self, path: &str) -> bool {
+        let file_path = self.get_file_path(path, None);
+        let relative_path = Path::new(path).with_extension(self.extension.as_str());
+        let mut cache = locked_write(&self.cache);
+        let len = cache.len();
+
+        cache.retain(|p, _| {
+            *p != file_path && !(relative_path.is_relative() && p.ends_with(&relative_path))
+        });
+
+        cache.len() != len
+    }
+
     /// Resolve an `AST` based on a path string.
     ///
     /// The file system is accessed during each call; the internal cache is by-passed.
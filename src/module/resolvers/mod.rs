@@ -8,6 +8,7 @@ mod collection;
 mod dummy;
 mod file;
 mod stat;
+mod url;
 
 pub use collection::ModuleResolversCollection;
 pub use dummy::DummyModuleResolver;
@@ -15,6 +16,10 @@ pub use dummy::DummyModuleResolver;
 #[cfg(not(target_family = "wasm"))]
 pub use file::FileModuleResolver;
 pub use stat::StaticModuleResolver;
+#[cfg(feature = "url_resolver")]
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_family = "wasm"))]
+pub use url::UrlModuleResolver;
 
 /// Trait that encapsulates a module resolution service.
 pub trait ModuleResolver: SendSync {
@@ -42,6 +47,29 @@ pub trait ModuleResolver: SendSync {
         self.resolve(engine, global.source(), path, pos)
     }
 
+    /// Resolve a module based on a path string, without side effects (e.g. during compilation).
+    ///
+    /// Returns [`None`] (default) if the module cannot be resolved statically
+    /// (e.g. if it needs to be loaded from a script file).
+    ///
+    /// Constants in modules that are statically resolved can be used in constant contexts
+    /// (e.g. `switch` case labels) and are folded into the `AST` during compilation.
+    #[allow(unused_variables)]
+    #[must_use]
+    fn resolve_static(&self, path: &str) -> Option<Shared<Module>> {
+        None
+    }
+
+    /// Invalidate any cached [module][Module] resolved from a path string, so that it is resolved
+    /// afresh the next time it is imported.
+    ///
+    /// Returns `true` if a cached [module][Module] was removed, or `false` (default) if there is
+    /// none (e.g. if the module resolver does not cache).
+    #[allow(unused_variables)]
+    fn invalidate(&self, path: &str) -> bool {
+        false
+    }
+
     /// Resolve an `AST` based on a path string.
     ///
     /// Returns [`None`] (default) if such resolution is not supported
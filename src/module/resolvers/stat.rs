@@ -163,6 +163,10 @@ impl ModuleResolver for StaticModuleResolver {
             .cloned()
             .ok_or_else(|| ERR::ErrorModuleNotFound(path.into(), pos).into())
     }
+    #[inline(always)]
+    fn resolve_static(&self, path: &str) -> Option<Shared<Module>> {
+        self.0.get(path).cloned()
+    }
 }
 
 impl AddAssign<Self> for StaticModuleResolver {
@@ -0,0 +1,558 @@
+#![cfg(feature = "url_resolver")]
+#![cfg(not(feature = "no_std"))]
+#![cfg(not(target_family = "wasm"))]
+
+use crate::eval::GlobalRuntimeState;
+use crate::func::{locked_read, locked_write};
+use crate::{
+    Engine, Identifier, Module, ModuleResolver, Position, RhaiResultOf, Scope, Shared, AST, ERR,
+};
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error as IoError, ErrorKind},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use url::Url;
+
+/// Default timeout for fetching a module script.
+pub const DEFAULT_URL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum number of redirects followed when fetching a module script.
+const MAX_REDIRECTS: usize = 5;
+
+/// Parse an HTTP(S) URL, returning [`None`] if it is not valid.
+///
+/// URL's are parsed per the WHATWG URL standard, the same way as they are fetched, so `.` and `..`
+/// segments (including percent-encoded dots) are resolved and backslashes are treated as path
+/// separators.
+///
+/// URL's containing ASCII control characters are rejected outright, as the standard silently
+/// strips tabs and line breaks from anywhere within a URL.
+#[must_use]
+fn parse_url(url: &str) -> Option<Url> {
+    if url.chars().any(|ch| ch.is_ascii_control()) {
+        return None;
+    }
+
+    Url::parse(url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+}
+
+/// Is a URL the same as, or below, another URL?
+///
+/// The scheme, host and port must match exactly, and the path must start with all the path
+/// segments of the other URL.  Path segments containing percent-encoded slashes or backslashes
+/// never match, as servers may decode them into path separators.
+#[must_use]
+fn is_within(url: &Url, other: &Url) -> bool {
+    let segments: Vec<_> = url.path_segments().into_iter().flatten().collect();
+    let prefix: Vec<_> = other.path_segments().into_iter().flatten().collect();
+    let prefix = match prefix.split_last() {
+        Some((&"", prefix)) => prefix,
+        _ => &prefix,
+    };
+
+    let is_encoded_separator = |segment: &&str| {
+        let segment = segment.to_ascii_lowercase();
+        segment.contains("%2f") || segment.contains("%5c")
+    };
+
+    url.scheme() == other.scheme()
+        && url.host() == other.host()
+        && url.port_or_known_default() == other.port_or_known_default()
+        && !segments.iter().any(is_encoded_separator)
+        && segments.len() >= prefix.len()
+        && segments.iter().zip(prefix).all(|(a, b)| a == b)
+}
+
+/// A [module][Module] resolution service that fetches [module][Module] scripts over HTTP(S).
+///
+/// Exported under the `url_resolver` feature only.
+///
+/// ## Allow-List
+///
+/// For security, no URL can be fetched until it is allowed.
+///
+/// Use [`allow`][UrlModuleResolver::allow] to add URL's to the allow-list. A URL is fetched only if
+/// it has the same scheme, host and port as one of the allowed URL's, and its path is the same as,
+/// or below, the path of that URL (e.g. `https://example.com/scripts` allows
+/// `https://example.com/scripts/hello.rhai` but not `https://example.com/scripts2/hello.rhai`).
+///
+/// URL's are parsed per the WHATWG URL standard (the same way as web browsers and the HTTP client
+/// do), so `.` and `..` segments in paths are resolved before comparing, and the URL that is
+/// checked is exactly the URL that is fetched. URL's containing ASCII control characters (e.g. tabs
+/// or line breaks) are never allowed.
+///
+/// Redirects are followed only to allowed URL's, up to five times.
+///
+/// ## Base URL
+///
+/// Import paths that do not start with `http://` or `https://` are appended to the base URL, if
+/// one is set (see [`set_base_url`][UrlModuleResolver::set_base_url]). Such relative paths can
+/// never resolve to a URL outside of the base URL.
+///
+/// ## Caching
+///
+/// Resolved [Modules][Module] are cached internally so scripts are not fetched and recompiled for
+/// subsequent requests.
+///
+/// Use [`clear_cache`][UrlModuleResolver::clear_cache] or
+/// [`clear_cache_for_url`][UrlModuleResolver::clear_cache_for_url] to clear the internal cache.
+///
+/// If a cache directory is set (see [`set_cache_dir`][UrlModuleResolver::set_cache_dir]), fetched
+/// scripts are also saved into it and loaded from it instead of the network afterwards, even across
+/// runs. Files in the cache directory never expire; delete them to force scripts to be fetched again.
+///
+/// # Example
+///
+/// ```
+/// use rhai::Engine;
+/// use rhai::module_resolvers::UrlModuleResolver;
+/// use std::time::Duration;
+///
+/// let mut resolver = UrlModuleResolver::new();
+///
+/// resolver
+///     .allow("https://storage.example.com/scripts/")
+///     .set_timeout(Duration::from_secs(5))
+///     .set_cache_dir("./script_cache");
+///
+/// let mut engine = Engine::new();
+///
+/// engine.set_module_resolver(resolver);
+/// ```
+#[derive(Debug)]
+pub struct UrlModuleResolver {
+    allow_list: Vec<Identifier>,
+    base_url: Option<Identifier>,
+    timeout: Duration,
+    cache_dir: Option<PathBuf>,
+    cache_enabled: bool,
+    scope: Scope<'static>,
+
+    #[cfg(not(feature = "sync"))]
+    cache: std::cell::RefCell<BTreeMap<String, Shared<Module>>>,
+    #[cfg(feature = "sync")]
+    cache: std::sync::RwLock<BTreeMap<String, Shared<Module>>>,
+}
+
+impl Default for UrlModuleResolver {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UrlModuleResolver {
+    /// Create a new [`UrlModuleResolver`] with an empty allow-list.
+    ///
+    /// The default timeout is 30 seconds.
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            allow_list: Vec::new(),
+            base_url: None,
+            timeout: DEFAULT_URL_TIMEOUT,
+            cache_dir: None,
+            cache_enabled: true,
+            scope: Scope::new(),
+            cache: BTreeMap::new().into(),
+        }
+    }
+
+    /// Allow a URL, and all URL's below it, to be fetched.
+    ///
+    /// An allowed URL that is not a valid HTTP(S) URL never matches anything.
+    #[inline]
+    pub fn allow(&mut self, url: impl Into<Identifier>) -> &mut Self {
+        self.allow_list.push(url.into());
+        self
+    }
+    /// Get the URL's allowed to be fetched.
+    #[inline(always)]
+    #[must_use]
+    pub fn allow_list(&self) -> &[Identifier] {
+        &self.allow_list
+    }
+    /// Is a URL allowed to be fetched?
+    ///
+    /// The URL must have the same scheme, host and port as one of the allowed URL's, and its path
+    /// (after resolving `.` and `..` segments) must be the same as, or below, the path of that URL.
+    #[inline]
+    #[must_use]
+    pub fn is_allowed(&self, url: &str) -> bool {
+        parse_url(url).map_or(false, |url| self.is_allowed_url(&url))
+    }
+    /// Is a parsed URL allowed to be fetched?
+    #[must_use]
+    fn is_allowed_url(&self, url: &Url) -> bool {
+        self.allow_list
+            .iter()
+            .filter_map(|allowed| parse_url(allowed))
+            .any(|allowed| is_within(url, &allowed))
+    }
+
+    /// Get the base URL for import paths that are not full URLs.
+    #[inline(always)]
+    #[must_use]
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_ref().map(|s| s.as_str())
+    }
+    /// Set the base URL for import paths that are not full URLs.
+    #[inline(always)]
+    pub fn set_base_url(&mut self, url: impl Into<Identifier>) -> &mut Self {
+        self.base_url = Some(url.into());
+        self
+    }
+
+    /// Get the timeout for fetching a module script.
+    #[inline(always)]
+    #[must_use]
+    pub const fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    /// Set the timeout for fetching a module script.
+    #[inline(always)]
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the directory where fetched scripts are saved.
+    #[inline(always)]
+    #[must_use]
+    pub fn cache_dir(&self) -> Option<&Path> {
+        self.cache_dir.as_ref().map(<_>::as_ref)
+    }
+    /// Set the directory where fetched scripts are saved.
+    ///
+    /// The directory is created if it does not exist.
+    #[inline(always)]
+    pub fn set_cache_dir(&mut self, path: impl Into<PathBuf>) -> &mut Self {
+        self.cache_dir = Some(path.into());
+        self
+    }
+
+    /// Get a reference to the URL module resolver's [scope][Scope].
+    ///
+    /// The [scope][Scope] is used for compiling module scripts.
+    #[must_use]
+    #[inline(always)]
+    pub const fn scope(&self) -> &Scope {
+        &self.scope
+    }
+
+    /// Set the URL module resolver's [scope][Scope].
+    ///
+    /// The [scope][Scope] is used for compiling module scripts.
+    #[inline(always)]
+    pub fn set_scope(&mut self, scope: Scope<'static>) {
+        self.scope = scope;
+    }
+
+    /// Get a mutable reference to the URL module resolver's [scope][Scope].
+    ///
+    /// The [scope][Scope] is used for compiling module scripts.
+    #[must_use]
+    #[inline(always)]
+    pub fn scope_mut(&mut self) -> &mut Scope<'static> {
+        &mut self.scope
+    }
+
+    /// Enable/disable the cache.
+    ///
+    /// This does not affect the cache directory.
+    #[inline(always)]
+    pub fn enable_cache(&mut self, enable: bool) -> &mut Self {
+        self.cache_enabled = enable;
+        self
+    }
+    /// Is the cache enabled?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_cache_enabled(&self) -> bool {
+        self.cache_enabled
+    }
+
+    /// Is a particular URL cached?
+    #[inline]
+    #[must_use]
+    pub fn is_cached(&self, url: &str) -> bool {
+        if !self.cache_enabled {
+            return false;
+        }
+
+        let cache = locked_read(&self.cache);
+
+        if cache.is_empty() {
+            false
+        } else {
+            cache.contains_key(url)
+        }
+    }
+    /// Empty the internal cache.
+    ///
+    /// This does not affect the cache directory.
+    #[inline]
+    pub fn clear_cache(&mut self) -> &mut Self {
+        locked_write(&self.cache).clear();
+        self
+    }
+    /// Remove the specified URL from internal cache.
+    ///
+    /// This does not affect the cache directory.
+    #[inline]
+    #[must_use]
+    pub fn clear_cache_for_url(&mut self, url: &str) -> Option<Shared<Module>> {
+        locked_write(&self.cache).remove_entry(url).map(|(.., v)| v)
+    }
+
+    /// Is an import path a full URL instead of a path relative to the base URL?
+    #[inline]
+    #[must_use]
+    fn is_full_url(path: &str) -> bool {
+        path.starts_with("https://") || path.starts_with("http://")
+    }
+    /// Construct a full URL, with `.` and `..` segments in its path resolved.
+    #[must_use]
+    pub fn get_url(&self, path: &str) -> String {
+        let url = self.join_base_url(path);
+        parse_url(&url).map_or(url, String::from)
+    }
+    /// Append an import path to the base URL, unless it is already a full URL.
+    #[must_use]
+    fn join_base_url(&self, path: &str) -> String {
+        match self.base_url {
+            _ if Self::is_full_url(path) => path.into(),
+            Some(ref base) if base.ends_with('/') || path.starts_with('/') => {
+                format!("{base}{path}")
+            }
+            Some(ref base) => format!("{base}/{path}"),
+            None => path.into(),
+        }
+    }
+    /// Is the full URL of an import path allowed to be fetched?
+    ///
+    /// Relative paths must also stay within the base URL.
+    #[must_use]
+    fn is_allowed_path(&self, url: &Url, path: &str) -> bool {
+        if !self.is_allowed_url(url) {
+            return false;
+        }
+
+        match self.base_url {
+            _ if Self::is_full_url(path) => true,
+            Some(ref base) => parse_url(base).map_or(false, |base| is_within(url, &base)),
+            None => true,
+        }
+    }
+
+    /// Get the path of the file in the cache directory holding the script of a URL.
+    #[must_use]
+    fn get_cache_file_path(&self, url: &str) -> Option<PathBuf> {
+        // Use FNV-1a, which is stable across runs and platforms.
+        let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{hash:016x}.rhai")))
+    }
+
+    /// Fetch the script of a URL, from the cache directory if possible.
+    ///
+    /// The import path is resolved into a URL which is checked against the allow-list and then
+    /// fetched as is, so there can be no discrepancy between the URL checked and the URL fetched.
+    fn fetch(&self, path: &str, pos: Position) -> RhaiResultOf<String> {
+        let url = match parse_url(&self.join_base_url(path)) {
+            Some(url) if self.is_allowed_path(&url, path) => url,
+            url => {
+                let url = url.map_or_else(|| self.join_base_url(path), String::from);
+                let err = IoError::new(ErrorKind::PermissionDenied, "URL is not allowed");
+                return Err(ERR::ErrorSystem(format!("Cannot fetch {url}"), err.into()).into());
+            }
+        };
+
+        let cache_file = self.get_cache_file_path(url.as_str());
+
+        if let Some(script) = cache_file
+            .as_ref()
+            .and_then(|file| fs::read_to_string(file).ok())
+        {
+            return Ok(script);
+        }
+
+        let fetch_error =
+            |err: IoError| ERR::ErrorSystem(format!("Cannot fetch {url}"), err.into());
+
+        // Redirects are followed here instead of by the agent, so that the allow-list is checked
+        // before fetching each URL
+        let agent = ureq::AgentBuilder::new()
+            .timeout(self.timeout)
+            .redirects(0)
+            .build();
+
+        let mut target = url.clone();
+        let mut redirects = 0;
+
+        let response = loop {
+            let response = match agent.request_url("GET", &target).call() {
+                Ok(response) => response,
+                Err(ureq::Error::Status(404, ..)) => {
+                    return Err(ERR::ErrorModuleNotFound(path.to_string(), pos).into())
+                }
+                Err(err) => {
+                    let err = IoError::new(ErrorKind::Other, err.to_string());
+                    return Err(fetch_error(err).into());
+                }
+            };
+
+            if !(300..400).contains(&response.status()) {
+                break response;
+            }
+
+            redirects += 1;
+
+            let err = match response
+                .header("Location")
+                .and_then(|location| parse_url(target.join(location).ok()?.as_str()))
+            {
+                _ if redirects > MAX_REDIRECTS => {
+                    IoError::new(ErrorKind::Other, "too many redirects")
+                }
+                Some(next) if self.is_allowed_url(&next) => {
+                    // Fetch the URL exactly as checked
+                    target = next;
+                    continue;
+                }
+                Some(next) => IoError::new(
+                    ErrorKind::PermissionDenied,
+                    format!("redirected to {next}, which is not allowed"),
+                ),
+                None => IoError::new(ErrorKind::Other, "invalid redirect"),
+            };
+
+            return Err(fetch_error(err).into());
+        };
+
+        let script = response.into_string().map_err(fetch_error)?;
+
+        // The cache directory is only an optimization, so failing to write into it is ignored
+        if let Some(file) = cache_file {
+            let _ = file
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(file, &script));
+        }
+
+        Ok(script)
+    }
+
+    /// Fetch and compile the script of a URL.
+    fn compile(&self, engine: &Engine, path: &str, pos: Position) -> RhaiResultOf<AST> {
+        let url = self.get_url(path);
+
+        let script = self.fetch(path, pos).map_err(|err| match *err {
+            ERR::ErrorModuleNotFound(..) => err,
+            _ => ERR::ErrorInModule(path.to_string(), err, pos).into(),
+        })?;
+
+        let mut ast = engine
+            .compile_with_scope(&self.scope, script)
+            .map_err(|err| ERR::ErrorInModule(path.to_string(), err.into(), pos))?;
+
+        ast.set_source(url);
+
+        Ok(ast)
+    }
+
+    /// Resolve a module based on a path.
+    fn impl_resolve(
+        &self,
+        engine: &Engine,
+        global: Option<&mut GlobalRuntimeState>,
+        path: &str,
+        pos: Position,
+    ) -> RhaiResultOf<Shared<Module>> {
+        let url = self.get_url(path);
+
+        if self.is_cache_enabled() {
+            if let Some(module) = locked_read(&self.cache).get(&url) {
+                return Ok(module.clone());
+            }
+        }
+
+        let ast = self.compile(engine, path, pos)?;
+
+        let scope = Scope::new();
+
+        let m: Shared<_> = if let Some(global) = global {
+            Module::eval_ast_as_new_raw(engine, scope, global, &ast)
+        } else {
+            Module::eval_ast_as_new(scope, &ast, engine)
+        }
+        .map_err(|err| Box::new(ERR::ErrorInModule(path.to_string(), err, pos)))?
+        .into();
+
+        if self.is_cache_enabled() {
+            locked_write(&self.cache).insert(url, m.clone());
+        }
+
+        Ok(m)
+    }
+}
+
+impl ModuleResolver for UrlModuleResolver {
+    fn resolve_raw(
+        &self,
+        engine: &Engine,
+        global: &mut GlobalRuntimeState,
+        path: &str,
+        pos: Position,
+    ) -> RhaiResultOf<Shared<Module>> {
+        self.impl_resolve(engine, Some(global), path, pos)
+    }
+
+    #[inline(always)]
+    fn resolve(
+        &self,
+        engine: &Engine,
+        _source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> RhaiResultOf<Shared<Module>> {
+        self.impl_resolve(engine, None, path, pos)
+    }
+
+    /// Remove the cached [module][Module] fetched from a path string, as well as its script in the
+    /// cache directory (if any).
+    fn invalidate(&self, path: &str) -> bool {
+        let url = self.get_url(path);
+
+        let removed_file = self
+            .get_cache_file_path(&url)
+            .map_or(false, |file| fs::remove_file(file).is_ok());
+
+        locked_write(&self.cache).remove(&url).is_some() || removed_file
+    }
+
+    /// Resolve an `AST` based on a path string.
+    ///
+    /// The script is fetched during each call (unless it is in the cache directory);
+    /// the internal cache is by-passed.
+    fn resolve_ast(
+        &self,
+        engine: &Engine,
+        _source: Option<&str>,
+        path: &str,
+        pos: Position,
+    ) -> Option<RhaiResultOf<AST>> {
+        Some(self.compile(engine, path, pos))
+    }
+}
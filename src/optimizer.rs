@@ -6,18 +6,19 @@ use crate::ast::{
 };
 use crate::engine::{KEYWORD_DEBUG, KEYWORD_EVAL, KEYWORD_FN_PTR, KEYWORD_PRINT, KEYWORD_TYPE_OF};
 use crate::eval::{Caches, GlobalRuntimeState};
-use crate::func::builtin::get_builtin_binary_op_fn;
+use crate::func::builtin::{
+    get_builtin_binary_op_fn, get_builtin_unary_op_fn, is_overridable_binary_op,
+};
 use crate::func::hashing::get_hasher;
 use crate::tokenizer::{Span, Token};
 use crate::types::dynamic::AccessMode;
 use crate::{
-    calc_fn_hash, calc_fn_params_hash, combine_hashes, Dynamic, Engine, FnPtr, Identifier,
-    ImmutableString, Position, Scope, StaticVec, AST, INT,
+    calc_fn_hash, Dynamic, Engine, FnPtr, Identifier, ImmutableString, ParseWarning, Position,
+    Scope, StaticVec, AST, INT,
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
-    any::TypeId,
     convert::TryFrom,
     hash::{Hash, Hasher},
     mem,
@@ -48,6 +49,8 @@ impl Default for OptimizationLevel {
 struct OptimizerState<'a> {
     /// Has the [`AST`] been changed during this pass?
     changed: bool,
+    /// Number of passes made over blocks of statements.
+    passes: usize,
     /// Collection of constants to use for eager function evaluations.
     variables: StaticVec<(Identifier, AccessMode, Option<Dynamic>)>,
     /// Activate constants propagation?
@@ -61,8 +64,16 @@ struct OptimizerState<'a> {
     /// [Module][crate::Module] containing script-defined functions.
     #[cfg(not(feature = "no_function"))]
     lib: &'a [&'a crate::Module],
+    /// Hashes of pure script-defined functions.
+    #[cfg(not(feature = "no_function"))]
+    pure_fns: &'a std::collections::BTreeSet<u64>,
+    /// Evaluate calls to pure script-defined functions with constant arguments?
+    #[cfg(not(feature = "no_function"))]
+    eval_pure_fns: bool,
     /// Optimization level.
     optimization_level: OptimizationLevel,
+    /// Warnings raised during optimization.
+    warnings: Vec<(ParseWarning, Position)>,
 }
 
 impl<'a> OptimizerState<'a> {
@@ -71,10 +82,12 @@ impl<'a> OptimizerState<'a> {
     pub fn new(
         engine: &'a Engine,
         #[cfg(not(feature = "no_function"))] lib: &'a [&'a crate::Module],
+        #[cfg(not(feature = "no_function"))] pure_fns: &'a std::collections::BTreeSet<u64>,
         optimization_level: OptimizationLevel,
     ) -> Self {
         Self {
             changed: false,
+            passes: 0,
             variables: StaticVec::new_const(),
             propagate_constants: true,
             engine,
@@ -82,7 +95,12 @@ impl<'a> OptimizerState<'a> {
             caches: Caches::new(),
             #[cfg(not(feature = "no_function"))]
             lib,
+            #[cfg(not(feature = "no_function"))]
+            pure_fns,
+            #[cfg(not(feature = "no_function"))]
+            eval_pure_fns: false,
             optimization_level,
+            warnings: Vec::new(),
         }
     }
     /// Set the [`AST`] state to be dirty (i.e. changed).
@@ -155,40 +173,55 @@ impl<'a> OptimizerState<'a> {
                 &mut arg_values.iter_mut().collect::<StaticVec<_>>(),
                 false,
                 false,
+                0,
                 Position::NONE,
                 0,
             )
             .ok()
             .map(|(v, ..)| v)
     }
-}
-
-// Has a system function a Rust-native override?
-fn has_native_fn_override(
-    engine: &Engine,
-    hash_script: u64,
-    arg_types: impl AsRef<[TypeId]>,
-) -> bool {
-    let hash_params = calc_fn_params_hash(arg_types.as_ref().iter().copied());
-    let hash = combine_hashes(hash_script, hash_params);
-
-    // First check the global namespace and packages, but skip modules that are standard because
-    // they should never conflict with system functions.
-    let result = engine
-        .global_modules
-        .iter()
-        .filter(|m| !m.standard)
-        .any(|m| m.contains_fn(hash));
+    /// Call a pure script-defined function.
+    #[cfg(not(feature = "no_function"))]
+    pub fn call_pure_fn_with_constant_arguments(
+        &mut self,
+        fn_name: &str,
+        arg_values: &mut [Dynamic],
+    ) -> Option<Dynamic> {
+        let lib = self.lib;
+        let fn_def = lib
+            .iter()
+            .find_map(|&m| m.get_script_fn(fn_name, arg_values.len()))?;
 
-    #[cfg(not(feature = "no_module"))]
-    // Then check sub-modules
-    let result = result
-        || engine
-            .global_sub_modules
-            .values()
-            .any(|m| m.contains_qualified_fn(hash));
+        // Each call has its own budget of operations
+        self.global.num_operations = 0;
+        #[cfg(not(feature = "unchecked"))]
+        {
+            self.global.fuel_consumed = 0;
+        }
 
-    result
+        self.engine
+            .call_script_fn(
+                &mut Scope::new(),
+                &mut self.global,
+                &mut self.caches,
+                lib,
+                &mut None,
+                fn_def,
+                &mut arg_values.iter_mut().collect::<StaticVec<_>>(),
+                false,
+                Position::NONE,
+                0,
+            )
+            .ok()
+    }
+    /// Report a [`ParseWarning`] to the callback registered with [`Engine::on_parse_warning`],
+    /// and record it.
+    pub fn report_warning(&mut self, warning: ParseWarning, pos: Position) {
+        if let Some(ref callback) = self.engine.parse_warning {
+            callback(warning.clone(), pos);
+        }
+        self.warnings.push((warning, pos));
+    }
 }
 
 /// Optimize a block of [statements][Stmt].
@@ -234,6 +267,7 @@ fn optimize_stmt_block(
     // Optimize
     loop {
         state.clear_dirty();
+        state.passes += 1;
 
         let orig_constants_len = state.variables.len(); // Original number of constants in the state, for restore later
         let orig_propagate_constants = state.propagate_constants;
@@ -274,6 +308,19 @@ fn optimize_stmt_block(
                         state.push_var(x.0.as_str(), AccessMode::ReadWrite, None);
                     }
                 }
+                Stmt::VarTuple(x, options, ..) => {
+                    let access = if options.contains(ASTFlags::CONSTANT) {
+                        AccessMode::ReadOnly
+                    } else {
+                        AccessMode::ReadWrite
+                    };
+
+                    // Add variables into the state
+                    optimize_expr(&mut x.1, state, false);
+                    x.0.iter()
+                        .flatten()
+                        .for_each(|var| state.push_var(var.as_str(), access, None));
+                }
                 // Optimize the statement
                 _ => optimize_stmt(stmt, state, preserve_result),
             }
@@ -447,6 +494,18 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
             }
         }
 
+        // (expr, ...) = expr
+        Stmt::Assignment(x, ..) if matches!(x.1.lhs, Expr::Tuple(..)) => {
+            match x.1.lhs {
+                Expr::Tuple(ref mut targets, ..) => targets
+                    .iter_mut()
+                    .filter(|target| !target.is_variable_access(false))
+                    .for_each(|target| optimize_expr(target, state, false)),
+                ref expr => unreachable!("Expr::Tuple expected but gets {:?}", expr),
+            }
+            optimize_expr(&mut x.1.rhs, state, false);
+        }
+
         // expr op= expr
         Stmt::Assignment(x, ..) => {
             if !x.1.lhs.is_variable_access(false) {
@@ -516,8 +575,8 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                 optimize_stmt_block(mem::take(&mut **other), state, preserve_result, true, false);
         }
 
-        // switch const { ... }
-        Stmt::Switch(x, pos) if x.0.is_constant() => {
+        // switch const { ... } - without patterns
+        Stmt::Switch(x, pos) if x.0.is_constant() && x.1.patterns.is_empty() => {
             let (
                 match_expr,
                 SwitchCasesCollection {
@@ -525,6 +584,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     cases,
                     ranges,
                     def_case,
+                    ..
                 },
             ) = &mut **x;
 
@@ -548,7 +608,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                             *stmt = statements;
                         } else {
                             // switch const { case if condition => stmt, _ => def } => if condition { stmt } else { def }
-                            optimize_expr(&mut b.condition, state, false);
+                            optimize_case_condition(&mut b.condition, state);
 
                             let else_stmt = if let Some(index) = def_case {
                                 let mut def_stmt =
@@ -591,16 +651,18 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
             }
 
             // Then check ranges
-            if value.is::<INT>() && !ranges.is_empty() {
-                let value = value.as_int().unwrap();
+            let is_numeric = value.is::<INT>();
+            #[cfg(not(feature = "no_float"))]
+            let is_numeric = is_numeric || value.is::<crate::FLOAT>();
 
+            if is_numeric && !ranges.is_empty() {
                 // Only one range or all ranges without conditions
                 if ranges.len() == 1
                     || ranges
                         .iter()
                         .all(|r| expressions[r.index()].is_always_true())
                 {
-                    if let Some(r) = ranges.iter().find(|r| r.contains(value)) {
+                    if let Some(r) = ranges.iter().find(|r| r.contains_value(&value)) {
                         let range_block = &mut expressions[r.index()];
 
                         if range_block.is_always_true() {
@@ -613,7 +675,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                             let mut condition = mem::take(&mut range_block.condition);
 
                             // switch const { range if condition => stmt, _ => def } => if condition { stmt } else { def }
-                            optimize_expr(&mut condition, state, false);
+                            optimize_case_condition(&mut condition, state);
 
                             let else_stmt = if let Some(index) = def_case {
                                 let mut def_stmt =
@@ -646,7 +708,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
 
                     let old_ranges_len = ranges.len();
 
-                    ranges.retain(|r| r.contains(value));
+                    ranges.retain(|r| r.contains_value(&value));
 
                     if ranges.len() != old_ranges_len {
                         state.set_dirty();
@@ -654,7 +716,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
 
                     for r in &*ranges {
                         let b = &mut expressions[r.index()];
-                        optimize_expr(&mut b.condition, state, false);
+                        optimize_case_condition(&mut b.condition, state);
                         optimize_expr(&mut b.expr, state, false);
                     }
                     return;
@@ -680,30 +742,54 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                     expressions,
                     cases,
                     ranges,
+                    patterns,
                     def_case,
-                    ..
+                    values,
                 },
             ) = &mut **x;
 
+            // Case values are no longer kept in sync with the cases
+            values.clear();
+
             optimize_expr(match_expr, state, false);
 
             // Optimize blocks
-            for b in expressions.iter_mut() {
-                optimize_expr(&mut b.condition, state, false);
+            for (index, b) in expressions.iter_mut().enumerate() {
+                let orig_vars_len = state.variables.len();
+
+                // Variables bound by patterns shadow any constants
+                patterns
+                    .iter()
+                    .filter(|p| p.index == index)
+                    .flat_map(|p| p.pattern.bindings())
+                    .for_each(|var| state.push_var(var.name.as_str(), AccessMode::ReadWrite, None));
+
+                optimize_case_condition(&mut b.condition, state);
                 optimize_expr(&mut b.expr, state, false);
 
+                state.restore_var(orig_vars_len);
+
                 if b.is_always_false() && !b.expr.is_unit() {
                     b.expr = Expr::Unit(b.expr.position());
                     state.set_dirty();
                 }
             }
 
+            // Cases removed because they can never match
+            let mut unreachable = StaticVec::<usize>::new_const();
+            let mut remove = |index: usize| {
+                if !unreachable.contains(&index) {
+                    unreachable.push(index);
+                }
+            };
+
             // Remove false cases
             cases.retain(|_, list| {
                 // Remove all entries that have false conditions
                 list.retain(|index| {
                     if expressions[*index].is_always_false() {
                         state.set_dirty();
+                        remove(*index);
                         false
                     } else {
                         true
@@ -716,7 +802,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                 {
                     if n + 1 < list.len() {
                         state.set_dirty();
-                        list.truncate(n + 1);
+                        list.drain(n + 1..).for_each(&mut remove);
                     }
                 }
                 // Remove if no entry left
@@ -732,12 +818,30 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
             ranges.retain(|r| {
                 if expressions[r.index()].is_always_false() {
                     state.set_dirty();
+                    remove(r.index());
+                    false
+                } else {
+                    true
+                }
+            });
+
+            // Remove false patterns
+            patterns.retain(|p| {
+                if expressions[p.index].is_always_false() {
+                    state.set_dirty();
+                    remove(p.index);
                     false
                 } else {
                     true
                 }
             });
 
+            for index in unreachable {
+                let b = &expressions[index];
+                let pos = b.condition.position().or_else(b.expr.position());
+                state.report_warning(ParseWarning::UnreachableSwitchCase, pos);
+            }
+
             if let Some(index) = def_case {
                 optimize_expr(&mut expressions[*index].expr, state, false);
             }
@@ -747,6 +851,7 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
                 if *def_case == Some(index)
                     || cases.values().flat_map(|c| c.iter()).any(|&n| n == index)
                     || ranges.iter().any(|r| r.index() == index)
+                    || patterns.iter().any(|p| p.index == index)
                 {
                     continue;
                 }
@@ -835,6 +940,8 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         Stmt::Var(x, options, ..) if !options.contains(ASTFlags::CONSTANT) => {
             optimize_expr(&mut x.1, state, false);
         }
+        // let (id, ...) = expr;
+        Stmt::VarTuple(x, ..) => optimize_expr(&mut x.1, state, false),
         // import expr as var;
         #[cfg(not(feature = "no_module"))]
         Stmt::Import(x, ..) => optimize_expr(&mut x.0, state, false),
@@ -917,11 +1024,29 @@ fn optimize_stmt(stmt: &mut Stmt, state: &mut OptimizerState, preserve_result: b
         // return expr;
         Stmt::Return(Some(ref mut expr), ..) => optimize_expr(expr, state, false),
 
+        // yield expr;
+        #[cfg(not(feature = "no_function"))]
+        Stmt::Yield(ref mut expr, ..) => optimize_expr(expr, state, false),
+
         // All other statements - skip
         _ => (),
     }
 }
 
+/// Optimize the condition of a `switch` case, evaluating calls to pure script-defined functions
+/// with constant arguments.
+fn optimize_case_condition(condition: &mut Expr, state: &mut OptimizerState) {
+    #[cfg(not(feature = "no_function"))]
+    let orig_eval_pure_fns = mem::replace(&mut state.eval_pure_fns, true);
+
+    optimize_expr(condition, state, false);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        state.eval_pure_fns = orig_eval_pure_fns;
+    }
+}
+
 /// Optimize an [expression][Expr].
 fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
     // These keywords are handled specially
@@ -1085,6 +1210,13 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
         // [ items .. ]
         #[cfg(not(feature = "no_index"))]
         Expr::Array(x, ..) => x.iter_mut().for_each(|expr| optimize_expr(expr, state, false)),
+        // ( constant, .. )
+        Expr::Tuple(..) if expr.is_constant() => {
+            state.set_dirty();
+            *expr = Expr::DynamicConstant(expr.get_literal_value().unwrap().into(), expr.position());
+        }
+        // ( items, .. )
+        Expr::Tuple(x, ..) => x.iter_mut().for_each(|expr| optimize_expr(expr, state, false)),
         // #{ key:constant, .. }
         #[cfg(not(feature = "no_object"))]
         Expr::Map(..) if expr.is_constant() => {
@@ -1157,6 +1289,22 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
             x.args.iter_mut().for_each(|a| optimize_expr(a, state, false));
         }
 
+        // Call pure script-defined functions
+        #[cfg(not(feature = "no_function"))]
+        Expr::FnCall(x, pos)
+                if state.eval_pure_fns // only in `switch` case conditions
+                && !x.is_qualified() // non-qualified
+                && x.args.iter().all(Expr::is_constant) // all arguments are constants
+                && state.pure_fns.contains(&calc_fn_hash(&x.name, x.args.len()))
+        => {
+            let arg_values = &mut x.args.iter().map(|e| e.get_literal_value().unwrap()).collect::<StaticVec<_>>();
+
+            if let Some(result) = state.call_pure_fn_with_constant_arguments(&x.name, arg_values) {
+                state.set_dirty();
+                *expr = Expr::from_dynamic(result, *pos);
+            }
+        }
+
         // Call built-in operators
         Expr::FnCall(x, pos)
                 if !x.is_qualified() // Non-qualified
@@ -1181,7 +1329,7 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
                     return;
                 }
                 // Overloaded operators can override built-in.
-                _ if x.args.len() == 2 && (state.engine.fast_operators() || !has_native_fn_override(state.engine, x.hashes.native, &arg_types)) => {
+                _ if x.args.len() == 2 && ((state.engine.fast_operators() && !is_overridable_binary_op(arg_types[0], arg_types[1])) || !state.engine.has_native_fn_override(x.hashes.native, &arg_types)) => {
                     if let Some(result) = get_builtin_binary_op_fn(&x.name, &arg_values[0], &arg_values[1])
                         .and_then(|f| {
                             #[cfg(not(feature = "no_function"))]
@@ -1198,6 +1346,22 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
                             return;
                         }
                 }
+                _ if x.args.len() == 1 && (state.engine.fast_operators() || !state.engine.has_native_fn_override(x.hashes.native, &arg_types)) => {
+                    if let Some(result) = get_builtin_unary_op_fn(&x.name, &arg_values[0])
+                        .and_then(|f| {
+                            #[cfg(not(feature = "no_function"))]
+                            let lib = state.lib;
+                            #[cfg(feature = "no_function")]
+                            let lib = &[];
+
+                            let context = (state.engine, &x.name, lib).into();
+                            (f)(context, &mut [ &mut arg_values[0] ]).ok()
+                        }) {
+                            state.set_dirty();
+                            *expr = Expr::from_dynamic(result, *pos);
+                            return;
+                        }
+                }
                 _ => ()
             }
 
@@ -1299,19 +1463,23 @@ fn optimize_expr(expr: &mut Expr, state: &mut OptimizerState, _chaining: bool) {
 /// Optimize a block of [statements][Stmt] at top level.
 ///
 /// Constants and variables from the scope are added.
+///
+/// Returns the optimized statements, the number of optimization passes made and the warnings
+/// raised.
 fn optimize_top_level(
     statements: StmtBlockContainer,
     engine: &Engine,
     scope: &Scope,
     #[cfg(not(feature = "no_function"))] lib: &[&crate::Module],
+    #[cfg(not(feature = "no_function"))] pure_fns: &std::collections::BTreeSet<u64>,
     optimization_level: OptimizationLevel,
-) -> StmtBlockContainer {
+) -> (StmtBlockContainer, usize, Vec<(ParseWarning, Position)>) {
     let mut statements = statements;
 
     // If optimization level is None then skip optimizing
     if optimization_level == OptimizationLevel::None {
         statements.shrink_to_fit();
-        return statements;
+        return (statements, 0, Vec::new());
     }
 
     // Set up the state
@@ -1319,6 +1487,8 @@ fn optimize_top_level(
         engine,
         #[cfg(not(feature = "no_function"))]
         lib,
+        #[cfg(not(feature = "no_function"))]
+        pure_fns,
         optimization_level,
     );
 
@@ -1332,19 +1502,144 @@ fn optimize_top_level(
         state.push_var(name, AccessMode::ReadOnly, Some(value.clone()));
     }
 
-    // Add constants and variables from the scope
+    // Add variables from the frozen base layer of the scope, which are all read-only
+    if let Some(base) = scope.base() {
+        for (name, .., value) in base.iter() {
+            state.push_var(name, AccessMode::ReadOnly, Some(value));
+        }
+    }
+
+    // Add constants and variables from the scope, but do not propagate constants that may be
+    // written to if permitted by the `on_invalid_assignment` callback
+    let propagate_constants = engine.invalid_assignment.is_none();
+
     for (name, constant, value) in scope.iter() {
-        if constant {
+        if constant && propagate_constants {
             state.push_var(name, AccessMode::ReadOnly, Some(value));
         } else {
             state.push_var(name, AccessMode::ReadWrite, None);
         }
     }
 
-    optimize_stmt_block(statements, &mut state, true, false, true)
+    let statements = optimize_stmt_block(statements, &mut state, true, false, true);
+
+    (statements, state.passes, state.warnings)
+}
+
+/// Has an operator a Rust-native override for any parameter types?
+#[cfg(not(feature = "no_function"))]
+fn has_any_native_operator_override(engine: &Engine, name: &str, num_params: usize) -> bool {
+    let is_override = |f: &crate::module::FuncInfo| f.name == name && f.num_params == num_params;
+
+    // First check the global namespace and packages, but skip modules that are standard because
+    // they should never conflict with system functions.
+    let result = engine
+        .global_modules
+        .iter()
+        .filter(|m| !m.standard)
+        .any(|m| m.iter_fn().any(is_override));
+
+    // Then check global functions in sub-modules
+    #[cfg(not(feature = "no_module"))]
+    fn has_global_fn(
+        module: &crate::Module,
+        is_override: &impl Fn(&crate::module::FuncInfo) -> bool,
+    ) -> bool {
+        module
+            .iter_fn()
+            .any(|f| f.namespace == crate::FnNamespace::Global && is_override(f))
+            || module
+                .iter_sub_modules()
+                .any(|(_, m)| has_global_fn(m, is_override))
+    }
+
+    #[cfg(not(feature = "no_module"))]
+    let result = result
+        || engine
+            .global_sub_modules
+            .values()
+            .any(|m| has_global_fn(m, &is_override));
+
+    result
+}
+
+/// Is a script-defined function pure, i.e. without side effects and with a result depending only
+/// on its arguments, so that calls to it with constant arguments can be evaluated at compile time?
+///
+/// To keep evaluation at compile time bounded, a pure function has no loops, and it calls only
+/// operators and other pure functions, without recursion. Operators overloaded by the
+/// application may have side effects, so they are not considered pure.
+#[cfg(not(feature = "no_function"))]
+fn is_pure_script_fn(
+    engine: &Engine,
+    fn_def: &crate::ast::ScriptFnDef,
+    functions: &[crate::Shared<crate::ast::ScriptFnDef>],
+    visiting: &mut StaticVec<u64>,
+) -> bool {
+    let hash = calc_fn_hash(&fn_def.name, fn_def.params.len());
+
+    if visiting.contains(&hash) {
+        return false;
+    }
+
+    visiting.push(hash);
+
+    let mut is_pure_call = |x: &crate::ast::FnCallExpr| {
+        if x.is_qualified() {
+            return false;
+        }
+
+        match functions
+            .iter()
+            .find(|f| f.name == x.name && f.params.len() == x.args.len())
+        {
+            Some(f) => is_pure_script_fn(engine, f, functions, visiting),
+            None => {
+                x.is_native_operator
+                    && !has_any_native_operator_override(engine, &x.name, x.args.len())
+            }
+        }
+    };
+
+    let path = &mut Vec::new();
+
+    let is_pure = fn_def.body.iter().all(|stmt| {
+        stmt.walk(path, &mut |path| match path.last().expect("not empty") {
+            crate::ast::ASTNode::Stmt(Stmt::FnCall(x, ..))
+            | crate::ast::ASTNode::Expr(Expr::FnCall(x, ..)) => is_pure_call(x),
+
+            crate::ast::ASTNode::Stmt(stmt) => matches!(
+                stmt,
+                Stmt::Noop(..)
+                    | Stmt::If(..)
+                    | Stmt::Switch(..)
+                    | Stmt::Var(..)
+                    | Stmt::VarTuple(..)
+                    | Stmt::Assignment(..)
+                    | Stmt::Block(..)
+                    | Stmt::TryCatch(..)
+                    | Stmt::Expr(..)
+                    | Stmt::Return(..)
+            ),
+
+            crate::ast::ASTNode::Expr(expr @ Expr::Variable(x, ..)) => {
+                expr.is_variable_access(true) && x.3 != crate::engine::KEYWORD_THIS
+            }
+            crate::ast::ASTNode::Expr(Expr::MethodCall(..)) => false,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            crate::ast::ASTNode::Expr(Expr::Custom(..)) => false,
+            crate::ast::ASTNode::Expr(..) => true,
+        })
+    });
+
+    visiting.pop();
+
+    is_pure
 }
 
 /// Optimize an [`AST`].
+///
+/// Returns the optimized [`AST`], the number of optimization passes made and the warnings raised.
 pub fn optimize_into_ast(
     engine: &Engine,
     scope: &Scope,
@@ -1353,23 +1648,54 @@ pub fn optimize_into_ast(
         crate::Shared<crate::ast::ScriptFnDef>,
     >,
     optimization_level: OptimizationLevel,
-) -> AST {
+) -> (AST, usize, Vec<(ParseWarning, Position)>) {
     let mut statements = statements;
+    let mut passes = 0;
+    let mut warnings = Vec::new();
+
+    // Calls to pure functions cannot be evaluated at compile time if variable access or
+    // evaluation can be intercepted
+    #[cfg(not(feature = "no_function"))]
+    let can_eval_fns = engine.resolve_var.is_none();
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(feature = "debugging")]
+    let can_eval_fns = can_eval_fns && engine.debugger.is_none();
+
+    #[cfg(not(feature = "no_function"))]
+    let pure_fns: std::collections::BTreeSet<_> =
+        if can_eval_fns && optimization_level != OptimizationLevel::None {
+            functions
+                .iter()
+                .filter(|f| is_pure_script_fn(engine, f, &functions, &mut StaticVec::new_const()))
+                .map(|f| calc_fn_hash(&f.name, f.params.len()))
+                .collect()
+        } else {
+            Default::default()
+        };
 
     #[cfg(not(feature = "no_function"))]
     let lib = {
         let mut module = crate::Module::new();
 
         if optimization_level != OptimizationLevel::None {
-            // We only need the script library's signatures for optimization purposes
+            // We only need the script library's signatures for optimization purposes,
+            // plus the bodies of pure functions for evaluating calls to them
             let mut lib2 = crate::Module::new();
 
             for fn_def in &functions {
+                let is_pure = pure_fns.contains(&calc_fn_hash(&fn_def.name, fn_def.params.len()));
+
                 lib2.set_script_fn(crate::ast::ScriptFnDef {
                     name: fn_def.name.clone(),
                     access: fn_def.access,
-                    body: crate::ast::StmtBlock::NONE,
+                    visibility: fn_def.visibility,
+                    body: if is_pure {
+                        fn_def.body.clone()
+                    } else {
+                        crate::ast::StmtBlock::NONE
+                    },
                     params: fn_def.params.clone(),
+                    is_generator: fn_def.is_generator,
                     #[cfg(not(feature = "no_module"))]
                     environ: None,
                     #[cfg(not(feature = "no_function"))]
@@ -1386,7 +1712,11 @@ pub fn optimize_into_ast(
                 // Optimize the function body
                 let body = mem::take(&mut *fn_def.body);
 
-                *fn_def.body = optimize_top_level(body, engine, scope, lib2, optimization_level);
+                let (body, n, w) =
+                    optimize_top_level(body, engine, scope, lib2, &pure_fns, optimization_level);
+                *fn_def.body = body;
+                passes += n;
+                warnings.extend(w);
 
                 module.set_script_fn(fn_def);
             }
@@ -1401,19 +1731,32 @@ pub fn optimize_into_ast(
 
     statements.shrink_to_fit();
 
-    AST::new(
-        match optimization_level {
-            OptimizationLevel::None => statements,
-            OptimizationLevel::Simple | OptimizationLevel::Full => optimize_top_level(
+    let statements = match optimization_level {
+        OptimizationLevel::None => statements,
+        OptimizationLevel::Simple | OptimizationLevel::Full => {
+            let (statements, n, w) = optimize_top_level(
                 statements,
                 engine,
                 scope,
                 #[cfg(not(feature = "no_function"))]
                 &[&lib],
+                #[cfg(not(feature = "no_function"))]
+                &pure_fns,
                 optimization_level,
-            ),
-        },
-        #[cfg(not(feature = "no_function"))]
-        lib,
+            );
+            passes += n;
+            warnings.extend(w);
+            statements
+        }
+    };
+
+    (
+        AST::new(
+            statements,
+            #[cfg(not(feature = "no_function"))]
+            lib,
+        ),
+        passes,
+        warnings,
     )
 }
@@ -1,6 +1,6 @@
 #![cfg(not(feature = "no_index"))]
 
-use crate::engine::OP_EQUALS;
+use crate::engine::{OP_EQUALS, OP_LESS_THAN};
 use crate::eval::{calc_index, calc_offset_len};
 use crate::plugin::*;
 use crate::{
@@ -9,7 +9,12 @@ use crate::{
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
-use std::{any::TypeId, cmp::Ordering, mem};
+use std::{
+    any::{type_name, TypeId},
+    cmp::Ordering,
+    convert::TryFrom,
+    mem,
+};
 
 def_package! {
     /// Package of basic array utilities.
@@ -230,9 +235,12 @@ pub mod array_functions {
         #[cfg(not(feature = "unchecked"))]
         {
             if _ctx.engine().max_array_size() > 0 && len > _ctx.engine().max_array_size() {
-                return Err(
-                    ERR::ErrorDataTooLarge("Size of array".to_string(), Position::NONE).into(),
-                );
+                return Err(ERR::ErrorDataTooLarge(
+                    "Size of array".to_string(),
+                    Default::default(),
+                    Position::NONE,
+                )
+                .into());
             }
 
             let check_sizes = match item.0 {
@@ -627,6 +635,73 @@ pub mod array_functions {
             result
         }
     }
+    /// Return all contiguous windows of length `size` in the array, as an array of arrays.
+    /// The windows overlap.
+    ///
+    /// * If `size` ≤ 0, an empty array is returned.
+    /// * If `size` > length of array, an empty array is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// let y = x.windows(3);
+    ///
+    /// print(y);       // prints "[[1, 2, 3], [2, 3, 4], [3, 4, 5]]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn windows(ctx: NativeCallContext, array: &mut Array, size: INT) -> RhaiResultOf<Array> {
+        let size = match usize::try_from(size) {
+            Ok(size) if size > 0 && size <= array.len() => size,
+            _ => return Ok(Array::new()),
+        };
+
+        let _ctx = ctx;
+
+        // Check if the windows will be over max size limit before allocating them
+        #[cfg(not(feature = "unchecked"))]
+        if _ctx.engine().max_array_size() > 0
+            && (array.len() - size + 1).saturating_mul(size + 1) > _ctx.engine().max_array_size()
+        {
+            return Err(ERR::ErrorDataTooLarge(
+                "Size of array".to_string(),
+                Default::default(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(array
+            .windows(size)
+            .map(|w| Dynamic::from_array(w.to_vec()))
+            .collect())
+    }
+    /// Split the array into consecutive chunks of length `size`, as an array of arrays.
+    /// The last chunk may be shorter than `size`.
+    ///
+    /// If `size` ≤ 0, an empty array is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4, 5];
+    ///
+    /// let y = x.chunks(2);
+    ///
+    /// print(y);       // prints "[[1, 2], [3, 4], [5]]"
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn chunks(array: &mut Array, size: INT) -> Array {
+        if size <= 0 || array.is_empty() {
+            return Array::new();
+        }
+
+        array
+            .chunks(usize::try_from(size).unwrap_or(usize::MAX))
+            .map(|c| Dynamic::from_array(c.to_vec()))
+            .collect()
+    }
     /// Iterate through all the elements in the array, applying a `mapper` function to each element
     /// in turn, and return the results as a new array.
     ///
@@ -716,6 +791,224 @@ pub mod array_functions {
     ) -> RhaiResultOf<Array> {
         map(ctx, array, FnPtr::new(mapper)?)
     }
+    /// Iterate through all the elements in the array, applying a `mapper` function to each element
+    /// in turn, and return the results flattened into a new array.
+    ///
+    /// If the `mapper` function returns an array, its elements are added to the result in order;
+    /// otherwise, the returned value is added as a single element.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3];
+    ///
+    /// let y = x.flat_map(|v| [v, v * 10]);
+    ///
+    /// print(y);       // prints "[1, 10, 2, 20, 3, 30]"
+    ///
+    /// let y = x.flat_map(|v, i| if i == 1 { [] } else { v });
+    ///
+    /// print(y);       // prints "[1, 3]"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn flat_map(ctx: NativeCallContext, array: Array, mapper: FnPtr) -> RhaiResultOf<Array> {
+        if array.is_empty() {
+            return Ok(array);
+        }
+
+        let mut ar = Array::with_capacity(array.len());
+
+        for (i, item) in array.into_iter().enumerate() {
+            let value = mapper
+                .call_raw(&ctx, None, [item.clone()])
+                .or_else(|err| match *err {
+                    ERR::ErrorFunctionNotFound(fn_sig, ..)
+                        if fn_sig.starts_with(mapper.fn_name()) =>
+                    {
+                        mapper.call_raw(&ctx, None, [item, (i as INT).into()])
+                    }
+                    _ => Err(err),
+                })
+                .map_err(|err| {
+                    Box::new(ERR::ErrorInFunctionCall(
+                        "flat_map".to_string(),
+                        ctx.source().unwrap_or("").to_string(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?;
+
+            if value.is::<Array>() {
+                ar.extend(value.into_array().expect("`Array`"));
+            } else {
+                ar.push(value);
+            }
+
+            // Check the size as the result grows, as each call may add many elements
+            #[cfg(not(feature = "unchecked"))]
+            if ctx.engine().max_array_size() > 0 && ar.len() > ctx.engine().max_array_size() {
+                return Err(ERR::ErrorDataTooLarge(
+                    "Size of array".to_string(),
+                    Default::default(),
+                    Position::NONE,
+                )
+                .into());
+            }
+        }
+
+        Ok(ar)
+    }
+    /// Iterate through all the elements in the array, applying a function named by `mapper` to each
+    /// element in turn, and return the results flattened into a new array.
+    ///
+    /// If the function returns an array, its elements are added to the result in order;
+    /// otherwise, the returned value is added as a single element.
+    ///
+    /// # Function Parameters
+    ///
+    /// A function with the same name as the value of `mapper` must exist taking these parameters:
+    ///
+    /// * `element`: copy of array element
+    /// * `index` _(optional)_: current index in the array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn twice(x) { [x, x] }
+    ///
+    /// let x = [1, 2, 3];
+    ///
+    /// let y = x.flat_map("twice");
+    ///
+    /// print(y);       // prints "[1, 1, 2, 2, 3, 3]"
+    /// ```
+    #[rhai_fn(name = "flat_map", return_raw)]
+    pub fn flat_map_by_fn_name(
+        ctx: NativeCallContext,
+        array: Array,
+        mapper: &str,
+    ) -> RhaiResultOf<Array> {
+        flat_map(ctx, array, FnPtr::new(mapper)?)
+    }
+    /// Pair up the elements of the array with the elements of another array, and return the pairs
+    /// as an array of two-element arrays.
+    ///
+    /// The result is as long as the shorter of the two arrays.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3];
+    /// let y = ["a", "b", "c", "d"];
+    ///
+    /// let z = x.zip(y);
+    ///
+    /// print(z);       // prints "[[1, "a"], [2, "b"], [3, "c"]]"
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn zip(array: &mut Array, array2: Array) -> Array {
+        let mut result = Array::with_capacity(array.len().min(array2.len()));
+
+        result.extend(
+            array
+                .iter()
+                .cloned()
+                .zip(array2)
+                .map(|(a, b)| Dynamic::from_array(vec![a, b])),
+        );
+
+        result
+    }
+    /// Iterate through the elements of the array and another array in pairs, applying a `mapper`
+    /// function to each pair in turn, and return the results as a new array.
+    ///
+    /// The result is as long as the shorter of the two arrays.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element1`: copy of element in this array
+    /// * `element2`: copy of element in the other array
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 4];
+    /// let y = [10, 20, 30];
+    ///
+    /// let z = x.zip(y, |a, b| a + b);
+    ///
+    /// print(z);       // prints "[11, 22, 33]"
+    /// ```
+    #[rhai_fn(name = "zip", return_raw, pure)]
+    pub fn zip_with(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        array2: Array,
+        mapper: FnPtr,
+    ) -> RhaiResultOf<Array> {
+        let mut ar = Array::with_capacity(array.len().min(array2.len()));
+
+        for (a, b) in array.iter().cloned().zip(array2) {
+            ar.push(mapper.call_raw(&ctx, None, [a, b]).map_err(|err| {
+                Box::new(ERR::ErrorInFunctionCall(
+                    "zip".to_string(),
+                    ctx.source().unwrap_or("").to_string(),
+                    err,
+                    Position::NONE,
+                ))
+            })?);
+        }
+
+        Ok(ar)
+    }
+    /// Split an array of pairs (i.e. two-element arrays) into two arrays, one holding the first
+    /// elements and the other holding the second elements, and return them as a two-element array.
+    ///
+    /// Missing elements in a pair are treated as `()`. Extra elements in a pair are ignored.
+    ///
+    /// An error is raised if any element in the array is not an array.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [[1, "a"], [2, "b"], [3, "c"]];
+    ///
+    /// let y = x.unzip();
+    ///
+    /// print(y);       // prints "[[1, 2, 3], ["a", "b", "c"]]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn unzip(ctx: NativeCallContext, array: &mut Array) -> RhaiResultOf<Array> {
+        let mut first = Array::with_capacity(array.len());
+        let mut second = Array::with_capacity(array.len());
+
+        for item in array.iter() {
+            let mut pair = item
+                .clone()
+                .into_array()
+                .map_err(|typ| {
+                    ERR::ErrorMismatchDataType(
+                        ctx.engine().map_type_name(type_name::<Array>()).into(),
+                        ctx.engine().map_type_name(typ).into(),
+                        Position::NONE,
+                    )
+                })?
+                .into_iter();
+
+            first.push(pair.next().unwrap_or(Dynamic::UNIT));
+            second.push(pair.next().unwrap_or(Dynamic::UNIT));
+        }
+
+        Ok(vec![
+            Dynamic::from_array(first),
+            Dynamic::from_array(second),
+        ])
+    }
 
     /// Iterate through all the elements in the array, applying a `filter` function to each element
     /// in turn, and return a copy of all elements (in order) that return `true` as a new array.
@@ -1131,6 +1424,88 @@ pub mod array_functions {
     ) -> RhaiResultOf<INT> {
         index_of_filter_starting_from(ctx, array, FnPtr::new(filter)?, start)
     }
+    /// Return the index of the partition point of the array according to the `predicate` function,
+    /// i.e. the index of the first element for which `predicate` returns `false`.
+    ///
+    /// The array is assumed to be partitioned according to `predicate`, meaning that all elements
+    /// for which it returns `true` are at the start of the array and all elements for which it
+    /// returns `false` are at the end. A binary search is used, so `predicate` is only called
+    /// O(log n) times. If the array is not partitioned, the result is unspecified.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 3, 3, 5, 6, 7];
+    ///
+    /// print(x.partition_point(|v| v < 5));    // prints 4
+    ///
+    /// print(x.partition_point(|v| v < 100));  // prints 7
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn partition_point(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        predicate: FnPtr,
+    ) -> RhaiResultOf<INT> {
+        let mut low = 0;
+        let mut high = array.len();
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+
+            if predicate
+                .call_raw(&ctx, None, [array[mid].clone()])
+                .map_err(|err| {
+                    Box::new(ERR::ErrorInFunctionCall(
+                        "partition_point".to_string(),
+                        ctx.source().unwrap_or("").to_string(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?
+                .as_bool()
+                .unwrap_or(false)
+            {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        Ok(low as INT)
+    }
+    /// Return the index of the partition point of the array according to a function named by
+    /// `predicate`, i.e. the index of the first element for which the function returns `false`.
+    ///
+    /// The array is assumed to be partitioned according to the function. A binary search is used.
+    ///
+    /// # Function Parameters
+    ///
+    /// A function with the same name as the value of `predicate` must exist taking these parameters:
+    ///
+    /// * `element`: copy of array element
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn small(x) { x < 5 }
+    ///
+    /// let x = [1, 2, 3, 3, 5, 6, 7];
+    ///
+    /// print(x.partition_point("small"));      // prints 4
+    /// ```
+    #[rhai_fn(name = "partition_point", return_raw, pure)]
+    pub fn partition_point_by_fn_name(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        predicate: &str,
+    ) -> RhaiResultOf<INT> {
+        partition_point(ctx, array, FnPtr::new(predicate)?)
+    }
     /// Return `true` if any element in the array that returns `true` when applied the `filter` function.
     ///
     /// # Function Parameters
@@ -1807,6 +2182,7 @@ pub mod array_functions {
     /// * characters
     /// * strings
     /// * booleans
+    /// * timestamps
     /// * `()`
     ///
     /// # Example
@@ -1884,12 +2260,210 @@ pub mod array_functions {
             });
             return Ok(());
         }
+        #[cfg(not(feature = "no_std"))]
+        if type_id == TypeId::of::<crate::Instant>() {
+            array.sort_by(|a, b| {
+                let a = a.read_lock::<crate::Instant>().expect("`Instant`");
+                let b = b.read_lock::<crate::Instant>().expect("`Instant`");
+                a.cmp(&*b)
+            });
+            return Ok(());
+        }
         if type_id == TypeId::of::<()>() {
             return Ok(());
         }
 
         Ok(())
     }
+    /// Sort the array by the keys returned by the `mapper` function, which is called exactly once
+    /// for each element.
+    ///
+    /// The sort is stable, i.e. elements with equal keys keep their original order.
+    ///
+    /// The operators `==` and `<` are used to compare the keys and must be defined.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = ["hello", "a", "world", "xy", "!"];
+    ///
+    /// x.sort_by_key_cached(|s| s.len);
+    ///
+    /// print(x);       // prints "["a", "!", "xy", "hello", "world"]"
+    /// ```
+    #[rhai_fn(name = "sort_by_key_cached", return_raw)]
+    pub fn sort_by_cached_key(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        mapper: FnPtr,
+    ) -> RhaiResultOf<()> {
+        if array.len() <= 1 {
+            return Ok(());
+        }
+
+        let mut keyed = Vec::with_capacity(array.len());
+
+        for (i, item) in array.iter().enumerate() {
+            let key = mapper.call_raw(&ctx, None, [item.clone()]).map_err(|err| {
+                Box::new(ERR::ErrorInFunctionCall(
+                    "sort_by_key_cached".to_string(),
+                    ctx.source().unwrap_or("").to_string(),
+                    err,
+                    Position::NONE,
+                ))
+            })?;
+            keyed.push((key, i));
+        }
+
+        let mut error = None;
+
+        keyed.sort_by(|(x, ..), (y, ..)| {
+            if error.is_some() {
+                return Ordering::Equal;
+            }
+            compare_values(&ctx, &mut x.clone(), y).unwrap_or_else(|err| {
+                error = Some(err);
+                Ordering::Equal
+            })
+        });
+
+        if let Some(err) = error {
+            return Err(err);
+        }
+
+        let mut items = mem::take(array);
+        array.extend(keyed.into_iter().map(|(.., i)| mem::take(&mut items[i])));
+
+        Ok(())
+    }
+    /// Sort the array by the keys returned by a function named by `mapper`, which is called
+    /// exactly once for each element.
+    ///
+    /// The sort is stable, i.e. elements with equal keys keep their original order.
+    ///
+    /// The operators `==` and `<` are used to compare the keys and must be defined.
+    ///
+    /// # Function Parameters
+    ///
+    /// A function with the same name as the value of `mapper` must exist taking these parameters:
+    ///
+    /// * `element`: copy of array element
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn get_len(s) { s.len }
+    ///
+    /// let x = ["hello", "a", "world", "xy", "!"];
+    ///
+    /// x.sort_by_key_cached("get_len");
+    ///
+    /// print(x);       // prints "["a", "!", "xy", "hello", "world"]"
+    /// ```
+    #[rhai_fn(name = "sort_by_key_cached", return_raw)]
+    pub fn sort_by_cached_key_by_fn_name(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        mapper: &str,
+    ) -> RhaiResultOf<()> {
+        sort_by_cached_key(ctx, array, FnPtr::new(mapper)?)
+    }
+    /// Search the sorted array for a particular `value` using a binary search.
+    ///
+    /// If `value` is found, its index is returned. If there are multiple matches, any one of them
+    /// may be returned.
+    ///
+    /// If `value` is not found, `-(insertion_point) - 1` is returned (which is always negative),
+    /// where `insertion_point` is the index at which `value` can be inserted to keep the array sorted.
+    ///
+    /// The operators `==` and `<` are used to compare elements with `value` and must be defined.
+    /// If the array is not sorted, the result is unspecified.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 3, 5, 7, 9];
+    ///
+    /// print(x.binary_search(7));      // prints 3
+    ///
+    /// print(x.binary_search(4));      // prints -3: insert at index 2
+    ///
+    /// let n = x.binary_search(4);
+    ///
+    /// if n < 0 { x.insert(-n - 1, 4); }
+    ///
+    /// print(x);       // prints "[1, 3, 4, 5, 7, 9]"
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn binary_search(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        value: Dynamic,
+    ) -> RhaiResultOf<INT> {
+        binary_search_with(array, |item| compare_values(&ctx, item, &value))
+    }
+    /// Search the sorted array using a binary search, applying the `comparer` function to compare
+    /// each element with the target.
+    ///
+    /// If a matching element is found, its index is returned. If there are multiple matches, any
+    /// one of them may be returned.
+    ///
+    /// If no element matches, `-(insertion_point) - 1` is returned (which is always negative),
+    /// where `insertion_point` is the index at which the target can be inserted to keep the array
+    /// sorted.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `element`: copy of array element
+    ///
+    /// ## Return Value
+    ///
+    /// * Any integer > 0 if `element` is greater than the target
+    /// * Zero if `element` matches the target
+    /// * Any integer < 0 if `element` is less than the target
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [#{id: 1}, #{id: 3}, #{id: 5}];
+    ///
+    /// print(x.binary_search(|v| v.id - 3));   // prints 1
+    ///
+    /// print(x.binary_search(|v| v.id - 4));   // prints -3: insert at index 2
+    /// ```
+    #[rhai_fn(name = "binary_search", return_raw, pure)]
+    pub fn binary_search_by(
+        ctx: NativeCallContext,
+        array: &mut Array,
+        comparer: FnPtr,
+    ) -> RhaiResultOf<INT> {
+        binary_search_with(array, |item| {
+            let v = comparer
+                .call_raw(&ctx, None, [item.clone()])
+                .map_err(|err| {
+                    Box::new(ERR::ErrorInFunctionCall(
+                        "binary_search".to_string(),
+                        ctx.source().unwrap_or("").to_string(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?
+                .as_int()
+                .map_err(|typ| {
+                    ERR::ErrorMismatchOutputType(
+                        ctx.engine().map_type_name(type_name::<INT>()).into(),
+                        ctx.engine().map_type_name(typ).into(),
+                        Position::NONE,
+                    )
+                })?;
+
+            Ok(v.cmp(&0))
+        })
+    }
     /// Remove all elements in the array that returns `true` when applied the `filter` function and
     /// return them as a new array.
     ///
@@ -2360,3 +2934,43 @@ pub mod array_functions {
         equals(ctx, array1, array2).map(|r| !r)
     }
 }
+
+/// Compare two values via the `==` and `<` operators.
+fn compare_values(ctx: &NativeCallContext, x: &mut Dynamic, y: &Dynamic) -> RhaiResultOf<Ordering> {
+    if ctx
+        .call_fn_raw(OP_EQUALS, true, false, &mut [&mut *x, &mut y.clone()])?
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(Ordering::Equal)
+    } else if ctx
+        .call_fn_raw(OP_LESS_THAN, true, false, &mut [&mut *x, &mut y.clone()])?
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(Ordering::Less)
+    } else {
+        Ok(Ordering::Greater)
+    }
+}
+
+/// Binary search a sorted array, returning the index of a match or `-(insertion_point) - 1`.
+fn binary_search_with(
+    array: &mut Array,
+    mut compare: impl FnMut(&mut Dynamic) -> RhaiResultOf<Ordering>,
+) -> RhaiResultOf<INT> {
+    let mut low = 0;
+    let mut high = array.len();
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        match compare(&mut array[mid])? {
+            Ordering::Equal => return Ok(mid as INT),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+        }
+    }
+
+    Ok(-(low as INT) - 1)
+}
@@ -0,0 +1,522 @@
+#![cfg(not(feature = "no_index"))]
+#![allow(non_snake_case)]
+
+use crate::eval::{calc_index, calc_offset_len};
+use crate::plugin::*;
+use crate::types::dynamic::Variant;
+use crate::{
+    def_package, Array, Dynamic, FnPtr, IntArray, NativeCallContext, Position, RhaiResult,
+    RhaiResultOf, StaticVec, ERR, INT,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{any::type_name, convert::TryFrom};
+
+#[cfg(not(feature = "no_float"))]
+use crate::{FloatArray, FLOAT};
+
+def_package! {
+    /// Package of packed numeric array utilities.
+    pub BasicPackedArrayPackage(lib) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "int_array", int_array_functions);
+        combine_with_exported_module!(lib, "packed_int", packed_basic::IntArray::functions);
+        lib.set_iterable::<IntArray>();
+
+        #[cfg(not(feature = "no_float"))]
+        {
+            combine_with_exported_module!(lib, "float_array", float_array_functions);
+            combine_with_exported_module!(lib, "packed_float", packed_float::FloatArray::functions);
+            lib.set_iterable::<FloatArray>();
+        }
+    }
+}
+
+/// Raise an error if a packed array of `len` elements would be over the maximum array size.
+#[inline]
+fn check_size(_ctx: &NativeCallContext, _len: usize) -> RhaiResultOf<()> {
+    #[cfg(not(feature = "unchecked"))]
+    if _ctx.engine().max_array_size() > 0 && _len > _ctx.engine().max_array_size() {
+        return Err(ERR::ErrorDataTooLarge(
+            "Size of array".to_string(),
+            Default::default(),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Convert a length into a `usize`, mapping negative values to zero.
+#[inline(always)]
+fn to_len(len: INT) -> usize {
+    if len < 0 {
+        0
+    } else {
+        usize::try_from(len).unwrap_or(usize::MAX)
+    }
+}
+
+/// Convert a [`Dynamic`] value into an element of a packed array.
+fn to_element<T: Variant + Clone>(ctx: &NativeCallContext, value: Dynamic) -> RhaiResultOf<T> {
+    let typ = value.type_name();
+
+    value.try_cast::<T>().ok_or_else(|| {
+        ERR::ErrorMismatchDataType(
+            ctx.engine().map_type_name(type_name::<T>()).into(),
+            ctx.engine().map_type_name(typ).into(),
+            Position::NONE,
+        )
+        .into()
+    })
+}
+
+/// Call a function pointer, retrying with the current index as an extra argument if the function
+/// is not found.
+fn call_with_index(
+    ctx: &NativeCallContext,
+    fn_name: &str,
+    fp: &FnPtr,
+    args: StaticVec<Dynamic>,
+    index: usize,
+) -> RhaiResult {
+    fp.call_raw(ctx, None, args.clone())
+        .or_else(|err| match *err {
+            ERR::ErrorFunctionNotFound(ref fn_sig, ..) if fn_sig.starts_with(fp.fn_name()) => {
+                let mut args = args;
+                args.push((index as INT).into());
+                fp.call_raw(ctx, None, args)
+            }
+            _ => Err(err),
+        })
+        .map_err(|err| {
+            ERR::ErrorInFunctionCall(
+                fn_name.to_string(),
+                ctx.source().unwrap_or("").to_string(),
+                err,
+                Position::NONE,
+            )
+            .into()
+        })
+}
+
+macro_rules! gen_packed_array_functions {
+    ($root:ident => $array:ident ( $elem:ty, $zero:expr, $ops:path )) => {
+        pub mod $root {
+            pub mod $array {
+                use super::super::*;
+                use $ops as ops;
+
+                /// Apply an operation to each pair of elements of two packed arrays of the same length.
+                fn zip_with(
+                    x: &$array,
+                    y: &$array,
+                    op: fn($elem, $elem) -> RhaiResultOf<$elem>,
+                ) -> RhaiResultOf<$array> {
+                    if x.len() != y.len() {
+                        return Err(ERR::ErrorArithmetic(
+                            format!("Array lengths do not match: {} and {}", x.len(), y.len()),
+                            Position::NONE,
+                        )
+                        .into());
+                    }
+
+                    x.iter().zip(y.iter()).map(|(&a, &b)| op(a, b)).collect()
+                }
+
+                #[export_module]
+                pub mod functions {
+                    /// Convert the packed array into an array.
+                    #[rhai_fn(pure)]
+                    pub fn to_array(array: &mut crate::$array) -> Array {
+                        array.iter().map(|&x| x.into()).collect()
+                    }
+                    /// Number of elements in the packed array.
+                    #[rhai_fn(name = "len", get = "len", pure)]
+                    pub fn len(array: &mut crate::$array) -> INT {
+                        array.len() as INT
+                    }
+                    /// Return true if the packed array is empty.
+                    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+                    pub fn is_empty(array: &mut crate::$array) -> bool {
+                        array.is_empty()
+                    }
+                    /// Clear the packed array.
+                    pub fn clear(array: &mut crate::$array) {
+                        array.clear();
+                    }
+                    /// Add a new element to the end of the packed array.
+                    #[rhai_fn(return_raw)]
+                    pub fn push(
+                        ctx: NativeCallContext,
+                        array: &mut crate::$array,
+                        value: $elem,
+                    ) -> RhaiResultOf<()> {
+                        check_size(&ctx, array.len() + 1)?;
+                        array.push(value);
+                        Ok(())
+                    }
+                    /// Remove the last element from the packed array and return it.
+                    ///
+                    /// If the packed array is empty, `()` is returned.
+                    pub fn pop(array: &mut crate::$array) -> Dynamic {
+                        array.pop().map_or(Dynamic::UNIT, Into::into)
+                    }
+                    /// Get a copy of the element at the `index` position in the packed array.
+                    ///
+                    /// * If `index` < 0, position counts from the end of the array (`-1` is the last element).
+                    /// * If `index` is out of bounds, `()` is returned.
+                    #[rhai_fn(pure)]
+                    pub fn get(array: &mut crate::$array, index: INT) -> Dynamic {
+                        let (index, ..) = calc_offset_len(array.len(), index, 0);
+                        array.get(index).map_or(Dynamic::UNIT, |&x| x.into())
+                    }
+                    /// Get the element at the `index` position in the packed array.
+                    #[rhai_fn(index_get, return_raw, pure)]
+                    pub fn index_get(array: &mut crate::$array, index: INT) -> RhaiResultOf<$elem> {
+                        let len = array.len();
+                        let i = calc_index(len, index, true, || {
+                            Err(ERR::ErrorArrayBounds(len, index, Position::NONE))
+                        })?;
+                        Ok(array[i])
+                    }
+                    /// Set the element at the `index` position in the packed array.
+                    #[rhai_fn(index_set, return_raw)]
+                    pub fn index_set(
+                        array: &mut crate::$array,
+                        index: INT,
+                        value: $elem,
+                    ) -> RhaiResultOf<()> {
+                        let len = array.len();
+                        let i = calc_index(len, index, true, || {
+                            Err(ERR::ErrorArrayBounds(len, index, Position::NONE))
+                        })?;
+                        array[i] = value;
+                        Ok(())
+                    }
+
+                    /// Add two packed arrays of the same length element by element.
+                    #[rhai_fn(name = "+", return_raw, pure)]
+                    pub fn add(x: &mut crate::$array, y: $array) -> RhaiResultOf<$array> {
+                        zip_with(x, &y, ops::add)
+                    }
+                    /// Subtract two packed arrays of the same length element by element.
+                    #[rhai_fn(name = "-", return_raw, pure)]
+                    pub fn subtract(x: &mut crate::$array, y: $array) -> RhaiResultOf<$array> {
+                        zip_with(x, &y, ops::subtract)
+                    }
+                    /// Multiply two packed arrays of the same length element by element.
+                    #[rhai_fn(name = "*", return_raw, pure)]
+                    pub fn multiply(x: &mut crate::$array, y: $array) -> RhaiResultOf<$array> {
+                        zip_with(x, &y, ops::multiply)
+                    }
+                    /// Divide two packed arrays of the same length element by element.
+                    #[rhai_fn(name = "/", return_raw, pure)]
+                    pub fn divide(x: &mut crate::$array, y: $array) -> RhaiResultOf<$array> {
+                        zip_with(x, &y, ops::divide)
+                    }
+                    /// Add a number to every element of the packed array.
+                    #[rhai_fn(name = "+", return_raw, pure)]
+                    pub fn add_scalar(x: &mut crate::$array, y: $elem) -> RhaiResultOf<$array> {
+                        x.iter().map(|&a| ops::add(a, y)).collect()
+                    }
+                    /// Subtract a number from every element of the packed array.
+                    #[rhai_fn(name = "-", return_raw, pure)]
+                    pub fn subtract_scalar(
+                        x: &mut crate::$array,
+                        y: $elem,
+                    ) -> RhaiResultOf<$array> {
+                        x.iter().map(|&a| ops::subtract(a, y)).collect()
+                    }
+                    /// Multiply every element of the packed array by a number.
+                    #[rhai_fn(name = "*", return_raw, pure)]
+                    pub fn multiply_scalar(
+                        x: &mut crate::$array,
+                        y: $elem,
+                    ) -> RhaiResultOf<$array> {
+                        x.iter().map(|&a| ops::multiply(a, y)).collect()
+                    }
+                    /// Divide every element of the packed array by a number.
+                    #[rhai_fn(name = "/", return_raw, pure)]
+                    pub fn divide_scalar(x: &mut crate::$array, y: $elem) -> RhaiResultOf<$array> {
+                        x.iter().map(|&a| ops::divide(a, y)).collect()
+                    }
+                    /// Return the sum of all elements in the packed array.
+                    #[rhai_fn(return_raw, pure)]
+                    pub fn sum(array: &mut crate::$array) -> RhaiResultOf<$elem> {
+                        array.iter().try_fold($zero, |s, &x| ops::add(s, x))
+                    }
+                    /// Return the smallest element in the packed array.
+                    ///
+                    /// If the packed array is empty, `()` is returned.
+                    #[rhai_fn(pure)]
+                    pub fn min(array: &mut crate::$array) -> Dynamic {
+                        array
+                            .iter()
+                            .copied()
+                            .reduce(|m, x| if x < m { x } else { m })
+                            .map_or(Dynamic::UNIT, Into::into)
+                    }
+                    /// Return the largest element in the packed array.
+                    ///
+                    /// If the packed array is empty, `()` is returned.
+                    #[rhai_fn(pure)]
+                    pub fn max(array: &mut crate::$array) -> Dynamic {
+                        array
+                            .iter()
+                            .copied()
+                            .reduce(|m, x| if x > m { x } else { m })
+                            .map_or(Dynamic::UNIT, Into::into)
+                    }
+                    /// Iterate through all the elements in the packed array, applying a `mapper`
+                    /// function to each element in turn, and return the results as a new packed array
+                    /// of the same type.
+                    ///
+                    /// # Function Parameters
+                    ///
+                    /// * `element`: copy of array element
+                    /// * `index` _(optional)_: current index in the array
+                    #[rhai_fn(return_raw, pure)]
+                    pub fn map(
+                        ctx: NativeCallContext,
+                        array: &mut crate::$array,
+                        mapper: FnPtr,
+                    ) -> RhaiResultOf<$array> {
+                        array
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &x)| {
+                                let args = std::iter::once(x.into()).collect();
+                                let value = call_with_index(&ctx, "map", &mapper, args, i)?;
+                                to_element::<$elem>(&ctx, value)
+                            })
+                            .collect()
+                    }
+                    /// Reduce a packed array by iterating through all elements while applying the
+                    /// `reducer` function, starting from `()`.
+                    ///
+                    /// # Function Parameters
+                    ///
+                    /// * `result`: accumulated result, initially `()`
+                    /// * `element`: copy of array element
+                    /// * `index` _(optional)_: current index in the array
+                    #[rhai_fn(return_raw, pure)]
+                    pub fn reduce(
+                        ctx: NativeCallContext,
+                        array: &mut crate::$array,
+                        reducer: FnPtr,
+                    ) -> RhaiResult {
+                        reduce_with_initial(ctx, array, reducer, Dynamic::UNIT)
+                    }
+                    /// Reduce a packed array by iterating through all elements while applying the
+                    /// `reducer` function, starting from `initial`.
+                    ///
+                    /// # Function Parameters
+                    ///
+                    /// * `result`: accumulated result, starting with the value of `initial`
+                    /// * `element`: copy of array element
+                    /// * `index` _(optional)_: current index in the array
+                    #[rhai_fn(name = "reduce", return_raw, pure)]
+                    pub fn reduce_with_initial(
+                        ctx: NativeCallContext,
+                        array: &mut crate::$array,
+                        reducer: FnPtr,
+                        initial: Dynamic,
+                    ) -> RhaiResult {
+                        let mut result = initial;
+
+                        for (i, &x) in array.iter().enumerate() {
+                            let args = IntoIterator::into_iter([result, x.into()]).collect();
+                            result = call_with_index(&ctx, "reduce", &reducer, args, i)?;
+                        }
+
+                        Ok(result)
+                    }
+                    /// Return `true` if two packed arrays contain the same elements.
+                    #[rhai_fn(name = "==", pure)]
+                    pub fn equals(x: &mut crate::$array, y: $array) -> bool {
+                        *x == y
+                    }
+                    /// Return `true` if two packed arrays do not contain the same elements.
+                    #[rhai_fn(name = "!=", pure)]
+                    pub fn not_equals(x: &mut crate::$array, y: $array) -> bool {
+                        *x != y
+                    }
+                    /// Convert the packed array into a string.
+                    #[rhai_fn(
+                        name = "print",
+                        name = "to_string",
+                        name = "debug",
+                        name = "to_debug",
+                        pure
+                    )]
+                    pub fn to_string(array: &mut crate::$array) -> String {
+                        format!("{:?}", array)
+                    }
+                }
+            }
+        }
+    };
+}
+
+gen_packed_array_functions!(packed_basic => IntArray(INT, 0, crate::packages::arithmetic::arith_basic::INT::functions));
+
+#[cfg(not(feature = "no_float"))]
+gen_packed_array_functions!(packed_float => FloatArray(FLOAT, 0.0, crate::packages::array_packed::float_ops));
+
+/// Floating-point arithmetic for [`FloatArray`].
+#[cfg(not(feature = "no_float"))]
+mod float_ops {
+    use super::*;
+
+    #[inline(always)]
+    pub fn add(x: FLOAT, y: FLOAT) -> RhaiResultOf<FLOAT> {
+        Ok(x + y)
+    }
+    #[inline(always)]
+    pub fn subtract(x: FLOAT, y: FLOAT) -> RhaiResultOf<FLOAT> {
+        Ok(x - y)
+    }
+    #[inline(always)]
+    pub fn multiply(x: FLOAT, y: FLOAT) -> RhaiResultOf<FLOAT> {
+        Ok(x * y)
+    }
+    #[inline(always)]
+    pub fn divide(x: FLOAT, y: FLOAT) -> RhaiResultOf<FLOAT> {
+        Ok(x / y)
+    }
+}
+
+#[export_module]
+mod int_array_functions {
+    /// Return a new, empty packed array of integers.
+    #[rhai_fn(name = "int_array")]
+    pub const fn new_int_array() -> IntArray {
+        IntArray::new()
+    }
+    /// Return a new packed array of integers of the specified length, filled with zeros.
+    ///
+    /// If `len` ≤ 0, an empty packed array is returned.
+    #[rhai_fn(name = "int_array", return_raw)]
+    pub fn int_array_with_len(ctx: NativeCallContext, len: INT) -> RhaiResultOf<IntArray> {
+        int_array_with_len_and_value(ctx, len, 0)
+    }
+    /// Return a new packed array of integers of the specified length, filled with copies of the
+    /// initial `value`.
+    ///
+    /// If `len` ≤ 0, an empty packed array is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let a = int_array(3, 42);
+    ///
+    /// print(a);           // prints "[42, 42, 42]"
+    /// ```
+    #[rhai_fn(name = "int_array", return_raw)]
+    pub fn int_array_with_len_and_value(
+        ctx: NativeCallContext,
+        len: INT,
+        value: INT,
+    ) -> RhaiResultOf<IntArray> {
+        let len = to_len(len);
+        check_size(&ctx, len)?;
+        Ok(vec![value; len].into())
+    }
+    /// Convert an array into a packed array of integers.
+    ///
+    /// An error is raised if any element of the array is not an integer.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let a = int_array([1, 2, 3]);
+    ///
+    /// print(a.sum());     // prints 6
+    /// ```
+    #[rhai_fn(name = "int_array", name = "to_int_array", return_raw, pure)]
+    pub fn to_int_array(ctx: NativeCallContext, array: &mut Array) -> RhaiResultOf<IntArray> {
+        array
+            .iter()
+            .map(|x| to_element::<INT>(&ctx, x.flatten_clone()))
+            .collect()
+    }
+    /// Convert a packed array of floating-point numbers into a packed array of integers,
+    /// discarding the fractional parts.
+    ///
+    /// Not available under `no_float`.
+    #[cfg(not(feature = "no_float"))]
+    #[rhai_fn(name = "to_int_array", pure)]
+    pub fn float_array_to_int_array(array: &mut FloatArray) -> IntArray {
+        array.iter().map(|&x| x as INT).collect()
+    }
+}
+
+#[cfg(not(feature = "no_float"))]
+#[export_module]
+mod float_array_functions {
+    /// Return a new, empty packed array of floating-point numbers.
+    #[rhai_fn(name = "float_array")]
+    pub const fn new_float_array() -> FloatArray {
+        FloatArray::new()
+    }
+    /// Return a new packed array of floating-point numbers of the specified length, filled with
+    /// zeros.
+    ///
+    /// If `len` ≤ 0, an empty packed array is returned.
+    #[rhai_fn(name = "float_array", return_raw)]
+    pub fn float_array_with_len(ctx: NativeCallContext, len: INT) -> RhaiResultOf<FloatArray> {
+        float_array_with_len_and_value(ctx, len, 0.0)
+    }
+    /// Return a new packed array of floating-point numbers of the specified length, filled with
+    /// copies of the initial `value`.
+    ///
+    /// If `len` ≤ 0, an empty packed array is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let a = float_array(3, 0.5);
+    ///
+    /// print(a);           // prints "[0.5, 0.5, 0.5]"
+    /// ```
+    #[rhai_fn(name = "float_array", return_raw)]
+    pub fn float_array_with_len_and_value(
+        ctx: NativeCallContext,
+        len: INT,
+        value: FLOAT,
+    ) -> RhaiResultOf<FloatArray> {
+        let len = to_len(len);
+        check_size(&ctx, len)?;
+        Ok(vec![value; len].into())
+    }
+    /// Convert an array into a packed array of floating-point numbers.
+    ///
+    /// Integer elements are converted into floating-point numbers.  An error is raised if any
+    /// other element of the array is not a floating-point number.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let a = float_array([1, 2.5, 3]);
+    ///
+    /// print(a.sum());     // prints 6.5
+    /// ```
+    #[rhai_fn(name = "float_array", name = "to_float_array", return_raw, pure)]
+    pub fn to_float_array(ctx: NativeCallContext, array: &mut Array) -> RhaiResultOf<FloatArray> {
+        array
+            .iter()
+            .map(|x| match x.as_int() {
+                Ok(n) => Ok(n as FLOAT),
+                Err(_) => to_element::<FLOAT>(&ctx, x.flatten_clone()),
+            })
+            .collect()
+    }
+    /// Convert a packed array of integers into a packed array of floating-point numbers.
+    #[rhai_fn(name = "to_float_array", pure)]
+    pub fn int_array_to_float_array(array: &mut IntArray) -> FloatArray {
+        array.iter().map(|&x| x as FLOAT).collect()
+    }
+}
@@ -81,9 +81,12 @@ pub mod blob_functions {
         // Check if blob will be over max size limit
         #[cfg(not(feature = "unchecked"))]
         if _ctx.engine().max_array_size() > 0 && len > _ctx.engine().max_array_size() {
-            return Err(
-                crate::ERR::ErrorDataTooLarge("Size of BLOB".to_string(), Position::NONE).into(),
-            );
+            return Err(crate::ERR::ErrorDataTooLarge(
+                "Size of BLOB".to_string(),
+                Default::default(),
+                Position::NONE,
+            )
+            .into());
         }
 
         let mut blob = Blob::new();
@@ -351,9 +354,12 @@ pub mod blob_functions {
         // Check if blob will be over max size limit
         #[cfg(not(feature = "unchecked"))]
         if _ctx.engine().max_array_size() > 0 && len > _ctx.engine().max_array_size() {
-            return Err(
-                crate::ERR::ErrorDataTooLarge("Size of BLOB".to_string(), Position::NONE).into(),
-            );
+            return Err(crate::ERR::ErrorDataTooLarge(
+                "Size of BLOB".to_string(),
+                Default::default(),
+                Position::NONE,
+            )
+            .into());
         }
 
         if len > blob.len() {
@@ -9,6 +9,10 @@ def_package! {
         lib.standard = true;
 
         combine_with_exported_module!(lib, "FnPtr", fn_ptr_functions);
+
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_index"))]
+        combine_with_exported_module!(lib, "call_stack", call_stack_functions);
     }
 }
 
@@ -45,3 +49,65 @@ mod fn_ptr_functions {
         fn_ptr.is_anonymous()
     }
 }
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+#[export_module]
+mod call_stack_functions {
+    use crate::Array;
+
+    /// Get an array of object maps, one for each active call to a script-defined function,
+    /// outermost call first.
+    ///
+    /// Each object map contains the function name (`fn_name`), its source (`source`, if any), the
+    /// line and position of the call (`line` and `position`, if any) and a text description
+    /// (`display`).
+    ///
+    /// An array of strings (the text descriptions) is returned under `no_object`.
+    ///
+    /// The array is always empty unless call stack tracking is turned on by the host.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn inner() { call_stack() }
+    /// fn outer() { inner() }
+    ///
+    /// let stack = outer();
+    ///
+    /// print(stack.len());             // prints 2
+    /// print(stack[0].fn_name);        // prints "outer"
+    /// print(stack[1].fn_name);        // prints "inner"
+    /// ```
+    pub fn call_stack(ctx: NativeCallContext) -> Array {
+        ctx.call_stack()
+            .iter()
+            .map(|frame| {
+                let display = frame.to_string();
+
+                #[cfg(not(feature = "no_object"))]
+                {
+                    let mut map = crate::Map::new();
+                    map.insert("display".into(), display.into());
+                    map.insert("fn_name".into(), frame.fn_name.clone().into());
+                    if !frame.source.is_empty() {
+                        map.insert("source".into(), frame.source.as_str().into());
+                    }
+                    if !frame.pos.is_none() {
+                        map.insert(
+                            "line".into(),
+                            (frame.pos.line().unwrap() as crate::INT).into(),
+                        );
+                        map.insert(
+                            "position".into(),
+                            (frame.pos.position().unwrap_or(0) as crate::INT).into(),
+                        );
+                    }
+                    Dynamic::from_map(map)
+                }
+                #[cfg(feature = "no_object")]
+                display.into()
+            })
+            .collect()
+    }
+}
@@ -224,6 +224,39 @@ impl ExactSizeIterator for CharsStream {
     }
 }
 
+// Lazy string iterator over lines
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct LinesStream(ImmutableString, usize);
+
+impl LinesStream {
+    #[inline(always)]
+    pub fn new(string: ImmutableString) -> Self {
+        Self(string, 0)
+    }
+}
+
+impl Iterator for LinesStream {
+    type Item = ImmutableString;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.0[self.1..];
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        let (line, consumed) = rest
+            .find('\n')
+            .map_or((rest, rest.len()), |n| (&rest[..n], n + 1));
+
+        self.1 += consumed;
+
+        Some(line.strip_suffix('\r').unwrap_or(line).into())
+    }
+}
+
+impl FusedIterator for LinesStream {}
+
 macro_rules! reg_range {
     ($lib:ident | $x:expr => $( $y:ty ),*) => {
         $(
@@ -495,6 +528,30 @@ def_package! {
             );
         }
 
+        // Register lines iterator
+        lib.set_iterator::<LinesStream>();
+
+        let _hash = lib.set_native_fn("lines", |string: ImmutableString| Ok(LinesStream::new(string)));
+        #[cfg(feature = "metadata")]
+        lib.update_fn_metadata_with_comments(
+            _hash,
+            ["string: ImmutableString", "Iterator<ImmutableString>"],
+            [
+                "/// Return a lazy iterator over the lines in the string.",
+                "///",
+                "/// Lines are separated by `\\n` or `\\r\\n`, which are not included in the lines.",
+                "/// The final line ending, if any, does not produce an empty line.",
+                "///",
+                "/// # Example",
+                "///",
+                "/// ```rhai",
+                r#"/// for line in "hello\nworld!".lines() {"#,
+                "///     print(line);",
+                "/// }",
+                "/// ```"
+            ]
+        );
+
         // Register bit-field iterator
         lib.set_iterator::<BitRange>();
 
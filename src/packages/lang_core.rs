@@ -1,7 +1,8 @@
 use crate::def_package;
 use crate::plugin::*;
 use crate::types::dynamic::Tag;
-use crate::{Dynamic, RhaiResultOf, ERR, INT, MAX_USIZE_INT};
+use crate::{Dynamic, RhaiResultOf, ERR, INT};
+use std::convert::TryFrom;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -12,6 +13,10 @@ def_package! {
 
         combine_with_exported_module!(lib, "core", core_functions);
 
+        #[cfg(not(feature = "no_function"))]
+        #[cfg(not(feature = "no_index"))]
+        combine_with_exported_module!(lib, "generator", generator_functions);
+
         #[cfg(not(feature = "no_function"))]
         #[cfg(not(feature = "no_index"))]
         #[cfg(not(feature = "no_object"))]
@@ -99,6 +104,97 @@ mod core_functions {
     }
 }
 
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+#[export_module]
+mod generator_functions {
+    use crate::{Array, Generator};
+
+    /// Run the generator to the end and return all the values it yields as an array.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn squares(n) {
+    ///     for x in 1..=n {
+    ///         yield x * x;
+    ///     }
+    /// }
+    ///
+    /// print(squares(4).collect());    // prints "[1, 4, 9, 16]"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn collect(ctx: NativeCallContext, gen: Generator) -> RhaiResultOf<Array> {
+        resume_generator(&ctx, &gen, usize::MAX)
+    }
+    /// Return an array of the next `len` values yielded by the generator, or fewer if the
+    /// generator finishes earlier.
+    ///
+    /// If `len` ≤ 0, an empty array is returned and the generator is not resumed.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// fn naturals() {
+    ///     let n = 0;
+    ///     loop {
+    ///         n += 1;
+    ///         yield n;
+    ///     }
+    /// }
+    ///
+    /// let gen = naturals();
+    ///
+    /// print(gen.take(3));     // prints "[1, 2, 3]"
+    /// print(gen.take(2));     // prints "[4, 5]"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn take(ctx: NativeCallContext, gen: Generator, len: INT) -> RhaiResultOf<Array> {
+        if len <= 0 {
+            return Ok(Array::new());
+        }
+        let len = usize::try_from(len).unwrap_or(usize::MAX);
+        resume_generator(&ctx, &gen, len)
+    }
+}
+
+/// Resume a generator up to `max` times, collecting the values it yields.
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+fn resume_generator(
+    ctx: &NativeCallContext,
+    gen: &crate::Generator,
+    max: usize,
+) -> RhaiResultOf<crate::Array> {
+    use crate::eval::{Caches, GlobalRuntimeState};
+
+    let engine = ctx.engine();
+    let global = &mut ctx
+        .global_runtime_state()
+        .cloned()
+        .unwrap_or_else(|| GlobalRuntimeState::new(engine));
+    let caches = &mut Caches::new();
+    let lib: crate::StaticVec<_> = ctx.iter_namespaces().collect();
+
+    let mut values = crate::Array::new();
+
+    while values.len() < max {
+        match engine.resume_generator(
+            global,
+            caches,
+            &lib,
+            gen,
+            ctx.position(),
+            ctx.call_level(),
+        )? {
+            Some(value) => values.push(value),
+            None => break,
+        }
+    }
+
+    Ok(values)
+}
+
 #[cfg(not(feature = "no_function"))]
 #[cfg(not(feature = "no_index"))]
 #[cfg(not(feature = "no_object"))]
@@ -113,11 +209,11 @@ mod reflection_functions {
     }
     #[rhai_fn(name = "get_fn_metadata_list")]
     pub fn get_fn_metadata2(ctx: NativeCallContext, name: &str, params: INT) -> crate::Array {
-        if params < 0 || params > MAX_USIZE_INT {
-            crate::Array::new()
-        } else {
-            collect_fn_metadata(ctx, |_, _, n, p, _| p == (params as usize) && n == name)
+        if params < 0 {
+            return crate::Array::new();
         }
+        let params = usize::try_from(params).unwrap_or(usize::MAX);
+        collect_fn_metadata(ctx, |_, _, n, p, _| p == params && n == name)
     }
 }
 
@@ -2,7 +2,10 @@
 
 use crate::engine::OP_EQUALS;
 use crate::plugin::*;
-use crate::{def_package, format_map_as_json, Dynamic, ImmutableString, Map, RhaiResultOf, INT};
+use crate::{
+    def_package, format_map_as_json, Dynamic, FnPtr, ImmutableString, Map, NativeCallContext,
+    Position, RhaiResultOf, ERR, INT,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -170,6 +173,84 @@ mod map_functions {
             }
         }
     }
+    /// Make a copy of the object map, add all property values of another object map, then
+    /// return it.
+    ///
+    /// When a property exists in both object maps, the `resolver` function is called to produce
+    /// the merged value.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `key`: name of the property
+    /// * `value1`: value of the property in the first object map
+    /// * `value2`: value of the property in the second object map
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = #{a:1, b:2, c:3};
+    /// let n = #{a: 42, d:0};
+    ///
+    /// print(m.merge(n, |key, v1, v2| v1 + v2));   // prints "#{a:43, b:2, c:3, d:0}"
+    /// ```
+    #[rhai_fn(name = "merge", return_raw)]
+    pub fn merge_with_resolver(
+        ctx: NativeCallContext,
+        map1: Map,
+        map2: Map,
+        resolver: FnPtr,
+    ) -> RhaiResultOf<Map> {
+        merge_maps(&ctx, "merge", map1, map2, Some(&resolver), false)
+    }
+    /// Make a copy of the object map, recursively add all property values of another object map,
+    /// then return it.
+    ///
+    /// When a property in both object maps holds an object map, the two are merged recursively.
+    /// Otherwise, the value in the second object map replaces the value in the first.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = #{a:1, b:#{x:1, y:2}};
+    /// let n = #{a:42, b:#{y:0, z:3}};
+    ///
+    /// print(m.deep_merge(n));     // prints "#{a:42, b:#{x:1, y:0, z:3}}"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn deep_merge(ctx: NativeCallContext, map1: Map, map2: Map) -> RhaiResultOf<Map> {
+        merge_maps(&ctx, "deep_merge", map1, map2, None, true)
+    }
+    /// Make a copy of the object map, recursively add all property values of another object map,
+    /// then return it.
+    ///
+    /// When a property in both object maps holds an object map, the two are merged recursively.
+    /// Otherwise, the `resolver` function is called to produce the merged value.
+    ///
+    /// # Function Parameters
+    ///
+    /// * `key`: name of the property
+    /// * `value1`: value of the property in the first object map
+    /// * `value2`: value of the property in the second object map
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = #{a:1, b:#{x:1, y:2}};
+    /// let n = #{a:42, b:#{y:40, z:3}};
+    ///
+    /// let r = m.deep_merge(n, |key, v1, v2| v1 + v2);
+    ///
+    /// print(r);       // prints "#{a:43, b:#{x:1, y:42, z:3}}"
+    /// ```
+    #[rhai_fn(name = "deep_merge", return_raw)]
+    pub fn deep_merge_with_resolver(
+        ctx: NativeCallContext,
+        map1: Map,
+        map2: Map,
+        resolver: FnPtr,
+    ) -> RhaiResultOf<Map> {
+        merge_maps(&ctx, "deep_merge", map1, map2, Some(&resolver), true)
+    }
     /// Return `true` if two object maps are equal (i.e. all property values are equal).
     ///
     /// The operator `==` is used to compare property values and must be defined,
@@ -293,3 +374,55 @@ mod map_functions {
         format_map_as_json(map)
     }
 }
+
+/// Merge two object maps, calling an optional `resolver` function for properties that exist in
+/// both, and optionally merging nested object maps recursively.
+fn merge_maps(
+    ctx: &NativeCallContext,
+    fn_name: &str,
+    map1: Map,
+    map2: Map,
+    resolver: Option<&FnPtr>,
+    deep: bool,
+) -> RhaiResultOf<Map> {
+    if map2.is_empty() {
+        return Ok(map1);
+    }
+    if map1.is_empty() {
+        return Ok(map2);
+    }
+
+    let mut map1 = map1;
+
+    for (key, value2) in map2 {
+        let value1 = match map1.get_mut(&key) {
+            Some(v) => v,
+            None => {
+                map1.insert(key, value2);
+                continue;
+            }
+        };
+
+        if deep && value1.is::<Map>() && value2.is::<Map>() {
+            let m1 = std::mem::take(value1).cast::<Map>();
+            let m2 = value2.cast::<Map>();
+            *value1 = merge_maps(ctx, fn_name, m1, m2, resolver, deep)?.into();
+        } else if let Some(resolver) = resolver {
+            let v1 = std::mem::take(value1);
+            *value1 = resolver
+                .call_raw(ctx, None, [key.clone().into(), v1, value2])
+                .map_err(|err| {
+                    Box::new(ERR::ErrorInFunctionCall(
+                        fn_name.to_string(),
+                        ctx.source().unwrap_or("").to_string(),
+                        err,
+                        Position::NONE,
+                    ))
+                })?;
+        } else {
+            *value1 = value2;
+        }
+    }
+
+    Ok(map1)
+}
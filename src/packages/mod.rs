@@ -4,6 +4,7 @@ use crate::{Engine, Module, Shared};
 
 pub(crate) mod arithmetic;
 pub(crate) mod array_basic;
+pub(crate) mod array_packed;
 pub(crate) mod bit_field;
 pub(crate) mod blob_basic;
 pub(crate) mod debugging;
@@ -13,15 +14,23 @@ pub(crate) mod lang_core;
 pub(crate) mod logic;
 pub(crate) mod map_basic;
 pub(crate) mod math_basic;
+pub(crate) mod ordered_map_basic;
 pub(crate) mod pkg_core;
 pub(crate) mod pkg_std;
+pub(crate) mod set_basic;
+pub(crate) mod sorted_map_basic;
+pub(crate) mod state_machine;
 pub(crate) mod string_basic;
 pub(crate) mod string_more;
+pub(crate) mod tasks;
 pub(crate) mod time_basic;
+pub(crate) mod tuple_basic;
 
 pub use arithmetic::ArithmeticPackage;
 #[cfg(not(feature = "no_index"))]
 pub use array_basic::BasicArrayPackage;
+#[cfg(not(feature = "no_index"))]
+pub use array_packed::BasicPackedArrayPackage;
 pub use bit_field::BitFieldPackage;
 #[cfg(not(feature = "no_index"))]
 pub use blob_basic::BasicBlobPackage;
@@ -34,12 +43,23 @@ pub use logic::LogicPackage;
 #[cfg(not(feature = "no_object"))]
 pub use map_basic::BasicMapPackage;
 pub use math_basic::BasicMathPackage;
+#[cfg(not(feature = "no_object"))]
+pub use ordered_map_basic::BasicOrderedMapPackage;
 pub use pkg_core::CorePackage;
 pub use pkg_std::StandardPackage;
+#[cfg(not(feature = "no_index"))]
+pub use set_basic::BasicSetPackage;
+#[cfg(not(feature = "no_index"))]
+pub use sorted_map_basic::BasicSortedMapPackage;
+#[cfg(not(feature = "no_custom_syntax"))]
+pub use state_machine::{OnStateCallback, StateMachineConfig, StateMachinePackage};
 pub use string_basic::BasicStringPackage;
 pub use string_more::MoreStringPackage;
+#[cfg(not(feature = "no_function"))]
+pub use tasks::TasksPackage;
 #[cfg(not(feature = "no_std"))]
 pub use time_basic::BasicTimePackage;
+pub use tuple_basic::BasicTuplePackage;
 
 /// Trait that all packages must implement.
 pub trait Package {
@@ -95,11 +115,36 @@ pub trait Package {
     fn as_shared_module(&self) -> Shared<Module>;
 }
 
+/// Append a line of package-level documentation to a package [module][Module].
+///
+/// This is used by [`def_package!`] to attach the doc-comments of a package to its
+/// [module][Module], which flow into the metadata and definitions output.
+///
+/// Does nothing unless the `metadata` feature is enabled.
+#[doc(hidden)]
+#[inline]
+#[allow(unused_variables)]
+pub fn append_package_doc(module: &mut Module, line: &str) {
+    #[cfg(feature = "metadata")]
+    {
+        let mut doc = crate::SmartString::from(module.doc());
+        if !doc.is_empty() {
+            doc.push('\n');
+        }
+        doc.push_str("//!");
+        doc.push_str(line);
+        module.set_doc(doc);
+    }
+}
+
 /// Macro that makes it easy to define a _package_ (which is basically a shared [module][Module])
 /// and register functions into it.
 ///
 /// Functions can be added to the package using [`Module::set_native_fn`].
 ///
+/// Doc-comments on the package are attached to its [module][Module] (under the `metadata` feature)
+/// and flow into the metadata and definitions output.
+///
 /// # Example
 ///
 /// Define a package named `MyPackage` with a single function named `my_add`:
@@ -118,14 +163,47 @@ pub trait Package {
 ///     }
 /// }
 /// ```
+///
+/// # Configuration
+///
+/// A package can accept a configuration, which must implement [`Default`], when instantiated via
+/// `with_config`.  `new` uses the default configuration, as do packages that include it as a
+/// base package.
+///
+/// ```
+/// use rhai::{Dynamic, EvalAltResult};
+/// use rhai::def_package;
+///
+/// fn add(x: i64, y: i64) -> Result<i64, Box<EvalAltResult>> { Ok(x + y) }
+/// fn mul(x: i64, y: i64) -> Result<i64, Box<EvalAltResult>> { Ok(x * y) }
+///
+/// #[derive(Default)]
+/// pub struct MyConfig {
+///     pub enable_mul: bool,
+/// }
+///
+/// def_package! {
+///     /// My configurable package.
+///     pub MyPackage(module, config: MyConfig) {
+///         module.set_native_fn("my_add", add);
+///
+///         // Only load `my_mul` when enabled.
+///         if config.enable_mul {
+///             module.set_native_fn("my_mul", mul);
+///         }
+///     }
+/// }
+///
+/// let package = MyPackage::with_config(MyConfig { enable_mul: true });
+/// ```
 #[macro_export]
 macro_rules! def_package {
-    ($($(#[$outer:meta])* $mod:vis $package:ident($lib:ident)
+    ($($(#[$($outer:tt)*])* $mod:vis $package:ident($lib:ident)
                 $( : $($(#[$base_meta:meta])* $base_pkg:ty),+ )?
                 $block:block
                 $( |> | $engine:ident | $init_engine:block )?
     )+) => { $(
-        $(#[$outer])*
+        $(#[$($outer)*])*
         $mod struct $package($crate::Shared<$crate::Module>);
 
         impl $crate::packages::Package for $package {
@@ -168,9 +246,76 @@ macro_rules! def_package {
             pub fn new() -> Self {
                 let mut module = $crate::Module::new();
                 <Self as $crate::packages::Package>::init(&mut module);
+                $( $crate::def_package_doc!(module, $($outer)*); )*
+                module.build_index();
+                Self(module.into())
+            }
+        }
+    )* };
+    ($($(#[$($outer:tt)*])* $mod:vis $package:ident($lib:ident, $config:ident : $config_ty:ty)
+                $( : $($(#[$base_meta:meta])* $base_pkg:ty),+ )?
+                $block:block
+                $( |> | $engine:ident | $init_engine:block )?
+    )+) => { $(
+        $(#[$($outer)*])*
+        $mod struct $package($crate::Shared<$crate::Module>);
+
+        impl $crate::packages::Package for $package {
+            #[inline(always)]
+            fn as_shared_module(&self) -> $crate::Shared<$crate::Module> {
+                self.0.clone()
+            }
+            #[inline]
+            fn init(module: &mut $crate::Module) {
+                Self::init_with_config(module, <$config_ty as Default>::default());
+            }
+            #[inline]
+            fn init_engine(_engine: &mut $crate::Engine) {
+                $($(
+                    $(#[$base_meta])* { <$base_pkg>::init_engine(_engine); }
+                )*)*
+
+                $(
+                    let $engine = _engine;
+                    $init_engine
+                )*
+            }
+        }
+
+        impl Default for $package {
+            #[inline(always)]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl $package {
+            #[doc=concat!("Create a new `", stringify!($package), "` with the default configuration.")]
+            #[inline]
+            #[must_use]
+            pub fn new() -> Self {
+                Self::with_config(Default::default())
+            }
+            #[doc=concat!("Create a new `", stringify!($package), "` with a configuration.")]
+            #[inline]
+            #[must_use]
+            pub fn with_config(config: $config_ty) -> Self {
+                let mut module = $crate::Module::new();
+                Self::init_with_config(&mut module, config);
+                $( $crate::def_package_doc!(module, $($outer)*); )*
                 module.build_index();
                 Self(module.into())
             }
+            #[doc=concat!("Initialize a module with the functions of `", stringify!($package), "` under a configuration.")]
+            #[inline]
+            #[allow(unused_variables)]
+            pub fn init_with_config($lib: &mut $crate::Module, $config: $config_ty) {
+                $($(
+                    $(#[$base_meta])* { <$base_pkg>::init($lib); }
+                )*)*
+
+                $block
+            }
         }
     )* };
     ($($(#[$outer:meta])* $root:ident :: $package:ident => | $lib:ident | $block:block)+) => { $(
@@ -244,3 +389,15 @@ macro_rules! def_package {
         }
     };
 }
+
+/// Attach a doc-comment attribute of a package defined by [`def_package!`] to its module.
+///
+/// Attributes other than doc-comments are ignored.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! def_package_doc {
+    ($module:ident, doc = $doc:literal) => {
+        $crate::packages::append_package_doc(&mut $module, $doc);
+    };
+    ($module:ident, $($attr:tt)*) => {};
+}
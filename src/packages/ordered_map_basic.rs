@@ -0,0 +1,234 @@
+#![cfg(not(feature = "no_object"))]
+
+use crate::eval::calc_offset_len;
+use crate::plugin::*;
+use crate::{
+    def_package, Dynamic, Map, NativeCallContext, OrderedMap, Position, RhaiResultOf, INT,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_index"))]
+use crate::Array;
+
+def_package! {
+    /// Package of basic ordered map utilities.
+    pub BasicOrderedMapPackage(lib) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "ordered_map", ordered_map_functions);
+    }
+}
+
+/// Set the value of a property in an ordered map, raising an error if the map grows too large.
+fn set_value(
+    ctx: &NativeCallContext,
+    map: &mut OrderedMap,
+    property: &str,
+    value: Dynamic,
+) -> RhaiResultOf<()> {
+    #[cfg(not(feature = "unchecked"))]
+    if ctx.engine().max_map_size() > 0
+        && map.len() >= ctx.engine().max_map_size()
+        && !map.contains_key(property)
+    {
+        return Err(crate::ERR::ErrorDataTooLarge(
+            "Size of object map".to_string(),
+            Default::default(),
+            Position::NONE,
+        )
+        .into());
+    }
+    #[cfg(feature = "unchecked")]
+    let _ = ctx;
+
+    map.insert(property, value);
+    Ok(())
+}
+
+#[export_module]
+mod ordered_map_functions {
+    /// Create a new empty ordered map.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = ordered_map();
+    ///
+    /// print(m.len());     // prints 0
+    /// ```
+    #[rhai_fn(name = "ordered_map")]
+    pub fn new_ordered_map() -> OrderedMap {
+        OrderedMap::new()
+    }
+    /// Create a new ordered map containing all the properties of an object map, ordered by name.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = ordered_map(#{b: 2, a: 1});
+    ///
+    /// m.c = 3;
+    ///
+    /// print(m.keys());    // prints ["a", "b", "c"]
+    /// ```
+    #[rhai_fn(name = "ordered_map")]
+    pub fn ordered_map_from_map(map: Map) -> OrderedMap {
+        map.into()
+    }
+    /// Return the number of properties in the ordered map.
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(map: &mut OrderedMap) -> INT {
+        map.len() as INT
+    }
+    /// Return true if the ordered map is empty.
+    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+    pub fn is_empty(map: &mut OrderedMap) -> bool {
+        map.is_empty()
+    }
+    /// Remove all properties from the ordered map.
+    pub fn clear(map: &mut OrderedMap) {
+        map.clear();
+    }
+    /// Return `true` if the ordered map contains a specified property.
+    ///
+    /// The operator `in` can also be used.
+    #[rhai_fn(pure)]
+    pub fn contains(map: &mut OrderedMap, property: &str) -> bool {
+        map.contains_key(property)
+    }
+    /// Get the value of the `property` in the ordered map and return a copy.
+    ///
+    /// If `property` does not exist in the ordered map, `()` is returned.
+    #[rhai_fn(pure)]
+    pub fn get(map: &mut OrderedMap, property: &str) -> Dynamic {
+        map.get(property).cloned().unwrap_or(Dynamic::UNIT)
+    }
+    /// Set the value of the `property` in the ordered map to a new `value`.
+    ///
+    /// If `property` does not exist in the ordered map, it is added to the end.
+    /// Otherwise it keeps its position.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = ordered_map();
+    ///
+    /// m.set("z", 1);
+    /// m.set("a", 2);
+    /// m.set("z", 3);
+    ///
+    /// print(m);           // prints "#{z: 3, a: 2}"
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn set(
+        ctx: NativeCallContext,
+        map: &mut OrderedMap,
+        property: &str,
+        value: Dynamic,
+    ) -> RhaiResultOf<()> {
+        set_value(&ctx, map, property, value)
+    }
+    /// Get the value of the `property` in the ordered map.
+    ///
+    /// If `property` does not exist in the ordered map, `()` is returned.
+    #[rhai_fn(index_get, pure)]
+    pub fn index_get(map: &mut OrderedMap, property: &str) -> Dynamic {
+        get(map, property)
+    }
+    /// Set the value of the `property` in the ordered map.
+    ///
+    /// If `property` does not exist in the ordered map, it is added to the end.
+    #[rhai_fn(index_set, return_raw)]
+    pub fn index_set(
+        ctx: NativeCallContext,
+        map: &mut OrderedMap,
+        property: &str,
+        value: Dynamic,
+    ) -> RhaiResultOf<()> {
+        set_value(&ctx, map, property, value)
+    }
+    /// Remove a property from the ordered map and return its value.
+    ///
+    /// Properties after it keep their order.  If `property` does not exist, `()` is returned.
+    pub fn remove(map: &mut OrderedMap, property: &str) -> Dynamic {
+        map.remove(property).unwrap_or(Dynamic::UNIT)
+    }
+    /// Get a copy of the value at the `index` position in the ordered map.
+    ///
+    /// * If `index` < 0, position counts from the end of the ordered map (`-1` is the last property).
+    /// * If `index` is out of bounds, `()` is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = ordered_map();
+    /// m.z = 1;
+    /// m.a = 2;
+    ///
+    /// print(m.get_by_index(0));   // prints 1
+    ///
+    /// print(m.get_by_index(-1));  // prints 2
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn get_by_index(map: &mut OrderedMap, index: INT) -> Dynamic {
+        let (index, ..) = calc_offset_len(map.len(), index, 0);
+        map.get_by_index(index)
+            .map_or(Dynamic::UNIT, |(.., value)| value.clone())
+    }
+    /// Get the name of the property at the `index` position in the ordered map.
+    ///
+    /// * If `index` < 0, position counts from the end of the ordered map (`-1` is the last property).
+    /// * If `index` is out of bounds, `()` is returned.
+    #[rhai_fn(pure)]
+    pub fn key_by_index(map: &mut OrderedMap, index: INT) -> Dynamic {
+        let (index, ..) = calc_offset_len(map.len(), index, 0);
+        map.get_by_index(index)
+            .map_or(Dynamic::UNIT, |(key, ..)| key.into())
+    }
+    /// Return the position of the `property` in the ordered map.
+    /// If `property` does not exist, `-1` is returned.
+    #[rhai_fn(pure)]
+    pub fn index_of(map: &mut OrderedMap, property: &str) -> INT {
+        map.index_of(property).map_or(-1, |index| index as INT)
+    }
+    /// Return an array with all the property names in the ordered map, in insertion order.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = ordered_map();
+    /// m.c = 1;
+    /// m.a = 2;
+    /// m.b = 3;
+    ///
+    /// print(m.keys());        // prints ["c", "a", "b"]
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(pure)]
+    pub fn keys(map: &mut OrderedMap) -> Array {
+        map.keys().map(Into::into).collect()
+    }
+    /// Return an array with all the property values in the ordered map, in insertion order.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = ordered_map();
+    /// m.c = 1;
+    /// m.a = 2;
+    /// m.b = 3;
+    ///
+    /// print(m.values());      // prints "[1, 2, 3]"
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(pure)]
+    pub fn values(map: &mut OrderedMap) -> Array {
+        map.values().cloned().collect()
+    }
+    /// Return an object map containing all the properties in the ordered map.
+    #[rhai_fn(pure)]
+    pub fn to_map(map: &mut OrderedMap) -> Map {
+        map.to_map()
+    }
+}
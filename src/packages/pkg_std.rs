@@ -14,20 +14,38 @@ def_package! {
     /// * [`LogicPackage`][super::LogicPackage]
     /// * [`BasicMathPackage`][super::BasicMathPackage]
     /// * [`BasicArrayPackage`][super::BasicArrayPackage]
+    /// * [`BasicPackedArrayPackage`][super::BasicPackedArrayPackage]
     /// * [`BasicBlobPackage`][super::BasicBlobPackage]
+    /// * [`BasicSetPackage`][super::BasicSetPackage]
     /// * [`BasicMapPackage`][super::BasicMapPackage]
+    /// * [`BasicOrderedMapPackage`][super::BasicOrderedMapPackage]
+    /// * [`BasicSortedMapPackage`][super::BasicSortedMapPackage]
+    /// * [`BasicTuplePackage`][super::BasicTuplePackage]
     /// * [`BasicTimePackage`][super::BasicTimePackage]
     /// * [`MoreStringPackage`][super::MoreStringPackage]
+    ///
+    /// The feature flags `no_pkg_string`, `no_pkg_array`, `no_pkg_blob`, `no_pkg_math`,
+    /// `no_pkg_time` and `no_pkg_logic` exclude the corresponding packages, which can still be
+    /// registered individually via [`rhai::packages`][super].
     pub StandardPackage(lib) :
             CorePackage,
             BitFieldPackage,
-            LogicPackage,
-            BasicMathPackage,
-            #[cfg(not(feature = "no_index"))] BasicArrayPackage,
-            #[cfg(not(feature = "no_index"))] BasicBlobPackage,
+            #[cfg(not(feature = "no_pkg_logic"))] LogicPackage,
+            #[cfg(not(feature = "no_pkg_math"))] BasicMathPackage,
+            #[cfg(not(feature = "no_index"))]
+            #[cfg(not(feature = "no_pkg_array"))] BasicArrayPackage,
+            #[cfg(not(feature = "no_index"))]
+            #[cfg(not(feature = "no_pkg_array"))] BasicPackedArrayPackage,
+            #[cfg(not(feature = "no_index"))]
+            #[cfg(not(feature = "no_pkg_blob"))] BasicBlobPackage,
+            #[cfg(not(feature = "no_index"))] BasicSetPackage,
             #[cfg(not(feature = "no_object"))] BasicMapPackage,
-            #[cfg(not(feature = "no_std"))] BasicTimePackage,
-            MoreStringPackage
+            #[cfg(not(feature = "no_object"))] BasicOrderedMapPackage,
+            #[cfg(not(feature = "no_index"))] BasicSortedMapPackage,
+            BasicTuplePackage,
+            #[cfg(not(feature = "no_std"))]
+            #[cfg(not(feature = "no_pkg_time"))] BasicTimePackage,
+            #[cfg(not(feature = "no_pkg_string"))] MoreStringPackage
     {
         lib.standard = true;
     }
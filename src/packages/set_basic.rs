@@ -0,0 +1,241 @@
+#![cfg(not(feature = "no_index"))]
+
+use crate::plugin::*;
+use crate::{
+    def_package, Array, Dynamic, NativeCallContext, Position, RhaiResultOf, Set, ERR, INT,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+def_package! {
+    /// Package of basic set utilities.
+    pub BasicSetPackage(lib) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "set", set_functions);
+
+        // Register set iterator
+        lib.set_iterable::<Set>();
+    }
+}
+
+/// Add a value into a set, raising an error if the value is not hashable.
+fn insert_value(ctx: &NativeCallContext, set: &mut Set, value: Dynamic) -> RhaiResultOf<bool> {
+    #[cfg(not(feature = "unchecked"))]
+    if ctx.engine().max_array_size() > 0
+        && set.len() >= ctx.engine().max_array_size()
+        && !set.contains(&value)
+    {
+        return Err(ERR::ErrorDataTooLarge(
+            "Size of set".to_string(),
+            Default::default(),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    set.insert(value).map_err(|value| {
+        ERR::ErrorMismatchDataType(
+            "hashable value".into(),
+            ctx.engine().map_type_name(value.type_name()).into(),
+            Position::NONE,
+        )
+        .into()
+    })
+}
+
+#[export_module]
+pub mod set_functions {
+    /// Create a new empty set.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s = set();
+    ///
+    /// print(s.len());     // prints 0
+    /// ```
+    #[rhai_fn(name = "set")]
+    pub fn new_set() -> Set {
+        Set::new()
+    }
+    /// Create a new set containing all the unique elements of an array.
+    ///
+    /// An error is raised if any element of the array is not hashable.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s = set([1, 2, 2, 3, 3, 3]);
+    ///
+    /// print(s.len());     // prints 3
+    /// ```
+    #[rhai_fn(name = "set", return_raw)]
+    pub fn set_from_array(ctx: NativeCallContext, array: Array) -> RhaiResultOf<Set> {
+        let mut set = Set::new();
+
+        for item in array {
+            insert_value(&ctx, &mut set, item)?;
+        }
+
+        Ok(set)
+    }
+    /// Create a new set containing all the unique elements of the array.
+    ///
+    /// An error is raised if any element of the array is not hashable.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = [1, 2, 2, 3, 3, 3];
+    ///
+    /// let s = x.to_set();
+    ///
+    /// print(s.contains(2));   // prints true
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn to_set(ctx: NativeCallContext, array: &mut Array) -> RhaiResultOf<Set> {
+        set_from_array(ctx, array.clone())
+    }
+    /// Number of values in the set.
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(set: &mut Set) -> INT {
+        set.len() as INT
+    }
+    /// Return true if the set is empty.
+    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+    pub fn is_empty(set: &mut Set) -> bool {
+        set.is_empty()
+    }
+    /// Remove all values from the set.
+    pub fn clear(set: &mut Set) {
+        set.clear();
+    }
+    /// Add a `value` into the set.
+    ///
+    /// Return `true` if the value is added, or `false` if it is already in the set.
+    ///
+    /// An error is raised if the value is not hashable.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s = set();
+    ///
+    /// print(s.insert(42));    // prints true
+    ///
+    /// print(s.insert(42));    // prints false
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn insert(ctx: NativeCallContext, set: &mut Set, value: Dynamic) -> RhaiResultOf<bool> {
+        insert_value(&ctx, set, value)
+    }
+    /// Remove a `value` from the set.
+    ///
+    /// Return `true` if the value was in the set.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s = set([1, 2, 3]);
+    ///
+    /// print(s.remove(2));     // prints true
+    ///
+    /// print(s.remove(42));    // prints false
+    /// ```
+    pub fn remove(set: &mut Set, value: Dynamic) -> bool {
+        set.remove(&value)
+    }
+    /// Return `true` if the set contains a `value`.
+    ///
+    /// The operator `in` can also be used.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s = set([1, 2, 3]);
+    ///
+    /// print(s.contains(2));   // prints true
+    ///
+    /// print(42 in s);         // prints false
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn contains(set: &mut Set, value: Dynamic) -> bool {
+        set.contains(&value)
+    }
+    /// Return a new set containing all values in either the set or `set2`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s1 = set([1, 2, 3]);
+    /// let s2 = set([3, 4, 5]);
+    ///
+    /// print(s1.union(s2).len());      // prints 5
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn union(ctx: NativeCallContext, set: &mut Set, set2: Set) -> RhaiResultOf<Set> {
+        let _ctx = ctx;
+        let result = set.union(&set2);
+
+        #[cfg(not(feature = "unchecked"))]
+        if _ctx.engine().max_array_size() > 0 && result.len() > _ctx.engine().max_array_size() {
+            return Err(ERR::ErrorDataTooLarge(
+                "Size of set".to_string(),
+                Default::default(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(result)
+    }
+    /// Return a new set containing all values in both the set and `set2`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s1 = set([1, 2, 3]);
+    /// let s2 = set([3, 4, 5]);
+    ///
+    /// print(s1.intersection(s2).to_array());  // prints "[3]"
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn intersection(set: &mut Set, set2: Set) -> Set {
+        set.intersection(&set2)
+    }
+    /// Return a new set containing all values in the set but not in `set2`.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let s1 = set([1, 2, 3]);
+    /// let s2 = set([3, 4, 5]);
+    ///
+    /// print(s1.difference(s2).len());     // prints 2
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn difference(set: &mut Set, set2: Set) -> Set {
+        set.difference(&set2)
+    }
+    /// Return `true` if all values in the set are also in `set2`.
+    #[rhai_fn(pure)]
+    pub fn is_subset(set: &mut Set, set2: Set) -> bool {
+        set.is_subset(&set2)
+    }
+    /// Return an array containing all values in the set, in no particular order.
+    #[rhai_fn(pure)]
+    pub fn to_array(set: &mut Set) -> Array {
+        set.iter().cloned().collect()
+    }
+    /// Return `true` if two sets contain the same values.
+    #[rhai_fn(name = "==", pure)]
+    pub fn equals(set1: &mut Set, set2: Set) -> bool {
+        *set1 == set2
+    }
+    /// Return `true` if two sets do not contain the same values.
+    #[rhai_fn(name = "!=", pure)]
+    pub fn not_equals(set1: &mut Set, set2: Set) -> bool {
+        *set1 != set2
+    }
+}
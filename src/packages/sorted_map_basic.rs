@@ -0,0 +1,219 @@
+#![cfg(not(feature = "no_index"))]
+
+use crate::plugin::*;
+use crate::{
+    def_package, Array, Dynamic, NativeCallContext, Position, RhaiResultOf, SortedMap, ERR, INT,
+};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+def_package! {
+    /// Package of basic sorted map utilities.
+    pub BasicSortedMapPackage(lib) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "sorted_map", sorted_map_functions);
+    }
+}
+
+/// Set the value of a key in a sorted map, raising an error if the key is not orderable
+/// or the map grows too large.
+fn set_value(
+    ctx: &NativeCallContext,
+    map: &mut SortedMap,
+    key: Dynamic,
+    value: Dynamic,
+) -> RhaiResultOf<()> {
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(not(feature = "no_object"))]
+    if ctx.engine().max_map_size() > 0
+        && map.len() >= ctx.engine().max_map_size()
+        && !map.contains_key(&key)
+    {
+        return Err(ERR::ErrorDataTooLarge(
+            "Size of sorted map".to_string(),
+            Default::default(),
+            Position::NONE,
+        )
+        .into());
+    }
+
+    map.insert(key, value).map(|_| ()).map_err(|key| {
+        ERR::ErrorMismatchDataType(
+            "orderable key".into(),
+            ctx.engine().map_type_name(key.type_name()).into(),
+            Position::NONE,
+        )
+        .into()
+    })
+}
+
+#[export_module]
+mod sorted_map_functions {
+    /// Create a new empty sorted map.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = sorted_map();
+    ///
+    /// print(m.len());     // prints 0
+    /// ```
+    #[rhai_fn(name = "sorted_map")]
+    pub fn new_sorted_map() -> SortedMap {
+        SortedMap::new()
+    }
+    /// Return the number of entries in the sorted map.
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(map: &mut SortedMap) -> INT {
+        map.len() as INT
+    }
+    /// Return true if the sorted map is empty.
+    #[rhai_fn(name = "is_empty", get = "is_empty", pure)]
+    pub fn is_empty(map: &mut SortedMap) -> bool {
+        map.is_empty()
+    }
+    /// Remove all entries from the sorted map.
+    pub fn clear(map: &mut SortedMap) {
+        map.clear();
+    }
+    /// Return `true` if the sorted map contains a `key`.
+    ///
+    /// The operator `in` can also be used.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = sorted_map();
+    /// m[42] = "x";
+    ///
+    /// print(m.contains(42));  // prints true
+    ///
+    /// print(0 in m);          // prints false
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn contains(map: &mut SortedMap, key: Dynamic) -> bool {
+        map.contains_key(&key)
+    }
+    /// Get a copy of the value of a `key` in the sorted map.
+    ///
+    /// If `key` does not exist in the sorted map, `()` is returned.
+    #[rhai_fn(pure)]
+    pub fn get(map: &mut SortedMap, key: Dynamic) -> Dynamic {
+        map.get(&key).cloned().unwrap_or(Dynamic::UNIT)
+    }
+    /// Set the value of a `key` in the sorted map to a new `value`.
+    ///
+    /// Keys can be booleans, numbers, characters, strings, timestamps and ranges.
+    /// An error is raised for any other type of key.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = sorted_map();
+    ///
+    /// m.set(3, "c");
+    /// m.set(1, "a");
+    /// m.set(2.5, "b");
+    ///
+    /// print(m.values());  // prints ["a", "b", "c"]
+    /// ```
+    #[rhai_fn(return_raw)]
+    pub fn set(
+        ctx: NativeCallContext,
+        map: &mut SortedMap,
+        key: Dynamic,
+        value: Dynamic,
+    ) -> RhaiResultOf<()> {
+        set_value(&ctx, map, key, value)
+    }
+    /// Get a copy of the value of a `key` in the sorted map.
+    ///
+    /// If `key` does not exist in the sorted map, `()` is returned.
+    #[rhai_fn(index_get, pure)]
+    pub fn index_get(map: &mut SortedMap, key: Dynamic) -> Dynamic {
+        get(map, key)
+    }
+    /// Set the value of a `key` in the sorted map.
+    #[rhai_fn(index_set, return_raw)]
+    pub fn index_set(
+        ctx: NativeCallContext,
+        map: &mut SortedMap,
+        key: Dynamic,
+        value: Dynamic,
+    ) -> RhaiResultOf<()> {
+        set_value(&ctx, map, key, value)
+    }
+    /// Remove a `key` from the sorted map and return its value.
+    ///
+    /// If `key` does not exist, `()` is returned.
+    pub fn remove(map: &mut SortedMap, key: Dynamic) -> Dynamic {
+        map.remove(&key).unwrap_or(Dynamic::UNIT)
+    }
+    /// Return the smallest key in the sorted map, or `()` if it is empty.
+    #[rhai_fn(name = "first_key", get = "first_key", pure)]
+    pub fn first_key(map: &mut SortedMap) -> Dynamic {
+        map.first().map_or(Dynamic::UNIT, |(key, ..)| key.clone())
+    }
+    /// Return the largest key in the sorted map, or `()` if it is empty.
+    #[rhai_fn(name = "last_key", get = "last_key", pure)]
+    pub fn last_key(map: &mut SortedMap) -> Dynamic {
+        map.last().map_or(Dynamic::UNIT, |(key, ..)| key.clone())
+    }
+    /// Return an array with all the keys in the sorted map, in order.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = sorted_map();
+    /// m[3] = "c";
+    /// m[1] = "a";
+    /// m[2] = "b";
+    ///
+    /// print(m.keys());    // prints [1, 2, 3]
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn keys(map: &mut SortedMap) -> Array {
+        map.keys().cloned().collect()
+    }
+    /// Return an array with all the values in the sorted map, in order of their keys.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let m = sorted_map();
+    /// m[3] = "c";
+    /// m[1] = "a";
+    /// m[2] = "b";
+    ///
+    /// print(m.values());  // prints ["a", "b", "c"]
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn values(map: &mut SortedMap) -> Array {
+        map.values().cloned().collect()
+    }
+    /// Return a new sorted map containing all the entries in the sorted map with keys from `start`
+    /// (inclusive) up to `end` (exclusive).
+    ///
+    /// If `start` or `end` is not orderable (e.g. `()` is passed), that side of the range is
+    /// unbounded.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let events = sorted_map();
+    /// let now = timestamp();
+    ///
+    /// events[now + 10.0] = "later";
+    /// events[now + 1.0] = "soon";
+    /// events[now + 5.0] = "next";
+    ///
+    /// print(events.range(now, now + 6.0).values());   // prints ["soon", "next"]
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn range(map: &mut SortedMap, start: Dynamic, end: Dynamic) -> SortedMap {
+        map.range(&start, &end)
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
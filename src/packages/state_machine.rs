@@ -0,0 +1,438 @@
+#![cfg(not(feature = "no_custom_syntax"))]
+
+use crate::api::custom_syntax::markers::{CUSTOM_SYNTAX_MARKER_IDENT, CUSTOM_SYNTAX_MARKER_SYMBOL};
+use crate::ast::Expr;
+use crate::def_package;
+use crate::func::SendSync;
+use crate::plugin::*;
+use crate::{
+    Dynamic, EvalContext, Expression, ImmutableString, LexError, ParseError, Position, RhaiResult,
+    RhaiResultOf, Shared, StateMachine, StaticVec, ERR,
+};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+#[cfg(not(feature = "no_index"))]
+use crate::Array;
+
+/// Keyword starting the declaration of a state machine.
+const KEYWORD_MACHINE: &str = "machine";
+
+/// Callback for a [`StateMachine`] exiting or entering a state, taking the name of the machine
+/// and the name of the state.
+#[cfg(not(feature = "sync"))]
+pub type OnStateCallback = dyn Fn(&str, &str) -> RhaiResultOf<()>;
+/// Callback for a [`StateMachine`] exiting or entering a state, taking the name of the machine
+/// and the name of the state.
+#[cfg(feature = "sync")]
+pub type OnStateCallback = dyn Fn(&str, &str) -> RhaiResultOf<()> + Send + Sync;
+
+/// Configuration of the [`StateMachinePackage`], holding the host hooks called when a
+/// [`StateMachine`] changes state.
+///
+/// Not available under `no_custom_syntax`.
+#[derive(Clone, Default)]
+pub struct StateMachineConfig {
+    /// Hook called when a state is entered.
+    on_enter: Option<Shared<OnStateCallback>>,
+    /// Hook called when a state is exited.
+    on_exit: Option<Shared<OnStateCallback>>,
+}
+
+impl fmt::Debug for StateMachineConfig {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateMachineConfig")
+            .field("on_enter", &self.on_enter.is_some())
+            .field("on_exit", &self.on_exit.is_some())
+            .finish()
+    }
+}
+
+impl StateMachineConfig {
+    /// Create a new [`StateMachineConfig`] with no hooks.
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Set the hook called, with the names of the machine and the state, after a
+    /// [`StateMachine`] enters a state.
+    ///
+    /// An error returned by the hook is raised in the script, after the state has changed.
+    #[inline(always)]
+    #[must_use]
+    pub fn on_enter(
+        mut self,
+        callback: impl Fn(&str, &str) -> RhaiResultOf<()> + SendSync + 'static,
+    ) -> Self {
+        self.on_enter = Some(Shared::new(callback));
+        self
+    }
+    /// Set the hook called, with the names of the machine and the state, before a
+    /// [`StateMachine`] exits a state.
+    ///
+    /// An error returned by the hook is raised in the script and the state does not change.
+    #[inline(always)]
+    #[must_use]
+    pub fn on_exit(
+        mut self,
+        callback: impl Fn(&str, &str) -> RhaiResultOf<()> + SendSync + 'static,
+    ) -> Self {
+        self.on_exit = Some(Shared::new(callback));
+        self
+    }
+    /// Move a [`StateMachine`] to the state at an index, calling the hooks.
+    fn transition(&self, machine: &mut StateMachine, index: usize) -> RhaiResultOf<()> {
+        if let Some(ref hook) = self.on_exit {
+            hook(machine.name(), machine.state())?;
+        }
+
+        machine.set_state_index(index);
+
+        if let Some(ref hook) = self.on_enter {
+            hook(machine.name(), machine.state())?;
+        }
+
+        Ok(())
+    }
+}
+
+def_package! {
+    /// Package of state machines declared via custom syntax.
+    ///
+    /// This package is not part of the [`StandardPackage`][super::StandardPackage] and must be
+    /// registered via [`register_into_engine`][super::Package::register_into_engine] (or
+    /// [`register_into_engine_as`][super::Package::register_into_engine_as]) so that the custom
+    /// syntax is also registered.
+    ///
+    /// A `machine` statement declares a [`StateMachine`] as a variable, starting in its first state.
+    /// Each state lists the events that it transitions on, optionally separated by `;` or `,`.
+    ///
+    /// Host hooks for entering and exiting states can be set via [`StateMachineConfig`].
+    ///
+    /// Not available under `no_custom_syntax`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    /// use rhai::packages::{Package, StateMachineConfig, StateMachinePackage};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let log = Arc::new(Mutex::new(Vec::new()));
+    /// let logger = log.clone();
+    ///
+    /// let config = StateMachineConfig::new().on_enter(move |machine, state| {
+    ///     logger.lock().unwrap().push(format!("{machine}: {state}"));
+    ///     Ok(())
+    /// });
+    ///
+    /// let mut engine = Engine::new();
+    /// StateMachinePackage::with_config(config).register_into_engine(&mut engine);
+    ///
+    /// let state = engine.eval::<String>("
+    ///     machine Door {
+    ///         state Open { on close => Closed }
+    ///         state Closed { on open => Open; on lock => Locked }
+    ///         state Locked { on unlock => Closed }
+    ///     }
+    ///
+    ///     fire(Door, \"close\");
+    ///     fire(Door, \"lock\");
+    ///     fire(Door, \"open\");       // no transition
+    ///
+    ///     state(Door)
+    /// ")?;
+    ///
+    /// assert_eq!(state, "Locked");
+    /// assert_eq!(*log.lock().unwrap(), ["Door: Closed", "Door: Locked"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub StateMachinePackage(lib, config: StateMachineConfig) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "state_machine", state_machine_functions);
+
+        let hooks = config.clone();
+        let _hash = lib.set_native_fn("fire", move |machine: &mut StateMachine, event: ImmutableString| {
+            match machine.target_index(&event) {
+                Some(index) => hooks.transition(machine, index).map(|_| true),
+                None => Ok(false),
+            }
+        });
+        #[cfg(feature = "metadata")]
+        lib.update_fn_metadata_with_comments(
+            _hash,
+            ["machine: &mut StateMachine", "event: &str", "bool"],
+            [
+                "/// Take the transition for an event out of the current state of the state machine.",
+                "///",
+                "/// Returns `false` if the current state has no transition for the event.",
+                "///",
+                "/// # Example",
+                "///",
+                "/// ```rhai",
+                "/// machine Switch {",
+                "///     state Off { on toggle => On }",
+                "///     state On { on toggle => Off }",
+                "/// }",
+                "///",
+                "/// Switch.fire(\"toggle\");",
+                "///",
+                "/// print(Switch.state);     // prints \"On\"",
+                "/// ```"
+            ]
+        );
+
+        let hooks = config;
+        let _hash = lib.set_native_fn("reset", move |machine: &mut StateMachine| {
+            if machine.state() == machine.initial_state() {
+                Ok(())
+            } else {
+                hooks.transition(machine, 0)
+            }
+        });
+        #[cfg(feature = "metadata")]
+        lib.update_fn_metadata_with_comments(
+            _hash,
+            ["machine: &mut StateMachine", "()"],
+            [
+                "/// Return the state machine to its initial state.",
+                "///",
+                "/// # Example",
+                "///",
+                "/// ```rhai",
+                "/// machine Switch {",
+                "///     state Off { on toggle => On }",
+                "///     state On { on toggle => Off }",
+                "/// }",
+                "///",
+                "/// Switch.fire(\"toggle\");",
+                "/// Switch.reset();",
+                "///",
+                "/// print(Switch.state);     // prints \"Off\"",
+                "/// ```"
+            ]
+        );
+    } |> |engine| {
+        engine.register_custom_syntax_raw(KEYWORD_MACHINE, parse_machine, true, eval_machine);
+    }
+}
+
+/// Part of a `machine` declaration expected next by [`parse_machine`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Expect {
+    /// Name of the machine.
+    MachineName,
+    /// `{` starting the machine.
+    MachineStart,
+    /// `state` or `}` ending the machine.
+    State,
+    /// Name of a state.
+    StateName,
+    /// `{` starting a state.
+    StateStart,
+    /// `on` or `}` ending a state.
+    Transition,
+    /// Name of an event.
+    Event,
+    /// `=>` after an event.
+    Arrow,
+    /// Name of the target state.
+    Target,
+    /// `;`, `,`, `on` or `}` ending a state.
+    Separator,
+    /// Nothing more.
+    Done,
+}
+
+/// Custom syntax parser for `machine` declarations.
+///
+/// The `}` ending each state is matched as a symbol so that the ends of states are marked in the
+/// inputs passed to [`eval_machine`].
+fn parse_machine(
+    symbols: &[ImmutableString],
+    look_ahead: &str,
+) -> Result<Option<ImmutableString>, ParseError> {
+    let mut expect = Expect::MachineName;
+    let mut states = StaticVec::<(&str, StaticVec<(&str, &str)>)>::new();
+    let mut event = "";
+
+    for symbol in &symbols[1..] {
+        let symbol = symbol.as_str();
+
+        expect = match expect {
+            Expect::MachineName => Expect::MachineStart,
+            Expect::MachineStart => Expect::State,
+            Expect::State if symbol == "}" => Expect::Done,
+            Expect::State => Expect::StateName,
+            Expect::StateName => {
+                states.push((symbol, StaticVec::new()));
+                Expect::StateStart
+            }
+            Expect::StateStart => Expect::Transition,
+            Expect::Transition | Expect::Separator if symbol == "}" => Expect::State,
+            Expect::Separator if symbol == ";" || symbol == "," => Expect::Transition,
+            Expect::Transition | Expect::Separator => Expect::Event,
+            Expect::Event => {
+                event = symbol;
+                Expect::Arrow
+            }
+            Expect::Arrow => Expect::Target,
+            Expect::Target => {
+                states.last_mut().unwrap().1.push((event, symbol));
+                Expect::Separator
+            }
+            Expect::Done => unreachable!("`machine` declaration is complete"),
+        };
+    }
+
+    let next = match expect {
+        Expect::MachineName | Expect::StateName | Expect::Event | Expect::Target => {
+            CUSTOM_SYNTAX_MARKER_IDENT
+        }
+        Expect::MachineStart | Expect::StateStart => "{",
+        Expect::Arrow => "=>",
+        Expect::State if look_ahead == "state" => "state",
+        Expect::State if look_ahead == "}" => {
+            // Check the complete declaration before it is closed
+            StateMachine::new(symbols[1].as_str(), states).map_err(|err| {
+                LexError::ImproperSymbol(look_ahead.to_string(), err).into_err(Position::NONE)
+            })?;
+            "}"
+        }
+        Expect::State => {
+            return Err(LexError::ImproperSymbol(
+                look_ahead.to_string(),
+                format!("Expecting 'state' or '}}' in machine '{}'", symbols[1]),
+            )
+            .into_err(Position::NONE))
+        }
+        Expect::Transition | Expect::Separator if look_ahead == "on" => "on",
+        Expect::Transition | Expect::Separator if look_ahead == "}" => CUSTOM_SYNTAX_MARKER_SYMBOL,
+        Expect::Separator if look_ahead == ";" || look_ahead == "," => {
+            return Ok(Some(look_ahead.into()))
+        }
+        Expect::Transition | Expect::Separator => {
+            return Err(LexError::ImproperSymbol(
+                look_ahead.to_string(),
+                format!("Expecting 'on' or '}}' in machine '{}'", symbols[1]),
+            )
+            .into_err(Position::NONE))
+        }
+        Expect::Done => return Ok(None),
+    };
+
+    Ok(Some(next.into()))
+}
+
+/// Custom syntax implementation of `machine` declarations, adding the [`StateMachine`] into the
+/// [`Scope`][crate::Scope].
+fn eval_machine(context: &mut EvalContext, inputs: &[Expression]) -> RhaiResult {
+    let name = inputs[0].get_string_value().unwrap();
+    let mut states = StaticVec::<(&str, StaticVec<(&str, &str)>)>::new();
+    let mut in_state = false;
+    let mut iter = inputs[1..].iter();
+
+    while let Some(input) = iter.next() {
+        match **input {
+            // End of state
+            Expr::StringConstant(..) => in_state = false,
+            // Transition
+            _ if in_state => {
+                let event = input.get_string_value().unwrap();
+                let target = iter.next().and_then(|t| t.get_string_value()).unwrap();
+                states.last_mut().unwrap().1.push((event, target));
+            }
+            // Start of state
+            _ => {
+                states.push((input.get_string_value().unwrap(), StaticVec::new()));
+                in_state = true;
+            }
+        }
+    }
+
+    let machine = StateMachine::new(name, states)
+        .map_err(|err| ERR::ErrorRuntime(err.into(), inputs[0].position()))?;
+
+    context.scope_mut().push(name, machine);
+
+    Ok(Dynamic::UNIT)
+}
+
+#[export_module]
+mod state_machine_functions {
+    /// Return the name of the state machine.
+    #[rhai_fn(name = "name", get = "name", pure)]
+    pub fn name(machine: &mut StateMachine) -> ImmutableString {
+        machine.name().into()
+    }
+    /// Return the name of the current state of the state machine.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// machine Switch {
+    ///     state Off { on toggle => On }
+    ///     state On { on toggle => Off }
+    /// }
+    ///
+    /// print(Switch.state);     // prints "Off"
+    /// ```
+    #[rhai_fn(name = "state", get = "state", pure)]
+    pub fn state(machine: &mut StateMachine) -> ImmutableString {
+        machine.state().into()
+    }
+    /// Return the name of the initial state of the state machine.
+    #[rhai_fn(name = "initial_state", get = "initial_state", pure)]
+    pub fn initial_state(machine: &mut StateMachine) -> ImmutableString {
+        machine.initial_state().into()
+    }
+    /// Return `true` if the current state of the state machine has a transition for an event.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// machine Switch {
+    ///     state Off { on toggle => On }
+    ///     state On { on toggle => Off }
+    /// }
+    ///
+    /// print(Switch.can_fire("toggle"));   // prints true
+    /// print(Switch.can_fire("break"));    // prints false
+    /// ```
+    #[rhai_fn(pure)]
+    pub fn can_fire(machine: &mut StateMachine, event: &str) -> bool {
+        machine.target_index(event).is_some()
+    }
+    /// Return an array of the names of all the states of the state machine, starting with the
+    /// initial state.
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(name = "states", get = "states", pure)]
+    pub fn states(machine: &mut StateMachine) -> Array {
+        machine.states().map(Into::into).collect()
+    }
+    /// Return an array of the events that have transitions out of the current state of the
+    /// state machine.
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(name = "events", get = "events", pure)]
+    pub fn events(machine: &mut StateMachine) -> Array {
+        machine.events().map(Into::into).collect()
+    }
+    /// Convert the state machine into a string.
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn to_string(machine: &mut StateMachine) -> String {
+        format!("{machine:?}")
+    }
+}
@@ -1,7 +1,6 @@
 use crate::plugin::*;
-use crate::{def_package, FnPtr, INT};
+use crate::{def_package, FnPtr};
 use std::any::TypeId;
-use std::fmt::{Binary, LowerHex, Octal};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -20,6 +19,7 @@ def_package! {
         lib.standard = true;
 
         combine_with_exported_module!(lib, "print_debug", print_debug_functions);
+        #[cfg(not(feature = "minimal"))]
         combine_with_exported_module!(lib, "number_formatting", number_formatting);
 
         // Register characters iterator
@@ -182,6 +182,88 @@ mod print_debug_functions {
         result.into()
     }
 
+    /// Convert the tuple into a string.
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn format_tuple(ctx: NativeCallContext, tuple: &mut crate::Tuple) -> ImmutableString {
+        let len = tuple.len();
+        let mut result = String::with_capacity(len * 5 + 3);
+        result.push('(');
+
+        tuple.iter_mut().enumerate().for_each(|(i, x)| {
+            result.push_str(&print_with_func(FUNC_TO_DEBUG, &ctx, x));
+            if i < len - 1 {
+                result.push_str(", ");
+            }
+        });
+
+        if len == 1 {
+            result.push(',');
+        }
+        result.push(')');
+        result.into()
+    }
+
+    /// Convert the set into a string.
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn format_set(ctx: NativeCallContext, set: &mut crate::Set) -> ImmutableString {
+        let len = set.len();
+        let mut result = String::with_capacity(len * 5 + 2);
+        result.push('{');
+
+        set.iter_mut().enumerate().for_each(|(i, x)| {
+            result.push_str(&print_with_func(FUNC_TO_DEBUG, &ctx, x));
+            if i < len - 1 {
+                result.push_str(", ");
+            }
+        });
+
+        result.push('}');
+        result.into()
+    }
+
+    /// Convert the sorted map into a string.
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn format_sorted_map(
+        ctx: NativeCallContext,
+        map: &mut crate::SortedMap,
+    ) -> ImmutableString {
+        let len = map.len();
+        let mut result = String::with_capacity(len * 5 + 3);
+        result.push_str("#{");
+
+        map.iter_mut().enumerate().for_each(|(i, (k, v))| {
+            result.push_str(&print_with_func(FUNC_TO_DEBUG, &ctx, &mut k.clone()));
+            result.push_str(": ");
+            result.push_str(&print_with_func(FUNC_TO_DEBUG, &ctx, v));
+            if i < len - 1 {
+                result.push_str(", ");
+            }
+        });
+
+        result.push('}');
+        result.into()
+    }
+
     /// Convert the object map into a string.
     #[cfg(not(feature = "no_object"))]
     #[rhai_fn(
@@ -212,10 +294,48 @@ mod print_debug_functions {
         result.push('}');
         result.into()
     }
+
+    /// Convert the ordered map into a string.
+    #[cfg(not(feature = "no_object"))]
+    #[rhai_fn(
+        name = "print",
+        name = "to_string",
+        name = "debug",
+        name = "to_debug",
+        pure
+    )]
+    pub fn format_ordered_map(
+        ctx: NativeCallContext,
+        map: &mut crate::OrderedMap,
+    ) -> ImmutableString {
+        let len = map.len();
+        let mut result = String::with_capacity(len * 5 + 3);
+        result.push_str("#{");
+
+        map.iter_mut().enumerate().for_each(|(i, (k, v))| {
+            use std::fmt::Write;
+
+            write!(
+                result,
+                "{:?}: {}{}",
+                k,
+                &print_with_func(FUNC_TO_DEBUG, &ctx, v),
+                if i < len - 1 { ", " } else { "" }
+            )
+            .unwrap();
+        });
+
+        result.push('}');
+        result.into()
+    }
 }
 
+#[cfg(not(feature = "minimal"))]
 #[export_module]
 mod number_formatting {
+    use crate::INT;
+    use std::fmt::{Binary, LowerHex, Octal};
+
     fn to_hex<T: LowerHex>(value: T) -> ImmutableString {
         format!("{value:x}").into()
     }
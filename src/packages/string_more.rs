@@ -1,13 +1,14 @@
+use crate::func::call::FnCallArgs;
 use crate::plugin::*;
 use crate::{
-    def_package, Dynamic, ExclusiveRange, InclusiveRange, RhaiResultOf, StaticVec, INT,
-    MAX_USIZE_INT,
+    def_package, Dynamic, ExclusiveRange, ImmutableString, InclusiveRange, Position, RhaiResultOf,
+    StaticVec, ERR, INT, MAX_USIZE_INT,
 };
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{any::TypeId, mem};
 
-use super::string_basic::{print_with_func, FUNC_TO_STRING};
+use super::string_basic::{print_with_func, FUNC_TO_DEBUG, FUNC_TO_STRING};
 
 def_package! {
     /// Package of additional string utilities over [`BasicStringPackage`][super::BasicStringPackage]
@@ -15,9 +16,42 @@ def_package! {
         lib.standard = true;
 
         combine_with_exported_module!(lib, "string", string_functions);
+
+        // Register `format` for each number of values
+        for n in 0..=MAX_FORMAT_VALUES {
+            let mut arg_types = StaticVec::<TypeId>::new_const();
+            arg_types.push(TypeId::of::<ImmutableString>());
+            arg_types.extend((0..n).map(|_| TypeId::of::<Dynamic>()));
+
+            let _hash = lib.set_raw_fn("format", FnNamespace::Internal, FnAccess::Public, arg_types, format_values);
+
+            #[cfg(feature = "metadata")]
+            {
+                let mut params = vec!["format: &str".to_string()];
+                params.extend((1..=n).map(|i| format!("value{i}: Dynamic")));
+                params.push("String".to_string());
+
+                lib.update_fn_metadata_with_comments(_hash, params, [
+                    "/// Format values into a string according to a `format` string, using a subset of Rust's",
+                    "/// format syntax: `{}` (next value), `{n}` (value `n`), `{{`/`}}` (literal braces),",
+                    "/// with an optional spec after `:` made up of `[[fill]align][0][width][.precision][?]`.",
+                    "///",
+                    "/// # Example",
+                    "///",
+                    "/// ```rhai",
+                    "/// let s = format(\"{}: {:>8.2}|\", \"total\", 3.14159);",
+                    "///",
+                    "/// print(s);      // prints \"total:     3.14|\"",
+                    "/// ```",
+                ]);
+            }
+        }
     }
 }
 
+/// Maximum number of values that can be formatted by `format`.
+const MAX_FORMAT_VALUES: usize = 12;
+
 #[export_module]
 mod string_functions {
     use crate::{ImmutableString, SmartString};
@@ -534,6 +568,30 @@ mod string_functions {
     pub fn ends_with(string: &str, match_string: &str) -> bool {
         string.ends_with(match_string)
     }
+    /// Return `true` if the entire string matches a glob-style `pattern`.
+    ///
+    /// * `*` matches any sequence of characters (including none).
+    /// * `?` matches any single character.
+    /// * `[...]` matches any single character in the set, which may contain ranges (e.g. `[a-z]`).
+    /// * `[!...]` or `[^...]` matches any single character _not_ in the set.
+    /// * `\` escapes the following character so that it is matched literally.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let text = "report-2022.txt";
+    ///
+    /// print(text.matches_glob("*.txt"));              // prints true
+    ///
+    /// print(text.matches_glob("report-20??.*"));      // prints true
+    ///
+    /// print(text.matches_glob("[!r]*"));              // prints false
+    /// ```
+    pub fn matches_glob(string: &str, pattern: &str) -> bool {
+        let pattern: Vec<_> = pattern.chars().collect();
+        let text: Vec<_> = string.chars().collect();
+        glob_match(&pattern, &text)
+    }
 
     /// Find the specified `character` in the string, starting from the specified `start` position,
     /// and return the first index where it is found.
@@ -1189,6 +1247,7 @@ mod string_functions {
         if _ctx.engine().max_string_size() > 0 && len > _ctx.engine().max_string_size() {
             return Err(crate::ERR::ErrorDataTooLarge(
                 "Length of string".to_string(),
+                Default::default(),
                 crate::Position::NONE,
             )
             .into());
@@ -1208,6 +1267,7 @@ mod string_functions {
             {
                 return Err(crate::ERR::ErrorDataTooLarge(
                     "Length of string".to_string(),
+                    Default::default(),
                     crate::Position::NONE,
                 )
                 .into());
@@ -1251,6 +1311,7 @@ mod string_functions {
         if _ctx.engine().max_string_size() > 0 && len > _ctx.engine().max_string_size() {
             return Err(crate::ERR::ErrorDataTooLarge(
                 "Length of string".to_string(),
+                Default::default(),
                 crate::Position::NONE,
             )
             .into());
@@ -1277,6 +1338,7 @@ mod string_functions {
             {
                 return Err(crate::ERR::ErrorDataTooLarge(
                     "Length of string".to_string(),
+                    Default::default(),
                     crate::Position::NONE,
                 )
                 .into());
@@ -1405,7 +1467,7 @@ mod string_functions {
         ///
         /// print(text.split("ll", 2));     // prints ["he", "o, world! hello, foo!"]
         /// ```
-        #[rhai_fn(name = "split")]
+        #[rhai_fn(name = "split", name = "splitn")]
         pub fn splitn(string: &str, delimiter: &str, segments: INT) -> Array {
             let segments = segments.min(MAX_USIZE_INT) as usize;
             let pieces: usize = if segments < 1 { 1 } else { segments };
@@ -1436,7 +1498,7 @@ mod string_functions {
         ///
         /// print(text.split('l', 3));      // prints ["he", "", "o, world! hello, foo!"]
         /// ```
-        #[rhai_fn(name = "split")]
+        #[rhai_fn(name = "split", name = "splitn")]
         pub fn splitn_char(string: &str, delimiter: char, segments: INT) -> Array {
             let segments = segments.min(MAX_USIZE_INT) as usize;
             let pieces: usize = if segments < 1 { 1 } else { segments };
@@ -1452,7 +1514,7 @@ mod string_functions {
         ///
         /// print(text.split_rev("ll"));    // prints ["o, foo!", "o, world! he", "he"]
         /// ```
-        #[rhai_fn(name = "split_rev")]
+        #[rhai_fn(name = "split_rev", name = "rsplit")]
         pub fn rsplit(string: &str, delimiter: &str) -> Array {
             string.rsplit(delimiter).map(Into::into).collect()
         }
@@ -1468,7 +1530,7 @@ mod string_functions {
         ///
         /// print(text.split_rev("ll", 2));     // prints ["o, foo!", "hello, world! he"]
         /// ```
-        #[rhai_fn(name = "split_rev")]
+        #[rhai_fn(name = "split_rev", name = "rsplitn")]
         pub fn rsplitn(string: &str, delimiter: &str, segments: INT) -> Array {
             let segments = segments.min(MAX_USIZE_INT) as usize;
             let pieces: usize = if segments < 1 { 1 } else { segments };
@@ -1484,7 +1546,7 @@ mod string_functions {
         ///
         /// print(text.split_rev('l'));     // prints ["o, foo!", "", "d! he", "o, wor", "", "he"]
         /// ```
-        #[rhai_fn(name = "split_rev")]
+        #[rhai_fn(name = "split_rev", name = "rsplit")]
         pub fn rsplit_char(string: &str, delimiter: char) -> Array {
             string.rsplit(delimiter).map(Into::into).collect()
         }
@@ -1500,7 +1562,7 @@ mod string_functions {
         ///
         /// print(text.split('l', 3));      // prints ["o, foo!", "", "hello, world! he"
         /// ```
-        #[rhai_fn(name = "split_rev")]
+        #[rhai_fn(name = "split_rev", name = "rsplitn")]
         pub fn rsplitn_char(string: &str, delimiter: char, segments: INT) -> Array {
             let segments = segments.min(MAX_USIZE_INT) as usize;
             let pieces: usize = if segments < 1 { 1 } else { segments };
@@ -1508,3 +1570,329 @@ mod string_functions {
         }
     }
 }
+
+/// Match a sequence of characters against a glob pattern.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let mut p = 0;
+    let mut t = 0;
+    // Positions (in pattern and text) of the last `*` seen, for backtracking
+    let mut star = None;
+
+    while t < text.len() {
+        if p < pattern.len() {
+            let ch = text[t];
+
+            let next = match pattern[p] {
+                '*' => {
+                    star = Some((p, t));
+                    p += 1;
+                    continue;
+                }
+                '?' => Some(p + 1),
+                '[' => match match_glob_class(pattern, p + 1, ch) {
+                    Some((true, next)) => Some(next),
+                    Some((false, ..)) => None,
+                    // Unterminated set - treat `[` as a literal character
+                    None if ch == '[' => Some(p + 1),
+                    None => None,
+                },
+                '\\' if p + 1 < pattern.len() && pattern[p + 1] == ch => Some(p + 2),
+                '\\' if p + 1 < pattern.len() => None,
+                c if c == ch => Some(p + 1),
+                _ => None,
+            };
+
+            if let Some(next) = next {
+                p = next;
+                t += 1;
+                continue;
+            }
+        }
+
+        // Mismatch - let the last `*` absorb one more character
+        match star {
+            Some((sp, st)) => {
+                p = sp + 1;
+                t = st + 1;
+                star = Some((sp, t));
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Match a character against a glob character set starting at `start` (after the `[`).
+///
+/// Returns whether the character matches together with the position after the closing `]`,
+/// or [`None`] if the set is not terminated.
+fn match_glob_class(pattern: &[char], start: usize, ch: char) -> Option<(bool, usize)> {
+    let mut i = start;
+    let negated = matches!(pattern.get(i), Some('!' | '^'));
+
+    if negated {
+        i += 1;
+    }
+
+    let mut found = false;
+    let mut first = true;
+
+    loop {
+        let c = *pattern.get(i)?;
+
+        // A `]` immediately after the `[` is a literal character
+        if c == ']' && !first {
+            break;
+        }
+        first = false;
+
+        match (pattern.get(i + 1), pattern.get(i + 2)) {
+            (Some('-'), Some(&end)) if end != ']' => {
+                found |= (c..=end).contains(&ch);
+                i += 3;
+            }
+            _ => {
+                found |= c == ch;
+                i += 1;
+            }
+        }
+    }
+
+    Some((found != negated, i + 1))
+}
+
+/// Make an error for an invalid `format` string.
+fn format_error(msg: &str) -> Box<ERR> {
+    ERR::ErrorRuntime(
+        format!("Invalid format string: {msg}").into(),
+        Position::NONE,
+    )
+    .into()
+}
+
+/// Format values into a string according to a format string (the first argument).
+fn format_values(ctx: NativeCallContext, args: &mut FnCallArgs) -> RhaiResultOf<ImmutableString> {
+    let (format, values) = args.split_first_mut().expect("at least one argument");
+    let format = format.clone_cast::<ImmutableString>();
+
+    let mut result = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    let mut next_index = 0;
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                result.push('}');
+            }
+            '}' => return Err(format_error("unmatched '}'")),
+            '{' => {
+                let rest = chars.as_str();
+                let end = rest
+                    .find('}')
+                    .ok_or_else(|| format_error("unmatched '{'"))?;
+                let (field, rest) = (&rest[..end], &rest[end + 1..]);
+                chars = rest.chars();
+
+                let (index, spec) = match field.find(':') {
+                    Some(n) => (&field[..n], &field[n + 1..]),
+                    None => (field, ""),
+                };
+
+                let index = if index.is_empty() {
+                    next_index += 1;
+                    next_index - 1
+                } else {
+                    index
+                        .parse::<usize>()
+                        .map_err(|_| format_error(&format!("invalid index '{index}'")))?
+                };
+
+                let spec = FormatSpec::parse(spec)
+                    .ok_or_else(|| format_error(&format!("invalid format spec '{spec}'")))?;
+
+                let value = values
+                    .get_mut(index)
+                    .ok_or_else(|| format_error(&format!("no value for index {index}")))?;
+
+                spec.format(&ctx, value, &mut result)?;
+            }
+            _ => result.push(ch),
+        }
+    }
+
+    Ok(result.into())
+}
+
+/// A format spec in the form `[[fill]align][0][width][.precision][?]`.
+struct FormatSpec {
+    /// Fill character.
+    fill: char,
+    /// Alignment: `<`, `^` or `>`.
+    align: Option<char>,
+    /// Pad numbers with zeros after the sign?
+    zero_pad: bool,
+    /// Minimum width in characters.
+    width: usize,
+    /// Number of decimal places for floating-point numbers, or maximum number of characters for
+    /// strings.
+    precision: Option<usize>,
+    /// Use the debug representation?
+    debug: bool,
+}
+
+impl FormatSpec {
+    /// Parse a format spec, returning [`None`] if it is invalid.
+    fn parse(spec: &str) -> Option<Self> {
+        let mut spec_chars = spec.chars();
+
+        let (fill, align, prefix) = match (spec_chars.next(), spec_chars.next()) {
+            (Some(fill), Some(align @ ('<' | '^' | '>'))) => {
+                (fill, Some(align), fill.len_utf8() + 1)
+            }
+            (Some(align @ ('<' | '^' | '>')), ..) => (' ', Some(align), 1),
+            _ => (' ', None, 0),
+        };
+
+        let mut rest = &spec[prefix..];
+
+        let zero_pad = align.is_none() && rest.starts_with('0');
+        if zero_pad {
+            rest = &rest[1..];
+        }
+
+        let digits = |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+        let n = digits(rest);
+        let width = if n > 0 { rest[..n].parse().ok()? } else { 0 };
+        rest = &rest[n..];
+
+        let precision = if rest.starts_with('.') {
+            let n = digits(&rest[1..]);
+            if n == 0 {
+                return None;
+            }
+            let precision = rest[1..=n].parse().ok()?;
+            // Rust's formatting machinery panics on larger precisions
+            if precision > u16::MAX as usize {
+                return None;
+            }
+            rest = &rest[n + 1..];
+            Some(precision)
+        } else {
+            None
+        };
+
+        let debug = rest == "?";
+
+        if !debug && !rest.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            fill,
+            align,
+            zero_pad,
+            width,
+            precision,
+            debug,
+        })
+    }
+    /// Raise an error if formatting a number with a precision would exceed the maximum string
+    /// size.
+    #[cfg(not(feature = "unchecked"))]
+    #[cfg(any(not(feature = "no_float"), feature = "decimal"))]
+    fn check_precision(ctx: &NativeCallContext, precision: usize) -> RhaiResultOf<()> {
+        if ctx.engine().max_string_size() > 0 && precision > ctx.engine().max_string_size() {
+            return Err(ERR::ErrorDataTooLarge(
+                "Length of string".to_string(),
+                Default::default(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+    /// Format a value according to this spec, appending it to `result`.
+    fn format(
+        &self,
+        ctx: &NativeCallContext,
+        value: &mut Dynamic,
+        result: &mut String,
+    ) -> RhaiResultOf<()> {
+        #[cfg(not(feature = "unchecked"))]
+        if ctx.engine().max_string_size() > 0 && self.width > ctx.engine().max_string_size() {
+            return Err(ERR::ErrorDataTooLarge(
+                "Length of string".to_string(),
+                Default::default(),
+                Position::NONE,
+            )
+            .into());
+        }
+
+        let is_number = value.is::<INT>();
+        #[cfg(not(feature = "no_float"))]
+        let is_number = is_number || value.is::<crate::FLOAT>();
+        #[cfg(feature = "decimal")]
+        let is_number = is_number || value.is::<rust_decimal::Decimal>();
+
+        let text = match self.precision {
+            #[cfg(not(feature = "no_float"))]
+            Some(precision) if value.is::<crate::FLOAT>() => {
+                #[cfg(not(feature = "unchecked"))]
+                Self::check_precision(ctx, precision)?;
+                format!("{:.*}", precision, value.as_float().unwrap())
+            }
+            #[cfg(feature = "decimal")]
+            Some(precision) if value.is::<rust_decimal::Decimal>() => {
+                #[cfg(not(feature = "unchecked"))]
+                Self::check_precision(ctx, precision)?;
+                format!("{:.*}", precision, value.as_decimal().unwrap())
+            }
+            Some(precision) if value.is::<ImmutableString>() => {
+                let s = value.read_lock::<ImmutableString>().unwrap();
+                s.chars().take(precision).collect()
+            }
+            _ if self.debug => print_with_func(FUNC_TO_DEBUG, ctx, value).to_string(),
+            _ => print_with_func(FUNC_TO_STRING, ctx, value).to_string(),
+        };
+
+        let len = text.chars().count();
+
+        if len >= self.width {
+            result.push_str(&text);
+            return Ok(());
+        }
+
+        let padding = self.width - len;
+
+        if self.zero_pad && is_number {
+            let digits = text.trim_start_matches(['-', '+']);
+            result.push_str(&text[..text.len() - digits.len()]);
+            result.extend(std::iter::repeat('0').take(padding));
+            result.push_str(digits);
+            return Ok(());
+        }
+
+        let (left, right) = match self.align {
+            Some('<') => (0, padding),
+            Some('^') => (padding / 2, padding - padding / 2),
+            Some(..) => (padding, 0),
+            None if is_number || self.zero_pad => (padding, 0),
+            None => (0, padding),
+        };
+        let fill = if self.zero_pad { '0' } else { self.fill };
+
+        result.extend(std::iter::repeat(fill).take(left));
+        result.push_str(&text);
+        result.extend(std::iter::repeat(fill).take(right));
+
+        Ok(())
+    }
+}
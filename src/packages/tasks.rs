@@ -0,0 +1,151 @@
+#![cfg(not(feature = "no_function"))]
+
+use crate::def_package;
+use crate::eval::{Caches, GlobalRuntimeState};
+use crate::plugin::*;
+use crate::{FnPtr, NativeCallContext, Position, RhaiResult, StaticVec, Task, ERR};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+def_package! {
+    /// Package of cooperative tasks.
+    ///
+    /// This package is not part of the [`StandardPackage`][super::StandardPackage] and must be
+    /// registered explicitly.
+    ///
+    /// Tasks spawned by a script are run by the [`Engine`][crate::Engine] in between statements,
+    /// during the same evaluation. No threads are involved, so the order in which tasks run is
+    /// deterministic.
+    ///
+    /// # Time slices
+    ///
+    /// Once a number of operations (a time slice) have been performed by the script, the next
+    /// pending task runs for a time slice, after which control returns to the statement of the
+    /// script that it interrupted. Unfinished tasks take turns in the order they were spawned,
+    /// so a script (or a task) waiting in a loop for another task to finish (e.g. polling its
+    /// `is_done` property) does not block it.
+    ///
+    /// A task is only suspended in between statements (including loop iterations) of the function
+    /// it calls. Calls to other functions made by the task, as well as tasks calling native
+    /// functions, run to completion once started. Joining a task runs it to completion
+    /// immediately, while other tasks still take turns in between.
+    ///
+    /// When a debugger, coverage tracking or a profiler is active, tasks always run to completion
+    /// once started. Under `unchecked`, operations are not counted, so tasks are only run when
+    /// joined or when the script finishes, and then to completion.
+    ///
+    /// Tasks still pending when the script finishes are run before the evaluation returns.
+    ///
+    /// Not available under `no_function`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// use rhai::Engine;
+    /// use rhai::packages::{Package, TasksPackage};
+    ///
+    /// let mut engine = Engine::new();
+    /// engine.register_global_module(TasksPackage::new().as_shared_module());
+    ///
+    /// let result = engine.eval::<i64>("
+    ///     let task = spawn(|| 40 + 2);
+    ///     task.join()
+    /// ")?;
+    ///
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub TasksPackage(lib) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "tasks", task_functions);
+    }
+}
+
+#[export_module]
+mod task_functions {
+    /// Spawn a new task that calls the function pointer with no arguments.
+    ///
+    /// The task is run later, in between statements of the script, or when it is joined.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let task = spawn(|| 40 + 2);
+    ///
+    /// print(task.join());     // prints 42
+    /// ```
+    pub fn spawn(ctx: NativeCallContext, f: FnPtr) -> Task {
+        let task = Task::new(f);
+
+        if let Some(global) = ctx.global_runtime_state() {
+            global.tasks.push(task.clone());
+        }
+
+        task
+    }
+    /// Return `true` if the task has finished.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let task = spawn(|| 42);
+    ///
+    /// print(task.is_done);    // prints false
+    ///
+    /// task.join();
+    ///
+    /// print(task.is_done);    // prints true
+    /// ```
+    #[rhai_fn(name = "is_done", get = "is_done", pure)]
+    pub fn is_done(task: &mut Task) -> bool {
+        task.is_done()
+    }
+    /// Wait for the task to finish and return its result.
+    ///
+    /// If the task has not finished yet, it is run to completion immediately.
+    /// If the task has failed, its error is raised again.
+    ///
+    /// An error is raised if the task is joined from within itself.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let task = spawn(|| 40 + 2);
+    ///
+    /// print(task.join());     // prints 42
+    /// ```
+    #[rhai_fn(return_raw, pure)]
+    pub fn join(ctx: NativeCallContext, task: &mut Task) -> RhaiResult {
+        if let Some(result) = task.result() {
+            return result;
+        }
+
+        let run = ctx
+            .global_runtime_state()
+            .and_then(|global| global.tasks.take(task));
+
+        match run {
+            Some(run) => {
+                let engine = ctx.engine();
+                let global = &mut ctx
+                    .global_runtime_state()
+                    .cloned()
+                    .unwrap_or_else(|| GlobalRuntimeState::new(engine));
+                let caches = &mut Caches::new();
+                let lib: StaticVec<_> = ctx.iter_namespaces().collect();
+                let (pos, level) = (ctx.position(), ctx.call_level());
+
+                engine.join_task(global, caches, &lib, task, run, pos, level)?;
+                task.result().expect("task is done")
+            }
+            None => Err(ERR::ErrorRuntime(
+                "Cannot join a task that is running".into(),
+                Position::NONE,
+            )
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::engine::OP_EQUALS;
+use crate::plugin::*;
+use crate::{def_package, Dynamic, NativeCallContext, RhaiResultOf, Tuple, ERR, INT};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+def_package! {
+    /// Package of basic tuple utilities.
+    pub BasicTuplePackage(lib) {
+        lib.standard = true;
+
+        combine_with_exported_module!(lib, "tuple", tuple_functions);
+
+        // Register tuple iterator
+        lib.set_iterable::<Tuple>();
+    }
+}
+
+#[export_module]
+mod tuple_functions {
+    /// Number of items in the tuple.
+    #[rhai_fn(name = "len", get = "len", pure)]
+    pub fn len(tuple: &mut Tuple) -> INT {
+        tuple.len() as INT
+    }
+    /// Return an array containing all the items in the tuple.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let t = (1, "hello", true);
+    ///
+    /// print(t.to_array());    // prints "[1, "hello", true]"
+    /// ```
+    #[cfg(not(feature = "no_index"))]
+    #[rhai_fn(pure)]
+    pub fn to_array(tuple: &mut Tuple) -> crate::Array {
+        tuple.to_vec()
+    }
+    /// Return `true` if two tuples are equal (i.e. all items are equal and in the same order).
+    ///
+    /// The operator `==` is used to compare items and must be defined,
+    /// otherwise `false` is assumed.
+    ///
+    /// # Example
+    ///
+    /// ```rhai
+    /// let x = (1, "a");
+    /// let y = (1, "a");
+    /// let z = (1, "a", 2);
+    ///
+    /// print(x == y);      // prints true
+    ///
+    /// print(x == z);      // prints false
+    /// ```
+    #[rhai_fn(name = "==", return_raw, pure)]
+    pub fn equals(ctx: NativeCallContext, tuple1: &mut Tuple, tuple2: Tuple) -> RhaiResultOf<bool> {
+        if tuple1.len() != tuple2.len() {
+            return Ok(false);
+        }
+
+        let mut tuple2 = tuple2;
+
+        for (a1, a2) in tuple1.iter_mut().zip(tuple2.iter_mut()) {
+            if !ctx
+                .call_fn_raw(OP_EQUALS, true, false, &mut [a1, a2])
+                .or_else(|err| match *err {
+                    ERR::ErrorFunctionNotFound(ref fn_sig, ..) if fn_sig.starts_with(OP_EQUALS) => {
+                        if a1.type_id() == a2.type_id() {
+                            // No default when comparing same type
+                            Err(err)
+                        } else {
+                            Ok(Dynamic::FALSE)
+                        }
+                    }
+                    _ => Err(err),
+                })?
+                .as_bool()
+                .unwrap_or(false)
+            {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+    /// Return `true` if two tuples are not-equal (i.e. any item not equal or not in the same order).
+    ///
+    /// The operator `==` is used to compare items and must be defined,
+    /// otherwise `false` is assumed.
+    #[rhai_fn(name = "!=", return_raw, pure)]
+    pub fn not_equals(
+        ctx: NativeCallContext,
+        tuple1: &mut Tuple,
+        tuple2: Tuple,
+    ) -> RhaiResultOf<bool> {
+        equals(ctx, tuple1, tuple2).map(|r| !r)
+    }
+}
@@ -1,11 +1,12 @@
 //! Main module defining the lexer and parser.
 
+use crate::api::compile::CompileStats;
 use crate::api::events::VarDefInfo;
 use crate::api::options::LangOptions;
 use crate::ast::{
     ASTFlags, BinaryExpr, CaseBlocksList, ConditionalExpr, Expr, FnCallExpr, FnCallHashes, Ident,
-    OpAssignment, RangeCase, ScriptFnDef, Stmt, StmtBlock, StmtBlockContainer,
-    SwitchCasesCollection, TryCatchBlock,
+    OpAssignment, PatternCase, RangeCase, ScriptFnDef, Stmt, StmtBlock, StmtBlockContainer,
+    SwitchCasesCollection, SwitchPattern, TryCatchBlock,
 };
 use crate::engine::{Precedence, KEYWORD_THIS, OP_CONTAINS};
 use crate::eval::GlobalRuntimeState;
@@ -18,9 +19,12 @@ use crate::types::dynamic::AccessMode;
 use crate::types::StringsInterner;
 use crate::{
     calc_fn_hash, Dynamic, Engine, EvalAltResult, EvalContext, ExclusiveRange, Identifier,
-    ImmutableString, InclusiveRange, LexError, OptimizationLevel, ParseError, Position, Scope,
-    Shared, SmartString, StaticVec, AST, INT, PERR,
+    ImmutableString, InclusiveRange, LexError, OptimizationLevel, ParseError, ParseWarning,
+    Position, Scope, Shared, SmartString, StaticVec, AST, INT, PERR,
 };
+
+#[cfg(not(feature = "no_custom_syntax"))]
+use crate::CustomOperatorKind;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
@@ -50,6 +54,15 @@ const SMALL_SWITCH_RANGE: INT = 16;
 /// Number of string interners used: two additional for property getters/setters if not `no_object`
 const NUM_INTERNERS: usize = if cfg!(feature = "no_object") { 1 } else { 3 };
 
+/// Interned property access - ((getter, hash), (setter, hash), prop, id)
+#[cfg(not(feature = "no_object"))]
+type InternedProperty = crate::Shared<(
+    (ImmutableString, u64),
+    (ImmutableString, u64),
+    ImmutableString,
+    usize,
+)>;
+
 /// _(internals)_ A type that encapsulates the current state of the parser.
 /// Exported under the `internals` feature only.
 pub struct ParseState<'e> {
@@ -59,6 +72,10 @@ pub struct ParseState<'e> {
     pub expr_filter: fn(&Token) -> bool,
     /// String interners.
     interned_strings: [StringsInterner<'e>; NUM_INTERNERS],
+    /// Table of interned property accesses, shared by all [`Property`][Expr::Property] expressions
+    /// of the same name and numbered in order of first appearance.
+    #[cfg(not(feature = "no_object"))]
+    interned_properties: BTreeMap<ImmutableString, InternedProperty>,
     /// External [scope][Scope] with constants.
     pub scope: &'e Scope<'e>,
     /// Global runtime state.
@@ -76,12 +93,25 @@ pub struct ParseState<'e> {
     /// All consequent calls to [`access_var`][ParseState::access_var] will not be affected.
     #[cfg(not(feature = "no_closure"))]
     pub allow_capture: bool,
+    /// Tracks a list of external variables assigned to, under strict constants mode, so that the
+    /// parent of a closure can check whether they are constants.
+    #[cfg(not(feature = "no_closure"))]
+    assigned_external_vars: Vec<crate::ast::Ident>,
     /// Encapsulates a local stack with imported [module][crate::Module] names.
     #[cfg(not(feature = "no_module"))]
     pub imports: StaticVec<ImmutableString>,
+    /// Statically-resolved [modules][crate::Module] (if any) corresponding to `imports`.
+    #[cfg(not(feature = "no_module"))]
+    pub import_modules: StaticVec<Option<crate::Shared<crate::Module>>>,
     /// List of globally-imported [module][crate::Module] names.
     #[cfg(not(feature = "no_module"))]
     pub global_imports: StaticVec<ImmutableString>,
+    /// Script-defined `enum` [modules][crate::Module], hoisted to the start of the script.
+    #[cfg(not(feature = "no_module"))]
+    pub enums: StaticVec<(Ident, crate::Shared<crate::Module>)>,
+    /// Inputs of the custom syntax macro being expanded, referred to as `$0`, `$1` etc.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    pub macro_inputs: StaticVec<Expr>,
     /// Maximum levels of expression nesting (0 for unlimited).
     #[cfg(not(feature = "unchecked"))]
     pub max_expr_depth: usize,
@@ -92,17 +122,24 @@ impl fmt::Debug for ParseState<'_> {
         let mut f = f.debug_struct("ParseState");
 
         f.field("tokenizer_control", &self.tokenizer_control)
-            .field("interned_strings", &self.interned_strings)
-            .field("scope", &self.scope)
+            .field("interned_strings", &self.interned_strings);
+        #[cfg(not(feature = "no_object"))]
+        f.field("interned_properties", &self.interned_properties);
+        f.field("scope", &self.scope)
             .field("global", &self.global)
             .field("stack", &self.stack)
             .field("block_stack_len", &self.block_stack_len);
         #[cfg(not(feature = "no_closure"))]
         f.field("external_vars", &self.external_vars)
-            .field("allow_capture", &self.allow_capture);
+            .field("allow_capture", &self.allow_capture)
+            .field("assigned_external_vars", &self.assigned_external_vars);
         #[cfg(not(feature = "no_module"))]
         f.field("imports", &self.imports)
-            .field("global_imports", &self.global_imports);
+            .field("import_modules", &self.import_modules)
+            .field("global_imports", &self.global_imports)
+            .field("enums", &self.enums);
+        #[cfg(not(feature = "no_custom_syntax"))]
+        f.field("macro_inputs", &self.macro_inputs);
         #[cfg(not(feature = "unchecked"))]
         f.field("max_expr_depth", &self.max_expr_depth);
         f.finish()
@@ -126,7 +163,11 @@ impl<'e> ParseState<'e> {
             external_vars: Vec::new(),
             #[cfg(not(feature = "no_closure"))]
             allow_capture: true,
+            #[cfg(not(feature = "no_closure"))]
+            assigned_external_vars: Vec::new(),
             interned_strings,
+            #[cfg(not(feature = "no_object"))]
+            interned_properties: BTreeMap::new(),
             scope,
             global: GlobalRuntimeState::new(engine),
             stack: Scope::new(),
@@ -134,12 +175,114 @@ impl<'e> ParseState<'e> {
             #[cfg(not(feature = "no_module"))]
             imports: StaticVec::new_const(),
             #[cfg(not(feature = "no_module"))]
+            import_modules: StaticVec::new_const(),
+            #[cfg(not(feature = "no_module"))]
             global_imports: StaticVec::new_const(),
+            #[cfg(not(feature = "no_module"))]
+            enums: StaticVec::new_const(),
+            #[cfg(not(feature = "no_custom_syntax"))]
+            macro_inputs: StaticVec::new_const(),
             #[cfg(not(feature = "unchecked"))]
             max_expr_depth: engine.max_expr_depth(),
         }
     }
 
+    /// Create a new [`ParseState`] for compiling an [`AST`], with interned strings taken from the
+    /// [`Engine`]'s pool of strings shared by all [`AST`]'s (if enabled).
+    #[inline]
+    #[must_use]
+    pub(crate) fn new_with_strings_pool(
+        engine: &Engine,
+        scope: &'e Scope,
+        tokenizer_control: TokenizerControl,
+    ) -> Self {
+        let mut interned_strings: [StringsInterner; NUM_INTERNERS] = Default::default();
+
+        if let Some(ref pool) = engine.strings_pool {
+            interned_strings[0] = crate::func::locked_read(pool).clone();
+        }
+
+        Self::new(engine, scope, interned_strings, tokenizer_control)
+    }
+
+    /// Return interned strings to the [`Engine`]'s pool of strings shared by all [`AST`]'s
+    /// (if enabled).
+    #[inline]
+    pub(crate) fn return_strings_pool(&mut self, engine: &Engine) {
+        if let Some(ref pool) = engine.strings_pool {
+            let strings = std::mem::take(&mut self.interned_strings[0]);
+            *crate::func::locked_write(pool) = strings.detach();
+        }
+    }
+
+    /// Record a parse error in a statement and skip to the end of the statement, if parse errors
+    /// are being collected. Otherwise, the error is returned.
+    ///
+    /// `stack_len` is the size of the local variables stack at the start of the statement.
+    /// `nesting` is the number of braces enclosing the statement (zero at global level).
+    ///
+    /// The closing `}` of the enclosing block is not consumed.
+    fn recover_from_error(
+        &mut self,
+        input: &mut TokenStream,
+        err: ParseError,
+        stack_len: usize,
+        nesting: isize,
+    ) -> ParseResult<()> {
+        if !self.tokenizer_control.borrow().is_collecting_errors() {
+            return Err(err);
+        }
+
+        let err = attach_span(input, err);
+        self.tokenizer_control.borrow_mut().add_error(err);
+
+        // Skip to the first `;` or `}` at the level of the statement
+        loop {
+            let (token, ..) = input.peek().expect(NEVER_ENDS);
+            let level = self.tokenizer_control.borrow().nesting();
+
+            match token {
+                Token::EOF => break,
+                Token::RightBrace if nesting > 0 && level < nesting => break,
+                _ => (),
+            }
+
+            match input.next().expect(NEVER_ENDS) {
+                (Token::SemiColon, ..) | (Token::RightBrace, ..) if level <= nesting => break,
+                _ => (),
+            }
+        }
+
+        // Discard any stray closing braces at global level
+        if nesting == 0 {
+            self.tokenizer_control.borrow_mut().reset_nesting();
+        }
+
+        // Discard any state left behind by the offending statement
+        self.stack.rewind(stack_len);
+        self.expr_filter = |_| true;
+
+        Ok(())
+    }
+
+    /// Report a [`ParseWarning`] to the callback registered with
+    /// [`Engine::on_parse_warning`], and record it if parse errors are being collected.
+    fn report_warning(&self, engine: &Engine, warning: ParseWarning, pos: Position) {
+        if let Some(ref callback) = engine.parse_warning {
+            callback(warning.clone(), pos);
+        }
+        self.tokenizer_control
+            .borrow_mut()
+            .add_warning(warning, pos);
+    }
+
+    /// Are parse warnings reported?
+    #[inline]
+    #[must_use]
+    fn has_warnings(&self, engine: &Engine) -> bool {
+        engine.parse_warning.is_some() || self.tokenizer_control.borrow().is_collecting_errors()
+    }
+
     /// Find explicitly declared variable by name in the [`ParseState`], searching in reverse order.
     ///
     /// The first return value is the offset to be deducted from `ParseState::stack::len()`,
@@ -247,6 +390,17 @@ impl<'e> ParseState<'e> {
             .and_then(|(i, ..)| NonZeroUsize::new(i + 1))
     }
 
+    /// Find a script-defined `enum` [module][crate::Module] by name.
+    #[cfg(not(feature = "no_module"))]
+    #[inline]
+    #[must_use]
+    pub fn find_enum(&self, name: &str) -> Option<&crate::Shared<crate::Module>> {
+        self.enums
+            .iter()
+            .find(|(n, ..)| n.as_str() == name)
+            .map(|(.., m)| m)
+    }
+
     /// Get an interned string, creating one if it is not yet interned.
     #[inline(always)]
     #[must_use]
@@ -280,6 +434,33 @@ impl<'e> ParseState<'e> {
         self.interned_strings[2]
             .get_with_mapper(|s| crate::engine::make_setter(s.as_ref()).into(), text)
     }
+
+    /// Get an interned property access, creating one if it is not yet interned.
+    ///
+    /// The property name, getter and setter names, their hashes, as well as a numeric id unique
+    /// within the [`AST`][crate::AST], are shared by all property accesses of the same name.
+    ///
+    /// Only property accesses are numbered this way. Method and function call names are interned
+    /// as plain strings.
+    #[cfg(not(feature = "no_object"))]
+    #[must_use]
+    pub fn get_interned_property(&mut self, name: &ImmutableString) -> InternedProperty {
+        if let Some(prop) = self.interned_properties.get(name) {
+            return prop.clone();
+        }
+
+        let getter = self.get_interned_getter(name.as_str());
+        let hash_get = calc_fn_hash(&getter, 1);
+        let setter = self.get_interned_setter(name.as_str());
+        let hash_set = calc_fn_hash(&setter, 2);
+
+        let id = self.interned_properties.len();
+
+        let prop: InternedProperty =
+            ((getter, hash_get), (setter, hash_set), name.clone(), id).into();
+        self.interned_properties.insert(name.clone(), prop.clone());
+        prop
+    }
 }
 
 /// A type that encapsulates all the settings for a particular parsing function.
@@ -330,11 +511,53 @@ impl ParseSettings {
 }
 
 /// Make an anonymous function.
+///
+/// The position of the definition, if any, is recorded in the name in order to give the function
+/// a stable, human-readable identity for diagnostics.
 #[cfg(not(feature = "no_function"))]
 #[inline]
 #[must_use]
-pub fn make_anonymous_fn(hash: u64) -> String {
-    format!("{}{:016x}", crate::engine::FN_ANONYMOUS, hash)
+pub fn make_anonymous_fn(hash: u64, pos: Position) -> String {
+    match (pos.line(), pos.position()) {
+        (Some(line), Some(col)) => {
+            format!("{}{line}:{col}${hash:016x}", crate::engine::FN_ANONYMOUS)
+        }
+        _ => format!("{}{:016x}", crate::engine::FN_ANONYMOUS, hash),
+    }
+}
+
+/// Get the [position][Position] of the definition of an anonymous function, if it is recorded.
+#[cfg(not(feature = "no_function"))]
+#[must_use]
+pub fn anonymous_fn_origin(fn_name: &str) -> Option<Position> {
+    let (origin, _) = fn_name
+        .strip_prefix(crate::engine::FN_ANONYMOUS)?
+        .split_once('$')?;
+    let (line, col) = origin.split_once(':')?;
+
+    match (line.parse(), col.parse()) {
+        (Ok(line), Ok(col)) if line > 0 => Some(Position::new(line, col)),
+        _ => None,
+    }
+}
+
+/// Get a human-readable name of an anonymous function for diagnostics,
+/// e.g. `closure@3:9` if it is defined at line 3, position 9.
+#[cfg(not(feature = "no_function"))]
+#[must_use]
+pub fn anonymous_fn_display_name(fn_name: &str) -> Option<String> {
+    if !is_anonymous_fn(fn_name) {
+        return None;
+    }
+
+    Some(match anonymous_fn_origin(fn_name) {
+        Some(pos) => format!(
+            "closure@{}:{}",
+            pos.line().unwrap_or(0),
+            pos.position().unwrap_or(0)
+        ),
+        None => "closure".to_string(),
+    })
 }
 
 /// Is this function an anonymous function?
@@ -355,18 +578,7 @@ impl Expr {
         match self {
             #[cfg(not(feature = "no_module"))]
             Self::Variable(x, ..) if !x.1.is_empty() => unreachable!("qualified property"),
-            Self::Variable(x, .., pos) => {
-                let ident = x.3.clone();
-                let getter = state.get_interned_getter(ident.as_str());
-                let hash_get = calc_fn_hash(&getter, 1);
-                let setter = state.get_interned_setter(ident.as_str());
-                let hash_set = calc_fn_hash(&setter, 2);
-
-                Self::Property(
-                    Box::new(((getter, hash_get), (setter, hash_set), ident)),
-                    pos,
-                )
-            }
+            Self::Variable(x, .., pos) => Self::Property(state.get_interned_property(&x.3), pos),
             _ => self,
         }
     }
@@ -382,6 +594,7 @@ impl Expr {
             Expr::StringConstant(..) => "a string",
             Expr::InterpolatedString(..) => "a string",
             Expr::Array(..) => "an array",
+            Expr::Tuple(..) => "a tuple",
             Expr::Map(..) => "an object map",
             _ => return Ok(self),
         };
@@ -391,6 +604,33 @@ impl Expr {
                 .into_err(self.start_position()),
         )
     }
+    /// Raise an error if the expression is a literal of a type other than the one required by a
+    /// typed expression marker in a custom syntax (e.g. `$expr:int$`).
+    #[cfg(not(feature = "no_custom_syntax"))]
+    fn ensure_custom_syntax_type(self, required_type: &str, required: &str) -> ParseResult<Expr> {
+        let (typ, type_name) = match self {
+            Expr::Unit(..) => ("()", "()"),
+            Expr::BoolConstant(..) => ("bool", "a boolean"),
+            Expr::IntegerConstant(..) => ("int", "a number"),
+            #[cfg(not(feature = "no_float"))]
+            Expr::FloatConstant(..) => ("float", "a floating-point number"),
+            Expr::CharConstant(..) => ("char", "a character"),
+            Expr::StringConstant(..) | Expr::InterpolatedString(..) => ("string", "a string"),
+            Expr::Array(..) => ("array", "an array"),
+            Expr::Tuple(..) => ("tuple", "a tuple"),
+            Expr::Map(..) => ("map", "an object map"),
+            _ => return Ok(self),
+        };
+
+        if typ == required_type {
+            return Ok(self);
+        }
+
+        Err(
+            PERR::MismatchedType(required.to_string(), type_name.to_string())
+                .into_err(self.start_position()),
+        )
+    }
     /// Raise an error if the expression can never yield an iterable value.
     fn ensure_iterable(self) -> ParseResult<Expr> {
         let type_name = match self {
@@ -411,6 +651,28 @@ impl Expr {
     }
 }
 
+/// Get the type, and its description, of the expression matched by a typed expression marker in
+/// a custom syntax (e.g. `$expr:int$`).
+#[cfg(not(feature = "no_custom_syntax"))]
+#[must_use]
+fn custom_syntax_expr_type(marker: &str) -> Option<(&'static str, &'static str)> {
+    use crate::api::custom_syntax::markers::*;
+
+    match marker {
+        CUSTOM_SYNTAX_MARKER_EXPR_INT => Some(("int", "an integer expression")),
+        #[cfg(not(feature = "no_float"))]
+        CUSTOM_SYNTAX_MARKER_EXPR_FLOAT => Some(("float", "a floating-point expression")),
+        CUSTOM_SYNTAX_MARKER_EXPR_BOOL => Some(("bool", "a boolean expression")),
+        CUSTOM_SYNTAX_MARKER_EXPR_CHAR => Some(("char", "a character expression")),
+        CUSTOM_SYNTAX_MARKER_EXPR_STRING => Some(("string", "a string expression")),
+        #[cfg(not(feature = "no_index"))]
+        CUSTOM_SYNTAX_MARKER_EXPR_ARRAY => Some(("array", "an array expression")),
+        #[cfg(not(feature = "no_object"))]
+        CUSTOM_SYNTAX_MARKER_EXPR_MAP => Some(("map", "an object map expression")),
+        _ => None,
+    }
+}
+
 /// Make sure that the next expression is not a statement expression (i.e. wrapped in `{}`).
 #[inline]
 fn ensure_not_statement_expr(
@@ -500,8 +762,117 @@ fn parse_symbol(input: &mut TokenStream) -> ParseResult<(SmartString, Position)>
     }
 }
 
+/// Attach the [span][crate::Span] of the offending token, if known, to a parse error.
+#[must_use]
+fn attach_span(input: &TokenStream, err: ParseError) -> ParseError {
+    let span = input.span_at(err.position());
+    err.with_span(span)
+}
+
+/// Get the [position][Position] of the statement that implicitly returns the value of an
+/// expression at the end of a function body, if any.
+///
+/// Function calls (other than operators) are not counted.
+#[cfg(not(feature = "no_function"))]
+fn find_implicit_return(stmt: &Stmt) -> Option<Position> {
+    match stmt {
+        Stmt::Block(block) => block.statements().last().and_then(find_implicit_return),
+        Stmt::If(x, ..) => {
+            let (.., if_block, else_block) = &**x;
+            if_block
+                .statements()
+                .last()
+                .and_then(find_implicit_return)
+                .or_else(|| {
+                    else_block
+                        .statements()
+                        .last()
+                        .and_then(find_implicit_return)
+                })
+        }
+        Stmt::Expr(expr) => {
+            // Find the end of a method call chain
+            let mut rhs = &**expr;
+            while let Expr::Dot(x, ..) = rhs {
+                rhs = &x.rhs;
+            }
+            match rhs {
+                Expr::FnCall(x, ..) | Expr::MethodCall(x, ..) if !x.is_native_operator => None,
+                _ => Some(expr.start_position()),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Check that `yield` statements in a function body only appear where evaluation can be
+/// suspended and resumed: in nested blocks, `if` branches and loop bodies.
+///
+/// Returns `true` if there is any `yield` statement, or the [position][Position] of the first
+/// `yield` statement that is out of place.
+#[cfg(not(feature = "no_function"))]
+fn check_yields(statements: &[Stmt]) -> Result<bool, Position> {
+    use crate::ast::ASTNode;
+
+    /// Get the [position][Position] of the first `yield` statement within a node, if any.
+    fn find_yield(node: ASTNode) -> Option<Position> {
+        let mut found = None;
+        let on_node = &mut |path: &[ASTNode]| match path.last() {
+            Some(ASTNode::Stmt(Stmt::Yield(.., pos))) => {
+                found = Some(*pos);
+                false
+            }
+            _ => true,
+        };
+
+        match node {
+            ASTNode::Stmt(stmt) => stmt.walk(&mut Vec::new(), on_node),
+            ASTNode::Expr(expr) => expr.walk(&mut Vec::new(), on_node),
+        };
+
+        found
+    }
+
+    let mut has_yield = false;
+
+    for stmt in statements {
+        has_yield |= match stmt {
+            Stmt::Yield(expr, ..) => match find_yield(expr.as_ref().into()) {
+                Some(pos) => return Err(pos),
+                None => true,
+            },
+            Stmt::Block(block) => check_yields(block.statements())?,
+            Stmt::If(x, ..) => {
+                let (expr, if_block, else_block) = &**x;
+                if let Some(pos) = find_yield(expr.into()) {
+                    return Err(pos);
+                }
+                check_yields(if_block.statements())? | check_yields(else_block.statements())?
+            }
+            Stmt::While(x, ..) | Stmt::Do(x, ..) => {
+                if let Some(pos) = find_yield((&x.0).into()) {
+                    return Err(pos);
+                }
+                check_yields(x.1.statements())?
+            }
+            Stmt::For(x, ..) => {
+                if let Some(pos) = find_yield((&x.2).into()) {
+                    return Err(pos);
+                }
+                check_yields(x.3.statements())?
+            }
+            _ => match find_yield(stmt.into()) {
+                Some(pos) => return Err(pos),
+                None => false,
+            },
+        };
+    }
+
+    Ok(has_yield)
+}
+
 impl Engine {
-    /// Parse `(` expr `)`
+    /// Parse `(` expr `)` or a tuple literal `(` expr `,` ... `)`.
     fn parse_paren_expr(
         &self,
         input: &mut TokenStream,
@@ -521,6 +892,59 @@ impl Engine {
         match input.next().expect(NEVER_ENDS) {
             // ( ... )
             (Token::RightParen, ..) => Ok(expr),
+            // ( ... , ... )
+            (Token::Comma, ..) => {
+                let mut items = StaticVec::new_const();
+                items.push(expr);
+
+                loop {
+                    const MISSING_RPAREN: &str = "to end this tuple literal";
+
+                    match input.peek().expect(NEVER_ENDS) {
+                        (Token::RightParen, ..) => {
+                            eat_token(input, Token::RightParen);
+                            break;
+                        }
+                        (Token::EOF, pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightParen.into(),
+                                MISSING_RPAREN.into(),
+                            )
+                            .into_err(*pos))
+                        }
+                        _ => {
+                            let expr = self.parse_expr(input, state, lib, settings.level_up())?;
+                            items.push(expr);
+                        }
+                    }
+
+                    match input.peek().expect(NEVER_ENDS) {
+                        (Token::Comma, ..) => {
+                            eat_token(input, Token::Comma);
+                        }
+                        (Token::RightParen, ..) => (),
+                        (Token::EOF, pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightParen.into(),
+                                MISSING_RPAREN.into(),
+                            )
+                            .into_err(*pos))
+                        }
+                        (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::Comma.into(),
+                                "to separate the items of this tuple literal".into(),
+                            )
+                            .into_err(*pos))
+                        }
+                    };
+                }
+
+                items.shrink_to_fit();
+
+                Ok(Expr::Tuple(items.into(), settings.pos))
+            }
             // ( <error>
             (Token::LexError(err), pos) => Err(err.into_err(pos)),
             // ( ... ???
@@ -592,6 +1016,7 @@ impl Engine {
                         && !is_global
                         && !state.global_imports.iter().any(|m| m.as_str() == root)
                         && !self.global_sub_modules.contains_key(root)
+                        && !self.lazy_sub_modules.contains_key(root)
                     {
                         return Err(
                             PERR::ModuleUndefined(root.to_string()).into_err(namespace.position())
@@ -661,6 +1086,7 @@ impl Engine {
                             && !is_global
                             && !state.global_imports.iter().any(|m| m.as_str() == root)
                             && !self.global_sub_modules.contains_key(root)
+                            && !self.lazy_sub_modules.contains_key(root)
                         {
                             return Err(PERR::ModuleUndefined(root.to_string())
                                 .into_err(namespace.position()));
@@ -794,6 +1220,7 @@ impl Engine {
 
             Expr::IntegerConstant(..)
             | Expr::Array(..)
+            | Expr::Tuple(..)
             | Expr::StringConstant(..)
             | Expr::InterpolatedString(..) => match idx_expr {
                 // lhs[int]
@@ -1119,13 +1546,17 @@ impl Engine {
         let mut expressions = StaticVec::<ConditionalExpr>::new();
         let mut cases = BTreeMap::<u64, CaseBlocksList>::new();
         let mut ranges = StaticVec::<RangeCase>::new();
+        let mut patterns = StaticVec::<PatternCase>::new();
+        let mut values = StaticVec::<(Dynamic, usize)>::new();
         let mut def_case = None;
         let mut def_case_pos = Position::NONE;
 
         loop {
             const MISSING_RBRACE: &str = "to end this switch block";
 
-            let (case_expr_list, condition) = match input.peek().expect(NEVER_ENDS) {
+            let prev_stack_len = state.stack.len();
+
+            let (case_expr_list, pattern) = match input.peek().expect(NEVER_ENDS) {
                 (Token::RightBrace, ..) => {
                     eat_token(input, Token::RightBrace);
                     break;
@@ -1146,14 +1577,22 @@ impl Engine {
                         return Err(PERR::WrongSwitchCaseCondition.into_err(if_pos));
                     }
 
-                    (
-                        StaticVec::default(),
-                        Expr::BoolConstant(true, Position::NONE),
-                    )
+                    (StaticVec::default(), None)
                 }
                 _ if def_case.is_some() => {
                     return Err(PERR::WrongSwitchDefaultCase.into_err(def_case_pos))
                 }
+                // is type => ..., case [a, b] => ..., case { x, y } => ...
+                (Token::Reserved(s), ..) if &**s == "is" || &**s == "case" => {
+                    let pattern = self.parse_switch_pattern(input, state)?;
+
+                    // Pattern variables are visible in the condition and the action
+                    for var in pattern.bindings() {
+                        state.stack.push(var.name.clone(), ());
+                    }
+
+                    (StaticVec::default(), Some(pattern))
+                }
 
                 _ => {
                     let mut case_expr_list = StaticVec::new();
@@ -1176,20 +1615,21 @@ impl Engine {
                         }
                     }
 
-                    let condition = if match_token(input, Token::If).0 {
-                        ensure_not_statement_expr(input, "a boolean")?;
-                        let guard = self
-                            .parse_expr(input, state, lib, settings.level_up())?
-                            .ensure_bool_expr()?;
-                        ensure_not_assignment(input)?;
-                        guard
-                    } else {
-                        Expr::BoolConstant(true, Position::NONE)
-                    };
-                    (case_expr_list, condition)
+                    (case_expr_list, None)
                 }
             };
 
+            let condition = if match_token(input, Token::If).0 {
+                ensure_not_statement_expr(input, "a boolean")?;
+                let guard = self
+                    .parse_expr(input, state, lib, settings.level_up())?
+                    .ensure_bool_expr()?;
+                ensure_not_assignment(input)?;
+                guard
+            } else {
+                Expr::BoolConstant(true, Position::NONE)
+            };
+
             match input.next().expect(NEVER_ENDS) {
                 (Token::DoubleArrow, ..) => (),
                 (Token::LexError(err), pos) => return Err(err.into_err(pos)),
@@ -1219,7 +1659,10 @@ impl Engine {
             expressions.push((condition, action_expr).into());
             let index = expressions.len() - 1;
 
-            if case_expr_list.is_empty() {
+            if let Some(pattern) = pattern {
+                state.stack.rewind(prev_stack_len);
+                patterns.push(PatternCase { pattern, index });
+            } else if case_expr_list.is_empty() {
                 def_case = Some(index);
             } else {
                 for expr in case_expr_list {
@@ -1227,6 +1670,8 @@ impl Engine {
                         PERR::ExprExpected("a literal".to_string()).into_err(expr.start_position())
                     })?;
 
+                    values.push((value.clone(), index));
+
                     let mut range_value: Option<RangeCase> = None;
 
                     let guard = value.read_lock::<ExclusiveRange>();
@@ -1302,11 +1747,130 @@ impl Engine {
             cases,
             def_case,
             ranges,
+            patterns,
+            values,
         };
 
         Ok(Stmt::Switch((item, cases).into(), settings.pos))
     }
 
+    /// Parse a pattern in a switch case.
+    fn parse_switch_pattern(
+        &self,
+        input: &mut TokenStream,
+        state: &mut ParseState,
+    ) -> ParseResult<SwitchPattern> {
+        match input.next().expect(NEVER_ENDS) {
+            // is type
+            (Token::Reserved(s), ..) if &*s == "is" => match input.next().expect(NEVER_ENDS) {
+                (Token::Identifier(s) | Token::StringConstant(s), ..) => {
+                    // Match against display names, so Rust type names are mapped
+                    // (unless already the display name of a custom type)
+                    let name = match self.find_type_name(&s) {
+                        Some(..) => state.get_interned_string(s),
+                        None => state.get_interned_string(self.map_type_name(&s).into_owned()),
+                    };
+                    Ok(SwitchPattern::Type(name))
+                }
+                (Token::LexError(err), pos) => Err(err.into_err(pos)),
+                (.., pos) => {
+                    Err(PERR::MissingSymbol("Expecting a type name".to_string()).into_err(pos))
+                }
+            },
+            // case [a, b, ...]
+            #[cfg(not(feature = "no_index"))]
+            (Token::Reserved(s), ..)
+                if &*s == "case"
+                    && matches!(input.peek().expect(NEVER_ENDS).0, Token::LeftBracket) =>
+            {
+                eat_token(input, Token::LeftBracket);
+
+                let mut vars = StaticVec::<Option<Ident>>::new();
+
+                loop {
+                    if match_token(input, Token::RightBracket).0 {
+                        break;
+                    }
+
+                    if match_token(input, Token::Underscore).0 {
+                        vars.push(None);
+                    } else {
+                        let (name, pos) = parse_var_name(input)?;
+
+                        if vars.iter().flatten().any(|v| v.name == name) {
+                            return Err(PERR::DuplicatedVariable(name.to_string()).into_err(pos));
+                        }
+
+                        let name = state.get_interned_string(name);
+                        vars.push(Some(Ident { name, pos }));
+                    }
+
+                    match input.next().expect(NEVER_ENDS) {
+                        (Token::Comma, ..) => (),
+                        (Token::RightBracket, ..) => break,
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightBracket.into(),
+                                "to end this array pattern".into(),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+
+                Ok(SwitchPattern::Array(vars))
+            }
+            // case { x, y, ... }
+            #[cfg(not(feature = "no_object"))]
+            (Token::Reserved(s), ..)
+                if &*s == "case"
+                    && matches!(
+                        input.peek().expect(NEVER_ENDS).0,
+                        Token::LeftBrace | Token::MapStart
+                    ) =>
+            {
+                input.next().expect(NEVER_ENDS);
+
+                let mut props = StaticVec::<Ident>::new();
+
+                loop {
+                    if match_token(input, Token::RightBrace).0 {
+                        break;
+                    }
+
+                    let (name, pos) = parse_var_name(input)?;
+
+                    if props.iter().any(|p| p.name == name) {
+                        return Err(PERR::DuplicatedProperty(name.to_string()).into_err(pos));
+                    }
+
+                    let name = state.get_interned_string(name);
+                    props.push(Ident { name, pos });
+
+                    match input.next().expect(NEVER_ENDS) {
+                        (Token::Comma, ..) => (),
+                        (Token::RightBrace, ..) => break,
+                        (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                        (.., pos) => {
+                            return Err(PERR::MissingToken(
+                                Token::RightBrace.into(),
+                                "to end this object map pattern".into(),
+                            )
+                            .into_err(pos))
+                        }
+                    }
+                }
+
+                Ok(SwitchPattern::Map(props))
+            }
+            (.., pos) => Err(PERR::MissingSymbol(
+                "Expecting an array or object map pattern".to_string(),
+            )
+            .into_err(pos)),
+        }
+    }
+
     /// Parse a primary expression.
     fn parse_primary(
         &self,
@@ -1402,6 +1966,11 @@ impl Engine {
                     state.tokenizer_control.clone(),
                 );
 
+                #[cfg(not(feature = "no_object"))]
+                {
+                    new_state.interned_properties = std::mem::take(&mut state.interned_properties);
+                }
+
                 #[cfg(not(feature = "no_module"))]
                 {
                     // Do not allow storing an index to a globally-imported module
@@ -1413,6 +1982,7 @@ impl Engine {
                     new_state
                         .global_imports
                         .extend(state.imports.iter().cloned());
+                    new_state.enums.clone_from(&state.enums);
                 }
 
                 #[cfg(not(feature = "unchecked"))]
@@ -1446,6 +2016,10 @@ impl Engine {
 
                 // Restore parse state
                 state.interned_strings = new_state.interned_strings;
+                #[cfg(not(feature = "no_object"))]
+                {
+                    state.interned_properties = new_state.interned_properties;
+                }
 
                 let (expr, func) = result?;
 
@@ -1470,11 +2044,28 @@ impl Engine {
                     },
                 )?;
 
-                let hash_script = calc_fn_hash(&func.name, func.params.len());
-                lib.insert(hash_script, func.into());
-
-                expr
-            }
+                // Under strict constants mode, captured constants cannot be assigned to
+                #[cfg(not(feature = "no_closure"))]
+                for crate::ast::Ident { name, pos } in new_state.assigned_external_vars {
+                    let (index, hit_barrier) = state.find_var(&name);
+                    let index = if hit_barrier || index == 0 {
+                        None
+                    } else {
+                        Some(index)
+                    };
+                    Self::check_assignment_to_var(state, &name, index, pos)?;
+                }
+
+                #[cfg(not(feature = "unchecked"))]
+                if self.max_functions() > 0 && lib.len() >= self.max_functions() {
+                    return Err(PERR::TooManyFunctions(self.max_functions()).into_err(settings.pos));
+                }
+
+                let hash_script = calc_fn_hash(&func.name, func.params.len());
+                lib.insert(hash_script, func.into());
+
+                expr
+            }
 
             // Interpolated string
             Token::InterpolatedString(..) => {
@@ -1528,6 +2119,17 @@ impl Engine {
                     }
                 }
 
+                #[cfg(not(feature = "unchecked"))]
+                if self.max_interpolation_pieces() > 0
+                    && segments.len() > self.max_interpolation_pieces()
+                {
+                    return Err(PERR::LiteralTooLarge(
+                        "Number of interpolated string segments".to_string(),
+                        self.max_interpolation_pieces(),
+                    )
+                    .into_err(settings.pos));
+                }
+
                 if segments.is_empty() {
                     Expr::StringConstant(state.get_interned_string(""), settings.pos)
                 } else {
@@ -1622,6 +2224,27 @@ impl Engine {
                 }
             }
 
+            // $0, $1 etc. - input of a custom syntax macro
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Reserved(s) if !state.macro_inputs.is_empty() && &**s == "$" => {
+                input.next();
+
+                match input.next().expect(NEVER_ENDS) {
+                    (Token::IntegerConstant(n), ..)
+                        if n >= 0 && (n as usize) < state.macro_inputs.len() =>
+                    {
+                        state.macro_inputs[n as usize].clone()
+                    }
+                    (.., pos) => {
+                        return Err(PERR::MissingSymbol(format!(
+                            "Expecting $0 to ${} for a macro input",
+                            state.macro_inputs.len() - 1
+                        ))
+                        .into_err(pos))
+                    }
+                }
+            }
+
             // Reserved keyword or symbol
             Token::Reserved(..) => {
                 #[cfg(not(feature = "no_module"))]
@@ -1853,6 +2476,7 @@ impl Engine {
                         && !is_global
                         && !state.global_imports.iter().any(|m| m.as_str() == root)
                         && !self.global_sub_modules.contains_key(root)
+                        && !self.lazy_sub_modules.contains_key(root)
                     {
                         return Err(
                             PERR::ModuleUndefined(root.to_string()).into_err(namespace.position())
@@ -1864,10 +2488,96 @@ impl Engine {
             }
         }
 
+        // Fold constants in statically-resolved modules
+        #[cfg(not(feature = "no_module"))]
+        if let Expr::Variable(ref x, None, pos) = lhs {
+            let (.., ref namespace, _, ref name) = **x;
+
+            if !namespace.is_empty() {
+                if let Some(value) = self.find_static_module_var(state, namespace, name) {
+                    lhs = Expr::from_dynamic(value, pos);
+                } else if namespace.len() == 1 && state.find_enum(namespace.root()).is_some() {
+                    // Catch misspelled `enum` variants
+                    let variant = format!("{}::{}", namespace.root(), name);
+                    return Err(PERR::VariableUndefined(variant).into_err(pos));
+                }
+            }
+        }
+
         // Make sure identifiers are valid
         Ok(lhs)
     }
 
+    /// Find the value of a variable in a statically-resolved [module][crate::Module].
+    #[cfg(not(feature = "no_module"))]
+    #[must_use]
+    fn find_static_module_var(
+        &self,
+        state: &ParseState,
+        namespace: &crate::ast::Namespace,
+        name: &str,
+    ) -> Option<Dynamic> {
+        let root = namespace.root();
+
+        let module = match state.find_module(root) {
+            Some(index) => state
+                .import_modules
+                .get(state.imports.len() - index.get())
+                .cloned()??,
+            None if state.global_imports.iter().any(|m| m.as_str() == root) => {
+                state.find_enum(root).cloned()?
+            }
+            None => self.global_sub_modules.get(root).cloned()?,
+        };
+
+        let mut module = &*module;
+
+        for ns in namespace.iter().skip(1) {
+            module = module.get_sub_module(ns.as_str())?;
+        }
+
+        module.get_var(name)
+    }
+
+    /// Get the [kind][CustomOperatorKind] of a custom operator.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    #[inline]
+    #[must_use]
+    fn custom_operator_kind(&self, op: &str) -> CustomOperatorKind {
+        if self.custom_operator_kinds.is_empty() {
+            return CustomOperatorKind::Binary;
+        }
+        self.custom_operator_kinds
+            .get(op)
+            .copied()
+            .unwrap_or(CustomOperatorKind::Binary)
+    }
+
+    /// Make a call to a unary custom operator.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    fn make_unary_custom_operator_call(
+        state: &mut ParseState,
+        op: &str,
+        args: StaticVec<Expr>,
+        pos: Position,
+    ) -> Expr {
+        let hash = calc_fn_hash(op, 1);
+
+        FnCallExpr {
+            name: state.get_interned_string(op),
+            hashes: if is_valid_function_name(op) {
+                hash.into()
+            } else {
+                FnCallHashes::from_native(hash)
+            },
+            args,
+            pos,
+            is_native_operator: !is_valid_function_name(op),
+            ..Default::default()
+        }
+        .into_fn_call_expr(pos)
+    }
+
     /// Parse a potential unary operator.
     fn parse_unary(
         &self,
@@ -1974,6 +2684,29 @@ impl Engine {
                 }
                 .into_fn_call_expr(pos))
             }
+            // custom_op expr
+            #[cfg(not(feature = "no_custom_syntax"))]
+            Token::Custom(op) if self.custom_operator_kind(op) == CustomOperatorKind::Prefix => {
+                let op = op.clone();
+                let precedence = self.custom_keywords.get(op.as_str()).copied().flatten();
+                let pos = eat_token(input, Token::Custom(op.clone()));
+
+                let operand = self.parse_unary(input, state, lib, settings.level_up())?;
+                let operand = self.parse_binary_op(
+                    input,
+                    state,
+                    lib,
+                    precedence,
+                    operand,
+                    settings.level_up(),
+                )?;
+
+                let mut args = StaticVec::new_const();
+                args.push(operand);
+                args.shrink_to_fit();
+
+                Ok(Self::make_unary_custom_operator_call(state, &op, args, pos))
+            }
             // <EOF>
             Token::EOF => Err(PERR::UnexpectedEOF.into_err(settings.pos)),
             // All other tokens
@@ -2077,6 +2810,24 @@ impl Engine {
                     }
                 }
             }
+            // (xxx, ...) = rhs
+            Expr::Tuple(ref x, ..) if op.is_none() => {
+                for item in x.iter() {
+                    match item {
+                        // Tuples cannot be nested
+                        Expr::Tuple(..) => {
+                            return Err(PERR::AssignmentToInvalidLHS(String::new())
+                                .into_err(item.position()))
+                        }
+                        // Each item must be a valid target on its own
+                        _ => {
+                            let rhs = Expr::Unit(Position::NONE);
+                            Self::make_assignment_stmt(None, state, item.clone(), rhs, op_pos)?;
+                        }
+                    }
+                }
+                Ok(Stmt::Assignment((op_info, (lhs, rhs).into()).into()))
+            }
             // ??? && ??? = rhs, ??? || ??? = rhs, xxx ?? xxx = rhs
             Expr::And(..) | Expr::Or(..) | Expr::Coalesce(..) => Err(LexError::ImproperSymbol(
                 "=".to_string(),
@@ -2115,10 +2866,69 @@ impl Engine {
         let mut settings = settings;
         settings.pos = pos;
 
+        if settings.options.contains(LangOptions::STRICT_CONST) {
+            Self::check_assignment_to_constant(state, &lhs)?;
+        }
+
         let rhs = self.parse_expr(input, state, lib, settings.level_up())?;
         Self::make_assignment_stmt(op, state, lhs, rhs, pos)
     }
 
+    /// Under strict constants mode, make sure that an assignment modifies neither a constant nor
+    /// any of its elements or properties.
+    fn check_assignment_to_constant(state: &mut ParseState, lhs: &Expr) -> ParseResult<()> {
+        let root = match lhs {
+            Expr::Index(x, ..) | Expr::Dot(x, ..) => &x.lhs,
+            Expr::Tuple(x, ..) => {
+                return x
+                    .iter()
+                    .try_for_each(|item| Self::check_assignment_to_constant(state, item))
+            }
+            expr => expr,
+        };
+
+        match root {
+            #[cfg(not(feature = "no_module"))]
+            Expr::Variable(x, ..) if !x.1.is_empty() => Ok(()),
+            Expr::Variable(x, i, pos) => {
+                let index =
+                    i.map_or_else(|| x.0.map(NonZeroUsize::get), |n| Some(n.get() as usize));
+                Self::check_assignment_to_var(state, &x.3, index, *pos)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Under strict constants mode, make sure that a variable assigned to is not a constant.
+    ///
+    /// Variables not found in the local stack are also checked against the external [scope][Scope]
+    /// and recorded, so that the parent of a closure can check the variables it captures.
+    fn check_assignment_to_var(
+        state: &mut ParseState,
+        name: &str,
+        index: Option<usize>,
+        pos: Position,
+    ) -> ParseResult<()> {
+        let is_constant = if let Some(index) = index {
+            let offset = state.stack.len() - index;
+            state.stack.get_mut_by_index(offset).access_mode() == AccessMode::ReadOnly
+        } else {
+            #[cfg(not(feature = "no_closure"))]
+            state.assigned_external_vars.push(crate::ast::Ident {
+                name: name.into(),
+                pos,
+            });
+
+            matches!(state.scope.is_constant(name), Some(true))
+        };
+
+        if is_constant {
+            Err(PERR::AssignmentToConstant(name.to_string()).into_err(pos))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Make a dot expression.
     #[cfg(not(feature = "no_object"))]
     fn make_dot_expr(
@@ -2295,6 +3105,23 @@ impl Engine {
                 }
                 _ => current_op.precedence(),
             };
+
+            #[cfg(not(feature = "no_custom_syntax"))]
+            let op_kind = match current_op {
+                Token::Custom(c) => self.custom_operator_kind(c),
+                _ => CustomOperatorKind::Binary,
+            };
+
+            // Prefix operators cannot follow an expression
+            #[cfg(not(feature = "no_custom_syntax"))]
+            if op_kind == CustomOperatorKind::Prefix {
+                return Ok(root);
+            }
+
+            #[cfg(not(feature = "no_custom_syntax"))]
+            let bind_right =
+                op_kind == CustomOperatorKind::BinaryRight || current_op.is_bind_right();
+            #[cfg(feature = "no_custom_syntax")]
             let bind_right = current_op.is_bind_right();
 
             // Bind left to the parent lhs expression if precedence is higher
@@ -2305,6 +3132,18 @@ impl Engine {
 
             let (op_token, pos) = input.next().expect(NEVER_ENDS);
 
+            // expr custom_op
+            #[cfg(not(feature = "no_custom_syntax"))]
+            if op_kind == CustomOperatorKind::Postfix {
+                let op = op_token.syntax();
+                let mut args = StaticVec::new_const();
+                args.push(root);
+                args.shrink_to_fit();
+
+                root = Self::make_unary_custom_operator_call(state, &op, args, pos);
+                continue;
+            }
+
             let rhs = self.parse_unary(input, state, lib, settings)?;
 
             let (next_op, next_pos) = input.peek().expect(NEVER_ENDS);
@@ -2469,10 +3308,14 @@ impl Engine {
         let mut segments = StaticVec::new_const();
         let mut tokens = StaticVec::new_const();
 
+        let stack_len = state.stack.len();
+
         // Adjust the variables stack
-        if syntax.scope_may_be_changed {
+        if syntax.scope_may_be_changed || syntax.template.is_some() {
             // Add a barrier variable to the stack so earlier variables will not be matched.
             // Variable searches stop at the first barrier.
+            // Inputs of a macro are also searched by name, as the template may add variables
+            // before them.
             let marker = state.get_interned_string(SCOPE_SEARCH_BARRIER_MARKER);
             state.stack.push(marker, ());
         }
@@ -2528,6 +3371,14 @@ impl Engine {
                     segments.push(keyword.clone().into());
                     tokens.push(keyword);
                 }
+                marker if custom_syntax_expr_type(marker).is_some() => {
+                    let (typ, required) = custom_syntax_expr_type(marker).expect("typed marker");
+                    let expr = self.parse_expr(input, state, lib, settings)?;
+                    inputs.push(expr.ensure_custom_syntax_type(typ, required)?);
+                    let keyword = state.get_interned_string(marker);
+                    segments.push(keyword.clone());
+                    tokens.push(keyword);
+                }
                 CUSTOM_SYNTAX_MARKER_BLOCK => {
                     match self.parse_block(input, state, lib, settings)? {
                         block @ Stmt::Block(..) => {
@@ -2609,6 +3460,36 @@ impl Engine {
             }
         }
 
+        // Expand a macro by parsing its template in place
+        if let Some(ref template) = syntax.template {
+            let (stream, tokenizer_control) = self.lex_raw(
+                std::slice::from_ref(template),
+                self.token_mapper.as_ref().map(<_>::as_ref),
+            );
+            let tokenizer_control =
+                std::mem::replace(&mut state.tokenizer_control, tokenizer_control);
+            let inputs = std::mem::replace(&mut state.macro_inputs, inputs);
+
+            // Errors in the template are reported at the macro call site
+            let stream = &mut TokenStream::new_at(stream, pos);
+            let result = self
+                .parse_expr(stream, state, lib, settings.level_up())
+                .and_then(|expr| match stream.next().expect(NEVER_ENDS) {
+                    (Token::EOF, ..) => Ok(expr),
+                    (token, pos) => {
+                        Err(LexError::UnexpectedInput(token.syntax().to_string()).into_err(pos))
+                    }
+                });
+
+            state.tokenizer_control = tokenizer_control;
+            state.macro_inputs = inputs;
+            state.stack.rewind(stack_len);
+
+            let mut expr = result?;
+            expr.set_position(pos);
+            return Ok(expr);
+        }
+
         inputs.shrink_to_fit();
         tokens.shrink_to_fit();
 
@@ -2884,43 +3765,15 @@ impl Engine {
         let mut settings = settings;
         settings.pos = input.next().expect(NEVER_ENDS).1;
 
-        // let name ...
-        let (name, pos) = parse_var_name(input)?;
-
-        if !self.allow_shadowing() && state.stack.iter().any(|(v, ..)| v == name) {
-            return Err(PERR::VariableExists(name.to_string()).into_err(pos));
+        // let ( ...
+        if matches!(input.peek().expect(NEVER_ENDS).0, Token::LeftParen) {
+            return self.parse_let_tuple(input, state, lib, access, is_export, settings);
         }
 
-        if let Some(ref filter) = self.def_var_filter {
-            let will_shadow = state.stack.iter().any(|(v, ..)| v == name);
-            let level = settings.level;
-            let is_const = access == AccessMode::ReadOnly;
-            let info = VarDefInfo {
-                name: &name,
-                is_const,
-                nesting_level: level,
-                will_shadow,
-            };
-            let mut this_ptr = None;
-            let context = EvalContext::new(
-                self,
-                &mut state.stack,
-                &mut state.global,
-                None,
-                &[],
-                &mut this_ptr,
-                level,
-            );
+        // let name ...
+        let (name, pos) = parse_var_name(input)?;
 
-            match filter(false, info, context) {
-                Ok(true) => (),
-                Ok(false) => return Err(PERR::ForbiddenVariable(name.to_string()).into_err(pos)),
-                Err(err) => match *err {
-                    EvalAltResult::ErrorParsing(e, pos) => return Err(e.into_err(pos)),
-                    _ => return Err(PERR::ForbiddenVariable(name.to_string()).into_err(pos)),
-                },
-            }
-        }
+        self.check_var_def(state, &name, pos, access, settings.level)?;
 
         let name = state.get_interned_string(name);
 
@@ -2969,6 +3822,143 @@ impl Engine {
         })
     }
 
+    /// Parse a tuple destructuring variable definition statement, after `let`/`const`.
+    fn parse_let_tuple(
+        &self,
+        input: &mut TokenStream,
+        state: &mut ParseState,
+        lib: &mut FnLib,
+        access: AccessMode,
+        is_export: bool,
+        settings: ParseSettings,
+    ) -> ParseResult<Stmt> {
+        // let ( ...
+        eat_token(input, Token::LeftParen);
+
+        let mut vars = StaticVec::<Option<Ident>>::new();
+
+        loop {
+            if match_token(input, Token::RightParen).0 {
+                break;
+            }
+
+            if match_token(input, Token::Underscore).0 {
+                vars.push(None);
+            } else {
+                let (name, pos) = parse_var_name(input)?;
+
+                if vars.iter().flatten().any(|v| v.name == name) {
+                    return Err(PERR::DuplicatedVariable(name.to_string()).into_err(pos));
+                }
+
+                self.check_var_def(state, &name, pos, access, settings.level)?;
+
+                let name = state.get_interned_string(name);
+                vars.push(Some(Ident { name, pos }));
+            }
+
+            match input.next().expect(NEVER_ENDS) {
+                (Token::Comma, ..) => (),
+                (Token::RightParen, ..) => break,
+                (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+                (.., pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::RightParen.into(),
+                        "to end this list of variables".into(),
+                    )
+                    .into_err(pos))
+                }
+            }
+        }
+
+        // let (name, ...) = expr
+        let expr = match input.next().expect(NEVER_ENDS) {
+            (Token::Equals, ..) => self.parse_expr(input, state, lib, settings.level_up())?,
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (.., pos) => {
+                return Err(PERR::MissingToken(
+                    Token::Equals.into(),
+                    "to destructure a tuple into these variables".into(),
+                )
+                .into_err(pos))
+            }
+        };
+
+        // Add the variables only after the expression, which may refer to shadowed variables
+        for var in vars.iter().flatten() {
+            state
+                .stack
+                .push_entry(var.name.as_str(), access, Dynamic::UNIT);
+        }
+
+        let mut options = if is_export {
+            ASTFlags::EXPORTED
+        } else {
+            ASTFlags::NONE
+        };
+        if access == AccessMode::ReadOnly {
+            options |= ASTFlags::CONSTANT;
+        }
+
+        Ok(Stmt::VarTuple((vars, expr).into(), options, settings.pos))
+    }
+
+    /// Make sure that a variable can be defined, according to the shadowing options and the
+    /// variable definition filter (if any).
+    fn check_var_def(
+        &self,
+        state: &mut ParseState,
+        name: &str,
+        pos: Position,
+        access: AccessMode,
+        level: usize,
+    ) -> ParseResult<()> {
+        if !self.allow_shadowing() && state.stack.iter().any(|(v, ..)| v == name) {
+            return Err(PERR::VariableExists(name.to_string()).into_err(pos));
+        }
+        if !self.allow_shadowing_in_same_scope()
+            && state
+                .stack
+                .iter()
+                .skip(state.block_stack_len)
+                .any(|(v, ..)| v == name)
+        {
+            return Err(PERR::VariableExists(name.to_string()).into_err(pos));
+        }
+
+        if let Some(ref filter) = self.def_var_filter {
+            let will_shadow = state.stack.iter().any(|(v, ..)| v == name);
+            let is_const = access == AccessMode::ReadOnly;
+            let info = VarDefInfo {
+                name,
+                is_const,
+                nesting_level: level,
+                will_shadow,
+            };
+            let mut this_ptr = None;
+            let context = EvalContext::new(
+                self,
+                &mut state.stack,
+                &mut state.global,
+                None,
+                &[],
+                &mut this_ptr,
+                level,
+            );
+
+            match filter(false, info, context) {
+                Ok(true) => (),
+                Ok(false) => return Err(PERR::ForbiddenVariable(name.to_string()).into_err(pos)),
+                Err(err) => match *err {
+                    EvalAltResult::ErrorParsing(e, pos) => return Err(e.into_err(pos)),
+                    _ => return Err(PERR::ForbiddenVariable(name.to_string()).into_err(pos)),
+                },
+            }
+        }
+
+        Ok(())
+    }
+
     /// Parse an import statement.
     #[cfg(not(feature = "no_module"))]
     fn parse_import(
@@ -3002,12 +3992,151 @@ impl Engine {
         let name = state.get_interned_string(name);
         state.imports.push(name.clone());
 
+        // Resolve the module statically if possible
+        let module = match expr {
+            Expr::StringConstant(ref path, ..) => self.module_resolver.resolve_static(path),
+            _ => None,
+        };
+        state.import_modules.push(module);
+
         Ok(Stmt::Import(
             (expr, Ident { name, pos }).into(),
             settings.pos,
         ))
     }
 
+    /// Parse an `enum` definition.
+    ///
+    /// The `enum` is turned into a [module][crate::Module] holding its variants as constants,
+    /// together with the helper functions `variants` and `is_variant`.  It is imported at the
+    /// start of the script (similar to functions, `enum` definitions are hoisted), and the
+    /// variants are folded into constants wherever they are referenced.
+    #[cfg(not(feature = "no_module"))]
+    fn parse_enum(
+        &self,
+        input: &mut TokenStream,
+        state: &mut ParseState,
+        settings: ParseSettings,
+    ) -> ParseResult<Stmt> {
+        #[cfg(not(feature = "unchecked"))]
+        settings.ensure_level_within_max_limit(state.max_expr_depth)?;
+
+        // enum ...
+        let mut settings = settings;
+        settings.pos = input.next().expect(NEVER_ENDS).1;
+
+        // enum name ...
+        let (name, pos) = parse_var_name(input)?;
+        let name = state.get_interned_string(name);
+
+        if state.find_module(&name).is_some() || state.find_enum(&name).is_some() {
+            return Err(PERR::DuplicatedVariable(name.to_string()).into_err(pos));
+        }
+
+        // enum name { ...
+        match input.next().expect(NEVER_ENDS) {
+            (Token::LeftBrace, ..) => (),
+            (Token::LexError(err), pos) => return Err(err.into_err(pos)),
+            (.., pos) => {
+                return Err(PERR::MissingToken(
+                    Token::LeftBrace.into(),
+                    format!("to start the definition of enum '{}'", name),
+                )
+                .into_err(pos))
+            }
+        }
+
+        // enum name { variant, ... }
+        let mut variants = StaticVec::<ImmutableString>::new_const();
+
+        loop {
+            match input.peek().expect(NEVER_ENDS) {
+                (Token::RightBrace, ..) => {
+                    eat_token(input, Token::RightBrace);
+                    break;
+                }
+                (Token::EOF, pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::RightBrace.into(),
+                        format!("to end the definition of enum '{}'", name),
+                    )
+                    .into_err(*pos))
+                }
+                _ => (),
+            }
+
+            let (variant, pos) = parse_var_name(input)?;
+
+            if variants.iter().any(|v| v.as_str() == variant.as_str()) {
+                return Err(PERR::DuplicatedVariable(variant.to_string()).into_err(pos));
+            }
+
+            variants.push(state.get_interned_string(variant));
+
+            match input.peek().expect(NEVER_ENDS) {
+                (Token::Comma, ..) => {
+                    eat_token(input, Token::Comma);
+                }
+                (Token::RightBrace, ..) => (),
+                (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+                (.., pos) => {
+                    return Err(PERR::MissingToken(
+                        Token::Comma.into(),
+                        format!("to separate the variants of enum '{}'", name),
+                    )
+                    .into_err(*pos))
+                }
+            }
+        }
+
+        // Build the module, with its definition as the path to import it from
+        let path = format!(
+            "enum {} {{ {} }}",
+            name,
+            variants
+                .iter()
+                .map(|v| v.as_str())
+                .collect::<StaticVec<_>>()
+                .join(", ")
+        );
+
+        let mut module = crate::Module::new();
+        module.set_id(path);
+
+        for variant in variants.iter() {
+            module.set_var(variant.as_str(), variant.clone());
+        }
+
+        #[cfg(not(feature = "no_index"))]
+        {
+            let variants = variants.clone();
+            module.set_native_fn("variants", move || {
+                Ok(variants
+                    .iter()
+                    .cloned()
+                    .map(Dynamic::from)
+                    .collect::<crate::Array>())
+            });
+        }
+
+        module.set_native_fn("is_variant", move |value: Dynamic| {
+            Ok(value
+                .read_lock::<ImmutableString>()
+                .map_or(false, |v| variants.contains(&*v)))
+        });
+
+        module.build_index();
+
+        // Enums are hoisted, so they come before all other imports
+        let module: crate::Shared<crate::Module> = module.into();
+        let index = state.enums.len();
+        state.imports.insert(index, name.clone());
+        state.import_modules.insert(index, Some(module.clone()));
+        state.enums.push((Ident { name, pos }, module));
+
+        Ok(Stmt::Noop(settings.pos))
+    }
+
     /// Parse an export statement.
     #[cfg(not(feature = "no_module"))]
     fn parse_export(
@@ -3109,6 +4238,9 @@ impl Engine {
 
         let prev_entry_stack_len = state.block_stack_len;
         state.block_stack_len = state.stack.len();
+        let entry_stack_len = state.block_stack_len;
+
+        let nesting = state.tokenizer_control.borrow().nesting();
 
         #[cfg(not(feature = "no_module"))]
         let orig_imports_len = state.imports.len();
@@ -3130,7 +4262,16 @@ impl Engine {
             // Parse statements inside the block
             settings.at_global_level = false;
 
-            let stmt = self.parse_stmt(input, state, lib, settings.level_up())?;
+            let stack_len = state.stack.len();
+
+            let stmt = match self.parse_stmt(input, state, lib, settings.level_up()) {
+                Ok(stmt) => stmt,
+                Err(err) => {
+                    state.recover_from_error(input, err, stack_len, nesting)?;
+                    state.block_stack_len = entry_stack_len;
+                    continue;
+                }
+            };
 
             if stmt.is_noop() {
                 continue;
@@ -3141,38 +4282,47 @@ impl Engine {
 
             statements.push(stmt);
 
-            match input.peek().expect(NEVER_ENDS) {
+            let err = match input.peek().expect(NEVER_ENDS) {
                 // { ... stmt }
                 (Token::RightBrace, ..) => break eat_token(input, Token::RightBrace),
                 // { ... stmt;
                 (Token::SemiColon, ..) if need_semicolon => {
                     eat_token(input, Token::SemiColon);
+                    continue;
                 }
                 // { ... { stmt } ;
                 (Token::SemiColon, ..) if !need_semicolon => {
                     eat_token(input, Token::SemiColon);
+                    continue;
                 }
                 // { ... { stmt } ???
-                _ if !need_semicolon => (),
+                _ if !need_semicolon => continue,
                 // { ... stmt <error>
-                (Token::LexError(err), err_pos) => return Err(err.clone().into_err(*err_pos)),
+                (Token::LexError(err), err_pos) => err.clone().into_err(*err_pos),
                 // { ... stmt ???
                 (.., pos) => {
                     // Semicolons are not optional between statements
-                    return Err(PERR::MissingToken(
+                    PERR::MissingToken(
                         Token::SemiColon.into(),
                         "to terminate this statement".into(),
                     )
-                    .into_err(*pos));
+                    .into_err(*pos)
                 }
-            }
+            };
+
+            // Keep the statement, which is complete
+            let stack_len = state.stack.len();
+            state.recover_from_error(input, err, stack_len, nesting)?;
         };
 
         state.stack.rewind(state.block_stack_len);
         state.block_stack_len = prev_entry_stack_len;
 
         #[cfg(not(feature = "no_module"))]
-        state.imports.truncate(orig_imports_len);
+        {
+            state.imports.truncate(orig_imports_len);
+            state.import_modules.truncate(orig_imports_len);
+        }
 
         Ok((statements, settings.pos, end_pos).into())
     }
@@ -3291,6 +4441,12 @@ impl Engine {
                             state.tokenizer_control.clone(),
                         );
 
+                        #[cfg(not(feature = "no_object"))]
+                        {
+                            new_state.interned_properties =
+                                std::mem::take(&mut state.interned_properties);
+                        }
+
                         #[cfg(not(feature = "no_module"))]
                         {
                             // Do not allow storing an index to a globally-imported module
@@ -3302,6 +4458,7 @@ impl Engine {
                             new_state
                                 .global_imports
                                 .extend(state.imports.iter().cloned());
+                            new_state.enums.clone_from(&state.enums);
                         }
 
                         #[cfg(not(feature = "unchecked"))]
@@ -3340,6 +4497,10 @@ impl Engine {
 
                         // Restore parse state
                         state.interned_strings = new_state.interned_strings;
+                        #[cfg(not(feature = "no_object"))]
+                        {
+                            state.interned_properties = new_state.interned_properties;
+                        }
 
                         let func = func?;
 
@@ -3353,6 +4514,11 @@ impl Engine {
                             .into_err(pos));
                         }
 
+                        #[cfg(not(feature = "unchecked"))]
+                        if self.max_functions() > 0 && lib.len() >= self.max_functions() {
+                            return Err(PERR::TooManyFunctions(self.max_functions()).into_err(pos));
+                        }
+
                         lib.insert(hash, func.into());
 
                         Ok(Stmt::Noop(pos))
@@ -3423,6 +4589,16 @@ impl Engine {
                 }
             }
 
+            #[cfg(not(feature = "no_function"))]
+            Token::Yield if !settings.in_fn_scope => Err(PERR::WrongYield.into_err(token_pos)),
+
+            #[cfg(not(feature = "no_function"))]
+            Token::Yield => {
+                let pos = eat_token(input, Token::Yield);
+                let expr = self.parse_expr(input, state, lib, settings.level_up())?;
+                Ok(Stmt::Yield(expr.into(), pos))
+            }
+
             Token::Try => self.parse_try_catch(input, state, lib, settings.level_up()),
 
             Token::Let => self.parse_let(input, state, lib, ReadWrite, false, settings.level_up()),
@@ -3439,6 +4615,16 @@ impl Engine {
             #[cfg(not(feature = "no_module"))]
             Token::Export => self.parse_export(input, state, lib, settings.level_up()),
 
+            #[cfg(not(feature = "no_module"))]
+            Token::Reserved(s) if &**s == "enum" && !settings.at_global_level => {
+                Err(PERR::WrongEnumDefinition.into_err(token_pos))
+            }
+
+            #[cfg(not(feature = "no_module"))]
+            Token::Reserved(s) if &**s == "enum" => {
+                self.parse_enum(input, state, settings.level_up())
+            }
+
             _ => self.parse_expr_stmt(input, state, lib, settings.level_up()),
         }
     }
@@ -3564,6 +4750,14 @@ impl Engine {
                             return Err(PERR::FnDuplicatedParam(name.to_string(), s.to_string())
                                 .into_err(pos));
                         }
+                        #[cfg(not(feature = "unchecked"))]
+                        if self.max_fn_params() > 0 && params.len() >= self.max_fn_params() {
+                            return Err(PERR::TooManyParameters(
+                                name.to_string(),
+                                self.max_fn_params(),
+                            )
+                            .into_err(pos));
+                        }
                         let s = state.get_interned_string(s);
                         state.stack.push(s.clone(), ());
                         params.push((s, pos));
@@ -3596,8 +4790,33 @@ impl Engine {
                 self.parse_block(input, state, lib, settings.level_up())?
             }
             (.., pos) => return Err(PERR::FnMissingBody(name.to_string()).into_err(*pos)),
+        };
+
+        if settings.options.contains(LangOptions::EXPLICIT_RETURN) {
+            if let Some(pos) = find_implicit_return(&body) {
+                return Err(PERR::FnImplicitReturn(name.to_string()).into_err(pos));
+            }
         }
-        .into();
+
+        if state.has_warnings(self) {
+            if let Stmt::Block(ref block) = body {
+                match block.statements().last() {
+                    Some(stmt @ Stmt::Var(..))
+                    | Some(stmt @ Stmt::VarTuple(..))
+                    | Some(stmt @ Stmt::Assignment(..)) => state.report_warning(
+                        self,
+                        ParseWarning::FnValueDiscarded(name.to_string()),
+                        stmt.position(),
+                    ),
+                    _ => (),
+                }
+            }
+        }
+
+        let body: StmtBlock = body.into();
+
+        let is_generator =
+            check_yields(body.statements()).map_err(|pos| PERR::WrongYield.into_err(pos))?;
 
         let mut params: StaticVec<_> = params.into_iter().map(|(p, ..)| p).collect();
         params.shrink_to_fit();
@@ -3605,7 +4824,9 @@ impl Engine {
         Ok(ScriptFnDef {
             name: state.get_interned_string(name),
             access,
+            visibility: crate::ast::FnVisibility::All,
             params,
+            is_generator,
             body,
             #[cfg(not(feature = "no_module"))]
             environ: None,
@@ -3701,6 +4922,14 @@ impl Engine {
                                 PERR::FnDuplicatedParam(String::new(), s.to_string()).into_err(pos)
                             );
                         }
+                        #[cfg(not(feature = "unchecked"))]
+                        if self.max_fn_params() > 0 && params_list.len() >= self.max_fn_params() {
+                            return Err(PERR::TooManyParameters(
+                                String::new(),
+                                self.max_fn_params(),
+                            )
+                            .into_err(pos));
+                        }
                         let s = state.get_interned_string(s);
                         state.stack.push(s.clone(), ());
                         params_list.push(s);
@@ -3734,6 +4963,9 @@ impl Engine {
         settings.is_breakable = false;
         let body = self.parse_stmt(input, state, lib, settings.level_up())?;
 
+        let is_generator = check_yields(std::slice::from_ref(&body))
+            .map_err(|pos| PERR::WrongYield.into_err(pos))?;
+
         // External variables may need to be processed in a consistent order,
         // so extract them into a list.
         #[cfg(not(feature = "no_closure"))]
@@ -3759,13 +4991,15 @@ impl Engine {
         params.iter().for_each(|p| p.hash(hasher));
         body.hash(hasher);
         let hash = hasher.finish();
-        let fn_name = state.get_interned_string(make_anonymous_fn(hash));
+        let fn_name = state.get_interned_string(make_anonymous_fn(hash, settings.pos));
 
         // Define the function
         let script = ScriptFnDef {
             name: fn_name.clone(),
             access: crate::FnAccess::Public,
+            visibility: crate::ast::FnVisibility::All,
             params,
+            is_generator,
             body: body.into(),
             #[cfg(not(feature = "no_module"))]
             environ: None,
@@ -3810,7 +5044,9 @@ impl Engine {
             options,
             pos: Position::NONE,
         };
-        let expr = self.parse_expr(input, state, &mut functions, settings)?;
+        let expr = self
+            .parse_expr(input, state, &mut functions, settings)
+            .map_err(|err| attach_span(input, err))?;
 
         assert!(functions.is_empty());
 
@@ -3818,7 +5054,8 @@ impl Engine {
             (Token::EOF, ..) => (),
             // Return error if the expression doesn't end
             (token, pos) => {
-                return Err(LexError::UnexpectedInput(token.syntax().to_string()).into_err(*pos))
+                let err = LexError::UnexpectedInput(token.syntax().to_string()).into_err(*pos);
+                return Err(attach_span(input, err));
             }
         }
 
@@ -3833,7 +5070,8 @@ impl Engine {
             #[cfg(not(feature = "no_function"))]
             StaticVec::new_const(),
             _optimization_level,
-        ));
+        )
+        .0);
 
         #[cfg(feature = "no_optimize")]
         return Ok(AST::new(
@@ -3867,7 +5105,16 @@ impl Engine {
                 pos: Position::NONE,
             };
 
-            let stmt = self.parse_stmt(input, state, &mut functions, settings)?;
+            let stack_len = state.stack.len();
+
+            let stmt = match self.parse_stmt(input, state, &mut functions, settings) {
+                Ok(stmt) => stmt,
+                Err(err) => {
+                    state.recover_from_error(input, err, stack_len, 0)?;
+                    state.block_stack_len = 0;
+                    continue;
+                }
+            };
 
             if stmt.is_noop() {
                 continue;
@@ -3877,72 +5124,171 @@ impl Engine {
 
             statements.push(stmt);
 
-            match input.peek().expect(NEVER_ENDS) {
+            let err = match input.peek().expect(NEVER_ENDS) {
                 // EOF
                 (Token::EOF, ..) => break,
                 // stmt ;
                 (Token::SemiColon, ..) if need_semicolon => {
                     eat_token(input, Token::SemiColon);
+                    continue;
                 }
                 // stmt ;
-                (Token::SemiColon, ..) if !need_semicolon => (),
+                (Token::SemiColon, ..) if !need_semicolon => continue,
                 // { stmt } ???
-                _ if !need_semicolon => (),
+                _ if !need_semicolon => continue,
                 // stmt <error>
-                (Token::LexError(err), pos) => return Err(err.clone().into_err(*pos)),
+                (Token::LexError(err), pos) => err.clone().into_err(*pos),
                 // stmt ???
                 (.., pos) => {
                     // Semicolons are not optional between statements
-                    return Err(PERR::MissingToken(
+                    PERR::MissingToken(
                         Token::SemiColon.into(),
                         "to terminate this statement".into(),
                     )
-                    .into_err(*pos));
+                    .into_err(*pos)
                 }
-            }
+            };
+
+            // Keep the statement, which is complete
+            let stack_len = state.stack.len();
+            state.recover_from_error(input, err, stack_len, 0)?;
+            state.block_stack_len = 0;
+        }
+
+        // Hoist the imports of `enum` definitions to the start of the script
+        #[cfg(not(feature = "no_module"))]
+        if !state.enums.is_empty() {
+            statements = state
+                .enums
+                .iter()
+                .map(|(ident, module)| {
+                    let path = Expr::StringConstant(module.id().unwrap().into(), ident.pos);
+                    Stmt::Import((path, ident.clone()).into(), ident.pos)
+                })
+                .chain(statements)
+                .collect();
         }
 
         Ok((statements, functions.into_iter().map(|(.., v)| v).collect()))
     }
 
     /// Run the parser on an input stream, returning an AST.
-    #[inline]
+    #[inline(always)]
     pub(crate) fn parse(
+        &self,
+        input: &mut TokenStream,
+        state: &mut ParseState,
+        optimization_level: OptimizationLevel,
+    ) -> ParseResult<AST> {
+        self.parse_with_stats(
+            input,
+            state,
+            optimization_level,
+            &mut CompileStats::default(),
+        )
+    }
+
+    /// Run the parser on an input stream, returning an AST and recording statistics of the
+    /// compilation, except the number of nodes.
+    pub(crate) fn parse_with_stats(
         &self,
         input: &mut TokenStream,
         state: &mut ParseState,
         _optimization_level: OptimizationLevel,
+        stats: &mut CompileStats,
     ) -> ParseResult<AST> {
-        let (statements, _lib) = self.parse_global_level(input, state)?;
+        #[cfg(not(feature = "no_std"))]
+        let start = crate::Instant::now();
+
+        let (statements, _lib) = self
+            .parse_global_level(input, state)
+            .map_err(|err| attach_span(input, err))?;
+
+        #[cfg(not(feature = "no_std"))]
+        {
+            stats.parse_time = start.elapsed();
+        }
+
+        // Do not count the end-of-script token
+        stats.tokens = state
+            .tokenizer_control
+            .borrow()
+            .num_tokens()
+            .saturating_sub(1);
+
+        #[cfg(not(feature = "no_function"))]
+        {
+            stats.functions = _lib.len();
+        }
+
+        #[cfg(not(feature = "no_std"))]
+        let start = crate::Instant::now();
 
         #[cfg(not(feature = "no_optimize"))]
-        return Ok(crate::optimizer::optimize_into_ast(
-            self,
-            state.scope,
-            statements,
-            #[cfg(not(feature = "no_function"))]
-            _lib,
-            _optimization_level,
-        ));
+        let _ast = {
+            let (ast, passes, warnings) = crate::optimizer::optimize_into_ast(
+                self,
+                state.scope,
+                statements,
+                #[cfg(not(feature = "no_function"))]
+                _lib,
+                _optimization_level,
+            );
+            stats.optimizer_passes = passes;
+
+            // Warnings from the optimizer are already reported
+            let mut control = state.tokenizer_control.borrow_mut();
+            warnings
+                .into_iter()
+                .for_each(|(warning, pos)| control.add_warning(warning, pos));
+
+            ast
+        };
+
+        #[cfg(not(feature = "no_std"))]
+        {
+            stats.optimize_time = start.elapsed();
+        }
 
         #[cfg(feature = "no_optimize")]
         #[cfg(not(feature = "no_function"))]
-        {
+        let _ast = {
             let mut m = crate::Module::new();
 
             for fn_def in _lib {
                 m.set_script_fn(fn_def);
             }
 
-            return Ok(AST::new(statements, m));
-        }
+            AST::new(statements, m)
+        };
 
         #[cfg(feature = "no_optimize")]
         #[cfg(feature = "no_function")]
-        return Ok(AST::new(
+        let _ast = AST::new(
             statements,
             #[cfg(not(feature = "no_function"))]
             crate::Module::new(),
-        ));
+        );
+
+        // Embed the modules of `enum` definitions
+        #[cfg(not(feature = "no_module"))]
+        let _ast = {
+            let mut ast = _ast;
+
+            if !state.enums.is_empty() {
+                let mut resolver = crate::module::resolvers::StaticModuleResolver::new();
+
+                for (.., module) in state.enums.iter() {
+                    let path = module.id().unwrap().to_string();
+                    resolver.insert(path, crate::func::shared_take_or_clone(module.clone()));
+                }
+
+                ast.set_resolver(resolver);
+            }
+
+            ast
+        };
+
+        Ok(_ast)
     }
 }
@@ -0,0 +1,1732 @@
+//! Exchange of scripts with visual block editors.
+//!
+//! The blocks format represents a script as a tree of typed nodes, each with named slots holding
+//! the nodes nested in it, in the style of Scratch-like visual editors.  Every node is a JSON
+//! object with a `"type"` field naming its kind, for example:
+//!
+//! ```json
+//! {
+//!     "functions": [
+//!         {
+//!             "name": "add",
+//!             "params": ["x", "y"],
+//!             "body": [
+//!                 {
+//!                     "type": "expr",
+//!                     "expr": {
+//!                         "type": "operator",
+//!                         "op": "+",
+//!                         "args": [
+//!                             { "type": "variable", "name": "x" },
+//!                             { "type": "variable", "name": "y" }
+//!                         ]
+//!                     }
+//!                 }
+//!             ]
+//!         }
+//!     ],
+//!     "body": [
+//!         {
+//!             "type": "let",
+//!             "name": "total",
+//!             "value": {
+//!                 "type": "call",
+//!                 "name": "add",
+//!                 "args": [
+//!                     { "type": "int", "value": 40 },
+//!                     { "type": "int", "value": 2 }
+//!                 ]
+//!             }
+//!         }
+//!     ]
+//! }
+//! ```
+
+use crate::ast::{ASTFlags, BinaryExpr, Expr, FnCallExpr, Stmt};
+use crate::engine::Precedence;
+use crate::parser::ParseResult;
+use crate::tokenizer::{is_valid_identifier, Token};
+use crate::types::dynamic::Union;
+use crate::{
+    Dynamic, Engine, LexError, OptimizationLevel, ParseError, ParseErrorType, Position, Scope, AST,
+    INT,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// A script in the blocks format, for exchange with visual (block-based) editors.
+///
+/// Use [`Engine::script_to_blocks`] to convert a script into blocks, and
+/// [`Engine::compile_blocks`] to compile blocks back into an [`AST`].  This type implements
+/// [`Serialize`] and [`Deserialize`], so it can be saved in JSON (see the
+/// [module documentation][self] for an example) or any other format supported by
+/// [`serde`](https://crates.io/crates/serde).
+///
+/// Comments are not kept.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::Engine;
+/// use rhai::serde::BlockScript;
+///
+/// let engine = Engine::new();
+///
+/// let blocks = engine.script_to_blocks("let x = 40; x + 2")?;
+///
+/// let json = serde_json::to_string(&blocks).unwrap();
+///
+/// // ... edit the blocks in a visual editor ...
+///
+/// let blocks: BlockScript = serde_json::from_str(&json).unwrap();
+/// let ast = engine.compile_blocks(&blocks)?;
+///
+/// assert_eq!(engine.eval_ast::<i64>(&ast)?, 42);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct BlockScript {
+    /// Script-defined functions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub functions: Vec<BlockFnDef>,
+    /// Statements at global level.
+    #[serde(default)]
+    pub body: Vec<BlockStmt>,
+}
+
+/// A script-defined function in the blocks format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockFnDef {
+    /// Function name.
+    pub name: String,
+    /// Names of parameters.
+    #[serde(default)]
+    pub params: Vec<String>,
+    /// Is the function private?
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub private: bool,
+    /// Function body.
+    #[serde(default)]
+    pub body: Vec<BlockStmt>,
+}
+
+/// A statement in the blocks format.
+///
+/// The `"type"` of each node is the name of the variant in `snake_case`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BlockStmt {
+    /// \[`export`\] `let`|`const` name \[`=` value\]
+    Let {
+        /// Variable name.
+        name: String,
+        /// Initial value, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<BlockExpr>,
+        /// Is this a constant?
+        #[serde(default, skip_serializing_if = "is_false")]
+        constant: bool,
+        /// Is the variable exported?
+        #[serde(default, skip_serializing_if = "is_false")]
+        exported: bool,
+    },
+    /// target op value
+    Assign {
+        /// Variable, property or index to assign to.
+        target: BlockExpr,
+        /// Assignment operator, e.g. `=` or `+=`.
+        #[serde(default = "default_assign_op")]
+        op: String,
+        /// Value to assign.
+        value: BlockExpr,
+    },
+    /// `if` condition `{` then `}` `else` `{` else `}`
+    If {
+        /// Condition.
+        condition: BlockExpr,
+        /// Statements to run if the condition is `true`.
+        #[serde(rename = "then", default)]
+        then_body: Vec<BlockStmt>,
+        /// Statements to run if the condition is `false`.
+        #[serde(rename = "else", default, skip_serializing_if = "Vec::is_empty")]
+        else_body: Vec<BlockStmt>,
+    },
+    /// `while` condition `{` body `}`
+    While {
+        /// Condition.
+        condition: BlockExpr,
+        /// Loop body.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+    },
+    /// `loop` `{` body `}`
+    Loop {
+        /// Loop body.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+    },
+    /// `do` `{` body `}` `while`|`until` condition
+    Do {
+        /// Loop body.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+        /// Condition.
+        condition: BlockExpr,
+        /// Does the loop run until the condition is `true` (instead of while it is `true`)?
+        #[serde(default, skip_serializing_if = "is_false")]
+        until: bool,
+    },
+    /// `for` variable \[`,` counter\] `in` iterable `{` body `}`
+    For {
+        /// Loop variable.
+        variable: String,
+        /// Counter variable, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        counter: Option<String>,
+        /// Expression to iterate over.
+        iterable: BlockExpr,
+        /// Loop body.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+    },
+    /// `break`
+    Break,
+    /// `continue`
+    Continue,
+    /// `return` \[value\]
+    Return {
+        /// Return value, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<BlockExpr>,
+    },
+    /// `throw` \[value\]
+    Throw {
+        /// Exception value, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        value: Option<BlockExpr>,
+    },
+    /// `try` `{` body `}` `catch` \[`(` variable `)`\] `{` catch `}`
+    Try {
+        /// Statements to try.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+        /// Variable holding the exception, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        catch_variable: Option<String>,
+        /// Statements to run if an exception is thrown.
+        #[serde(default)]
+        catch: Vec<BlockStmt>,
+    },
+    /// `{` body `}`
+    Block {
+        /// Statements in the block.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+    },
+    /// `import` path \[`as` alias\]
+    Import {
+        /// Path of the module.
+        path: BlockExpr,
+        /// Alias of the module, if any.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alias: Option<String>,
+    },
+    /// `export` name \[`as` alias\]
+    Export {
+        /// Variable to export.
+        name: String,
+        /// Exported name, if different.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alias: Option<String>,
+    },
+    /// \[`export`\] `let`|`const` `(` name `,` ... `)` `=` value
+    LetTuple {
+        /// Variable names, or [`None`] for items that are skipped (i.e. `_`).
+        names: Vec<Option<String>>,
+        /// Tuple to destructure.
+        value: BlockExpr,
+        /// Are the variables constants?
+        #[serde(default, skip_serializing_if = "is_false")]
+        constant: bool,
+        /// Are the variables exported?
+        #[serde(default, skip_serializing_if = "is_false")]
+        exported: bool,
+    },
+    /// `switch` value `{` case `,` ... `}`
+    Switch {
+        /// Value to match.
+        value: BlockExpr,
+        /// Cases, tested in order.
+        #[serde(default)]
+        cases: Vec<BlockSwitchCase>,
+    },
+    /// `yield` value
+    Yield {
+        /// Value to yield.
+        value: BlockExpr,
+    },
+    /// An expression as a statement.
+    Expr {
+        /// The expression.
+        expr: BlockExpr,
+    },
+}
+
+/// An expression in the blocks format.
+///
+/// The `"type"` of each node is the name of the variant in `snake_case`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BlockExpr {
+    /// `()`
+    Unit,
+    /// Boolean constant.
+    Bool {
+        /// Value.
+        value: bool,
+    },
+    /// Integer constant.
+    Int {
+        /// Value.
+        value: INT,
+    },
+    /// Floating-point constant.
+    ///
+    /// Not available under `no_float`.
+    #[cfg(not(feature = "no_float"))]
+    Float {
+        /// Value.
+        value: crate::FLOAT,
+    },
+    /// Character constant.
+    Char {
+        /// Value.
+        value: char,
+    },
+    /// String constant.
+    String {
+        /// Value.
+        value: String,
+    },
+    /// Interpolated string, made up of the string values of its parts.
+    Interpolated {
+        /// Parts of the string.
+        #[serde(default)]
+        parts: Vec<BlockExpr>,
+    },
+    /// `(` item `,` ... `)`
+    Tuple {
+        /// Items of the tuple.
+        #[serde(default)]
+        items: Vec<BlockExpr>,
+    },
+    /// `[` item `,` ... `]`
+    Array {
+        /// Items of the array.
+        #[serde(default)]
+        items: Vec<BlockExpr>,
+    },
+    /// `#{` key `:` value `,` ... `}`
+    Map {
+        /// Properties of the object map.
+        #[serde(default)]
+        entries: Vec<BlockMapEntry>,
+    },
+    /// \[namespace `::` ...\] name
+    Variable {
+        /// Variable name.
+        name: String,
+        /// Namespace path, if any.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        namespace: Vec<String>,
+    },
+    /// object `.` name | object `?.` name
+    Property {
+        /// Object.
+        object: Box<BlockExpr>,
+        /// Property name.
+        name: String,
+        /// Is the access skipped if the object is `()` (i.e. `?.`)?
+        #[serde(default, skip_serializing_if = "is_false")]
+        optional: bool,
+    },
+    /// object `[` index `]` | object `?[` index `]`
+    Index {
+        /// Object.
+        object: Box<BlockExpr>,
+        /// Index.
+        index: Box<BlockExpr>,
+        /// Is the access skipped if the object is `()` (i.e. `?[`)?
+        #[serde(default, skip_serializing_if = "is_false")]
+        optional: bool,
+    },
+    /// object `.` name `(` arg `,` ... `)`
+    Method {
+        /// Object.
+        object: Box<BlockExpr>,
+        /// Method name.
+        name: String,
+        /// Arguments.
+        #[serde(default)]
+        args: Vec<BlockExpr>,
+        /// Is the call skipped if the object is `()` (i.e. `?.`)?
+        #[serde(default, skip_serializing_if = "is_false")]
+        optional: bool,
+    },
+    /// \[namespace `::` ...\] name \[`!`\] `(` arg `,` ... `)`
+    Call {
+        /// Function name.
+        name: String,
+        /// Namespace path, if any.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        namespace: Vec<String>,
+        /// Arguments.
+        #[serde(default)]
+        args: Vec<BlockExpr>,
+        /// Does the function run in the scope of the caller (i.e. `!`)?
+        #[serde(default, skip_serializing_if = "is_false")]
+        capture: bool,
+    },
+    /// A unary (e.g. `!`) or binary (e.g. `+`, `&&`) operator.
+    Operator {
+        /// Operator symbol.
+        op: String,
+        /// One or two operands.
+        args: Vec<BlockExpr>,
+    },
+    /// `|` param `,` ... `|` `{` body `}`
+    Closure {
+        /// Names of parameters.
+        #[serde(default)]
+        params: Vec<String>,
+        /// Closure body.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+    },
+    /// `{` body `}` - statements block as an expression.
+    Block {
+        /// Statements in the block.
+        #[serde(default)]
+        body: Vec<BlockStmt>,
+    },
+    /// Custom syntax.
+    ///
+    /// Not available under `no_custom_syntax`.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    Custom {
+        /// Tokens of the custom syntax, with markers (e.g. `$expr$`) in place of the inputs.
+        tokens: Vec<String>,
+        /// Inputs, one for each marker.
+        ///
+        /// `$ident$` takes a variable, `$symbol$` takes a string holding the symbol, and
+        /// `$block$` takes a statements block.
+        #[serde(default)]
+        inputs: Vec<BlockExpr>,
+    },
+}
+
+/// A case of a `switch` statement in the blocks format.
+///
+/// A case with neither values nor a pattern is the default case (i.e. `_`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockSwitchCase {
+    /// Values matching this case (i.e. value `|` value ...), if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<BlockExpr>,
+    /// Pattern matching this case, if any.  A case cannot have both values and a pattern.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<BlockSwitchPattern>,
+    /// Condition of this case (i.e. `if` condition), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<BlockExpr>,
+    /// Statements to run if this case matches.
+    #[serde(default)]
+    pub body: Vec<BlockStmt>,
+}
+
+/// A pattern in a `switch` case in the blocks format.
+///
+/// The `"type"` of each node is the name of the variant in `snake_case`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum BlockSwitchPattern {
+    /// `is` name
+    Type {
+        /// Type name (as returned by `type_of`).
+        name: String,
+    },
+    /// `case` `[` variable `,` ... `]`
+    Array {
+        /// Variables bound to the items, or [`None`] for items that are skipped (i.e. `_`).
+        items: Vec<Option<String>>,
+    },
+    /// `case` `{` property `,` ... `}`
+    Map {
+        /// Properties, each bound to a variable of the same name.
+        properties: Vec<String>,
+    },
+}
+
+/// A property of an object map literal in the blocks format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockMapEntry {
+    /// Property name.
+    pub key: String,
+    /// Property value.
+    pub value: BlockExpr,
+}
+
+#[inline(always)]
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[inline(always)]
+fn default_assign_op() -> String {
+    Token::Equals.literal_syntax().into()
+}
+
+/// Make the error for a language construct that cannot be converted into blocks.
+#[must_use]
+fn unsupported(what: &str, pos: Position) -> ParseError {
+    ParseErrorType::UnsupportedInBlocks(what.into()).into_err(pos)
+}
+
+/// Is a token of custom syntax a marker for an input (e.g. `$expr$`)?
+#[cfg(not(feature = "no_custom_syntax"))]
+#[must_use]
+fn is_custom_syntax_marker(token: &str) -> bool {
+    token.len() > 2 && token.starts_with('$') && token.ends_with('$')
+}
+
+/// Convert the names in a namespace into a list.
+#[cfg(not(feature = "no_module"))]
+#[must_use]
+fn namespace_to_blocks(namespace: &crate::ast::Namespace) -> Vec<String> {
+    namespace.iter().map(|id| id.name.to_string()).collect()
+}
+/// Convert the names in a namespace into a list.
+#[cfg(feature = "no_module")]
+#[must_use]
+fn namespace_to_blocks(_: &()) -> Vec<String> {
+    Vec::new()
+}
+
+/// Convert a list of [statements][Stmt] into blocks.
+fn stmts_to_blocks(ast: &AST, stmts: &[Stmt]) -> ParseResult<Vec<BlockStmt>> {
+    stmts
+        .iter()
+        .filter_map(|stmt| stmt_to_block(ast, stmt).transpose())
+        .collect()
+}
+
+/// Convert a list of [expressions][Expr] into blocks.
+fn exprs_to_blocks(ast: &AST, exprs: &[Expr]) -> ParseResult<Vec<BlockExpr>> {
+    exprs.iter().map(|expr| expr_to_block(ast, expr)).collect()
+}
+
+/// Convert a [statement][Stmt] into a block, or [`None`] if it does not map to any language
+/// structure.
+fn stmt_to_block(ast: &AST, stmt: &Stmt) -> ParseResult<Option<BlockStmt>> {
+    let name_of = |id: &crate::ast::Ident| {
+        if id.name.is_empty() {
+            None
+        } else {
+            Some(id.name.to_string())
+        }
+    };
+
+    Ok(Some(match stmt {
+        Stmt::Noop(..) => return Ok(None),
+        #[cfg(not(feature = "no_closure"))]
+        Stmt::Share(..) => return Ok(None),
+
+        Stmt::If(x, ..) => BlockStmt::If {
+            condition: expr_to_block(ast, &x.0)?,
+            then_body: stmts_to_blocks(ast, &x.1)?,
+            else_body: stmts_to_blocks(ast, &x.2)?,
+        },
+        Stmt::While(x, ..) if matches!(x.0, Expr::Unit(..)) => BlockStmt::Loop {
+            body: stmts_to_blocks(ast, &x.1)?,
+        },
+        Stmt::While(x, ..) => BlockStmt::While {
+            condition: expr_to_block(ast, &x.0)?,
+            body: stmts_to_blocks(ast, &x.1)?,
+        },
+        Stmt::Do(x, options, ..) => BlockStmt::Do {
+            body: stmts_to_blocks(ast, &x.1)?,
+            condition: expr_to_block(ast, &x.0)?,
+            until: options.contains(ASTFlags::NEGATED),
+        },
+        Stmt::For(x, ..) => BlockStmt::For {
+            variable: x.0.name.to_string(),
+            counter: name_of(&x.1),
+            iterable: expr_to_block(ast, &x.2)?,
+            body: stmts_to_blocks(ast, &x.3)?,
+        },
+        Stmt::Var(x, options, ..) => BlockStmt::Let {
+            name: x.0.name.to_string(),
+            value: match x.1 {
+                Expr::Unit(..) => None,
+                ref expr => Some(expr_to_block(ast, expr)?),
+            },
+            constant: options.contains(ASTFlags::CONSTANT),
+            exported: options.contains(ASTFlags::EXPORTED),
+        },
+        Stmt::Assignment(x) => {
+            let (op, BinaryExpr { lhs, rhs }) = &**x;
+
+            BlockStmt::Assign {
+                target: expr_to_block(ast, lhs)?,
+                op: op.op_assign.into(),
+                value: expr_to_block(ast, rhs)?,
+            }
+        }
+        Stmt::FnCall(x, ..) => BlockStmt::Expr {
+            expr: fn_call_to_block(ast, x)?,
+        },
+        Stmt::Block(x) => BlockStmt::Block {
+            body: stmts_to_blocks(ast, x)?,
+        },
+        Stmt::TryCatch(x, ..) => BlockStmt::Try {
+            body: stmts_to_blocks(ast, &x.try_block)?,
+            catch_variable: name_of(&x.catch_var),
+            catch: stmts_to_blocks(ast, &x.catch_block)?,
+        },
+        Stmt::Expr(expr) => BlockStmt::Expr {
+            expr: expr_to_block(ast, expr)?,
+        },
+        Stmt::BreakLoop(options, ..) if options.contains(ASTFlags::BREAK) => BlockStmt::Break,
+        Stmt::BreakLoop(..) => BlockStmt::Continue,
+        Stmt::Return(expr, options, ..) => {
+            let value = expr
+                .as_ref()
+                .map(|expr| expr_to_block(ast, expr))
+                .transpose()?;
+
+            if options.contains(ASTFlags::BREAK) {
+                BlockStmt::Throw { value }
+            } else {
+                BlockStmt::Return { value }
+            }
+        }
+        #[cfg(not(feature = "no_function"))]
+        Stmt::Yield(expr, ..) => BlockStmt::Yield {
+            value: expr_to_block(ast, expr)?,
+        },
+        #[cfg(not(feature = "no_module"))]
+        Stmt::Import(x, ..) => BlockStmt::Import {
+            path: expr_to_block(ast, &x.0)?,
+            alias: name_of(&x.1),
+        },
+        #[cfg(not(feature = "no_module"))]
+        Stmt::Export(x, ..) => BlockStmt::Export {
+            name: x.0.name.to_string(),
+            alias: name_of(&x.1).filter(|alias| *alias != x.0.name),
+        },
+
+        Stmt::VarTuple(x, options, ..) => BlockStmt::LetTuple {
+            names: x.0.iter().map(|v| v.as_ref().and_then(name_of)).collect(),
+            value: expr_to_block(ast, &x.1)?,
+            constant: options.contains(ASTFlags::CONSTANT),
+            exported: options.contains(ASTFlags::EXPORTED),
+        },
+        Stmt::Switch(x, ..) => {
+            let (value, cases) = &**x;
+
+            BlockStmt::Switch {
+                value: expr_to_block(ast, value)?,
+                cases: cases
+                    .expressions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, case)| switch_case_to_block(ast, cases, index, case))
+                    .collect::<ParseResult<_>>()?,
+            }
+        }
+    }))
+}
+
+/// Convert a case of a `switch` statement into a block.
+fn switch_case_to_block(
+    ast: &AST,
+    cases: &crate::ast::SwitchCasesCollection,
+    index: usize,
+    case: &crate::ast::ConditionalExpr,
+) -> ParseResult<BlockSwitchCase> {
+    use crate::ast::SwitchPattern;
+
+    let values = cases
+        .values
+        .iter()
+        .filter(|(.., n)| *n == index)
+        .map(|(value, ..)| value_to_block(ast, value, case.expr.position()))
+        .collect::<ParseResult<_>>()?;
+
+    let pattern = cases
+        .patterns
+        .iter()
+        .find(|p| p.index == index)
+        .map(|p| match p.pattern {
+            SwitchPattern::Type(ref name) => BlockSwitchPattern::Type {
+                name: name.to_string(),
+            },
+            #[cfg(not(feature = "no_index"))]
+            SwitchPattern::Array(ref vars) => BlockSwitchPattern::Array {
+                items: vars
+                    .iter()
+                    .map(|v| v.as_ref().map(|v| v.name.to_string()))
+                    .collect(),
+            },
+            #[cfg(not(feature = "no_object"))]
+            SwitchPattern::Map(ref props) => BlockSwitchPattern::Map {
+                properties: props.iter().map(|p| p.name.to_string()).collect(),
+            },
+        });
+
+    let condition = match case.condition {
+        Expr::BoolConstant(true, ..) => None,
+        ref condition => Some(expr_to_block(ast, condition)?),
+    };
+
+    let body = match expr_to_block(ast, &case.expr)? {
+        BlockExpr::Block { body } => body,
+        expr => vec![BlockStmt::Expr { expr }],
+    };
+
+    Ok(BlockSwitchCase {
+        values,
+        pattern,
+        condition,
+        body,
+    })
+}
+
+/// Convert an [expression][Expr] into a block.
+fn expr_to_block(ast: &AST, expr: &Expr) -> ParseResult<BlockExpr> {
+    Ok(match expr {
+        Expr::DynamicConstant(value, pos) => value_to_block(ast, value, *pos)?,
+        Expr::BoolConstant(value, ..) => BlockExpr::Bool { value: *value },
+        Expr::IntegerConstant(value, ..) => BlockExpr::Int { value: *value },
+        #[cfg(not(feature = "no_float"))]
+        Expr::FloatConstant(value, ..) => BlockExpr::Float { value: **value },
+        Expr::CharConstant(value, ..) => BlockExpr::Char { value: *value },
+        Expr::StringConstant(value, ..) => BlockExpr::String {
+            value: value.to_string(),
+        },
+        Expr::InterpolatedString(x, ..) => BlockExpr::Interpolated {
+            parts: x
+                .iter()
+                .map(|part| match part {
+                    // Each interpolated expression is parsed as a statements block
+                    Expr::Stmt(block) => match block.as_ref().as_ref() {
+                        [Stmt::Expr(expr)] => expr_to_block(ast, expr),
+                        _ => expr_to_block(ast, part),
+                    },
+                    _ => expr_to_block(ast, part),
+                })
+                .collect::<ParseResult<_>>()?,
+        },
+        Expr::Array(x, ..) => BlockExpr::Array {
+            items: exprs_to_blocks(ast, x)?,
+        },
+        Expr::Map(x, ..) => BlockExpr::Map {
+            entries: x
+                .0
+                .iter()
+                .map(|(key, value)| {
+                    Ok(BlockMapEntry {
+                        key: key.name.to_string(),
+                        value: expr_to_block(ast, value)?,
+                    })
+                })
+                .collect::<ParseResult<_>>()?,
+        },
+        Expr::Unit(..) => BlockExpr::Unit,
+        Expr::Variable(x, ..) => BlockExpr::Variable {
+            name: x.3.to_string(),
+            namespace: namespace_to_blocks(&x.1),
+        },
+        Expr::Stmt(block) => match closure_to_block(ast, block)? {
+            Some(closure) => closure,
+            None => BlockExpr::Block {
+                body: stmts_to_blocks(ast, block)?,
+            },
+        },
+        Expr::FnCall(x, ..) => fn_call_to_block(ast, x)?,
+        Expr::Dot(x, options, ..) => {
+            chain_to_block(ast, expr_to_block(ast, &x.lhs)?, &x.rhs, true, *options)?
+        }
+        Expr::Index(x, options, ..) => {
+            chain_to_block(ast, expr_to_block(ast, &x.lhs)?, &x.rhs, false, *options)?
+        }
+        Expr::And(x, ..) | Expr::Or(x, ..) | Expr::Coalesce(x, ..) => BlockExpr::Operator {
+            op: match expr {
+                Expr::And(..) => Token::And,
+                Expr::Or(..) => Token::Or,
+                _ => Token::DoubleQuestion,
+            }
+            .literal_syntax()
+            .into(),
+            args: vec![expr_to_block(ast, &x.lhs)?, expr_to_block(ast, &x.rhs)?],
+        },
+
+        Expr::Tuple(x, ..) => BlockExpr::Tuple {
+            items: exprs_to_blocks(ast, x)?,
+        },
+        #[cfg(not(feature = "no_custom_syntax"))]
+        Expr::Custom(x, ..) => {
+            // Skip the name of the syntax variant, if any, following the inputs
+            let num_inputs = x
+                .tokens
+                .iter()
+                .filter(|t| is_custom_syntax_marker(t))
+                .count()
+                .min(x.inputs.len());
+
+            BlockExpr::Custom {
+                tokens: x.tokens.iter().map(|t| t.to_string()).collect(),
+                inputs: exprs_to_blocks(ast, &x.inputs[..num_inputs])?,
+            }
+        }
+
+        Expr::Property(.., pos) | Expr::MethodCall(.., pos) => {
+            return Err(unsupported(
+                "property or method call outside of a dot chain",
+                *pos,
+            ))
+        }
+    })
+}
+
+/// Convert a function call into a block.
+fn fn_call_to_block(ast: &AST, x: &FnCallExpr) -> ParseResult<BlockExpr> {
+    let args = exprs_to_blocks(ast, &x.args)?;
+
+    #[cfg(not(feature = "no_module"))]
+    let namespace = namespace_to_blocks(&x.namespace);
+    #[cfg(feature = "no_module")]
+    let namespace = Vec::new();
+
+    Ok(if x.is_native_operator && namespace.is_empty() {
+        BlockExpr::Operator {
+            op: x.name.to_string(),
+            args,
+        }
+    } else {
+        BlockExpr::Call {
+            name: x.name.to_string(),
+            namespace,
+            args,
+            capture: x.capture_parent_scope,
+        }
+    })
+}
+
+/// Convert the rest of a dot/index chain into blocks, applying it on an object.
+///
+/// Chains are right-nested in the [`AST`] but left-nested in blocks.
+fn chain_to_block(
+    ast: &AST,
+    object: BlockExpr,
+    rhs: &Expr,
+    is_dot: bool,
+    options: ASTFlags,
+) -> ParseResult<BlockExpr> {
+    let optional = options.contains(ASTFlags::NEGATED);
+
+    let (member, rest) = match rhs {
+        Expr::Dot(x, ..) | Expr::Index(x, ..) if !options.contains(ASTFlags::BREAK) => {
+            (&x.lhs, Some(rhs))
+        }
+        _ => (rhs, None),
+    };
+
+    let object = Box::new(object);
+
+    let object = match member {
+        _ if !is_dot => BlockExpr::Index {
+            object,
+            index: expr_to_block(ast, member)?.into(),
+            optional,
+        },
+        Expr::Property(x, ..) => BlockExpr::Property {
+            object,
+            name: x.2.to_string(),
+            optional,
+        },
+        Expr::MethodCall(x, ..) => BlockExpr::Method {
+            object,
+            name: x.name.to_string(),
+            args: exprs_to_blocks(ast, &x.args)?,
+            optional,
+        },
+        expr => {
+            return Err(unsupported(
+                "dot chain member other than a property or method call",
+                expr.position(),
+            ))
+        }
+    };
+
+    match rest {
+        Some(Expr::Dot(x, options, ..)) => chain_to_block(ast, object, &x.rhs, true, *options),
+        Some(Expr::Index(x, options, ..)) => chain_to_block(ast, object, &x.rhs, false, *options),
+        _ => Ok(object),
+    }
+}
+
+/// Convert a constant value into a block.
+fn value_to_block(ast: &AST, value: &Dynamic, pos: Position) -> ParseResult<BlockExpr> {
+    Ok(match value.0 {
+        Union::Unit(..) => BlockExpr::Unit,
+        Union::Bool(value, ..) => BlockExpr::Bool { value },
+        Union::Int(value, ..) => BlockExpr::Int { value },
+        #[cfg(not(feature = "no_float"))]
+        Union::Float(ref value, ..) => BlockExpr::Float { value: **value },
+        Union::Char(value, ..) => BlockExpr::Char { value },
+        Union::Str(ref value, ..) => BlockExpr::String {
+            value: value.to_string(),
+        },
+        #[cfg(not(feature = "no_index"))]
+        Union::Array(ref items, ..) => BlockExpr::Array {
+            items: items
+                .iter()
+                .map(|item| value_to_block(ast, item, pos))
+                .collect::<ParseResult<_>>()?,
+        },
+        #[cfg(not(feature = "no_object"))]
+        Union::Map(ref map, ..) => BlockExpr::Map {
+            entries: map
+                .iter()
+                .map(|(key, value)| {
+                    Ok(BlockMapEntry {
+                        key: key.to_string(),
+                        value: value_to_block(ast, value, pos)?,
+                    })
+                })
+                .collect::<ParseResult<_>>()?,
+        },
+        Union::FnPtr(ref fn_ptr, ..) if fn_ptr.curry().is_empty() => {
+            fn_ptr_to_block(ast, fn_ptr.fn_name(), 0, pos)?
+        }
+        _ if value.is::<crate::Tuple>() => BlockExpr::Tuple {
+            items: value
+                .read_lock::<crate::Tuple>()
+                .unwrap()
+                .iter()
+                .map(|item| value_to_block(ast, item, pos))
+                .collect::<ParseResult<_>>()?,
+        },
+        // Ranges in `switch` cases are kept as values
+        _ if value.is::<crate::ExclusiveRange>() => {
+            let range = value.read_lock::<crate::ExclusiveRange>().unwrap();
+            BlockExpr::Operator {
+                op: Token::ExclusiveRange.literal_syntax().into(),
+                args: vec![
+                    BlockExpr::Int { value: range.start },
+                    BlockExpr::Int { value: range.end },
+                ],
+            }
+        }
+        _ if value.is::<crate::InclusiveRange>() => {
+            let range = value.read_lock::<crate::InclusiveRange>().unwrap();
+            BlockExpr::Operator {
+                op: Token::InclusiveRange.literal_syntax().into(),
+                args: vec![
+                    BlockExpr::Int {
+                        value: *range.start(),
+                    },
+                    BlockExpr::Int {
+                        value: *range.end(),
+                    },
+                ],
+            }
+        }
+        _ => return Err(unsupported(value.type_name(), pos)),
+    })
+}
+
+/// Convert a function pointer into a block.
+///
+/// Pointers to anonymous functions are converted into closures, dropping the parameters that
+/// hold the first `num_captured` captured variables.
+fn fn_ptr_to_block(
+    ast: &AST,
+    fn_name: &str,
+    num_captured: usize,
+    pos: Position,
+) -> ParseResult<BlockExpr> {
+    #[cfg(not(feature = "no_function"))]
+    if crate::parser::is_anonymous_fn(fn_name) {
+        let fn_def = ast
+            .iter_fn_def()
+            .find(|f| f.name == fn_name)
+            .ok_or_else(|| unsupported("closure", pos))?;
+
+        return Ok(BlockExpr::Closure {
+            params: fn_def
+                .params
+                .iter()
+                .skip(num_captured)
+                .map(|p| p.to_string())
+                .collect(),
+            body: stmts_to_blocks(ast, &fn_def.body)?,
+        });
+    }
+
+    let _ = (ast, num_captured, pos);
+
+    Ok(BlockExpr::Call {
+        name: crate::engine::KEYWORD_FN_PTR.into(),
+        namespace: Vec::new(),
+        args: vec![BlockExpr::String {
+            value: fn_name.into(),
+        }],
+        capture: false,
+    })
+}
+
+/// Convert a statements block into a closure, if it is made up of sharing the captured variables
+/// followed by currying them into a pointer to an anonymous function.
+fn closure_to_block(ast: &AST, stmts: &[Stmt]) -> ParseResult<Option<BlockExpr>> {
+    #[cfg(not(feature = "no_closure"))]
+    if let Some((last, shared)) = stmts.split_last() {
+        let call = match last {
+            Stmt::Expr(expr) => match **expr {
+                Expr::FnCall(ref x, ..) => Some(x),
+                _ => None,
+            },
+            Stmt::FnCall(x, ..) => Some(x),
+            _ => None,
+        };
+
+        match call {
+            Some(x)
+                if !shared.is_empty()
+                    && shared.iter().all(|s| matches!(s, Stmt::Share(..)))
+                    && x.name == crate::engine::KEYWORD_FN_PTR_CURRY =>
+            {
+                if let Some(Expr::DynamicConstant(value, pos)) = x.args.first() {
+                    if let Union::FnPtr(ref fn_ptr, ..) = value.0 {
+                        if crate::parser::is_anonymous_fn(fn_ptr.fn_name()) {
+                            let num_captured = x.args.len() - 1;
+                            let closure =
+                                fn_ptr_to_block(ast, fn_ptr.fn_name(), num_captured, *pos)?;
+                            return Ok(Some(closure));
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let _ = (ast, stmts);
+
+    Ok(None)
+}
+
+/// Convert an unoptimized [`AST`] into the [blocks format][BlockScript].
+fn ast_to_blocks(ast: &AST) -> ParseResult<BlockScript> {
+    #[cfg(not(feature = "no_function"))]
+    let functions = ast
+        .iter_fn_def()
+        .filter(|f| !crate::parser::is_anonymous_fn(&f.name))
+        .map(|f| {
+            Ok(BlockFnDef {
+                name: f.name.to_string(),
+                params: f.params.iter().map(|p| p.to_string()).collect(),
+                private: f.access == crate::FnAccess::Private,
+                body: stmts_to_blocks(ast, &f.body)?,
+            })
+        })
+        .collect::<ParseResult<_>>()?;
+    #[cfg(feature = "no_function")]
+    let functions = Vec::new();
+
+    Ok(BlockScript {
+        functions,
+        body: stmts_to_blocks(ast, ast.statements())?,
+    })
+}
+
+/// Writer of script text from blocks.
+struct ScriptWriter {
+    /// Script text.
+    script: String,
+    /// Current indentation level.
+    indent: usize,
+}
+
+impl ScriptWriter {
+    /// Start a new line at the current indentation level.
+    fn new_line(&mut self) {
+        if !self.script.is_empty() {
+            self.script.push('\n');
+        }
+        for _ in 0..self.indent {
+            self.script.push_str("    ");
+        }
+    }
+    /// Write an identifier, checking that it is valid.
+    fn write_ident(&mut self, name: &str) -> ParseResult<()> {
+        if !is_valid_identifier(name.chars()) {
+            return Err(LexError::MalformedIdentifier(name.into()).into_err(Position::NONE));
+        }
+        self.script.push_str(name);
+        Ok(())
+    }
+    /// Write a namespace path, if any, followed by an identifier.
+    fn write_qualified_ident(&mut self, namespace: &[String], name: &str) -> ParseResult<()> {
+        for ns in namespace {
+            self.write_ident(ns)?;
+            self.script.push_str(Token::DoubleColon.literal_syntax());
+        }
+        self.write_ident(name)
+    }
+    /// Write a quoted string or character literal.
+    fn write_quoted(&mut self, text: &str, quote: char) {
+        self.script.push(quote);
+        for ch in text.chars() {
+            match ch {
+                '\\' => self.script.push_str("\\\\"),
+                '\n' => self.script.push_str("\\n"),
+                '\r' => self.script.push_str("\\r"),
+                '\t' => self.script.push_str("\\t"),
+                _ if ch == quote => {
+                    self.script.push('\\');
+                    self.script.push(ch);
+                }
+                _ if ch.is_control() => self.script.push_str(&format!("\\U{:08X}", ch as u32)),
+                _ => self.script.push(ch),
+            }
+        }
+        self.script.push(quote);
+    }
+    /// Write a list of items separated by commas.
+    fn write_list<T>(
+        &mut self,
+        items: &[T],
+        mut write: impl FnMut(&mut Self, &T) -> ParseResult<()>,
+    ) -> ParseResult<()> {
+        for (i, item) in items.iter().enumerate() {
+            if i > 0 {
+                self.script.push_str(", ");
+            }
+            write(self, item)?;
+        }
+        Ok(())
+    }
+    /// Write a list of statements enclosed in braces.
+    fn write_block(&mut self, stmts: &[BlockStmt]) -> ParseResult<()> {
+        if stmts.is_empty() {
+            self.script.push_str("{}");
+            return Ok(());
+        }
+
+        self.script.push('{');
+        self.indent += 1;
+        self.write_stmts(stmts)?;
+        self.indent -= 1;
+        self.new_line();
+        self.script.push('}');
+        Ok(())
+    }
+    /// Write a list of statements, each on its own line.
+    fn write_stmts(&mut self, stmts: &[BlockStmt]) -> ParseResult<()> {
+        for (i, stmt) in stmts.iter().enumerate() {
+            self.new_line();
+            self.write_stmt(stmt, i == stmts.len() - 1)?;
+        }
+        Ok(())
+    }
+    /// Write a statement.
+    ///
+    /// The last statement in a block is not terminated by a semicolon so that its value is the
+    /// value of the block.
+    fn write_stmt(&mut self, stmt: &BlockStmt, is_last: bool) -> ParseResult<()> {
+        match stmt {
+            BlockStmt::Let {
+                name,
+                value,
+                constant,
+                exported,
+            } => {
+                if *exported {
+                    self.script.push_str("export ");
+                }
+                self.script
+                    .push_str(if *constant { "const " } else { "let " });
+                self.write_ident(name)?;
+                if let Some(value) = value {
+                    self.script.push_str(" = ");
+                    self.write_expr(value)?;
+                }
+                self.script.push(';');
+            }
+            BlockStmt::Assign { target, op, value } => {
+                match Token::lookup_from_syntax(op) {
+                    Some(Token::Equals) => (),
+                    Some(token) if token.is_op_assignment() => (),
+                    _ => {
+                        return Err(
+                            ParseErrorType::UnknownOperator(op.into()).into_err(Position::NONE)
+                        )
+                    }
+                }
+                self.write_expr(target)?;
+                self.script.push(' ');
+                self.script.push_str(op);
+                self.script.push(' ');
+                self.write_expr(value)?;
+                self.script.push(';');
+            }
+            BlockStmt::If {
+                condition,
+                then_body,
+                else_body,
+            } => {
+                self.script.push_str("if ");
+                self.write_expr(condition)?;
+                self.script.push(' ');
+                self.write_block(then_body)?;
+                match else_body.as_slice() {
+                    [] => (),
+                    [stmt @ BlockStmt::If { .. }] => {
+                        self.script.push_str(" else ");
+                        self.write_stmt(stmt, true)?;
+                    }
+                    _ => {
+                        self.script.push_str(" else ");
+                        self.write_block(else_body)?;
+                    }
+                }
+            }
+            BlockStmt::While { condition, body } => {
+                self.script.push_str("while ");
+                self.write_expr(condition)?;
+                self.script.push(' ');
+                self.write_block(body)?;
+            }
+            BlockStmt::Loop { body } => {
+                self.script.push_str("loop ");
+                self.write_block(body)?;
+            }
+            BlockStmt::Do {
+                body,
+                condition,
+                until,
+            } => {
+                self.script.push_str("do ");
+                self.write_block(body)?;
+                self.script
+                    .push_str(if *until { " until " } else { " while " });
+                self.write_expr(condition)?;
+                self.script.push(';');
+            }
+            BlockStmt::For {
+                variable,
+                counter,
+                iterable,
+                body,
+            } => {
+                self.script.push_str("for ");
+                if let Some(counter) = counter {
+                    self.script.push('(');
+                    self.write_ident(variable)?;
+                    self.script.push_str(", ");
+                    self.write_ident(counter)?;
+                    self.script.push(')');
+                } else {
+                    self.write_ident(variable)?;
+                }
+                self.script.push_str(" in ");
+                self.write_expr(iterable)?;
+                self.script.push(' ');
+                self.write_block(body)?;
+            }
+            BlockStmt::Break => self.script.push_str("break;"),
+            BlockStmt::Continue => self.script.push_str("continue;"),
+            BlockStmt::Return { value } | BlockStmt::Throw { value } => {
+                self.script.push_str(match stmt {
+                    BlockStmt::Return { .. } => "return",
+                    _ => "throw",
+                });
+                if let Some(value) = value {
+                    self.script.push(' ');
+                    self.write_expr(value)?;
+                }
+                self.script.push(';');
+            }
+            BlockStmt::Try {
+                body,
+                catch_variable,
+                catch,
+            } => {
+                self.script.push_str("try ");
+                self.write_block(body)?;
+                self.script.push_str(" catch ");
+                if let Some(var) = catch_variable {
+                    self.script.push('(');
+                    self.write_ident(var)?;
+                    self.script.push_str(") ");
+                }
+                self.write_block(catch)?;
+            }
+            BlockStmt::Block { body } => self.write_block(body)?,
+            BlockStmt::Import { path, alias } => {
+                self.script.push_str("import ");
+                self.write_expr(path)?;
+                if let Some(alias) = alias {
+                    self.script.push_str(" as ");
+                    self.write_ident(alias)?;
+                }
+                self.script.push(';');
+            }
+            BlockStmt::Export { name, alias } => {
+                self.script.push_str("export ");
+                self.write_ident(name)?;
+                if let Some(alias) = alias {
+                    self.script.push_str(" as ");
+                    self.write_ident(alias)?;
+                }
+                self.script.push(';');
+            }
+            BlockStmt::LetTuple {
+                names,
+                value,
+                constant,
+                exported,
+            } => {
+                if *exported {
+                    self.script.push_str("export ");
+                }
+                self.script
+                    .push_str(if *constant { "const (" } else { "let (" });
+                self.write_list(names, |w, name| match name {
+                    Some(name) => w.write_ident(name),
+                    None => {
+                        w.script.push('_');
+                        Ok(())
+                    }
+                })?;
+                self.script.push_str(") = ");
+                self.write_expr(value)?;
+                self.script.push(';');
+            }
+            BlockStmt::Switch { value, cases } => {
+                self.script.push_str("switch ");
+                self.write_expr(value)?;
+                if cases.is_empty() {
+                    self.script.push_str(" {}");
+                    return Ok(());
+                }
+                self.script.push_str(" {");
+                self.indent += 1;
+                for (i, case) in cases.iter().enumerate() {
+                    self.new_line();
+                    self.write_switch_case(case)?;
+                    if i < cases.len() - 1 {
+                        self.script.push(',');
+                    }
+                }
+                self.indent -= 1;
+                self.new_line();
+                self.script.push('}');
+            }
+            BlockStmt::Yield { value } => {
+                self.script.push_str("yield ");
+                self.write_expr(value)?;
+                self.script.push(';');
+            }
+            BlockStmt::Expr { expr } => {
+                self.write_expr(expr)?;
+                if !is_last {
+                    self.script.push(';');
+                }
+            }
+        }
+
+        Ok(())
+    }
+    /// Write a case of a `switch` statement.
+    fn write_switch_case(&mut self, case: &BlockSwitchCase) -> ParseResult<()> {
+        match (case.values.as_slice(), &case.pattern) {
+            ([], None) => self.script.push('_'),
+            (values, None) => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        self.script.push_str(" | ");
+                    }
+                    self.write_expr(value)?;
+                }
+            }
+            ([], Some(BlockSwitchPattern::Type { name })) => {
+                self.script.push_str("is ");
+                if is_valid_identifier(name.chars()) {
+                    self.script.push_str(name);
+                } else {
+                    self.write_quoted(name, '"');
+                }
+            }
+            ([], Some(BlockSwitchPattern::Array { items })) => {
+                self.script.push_str("case [");
+                self.write_list(items, |w, item| match item {
+                    Some(name) => w.write_ident(name),
+                    None => {
+                        w.script.push('_');
+                        Ok(())
+                    }
+                })?;
+                self.script.push(']');
+            }
+            ([], Some(BlockSwitchPattern::Map { properties })) => {
+                self.script.push_str("case {");
+                self.write_list(properties, |w, p| w.write_ident(p))?;
+                self.script.push('}');
+            }
+            (..) => {
+                return Err(LexError::ImproperSymbol(
+                    "case".into(),
+                    "A switch case cannot have both values and a pattern".into(),
+                )
+                .into_err(Position::NONE))
+            }
+        }
+        if let Some(ref condition) = case.condition {
+            self.script.push_str(" if ");
+            self.write_expr(condition)?;
+        }
+        self.script.push_str(" => ");
+        self.write_block(&case.body)
+    }
+    /// Write an expression, enclosing it in parentheses if required.
+    fn write_enclosed(&mut self, expr: &BlockExpr, enclose: bool) -> ParseResult<()> {
+        if enclose {
+            self.script.push('(');
+            self.write_expr(expr)?;
+            self.script.push(')');
+            Ok(())
+        } else {
+            self.write_expr(expr)
+        }
+    }
+    /// Write an object followed by a property access, indexing or method call, enclosing it in
+    /// parentheses if necessary.
+    fn write_object(&mut self, expr: &BlockExpr) -> ParseResult<()> {
+        let enclose = match expr {
+            BlockExpr::Operator { .. } | BlockExpr::Closure { .. } | BlockExpr::Block { .. } => {
+                true
+            }
+            #[cfg(not(feature = "no_custom_syntax"))]
+            BlockExpr::Custom { .. } => true,
+            // Negative numbers are always enclosed
+            BlockExpr::Int { value } => *value >= 0,
+            #[cfg(not(feature = "no_float"))]
+            BlockExpr::Float { value } => value.is_sign_positive(),
+            _ => false,
+        };
+
+        self.write_enclosed(expr, enclose)
+    }
+    /// Write an operand of an operator, enclosing it in parentheses if necessary.
+    ///
+    /// `precedence` is the precedence of the operator, or [`None`] for a unary operator.
+    fn write_operand(
+        &mut self,
+        expr: &BlockExpr,
+        precedence: Option<Precedence>,
+        is_rhs: bool,
+    ) -> ParseResult<()> {
+        let enclose = match (expr, precedence) {
+            (BlockExpr::Operator { op, args }, Some(parent)) if args.len() == 2 => {
+                match Token::lookup_from_syntax(op).and_then(|t| t.precedence()) {
+                    Some(p) if p > parent => false,
+                    // Only `**` binds to the right
+                    Some(p) if p == parent => is_rhs || op == Token::PowerOf.literal_syntax(),
+                    _ => true,
+                }
+            }
+            (BlockExpr::Operator { args, .. }, Some(..)) => args.len() != 1,
+            (BlockExpr::Operator { .. }, None)
+            | (BlockExpr::Closure { .. }, ..)
+            | (BlockExpr::Block { .. }, ..) => true,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            (BlockExpr::Custom { .. }, ..) => true,
+            _ => false,
+        };
+
+        self.write_enclosed(expr, enclose)
+    }
+    /// Write an expression.
+    fn write_expr(&mut self, expr: &BlockExpr) -> ParseResult<()> {
+        match expr {
+            BlockExpr::Unit => self.script.push_str("()"),
+            BlockExpr::Bool { value } => self.script.push_str(if *value {
+                Token::True.literal_syntax()
+            } else {
+                Token::False.literal_syntax()
+            }),
+            BlockExpr::Int { value } if *value < 0 => self.script.push_str(&format!("({value})")),
+            BlockExpr::Int { value } => self.script.push_str(&value.to_string()),
+            #[cfg(not(feature = "no_float"))]
+            BlockExpr::Float { value } => {
+                if !value.is_finite() {
+                    return Err(
+                        LexError::MalformedNumber(value.to_string()).into_err(Position::NONE)
+                    );
+                }
+                let text = format!("{value:?}");
+                if value.is_sign_negative() {
+                    self.script.push_str(&format!("({text})"));
+                } else {
+                    self.script.push_str(&text);
+                }
+            }
+            BlockExpr::Char { value } => self.write_quoted(&value.to_string(), '\''),
+            BlockExpr::String { value } => self.write_quoted(value, '"'),
+            BlockExpr::Interpolated { parts } => {
+                self.script.push('`');
+                for part in parts {
+                    match part {
+                        // Literal text cannot contain `$` (which may start an interpolation)
+                        // or `\r` (which is skipped before `\n`)
+                        BlockExpr::String { value }
+                            if !value.contains('$') && !value.contains('\r') =>
+                        {
+                            self.script.push_str(&value.replace('`', "``"));
+                        }
+                        _ => {
+                            self.script.push_str("${");
+                            self.write_expr(part)?;
+                            self.script.push('}');
+                        }
+                    }
+                }
+                self.script.push('`');
+            }
+            BlockExpr::Tuple { items } => {
+                self.script.push('(');
+                self.write_list(items, Self::write_expr)?;
+                if items.len() == 1 {
+                    self.script.push(',');
+                }
+                self.script.push(')');
+            }
+            BlockExpr::Array { items } => {
+                self.script.push('[');
+                self.write_list(items, Self::write_expr)?;
+                self.script.push(']');
+            }
+            BlockExpr::Map { entries } => {
+                self.script.push_str("#{");
+                self.write_list(entries, |w, entry| {
+                    w.write_quoted(&entry.key, '"');
+                    w.script.push_str(": ");
+                    w.write_expr(&entry.value)
+                })?;
+                self.script.push('}');
+            }
+            BlockExpr::Variable { name, namespace } => {
+                self.write_qualified_ident(namespace, name)?;
+            }
+            BlockExpr::Property {
+                object,
+                name,
+                optional,
+            } => {
+                self.write_object(object)?;
+                self.script.push_str(if *optional { "?." } else { "." });
+                self.write_ident(name)?;
+            }
+            BlockExpr::Index {
+                object,
+                index,
+                optional,
+            } => {
+                self.write_object(object)?;
+                self.script.push_str(if *optional { "?[" } else { "[" });
+                self.write_expr(index)?;
+                self.script.push(']');
+            }
+            BlockExpr::Method {
+                object,
+                name,
+                args,
+                optional,
+            } => {
+                self.write_object(object)?;
+                self.script.push_str(if *optional { "?." } else { "." });
+                self.write_ident(name)?;
+                self.script.push('(');
+                self.write_list(args, Self::write_expr)?;
+                self.script.push(')');
+            }
+            BlockExpr::Call {
+                name,
+                namespace,
+                args,
+                capture,
+            } => {
+                self.write_qualified_ident(namespace, name)?;
+                if *capture {
+                    self.script.push('!');
+                }
+                self.script.push('(');
+                self.write_list(args, Self::write_expr)?;
+                self.script.push(')');
+            }
+            BlockExpr::Operator { op, args } => {
+                let token = Token::lookup_from_syntax(op);
+
+                match (args.as_slice(), token) {
+                    ([arg], Some(Token::UnaryMinus | Token::UnaryPlus | Token::Bang))
+                    | ([arg], Some(Token::Minus | Token::Plus)) => {
+                        self.script.push_str(op);
+                        self.write_operand(arg, None, false)?;
+                    }
+                    ([lhs, rhs], Some(token)) if token.precedence().is_some() => {
+                        let precedence = token.precedence();
+                        self.write_operand(lhs, precedence, false)?;
+                        self.script.push(' ');
+                        self.script.push_str(op);
+                        self.script.push(' ');
+                        self.write_operand(rhs, precedence, true)?;
+                    }
+                    _ => {
+                        return Err(
+                            ParseErrorType::UnknownOperator(op.into()).into_err(Position::NONE)
+                        )
+                    }
+                }
+            }
+            BlockExpr::Closure { params, body } => {
+                self.script.push('|');
+                self.write_list(params, |w, p| w.write_ident(p))?;
+                self.script.push_str("| ");
+                self.write_block(body)?;
+            }
+            BlockExpr::Block { body } => self.write_block(body)?,
+            #[cfg(not(feature = "no_custom_syntax"))]
+            BlockExpr::Custom { tokens, inputs } => self.write_custom(tokens, inputs)?,
+        }
+
+        Ok(())
+    }
+    /// Write custom syntax, checking that each token and symbol is a single token.
+    #[cfg(not(feature = "no_custom_syntax"))]
+    fn write_custom(&mut self, tokens: &[String], inputs: &[BlockExpr]) -> ParseResult<()> {
+        use crate::api::custom_syntax::markers::CUSTOM_SYNTAX_MARKER_SYMBOL;
+
+        let is_symbol =
+            |s: &str| !is_valid_identifier(s.chars()) && Token::lookup_from_syntax(s).is_some();
+        let mut inputs = inputs.iter();
+
+        for (i, token) in tokens.iter().enumerate() {
+            if i > 0 {
+                self.script.push(' ');
+            }
+
+            if !is_custom_syntax_marker(token) {
+                if !is_valid_identifier(token.chars()) && !is_symbol(token) {
+                    return Err(LexError::UnexpectedInput(token.into()).into_err(Position::NONE));
+                }
+                self.script.push_str(token);
+                continue;
+            }
+
+            match (token.as_str(), inputs.next()) {
+                (CUSTOM_SYNTAX_MARKER_SYMBOL, Some(BlockExpr::String { value }))
+                    if is_symbol(value) =>
+                {
+                    self.script.push_str(value);
+                }
+                (CUSTOM_SYNTAX_MARKER_SYMBOL, ..) => {
+                    return Err(
+                        ParseErrorType::MissingSymbol(String::new()).into_err(Position::NONE)
+                    )
+                }
+                (.., Some(input)) => self.write_expr(input)?,
+                (.., None) => {
+                    return Err(ParseErrorType::ExprExpected(token.into()).into_err(Position::NONE))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl BlockScript {
+    /// Generate the text of the script represented by the blocks.
+    ///
+    /// An error is returned if any name is not a valid identifier, or if any operator is unknown.
+    /// Other syntax errors (e.g. assigning to an expression that is not a variable, property or
+    /// index) are only detected when the script is compiled.
+    pub fn to_script(&self) -> Result<String, ParseError> {
+        let mut writer = ScriptWriter {
+            script: String::new(),
+            indent: 0,
+        };
+
+        for f in &self.functions {
+            writer.new_line();
+            if f.private {
+                writer.script.push_str("private ");
+            }
+            writer.script.push_str("fn ");
+            writer.write_ident(&f.name)?;
+            writer.script.push('(');
+            writer.write_list(&f.params, |w, p| w.write_ident(p))?;
+            writer.script.push_str(") ");
+            writer.write_block(&f.body)?;
+            writer.new_line();
+        }
+
+        writer.write_stmts(&self.body)?;
+
+        Ok(writer.script)
+    }
+}
+
+impl Engine {
+    /// Compile a script and convert it into the [blocks format][BlockScript], for exchange with
+    /// visual (block-based) editors.
+    ///
+    /// Exported under the `serde` feature only.
+    ///
+    /// The script is compiled without optimization, so it is converted as written.  Custom syntax
+    /// is converted into its tokens and inputs (so it must also be registered with the [`Engine`]
+    /// compiling the blocks back), except for custom syntax macros which are converted as
+    /// expanded.
+    #[inline]
+    pub fn script_to_blocks(&self, script: &str) -> Result<BlockScript, ParseError> {
+        let ast = self.compile_with_scope_and_optimization_level(
+            &Scope::new(),
+            [script],
+            #[cfg(not(feature = "no_optimize"))]
+            OptimizationLevel::None,
+            #[cfg(feature = "no_optimize")]
+            OptimizationLevel::default(),
+        )?;
+        ast_to_blocks(&ast)
+    }
+    /// Compile a script in the [blocks format][BlockScript] into an [`AST`].
+    ///
+    /// Exported under the `serde` feature only.
+    ///
+    /// The blocks are converted into script text (see [`BlockScript::to_script`]) which is then
+    /// compiled as usual.
+    #[inline]
+    pub fn compile_blocks(&self, blocks: &BlockScript) -> Result<AST, ParseError> {
+        self.compile(blocks.to_script()?)
+    }
+}
@@ -52,6 +52,10 @@ impl<'de> DynamicDeserializer<'de> {
 
 /// Deserialize a [`Dynamic`][crate::Dynamic] value into a Rust type that implements [`serde::Deserialize`].
 ///
+/// Strings and BLOB's are borrowed from the [`Dynamic`][crate::Dynamic] value without copying,
+/// so types holding `&str` or `&[u8]` (or `Cow` with `#[serde(borrow)]`) can be deserialized.
+/// Shared values cannot be borrowed and must first be [flattened][crate::Dynamic::flatten].
+///
 /// # Example
 ///
 /// ```
@@ -151,6 +155,7 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
             Union::FnPtr(..) => self.type_error(),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(..) => self.type_error(),
+            Union::Tuple(..) => self.type_error(),
 
             Union::Variant(ref value, ..) if value.is::<i8>() => self.deserialize_i8(visitor),
             Union::Variant(ref value, ..) if value.is::<i16>() => self.deserialize_i16(visitor),
@@ -350,7 +355,7 @@ impl<'de> Deserializer<'de> for &mut DynamicDeserializer<'de> {
         return self
             .value
             .downcast_ref::<crate::Blob>()
-            .map_or_else(|| self.type_error(), |x| _visitor.visit_bytes(x));
+            .map_or_else(|| self.type_error(), |x| _visitor.visit_borrowed_bytes(x));
 
         #[cfg(feature = "no_index")]
         return self.type_error();
@@ -1,7 +1,7 @@
 //! Implementations of [`serde::Deserialize`].
 
-use crate::{Dynamic, ImmutableString, INT};
-use serde::de::{Deserialize, Deserializer, Error, Visitor};
+use crate::{Dynamic, FnPtr, ImmutableString, StaticVec, INT};
+use serde::de::{Deserialize, Deserializer, Error, IgnoredAny, MapAccess, Visitor};
 use std::fmt;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -163,3 +163,41 @@ impl<'d> Deserialize<'d> for ImmutableString {
         Ok(s.into())
     }
 }
+
+struct FnPtrVisitor;
+
+impl<'d> Visitor<'d> for FnPtrVisitor {
+    type Value = FnPtr;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a function name or a function pointer with curried arguments")
+    }
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(FnPtr::new_unchecked(v, StaticVec::new_const()))
+    }
+    fn visit_map<M: MapAccess<'d>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+        let mut name: Option<String> = None;
+        let mut curry = StaticVec::new_const();
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" => name = Some(map.next_value()?),
+                "curry" => curry = map.next_value::<Vec<Dynamic>>()?.into(),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        let name = name.ok_or_else(|| M::Error::missing_field("name"))?;
+
+        Ok(FnPtr::new_unchecked(name, curry))
+    }
+}
+
+impl<'d> Deserialize<'d> for FnPtr {
+    // The function pointer is not checked - use `FnPtr::rehydrate` to do that
+    fn deserialize<D: Deserializer<'d>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_any(FnPtrVisitor)
+    }
+}
@@ -120,6 +120,42 @@ impl<'a> From<&'a FuncInfo> for FnMetadata<'a> {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CustomTypeMetadata<'a> {
+    pub type_name: &'a str,
+    pub name: Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "StaticVec::is_empty")]
+    pub doc_comments: StaticVec<&'a str>,
+}
+
+impl<'a> CustomTypeMetadata<'a> {
+    /// Create metadata for a custom type, with its display name qualified by a namespace (empty
+    /// for the global namespace).
+    fn new(
+        (type_name, display_name, comments): (&'a str, &'a str, &'a [Box<str>]),
+        namespace: &str,
+    ) -> Self {
+        Self {
+            type_name,
+            name: if namespace.is_empty() {
+                display_name.into()
+            } else {
+                format!("{namespace}::{display_name}").into()
+            },
+            doc_comments: comments.iter().map(<_>::as_ref).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConstMetadata<'a> {
+    pub name: &'a str,
+    #[serde(rename = "type")]
+    pub typ: Cow<'a, str>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ModuleMetadata<'a> {
@@ -129,34 +165,69 @@ struct ModuleMetadata<'a> {
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub modules: BTreeMap<&'a str, Self>,
     #[serde(skip_serializing_if = "StaticVec::is_empty")]
+    pub custom_types: StaticVec<CustomTypeMetadata<'a>>,
+    #[serde(skip_serializing_if = "StaticVec::is_empty")]
+    pub constants: StaticVec<ConstMetadata<'a>>,
+    #[serde(skip_serializing_if = "StaticVec::is_empty")]
     pub functions: StaticVec<FnMetadata<'a>>,
 }
 
-impl ModuleMetadata<'_> {
+impl<'a> ModuleMetadata<'a> {
     #[inline(always)]
     pub fn new() -> Self {
         Self {
             #[cfg(feature = "metadata")]
             doc: "",
             modules: BTreeMap::new(),
+            custom_types: StaticVec::new_const(),
+            constants: StaticVec::new_const(),
             functions: StaticVec::new_const(),
         }
     }
-}
 
-impl<'a> From<&'a crate::Module> for ModuleMetadata<'a> {
-    fn from(module: &'a crate::Module) -> Self {
+    /// Add the custom types and constants of a [`Module`][crate::Module] in a namespace (empty
+    /// for the global namespace).
+    fn add_types_and_constants(
+        &mut self,
+        engine: &'a Engine,
+        module: &'a crate::Module,
+        namespace: &str,
+    ) {
+        self.custom_types.extend(
+            module
+                .iter_custom_types()
+                .map(|t| CustomTypeMetadata::new(t, namespace)),
+        );
+        self.custom_types.sort_by(|a, b| a.name.cmp(&b.name));
+
+        self.constants
+            .extend(module.iter_var().map(|(name, value)| ConstMetadata {
+                name,
+                typ: engine.format_type_name(value.type_name()),
+            }));
+        self.constants.sort_by(|a, b| a.name.cmp(b.name));
+    }
+
+    /// Create metadata for a [`Module`][crate::Module] in a namespace.
+    fn from_module(engine: &'a Engine, module: &'a crate::Module, namespace: &str) -> Self {
         let mut functions: StaticVec<_> = module.iter_fn().map(Into::into).collect();
         functions.sort();
 
-        Self {
+        let mut meta = Self {
             doc: module.doc(),
             modules: module
                 .iter_sub_modules()
-                .map(|(name, m)| (name, m.as_ref().into()))
+                .map(|(name, m)| {
+                    let namespace = format!("{namespace}::{name}");
+                    (name, Self::from_module(engine, m, &namespace))
+                })
                 .collect(),
             functions,
-        }
+            ..Self::new()
+        };
+
+        meta.add_types_and_constants(engine, module, namespace);
+        meta
     }
 }
 
@@ -171,9 +242,17 @@ pub fn gen_metadata_to_json(
 
     #[cfg(not(feature = "no_module"))]
     for (name, m) in &engine.global_sub_modules {
-        global.modules.insert(name, m.as_ref().into());
+        global
+            .modules
+            .insert(name, ModuleMetadata::from_module(engine, m, name));
     }
 
+    engine
+        .global_modules
+        .iter()
+        .filter(|m| include_standard_packages || !m.standard)
+        .for_each(|m| global.add_types_and_constants(engine, m, ""));
+
     engine
         .global_modules
         .iter()
@@ -1,12 +1,21 @@
 //! _(serde)_ Serialization and deserialization support for [`serde`](https://crates.io/crates/serde).
 //! Exported under the `serde` feature only.
 
+mod blocks;
 mod de;
 mod deserialize;
 mod metadata;
+mod portable;
 mod ser;
 mod serialize;
 mod str;
 
+pub use blocks::{
+    BlockExpr, BlockFnDef, BlockMapEntry, BlockScript, BlockStmt, BlockSwitchCase,
+    BlockSwitchPattern,
+};
 pub use de::from_dynamic;
+#[cfg(not(feature = "no_function"))]
+pub use portable::PortableFnPtr;
+pub(crate) use portable::PortableValue;
 pub use ser::to_dynamic;
@@ -0,0 +1,272 @@
+//! Portable serialization of values, script-defined functions and closures.
+
+#[cfg(not(feature = "no_function"))]
+use crate::parser::anonymous_fn_origin;
+use crate::types::dynamic::Union;
+#[cfg(not(feature = "no_function"))]
+use crate::AST;
+use crate::{Dynamic, Engine, FnPtr, Identifier, Position, RhaiResultOf, StaticVec, ERR, INT};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{collections::BTreeMap, fmt};
+
+/// A value curried into a [`PortableFnPtr`] or saved in a [`Snapshot`][crate::Snapshot].
+///
+/// Unlike the [`serde`] implementations of [`Dynamic`], values of this type round-trip exactly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum PortableValue {
+    Unit,
+    Bool(bool),
+    Int(INT),
+    #[cfg(not(feature = "no_float"))]
+    Float(crate::FLOAT),
+    #[cfg(feature = "decimal")]
+    Decimal(String),
+    Char(char),
+    Str(String),
+    #[cfg(not(feature = "no_index"))]
+    Array(Vec<PortableValue>),
+    #[cfg(not(feature = "no_index"))]
+    Blob(Vec<u8>),
+    #[cfg(not(feature = "no_object"))]
+    Map(BTreeMap<String, PortableValue>),
+    FnPtr(String, Vec<PortableValue>),
+}
+
+/// Make the name of a function pointer portable.
+///
+/// Names of anonymous functions contain a hash that is different in every process, so they are
+/// only valid if the position of the definition is recorded.
+fn portable_fn_name(name: &str) -> RhaiResultOf<String> {
+    #[cfg(not(feature = "no_function"))]
+    if name.starts_with(crate::engine::FN_ANONYMOUS) && anonymous_fn_origin(name).is_none() {
+        return Err(ERR::ErrorFunctionNotFound(name.to_string(), Position::NONE).into());
+    }
+    Ok(name.to_string())
+}
+
+impl PortableValue {
+    /// Convert a [`Dynamic`] into a [`PortableValue`].
+    pub(crate) fn from_dynamic(engine: &Engine, value: &Dynamic) -> RhaiResultOf<Self> {
+        let value = value.flatten_clone();
+
+        Ok(match value.0 {
+            Union::Unit(..) => Self::Unit,
+            Union::Bool(b, ..) => Self::Bool(b),
+            Union::Int(n, ..) => Self::Int(n),
+            #[cfg(not(feature = "no_float"))]
+            Union::Float(ref f, ..) => Self::Float(**f),
+            #[cfg(feature = "decimal")]
+            Union::Decimal(ref d, ..) => Self::Decimal(d.to_string()),
+            Union::Char(c, ..) => Self::Char(c),
+            Union::Str(ref s, ..) => Self::Str(s.to_string()),
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(ref a, ..) => Self::Array(
+                a.iter()
+                    .map(|v| Self::from_dynamic(engine, v))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(ref b, ..) => Self::Blob(b.to_vec()),
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(ref m, ..) => Self::Map(
+                m.iter()
+                    .map(|(k, v)| Ok((k.to_string(), Self::from_dynamic(engine, v)?)))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            Union::FnPtr(ref f, ..) => Self::FnPtr(
+                portable_fn_name(f.fn_name())?,
+                f.curry()
+                    .iter()
+                    .map(|v| Self::from_dynamic(engine, v))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            _ => {
+                let typ = engine.map_type_name(value.type_name());
+                return Err(
+                    ERR::ErrorMismatchDataType(String::new(), typ.into(), Position::NONE).into(),
+                );
+            }
+        })
+    }
+    /// Convert a [`PortableValue`] back into a [`Dynamic`].
+    pub(crate) fn into_dynamic(
+        self,
+        names: &BTreeMap<Position, Identifier>,
+    ) -> RhaiResultOf<Dynamic> {
+        Ok(match self {
+            Self::Unit => Dynamic::UNIT,
+            Self::Bool(b) => Dynamic::from_bool(b),
+            Self::Int(n) => Dynamic::from_int(n),
+            #[cfg(not(feature = "no_float"))]
+            Self::Float(f) => Dynamic::from_float(f),
+            #[cfg(feature = "decimal")]
+            Self::Decimal(d) => Dynamic::from_decimal(
+                d.parse()
+                    .map_err(|_| ERR::ErrorMismatchDataType("decimal".into(), d, Position::NONE))?,
+            ),
+            Self::Char(c) => Dynamic::from_char(c),
+            Self::Str(s) => s.into(),
+            #[cfg(not(feature = "no_index"))]
+            Self::Array(a) => Dynamic::from_array(
+                a.into_iter()
+                    .map(|v| v.into_dynamic(names))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            #[cfg(not(feature = "no_index"))]
+            Self::Blob(b) => Dynamic::from_blob(b),
+            #[cfg(not(feature = "no_object"))]
+            Self::Map(m) => Dynamic::from_map(
+                m.into_iter()
+                    .map(|(k, v)| Ok((k.into(), v.into_dynamic(names)?)))
+                    .collect::<RhaiResultOf<_>>()?,
+            ),
+            Self::FnPtr(name, curry) => restore_fn_ptr(name, curry, names)?.into(),
+        })
+    }
+}
+
+/// Restore a function pointer, mapping anonymous functions to their definitions in the new
+/// [`AST`][crate::AST] via the positions of their definitions.
+fn restore_fn_ptr(
+    name: String,
+    curry: Vec<PortableValue>,
+    names: &BTreeMap<Position, Identifier>,
+) -> RhaiResultOf<FnPtr> {
+    #[cfg(not(feature = "no_function"))]
+    let name: Identifier = match anonymous_fn_origin(&name) {
+        Some(pos) => names
+            .get(&pos)
+            .cloned()
+            .ok_or_else(|| ERR::ErrorFunctionNotFound(name, Position::NONE))?,
+        _ => name.into(),
+    };
+    #[cfg(feature = "no_function")]
+    let name: Identifier = name.into();
+
+    let curry = curry
+        .into_iter()
+        .map(|v| v.into_dynamic(names))
+        .collect::<RhaiResultOf<StaticVec<_>>>()?;
+
+    Ok(FnPtr::new_unchecked(name, curry))
+}
+
+/// _(serde)_ A function pointer to a script-defined function or closure, packaged together with
+/// the script that defines it so that it can be persisted and called in another process.
+/// Exported under the `serde` feature only.
+///
+/// Captured variables and other curried arguments are kept as a snapshot of their values.
+/// Only the following types can be captured: `()`, `bool`, integers, floating-point numbers,
+/// decimals, characters, strings, arrays, BLOB's, object maps and function pointers.
+///
+/// The function is restored by compiling the script again via [`Engine::compile`], so the
+/// [`Engine`] must have the same configuration (e.g. options, custom syntax and registered
+/// functions) as the one that originally compiled the script.
+/// Statements at global level are never run.
+///
+/// Not available under `no_function`.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, FnPtr};
+/// use rhai::serde::PortableFnPtr;
+///
+/// let engine = Engine::new();
+///
+/// let script = "let x = 40; |y| x + y";
+///
+/// let ast = engine.compile(script)?;
+/// let closure = engine.eval_ast::<FnPtr>(&ast)?;
+///
+/// let json = serde_json::to_string(&PortableFnPtr::new(&engine, script, &closure)?).unwrap();
+///
+/// // ... in another process ...
+///
+/// let portable: PortableFnPtr = serde_json::from_str(&json).unwrap();
+/// let (ast, closure) = portable.restore(&engine)?;
+///
+/// assert_eq!(closure.call::<i64>(&engine, &ast, (2_i64,))?, 42);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(not(feature = "no_function"))]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortableFnPtr {
+    /// Text of the script defining the function.
+    script: String,
+    /// Name of the function.
+    name: String,
+    /// Curried arguments.
+    curry: Vec<PortableValue>,
+}
+
+#[cfg(not(feature = "no_function"))]
+impl fmt::Debug for PortableFnPtr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PortableFnPtr")
+            .field("name", &self.name)
+            .field("curry", &self.curry)
+            .finish()
+    }
+}
+
+#[cfg(not(feature = "no_function"))]
+impl PortableFnPtr {
+    /// Package a function pointer together with the text of the script that defines it.
+    ///
+    /// The script must be the same text that was compiled into the [`AST`] which created the
+    /// function pointer.
+    ///
+    /// An error is returned if any curried argument cannot be captured, or if the function
+    /// pointer refers to an anonymous function without a recorded position.
+    pub fn new(engine: &Engine, script: impl Into<String>, fn_ptr: &FnPtr) -> RhaiResultOf<Self> {
+        Ok(Self {
+            script: script.into(),
+            name: portable_fn_name(fn_ptr.fn_name())?,
+            curry: fn_ptr
+                .curry()
+                .iter()
+                .map(|v| PortableValue::from_dynamic(engine, v))
+                .collect::<RhaiResultOf<_>>()?,
+        })
+    }
+    /// Get the text of the script defining the function.
+    #[inline(always)]
+    #[must_use]
+    pub fn script(&self) -> &str {
+        &self.script
+    }
+    /// Get the name of the function.
+    #[inline(always)]
+    #[must_use]
+    pub fn fn_name(&self) -> &str {
+        &self.name
+    }
+    /// Compile the script and restore the function pointer.
+    ///
+    /// Returns the [`AST`] (with all statements at global level removed) together with the
+    /// restored function pointer, which can then be called via [`FnPtr::call`].
+    ///
+    /// An error is returned if the script fails to compile, or if the function is not found.
+    pub fn restore(&self, engine: &Engine) -> RhaiResultOf<(AST, FnPtr)> {
+        let mut ast = engine.compile(&self.script)?;
+        ast.clear_statements();
+
+        let names = ast
+            .shared_lib()
+            .iter_script_fn()
+            .filter_map(|(.., f)| {
+                anonymous_fn_origin(&f.name).map(|pos| (pos, f.name.as_str().into()))
+            })
+            .collect();
+
+        let fn_ptr = restore_fn_ptr(self.name.clone(), self.curry.clone(), &names)?
+            .rehydrate(engine, &ast)?;
+
+        Ok((ast, fn_ptr))
+    }
+}
@@ -1,8 +1,8 @@
 //! Implementations of [`serde::Serialize`].
 
 use crate::types::dynamic::Union;
-use crate::{Dynamic, ImmutableString};
-use serde::ser::{Serialize, Serializer};
+use crate::{Dynamic, FnPtr, ImmutableString};
+use serde::ser::{Serialize, SerializeStruct, SerializeTuple, Serializer};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
@@ -66,9 +66,15 @@ impl Serialize for Dynamic {
                     .try_for_each(|(k, v)| map.serialize_entry(k.as_str(), v))?;
                 map.end()
             }
-            Union::FnPtr(ref f, ..) => ser.serialize_str(f.fn_name()),
+            Union::FnPtr(ref f, ..) if !f.is_curried() => ser.serialize_str(f.fn_name()),
+            Union::FnPtr(ref f, ..) => (**f).serialize(ser),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(ref x, ..) => ser.serialize_str(x.as_ref().type_name()),
+            Union::Tuple(ref t, ..) => {
+                let mut seq = ser.serialize_tuple(t.len())?;
+                t.iter().try_for_each(|v| seq.serialize_element(v))?;
+                seq.end()
+            }
 
             Union::Variant(ref v, ..) => ser.serialize_str((***v).type_name()),
 
@@ -87,3 +93,12 @@ impl Serialize for ImmutableString {
         ser.serialize_str(self.as_str())
     }
 }
+
+impl Serialize for FnPtr {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        let mut s = ser.serialize_struct("FnPtr", 2)?;
+        s.serialize_field("name", self.fn_name())?;
+        s.serialize_field("curry", self.curry())?;
+        s.end()
+    }
+}
@@ -27,7 +27,7 @@ fn check_struct_sizes() {
 
     #[cfg(target_pointer_width = "64")]
     {
-        assert_eq!(size_of::<Scope>(), 536);
+        assert_eq!(size_of::<Scope>(), 544);
         assert_eq!(size_of::<FnPtr>(), 80);
         assert_eq!(size_of::<LexError>(), 56);
         assert_eq!(
@@ -45,3 +45,34 @@ fn check_struct_sizes() {
         );
     }
 }
+
+/// This test is to make sure that every feature flag in `Cargo.toml` is reported by
+/// [`Engine::build_info`][crate::Engine::build_info].
+#[test]
+fn check_feature_flags() {
+    let manifest = include_str!("../Cargo.toml");
+    let features = manifest
+        .split("\n[features]\n")
+        .nth(1)
+        .expect("[features] section")
+        .split("\n[")
+        .next()
+        .unwrap();
+
+    for line in features.lines() {
+        let name = match line.split_once('=') {
+            Some((name, ..)) if !line.starts_with('#') => name.trim(),
+            _ => continue,
+        };
+
+        if name != "default" {
+            assert!(
+                crate::api::build_info::ALL_FEATURES
+                    .iter()
+                    .any(|&(f, ..)| f == name),
+                "feature {} is not listed",
+                name
+            );
+        }
+    }
+}
@@ -5,7 +5,10 @@ use crate::engine::{
     KEYWORD_FN_PTR_CURRY, KEYWORD_IS_DEF_VAR, KEYWORD_PRINT, KEYWORD_THIS, KEYWORD_TYPE_OF,
 };
 use crate::func::native::OnParseTokenCallback;
-use crate::{Engine, Identifier, LexError, SmartString, StaticVec, INT, UNSIGNED_INT};
+use crate::{
+    Engine, Identifier, LexError, ParseError, ParseWarning, SmartString, StaticVec, INT,
+    UNSIGNED_INT,
+};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
@@ -28,6 +31,14 @@ pub struct TokenizerControlBlock {
     /// Collection of global comments.
     #[cfg(feature = "metadata")]
     pub global_comments: Vec<SmartString>,
+    /// Number of tokens produced so far.
+    num_tokens: usize,
+    /// Number of currently open braces, used to skip to the end of a statement after a parse error.
+    nesting: isize,
+    /// Parse errors collected during parsing, if enabled.
+    errors: Option<Vec<ParseError>>,
+    /// Parse warnings collected during parsing, if parse errors are collected.
+    warnings: Vec<(ParseWarning, Position)>,
 }
 
 impl TokenizerControlBlock {
@@ -39,8 +50,81 @@ impl TokenizerControlBlock {
             is_within_text: false,
             #[cfg(feature = "metadata")]
             global_comments: Vec::new(),
+            num_tokens: 0,
+            nesting: 0,
+            errors: None,
+            warnings: Vec::new(),
+        }
+    }
+    /// Count a token just produced.
+    #[inline(always)]
+    pub(crate) fn count_token(&mut self) {
+        self.num_tokens += 1;
+    }
+    /// Get the number of tokens produced so far, including any end-of-script token.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) const fn num_tokens(&self) -> usize {
+        self.num_tokens
+    }
+    /// Track the nesting of braces with a token just produced.
+    #[inline]
+    pub(crate) fn track_nesting(&mut self, token: &Token) {
+        match token {
+            Token::LeftBrace | Token::MapStart => self.nesting += 1,
+            Token::RightBrace => self.nesting -= 1,
+            _ => (),
+        }
+    }
+    /// Get the number of currently open braces.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) const fn nesting(&self) -> isize {
+        self.nesting
+    }
+    /// Reset the number of currently open braces.
+    #[inline(always)]
+    pub(crate) fn reset_nesting(&mut self) {
+        self.nesting = 0;
+    }
+    /// Start collecting parse errors and warnings instead of stopping at the first parse error.
+    #[inline(always)]
+    pub(crate) fn collect_errors(&mut self) {
+        self.errors = Some(Vec::new());
+    }
+    /// Are parse errors being collected?
+    #[inline(always)]
+    #[must_use]
+    pub(crate) const fn is_collecting_errors(&self) -> bool {
+        self.errors.is_some()
+    }
+    /// Add a parse error, if parse errors are being collected.
+    ///
+    /// An error that propagates through multiple levels of blocks is only added once.
+    #[inline]
+    pub(crate) fn add_error(&mut self, err: ParseError) {
+        if let Some(ref mut errors) = self.errors {
+            if errors.last() != Some(&err) {
+                errors.push(err);
+            }
         }
     }
+    /// Add a parse warning, if parse errors are being collected.
+    #[inline]
+    pub(crate) fn add_warning(&mut self, warning: ParseWarning, pos: Position) {
+        if self.errors.is_some() {
+            self.warnings.push((warning, pos));
+        }
+    }
+    /// Take all the parse errors and warnings collected so far.
+    #[inline]
+    #[must_use]
+    pub(crate) fn take_errors(&mut self) -> (Vec<ParseError>, Vec<(ParseWarning, Position)>) {
+        (
+            self.errors.take().unwrap_or_default(),
+            std::mem::take(&mut self.warnings),
+        )
+    }
 }
 
 /// _(internals)_ A shared object that allows control of the tokenizer from outside.
@@ -51,8 +135,94 @@ type LERR = LexError;
 /// Separator character for numbers.
 const NUMBER_SEPARATOR: char = '_';
 
-/// A stream of tokens.
-pub type TokenStream<'a> = Peekable<TokenIterator<'a>>;
+/// A stream of tokens that can be peeked one token ahead.
+///
+/// Each token is kept together with its [span][Span], so the span of the token causing a parse
+/// error can be attached to the error.
+pub struct TokenStream<'a> {
+    /// Underlying token iterator.
+    iter: TokenIterator<'a>,
+    /// Token peeked but not yet consumed (if any), with its ending [position][Position].
+    peeked: Option<Option<((Token, Position), Position)>>,
+    /// [Span] of the token consumed last.
+    last_span: Span,
+    /// [Position] reported for all tokens, if not [`NONE`][Position::NONE].
+    fixed_pos: Position,
+}
+
+impl<'a> TokenStream<'a> {
+    /// Create a new [`TokenStream`] from a [`TokenIterator`].
+    #[inline(always)]
+    #[must_use]
+    pub const fn new(iter: TokenIterator<'a>) -> Self {
+        Self::new_at(iter, Position::NONE)
+    }
+    /// Create a new [`TokenStream`] from a [`TokenIterator`], reporting all tokens at a fixed
+    /// [position][Position] (unless it is [`NONE`][Position::NONE]).
+    ///
+    /// This is used for text that does not appear in the script itself (e.g. the template of a
+    /// custom syntax macro), so that errors point to where the text is used.
+    #[inline(always)]
+    #[must_use]
+    pub const fn new_at(iter: TokenIterator<'a>, pos: Position) -> Self {
+        Self {
+            iter,
+            peeked: None,
+            last_span: Span::NONE,
+            fixed_pos: pos,
+        }
+    }
+    /// Get the next token with its ending [position][Position] from the underlying iterator.
+    #[inline]
+    fn fetch(&mut self) -> Option<((Token, Position), Position)> {
+        let (token, pos) = self.iter.next()?;
+
+        if self.fixed_pos.is_none() {
+            Some(((token, pos), self.iter.pos))
+        } else {
+            Some(((token, self.fixed_pos), self.fixed_pos))
+        }
+    }
+    /// Peek at the next token without consuming it.
+    #[inline]
+    pub fn peek(&mut self) -> Option<&(Token, Position)> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.fetch());
+        }
+        self.peeked
+            .as_ref()
+            .and_then(|item| item.as_ref().map(|(item, ..)| item))
+    }
+    /// Get the [span][Span] of the token consumed last or the token peeked, if it starts at a
+    /// particular [position][Position].
+    #[must_use]
+    pub(crate) fn span_at(&self, start: Position) -> Option<Span> {
+        if start.is_none() {
+            return None;
+        }
+        if self.last_span.start() == start {
+            return Some(self.last_span);
+        }
+        match self.peeked {
+            Some(Some(((.., pos), end))) if pos == start => Some(Span::new(pos, end)),
+            _ => None,
+        }
+    }
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = (Token, Position);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (item, end) = match self.peeked.take() {
+            Some(item) => item?,
+            None => self.fetch()?,
+        };
+        self.last_span = Span::new(item.1, end);
+        Some(item)
+    }
+}
 
 /// A location (line number + character position) in the input script.
 ///
@@ -62,6 +232,9 @@ pub type TokenStream<'a> = Peekable<TokenIterator<'a>>;
 /// meaning they go up to a maximum of 65,535 lines and 65,535 characters per line.
 ///
 /// Advancing beyond the maximum line length or maximum number of lines is not an error but has no effect.
+///
+/// A [`Position`] is always a single point. Ranges of input are represented by a separate
+/// [`Span`], which only parse errors carry (see [`ParseError::span`][crate::ParseError::span]).
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
 pub struct Position {
     /// Line number: 0 = none
@@ -286,8 +459,10 @@ impl AddAssign for Position {
     }
 }
 
-/// _(internals)_ A span consisting of a starting and an ending [positions][Position].
-/// Exported under the `internals` feature only.
+/// A span consisting of a starting and an ending [positions][Position].
+///
+/// The ending [position][Position] is the last character in the span, or
+/// [`Position::NONE`] if the span is not known.
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy)]
 pub struct Span {
     /// Starting [position][Position].
@@ -330,6 +505,12 @@ impl Span {
     pub const fn end(&self) -> Position {
         self.end
     }
+    /// Does this [`Span`] cover more than a single character?
+    #[inline]
+    #[must_use]
+    pub fn is_multi_char(&self) -> bool {
+        !self.start.is_none() && !self.end.is_none() && self.end > self.start
+    }
 }
 
 impl fmt::Display for Span {
@@ -525,6 +706,11 @@ pub enum Token {
     Try,
     /// `catch`
     Catch,
+    /// `yield`
+    ///
+    /// Reserved under the `no_function` feature.
+    #[cfg(not(feature = "no_function"))]
+    Yield,
     /// `+=`
     PlusAssign,
     /// `-=`
@@ -676,6 +862,8 @@ impl Token {
             Fn => "fn",
             #[cfg(not(feature = "no_function"))]
             Private => "private",
+            #[cfg(not(feature = "no_function"))]
+            Yield => "yield",
 
             #[cfg(not(feature = "no_module"))]
             Import => "import",
@@ -882,9 +1070,11 @@ impl Token {
             "fn" => Fn,
             #[cfg(not(feature = "no_function"))]
             "private" => Private,
+            #[cfg(not(feature = "no_function"))]
+            "yield" => Yield,
 
             #[cfg(feature = "no_function")]
-            "fn" | "private" => Reserved(syntax.into()),
+            "fn" | "private" | "yield" => Reserved(syntax.into()),
 
             #[cfg(not(feature = "no_module"))]
             "import" => Import,
@@ -903,8 +1093,8 @@ impl Token {
             // List of reserved keywords
             "public" | "protected" | "super" | "new" | "use" | "module" | "package" | "var"
             | "static" | "shared" | "with" | "is" | "goto" | "exit" | "match" | "case"
-            | "default" | "void" | "null" | "nil" | "spawn" | "thread" | "go" | "sync"
-            | "async" | "await" | "yield" => Reserved(syntax.into()),
+            | "default" | "void" | "null" | "nil" | "thread" | "go" | "sync" | "async"
+            | "await" | "enum" => Reserved(syntax.into()),
 
             KEYWORD_PRINT | KEYWORD_DEBUG | KEYWORD_TYPE_OF | KEYWORD_EVAL | KEYWORD_FN_PTR
             | KEYWORD_FN_PTR_CALL | KEYWORD_FN_PTR_CURRY | KEYWORD_THIS | KEYWORD_IS_DEF_VAR => {
@@ -913,6 +1103,9 @@ impl Token {
 
             #[cfg(not(feature = "no_function"))]
             crate::engine::KEYWORD_IS_DEF_FN => Reserved(syntax.into()),
+            #[cfg(not(feature = "no_module"))]
+            #[cfg(not(feature = "no_index"))]
+            crate::engine::KEYWORD_CALL_QUALIFIED => Reserved(syntax.into()),
 
             _ => return None,
         })
@@ -990,6 +1183,9 @@ impl Token {
             Return           |
             Throw                           => true,
 
+            #[cfg(not(feature = "no_function"))]
+            Yield                           => true,
+
             _ => false,
         }
     }
@@ -1072,7 +1268,7 @@ impl Token {
 
         match self {
             #[cfg(not(feature = "no_function"))]
-            Fn | Private => true,
+            Fn | Private | Yield => true,
 
             #[cfg(not(feature = "no_module"))]
             Import | Export | As => true,
@@ -2220,6 +2416,9 @@ pub fn is_keyword_function(name: &str) -> bool {
 
         #[cfg(not(feature = "no_function"))]
         crate::engine::KEYWORD_IS_DEF_FN => true,
+        #[cfg(not(feature = "no_module"))]
+        #[cfg(not(feature = "no_index"))]
+        crate::engine::KEYWORD_CALL_QUALIFIED => true,
 
         _ => false,
     }
@@ -2284,6 +2483,122 @@ pub const fn is_id_continue(x: char) -> bool {
     x.is_ascii_alphanumeric() || x == '_'
 }
 
+/// _(internals)_ A source of characters for a [`MultiInputsStream`].
+/// Exported under the `internals` feature only.
+pub enum InputChars<'a> {
+    /// Characters in a text string.
+    Text(Chars<'a>),
+    /// UTF-8 characters decoded from a [`Read`][std::io::Read] source.
+    ///
+    /// Not available under `no_std`.
+    #[cfg(not(feature = "no_std"))]
+    Reader(ReadChars<'a>),
+    /// An arbitrary stream of characters.
+    Stream(Box<dyn Iterator<Item = char> + 'a>),
+}
+
+impl Iterator for InputChars<'_> {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Text(chars) => chars.next(),
+            #[cfg(not(feature = "no_std"))]
+            Self::Reader(chars) => chars.next(),
+            Self::Stream(chars) => chars.next(),
+        }
+    }
+}
+
+/// _(internals)_ An iterator decoding UTF-8 characters from a [`Read`][std::io::Read] source.
+/// Exported under the `internals` feature only.
+///
+/// Not available under `no_std`.
+///
+/// Iteration stops at the first error, which is kept in the error slot.
+#[cfg(not(feature = "no_std"))]
+pub struct ReadChars<'a> {
+    /// Bytes from the source.
+    bytes: std::io::Bytes<std::io::BufReader<Box<dyn std::io::Read + 'a>>>,
+    /// Error encountered, if any.
+    error: &'a mut Option<std::io::Error>,
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<'a> ReadChars<'a> {
+    /// Create a new [`ReadChars`] reading from a [`Read`][std::io::Read] source.
+    ///
+    /// Any error encountered is stored into `error`.
+    #[inline]
+    #[must_use]
+    pub fn new(reader: impl std::io::Read + 'a, error: &'a mut Option<std::io::Error>) -> Self {
+        use std::io::{BufReader, Read};
+
+        Self {
+            bytes: BufReader::new(Box::new(reader) as Box<dyn Read + 'a>).bytes(),
+            error,
+        }
+    }
+    /// Get the next byte, keeping any error.
+    ///
+    /// If `required` is `true`, running out of bytes is also an error.
+    fn next_byte(&mut self, required: bool) -> Option<u8> {
+        use std::io::{Error, ErrorKind};
+
+        match self.bytes.next() {
+            Some(Ok(byte)) => Some(byte),
+            Some(Err(err)) => {
+                *self.error = Some(err);
+                None
+            }
+            None if required => {
+                *self.error = Some(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "incomplete UTF-8 character",
+                ));
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl Iterator for ReadChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use std::io::{Error, ErrorKind};
+
+        let first = self.next_byte(false)?;
+
+        let len = match first {
+            0x00..=0x7f => return Some(first as char),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+
+        let mut buf = [first, 0, 0, 0];
+
+        if len > 0 {
+            for byte in &mut buf[1..len] {
+                *byte = self.next_byte(true)?;
+            }
+        }
+
+        match std::str::from_utf8(&buf[..len.max(1)]) {
+            Ok(s) => s.chars().next(),
+            Err(err) => {
+                *self.error = Some(Error::new(ErrorKind::InvalidData, err));
+                None
+            }
+        }
+    }
+}
+
 /// _(internals)_ A type that implements the [`InputStream`] trait.
 /// Exported under the `internals` feature only.
 ///
@@ -2294,7 +2609,24 @@ pub struct MultiInputsStream<'a> {
     /// The current stream index.
     pub index: usize,
     /// The input character streams.
-    pub streams: StaticVec<Peekable<Chars<'a>>>,
+    pub streams: StaticVec<Peekable<InputChars<'a>>>,
+    /// Number of bytes read from the input character streams so far.
+    bytes_read: usize,
+    /// Maximum number of bytes to read (0 = unlimited).
+    #[cfg(not(feature = "unchecked"))]
+    max_size: usize,
+}
+
+impl MultiInputsStream<'_> {
+    /// Has reading stopped because the input exceeds the maximum size?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_too_large(&self) -> bool {
+        #[cfg(not(feature = "unchecked"))]
+        return self.max_size > 0 && self.bytes_read > self.max_size;
+        #[cfg(feature = "unchecked")]
+        return false;
+    }
 }
 
 impl InputStream for MultiInputsStream<'_> {
@@ -2312,12 +2644,17 @@ impl InputStream for MultiInputsStream<'_> {
         }
 
         loop {
-            if self.index >= self.streams.len() {
-                // No more streams
+            if self.index >= self.streams.len() || self.is_too_large() {
+                // No more streams, or stop reading if the input is too large
                 return None;
             }
             if let Some(ch) = self.streams[self.index].next() {
                 // Next character in current stream
+                self.bytes_read += ch.len_utf8();
+
+                if self.is_too_large() {
+                    return None;
+                }
                 return Some(ch);
             }
             // Jump to the next stream
@@ -2330,8 +2667,8 @@ impl InputStream for MultiInputsStream<'_> {
         }
 
         loop {
-            if self.index >= self.streams.len() {
-                // No more streams
+            if self.index >= self.streams.len() || self.is_too_large() {
+                // No more streams, or stop reading if the input is too large
                 return None;
             }
             if let Some(&ch) = self.streams[self.index].peek() {
@@ -2458,12 +2795,33 @@ impl<'a> Iterator for TokenIterator<'a> {
             Some(r) => r,
         };
 
+        // Check the size limits of the script
+        #[cfg(not(feature = "unchecked"))]
+        let token = match token {
+            _ if self.stream.is_too_large() => {
+                Token::LexError(LERR::ScriptTooLarge(self.engine.max_script_size()).into())
+            }
+            Token::EOF => token,
+            _ if self.engine.max_tokens() > 0
+                && self.state.tokenizer_control.borrow().num_tokens >= self.engine.max_tokens() =>
+            {
+                Token::LexError(LERR::TooManyTokens(self.engine.max_tokens()).into())
+            }
+            _ => token,
+        };
+
         // Run the mapper, if any
         let token = match self.token_mapper {
             Some(map_func) => map_func(token, pos, &self.state),
             None => token,
         };
 
+        // Count the token and track braces
+        let mut control = self.state.tokenizer_control.borrow_mut();
+        control.count_token();
+        control.track_nesting(&token);
+        drop(control);
+
         Some((token, pos))
     }
 }
@@ -2501,6 +2859,20 @@ impl Engine {
         &'a self,
         input: impl IntoIterator<Item = &'a (impl AsRef<str> + 'a)>,
         token_mapper: Option<&'a OnParseTokenCallback>,
+    ) -> (TokenIterator<'a>, TokenizerControl) {
+        self.lex_streams(
+            input
+                .into_iter()
+                .map(|s| InputChars::Text(s.as_ref().chars())),
+            token_mapper,
+        )
+    }
+    /// Tokenize a list of character streams with an optional mapping function.
+    #[must_use]
+    pub(crate) fn lex_streams<'a>(
+        &'a self,
+        streams: impl IntoIterator<Item = InputChars<'a>>,
+        token_mapper: Option<&'a OnParseTokenCallback>,
     ) -> (TokenIterator<'a>, TokenizerControl) {
         let buffer: TokenizerControl = RefCell::new(TokenizerControlBlock::new()).into();
         let buffer2 = buffer.clone();
@@ -2522,11 +2894,11 @@ impl Engine {
                 pos: Position::new(1, 0),
                 stream: MultiInputsStream {
                     buf: None,
-                    streams: input
-                        .into_iter()
-                        .map(|s| s.as_ref().chars().peekable())
-                        .collect(),
+                    streams: streams.into_iter().map(Iterator::peekable).collect(),
                     index: 0,
+                    bytes_read: 0,
+                    #[cfg(not(feature = "unchecked"))]
+                    max_size: self.max_script_size(),
                 },
                 token_mapper,
             },
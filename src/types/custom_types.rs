@@ -9,6 +9,9 @@ use std::{any::type_name, collections::BTreeMap, fmt};
 pub struct CustomTypeInfo {
     /// Friendly display name of the custom type.
     pub display_name: Identifier,
+    /// Comments.
+    #[cfg(feature = "metadata")]
+    pub comments: Box<[Box<str>]>,
 }
 
 /// _(internals)_ A collection of custom types.
@@ -41,6 +44,8 @@ impl CustomTypesCollection {
             type_name,
             CustomTypeInfo {
                 display_name: name.into(),
+                #[cfg(feature = "metadata")]
+                comments: Box::default(),
             },
         );
     }
@@ -51,6 +56,8 @@ impl CustomTypesCollection {
             type_name::<T>(),
             CustomTypeInfo {
                 display_name: name.into(),
+                #[cfg(feature = "metadata")]
+                comments: Box::default(),
             },
         );
     }
@@ -64,4 +71,18 @@ impl CustomTypesCollection {
     pub fn get(&self, key: &str) -> Option<&CustomTypeInfo> {
         self.0.get(key)
     }
+    /// Find the type name of a custom type by its display name.
+    #[inline]
+    pub fn find_by_display_name(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(.., t)| t.display_name == name)
+            .map(|(type_name, ..)| type_name.as_str())
+    }
+    /// Iterate all the custom types, as (type name, [`CustomTypeInfo`]) pairs.
+    #[cfg(feature = "metadata")]
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &CustomTypeInfo)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v))
+    }
 }
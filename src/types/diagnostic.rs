@@ -0,0 +1,439 @@
+//! Module containing structured diagnostics for errors and warnings.
+
+use crate::{EvalAltResult, LexError, ParseError, ParseErrorType, ParseWarning, Position, Span};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Severity of a [`Diagnostic`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[non_exhaustive]
+pub enum Severity {
+    /// An error that prevents the script from being compiled or run.
+    Error,
+    /// A potential mistake that does not stop compilation.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        })
+    }
+}
+
+/// A secondary label of a [`Diagnostic`], pointing at a related location in the script.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DiagnosticLabel {
+    /// [Span] of the location.
+    span: Span,
+    /// Message describing the location.
+    message: String,
+}
+
+impl DiagnosticLabel {
+    /// Get the [span][Span] of the location.
+    #[inline(always)]
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        self.span
+    }
+    /// Get the message describing the location.
+    #[inline(always)]
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// A structured error or warning, suitable for display by IDE integrations.
+///
+/// A [`Diagnostic`] can be created from a [`ParseError`] or an [`EvalAltResult`], and is returned
+/// by [`Engine::compile_with_diagnostics`][crate::Engine::compile_with_diagnostics].
+///
+/// Each [`Diagnostic`] has a stable code: `L` for errors in tokenizing the script text, `P` for
+/// parse errors, `E` for evaluation errors and `W` for warnings.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Diagnostic, Engine, Severity};
+///
+/// let engine = Engine::new();
+///
+/// let err = engine.compile("let x = 40 let y = 2").unwrap_err();
+/// let diagnostic = Diagnostic::from(&err);
+///
+/// assert_eq!(diagnostic.severity(), Severity::Error);
+/// assert_eq!(diagnostic.code(), "P0003");
+/// assert_eq!(diagnostic.message(), "Expecting ';' to terminate this statement");
+/// assert_eq!(diagnostic.help(), Some("insert ';' here"));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Diagnostic {
+    /// Stable code identifying the kind of diagnostic.
+    code: &'static str,
+    /// Severity of the diagnostic.
+    severity: Severity,
+    /// Message describing the problem.
+    message: String,
+    /// [Span] of the problem.
+    span: Span,
+    /// Related locations.
+    labels: Vec<DiagnosticLabel>,
+    /// Suggestion on how to fix the problem.
+    help: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+
+        if !self.span.start().is_none() {
+            write!(f, " ({})", self.span.start())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    /// Create a new [`Diagnostic`].
+    #[inline(always)]
+    #[must_use]
+    fn new(code: &'static str, severity: Severity, message: String, span: Span) -> Self {
+        Self {
+            code,
+            severity,
+            message,
+            span,
+            labels: Vec::new(),
+            help: None,
+        }
+    }
+    /// Create a [`Diagnostic`] from a [`ParseWarning`].
+    #[must_use]
+    pub fn from_warning(warning: &ParseWarning, pos: Position) -> Self {
+        let mut diagnostic = Self::new(
+            warning_code(warning),
+            Severity::Warning,
+            warning.to_string(),
+            Span::new(pos, Position::NONE),
+        );
+
+        diagnostic.help = match warning {
+            ParseWarning::FnValueDiscarded(..) => {
+                Some("add an expression after this statement to return its value".into())
+            }
+            ParseWarning::UnreachableSwitchCase => {
+                Some("remove this case or change its condition".into())
+            }
+        };
+
+        diagnostic
+    }
+    /// Get the stable code identifying the kind of diagnostic.
+    #[inline(always)]
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        self.code
+    }
+    /// Get the [severity][Severity] of the diagnostic.
+    #[inline(always)]
+    #[must_use]
+    pub const fn severity(&self) -> Severity {
+        self.severity
+    }
+    /// Is this diagnostic an error?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+    /// Get the message describing the problem.
+    #[inline(always)]
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+    /// Get the [span][Span] of the problem.
+    ///
+    /// The ending [position][Position] of the [`Span`] is [`Position::NONE`] if only the
+    /// starting [position][Position] is known.
+    #[inline(always)]
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        self.span
+    }
+    /// Get the secondary labels pointing at related locations, outermost first.
+    #[inline(always)]
+    #[must_use]
+    pub fn labels(&self) -> &[DiagnosticLabel] {
+        &self.labels
+    }
+    /// Get the suggestion on how to fix the problem, if any.
+    #[inline(always)]
+    #[must_use]
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(err: &ParseError) -> Self {
+        let err_type = err.err_type();
+
+        let mut diagnostic = Self::new(
+            parse_error_code(err_type),
+            Severity::Error,
+            err_type.to_string(),
+            err.span(),
+        );
+        diagnostic.help = parse_error_help(err_type);
+        diagnostic
+    }
+}
+
+impl From<ParseError> for Diagnostic {
+    #[inline(always)]
+    fn from(err: ParseError) -> Self {
+        Self::from(&err)
+    }
+}
+
+impl From<&EvalAltResult> for Diagnostic {
+    fn from(err: &EvalAltResult) -> Self {
+        let mut labels = Vec::new();
+        let mut err = err;
+
+        // Unwrap nested errors, turning each call site into a label
+        loop {
+            err = match err {
+                EvalAltResult::ErrorInFunctionCall(name, .., inner, pos) => {
+                    #[cfg(not(feature = "no_function"))]
+                    let message = match crate::parser::anonymous_fn_display_name(name) {
+                        Some(name) => format!("in call to {name}"),
+                        None => format!("in call to function {name}"),
+                    };
+                    #[cfg(feature = "no_function")]
+                    let message = format!("in call to function {name}");
+
+                    labels.push(DiagnosticLabel {
+                        span: Span::new(*pos, Position::NONE),
+                        message,
+                    });
+                    &**inner
+                }
+                EvalAltResult::ErrorInModule(name, inner, pos) => {
+                    labels.push(DiagnosticLabel {
+                        span: Span::new(*pos, Position::NONE),
+                        message: if name.is_empty() {
+                            "in module".into()
+                        } else {
+                            format!("in module '{name}'")
+                        },
+                    });
+                    &**inner
+                }
+                _ => break,
+            };
+        }
+
+        let mut diagnostic = match err {
+            EvalAltResult::ErrorParsing(err_type, pos) => {
+                let mut diagnostic = Self::new(
+                    parse_error_code(err_type),
+                    Severity::Error,
+                    err_type.to_string(),
+                    Span::new(*pos, Position::NONE),
+                );
+                diagnostic.help = parse_error_help(err_type);
+                diagnostic
+            }
+            _ => {
+                // Render the message without the position
+                let pos = err.position();
+                let mut message = err.to_string();
+
+                if !pos.is_none() {
+                    message.truncate(message.len() - format!(" ({pos})").len());
+                }
+
+                let mut diagnostic = Self::new(
+                    eval_error_code(err),
+                    Severity::Error,
+                    message,
+                    Span::new(pos, Position::NONE),
+                );
+                diagnostic.help = eval_error_help(err);
+                diagnostic
+            }
+        };
+
+        diagnostic.labels = labels;
+        diagnostic
+    }
+}
+
+impl From<EvalAltResult> for Diagnostic {
+    #[inline(always)]
+    fn from(err: EvalAltResult) -> Self {
+        Self::from(&err)
+    }
+}
+
+/// Get the code of a [`LexError`].
+#[must_use]
+const fn lex_error_code(err: &LexError) -> &'static str {
+    match err {
+        LexError::UnexpectedInput(..) => "L0001",
+        LexError::UnterminatedString => "L0002",
+        LexError::StringTooLong(..) => "L0003",
+        LexError::MalformedEscapeSequence(..) => "L0004",
+        LexError::MalformedNumber(..) => "L0005",
+        LexError::MalformedChar(..) => "L0006",
+        LexError::MalformedIdentifier(..) => "L0007",
+        LexError::ImproperSymbol(..) => "L0008",
+        LexError::ScriptTooLarge(..) => "L0009",
+        LexError::TooManyTokens(..) => "L0010",
+    }
+}
+
+/// Get the code of a [`ParseErrorType`].
+#[must_use]
+const fn parse_error_code(err: &ParseErrorType) -> &'static str {
+    match err {
+        ParseErrorType::UnexpectedEOF => "P0001",
+        ParseErrorType::BadInput(err) => lex_error_code(err),
+        ParseErrorType::UnknownOperator(..) => "P0002",
+        ParseErrorType::MissingToken(..) => "P0003",
+        ParseErrorType::MissingSymbol(..) => "P0004",
+        ParseErrorType::MalformedCallExpr(..) => "P0005",
+        ParseErrorType::MalformedIndexExpr(..) => "P0006",
+        ParseErrorType::MalformedInExpr(..) => "P0007",
+        ParseErrorType::MalformedCapture(..) => "P0008",
+        ParseErrorType::DuplicatedProperty(..) => "P0009",
+        #[allow(deprecated)]
+        ParseErrorType::DuplicatedSwitchCase => "P0010",
+        ParseErrorType::DuplicatedVariable(..) => "P0011",
+        ParseErrorType::WrongSwitchIntegerCase => "P0012",
+        ParseErrorType::WrongSwitchDefaultCase => "P0013",
+        ParseErrorType::WrongSwitchCaseCondition => "P0014",
+        ParseErrorType::PropertyExpected => "P0015",
+        ParseErrorType::VariableExpected => "P0016",
+        ParseErrorType::ForbiddenVariable(..) => "P0017",
+        ParseErrorType::Reserved(..) => "P0018",
+        ParseErrorType::MismatchedType(..) => "P0019",
+        ParseErrorType::ExprExpected(..) => "P0020",
+        ParseErrorType::WrongDocComment => "P0021",
+        ParseErrorType::WrongFnDefinition => "P0022",
+        ParseErrorType::FnDuplicatedDefinition(..) => "P0023",
+        ParseErrorType::FnMissingName => "P0024",
+        ParseErrorType::FnMissingParams(..) => "P0025",
+        ParseErrorType::FnDuplicatedParam(..) => "P0026",
+        ParseErrorType::FnMissingBody(..) => "P0027",
+        ParseErrorType::FnImplicitReturn(..) => "P0028",
+        ParseErrorType::WrongExport => "P0029",
+        ParseErrorType::WrongEnumDefinition => "P0030",
+        ParseErrorType::AssignmentToConstant(..) => "P0031",
+        ParseErrorType::AssignmentToInvalidLHS(..) => "P0032",
+        ParseErrorType::VariableExists(..) => "P0033",
+        ParseErrorType::VariableUndefined(..) => "P0034",
+        ParseErrorType::ModuleUndefined(..) => "P0035",
+        ParseErrorType::ExprTooDeep => "P0036",
+        ParseErrorType::LiteralTooLarge(..) => "P0037",
+        ParseErrorType::LoopBreak => "P0038",
+        ParseErrorType::ScriptTooLarge(..) => "P0039",
+        ParseErrorType::TooManyTokens(..) => "P0040",
+        ParseErrorType::TooManyFunctions(..) => "P0041",
+        ParseErrorType::TooManyParameters(..) => "P0042",
+        ParseErrorType::WrongYield => "P0043",
+        ParseErrorType::UnsupportedInBlocks(..) => "P0044",
+    }
+}
+
+/// Get a suggestion on how to fix a [`ParseErrorType`], if any.
+#[must_use]
+fn parse_error_help(err: &ParseErrorType) -> Option<String> {
+    Some(match err {
+        ParseErrorType::MissingToken(token, ..) => format!("insert '{token}' here"),
+        ParseErrorType::VariableUndefined(..) => {
+            "declare the variable with 'let' or 'const' before using it".into()
+        }
+        ParseErrorType::FnImplicitReturn(..) => {
+            "use a 'return' statement to return the value".into()
+        }
+        ParseErrorType::WrongFnDefinition => "move the function definition to global level".into(),
+        ParseErrorType::AssignmentToConstant(..) => {
+            "declare the variable with 'let' instead of 'const'".into()
+        }
+        _ => return None,
+    })
+}
+
+/// Get the code of a [`ParseWarning`].
+#[must_use]
+const fn warning_code(warning: &ParseWarning) -> &'static str {
+    match warning {
+        ParseWarning::FnValueDiscarded(..) => "W0001",
+        ParseWarning::UnreachableSwitchCase => "W0002",
+    }
+}
+
+/// Get the code of an [`EvalAltResult`].
+#[must_use]
+fn eval_error_code(err: &EvalAltResult) -> &'static str {
+    match err {
+        EvalAltResult::ErrorSystem(..) => "E0001",
+        EvalAltResult::ErrorParsing(err, ..) => parse_error_code(err),
+        EvalAltResult::ErrorVariableExists(..) => "E0002",
+        EvalAltResult::ErrorForbiddenVariable(..) => "E0003",
+        EvalAltResult::ErrorVariableNotFound(..) => "E0004",
+        EvalAltResult::ErrorPropertyNotFound(..) => "E0005",
+        EvalAltResult::ErrorIndexNotFound(..) => "E0006",
+        EvalAltResult::ErrorFunctionNotFound(..) => "E0007",
+        EvalAltResult::ErrorModuleNotFound(..) => "E0008",
+        EvalAltResult::ErrorInFunctionCall(.., err, _) => eval_error_code(err),
+        EvalAltResult::ErrorInModule(.., err, _) => eval_error_code(err),
+        EvalAltResult::ErrorUnboundThis(..) => "E0009",
+        EvalAltResult::ErrorMismatchDataType(..) => "E0010",
+        EvalAltResult::ErrorMismatchOutputType(..) => "E0011",
+        EvalAltResult::ErrorIndexingType(..) => "E0012",
+        EvalAltResult::ErrorArrayBounds(..) => "E0013",
+        EvalAltResult::ErrorStringBounds(..) => "E0014",
+        EvalAltResult::ErrorBitFieldBounds(..) => "E0015",
+        EvalAltResult::ErrorFor(..) => "E0016",
+        EvalAltResult::ErrorDataRace(..) => "E0017",
+        EvalAltResult::ErrorAssignmentToConstant(..) => "E0018",
+        EvalAltResult::ErrorDotExpr(..) => "E0019",
+        EvalAltResult::ErrorArithmetic(..) => "E0020",
+        EvalAltResult::ErrorTooManyOperations(..) => "E0021",
+        EvalAltResult::ErrorTooManyModules(..) => "E0022",
+        EvalAltResult::ErrorStackOverflow(..) => "E0023",
+        EvalAltResult::ErrorDataTooLarge(..) => "E0024",
+        EvalAltResult::ErrorTerminated(..) => "E0025",
+        EvalAltResult::ErrorCustomSyntax(..) => "E0026",
+        EvalAltResult::ErrorRuntime(..) => "E0027",
+        EvalAltResult::LoopBreak(..) => "E0028",
+        EvalAltResult::Return(..) => "E0029",
+    }
+}
+
+/// Get a suggestion on how to fix an [`EvalAltResult`], if any.
+#[must_use]
+fn eval_error_help(err: &EvalAltResult) -> Option<String> {
+    Some(match err {
+        EvalAltResult::ErrorVariableNotFound(..) => {
+            "declare the variable with 'let' or 'const' before using it".into()
+        }
+        EvalAltResult::ErrorFunctionNotFound(..) => {
+            "check the name of the function and the number and types of its arguments".into()
+        }
+        EvalAltResult::ErrorAssignmentToConstant(..) => {
+            "declare the variable with 'let' instead of 'const'".into()
+        }
+        _ => return None,
+    })
+}
@@ -182,6 +182,8 @@ pub enum Union {
     Map(Box<crate::Map>, Tag, AccessMode),
     /// A function pointer.
     FnPtr(Box<FnPtr>, Tag, AccessMode),
+    /// A tuple value.
+    Tuple(Box<crate::Tuple>, Tag, AccessMode),
     /// A timestamp value.
     #[cfg(not(feature = "no_std"))]
     TimeStamp(Box<Instant>, Tag, AccessMode),
@@ -283,6 +285,7 @@ impl Dynamic {
             | Union::Char(_, tag, _)
             | Union::Int(_, tag, _)
             | Union::FnPtr(_, tag, _)
+            | Union::Tuple(_, tag, _)
             | Union::Variant(_, tag, _) => tag,
 
             #[cfg(not(feature = "no_float"))]
@@ -308,6 +311,7 @@ impl Dynamic {
             | Union::Char(_, ref mut tag, _)
             | Union::Int(_, ref mut tag, _)
             | Union::FnPtr(_, ref mut tag, _)
+            | Union::Tuple(_, ref mut tag, _)
             | Union::Variant(_, ref mut tag, _) => *tag = value,
 
             #[cfg(not(feature = "no_float"))]
@@ -390,6 +394,9 @@ impl Dynamic {
         if TypeId::of::<T>() == TypeId::of::<FnPtr>() {
             return matches!(self.0, Union::FnPtr(..));
         }
+        if TypeId::of::<T>() == TypeId::of::<crate::Tuple>() {
+            return matches!(self.0, Union::Tuple(..));
+        }
         #[cfg(not(feature = "no_std"))]
         if TypeId::of::<T>() == TypeId::of::<crate::Instant>() {
             return matches!(self.0, Union::TimeStamp(..));
@@ -422,6 +429,7 @@ impl Dynamic {
             #[cfg(not(feature = "no_object"))]
             Union::Map(..) => TypeId::of::<crate::Map>(),
             Union::FnPtr(..) => TypeId::of::<FnPtr>(),
+            Union::Tuple(..) => TypeId::of::<crate::Tuple>(),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(..) => TypeId::of::<Instant>(),
 
@@ -456,6 +464,7 @@ impl Dynamic {
             #[cfg(not(feature = "no_object"))]
             Union::Map(..) => "map",
             Union::FnPtr(..) => "Fn",
+            Union::Tuple(..) => "tuple",
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(..) => "timestamp",
 
@@ -500,14 +509,25 @@ impl Hash for Dynamic {
             #[cfg(not(feature = "no_object"))]
             Union::Map(ref m, ..) => m.hash(state),
             Union::FnPtr(ref f, ..) => f.hash(state),
+            Union::Tuple(ref t, ..) => t.hash(state),
 
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(ref cell, ..) => (*crate::func::locked_read(cell)).hash(state),
 
-            Union::Variant(..) => unimplemented!("{} cannot be hashed", self.type_name()),
+            Union::Variant(ref v, ..) => {
+                let value_any = (***v).as_any();
+
+                if let Some(range) = value_any.downcast_ref::<ExclusiveRange>() {
+                    range.hash(state);
+                } else if let Some(range) = value_any.downcast_ref::<InclusiveRange>() {
+                    range.hash(state);
+                } else {
+                    unimplemented!("{} cannot be hashed", self.type_name())
+                }
+            }
 
             #[cfg(not(feature = "no_std"))]
-            Union::TimeStamp(..) => unimplemented!("{} cannot be hashed", self.type_name()),
+            Union::TimeStamp(ref t, ..) => t.hash(state),
         }
     }
 }
@@ -531,6 +551,7 @@ impl fmt::Display for Dynamic {
             #[cfg(not(feature = "no_object"))]
             Union::Map(..) => fmt::Debug::fmt(self, f),
             Union::FnPtr(ref v, ..) => fmt::Display::fmt(v, f),
+            Union::Tuple(ref v, ..) => fmt::Debug::fmt(v, f),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(..) => f.write_str("<timestamp>"),
 
@@ -634,6 +655,7 @@ impl fmt::Debug for Dynamic {
                 fmt::Debug::fmt(v, f)
             }
             Union::FnPtr(ref v, ..) => fmt::Debug::fmt(v, f),
+            Union::Tuple(ref v, ..) => fmt::Debug::fmt(v, f),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(..) => write!(f, "<timestamp>"),
 
@@ -732,6 +754,7 @@ impl Clone for Dynamic {
             #[cfg(not(feature = "no_object"))]
             Union::Map(ref v, tag, ..) => Self(Union::Map(v.clone(), tag, ReadWrite)),
             Union::FnPtr(ref v, tag, ..) => Self(Union::FnPtr(v.clone(), tag, ReadWrite)),
+            Union::Tuple(ref v, tag, ..) => Self(Union::Tuple(v.clone(), tag, ReadWrite)),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(ref v, tag, ..) => Self(Union::TimeStamp(v.clone(), tag, ReadWrite)),
 
@@ -991,6 +1014,7 @@ impl Dynamic {
             | Union::Char(.., access)
             | Union::Int(.., access)
             | Union::FnPtr(.., access)
+            | Union::Tuple(.., access)
             | Union::Variant(.., access) => access,
 
             #[cfg(not(feature = "no_float"))]
@@ -1017,6 +1041,12 @@ impl Dynamic {
             | Union::Int(.., ref mut access)
             | Union::FnPtr(.., ref mut access)
             | Union::Variant(.., ref mut access) => *access = typ,
+            Union::Tuple(ref mut t, _, ref mut access) => {
+                *access = typ;
+                for v in t.iter_mut() {
+                    v.set_access_mode(typ);
+                }
+            }
 
             #[cfg(not(feature = "no_float"))]
             Union::Float(.., ref mut access) => *access = typ,
@@ -1039,6 +1069,7 @@ impl Dynamic {
                 }
             }
             #[cfg(not(feature = "no_std"))]
+            #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(.., ref mut access) => *access = typ,
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(.., ref mut access) => *access = typ,
@@ -1097,10 +1128,24 @@ impl Dynamic {
 
             #[cfg(not(feature = "no_float"))]
             Union::Float(..) => true,
+            #[cfg(feature = "decimal")]
+            Union::Decimal(..) => true,
             #[cfg(not(feature = "no_index"))]
-            Union::Array(..) => true,
+            Union::Array(ref a, ..) => a.iter().all(Self::is_hashable),
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(..) => true,
             #[cfg(not(feature = "no_object"))]
-            Union::Map(..) => true,
+            Union::Map(ref m, ..) => m.values().all(Self::is_hashable),
+            #[cfg(not(feature = "no_std"))]
+            Union::TimeStamp(..) => true,
+
+            Union::Tuple(ref t, ..) => t.iter().all(Self::is_hashable),
+
+            Union::Variant(ref v, ..) => {
+                let type_id = (***v).as_any().type_id();
+                type_id == TypeId::of::<ExclusiveRange>()
+                    || type_id == TypeId::of::<InclusiveRange>()
+            }
 
             #[cfg(not(feature = "no_closure"))]
             Union::Shared(ref cell, ..) => crate::func::locked_read(cell).is_hashable(),
@@ -1108,6 +1153,79 @@ impl Dynamic {
             _ => false,
         }
     }
+    /// Compare two values structurally.
+    ///
+    /// Floating-point numbers are compared bit-wise, so values that are the same always have the
+    /// same hash.  Values of different types are never the same.
+    ///
+    /// Returns [`None`] if the values cannot be compared without a script engine (e.g. custom
+    /// types other than ranges and tuples).
+    #[must_use]
+    pub(crate) fn structural_eq(&self, other: &Self) -> Option<bool> {
+        /// Compare two lists of values.
+        fn all_eq<'a>(
+            a: impl ExactSizeIterator<Item = &'a Dynamic>,
+            b: impl ExactSizeIterator<Item = &'a Dynamic>,
+        ) -> Option<bool> {
+            if a.len() != b.len() {
+                return Some(false);
+            }
+            for (x, y) in a.zip(b) {
+                match x.structural_eq(y) {
+                    Some(true) => (),
+                    r => return r,
+                }
+            }
+            Some(true)
+        }
+
+        match (&self.0, &other.0) {
+            #[cfg(not(feature = "no_closure"))]
+            (Union::Shared(cell, ..), _) => crate::func::locked_read(cell).structural_eq(other),
+            #[cfg(not(feature = "no_closure"))]
+            (_, Union::Shared(cell, ..)) => self.structural_eq(&crate::func::locked_read(cell)),
+
+            (Union::Unit(..), Union::Unit(..)) => Some(true),
+            (Union::Bool(a, ..), Union::Bool(b, ..)) => Some(a == b),
+            (Union::Str(a, ..), Union::Str(b, ..)) => Some(a == b),
+            (Union::Char(a, ..), Union::Char(b, ..)) => Some(a == b),
+            (Union::Int(a, ..), Union::Int(b, ..)) => Some(a == b),
+            #[cfg(not(feature = "no_float"))]
+            (Union::Float(a, ..), Union::Float(b, ..)) => Some(a.to_ne_bytes() == b.to_ne_bytes()),
+            #[cfg(feature = "decimal")]
+            (Union::Decimal(a, ..), Union::Decimal(b, ..)) => Some(a == b),
+            #[cfg(not(feature = "no_index"))]
+            (Union::Array(a, ..), Union::Array(b, ..)) => all_eq(a.iter(), b.iter()),
+            #[cfg(not(feature = "no_index"))]
+            (Union::Blob(a, ..), Union::Blob(b, ..)) => Some(a == b),
+            #[cfg(not(feature = "no_object"))]
+            (Union::Map(a, ..), Union::Map(b, ..)) if a.keys().eq(b.keys()) => {
+                all_eq(a.values(), b.values())
+            }
+            (Union::FnPtr(a, ..), Union::FnPtr(b, ..)) if a.fn_name() == b.fn_name() => {
+                all_eq(a.curry().iter(), b.curry().iter())
+            }
+            #[cfg(not(feature = "no_std"))]
+            (Union::TimeStamp(a, ..), Union::TimeStamp(b, ..)) => Some(a == b),
+            (Union::Tuple(a, ..), Union::Tuple(b, ..)) => all_eq(a.iter(), b.iter()),
+
+            (Union::Variant(a, ..), Union::Variant(b, ..)) => {
+                let (a, b) = ((***a).as_any(), (***b).as_any());
+
+                if a.type_id() != b.type_id() {
+                    Some(false)
+                } else if let Some(a) = a.downcast_ref::<ExclusiveRange>() {
+                    Some(Some(a) == b.downcast_ref())
+                } else if let Some(a) = a.downcast_ref::<InclusiveRange>() {
+                    Some(Some(a) == b.downcast_ref())
+                } else {
+                    None
+                }
+            }
+
+            _ => Some(false),
+        }
+    }
     /// Create a [`Dynamic`] from any type.  A [`Dynamic`] value is simply returned as is.
     ///
     /// # Notes
@@ -1123,6 +1241,10 @@ impl Dynamic {
     /// custom type. Again, use `Into<Dynamic>` to get a [`Dynamic`] with a [`Map`][crate::Map]
     /// value.
     ///
+    /// The same goes for [`Option<T>`] (converted into the value or `()`), [`Result<T, E>`]
+    /// (converted into an object map with either an `ok` or an `err` property) and tuples of up to
+    /// eight items (converted into an [`Array`][crate::Array]).
+    ///
     /// # Examples
     ///
     /// ```
@@ -1171,6 +1293,7 @@ impl Dynamic {
         #[cfg(not(feature = "no_object"))]
         reify!(value, |v: crate::Map| return v.into());
         reify!(value, |v: FnPtr| return v.into());
+        reify!(value, |v: crate::Tuple| return v.into());
 
         #[cfg(not(feature = "no_std"))]
         reify!(value, |v: Instant| return v.into());
@@ -1205,11 +1328,13 @@ impl Dynamic {
 
         match self.0 {
             Union::Shared(..) => self,
-            _ => Self(Union::Shared(
-                crate::Locked::new(self).into(),
-                DEFAULT_TAG_VALUE,
-                _access,
-            )),
+            _ => {
+                let cell = crate::Locked::new(self).into();
+                #[cfg(feature = "gc")]
+                #[cfg(not(feature = "no_std"))]
+                crate::api::gc::track_shared_value(&cell);
+                Self(Union::Shared(cell, DEFAULT_TAG_VALUE, _access))
+            }
         }
     }
     /// Convert the [`Dynamic`] value into specific type.
@@ -1265,6 +1390,7 @@ impl Dynamic {
             #[cfg(not(feature = "no_object"))]
             Union::Map(v, ..) => reify!(*v => Option<T>),
             Union::FnPtr(v, ..) => reify!(*v => Option<T>),
+            Union::Tuple(v, ..) => reify!(*v => Option<T>),
             #[cfg(not(feature = "no_std"))]
             Union::TimeStamp(v, ..) => reify!(*v => Option<T>),
             Union::Unit(v, ..) => reify!(v => Option<T>),
@@ -1273,6 +1399,52 @@ impl Dynamic {
             Union::Shared(..) => unreachable!("Union::Shared case should be already handled"),
         }
     }
+    /// Convert the [`Dynamic`] value into specific type, returning the value itself if the types
+    /// mismatch.
+    ///
+    /// Unlike [`try_cast`][Dynamic::try_cast], the original value is kept on failure, so its
+    /// [type name][Dynamic::type_name] and contents are available for error messages.
+    ///
+    /// Casting to a [`Dynamic`] just returns as is, but if it contains a shared value,
+    /// it is cloned into a [`Dynamic`] with a normal value.
+    ///
+    /// # Panics or Deadlocks
+    ///
+    /// Under the `sync` feature, this call may deadlock, or [panic](https://doc.rust-lang.org/std/sync/struct.RwLock.html#panics-1).
+    /// Otherwise, this call panics if the data is currently borrowed for write.
+    ///
+    /// These normally shouldn't occur since most operations in Rhai is single-threaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Dynamic;
+    ///
+    /// let x = Dynamic::from(42_u32);
+    ///
+    /// let err = x.try_cast_result::<String>().unwrap_err();
+    ///
+    /// assert_eq!(err.type_name(), "u32");
+    /// assert_eq!(err.try_cast_result::<u32>().unwrap(), 42);
+    /// ```
+    #[inline]
+    pub fn try_cast_result<T: Any>(self) -> Result<T, Self> {
+        #[cfg(not(feature = "no_closure"))]
+        if let Union::Shared(..) = self.0 {
+            return self.flatten().try_cast_result::<T>();
+        }
+
+        let type_id = TypeId::of::<T>();
+
+        if type_id == TypeId::of::<Self>()
+            || type_id == self.type_id()
+            || (type_id == TypeId::of::<String>() && matches!(self.0, Union::Str(..)))
+        {
+            Ok(self.try_cast::<T>().expect(CHECKED))
+        } else {
+            Err(self)
+        }
+    }
     /// Convert the [`Dynamic`] value into a specific type.
     ///
     /// Casting to a [`Dynamic`] just returns as is, but if it contains a shared value,
@@ -1358,6 +1530,88 @@ impl Dynamic {
             _ => self.clone(),
         }
     }
+    /// Deep-clone the [`Dynamic`], failing as soon as the copy grows beyond a size budget.
+    ///
+    /// Size is measured as for the data size limits of an [`Engine`][crate::Engine]: the total
+    /// number of array items, BLOB bytes, object map properties and string bytes contained.
+    ///
+    /// Shared values are copied, so the clone does not share any data with the original.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorDataTooLarge`][crate::EvalAltResult::ErrorDataTooLarge] if the size of the
+    /// [`Dynamic`] exceeds `max_size`.  The clone stops at that point, so deeply-nested or cyclic
+    /// data is never copied in full.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Dynamic, Map};
+    ///
+    /// let mut map = Map::new();
+    /// map.insert("name".into(), "hello".into());
+    ///
+    /// let value = Dynamic::from_map(map);
+    ///
+    /// // 1 property + 5 characters
+    /// assert!(value.deep_clone_with_limit(6).is_ok());
+    /// assert!(value.deep_clone_with_limit(5).is_err());
+    /// ```
+    #[inline]
+    pub fn deep_clone_with_limit(&self, max_size: usize) -> crate::RhaiResultOf<Self> {
+        let mut budget = max_size;
+        self.deep_clone_within_budget(&mut budget)
+    }
+    /// Deep-clone the [`Dynamic`], deducting its size from a budget.
+    fn deep_clone_within_budget(&self, budget: &mut usize) -> crate::RhaiResultOf<Self> {
+        fn consume(budget: &mut usize, size: usize) -> crate::RhaiResultOf<()> {
+            *budget = budget.checked_sub(size).ok_or_else(|| {
+                crate::ERR::ErrorDataTooLarge(
+                    "Size of value".to_string(),
+                    Default::default(),
+                    crate::Position::NONE,
+                )
+            })?;
+            Ok(())
+        }
+
+        match self.0 {
+            Union::Str(ref s, ..) => {
+                consume(budget, s.len())?;
+                Ok(self.clone())
+            }
+            #[cfg(not(feature = "no_index"))]
+            Union::Array(ref arr, tag, access) => {
+                consume(budget, arr.len())?;
+
+                let mut copy = crate::Array::with_capacity(arr.len());
+                for item in arr.iter() {
+                    copy.push(item.deep_clone_within_budget(budget)?);
+                }
+                Ok(Self(Union::Array(copy.into(), tag, access)))
+            }
+            #[cfg(not(feature = "no_index"))]
+            Union::Blob(ref blob, ..) => {
+                consume(budget, blob.len())?;
+                Ok(self.clone())
+            }
+            #[cfg(not(feature = "no_object"))]
+            Union::Map(ref map, tag, access) => {
+                consume(budget, map.len())?;
+
+                let mut copy = crate::Map::new();
+                for (key, value) in map.iter() {
+                    copy.insert(key.clone(), value.deep_clone_within_budget(budget)?);
+                }
+                Ok(Self(Union::Map(copy.into(), tag, access)))
+            }
+            #[cfg(not(feature = "no_closure"))]
+            Union::Shared(ref cell, ..) => {
+                crate::func::locked_read(cell).deep_clone_within_budget(budget)
+            }
+            _ => Ok(self.clone()),
+        }
+    }
     /// Flatten the [`Dynamic`].
     ///
     /// If the [`Dynamic`] is not a shared value, it returns itself.
@@ -1564,6 +1818,12 @@ impl Dynamic {
                 _ => None,
             };
         }
+        if TypeId::of::<T>() == TypeId::of::<crate::Tuple>() {
+            return match self.0 {
+                Union::Tuple(ref v, ..) => v.as_ref().as_any().downcast_ref::<T>(),
+                _ => None,
+            };
+        }
         #[cfg(not(feature = "no_std"))]
         if TypeId::of::<T>() == TypeId::of::<Instant>() {
             return match self.0 {
@@ -1662,6 +1922,12 @@ impl Dynamic {
                 _ => None,
             };
         }
+        if TypeId::of::<T>() == TypeId::of::<crate::Tuple>() {
+            return match self.0 {
+                Union::Tuple(ref mut v, ..) => v.as_mut().as_any_mut().downcast_mut::<T>(),
+                _ => None,
+            };
+        }
         #[cfg(not(feature = "no_std"))]
         if TypeId::of::<T>() == TypeId::of::<Instant>() {
             return match self.0 {
@@ -2050,12 +2316,77 @@ impl<K: Into<crate::Identifier>> From<std::collections::BTreeSet<K>> for Dynamic
         ))
     }
 }
+impl<T: Variant + Clone> From<Option<T>> for Dynamic {
+    /// Convert an [`Option`] into its value, or `()` if it is [`None`].
+    #[inline]
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::UNIT, Self::from)
+    }
+}
+#[cfg(not(feature = "no_object"))]
+impl<T: Variant + Clone, E: Variant + Clone> From<Result<T, E>> for Dynamic {
+    /// Convert a [`Result`] into an object map with an `ok` property holding the value,
+    /// or an `err` property holding the error.
+    #[inline]
+    fn from(value: Result<T, E>) -> Self {
+        let (key, value) = match value {
+            Ok(v) => ("ok", Self::from(v)),
+            Err(e) => ("err", Self::from(e)),
+        };
+        let mut map = crate::Map::new();
+        map.insert(key.into(), value);
+        Self(Union::Map(Box::new(map), DEFAULT_TAG_VALUE, ReadWrite))
+    }
+}
+
+/// Macro to implement conversions from tuples into [`Dynamic`] arrays.
+#[cfg(not(feature = "no_index"))]
+macro_rules! impl_from_tuple {
+    ($($p:ident),+) => {
+        impl<$($p: Variant + Clone),+> From<($($p,)+)> for Dynamic {
+            #[inline]
+            #[allow(non_snake_case)]
+            fn from(value: ($($p,)+)) -> Self {
+                let ($($p,)+) = value;
+                Self(Union::Array(
+                    Box::new(vec![$(Self::from($p)),+]),
+                    DEFAULT_TAG_VALUE,
+                    ReadWrite,
+                ))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B, C);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B, C, D);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B, C, D, E);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B, C, D, E, F);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B, C, D, E, F, G);
+#[cfg(not(feature = "no_index"))]
+impl_from_tuple!(A, B, C, D, E, F, G, H);
+
 impl From<FnPtr> for Dynamic {
     #[inline(always)]
     fn from(value: FnPtr) -> Self {
         Self(Union::FnPtr(value.into(), DEFAULT_TAG_VALUE, ReadWrite))
     }
 }
+impl From<crate::Tuple> for Dynamic {
+    #[inline(always)]
+    fn from(value: crate::Tuple) -> Self {
+        Self(Union::Tuple(value.into(), DEFAULT_TAG_VALUE, ReadWrite))
+    }
+}
 #[cfg(not(feature = "no_std"))]
 impl From<Instant> for Dynamic {
     #[inline(always)]
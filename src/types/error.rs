@@ -9,9 +9,39 @@ use std::fmt;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 
+/// Context information attached to an error raised when a limit is exceeded, to help locate the
+/// offending construct.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct LimitContext {
+    /// Name of the script-defined function being evaluated, empty if at global level.
+    pub fn_name: String,
+    /// [Position][`Position`] of the innermost loop being evaluated, [`NONE`][Position::NONE] if
+    /// not within a loop.
+    pub loop_pos: Position,
+    /// Largest variables in scope (name and data size), in descending order of size.
+    ///
+    /// Data size is the total number of array items, object map properties and string
+    /// characters contained. Variables with no data size are omitted.
+    pub variables: Vec<(String, usize)>,
+}
+
+impl Default for LimitContext {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            fn_name: String::new(),
+            loop_pos: Position::NONE,
+            variables: Vec::new(),
+        }
+    }
+}
+
 /// Evaluation result.
 ///
 /// All wrapped [`Position`] values represent the location in the script where the error occurs.
+/// Unlike a [`ParseError`][crate::ParseError], an [`EvalAltResult`] has no [span][crate::Span],
+/// only the starting [position][Position] of the offending expression or statement.
 ///
 /// Some errors never appear when certain features are turned on.
 /// They still exist so that the application can turn features on and off without going through
@@ -90,14 +120,15 @@ pub enum EvalAltResult {
     /// Arithmetic error encountered. Wrapped value is the error message.
     ErrorArithmetic(String, Position),
 
-    /// Number of operations over maximum limit.
-    ErrorTooManyOperations(Position),
-    /// [Modules][crate::Module] over maximum limit.
-    ErrorTooManyModules(Position),
-    /// Call stack over maximum limit.
-    ErrorStackOverflow(Position),
-    /// Data value over maximum size limit. Wrapped value is the type name.
-    ErrorDataTooLarge(String, Position),
+    /// Number of operations over maximum limit. Wrapped value is the [context][LimitContext].
+    ErrorTooManyOperations(Box<LimitContext>, Position),
+    /// [Modules][crate::Module] over maximum limit. Wrapped value is the [context][LimitContext].
+    ErrorTooManyModules(Box<LimitContext>, Position),
+    /// Call stack over maximum limit. Wrapped value is the [context][LimitContext].
+    ErrorStackOverflow(Box<LimitContext>, Position),
+    /// Data value over maximum size limit. Wrapped values are the type name and the
+    /// [context][LimitContext].
+    ErrorDataTooLarge(String, Box<LimitContext>, Position),
     /// The script is prematurely terminated. Wrapped value is the termination token.
     ErrorTerminated(Dynamic, Position),
 
@@ -134,7 +165,8 @@ impl fmt::Display for EvalAltResult {
 
             #[cfg(not(feature = "no_function"))]
             Self::ErrorInFunctionCall(s, src, err, ..) if crate::parser::is_anonymous_fn(s) => {
-                write!(f, "{} in call to closure", err)?;
+                let name = crate::parser::anonymous_fn_display_name(s).expect("anonymous function");
+                write!(f, "{} in call to {}", err, name)?;
                 if !src.is_empty() {
                     write!(f, " @ '{}'", src)?;
                 }
@@ -418,6 +450,30 @@ impl EvalAltResult {
             }
         };
     }
+    /// Get the [context][LimitContext] of this error if it is raised when a limit is exceeded.
+    #[must_use]
+    pub fn limit_context(&self) -> Option<&LimitContext> {
+        match self {
+            Self::ErrorTooManyOperations(ctx, ..)
+            | Self::ErrorTooManyModules(ctx, ..)
+            | Self::ErrorStackOverflow(ctx, ..)
+            | Self::ErrorDataTooLarge(_, ctx, ..) => Some(ctx),
+            _ => None,
+        }
+    }
+    /// Get a mutable reference to the [context][LimitContext] of this error if it is raised when
+    /// a limit is exceeded.
+    #[cfg(not(feature = "unchecked"))]
+    #[must_use]
+    pub(crate) fn limit_context_mut(&mut self) -> Option<&mut LimitContext> {
+        match self {
+            Self::ErrorTooManyOperations(ctx, ..)
+            | Self::ErrorTooManyModules(ctx, ..)
+            | Self::ErrorStackOverflow(ctx, ..)
+            | Self::ErrorDataTooLarge(_, ctx, ..) => Some(ctx),
+            _ => None,
+        }
+    }
     /// Unwrap this error and get the very base error.
     #[must_use]
     pub fn unwrap_inner(&self) -> &Self {
@@ -428,6 +484,44 @@ impl EvalAltResult {
             _ => self,
         }
     }
+    /// Get the kind of this error, which is the name of its variant (e.g. `ErrorVariableNotFound`).
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::ErrorSystem(..) => "ErrorSystem",
+            Self::ErrorParsing(..) => "ErrorParsing",
+            Self::ErrorVariableExists(..) => "ErrorVariableExists",
+            Self::ErrorForbiddenVariable(..) => "ErrorForbiddenVariable",
+            Self::ErrorVariableNotFound(..) => "ErrorVariableNotFound",
+            Self::ErrorPropertyNotFound(..) => "ErrorPropertyNotFound",
+            Self::ErrorIndexNotFound(..) => "ErrorIndexNotFound",
+            Self::ErrorFunctionNotFound(..) => "ErrorFunctionNotFound",
+            Self::ErrorModuleNotFound(..) => "ErrorModuleNotFound",
+            Self::ErrorInFunctionCall(..) => "ErrorInFunctionCall",
+            Self::ErrorInModule(..) => "ErrorInModule",
+            Self::ErrorUnboundThis(..) => "ErrorUnboundThis",
+            Self::ErrorMismatchDataType(..) => "ErrorMismatchDataType",
+            Self::ErrorMismatchOutputType(..) => "ErrorMismatchOutputType",
+            Self::ErrorIndexingType(..) => "ErrorIndexingType",
+            Self::ErrorArrayBounds(..) => "ErrorArrayBounds",
+            Self::ErrorStringBounds(..) => "ErrorStringBounds",
+            Self::ErrorBitFieldBounds(..) => "ErrorBitFieldBounds",
+            Self::ErrorFor(..) => "ErrorFor",
+            Self::ErrorDataRace(..) => "ErrorDataRace",
+            Self::ErrorAssignmentToConstant(..) => "ErrorAssignmentToConstant",
+            Self::ErrorDotExpr(..) => "ErrorDotExpr",
+            Self::ErrorArithmetic(..) => "ErrorArithmetic",
+            Self::ErrorTooManyOperations(..) => "ErrorTooManyOperations",
+            Self::ErrorTooManyModules(..) => "ErrorTooManyModules",
+            Self::ErrorStackOverflow(..) => "ErrorStackOverflow",
+            Self::ErrorDataTooLarge(..) => "ErrorDataTooLarge",
+            Self::ErrorTerminated(..) => "ErrorTerminated",
+            Self::ErrorCustomSyntax(..) => "ErrorCustomSyntax",
+            Self::ErrorRuntime(..) => "ErrorRuntime",
+            Self::LoopBreak(..) => "LoopBreak",
+            Self::Return(..) => "Return",
+        }
+    }
     /// Get the [position][Position] of this error.
     #[must_use]
     pub const fn position(&self) -> Position {
@@ -456,9 +550,9 @@ impl EvalAltResult {
             | Self::ErrorMismatchOutputType(.., pos)
             | Self::ErrorDotExpr(.., pos)
             | Self::ErrorArithmetic(.., pos)
-            | Self::ErrorTooManyOperations(pos)
-            | Self::ErrorTooManyModules(pos)
-            | Self::ErrorStackOverflow(pos)
+            | Self::ErrorTooManyOperations(.., pos)
+            | Self::ErrorTooManyModules(.., pos)
+            | Self::ErrorStackOverflow(.., pos)
             | Self::ErrorDataTooLarge(.., pos)
             | Self::ErrorTerminated(.., pos)
             | Self::ErrorCustomSyntax(.., pos)
@@ -508,9 +602,9 @@ impl EvalAltResult {
             | Self::ErrorMismatchOutputType(.., pos)
             | Self::ErrorDotExpr(.., pos)
             | Self::ErrorArithmetic(.., pos)
-            | Self::ErrorTooManyOperations(pos)
-            | Self::ErrorTooManyModules(pos)
-            | Self::ErrorStackOverflow(pos)
+            | Self::ErrorTooManyOperations(.., pos)
+            | Self::ErrorTooManyModules(.., pos)
+            | Self::ErrorStackOverflow(.., pos)
             | Self::ErrorDataTooLarge(.., pos)
             | Self::ErrorTerminated(.., pos)
             | Self::ErrorCustomSyntax(.., pos)
@@ -530,4 +624,97 @@ impl EvalAltResult {
         }
         self
     }
+    /// Make a copy of this error, with any values flattened.
+    ///
+    /// A [system error][Self::ErrorSystem] is copied with its message only.
+    #[must_use]
+    pub(crate) fn copy(&self) -> Self {
+        match self {
+            Self::ErrorSystem(s, e) => Self::ErrorSystem(s.clone(), e.to_string().into()),
+            Self::ErrorParsing(t, p) => Self::ErrorParsing(t.clone(), *p),
+            Self::ErrorVariableExists(s, p) => Self::ErrorVariableExists(s.clone(), *p),
+            Self::ErrorForbiddenVariable(s, p) => Self::ErrorForbiddenVariable(s.clone(), *p),
+            Self::ErrorVariableNotFound(s, p) => Self::ErrorVariableNotFound(s.clone(), *p),
+            Self::ErrorPropertyNotFound(s, p) => Self::ErrorPropertyNotFound(s.clone(), *p),
+            Self::ErrorIndexNotFound(v, p) => Self::ErrorIndexNotFound(v.flatten_clone(), *p),
+            Self::ErrorFunctionNotFound(s, p) => Self::ErrorFunctionNotFound(s.clone(), *p),
+            Self::ErrorModuleNotFound(s, p) => Self::ErrorModuleNotFound(s.clone(), *p),
+            Self::ErrorInFunctionCall(f, s, e, p) => {
+                Self::ErrorInFunctionCall(f.clone(), s.clone(), e.copy().into(), *p)
+            }
+            Self::ErrorInModule(s, e, p) => Self::ErrorInModule(s.clone(), e.copy().into(), *p),
+            Self::ErrorUnboundThis(p) => Self::ErrorUnboundThis(*p),
+            Self::ErrorMismatchDataType(t, s, p) => {
+                Self::ErrorMismatchDataType(t.clone(), s.clone(), *p)
+            }
+            Self::ErrorMismatchOutputType(t, s, p) => {
+                Self::ErrorMismatchOutputType(t.clone(), s.clone(), *p)
+            }
+            Self::ErrorIndexingType(s, p) => Self::ErrorIndexingType(s.clone(), *p),
+            Self::ErrorArrayBounds(n, i, p) => Self::ErrorArrayBounds(*n, *i, *p),
+            Self::ErrorStringBounds(n, i, p) => Self::ErrorStringBounds(*n, *i, *p),
+            Self::ErrorBitFieldBounds(n, i, p) => Self::ErrorBitFieldBounds(*n, *i, *p),
+            Self::ErrorFor(p) => Self::ErrorFor(*p),
+            Self::ErrorDataRace(s, p) => Self::ErrorDataRace(s.clone(), *p),
+            Self::ErrorAssignmentToConstant(s, p) => Self::ErrorAssignmentToConstant(s.clone(), *p),
+            Self::ErrorDotExpr(s, p) => Self::ErrorDotExpr(s.clone(), *p),
+            Self::ErrorArithmetic(s, p) => Self::ErrorArithmetic(s.clone(), *p),
+            Self::ErrorTooManyOperations(c, p) => Self::ErrorTooManyOperations(c.clone(), *p),
+            Self::ErrorTooManyModules(c, p) => Self::ErrorTooManyModules(c.clone(), *p),
+            Self::ErrorStackOverflow(c, p) => Self::ErrorStackOverflow(c.clone(), *p),
+            Self::ErrorDataTooLarge(s, c, p) => Self::ErrorDataTooLarge(s.clone(), c.clone(), *p),
+            Self::ErrorTerminated(v, p) => Self::ErrorTerminated(v.flatten_clone(), *p),
+            Self::ErrorCustomSyntax(s, t, p) => Self::ErrorCustomSyntax(s.clone(), t.clone(), *p),
+            Self::ErrorRuntime(v, p) => Self::ErrorRuntime(v.flatten_clone(), *p),
+            Self::LoopBreak(b, p) => Self::LoopBreak(*b, *p),
+            Self::Return(v, p) => Self::Return(v.flatten_clone(), *p),
+        }
+    }
+}
+
+/// Error of a typed evaluation (e.g. [`Engine::eval_typed`][crate::Engine::eval_typed]).
+///
+/// It converts into [`EvalAltResult::ErrorMismatchOutputType`] (dropping the result value) when
+/// returned via `?` from a function returning `Result<_, Box<EvalAltResult>>`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TypedEvalError {
+    /// Evaluation failed.
+    Eval(Box<EvalAltResult>),
+    /// Returned type is not the same as the required output type.
+    /// Wrapped values are the type requested, type of the actual result and the result value
+    /// itself, so that it can be coerced without evaluating the script again.
+    MismatchOutputType(String, String, Dynamic),
+}
+
+impl Error for TypedEvalError {}
+
+impl fmt::Display for TypedEvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Eval(err) => fmt::Display::fmt(err, f),
+            Self::MismatchOutputType(e, a, ..) => {
+                write!(f, "Output type incorrect: {} (expecting {})", a, e)
+            }
+        }
+    }
+}
+
+impl From<Box<EvalAltResult>> for TypedEvalError {
+    #[inline(always)]
+    fn from(err: Box<EvalAltResult>) -> Self {
+        Self::Eval(err)
+    }
+}
+
+impl From<TypedEvalError> for Box<EvalAltResult> {
+    #[inline(never)]
+    fn from(err: TypedEvalError) -> Self {
+        match err {
+            TypedEvalError::Eval(err) => err,
+            TypedEvalError::MismatchOutputType(e, a, ..) => {
+                EvalAltResult::ErrorMismatchOutputType(e, a, Position::NONE).into()
+            }
+        }
+    }
 }
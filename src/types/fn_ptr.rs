@@ -103,6 +103,176 @@ impl FnPtr {
     pub fn is_anonymous(&self) -> bool {
         self.name.starts_with(crate::engine::FN_ANONYMOUS)
     }
+    /// Get the [position][Position] of the definition of the anonymous function this pointer
+    /// refers to, or [`None`] if it is not an anonymous function or the position is unknown.
+    ///
+    /// Not available under `no_function`.
+    #[cfg(not(feature = "no_function"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn origin(&self) -> Option<Position> {
+        crate::parser::anonymous_fn_origin(&self.name)
+    }
+    /// Rehydrate the function pointer (e.g. after deserialization) against an [`AST`] and an
+    /// [`Engine`], checking that the function it refers to exists and can take all the curried
+    /// arguments.
+    ///
+    /// The function may be script-defined in the [`AST`] (not available under `no_function`) or
+    /// native Rust, registered with the [`Engine`].
+    ///
+    /// An error is returned if no such function is found.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, FnPtr};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("fn foo(x, y) { x + y }")?;
+    ///
+    /// let mut fn_ptr = FnPtr::new("foo")?;
+    /// fn_ptr.add_curry(40_i64.into());
+    ///
+    /// let fn_ptr = fn_ptr.rehydrate(&engine, &ast)?;
+    ///
+    /// assert_eq!(fn_ptr.call::<i64>(&engine, &ast, (2_i64,))?, 42);
+    ///
+    /// assert!(FnPtr::new("bar")?.rehydrate(&engine, &ast).is_err());
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rehydrate(self, engine: &Engine, ast: &AST) -> RhaiResultOf<Self> {
+        let min_params = self.curry.len();
+
+        if Self::find_arities(self.fn_name(), engine, ast)
+            .iter()
+            .any(|&n| n >= min_params)
+        {
+            Ok(self)
+        } else {
+            Err(ERR::ErrorFunctionNotFound(self.fn_name().to_string(), Position::NONE).into())
+        }
+    }
+    /// Create a new function pointer, checking against an [`AST`] and an [`Engine`] that the
+    /// function it refers to exists and takes `num_params` parameters.
+    ///
+    /// This is useful when function pointers are created from untrusted input (e.g. configuration),
+    /// so that errors are caught at creation time instead of when the function pointer is called.
+    ///
+    /// The function may be script-defined in the [`AST`] (not available under `no_function`) or
+    /// native Rust, registered with the [`Engine`].
+    ///
+    /// An error is returned if the name is not a valid function name, if no such function is found,
+    /// or if the function is only found with a different number of parameters (in which case the
+    /// numbers of parameters available are listed in the error).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+    /// # #[cfg(not(feature = "no_function"))]
+    /// # {
+    /// use rhai::{Engine, FnPtr};
+    ///
+    /// let engine = Engine::new();
+    ///
+    /// let ast = engine.compile("fn foo(x, y) { x + y }")?;
+    ///
+    /// let fn_ptr = FnPtr::try_new_checked("foo", 2, &engine, &ast)?;
+    ///
+    /// assert_eq!(fn_ptr.call::<i64>(&engine, &ast, (40_i64, 2_i64))?, 42);
+    ///
+    /// let err = FnPtr::try_new_checked("foo", 1, &engine, &ast).expect_err("should error");
+    ///
+    /// assert_eq!(err.to_string(), "Function not found: foo (1 parameter; found: 2)");
+    ///
+    /// assert!(FnPtr::try_new_checked("bar", 2, &engine, &ast).is_err());
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_new_checked(
+        name: impl Into<Identifier>,
+        num_params: usize,
+        engine: &Engine,
+        ast: &AST,
+    ) -> RhaiResultOf<Self> {
+        let fn_ptr = Self::new(name)?;
+        let arities = Self::find_arities(fn_ptr.fn_name(), engine, ast);
+
+        if arities.contains(&num_params) {
+            return Ok(fn_ptr);
+        }
+
+        let name = fn_ptr.fn_name();
+
+        let signature = if arities.is_empty() {
+            name.to_string()
+        } else {
+            let mut arities = arities;
+            arities.sort_unstable();
+            arities.dedup();
+
+            format!(
+                "{name} ({num_params} parameter{}; found: {})",
+                if num_params == 1 { "" } else { "s" },
+                arities
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<StaticVec<_>>()
+                    .join(", ")
+            )
+        };
+
+        Err(ERR::ErrorFunctionNotFound(signature, Position::NONE).into())
+    }
+    /// Find the numbers of parameters of all functions with a particular name, script-defined in
+    /// an [`AST`] or registered with an [`Engine`].
+    fn find_arities(name: &str, engine: &Engine, ast: &AST) -> StaticVec<usize> {
+        /// Collect the numbers of parameters of all functions in a module with a name.
+        fn collect(module: &Module, name: &str, global_only: bool, arities: &mut StaticVec<usize>) {
+            arities.extend(
+                module
+                    .iter_fn()
+                    .filter(|f| {
+                        f.name == name
+                            && (!global_only || f.namespace == crate::FnNamespace::Global)
+                    })
+                    .map(|f| f.num_params),
+            );
+
+            if global_only {
+                module
+                    .iter_sub_modules()
+                    .for_each(|(_, m)| collect(m, name, true, arities));
+            }
+        }
+
+        let mut arities = StaticVec::new_const();
+
+        #[cfg(not(feature = "no_function"))]
+        collect(ast.shared_lib(), name, false, &mut arities);
+        #[cfg(feature = "no_function")]
+        let _ast = ast;
+
+        engine
+            .global_modules
+            .iter()
+            .for_each(|m| collect(m, name, false, &mut arities));
+
+        #[cfg(not(feature = "no_module"))]
+        engine
+            .global_sub_modules
+            .values()
+            .for_each(|m| collect(m, name, true, &mut arities));
+
+        arities
+    }
     /// Call the function pointer with curried arguments (if any).
     /// The function may be script-defined (not available under `no_function`) or native Rust.
     ///
@@ -5,7 +5,7 @@ use crate::{Shared, SmartString};
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
 use std::{
-    borrow::Borrow,
+    borrow::{Borrow, Cow},
     cmp::Ordering,
     fmt,
     hash::Hash,
@@ -114,6 +114,15 @@ impl From<String> for ImmutableString {
         Self(value.into())
     }
 }
+impl From<Cow<'_, str>> for ImmutableString {
+    #[inline(always)]
+    fn from(value: Cow<'_, str>) -> Self {
+        match value {
+            Cow::Borrowed(s) => s.into(),
+            Cow::Owned(s) => s.into(),
+        }
+    }
+}
 impl From<&SmartString> for ImmutableString {
     #[inline(always)]
     fn from(value: &SmartString) -> Self {
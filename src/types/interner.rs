@@ -28,6 +28,10 @@ pub struct StringsInterner<'a> {
     pub max_string_len: usize,
     /// Normal strings.
     strings: StraightHashMap<u64, ImmutableString>,
+    /// Number of lookups that found an interned string.
+    hits: u64,
+    /// Number of lookups that did not find an interned string.
+    misses: u64,
     /// Take care of the lifetime parameter.
     dummy: PhantomData<&'a ()>,
 }
@@ -55,6 +59,8 @@ impl StringsInterner<'_> {
             capacity: MAX_INTERNED_STRINGS,
             max_string_len: MAX_STRING_LEN,
             strings: StraightHashMap::default(),
+            hits: 0,
+            misses: 0,
             dummy: PhantomData,
         }
     }
@@ -76,7 +82,7 @@ impl StringsInterner<'_> {
     ) -> ImmutableString {
         let key = text.as_ref();
 
-        if key.len() > MAX_STRING_LEN {
+        if key.len() > self.max_string_len || self.capacity == 0 {
             return mapper(text);
         }
 
@@ -85,9 +91,12 @@ impl StringsInterner<'_> {
         let key = hasher.finish();
 
         if !self.strings.is_empty() && self.strings.contains_key(&key) {
+            self.hits += 1;
             return self.strings.get(&key).unwrap().clone();
         }
 
+        self.misses += 1;
+
         let value = mapper(text);
 
         if value.strong_count() > 1 {
@@ -123,6 +132,20 @@ impl StringsInterner<'_> {
         value
     }
 
+    /// Move all interned strings into a [`StringsInterner`] with a different lifetime.
+    #[inline]
+    #[must_use]
+    pub(crate) fn detach<'x>(self) -> StringsInterner<'x> {
+        StringsInterner {
+            capacity: self.capacity,
+            max_string_len: self.max_string_len,
+            strings: self.strings,
+            hits: self.hits,
+            misses: self.misses,
+            dummy: PhantomData,
+        }
+    }
+
     /// Number of strings interned.
     #[inline(always)]
     #[must_use]
@@ -139,6 +162,20 @@ impl StringsInterner<'_> {
         self.strings.is_empty()
     }
 
+    /// Number of lookups that found an interned string.
+    #[inline(always)]
+    #[must_use]
+    pub const fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups that did not find an interned string.
+    #[inline(always)]
+    #[must_use]
+    pub const fn misses(&self) -> u64 {
+        self.misses
+    }
+
     /// Clear all interned strings.
     #[inline(always)]
     #[allow(dead_code)]
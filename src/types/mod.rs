@@ -2,22 +2,49 @@
 
 pub mod bloom_filter;
 pub mod custom_types;
+pub mod diagnostic;
 pub mod dynamic;
 pub mod error;
 pub mod fn_ptr;
 pub mod immutable_string;
 pub mod interner;
+pub mod ordered_map;
+pub mod packed_array;
 pub mod parse_error;
+pub mod proxy;
 pub mod scope;
+pub mod set;
+pub mod sorted_map;
+pub mod state_machine;
+pub mod tuple;
 
 pub use bloom_filter::BloomFilterU64;
 pub use custom_types::{CustomTypeInfo, CustomTypesCollection};
+pub use diagnostic::{Diagnostic, DiagnosticLabel, Severity};
 pub use dynamic::Dynamic;
 #[cfg(not(feature = "no_std"))]
 pub use dynamic::Instant;
-pub use error::EvalAltResult;
+pub use error::{EvalAltResult, LimitContext, TypedEvalError};
 pub use fn_ptr::FnPtr;
 pub use immutable_string::ImmutableString;
 pub use interner::StringsInterner;
-pub use parse_error::{LexError, ParseError, ParseErrorType};
-pub use scope::Scope;
+#[cfg(not(feature = "no_object"))]
+pub use ordered_map::OrderedMap;
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_float"))]
+pub use packed_array::FloatArray;
+#[cfg(not(feature = "no_index"))]
+pub use packed_array::IntArray;
+pub use parse_error::{LexError, ParseError, ParseErrorType, ParseWarning};
+#[cfg(not(feature = "no_object"))]
+pub use proxy::{Proxy, ProxyHandler};
+#[cfg(not(feature = "no_object"))]
+pub use scope::ScopeMetadata;
+pub use scope::{FrozenScope, Scope};
+#[cfg(not(feature = "no_index"))]
+pub use set::Set;
+#[cfg(not(feature = "no_index"))]
+pub use sorted_map::SortedMap;
+#[cfg(not(feature = "no_custom_syntax"))]
+pub use state_machine::StateMachine;
+pub use tuple::Tuple;
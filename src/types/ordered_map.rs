@@ -0,0 +1,189 @@
+//! The [`OrderedMap`] type: a dictionary of [`Dynamic`] values that keeps insertion order.
+#![cfg(not(feature = "no_object"))]
+
+use crate::{Dynamic, Identifier, Map};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{collections::BTreeMap, fmt, iter::FromIterator};
+
+/// A dictionary of [`Dynamic`] values with string keys, which keeps its properties in the order
+/// they are first inserted.
+///
+/// Unlike [`Map`], which is sorted by key, the properties of an [`OrderedMap`] can also be accessed
+/// by position.
+///
+/// Not available under `no_object`.
+#[derive(Clone, Default)]
+pub struct OrderedMap {
+    /// Properties in insertion order.
+    entries: Vec<(Identifier, Dynamic)>,
+    /// Positions of properties in `entries`, by key.
+    index: BTreeMap<Identifier, usize>,
+}
+
+impl fmt::Debug for OrderedMap {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl OrderedMap {
+    /// Create a new empty [`OrderedMap`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            index: BTreeMap::new(),
+        }
+    }
+    /// Number of properties in this [`OrderedMap`].
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Is this [`OrderedMap`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Remove all properties from this [`OrderedMap`].
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+    /// Does this [`OrderedMap`] contain a property?
+    #[inline(always)]
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+    /// Get the value of a property.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Dynamic> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+    /// Get a mutable reference to the value of a property.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Dynamic> {
+        match self.index.get(key) {
+            Some(&i) => Some(&mut self.entries[i].1),
+            None => None,
+        }
+    }
+    /// Get the key and value of the property at a position.
+    #[inline]
+    #[must_use]
+    pub fn get_by_index(&self, index: usize) -> Option<(&str, &Dynamic)> {
+        self.entries.get(index).map(|(k, v)| (k.as_str(), v))
+    }
+    /// Get the position of a property.
+    #[inline]
+    #[must_use]
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+    /// Set the value of a property.
+    ///
+    /// A new property is added to the end.  An existing property keeps its position, and its
+    /// previous value is returned.
+    pub fn insert(&mut self, key: impl Into<Identifier>, value: Dynamic) -> Option<Dynamic> {
+        let key = key.into();
+
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+    /// Remove a property, returning its value.
+    ///
+    /// Properties after it are shifted forward, keeping their order.
+    pub fn remove(&mut self, key: &str) -> Option<Dynamic> {
+        let i = self.index.remove(key)?;
+        let (.., value) = self.entries.remove(i);
+
+        for (k, ..) in &self.entries[i..] {
+            *self.index.get_mut(k).expect("key exists") -= 1;
+        }
+
+        Some(value)
+    }
+    /// Get an iterator over the keys of this [`OrderedMap`], in insertion order.
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(k, ..)| k.as_str())
+    }
+    /// Get an iterator over the values of this [`OrderedMap`], in insertion order.
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &Dynamic> {
+        self.entries.iter().map(|(.., v)| v)
+    }
+    /// Get a mutable iterator over the values of this [`OrderedMap`], in insertion order.
+    #[inline]
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Dynamic> {
+        self.entries.iter_mut().map(|(.., v)| v)
+    }
+    /// Get an iterator over the properties of this [`OrderedMap`], in insertion order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Dynamic)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v))
+    }
+    /// Get a mutable iterator over the properties of this [`OrderedMap`], in insertion order.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut Dynamic)> {
+        self.entries.iter_mut().map(|(k, v)| (k.as_str(), v))
+    }
+    /// Convert this [`OrderedMap`] into a [`Map`], which is sorted by key.
+    #[inline]
+    #[must_use]
+    pub fn to_map(&self) -> Map {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+impl From<Map> for OrderedMap {
+    /// Create an [`OrderedMap`] from a [`Map`], with properties ordered by key.
+    #[inline]
+    fn from(map: Map) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Into<Identifier>> Extend<(K, Dynamic)> for OrderedMap {
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, Dynamic)>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|(k, v)| {
+            self.insert(k, v);
+        });
+    }
+}
+
+impl<K: Into<Identifier>> FromIterator<(K, Dynamic)> for OrderedMap {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, Dynamic)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl IntoIterator for OrderedMap {
+    type Item = (Identifier, Dynamic);
+    type IntoIter = std::vec::IntoIter<(Identifier, Dynamic)>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
@@ -0,0 +1,120 @@
+//! The [`IntArray`] and [`FloatArray`] types: packed arrays of numbers.
+#![cfg(not(feature = "no_index"))]
+
+use crate::INT;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    fmt,
+    iter::FromIterator,
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(not(feature = "no_float"))]
+use crate::FLOAT;
+
+macro_rules! packed_array {
+    ($(#[$attr:meta])* $array:ident => $elem:ty) => {
+        $(#[$attr])*
+        #[derive(Clone, Default, PartialEq)]
+        pub struct $array(Vec<$elem>);
+
+        impl fmt::Debug for $array {
+            #[cold]
+            #[inline(never)]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_list().entries(self.0.iter()).finish()
+            }
+        }
+
+        impl $array {
+            #[doc = concat!("Create a new empty [`", stringify!($array), "`].")]
+            #[inline(always)]
+            #[must_use]
+            pub const fn new() -> Self {
+                Self(Vec::new())
+            }
+            #[doc = concat!("Consume this [`", stringify!($array), "`], returning the packed elements.")]
+            #[inline(always)]
+            #[must_use]
+            pub fn into_vec(self) -> Vec<$elem> {
+                self.0
+            }
+        }
+
+        impl Deref for $array {
+            type Target = Vec<$elem>;
+
+            #[inline(always)]
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $array {
+            #[inline(always)]
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl From<Vec<$elem>> for $array {
+            #[inline(always)]
+            fn from(value: Vec<$elem>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$array> for Vec<$elem> {
+            #[inline(always)]
+            fn from(value: $array) -> Self {
+                value.0
+            }
+        }
+
+        impl IntoIterator for $array {
+            type Item = $elem;
+            type IntoIter = std::vec::IntoIter<$elem>;
+
+            #[inline(always)]
+            fn into_iter(self) -> Self::IntoIter {
+                self.0.into_iter()
+            }
+        }
+
+        impl Extend<$elem> for $array {
+            #[inline(always)]
+            fn extend<T: IntoIterator<Item = $elem>>(&mut self, iter: T) {
+                self.0.extend(iter);
+            }
+        }
+
+        impl FromIterator<$elem> for $array {
+            #[inline(always)]
+            fn from_iter<T: IntoIterator<Item = $elem>>(iter: T) -> Self {
+                Self(iter.into_iter().collect())
+            }
+        }
+    };
+}
+
+packed_array! {
+    /// A packed array of integers.
+    ///
+    /// Unlike an [`Array`][crate::Array], elements are stored directly without being wrapped in
+    /// [`Dynamic`][crate::Dynamic], saving memory and making numeric operations faster.
+    ///
+    /// Not available under `no_index`.
+    IntArray => INT
+}
+
+#[cfg(not(feature = "no_float"))]
+packed_array! {
+    /// A packed array of floating-point numbers.
+    ///
+    /// Unlike an [`Array`][crate::Array], elements are stored directly without being wrapped in
+    /// [`Dynamic`][crate::Dynamic], saving memory and making numeric operations faster.
+    ///
+    /// Not available under `no_index` or `no_float`.
+    FloatArray => FLOAT
+}
@@ -1,7 +1,7 @@
 //! Module containing error definitions for the parsing process.
 
 use crate::tokenizer::is_valid_identifier;
-use crate::{Position, RhaiError, ERR};
+use crate::{Position, RhaiError, Span, ERR};
 #[cfg(feature = "no_std")]
 use core_error::Error;
 #[cfg(not(feature = "no_std"))]
@@ -30,6 +30,10 @@ pub enum LexError {
     MalformedIdentifier(String),
     /// Bad symbol encountered when tokenizing the script text.
     ImproperSymbol(String, String),
+    /// The script text exceeds the maximum size. Wrapped value is the maximum size.
+    ScriptTooLarge(usize),
+    /// The script text has more tokens than the maximum. Wrapped value is the maximum number of tokens.
+    TooManyTokens(usize),
 }
 
 impl Error for LexError {}
@@ -52,6 +56,12 @@ impl fmt::Display for LexError {
                 write!(f, "Invalid symbol encountered: '{}'", s)
             }
             Self::ImproperSymbol(.., d) => f.write_str(d),
+            Self::ScriptTooLarge(max) => {
+                write!(f, "Script size exceeds the maximum limit ({})", max)
+            }
+            Self::TooManyTokens(max) => {
+                write!(f, "Number of tokens exceeds the maximum limit ({})", max)
+            }
         }
     }
 }
@@ -61,7 +71,7 @@ impl LexError {
     #[inline(always)]
     #[must_use]
     pub fn into_err(self, pos: Position) -> ParseError {
-        ParseError(Box::new(self.into()), pos)
+        ParseError(Box::new(self.into()), pos, Position::NONE)
     }
 }
 
@@ -142,8 +152,18 @@ pub enum ParseErrorType {
     FnDuplicatedParam(String, String),
     /// A function definition is missing the body. Wrapped value is the function name.
     FnMissingBody(String),
+    /// A function returns the value of its last expression without an explicit `return`.
+    /// Wrapped value is the function name.
+    ///
+    /// Only appears when explicit return mode is enabled.
+    FnImplicitReturn(String),
     /// Export statement not at global level.
     WrongExport,
+    /// Yield statement in an inappropriate place (e.g. outside a function, or inside a `switch`
+    /// or `try` block).
+    WrongYield,
+    /// Defining an `enum` in an inappropriate place (e.g. inside a block or a function).
+    WrongEnumDefinition,
     /// Assignment to an a constant variable. Wrapped value is the constant variable name.
     AssignmentToConstant(String),
     /// Assignment to an inappropriate LHS (left-hand-side) expression.
@@ -167,6 +187,21 @@ pub enum ParseErrorType {
     LiteralTooLarge(String, usize),
     /// Break statement not inside a loop.
     LoopBreak,
+    /// The script text exceeds the maximum size. Wrapped value is the maximum size in bytes.
+    ScriptTooLarge(usize),
+    /// The script text has more tokens than the maximum. Wrapped value is the maximum number of tokens.
+    TooManyTokens(usize),
+    /// The script defines more functions than the maximum. Wrapped value is the maximum number of
+    /// functions.
+    TooManyFunctions(usize),
+    /// A function definition has more parameters than the maximum. Wrapped values are the function
+    /// name (empty for an anonymous function) and the maximum number of parameters.
+    TooManyParameters(String, usize),
+    /// A language construct cannot be converted into the blocks format.
+    /// Wrapped value is a description of the construct.
+    ///
+    /// Only appears under the `serde` feature.
+    UnsupportedInBlocks(String),
 }
 
 impl ParseErrorType {
@@ -174,7 +209,7 @@ impl ParseErrorType {
     #[inline(always)]
     #[must_use]
     pub(crate) fn into_err(self, pos: Position) -> ParseError {
-        ParseError(self.into(), pos)
+        ParseError(self.into(), pos, Position::NONE)
     }
 }
 
@@ -216,6 +251,7 @@ impl fmt::Display for ParseErrorType {
             },
             Self::FnMissingParams(s) => write!(f, "Expecting parameters for function {}", s),
             Self::FnDuplicatedParam(s, arg) => write!(f, "Duplicated parameter {} for function {}", arg, s),
+            Self::FnImplicitReturn(s) => write!(f, "Expecting explicit 'return' in function {}", s),
 
             Self::DuplicatedProperty(s) => write!(f, "Duplicated property for object map literal: {}", s),
             #[allow(deprecated)]
@@ -256,8 +292,16 @@ impl fmt::Display for ParseErrorType {
             Self::FnMissingName => f.write_str("Expecting function name in function declaration"),
             Self::WrongDocComment => f.write_str("Doc-comment must be followed immediately by a function definition"),
             Self::WrongExport => f.write_str("Export statement can only appear at global level"),
+            Self::WrongYield => f.write_str("Yield statement can only appear in a function body, outside of switch, try and expressions"),
+            Self::WrongEnumDefinition => f.write_str("Enum definitions must be at global level and cannot be inside a block or a function"),
             Self::ExprTooDeep => f.write_str("Expression exceeds maximum complexity"),
             Self::LoopBreak => f.write_str("Break statement should only be used inside a loop"),
+            Self::ScriptTooLarge(max) => write!(f, "Script size exceeds the maximum limit ({})", max),
+            Self::TooManyTokens(max) => write!(f, "Number of tokens exceeds the maximum limit ({})", max),
+            Self::TooManyFunctions(max) => write!(f, "Number of functions exceeds the maximum limit ({})", max),
+            Self::TooManyParameters(s, max) if s.is_empty() => write!(f, "Number of parameters of anonymous function exceeds the maximum limit ({})", max),
+            Self::TooManyParameters(s, max) => write!(f, "Number of parameters of function {} exceeds the maximum limit ({})", s, max),
+            Self::UnsupportedInBlocks(s) => write!(f, "{} cannot be converted into blocks", s),
         }
     }
 }
@@ -269,11 +313,41 @@ impl From<LexError> for ParseErrorType {
             LexError::StringTooLong(max) => {
                 Self::LiteralTooLarge("Length of string literal".to_string(), max)
             }
+            LexError::ScriptTooLarge(max) => Self::ScriptTooLarge(max),
+            LexError::TooManyTokens(max) => Self::TooManyTokens(max),
             _ => Self::BadInput(err),
         }
     }
 }
 
+/// Warning encountered when parsing a script.
+///
+/// Warnings do not stop compilation. They are reported via the callback registered with
+/// [`Engine::on_parse_warning`][crate::Engine::on_parse_warning].
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+#[non_exhaustive]
+pub enum ParseWarning {
+    /// The last statement of a function body is a variable definition or an assignment, so the
+    /// function returns `()` instead of the value. Wrapped value is the function name.
+    FnValueDiscarded(String),
+    /// A case in a `switch` statement can never match, because its condition is always `false`
+    /// or an earlier case always matches, so it is removed by the optimizer.
+    UnreachableSwitchCase,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FnValueDiscarded(s) => write!(
+                f,
+                "The value of the last statement in function {} is discarded",
+                s
+            ),
+            Self::UnreachableSwitchCase => f.write_str("This switch case can never match"),
+        }
+    }
+}
+
 /// Error when parsing a script.
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub struct ParseError(
@@ -281,6 +355,9 @@ pub struct ParseError(
     pub Box<ParseErrorType>,
     /// [Position] of the parse error.
     pub Position,
+    /// Ending [position][Position] of the parse error (the last character of the offending
+    /// input), or [`Position::NONE`] if only the starting [position][Position] is known.
+    pub Position,
 );
 
 impl Error for ParseError {}
@@ -290,11 +367,24 @@ impl fmt::Display for ParseError {
         fmt::Display::fmt(&self.0, f)?;
 
         // Do not write any position if None
-        if !self.1.is_none() {
-            write!(f, " ({})", self.1)?;
+        if self.1.is_none() {
+            return Ok(());
+        }
+
+        let span = self.span();
+
+        #[cfg(not(feature = "no_position"))]
+        if span.is_multi_char() {
+            let (start, end) = (span.start(), span.end());
+
+            return if start.line() == end.line() {
+                write!(f, " ({}-{})", start, end.position().unwrap_or(0))
+            } else {
+                write!(f, " ({} to {})", start, end)
+            };
         }
 
-        Ok(())
+        write!(f, " ({})", span.start())
     }
 }
 
@@ -311,6 +401,27 @@ impl ParseError {
     pub const fn position(&self) -> Position {
         self.1
     }
+    /// Get the [span][Span] of this parse error.
+    ///
+    /// The ending [position][Position] of the [`Span`] is [`Position::NONE`] if only the
+    /// starting [position][Position] is known.
+    #[inline(always)]
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        Span::new(self.1, self.2)
+    }
+    /// Attach an ending [position][Position] to this parse error, if it does not already have one.
+    #[inline]
+    #[must_use]
+    pub(crate) fn with_span(mut self, span: Option<Span>) -> Self {
+        match span {
+            Some(span) if self.2.is_none() && span.start() == self.1 && span.is_multi_char() => {
+                self.2 = span.end();
+                self
+            }
+            _ => self,
+        }
+    }
 }
 
 impl From<ParseErrorType> for RhaiError {
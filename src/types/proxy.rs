@@ -0,0 +1,129 @@
+//! The [`Proxy`] type: a dynamic object delivering all accesses to a host handler.
+#![cfg(not(feature = "no_object"))]
+
+use crate::func::SendSync;
+use crate::{Dynamic, ImmutableString, RhaiResult, RhaiResultOf, Shared, StaticVec};
+use std::fmt;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+
+/// Trait for handling all property accesses and method calls on a [`Proxy`].
+///
+/// Each access is identified by its full path from the root [`Proxy`], e.g. `api.users.list(1)`
+/// calls [`call_method`][ProxyHandler::call_method] with the path `["users", "list"]` and the
+/// argument `1`.
+///
+/// Not available under `no_object`.
+pub trait ProxyHandler: SendSync + 'static {
+    /// Get the value of the property at a `path`.
+    ///
+    /// Return `Ok(None)` if the `path` is not a value but leads to further properties or methods,
+    /// in which case a [`Proxy`] for the `path` is returned to the script.
+    ///
+    /// The default implementation always returns `Ok(None)`.
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn get_property(&self, path: &[ImmutableString]) -> RhaiResultOf<Option<Dynamic>> {
+        Ok(None)
+    }
+    /// Call the method at a `path` with arguments.
+    fn call_method(&self, path: &[ImmutableString], args: &mut [&mut Dynamic]) -> RhaiResult;
+}
+
+/// A dynamic object whose property accesses and method calls are all delivered to a single
+/// [`ProxyHandler`], making it easy to expose REST/RPC surfaces to scripts without registering
+/// each endpoint.
+///
+/// Properties cannot be set on a [`Proxy`].
+///
+/// Not available under `no_object`.
+///
+/// # Example
+///
+/// ```
+/// use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Proxy, ProxyHandler};
+///
+/// struct Api;
+///
+/// impl ProxyHandler for Api {
+///     fn call_method(
+///         &self,
+///         path: &[ImmutableString],
+///         args: &mut [&mut Dynamic],
+///     ) -> Result<Dynamic, Box<EvalAltResult>> {
+///         let path: Vec<_> = path.iter().map(|s| s.as_str()).collect();
+///         Ok(format!("{}({})", path.join("/"), args.len()).into())
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// let engine = Engine::new();
+/// let mut scope = rhai::Scope::new();
+///
+/// scope.push("api", Proxy::new(Api));
+///
+/// assert_eq!(
+///     engine.eval_with_scope::<String>(&mut scope, "api.users.list(1, 2)")?,
+///     "users/list(2)"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Proxy {
+    /// Handler for all accesses.
+    handler: Shared<dyn ProxyHandler>,
+    /// Path of this [`Proxy`] from the root.
+    path: StaticVec<ImmutableString>,
+}
+
+impl fmt::Debug for Proxy {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy").field("path", &self.path).finish()
+    }
+}
+
+impl Proxy {
+    /// Create a new root [`Proxy`] with a [`ProxyHandler`].
+    #[inline]
+    #[must_use]
+    pub fn new(handler: impl ProxyHandler) -> Self {
+        let handler: Shared<dyn ProxyHandler> = Shared::new(handler);
+
+        Self {
+            handler,
+            path: StaticVec::new_const(),
+        }
+    }
+    /// Get the path of this [`Proxy`] from the root.
+    #[inline(always)]
+    #[must_use]
+    pub fn path(&self) -> &[ImmutableString] {
+        &self.path
+    }
+    /// Make the path to a member of this [`Proxy`].
+    #[inline]
+    fn member_path(&self, name: &str) -> StaticVec<ImmutableString> {
+        let mut path = self.path.clone();
+        path.push(name.into());
+        path
+    }
+    /// Get a property of this [`Proxy`].
+    pub(crate) fn get_property(&self, prop: &str) -> RhaiResult {
+        let path = self.member_path(prop);
+
+        match self.handler.get_property(&path)? {
+            Some(value) => Ok(value),
+            None => Ok(Dynamic::from(Self {
+                handler: self.handler.clone(),
+                path,
+            })),
+        }
+    }
+    /// Call a method of this [`Proxy`].
+    pub(crate) fn call_method(&self, name: &str, args: &mut [&mut Dynamic]) -> RhaiResult {
+        self.handler.call_method(&self.member_path(name), args)
+    }
+}
@@ -1,7 +1,7 @@
 //! Module that defines the [`Scope`] type representing a function call-stack scope.
 
 use super::dynamic::{AccessMode, Variant};
-use crate::{Dynamic, Identifier};
+use crate::{Dynamic, Identifier, Shared};
 use smallvec::SmallVec;
 #[cfg(feature = "no_std")]
 use std::prelude::v1::*;
@@ -68,10 +68,90 @@ pub struct Scope<'a> {
     names: SmallVec<[Identifier; SCOPE_ENTRIES_INLINED]>,
     /// Aliases of the entry.
     aliases: SmallVec<[Vec<Identifier>; SCOPE_ENTRIES_INLINED]>,
+    /// Frozen base layer, searched when an entry is not found.
+    base: Option<FrozenScope>,
     /// Phantom to keep the lifetime parameter in order not to break existing code.
     dummy: PhantomData<&'a ()>,
 }
 
+/// A [`Scope`] that has been [frozen][Scope::freeze] into a read-only layer, which can be cheaply
+/// shared as the base of any number of other [`Scope`]'s.
+///
+/// All variables in a [`FrozenScope`] are read-only, even those that were not constants.
+/// A script can shadow them with new variables of the same names, which are then kept in the
+/// overlaying [`Scope`], leaving the [`FrozenScope`] untouched.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), Box<rhai::EvalAltResult>> {
+/// use rhai::{Engine, Scope};
+///
+/// let engine = Engine::new();
+///
+/// let mut base = Scope::new();
+/// base.push_constant("RATE", 2_i64);
+/// base.push("limit", 100_i64);
+///
+/// let base = base.freeze();
+///
+/// for x in 1..=3_i64 {
+///     // Creating a scope on top of a frozen base is cheap
+///     let mut scope = Scope::with_base(&base);
+///     scope.push("x", x);
+///
+///     let result = engine.eval_with_scope::<i64>(&mut scope, "let limit = limit / 2; x * RATE + limit")?;
+///
+///     assert_eq!(result, x * 2 + 50);
+/// }
+///
+/// // The base is untouched
+/// assert_eq!(base.get_value::<i64>("limit").expect("limit should exist"), 100);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Hash)]
+pub struct FrozenScope(Shared<Scope<'static>>);
+
+impl FrozenScope {
+    /// Get the number of entries inside the [`FrozenScope`].
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Returns `true` if this [`FrozenScope`] contains no variables.
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Does the [`FrozenScope`] contain the entry?
+    #[inline(always)]
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains(name)
+    }
+    /// Get the value of an entry in the [`FrozenScope`], starting from the last.
+    #[inline(always)]
+    #[must_use]
+    pub fn get_value<T: Variant + Clone>(&self, name: &str) -> Option<T> {
+        self.0.get_value(name)
+    }
+    /// Get a reference to an entry in the [`FrozenScope`], starting from the last.
+    #[inline(always)]
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Dynamic> {
+        self.0.get(name)
+    }
+    /// Get an iterator to entries in the [`FrozenScope`].
+    /// All entries are read-only.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, bool, Dynamic)> {
+        self.0.iter().map(|(name, .., value)| (name, true, value))
+    }
+}
+
 impl fmt::Display for Scope<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -112,6 +192,7 @@ impl Clone for Scope<'_> {
                 .collect(),
             names: self.names.clone(),
             aliases: self.aliases.clone(),
+            base: self.base.clone(),
             dummy: self.dummy,
         }
     }
@@ -167,9 +248,95 @@ impl Scope<'_> {
             values: SmallVec::new_const(),
             names: SmallVec::new_const(),
             aliases: SmallVec::new_const(),
+            base: None,
             dummy: PhantomData,
         }
     }
+    /// Create a new [`Scope`] as an overlay on top of a [frozen][Scope::freeze] base layer.
+    ///
+    /// Entries in the base layer are visible in the new [`Scope`] but are read-only.
+    /// New entries (including those shadowing entries in the base layer) are added to the new
+    /// [`Scope`] only.
+    ///
+    /// Entries in the base layer are not counted by [`len`][Scope::len] nor iterated by
+    /// [`iter`][Scope::iter].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Scope;
+    ///
+    /// let mut base = Scope::new();
+    /// base.push("x", 42_i64);
+    /// let base = base.freeze();
+    ///
+    /// let mut my_scope = Scope::with_base(&base);
+    /// assert_eq!(my_scope.get_value::<i64>("x").expect("x should exist"), 42);
+    /// assert_eq!(my_scope.is_constant("x"), Some(true));
+    /// assert_eq!(my_scope.len(), 0);
+    ///
+    /// my_scope.set_value("x", 0_i64);
+    /// assert_eq!(my_scope.get_value::<i64>("x").expect("x should exist"), 0);
+    /// assert_eq!(my_scope.len(), 1);
+    ///
+    /// assert_eq!(base.get_value::<i64>("x").expect("x should exist"), 42);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_base(base: &FrozenScope) -> Self {
+        let mut scope = Self::new();
+        scope.base = Some(base.clone());
+        scope
+    }
+    /// Get the [frozen][Scope::freeze] base layer of this [`Scope`], if any.
+    #[inline(always)]
+    #[must_use]
+    pub const fn base(&self) -> Option<&FrozenScope> {
+        self.base.as_ref()
+    }
+    /// Freeze this [`Scope`] into a read-only [`FrozenScope`], which can be cheaply shared as the
+    /// base layer of other [`Scope`]'s via [`Scope::with_base`].
+    ///
+    /// If this [`Scope`] already has a base layer, its entries are copied into the new
+    /// [`FrozenScope`].
+    #[must_use]
+    pub fn freeze(self) -> FrozenScope {
+        let mut scope = match self.base {
+            Some(base) => (*base.0).clone(),
+            None => Scope::new(),
+        };
+
+        // Turn the values into read-only shared values, so reading them never deep-clones
+        scope.values.extend(self.values.into_iter().map(|value| {
+            let mut value = value.flatten();
+            value.set_access_mode(AccessMode::ReadOnly);
+            #[cfg(not(feature = "no_closure"))]
+            let mut value = value.into_shared();
+            #[cfg(not(feature = "no_closure"))]
+            value.set_access_mode(AccessMode::ReadOnly);
+            value
+        }));
+        scope.names.extend(self.names);
+        scope.aliases.extend(self.aliases);
+
+        FrozenScope(scope.into())
+    }
+    /// Find an entry in the [frozen][Scope::freeze] base layer of this [`Scope`], if any.
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_frozen(&self, name: &str) -> Option<&Dynamic> {
+        self.base.as_ref().and_then(|base| base.get(name))
+    }
+    /// Is the entry only found in the [frozen][Scope::freeze] base layer of this [`Scope`]?
+    ///
+    /// Such entries can never be written to, not even when permitted by the
+    /// [`on_invalid_assignment`][crate::Engine::on_invalid_assignment] callback, because the base
+    /// layer is shared by all overlaying [`Scope`]'s.
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_frozen(&self, name: &str) -> bool {
+        self.get_frozen(name).is_some() && self.get_index(name).is_none()
+    }
     /// Empty the [`Scope`].
     ///
     /// # Example
@@ -374,6 +541,7 @@ impl Scope<'_> {
     #[must_use]
     pub fn contains(&self, name: &str) -> bool {
         self.names.iter().any(|key| name == key)
+            || self.base.as_ref().map_or(false, |base| base.contains(name))
     }
     /// Find an entry in the [`Scope`], starting from the last.
     #[inline]
@@ -416,7 +584,9 @@ impl Scope<'_> {
             .rev()
             .enumerate()
             .find(|(.., key)| &name == key)
-            .and_then(|(index, ..)| self.values[len - 1 - index].flatten_clone().try_cast())
+            .map(|(index, ..)| &self.values[len - 1 - index])
+            .or_else(|| self.get_frozen(name))
+            .and_then(|value| value.flatten_clone().try_cast())
     }
     /// Check if the named entry in the [`Scope`] is constant.
     ///
@@ -438,10 +608,12 @@ impl Scope<'_> {
     #[inline]
     #[must_use]
     pub fn is_constant(&self, name: &str) -> Option<bool> {
-        self.get_index(name).map(|(.., access)| match access {
-            AccessMode::ReadWrite => false,
-            AccessMode::ReadOnly => true,
-        })
+        self.get_index(name)
+            .map(|(.., access)| match access {
+                AccessMode::ReadWrite => false,
+                AccessMode::ReadOnly => true,
+            })
+            .or_else(|| self.get_frozen(name).map(|_| true))
     }
     /// Update the value of the named entry in the [`Scope`] if it already exists and is not constant.
     /// Push a new entry with the value into the [`Scope`] if the name doesn't exist or if the
@@ -547,7 +719,9 @@ impl Scope<'_> {
     #[inline(always)]
     #[must_use]
     pub fn get(&self, name: &str) -> Option<&Dynamic> {
-        self.get_index(name).map(|(index, _)| &self.values[index])
+        self.get_index(name)
+            .map(|(index, _)| &self.values[index])
+            .or_else(|| self.get_frozen(name))
     }
     /// Remove the last entry in the [`Scope`] by the specified name and return its value.
     ///
@@ -671,6 +845,7 @@ impl Scope<'_> {
     pub fn clone_visible(&self) -> Self {
         let len = self.len();
         let mut scope = Self::new();
+        scope.base = self.base.clone();
 
         self.names.iter().rev().enumerate().for_each(|(i, name)| {
             if scope.names.contains(name) {
@@ -737,6 +912,12 @@ impl Scope<'_> {
             .zip(self.values.iter())
             .map(|(name, value)| (name.as_str(), value.is_read_only(), value))
     }
+    /// Get an iterator to the export aliases of entries in the [`Scope`].
+    #[inline]
+    #[allow(dead_code)]
+    pub(crate) fn iter_aliases(&self) -> impl Iterator<Item = &[Identifier]> {
+        self.aliases.iter().map(Vec::as_slice)
+    }
     /// Get a reverse iterator to entries in the [`Scope`].
     /// Shared values are not expanded.
     #[inline]
@@ -804,3 +985,137 @@ impl<K: Into<Identifier>> FromIterator<(K, bool, Dynamic)> for Scope<'_> {
         scope
     }
 }
+
+/// Metadata of the variables in a [`Scope`] that is not preserved when converting it into an
+/// object map via [`Scope::to_map_with_metadata`].
+///
+/// It is kept in a sidecar, separate from the object map, so that the object map itself contains
+/// only the variables' values (and can be easily serialized or sent to a script).
+///
+/// Not available under `no_object`.
+#[cfg(not(feature = "no_object"))]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScopeMetadata {
+    /// Names of variables that are constants.
+    pub constants: std::collections::BTreeSet<Identifier>,
+    /// Export aliases of variables, keyed by variable name.
+    pub aliases: std::collections::BTreeMap<Identifier, Vec<Identifier>>,
+}
+
+#[cfg(not(feature = "no_object"))]
+impl Scope<'_> {
+    /// Create a new [`Scope`] from an [object map][crate::Map], with each property becoming a
+    /// variable.
+    ///
+    /// All variables are created as non-constant.
+    ///
+    /// Not available under `no_object`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::{Dynamic, Map, Scope};
+    ///
+    /// let mut map = Map::new();
+    /// map.insert("x".into(), Dynamic::from(42_i64));
+    ///
+    /// let my_scope = Scope::from_map(map);
+    /// assert_eq!(my_scope.get_value::<i64>("x").expect("x should exist"), 42);
+    /// assert_eq!(my_scope.is_constant("x"), Some(false));
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub fn from_map(map: crate::Map) -> Self {
+        Self::from_map_with_metadata(map, &ScopeMetadata::default())
+    }
+    /// Create a new [`Scope`] from an [object map][crate::Map], restoring constants and aliases
+    /// from a [`ScopeMetadata`] sidecar.
+    ///
+    /// Not available under `no_object`.
+    #[must_use]
+    pub fn from_map_with_metadata(map: crate::Map, metadata: &ScopeMetadata) -> Self {
+        let mut scope = Self::new();
+
+        for (name, value) in map {
+            let access = if metadata.constants.contains(&name) {
+                AccessMode::ReadOnly
+            } else {
+                AccessMode::ReadWrite
+            };
+            let aliases = metadata.aliases.get(&name).cloned().unwrap_or_default();
+
+            scope.push_entry(name, access, value);
+            *scope.aliases.last_mut().unwrap() = aliases;
+        }
+
+        scope
+    }
+    /// Convert the [`Scope`] into an [object map][crate::Map], with each variable becoming a
+    /// property.
+    ///
+    /// Shadowed variables are omitted (i.e. only the last instance of each variable name is kept).
+    /// Shared values are flatten-cloned.
+    ///
+    /// Not available under `no_object`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rhai::Scope;
+    ///
+    /// let mut my_scope = Scope::new();
+    ///
+    /// my_scope.push("x", 1_i64);
+    /// my_scope.push("x", 42_i64);
+    /// my_scope.push_constant("y", true);
+    ///
+    /// let map = my_scope.to_map();
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map["x"].as_int().unwrap(), 42);
+    /// assert!(map["y"].as_bool().unwrap());
+    /// ```
+    #[inline(always)]
+    #[must_use]
+    pub fn to_map(&self) -> crate::Map {
+        self.to_map_with_metadata().0
+    }
+    /// Convert the [`Scope`] into an [object map][crate::Map], together with a
+    /// [`ScopeMetadata`] sidecar recording which variables are constants and their aliases.
+    ///
+    /// Shadowed variables are omitted (i.e. only the last instance of each variable name is kept).
+    /// Shared values are flatten-cloned.
+    ///
+    /// Not available under `no_object`.
+    #[must_use]
+    pub fn to_map_with_metadata(&self) -> (crate::Map, ScopeMetadata) {
+        let mut map = crate::Map::new();
+        let mut metadata = ScopeMetadata::default();
+
+        for ((name, value), aliases) in self
+            .names
+            .iter()
+            .zip(self.values.iter())
+            .zip(self.aliases.iter())
+        {
+            // Later variables shadow earlier ones of the same name
+            if value.is_read_only() {
+                metadata.constants.insert(name.clone());
+            } else {
+                metadata.constants.remove(name);
+            }
+            if aliases.is_empty() {
+                metadata.aliases.remove(name);
+            } else {
+                metadata.aliases.insert(name.clone(), aliases.clone());
+            }
+
+            let mut value = value.flatten_clone();
+            value.set_access_mode(AccessMode::ReadWrite);
+            map.insert(name.clone(), value);
+        }
+
+        (map, metadata)
+    }
+}
@@ -0,0 +1,227 @@
+//! The [`Set`] type: a collection of unique [`Dynamic`] values.
+#![cfg(not(feature = "no_index"))]
+
+use crate::func::get_hasher;
+use crate::Dynamic;
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    collections::{btree_map, BTreeMap},
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+};
+
+/// A collection of unique [`Dynamic`] values.
+///
+/// Values are keyed by their hashes, so only hashable values (e.g. numbers, strings, characters,
+/// arrays and object maps) can be stored.  Values with the same hash are kept apart by comparing
+/// them with each other.
+///
+/// The order of values in a [`Set`] is unspecified.
+///
+/// Not available under `no_index`.
+#[derive(Clone, Default)]
+pub struct Set {
+    /// Values, grouped into buckets by their hashes.
+    buckets: BTreeMap<u64, Vec<Dynamic>>,
+    /// Number of values.
+    len: usize,
+}
+
+impl fmt::Debug for Set {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl PartialEq for Set {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.is_subset(other)
+    }
+}
+
+/// Are two hashable values the same?
+#[inline(always)]
+fn is_same(a: &Dynamic, b: &Dynamic) -> bool {
+    a.structural_eq(b).unwrap_or(false)
+}
+
+impl Set {
+    /// Create a new empty [`Set`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            buckets: BTreeMap::new(),
+            len: 0,
+        }
+    }
+    /// Calculate the hash key of a value, or [`None`] if the value is not hashable.
+    ///
+    /// Shared values are hashed by their underlying values.
+    #[must_use]
+    pub fn hash_of(value: &Dynamic) -> Option<u64> {
+        #[cfg(not(feature = "no_closure"))]
+        if value.is_shared() {
+            return Self::hash_of(&value.flatten_clone());
+        }
+        if !value.is_hashable() {
+            return None;
+        }
+        let hasher = &mut get_hasher();
+        value.hash(hasher);
+        Some(hasher.finish())
+    }
+    /// Find the bucket holding a value, if any, together with the index of the value in it.
+    #[must_use]
+    fn find(&self, value: &Dynamic) -> Option<(u64, Option<usize>)> {
+        let hash = Self::hash_of(value)?;
+        let index = self
+            .buckets
+            .get(&hash)
+            .and_then(|bucket| bucket.iter().position(|v| is_same(v, value)));
+        Some((hash, index))
+    }
+    /// Number of values in this [`Set`].
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Is this [`Set`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Remove all values from this [`Set`].
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+        self.len = 0;
+    }
+    /// Add a value into this [`Set`].
+    ///
+    /// Returns `Ok(true)` if the value is added, `Ok(false)` if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value back if it is not hashable.
+    pub fn insert(&mut self, value: Dynamic) -> Result<bool, Dynamic> {
+        let value = value.flatten();
+
+        match self.find(&value) {
+            Some((.., Some(..))) => Ok(false),
+            Some((hash, None)) => {
+                self.buckets.entry(hash).or_default().push(value);
+                self.len += 1;
+                Ok(true)
+            }
+            None => Err(value),
+        }
+    }
+    /// Remove a value from this [`Set`].
+    ///
+    /// Returns `true` if the value was in the [`Set`].
+    pub fn remove(&mut self, value: &Dynamic) -> bool {
+        let (hash, index) = match self.find(value) {
+            Some((hash, Some(index))) => (hash, index),
+            _ => return false,
+        };
+
+        if let btree_map::Entry::Occupied(mut entry) = self.buckets.entry(hash) {
+            entry.get_mut().swap_remove(index);
+
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+        }
+        self.len -= 1;
+        true
+    }
+    /// Does this [`Set`] contain a value?
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, value: &Dynamic) -> bool {
+        matches!(self.find(value), Some((.., Some(..))))
+    }
+    /// Get an iterator over the values in this [`Set`].
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = &Dynamic> {
+        self.buckets.values().flatten()
+    }
+    /// Get a mutable iterator over the values in this [`Set`].
+    ///
+    /// Values should not be changed in a way that changes their hashes.
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Dynamic> {
+        self.buckets.values_mut().flatten()
+    }
+    /// Create a new [`Set`] containing all values in either this [`Set`] or `other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let mut set = self.clone();
+        set.extend(other.iter().cloned());
+        set
+    }
+    /// Create a new [`Set`] containing all values in both this [`Set`] and `other`.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|value| other.contains(value))
+            .cloned()
+            .collect()
+    }
+    /// Create a new [`Set`] containing all values in this [`Set`] but not in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.iter()
+            .filter(|value| !other.contains(value))
+            .cloned()
+            .collect()
+    }
+    /// Is every value in this [`Set`] also in `other`?
+    #[inline]
+    #[must_use]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.iter().all(|value| other.contains(value))
+    }
+}
+
+impl IntoIterator for Set {
+    type Item = Dynamic;
+    type IntoIter = std::iter::Flatten<btree_map::IntoValues<u64, Vec<Dynamic>>>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.buckets.into_values().flatten()
+    }
+}
+
+impl Extend<Dynamic> for Set {
+    /// Add values into this [`Set`].
+    ///
+    /// Values that are not hashable are skipped.
+    #[inline]
+    fn extend<T: IntoIterator<Item = Dynamic>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|value| {
+            let _ = self.insert(value);
+        });
+    }
+}
+
+impl FromIterator<Dynamic> for Set {
+    /// Create a [`Set`] from values.
+    ///
+    /// Values that are not hashable are skipped.
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = Dynamic>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
@@ -0,0 +1,360 @@
+//! The [`SortedMap`] type: a dictionary of [`Dynamic`] values sorted by keys of any orderable type.
+#![cfg(not(feature = "no_index"))]
+
+use crate::{Dynamic, ExclusiveRange, ImmutableString, InclusiveRange, INT};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    cmp::Ordering,
+    collections::{btree_map, BTreeMap},
+    fmt,
+    iter::FromIterator,
+    ops::Bound,
+};
+
+/// A key in a [`SortedMap`].
+///
+/// Keys are always valid (see [`SortedMap::is_valid_key`]) and never shared.
+#[derive(Debug, Clone)]
+struct SortKey(Dynamic);
+
+impl SortKey {
+    /// Rank of the type of a key.  Keys of different types are ordered by their ranks.
+    ///
+    /// Returns [`None`] if the key is not orderable.
+    #[must_use]
+    fn rank(key: &Dynamic) -> Option<u8> {
+        if key.is::<bool>() {
+            return Some(1);
+        }
+        if key.is::<INT>() {
+            return Some(2);
+        }
+        #[cfg(not(feature = "no_float"))]
+        if let Ok(f) = key.as_float() {
+            return if f.is_nan() { None } else { Some(2) };
+        }
+        #[cfg(feature = "decimal")]
+        if key.is::<rust_decimal::Decimal>() {
+            return Some(3);
+        }
+        if key.is::<char>() {
+            return Some(4);
+        }
+        if key.is::<ImmutableString>() {
+            return Some(5);
+        }
+        #[cfg(not(feature = "no_std"))]
+        if key.is::<crate::Instant>() {
+            return Some(6);
+        }
+        if key.is::<ExclusiveRange>() {
+            return Some(7);
+        }
+        if key.is::<InclusiveRange>() {
+            return Some(8);
+        }
+        None
+    }
+}
+
+impl Ord for SortKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (&self.0, &other.0);
+        let rank = Self::rank(a).expect("valid key");
+
+        match rank.cmp(&Self::rank(b).expect("valid key")) {
+            Ordering::Equal => (),
+            ordering => return ordering,
+        }
+
+        match rank {
+            1 => a
+                .as_bool()
+                .expect("`bool`")
+                .cmp(&b.as_bool().expect("`bool`")),
+            #[cfg(not(feature = "no_float"))]
+            2 if !a.is::<INT>() || !b.is::<INT>() => {
+                let to_float = |v: &Dynamic| {
+                    v.as_float()
+                        .or_else(|_| v.as_int().map(|n| n as crate::FLOAT))
+                        .expect("number")
+                };
+                to_float(a).partial_cmp(&to_float(b)).expect("not NaN")
+            }
+            2 => a.as_int().expect("`INT`").cmp(&b.as_int().expect("`INT`")),
+            #[cfg(feature = "decimal")]
+            3 => {
+                let a = a.as_decimal().expect("`Decimal`");
+                a.cmp(&b.as_decimal().expect("`Decimal`"))
+            }
+            4 => a
+                .as_char()
+                .expect("`char`")
+                .cmp(&b.as_char().expect("`char`")),
+            5 => {
+                let a = a
+                    .downcast_ref::<ImmutableString>()
+                    .expect("`ImmutableString`");
+                a.cmp(b.downcast_ref().expect("`ImmutableString`"))
+            }
+            #[cfg(not(feature = "no_std"))]
+            6 => {
+                let a = a.downcast_ref::<crate::Instant>().expect("`Instant`");
+                a.cmp(b.downcast_ref().expect("`Instant`"))
+            }
+            7 => {
+                let a = a
+                    .downcast_ref::<ExclusiveRange>()
+                    .expect("`ExclusiveRange`");
+                let b = b
+                    .downcast_ref::<ExclusiveRange>()
+                    .expect("`ExclusiveRange`");
+                (a.start, a.end).cmp(&(b.start, b.end))
+            }
+            8 => {
+                let a = a
+                    .downcast_ref::<InclusiveRange>()
+                    .expect("`InclusiveRange`");
+                let b = b
+                    .downcast_ref::<InclusiveRange>()
+                    .expect("`InclusiveRange`");
+                (a.start(), a.end()).cmp(&(b.start(), b.end()))
+            }
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+impl PartialOrd for SortKey {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortKey {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortKey {}
+
+/// A dictionary of [`Dynamic`] values, sorted by keys which can be of any orderable type.
+///
+/// Keys can be booleans, integers, floating-point numbers (except NaN), decimals,
+/// characters, strings, timestamps and ranges.  Keys of the same type are ordered by value
+/// (integers and floating-point numbers are compared with each other by value), while keys of
+/// different types are ordered by type in the order listed above.
+///
+/// Unlike [`Map`][crate::Map], whose keys are always strings, a [`SortedMap`] can be keyed by
+/// timestamps or numbers and queried for ranges of keys, which is useful for scheduling events.
+///
+/// Not available under `no_index`.
+#[derive(Clone, Default)]
+pub struct SortedMap(BTreeMap<SortKey, Dynamic>);
+
+impl fmt::Debug for SortedMap {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl SortedMap {
+    /// Create a new empty [`SortedMap`].
+    #[inline(always)]
+    #[must_use]
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+    /// Can a value be used as a key in a [`SortedMap`]?
+    #[inline]
+    #[must_use]
+    pub fn is_valid_key(key: &Dynamic) -> bool {
+        #[cfg(not(feature = "no_closure"))]
+        if key.is_shared() {
+            return Self::is_valid_key(&key.flatten_clone());
+        }
+        SortKey::rank(key).is_some()
+    }
+    /// Turn a value into a key for lookup, or [`None`] if it is not a valid key.
+    #[must_use]
+    fn make_key(key: &Dynamic) -> Option<SortKey> {
+        let key = key.flatten_clone();
+        SortKey::rank(&key).map(|_| SortKey(key))
+    }
+    /// Number of entries in this [`SortedMap`].
+    #[inline(always)]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Is this [`SortedMap`] empty?
+    #[inline(always)]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Remove all entries from this [`SortedMap`].
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+    /// Does this [`SortedMap`] contain a key?
+    #[inline]
+    #[must_use]
+    pub fn contains_key(&self, key: &Dynamic) -> bool {
+        Self::make_key(key).map_or(false, |key| self.0.contains_key(&key))
+    }
+    /// Get a reference to the value of a key.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, key: &Dynamic) -> Option<&Dynamic> {
+        Self::make_key(key).and_then(|key| self.0.get(&key))
+    }
+    /// Get a mutable reference to the value of a key.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self, key: &Dynamic) -> Option<&mut Dynamic> {
+        Self::make_key(key).and_then(move |key| self.0.get_mut(&key))
+    }
+    /// Insert an entry into this [`SortedMap`], returning the previous value of the key, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns the key back if it is not a valid key.
+    pub fn insert(&mut self, key: Dynamic, value: Dynamic) -> Result<Option<Dynamic>, Dynamic> {
+        let key = key.flatten();
+
+        if SortKey::rank(&key).is_none() {
+            return Err(key);
+        }
+
+        Ok(self.0.insert(SortKey(key), value))
+    }
+    /// Remove a key from this [`SortedMap`], returning its value, if any.
+    #[inline]
+    pub fn remove(&mut self, key: &Dynamic) -> Option<Dynamic> {
+        Self::make_key(key).and_then(|key| self.0.remove(&key))
+    }
+    /// Get the entry with the smallest key.
+    #[inline]
+    #[must_use]
+    pub fn first(&self) -> Option<(&Dynamic, &Dynamic)> {
+        self.0.iter().next().map(|(k, v)| (&k.0, v))
+    }
+    /// Get the entry with the largest key.
+    #[inline]
+    #[must_use]
+    pub fn last(&self) -> Option<(&Dynamic, &Dynamic)> {
+        self.0.iter().next_back().map(|(k, v)| (&k.0, v))
+    }
+    /// Get an iterator over the entries with keys between `start` (inclusive) and `end`
+    /// (exclusive), in order of their keys.
+    ///
+    /// Invalid bounds are treated as unbounded.
+    pub fn range(
+        &self,
+        start: &Dynamic,
+        end: &Dynamic,
+    ) -> impl DoubleEndedIterator<Item = (&Dynamic, &Dynamic)> {
+        let start = Self::make_key(start).map_or(Bound::Unbounded, Bound::Included);
+        let end = Self::make_key(end).map_or(Bound::Unbounded, Bound::Excluded);
+
+        let range = match (&start, &end) {
+            (Bound::Included(s), Bound::Excluded(e)) if s >= e => None,
+            _ => Some(self.0.range::<SortKey, _>((start, end))),
+        };
+
+        range.into_iter().flatten().map(|(k, v)| (&k.0, v))
+    }
+    /// Get an iterator over the keys in this [`SortedMap`], in order.
+    #[inline]
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &Dynamic> {
+        self.0.keys().map(|k| &k.0)
+    }
+    /// Get an iterator over the values in this [`SortedMap`], in order of their keys.
+    #[inline(always)]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Dynamic> {
+        self.0.values()
+    }
+    /// Get a mutable iterator over the values in this [`SortedMap`], in order of their keys.
+    #[inline(always)]
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Dynamic> {
+        self.0.values_mut()
+    }
+    /// Get an iterator over the entries in this [`SortedMap`], in order of their keys.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&Dynamic, &Dynamic)> {
+        self.0.iter().map(|(k, v)| (&k.0, v))
+    }
+    /// Get an iterator over the entries in this [`SortedMap`], with mutable values.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (&Dynamic, &mut Dynamic)> {
+        self.0.iter_mut().map(|(k, v)| (&k.0, v))
+    }
+}
+
+/// An owning iterator over the entries of a [`SortedMap`], in order of their keys.
+#[derive(Debug)]
+pub struct IntoIter(btree_map::IntoIter<SortKey, Dynamic>);
+
+impl Iterator for IntoIter {
+    type Item = (Dynamic, Dynamic);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (k.0, v))
+    }
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IntoIter {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(k, v)| (k.0, v))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for SortedMap {
+    type Item = (Dynamic, Dynamic);
+    type IntoIter = IntoIter;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+impl Extend<(Dynamic, Dynamic)> for SortedMap {
+    /// Insert entries into this [`SortedMap`].
+    ///
+    /// Entries with invalid keys are skipped.
+    #[inline]
+    fn extend<T: IntoIterator<Item = (Dynamic, Dynamic)>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|(key, value)| {
+            let _ = self.insert(key, value);
+        });
+    }
+}
+
+impl FromIterator<(Dynamic, Dynamic)> for SortedMap {
+    /// Create a [`SortedMap`] from entries.
+    ///
+    /// Entries with invalid keys are skipped.
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (Dynamic, Dynamic)>>(iter: T) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
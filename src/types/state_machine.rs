@@ -0,0 +1,215 @@
+//! The [`StateMachine`] type: a finite state machine declared by a script.
+#![cfg(not(feature = "no_custom_syntax"))]
+
+use crate::{Identifier, Shared, StaticVec};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{collections::BTreeMap, fmt};
+
+/// Definition of a [`StateMachine`], shared by all its instances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StateMachineDef {
+    /// Name of the state machine.
+    name: Identifier,
+    /// Names of all the states, the first being the initial state.
+    states: StaticVec<Identifier>,
+    /// Transitions out of each state, mapping each event to the index of the target state.
+    transitions: StaticVec<BTreeMap<Identifier, usize>>,
+}
+
+/// A finite state machine, usually declared by a script via the custom syntax registered by the
+/// [`StateMachinePackage`][crate::packages::StateMachinePackage]:
+///
+/// ```rhai
+/// machine Door {
+///     state Open { on close => Closed }
+///     state Closed { on open => Open; on lock => Locked }
+///     state Locked { on unlock => Closed }
+/// }
+/// ```
+///
+/// The definition of a [`StateMachine`] is shared, so cloning it is cheap.  Each clone keeps
+/// track of its own current state.
+///
+/// Not available under `no_custom_syntax`.
+///
+/// # Example
+///
+/// ```
+/// use rhai::StateMachine;
+///
+/// let mut door = StateMachine::new(
+///     "Door",
+///     [
+///         ("Open", vec![("close", "Closed")]),
+///         ("Closed", vec![("open", "Open")]),
+///     ],
+/// )
+/// .expect("should succeed");
+///
+/// assert_eq!(door.state(), "Open");
+/// assert!(!door.fire("open"));
+/// assert!(door.fire("close"));
+/// assert_eq!(door.state(), "Closed");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct StateMachine {
+    /// Shared definition of the state machine.
+    def: Shared<StateMachineDef>,
+    /// Index of the current state.
+    state: usize,
+}
+
+impl fmt::Debug for StateMachine {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.name(), self.state())
+    }
+}
+
+impl StateMachine {
+    /// Create a new [`StateMachine`] from a list of states, each with a list of transitions
+    /// mapping an event to a target state.
+    ///
+    /// The first state is the initial state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if there are no states, if a state is defined more than once,
+    /// if an event has more than one transition out of the same state, or if a transition
+    /// targets a state that is not defined.
+    pub fn new<S, L, E, T>(
+        name: impl Into<Identifier>,
+        states: impl IntoIterator<Item = (S, L)>,
+    ) -> Result<Self, String>
+    where
+        S: AsRef<str>,
+        L: IntoIterator<Item = (E, T)>,
+        E: AsRef<str>,
+        T: AsRef<str>,
+    {
+        let name = name.into();
+        let mut names = StaticVec::<Identifier>::new();
+        let mut edges = StaticVec::<StaticVec<(Identifier, Identifier)>>::new();
+
+        for (state, transitions) in states {
+            let state = state.as_ref();
+
+            if names.iter().any(|s| s == state) {
+                return Err(format!(
+                    "State '{state}' is defined more than once in machine '{name}'"
+                ));
+            }
+
+            names.push(state.into());
+            edges.push(
+                transitions
+                    .into_iter()
+                    .map(|(event, target)| (event.as_ref().into(), target.as_ref().into()))
+                    .collect(),
+            );
+        }
+
+        if names.is_empty() {
+            return Err(format!("Machine '{name}' has no states"));
+        }
+
+        let mut transitions = StaticVec::new_const();
+
+        for (state, edges) in names.iter().zip(edges) {
+            let mut map = BTreeMap::new();
+
+            for (event, target) in edges {
+                let index = names
+                    .iter()
+                    .position(|s| *s == target)
+                    .ok_or_else(|| format!("Undefined state '{target}' in machine '{name}'"))?;
+
+                if map.insert(event.clone(), index).is_some() {
+                    return Err(format!(
+                        "Event '{event}' has more than one transition out of state '{state}' in machine '{name}'"
+                    ));
+                }
+            }
+
+            transitions.push(map);
+        }
+
+        Ok(Self {
+            def: StateMachineDef {
+                name,
+                states: names,
+                transitions,
+            }
+            .into(),
+            state: 0,
+        })
+    }
+    /// Get the name of the state machine.
+    #[inline(always)]
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.def.name
+    }
+    /// Get the name of the current state.
+    #[inline(always)]
+    #[must_use]
+    pub fn state(&self) -> &str {
+        &self.def.states[self.state]
+    }
+    /// Get the name of the initial state.
+    #[inline(always)]
+    #[must_use]
+    pub fn initial_state(&self) -> &str {
+        &self.def.states[0]
+    }
+    /// Get an iterator over the names of all the states, starting with the initial state.
+    #[inline]
+    pub fn states(&self) -> impl Iterator<Item = &str> {
+        self.def.states.iter().map(Identifier::as_str)
+    }
+    /// Get an iterator over the events that have transitions out of the current state.
+    #[inline]
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.def.transitions[self.state]
+            .keys()
+            .map(Identifier::as_str)
+    }
+    /// Get the name of the state that an event transitions to from the current state, if any.
+    #[inline]
+    #[must_use]
+    pub fn target(&self, event: &str) -> Option<&str> {
+        self.target_index(event)
+            .map(|index| &*self.def.states[index])
+    }
+    /// Get the index of the state that an event transitions to from the current state, if any.
+    #[inline(always)]
+    #[must_use]
+    pub(crate) fn target_index(&self, event: &str) -> Option<usize> {
+        self.def.transitions[self.state].get(event).copied()
+    }
+    /// Move to the state at an index.
+    #[inline(always)]
+    pub(crate) fn set_state_index(&mut self, index: usize) {
+        self.state = index;
+    }
+    /// Take the transition for an event out of the current state.
+    ///
+    /// Returns `false` if the current state has no transition for the event.
+    #[inline]
+    pub fn fire(&mut self, event: &str) -> bool {
+        match self.target_index(event) {
+            Some(index) => {
+                self.state = index;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Return to the initial state.
+    #[inline(always)]
+    pub fn reset(&mut self) {
+        self.state = 0;
+    }
+}
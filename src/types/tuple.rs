@@ -0,0 +1,107 @@
+//! The [`Tuple`] type: a fixed-size list of [`Dynamic`] values.
+
+use crate::{Dynamic, StaticVec};
+#[cfg(feature = "no_std")]
+use std::prelude::v1::*;
+use std::{
+    fmt,
+    iter::FromIterator,
+    ops::{Deref, DerefMut},
+};
+
+/// A fixed-size list of [`Dynamic`] values, created in scripts via `(` _expr_ `,` ... `)` and
+/// destructured via `let (` _var_ `,` ... `) =` _expr_.
+///
+/// Unlike an [`Array`][crate::Array], the number of items in a [`Tuple`] never changes, which makes
+/// it a natural way to return multiple values from a function.
+///
+/// A [`Tuple`] is held directly in a [`Dynamic`] (boxed), with up to three items stored inline,
+/// so creating a small [`Tuple`] takes a single allocation instead of the two needed for an
+/// [`Array`][crate::Array] of the same size.
+#[derive(Clone, Default, Hash)]
+pub struct Tuple(StaticVec<Dynamic>);
+
+impl fmt::Debug for Tuple {
+    #[cold]
+    #[inline(never)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        self.0.iter().enumerate().try_for_each(|(i, v)| {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            fmt::Debug::fmt(v, f)
+        })?;
+        if self.0.len() == 1 {
+            f.write_str(",")?;
+        }
+        f.write_str(")")
+    }
+}
+
+impl Tuple {
+    /// Create a new [`Tuple`] from a list of values.
+    #[inline(always)]
+    #[must_use]
+    pub fn new(items: impl IntoIterator<Item = Dynamic>) -> Self {
+        Self(items.into_iter().collect())
+    }
+    /// Convert this [`Tuple`] into a [`Vec`] of its items.
+    #[inline(always)]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<Dynamic> {
+        self.0.into_vec()
+    }
+    /// Convert this [`Tuple`] into an [`Array`][crate::Array].
+    ///
+    /// Not available under `no_index`.
+    #[cfg(not(feature = "no_index"))]
+    #[inline(always)]
+    #[must_use]
+    pub fn into_array(self) -> crate::Array {
+        self.into_vec()
+    }
+}
+
+impl Deref for Tuple {
+    type Target = [Dynamic];
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Tuple {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl FromIterator<Dynamic> for Tuple {
+    #[inline(always)]
+    fn from_iter<T: IntoIterator<Item = Dynamic>>(iter: T) -> Self {
+        Self::new(iter)
+    }
+}
+
+impl IntoIterator for Tuple {
+    type Item = Dynamic;
+    type IntoIter = smallvec::IntoIter<[Dynamic; 3]>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Tuple {
+    type Item = &'a Dynamic;
+    type IntoIter = std::slice::Iter<'a, Dynamic>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, INT};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// An allocator counting the allocations made on each thread.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Count the allocations made by evaluating an [`AST`][rhai::AST] many times.
+fn count_allocations(engine: &Engine, ast: &rhai::AST) -> Result<usize, Box<EvalAltResult>> {
+    // Warm up
+    engine.eval_ast::<INT>(ast)?;
+
+    let start = ALLOCATIONS.with(Cell::get);
+
+    for _ in 0..100 {
+        assert_eq!(engine.eval_ast::<INT>(ast)?, 970);
+    }
+
+    Ok(ALLOCATIONS.with(Cell::get) - start)
+}
+
+#[test]
+fn test_arena_allocations() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile(
+        r#"
+            fn sum(a, b, c, d, e, f, g) { a + b + c + d + e + f + g }
+
+            let total = 0;
+
+            for n in 0..20 {
+                let s = `the quick brown fox ${n} jumps over the lazy dog ${sum(n, 1, 2, 3, 4, 5, 6)}`;
+                total += s.len;
+            }
+
+            total
+        "#,
+    )?;
+
+    let without_arena = count_allocations(&engine, &ast)?;
+
+    engine.with_arena(true);
+
+    let with_arena = count_allocations(&engine, &ast)?;
+
+    assert!(
+        with_arena < without_arena,
+        "{} allocations with an arena, {} without",
+        with_arena,
+        without_arena
+    );
+
+    Ok(())
+}
@@ -435,3 +435,127 @@ fn test_arrays_elvis() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_arrays_windows_chunks_zip() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let w = [1, 2, 3, 4, 5].windows(3);
+                w.len() * 100 + w[1][0] * 10 + w[2][2]
+            "
+        )?,
+        325
+    );
+    assert_eq!(engine.eval::<INT>("[1, 2].windows(3).len()")?, 0);
+    assert_eq!(engine.eval::<INT>("[1, 2].windows(0).len()")?, 0);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let c = [1, 2, 3, 4, 5].chunks(2);
+                c.len() * 10 + c[2].len()
+            "
+        )?,
+        31
+    );
+
+    assert_eq!(
+        engine
+            .eval::<Dynamic>("[1, 2, 3].flat_map(|v, i| if i == 1 { [] } else { [v, v * 10] })")?
+            .into_typed_array::<INT>()?,
+        [1, 10, 3, 30]
+    );
+    assert_eq!(
+        engine
+            .eval::<Dynamic>("[1, 2].flat_map(|v| v + 1)")?
+            .into_typed_array::<INT>()?,
+        [2, 3]
+    );
+
+    assert_eq!(
+        engine
+            .eval::<Dynamic>("[1, 2, 3, 4].zip([10, 20, 30], |a, b| a + b)")?
+            .into_typed_array::<INT>()?,
+        [11, 22, 33]
+    );
+    assert_eq!(
+        engine
+            .eval::<Dynamic>(
+                "
+                    let z = [1, 2, 3].zip([4, 5, 6]);
+                    let u = z.unzip();
+                    u[0] + u[1]
+                "
+            )?
+            .into_typed_array::<INT>()?,
+        [1, 2, 3, 4, 5, 6]
+    );
+    assert!(matches!(
+        *engine
+            .eval::<Array>("[[1, 2], 3].unzip()")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+
+    assert_eq!(
+        engine.eval::<INT>("[1, 2, 3, 3, 5, 6, 7].partition_point(|v| v < 5)")?,
+        4
+    );
+    assert_eq!(engine.eval::<INT>("[].partition_point(|v| v < 5)")?, 0);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_closure"))]
+fn test_arrays_sort_by_key_binary_search() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<Dynamic>(
+                "
+                    let count = 0;
+                    let x = [31, 12, 22, 11, 32, 21];
+                    x.sort_by_key_cached(|v| { count += 1; v / 10 });
+                    x.push(count);
+                    x
+                "
+            )?
+            .into_typed_array::<INT>()?,
+        [12, 11, 22, 21, 31, 32, 6]
+    );
+    assert_eq!(
+        engine
+            .eval::<Dynamic>(
+                r#"
+                    let x = ["hello", "a", "world", "xy", "!"];
+                    x.sort_by_key_cached(|s| s.len);
+                    x.map(|s| s.len)
+                "#
+            )?
+            .into_typed_array::<INT>()?,
+        [1, 1, 2, 5, 5]
+    );
+
+    assert_eq!(engine.eval::<INT>("[1, 3, 5, 7, 9].binary_search(7)")?, 3);
+    assert_eq!(engine.eval::<INT>("[1, 3, 5, 7, 9].binary_search(4)")?, -3);
+    assert_eq!(engine.eval::<INT>("[1, 3, 5, 7, 9].binary_search(0)")?, -1);
+    assert_eq!(engine.eval::<INT>("[1, 3, 5, 7, 9].binary_search(10)")?, -6);
+    assert_eq!(engine.eval::<INT>("[].binary_search(42)")?, -1);
+    assert_eq!(
+        engine.eval::<INT>("[1, 3, 5, 7, 9].binary_search(|v| v - 5)")?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<INT>("[1, 3, 5, 7, 9].binary_search(|v| v - 6)")?,
+        -4
+    );
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "async")]
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, INT};
+use std::any::TypeId;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::Duration;
+
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// A minimal executor for testing.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let cx = &mut Context::from_waker(&waker);
+
+    loop {
+        if let Poll::Ready(result) = future.as_mut().poll(cx) {
+            return result;
+        }
+        thread::park();
+    }
+}
+
+/// A future that is pending once before it completes.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn test_async() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_async_fn("double", [TypeId::of::<INT>()], |args| async move {
+        YieldOnce(false).await;
+        Ok(Dynamic::from(args[0].as_int().unwrap() * 2))
+    });
+
+    assert!(matches!(
+        *engine.eval::<INT>("double(21)").expect_err("should error"),
+        EvalAltResult::ErrorRuntime(..)
+    ));
+
+    let engine = Arc::new(engine);
+
+    assert_eq!(
+        block_on(engine.clone().eval_async::<INT>("double(1) + double(20)"))?,
+        42
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let ast = engine.compile("fn foo(x) { double(x) + 1 }")?;
+        let mut scope = Scope::new();
+        scope.push("y", 1 as INT);
+
+        let (result, scope) = block_on(engine.clone().call_fn_async::<INT>(
+            scope,
+            ast,
+            "foo",
+            (20 as INT,),
+        ))?;
+        assert_eq!(result, 41);
+        assert_eq!(scope.get_value::<INT>("y"), Some(1));
+    }
+
+    let mut scope = Scope::new();
+    scope.push("x", 20 as INT);
+
+    let (result, scope) = block_on(
+        engine
+            .clone()
+            .eval_with_scope_async::<INT>(scope, "x = double(x); let y = x + 2; y"),
+    )?;
+    assert_eq!(result, 42);
+    assert_eq!(scope.get_value::<INT>("x"), Some(40));
+    assert_eq!(scope.get_value::<INT>("y"), Some(42));
+
+    assert!(matches!(
+        *block_on(engine.eval_async::<String>("double(1)")).expect_err("should error"),
+        EvalAltResult::ErrorMismatchOutputType(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_async_spawn() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let spawned = Arc::new(AtomicUsize::new(0));
+    let counter = spawned.clone();
+    engine.on_async_spawn(move |job| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        thread::spawn(job);
+    });
+    engine.register_async_fn("double", [TypeId::of::<INT>()], |args| async move {
+        YieldOnce(false).await;
+        Ok(Dynamic::from(args[0].as_int().unwrap() * 2))
+    });
+
+    let engine = Arc::new(engine);
+
+    assert_eq!(
+        block_on(engine.clone().eval_async::<INT>("double(21)"))?,
+        42
+    );
+    assert_eq!(block_on(engine.eval_async::<INT>("40 + 2"))?, 42);
+    assert_eq!(spawned.load(Ordering::Relaxed), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_async_panic() {
+    let mut engine = Engine::new();
+
+    engine.register_fn("boom", || -> INT { panic!("boom") });
+
+    let engine = Arc::new(engine);
+
+    assert!(matches!(
+        *block_on(engine.eval_async::<INT>("boom()")).expect_err("should error"),
+        EvalAltResult::ErrorRuntime(..)
+    ));
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+fn test_async_cancel() {
+    let mut engine = Engine::new();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    engine.register_fn("tick", move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let engine = Arc::new(engine);
+
+    let mut future = engine.eval_async::<()>("loop { tick(); }");
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let cx = &mut Context::from_waker(&waker);
+
+    // Start the evaluation, then cancel it
+    assert!(Pin::new(&mut future).poll(cx).is_pending());
+    while count.load(Ordering::Relaxed) == 0 {
+        thread::yield_now();
+    }
+    drop(future);
+
+    thread::sleep(Duration::from_millis(50));
+    let n = count.load(Ordering::Relaxed);
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(count.load(Ordering::Relaxed), n);
+}
@@ -145,3 +145,45 @@ fn test_binary_ops_null_coalesce() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_binary_ops_trace() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::{Arc, RwLock};
+
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let mut engine = Engine::new();
+
+    let logger = log.clone();
+    engine.on_operator(move |op, types, value, _| {
+        logger
+            .write()
+            .unwrap()
+            .push(format!("{op}({}) = {value}", types.join(", ")))
+    });
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"let x = 40; let y = -x; let s = "a"; if s == "b" { 0 } else { 2 - y }"#
+        )?,
+        42
+    );
+
+    let int = std::any::type_name::<INT>();
+
+    assert_eq!(
+        *log.read().unwrap(),
+        [
+            format!("-({int}) = -40"),
+            "==(string, string) = false".to_string(),
+            format!("-({int}, {int}) = 42"),
+        ]
+    );
+
+    engine.set_fast_operators(false);
+    log.write().unwrap().clear();
+
+    assert_eq!(engine.eval::<INT>("let x = 40; x + 2")?, 42);
+    assert_eq!(*log.read().unwrap(), [format!("+({int}, {int}) = 42")]);
+
+    Ok(())
+}
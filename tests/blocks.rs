@@ -0,0 +1,262 @@
+#![cfg(feature = "serde")]
+use rhai::serde::{BlockExpr, BlockScript, BlockStmt};
+use rhai::{Engine, EvalAltResult, LexError, ParseErrorType, INT};
+use serde_json::json;
+
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_blocks_round_trip() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = r#"
+        fn add(x, y) { x + y }
+        private fn twice(f, x) { f.call(f.call(x)) }
+
+        let x = 40;
+        let m = #{ a: 1, "b c": [1, 2, 'x'], d: "q\"\n" };
+        m.a += add(x, 1);
+        m["b c"][1] = !true;
+        let s = `x=${x} $ ``${m?.a ?? 0}`;
+        let f = |v| v * 2 - -1;
+        for (v, i) in 0..5 {
+            if v == 1 { continue; } else if v > 3 { break; }
+            x -= v * i;
+        }
+        do { x += 1; } until x > 50;
+        try { throw "oops"; } catch (e) { x += e.len(); }
+        let r = if x > 0 { (x + 1) * 2 ** 3 ** 2 } else { 0 };
+        let (a, _, b) = (1, 2, 3);
+        (a, b) = (b, a);
+        switch x {
+            1 | 2 => r += 1,
+            3..10 if a > 1 => r += 2,
+            "x" => (),
+            _ => r += a * 10 + b
+        }
+        let t = switch [a, b] {
+            is string => 0,
+            case [p, _] if p > 1 => p,
+            case [p] => -p,
+            _ => 1
+        };
+        s.len() + twice(f, 1) + m.a + r + t + (-1).abs()
+    "#;
+
+    let expected = engine.eval::<INT>(script)?;
+
+    let blocks = engine.script_to_blocks(script)?;
+    let json = serde_json::to_string(&blocks).unwrap();
+    let blocks2: BlockScript = serde_json::from_str(&json).unwrap();
+    assert_eq!(blocks2, blocks);
+
+    let ast = engine.compile_blocks(&blocks2)?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, expected);
+
+    // Converting again yields the same blocks
+    assert_eq!(engine.script_to_blocks(&blocks.to_script()?)?, blocks);
+
+    Ok(())
+}
+
+#[test]
+fn test_blocks_json() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        serde_json::to_value(engine.script_to_blocks("let x = 1; x + 2")?).unwrap(),
+        json!({
+            "body": [
+                { "type": "let", "name": "x", "value": { "type": "int", "value": 1 } },
+                {
+                    "type": "expr",
+                    "expr": {
+                        "type": "operator",
+                        "op": "+",
+                        "args": [
+                            { "type": "variable", "name": "x" },
+                            { "type": "int", "value": 2 }
+                        ]
+                    }
+                }
+            ]
+        })
+    );
+
+    let blocks: BlockScript = serde_json::from_value(json!({
+        "body": [
+            { "type": "let", "name": "total", "value": { "type": "int", "value": 0 } },
+            {
+                "type": "while",
+                "condition": {
+                    "type": "operator",
+                    "op": "<",
+                    "args": [
+                        { "type": "variable", "name": "total" },
+                        { "type": "int", "value": 40 }
+                    ]
+                },
+                "body": [
+                    {
+                        "type": "assign",
+                        "target": { "type": "variable", "name": "total" },
+                        "op": "+=",
+                        "value": { "type": "int", "value": 10 }
+                    }
+                ]
+            },
+            {
+                "type": "expr",
+                "expr": {
+                    "type": "operator",
+                    "op": "+",
+                    "args": [
+                        { "type": "variable", "name": "total" },
+                        { "type": "int", "value": 2 }
+                    ]
+                }
+            }
+        ]
+    }))
+    .unwrap();
+
+    assert_eq!(
+        blocks.to_script()?,
+        "let total = 0;\nwhile total < 40 {\n    total += 10;\n}\ntotal + 2"
+    );
+    assert_eq!(
+        engine.eval_ast::<INT>(&engine.compile_blocks(&blocks)?)?,
+        42
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_custom_syntax"))]
+#[test]
+fn test_blocks_custom_syntax() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax(
+        ["repeat", "$ident$", "$symbol$", "$int$", "$block$"],
+        true,
+        |context, inputs| {
+            let var = inputs[0].get_string_value().unwrap().to_string();
+            let op = inputs[1].get_string_value().unwrap();
+            let count = inputs[2].get_literal_value::<INT>().unwrap();
+            let mut value = 0 as INT;
+            context.scope_mut().push(var.clone(), 0 as INT);
+            for n in 0..count {
+                context.scope_mut().set_value(var.clone(), n);
+                let x = context.eval_expression_tree(&inputs[3])?.as_int().unwrap();
+                value = if op == "+" { value + x } else { value * x };
+            }
+            Ok(value.into())
+        },
+    )?;
+
+    let script = "let x = 2; repeat n + 4 { n * x } * 10";
+
+    let blocks = engine.script_to_blocks(script)?;
+    let json = serde_json::to_value(&blocks).unwrap();
+
+    assert_eq!(
+        json["body"][1]["expr"]["args"][0],
+        json!({
+            "type": "custom",
+            "tokens": ["repeat", "$ident$", "$symbol$", "$int$", "$block$"],
+            "inputs": [
+                { "type": "variable", "name": "n" },
+                { "type": "string", "value": "+" },
+                { "type": "int", "value": 4 },
+                {
+                    "type": "block",
+                    "body": [
+                        {
+                            "type": "expr",
+                            "expr": {
+                                "type": "operator",
+                                "op": "*",
+                                "args": [
+                                    { "type": "variable", "name": "n" },
+                                    { "type": "variable", "name": "x" }
+                                ]
+                            }
+                        }
+                    ]
+                }
+            ]
+        })
+    );
+
+    assert_eq!(
+        blocks.to_script()?,
+        "let x = 2;\n(repeat n + 4 {\n    n * x\n}) * 10"
+    );
+    assert_eq!(
+        engine.eval_ast::<INT>(&engine.compile_blocks(&blocks)?)?,
+        engine.eval::<INT>(script)?
+    );
+
+    // Symbols cannot inject code
+    let blocks: BlockScript = serde_json::from_value(json!({
+        "body": [{
+            "type": "expr",
+            "expr": {
+                "type": "custom",
+                "tokens": ["repeat", "$ident$", "$symbol$", "$int$", "$block$"],
+                "inputs": [
+                    { "type": "variable", "name": "n" },
+                    { "type": "string", "value": "+ 1 launch() +" },
+                    { "type": "int", "value": 4 },
+                    { "type": "block" }
+                ]
+            }
+        }]
+    }))
+    .unwrap();
+    assert!(matches!(
+        engine.compile_blocks(&blocks).unwrap_err().err_type(),
+        ParseErrorType::MissingSymbol(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_blocks_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    // Names cannot inject code
+    let blocks = BlockScript {
+        functions: Vec::new(),
+        body: vec![BlockStmt::Expr {
+            expr: BlockExpr::Variable {
+                name: "x; launch()".into(),
+                namespace: Vec::new(),
+            },
+        }],
+    };
+    assert!(matches!(
+        engine.compile_blocks(&blocks).unwrap_err().err_type(),
+        ParseErrorType::BadInput(LexError::MalformedIdentifier(..))
+    ));
+
+    let blocks = BlockScript {
+        functions: Vec::new(),
+        body: vec![BlockStmt::Expr {
+            expr: BlockExpr::Operator {
+                op: "+ 1; launch() +".into(),
+                args: vec![BlockExpr::Int { value: 1 }, BlockExpr::Int { value: 2 }],
+            },
+        }],
+    };
+    assert!(matches!(
+        engine.compile_blocks(&blocks).unwrap_err().err_type(),
+        ParseErrorType::UnknownOperator(..)
+    ));
+
+    Ok(())
+}
@@ -145,3 +145,120 @@ fn build_type() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn build_type_unknown_members() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone)]
+    struct Row {
+        columns: Vec<(String, INT)>,
+    }
+
+    impl Row {
+        fn new() -> Self {
+            Self {
+                columns: vec![("id".into(), 1), ("count".into(), 42)],
+            }
+        }
+    }
+
+    impl CustomType for Row {
+        fn build(mut builder: TypeBuilder<Self>) {
+            builder
+                .with_name("Row")
+                .with_fn("new_row", Self::new)
+                .with_get("len", |row: &mut Row| row.columns.len() as INT)
+                .on_unknown_property(|row, prop| {
+                    row.columns
+                        .iter()
+                        .find(|(name, _)| name == prop)
+                        .map(|&(_, value)| value.into())
+                        .ok_or_else(|| {
+                            EvalAltResult::ErrorPropertyNotFound(prop.into(), Position::NONE).into()
+                        })
+                })
+                .on_unknown_method(|row, name, args| match name {
+                    "add_column" => {
+                        let value = args[0].as_int().unwrap();
+                        row.columns
+                            .push((format!("col{}", row.columns.len()), value));
+                        Ok(().into())
+                    }
+                    _ => Err(
+                        EvalAltResult::ErrorFunctionNotFound(name.into(), Position::NONE).into(),
+                    ),
+                });
+        }
+    }
+
+    let mut engine = Engine::new();
+    engine.build_type::<Row>();
+
+    assert_eq!(engine.eval::<INT>("let r = new_row(); r.count")?, 42);
+    assert_eq!(engine.eval::<INT>("let r = new_row(); r.len")?, 2);
+    assert_eq!(
+        engine.eval::<INT>("let r = new_row(); r.add_column(99); r.col2 + r.len")?,
+        102
+    );
+    assert!(matches!(
+        *engine
+            .run("let r = new_row(); r.name")
+            .expect_err("should error"),
+        EvalAltResult::ErrorPropertyNotFound(..)
+    ));
+    assert!(matches!(
+        *engine
+            .run("let r = new_row(); r.foo()")
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn build_type_field_iter() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone)]
+    struct Account {
+        owner: String,
+        balance: INT,
+    }
+
+    impl CustomType for Account {
+        fn build(mut builder: TypeBuilder<Self>) {
+            builder
+                .with_name("Account")
+                .with_fn("new_account", || Account {
+                    owner: "alice".into(),
+                    balance: 42,
+                })
+                .with_field_iter()
+                .with_get("owner", |a: &mut Self| a.owner.clone())
+                .with_get_set(
+                    "balance",
+                    |a: &mut Self| a.balance,
+                    |a: &mut Self, value: INT| a.balance = value,
+                );
+        }
+    }
+
+    let mut engine = Engine::new();
+    engine.build_type::<Account>();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let a = new_account();
+                a.balance = 100;
+                let s = "";
+                for (field, i) in a {
+                    s += `${i}:${field[0]}=${field[1]};`;
+                }
+                s
+            "#
+        )?,
+        "0:owner=alice;1:balance=100;"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,191 @@
+#![cfg(feature = "bytecode")]
+use rhai::{Engine, EvalAltResult, Scope, INT};
+
+fn eval_both(engine: &Engine, script: &str) -> Result<(INT, INT), Box<EvalAltResult>> {
+    let ast = engine.compile(script)?;
+    let bytecode = engine.compile_bytecode(&ast);
+
+    Ok((engine.eval_ast(&ast)?, engine.eval_bytecode(&bytecode)?))
+}
+
+#[test]
+fn test_bytecode() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    for script in [
+        "40 + 2",
+        "let x = 1; if x > 0 && x < 10 || false { x * 2 } else { -x }",
+        "let x = 0; let i = 0; while i < 10 { i += 1; x += i; } x",
+        "let x = 0; loop { x += 1; if x > 20 { break; } } x",
+        "let x = 0; do { x += 3; } until x > 20; x",
+        "let x = 0; do { x += 1; if x < 5 { continue; } x *= 2; } while x < 100; x",
+        "let x = 0; for (v, i) in 10..20 { if i % 2 == 0 { continue; } x += v * i; } x",
+        "let x = 0; for i in 0..10 { for j in 0..10 { if j > i { break; } x += j; } } x",
+        "let x = 0; { let x = 42; x += 1; } x",
+        "fn f(n) { if n < 2 { n } else { f(n - 1) + f(n - 2) } } f(6)",
+        "let a = [1, 2, 3]; for x in a { a.push(x * 2); } a.len()",
+        "let x = 0; for i in 0..100 { x += i; if x > 50 { return x; } } -1",
+        "let x = { let y = 40; y + 2 }; x",
+    ] {
+        let (walker, bytecode) = eval_both(&engine, script)?;
+        assert_eq!(walker, bytecode, "{script}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_bytecode_scope() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let bytecode = engine.compile_bytecode(&engine.compile("let y = x * 2; x += 1; y")?);
+
+    let mut scope = Scope::new();
+    scope.push("x", 20 as INT);
+
+    assert_eq!(
+        engine.eval_bytecode_with_scope::<INT>(&mut scope, &bytecode)?,
+        40
+    );
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 21);
+    assert_eq!(scope.get_value::<INT>("y").unwrap(), 40);
+
+    Ok(())
+}
+
+#[test]
+fn test_bytecode_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    for script in [
+        "let x = 0; while x { x += 1; }",
+        "let x = 0; let y = 42; for i in y { x += i; }",
+        "let x = 0; for i in 0..10 { x += i; if i == 5 { throw i; } }",
+        "let x = 1; x + true",
+        r#"let x = "hello"; if x.len() > 0 && x { 1 }"#,
+    ] {
+        let ast = engine.compile(script)?;
+        let bytecode = engine.compile_bytecode(&ast);
+
+        let walker = engine.run_ast(&ast).unwrap_err();
+        let bytecode = engine.eval_bytecode::<()>(&bytecode).unwrap_err();
+
+        assert_eq!(walker.to_string(), bytecode.to_string(), "{script}");
+        assert_eq!(walker.position(), bytecode.position(), "{script}");
+    }
+
+    assert!(matches!(
+        *engine
+            .eval_bytecode::<INT>(&engine.compile_bytecode(&engine.compile("true")?))
+            .unwrap_err(),
+        EvalAltResult::ErrorMismatchOutputType(..)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_bytecode_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    for script in [
+        "fn inc(x) { x + 1 } let s = 0; for i in 0..10 { s = inc(s); } s",
+        "fn f(x) { let y = x * 2; const z = 1; y + z } f(20) + f(0)",
+        "fn f(x) { if x > 10 { return x; } x * 10 } f(3) + f(42)",
+        "fn f(x) { for i in 0..x { if i > 3 { return i; } } -1 } f(10)",
+        "fn add(x) { this += x; } let v = 40; v.add(2); v",
+        "fn foo() { 42 } let f = foo; let foo = 1; f.call() + foo",
+        "let a = [1, 2]; push(a, 3); push(a, len(a)); a.len() + a[3]",
+        "let x = 40; fn f() { 2 } x + f()",
+        r#"let s = "hello"; let n = 0; for c in s { n += to_int(c); } n"#,
+        "fn f(x) { let r = 0; try { g(x); } catch (e) { r = e; } r } fn g(x) { throw x + 1; } f(41)",
+    ] {
+        let (walker, bytecode) = eval_both(&engine, script)?;
+        assert_eq!(walker, bytecode, "{script}");
+    }
+
+    for script in [
+        "fn f(x) { g(x) } fn g(x) { x + true } f(1)",
+        "fn f(x) { let y = z; y } f(1)",
+        "fn f(x) { throw x; } f(42)",
+        "let x = 1; foo(x)",
+    ] {
+        let ast = engine.compile(script)?;
+        let bytecode = engine.compile_bytecode(&ast);
+
+        let walker = engine.run_ast(&ast).unwrap_err();
+        let bytecode = engine.eval_bytecode::<()>(&bytecode).unwrap_err();
+
+        assert_eq!(walker.to_string(), bytecode.to_string(), "{script}");
+        assert_eq!(walker.position(), bytecode.position(), "{script}");
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_bytecode_tail_calls() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_optimize_tail_calls(true);
+
+    let script = "
+        fn sum(n, acc) {
+            if n == 0 { acc } else { sum(n - 1, acc + n) }
+        }
+        fn count(n) {
+            if n == 0 { return 0; }
+            count(n - 1)
+        }
+        sum(100, 0) + count(100)
+    ";
+
+    let (walker, bytecode) = eval_both(&engine, script)?;
+    assert_eq!(walker, 5050);
+    assert_eq!(bytecode, 5050);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_bytecode_operations() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(500);
+
+    let ast = engine.compile("let x = 0; for i in 0..1000 { if i % 2 == 0 { x += i; } } x")?;
+    let bytecode = engine.compile_bytecode(&ast);
+
+    let walker = engine.eval_ast::<INT>(&ast).unwrap_err();
+    let bytecode = engine.eval_bytecode::<INT>(&bytecode).unwrap_err();
+
+    assert!(matches!(*walker, EvalAltResult::ErrorTooManyOperations(..)));
+    assert_eq!(walker.to_string(), bytecode.to_string());
+    assert_eq!(walker.position(), bytecode.position());
+
+    Ok(())
+}
+
+#[test]
+fn test_bytecode_indexed_iterator() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Clone)]
+    struct Pairs;
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type::<Pairs>()
+        .register_fn("pairs", || Pairs)
+        .register_indexed_iterator_fn(|_: Pairs| {
+            Box::new((1..=3 as INT).map(|i| ((i * 10).into(), i.into())))
+        });
+
+    let (walker, bytecode) = eval_both(
+        &engine,
+        "let x = 0; for (v, i) in pairs() { x += v * i; } x",
+    )?;
+    assert_eq!(walker, 140);
+    assert_eq!(bytecode, 140);
+
+    Ok(())
+}
@@ -1,6 +1,7 @@
 #![cfg(not(feature = "no_function"))]
-use rhai::{Dynamic, Engine, EvalAltResult, FnPtr, Func, FuncArgs, Scope, AST, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, EvalSession, FnPtr, Func, FuncArgs, Scope, AST, INT};
 use std::any::TypeId;
+use std::sync::{Arc, RwLock};
 
 #[test]
 fn test_call_fn() -> Result<(), Box<EvalAltResult>> {
@@ -157,6 +158,71 @@ fn test_call_fn_private() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_call_fn_with_session() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+    engine.on_print(move |s| logger.write().unwrap().push(s.to_string()));
+
+    #[cfg(not(feature = "unchecked"))]
+    engine.set_max_operations(1000);
+
+    let ast = engine.compile(
+        r#"
+            print("init");
+            fn double(x) { if x < 0 { throw "negative"; } x * 2 }
+            fn sum(n) { let s = 0; for i in 0..n { s += i; } s }
+        "#,
+    )?;
+
+    let mut session = EvalSession::new(&engine);
+    let mut scope = Scope::new();
+
+    for x in 0..100 as INT {
+        let r =
+            engine.call_fn_with_session::<INT>(&mut session, &mut scope, &ast, "double", (x,))?;
+        assert_eq!(r, x * 2);
+    }
+    assert_eq!(*log.read().unwrap(), ["init"]);
+
+    // Operations are counted per call
+    for _ in 0..10 {
+        let r = engine.call_fn_with_session::<INT>(
+            &mut session,
+            &mut scope,
+            &ast,
+            "sum",
+            (100 as INT,),
+        )?;
+        assert_eq!(r, 4950);
+    }
+
+    // The AST is evaluated again after an error, or when it changes
+    assert!(engine
+        .call_fn_with_session::<INT>(&mut session, &mut scope, &ast, "double", (-1 as INT,))
+        .is_err());
+    engine.call_fn_with_session::<INT>(&mut session, &mut scope, &ast, "double", (1 as INT,))?;
+
+    *session.tag_mut() = Dynamic::from(42 as INT);
+
+    let ast2 = engine.compile(r#"print("init2"); fn double(x) { x + x }"#)?;
+    let r = engine.call_fn_with_session::<INT>(
+        &mut session,
+        &mut scope,
+        &ast2,
+        "double",
+        (1 as INT,),
+    )?;
+    assert_eq!(r, 2);
+    assert_eq!(session.tag().as_int().unwrap(), 42);
+
+    assert_eq!(*log.read().unwrap(), ["init", "init", "init2"]);
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_object"))]
 fn test_fn_ptr_raw() -> Result<(), Box<EvalAltResult>> {
@@ -432,3 +432,108 @@ fn test_closures_callback() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(not(feature = "no_position"))]
+fn test_closures_origin() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let f = engine.eval::<FnPtr>(
+        "
+            let x = 1;
+            |y| y + 1
+        ",
+    )?;
+
+    assert!(f.is_anonymous());
+    assert_eq!(f.origin(), Some(rhai::Position::new(3, 13)));
+
+    let g = engine.eval::<FnPtr>("Fn(\"foo\")")?;
+    assert!(!g.is_anonymous());
+    assert_eq!(g.origin(), None);
+
+    let err = engine
+        .run(
+            "
+                let f = |x| throw x;
+                f.call(1);
+            ",
+        )
+        .expect_err("should error");
+
+    assert!(err.to_string().contains("in call to closure@2:25"));
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gc")]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_object"))]
+fn test_closures_collect_garbage() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    engine.run_with_scope(
+        &mut scope,
+        "
+            let a = #{ value: 1 };
+            let b = #{ value: 2 };
+            a.other = || b.value;
+            b.other = || a.value;
+            let c = #{ value: 3 };
+            c.get = || c.value;
+        ",
+    )?;
+
+    let stats = engine.collect_garbage(&mut scope);
+    assert_eq!(stats.freed, 0);
+    assert!(stats.scanned >= 3);
+
+    // Keep 'c' alive in the host only
+    let c = scope.get("c").unwrap().clone();
+    scope.clear();
+
+    assert_eq!(engine.collect_garbage(&mut scope).freed, 2);
+    assert_eq!(engine.collect_garbage(&mut scope).freed, 0);
+
+    scope.push("c", c);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "c.value")?, 3);
+
+    drop(scope);
+    assert_eq!(engine.collect_garbage(&mut Scope::new()).freed, 1);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gc")]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_object"))]
+fn test_closures_collect_garbage_tuples() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    engine.run_with_scope(
+        &mut scope,
+        "
+            let a = #{ value: 1 };
+            a.pair = (|| a.value, 0);
+            let b = #{ value: 2 };
+            let t = (|| b.value, 0);
+        ",
+    )?;
+
+    assert_eq!(engine.collect_garbage(&mut scope).freed, 0);
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "a.value + b.value")?,
+        3
+    );
+
+    // 'a' refers to itself via the closure inside the tuple
+    scope.clear();
+
+    assert_eq!(engine.collect_garbage(&mut scope).freed, 1);
+
+    Ok(())
+}
@@ -64,3 +64,34 @@ fn test_modulo_equals() -> Result<(), Box<EvalAltResult>> {
     assert_eq!(engine.eval::<INT>("let x = 10; x %= 4; x")?, 2);
     Ok(())
 }
+
+#[test]
+fn test_compound_equals_in_place() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "ab"; x *= 3; x"#)?,
+        "ababab"
+    );
+    assert_eq!(engine.eval::<String>(r#"let x = "ab"; x *= 0; x"#)?, "");
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let x = [1, 2, 3, 2]; x -= 2; x[1] + x.len()")?,
+            6
+        );
+        assert_eq!(
+            engine.eval::<INT>(r#"let x = [1, 2, 3]; x -= "hello"; x.len()"#)?,
+            3
+        );
+    }
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = #{a: 1, b: 2}; x += #{b: 40, c: 1}; x.a + x.b + x.c")?,
+        42
+    );
+
+    Ok(())
+}
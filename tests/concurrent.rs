@@ -0,0 +1,78 @@
+#![cfg(feature = "sync")]
+use rhai::{Engine, EvalAltResult, NativeCallContext, Scope, AST, INT};
+use std::{sync::Arc, thread};
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_eval_concurrent() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.with_arena(true);
+
+    let engine = Arc::new(engine);
+
+    let ast = Arc::new(engine.compile(
+        r#"
+            fn label(n) { `item-${n}` }
+
+            let s = "";
+            for n in 0..k { s += label(n); s += ","; }
+            s
+        "#,
+    )?);
+
+    let handles = (0..8 as INT)
+        .map(|k| {
+            let engine = engine.clone();
+            let ast = ast.clone();
+
+            thread::spawn(move || -> Result<String, Box<EvalAltResult>> {
+                let mut result = String::new();
+
+                for _ in 0..10 {
+                    let mut scope = Scope::new();
+                    scope.push("k", k);
+                    result = engine.eval_concurrent(&mut scope, &ast)?;
+                }
+
+                Ok(result)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for (k, handle) in handles.into_iter().enumerate() {
+        let expected = (0..k).map(|n| format!("item-{},", n)).collect::<String>();
+        assert_eq!(handle.join().unwrap()?, expected);
+    }
+
+    // The same thread can evaluate different scripts
+    let mut scope = Scope::new();
+    assert_eq!(
+        engine.eval_concurrent::<INT>(
+            &mut scope,
+            &engine.compile("fn label(n) { n * 2 } label(21)")?
+        )?,
+        42
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_eval_concurrent_reentrant() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn(
+        "inner",
+        |ctx: NativeCallContext, script: &str| -> Result<INT, Box<EvalAltResult>> {
+            let ast: AST = ctx.engine().compile(script)?;
+            ctx.engine().eval_concurrent(&mut Scope::new(), &ast)
+        },
+    );
+
+    let ast = engine.compile(r#"inner("40 + 2") - 2"#)?;
+
+    assert_eq!(engine.eval_concurrent::<INT>(&mut Scope::new(), &ast)?, 40);
+
+    Ok(())
+}
@@ -37,6 +37,74 @@ fn test_constant_scope() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_constant_invalid_assignment() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.on_invalid_assignment(|name, value, pos| match name {
+        "x" => Ok(true),
+        "y" => Err(EvalAltResult::ErrorRuntime(value.clone(), pos).into()),
+        _ => Ok(false),
+    });
+
+    let mut scope = Scope::new();
+    scope.push_constant("x", 42 as INT);
+    scope.push_constant("y", 42 as INT);
+    scope.push_constant("z", 42 as INT);
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "x = 1; x += 1; x")?,
+        2
+    );
+    assert_eq!(scope.get_value::<INT>("x").expect("x"), 2);
+    assert_eq!(scope.is_constant("x"), Some(true));
+
+    assert!(matches!(
+        *engine.run_with_scope(&mut scope, "y = 1").expect_err("expects error"),
+        EvalAltResult::ErrorRuntime(v, ..) if v.as_int().unwrap() == 1
+    ));
+    assert!(matches!(
+        *engine.run_with_scope(&mut scope, "z = 1").expect_err("expects error"),
+        EvalAltResult::ErrorAssignmentToConstant(z, ..) if z == "z"
+    ));
+    assert!(matches!(
+        *engine.run_with_scope(&mut scope, "(z, x) = (1, 2)").expect_err("expects error"),
+        EvalAltResult::ErrorAssignmentToConstant(z, ..) if z == "z"
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        scope.push_constant("x", vec![rhai::Dynamic::from(1 as INT)]);
+        assert_eq!(
+            engine.eval_with_scope::<INT>(&mut scope, "x[0] = 42; x[0]")?,
+            42
+        );
+    }
+
+    // Variables in a frozen scope can never be written to
+    let base = scope.freeze();
+    let mut scope = Scope::with_base(&base);
+
+    assert!(matches!(
+        *engine.run_with_scope(&mut scope, "x = 1").expect_err("expects error"),
+        EvalAltResult::ErrorAssignmentToConstant(x, ..) if x == "x"
+    ));
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert!(matches!(
+            *engine.run_with_scope(&mut scope, "x[0] = 1").expect_err("expects error"),
+            EvalAltResult::ErrorAssignmentToConstant(x, ..) if x == "x"
+        ));
+        assert_eq!(
+            engine.eval_with_scope::<INT>(&mut Scope::with_base(&base), "x[0]")?,
+            42
+        );
+    }
+
+    Ok(())
+}
+
 #[cfg(not(feature = "no_object"))]
 #[test]
 fn test_constant_mut() -> Result<(), Box<EvalAltResult>> {
@@ -123,5 +191,18 @@ fn test_constant_mut() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorAssignmentToConstant(..)
     ));
 
+    engine.on_invalid_assignment(|name, _, _| Ok(name == "value"));
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            "
+                MY_NUMBER.value = 1;
+                MY_NUMBER.value
+            ",
+        )?,
+        1
+    );
+
     Ok(())
 }
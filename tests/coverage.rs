@@ -0,0 +1,190 @@
+#![cfg(feature = "coverage")]
+#![cfg(not(feature = "no_position"))]
+use rhai::{Engine, EvalAltResult, Position, Scope, INT};
+
+#[test]
+fn test_coverage() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert!(engine.take_coverage().is_none());
+
+    let ast = engine.compile(
+        "
+            let y = 0;
+            if x > 0 {
+                y = 1;
+            } else {
+                y = 2;
+            }
+            switch x {
+                1 => y += 10,
+                2 if y > 100 => y += 20,
+                3..5 => y += 30,
+                _ => ()
+            }
+            y
+        ",
+    )?;
+
+    engine.start_coverage();
+
+    let mut scope = Scope::new();
+    scope.push("x", 1 as INT);
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 11);
+
+    let coverage = engine.take_coverage().expect("coverage");
+    assert!(engine.take_coverage().is_none());
+    assert!(!coverage.is_empty());
+    assert_eq!(coverage.sources().collect::<Vec<_>>(), [""]);
+
+    let report = coverage.report(&ast);
+
+    assert_eq!(coverage.statement_hits("", Position::new(2, 13)), 1);
+    assert_eq!(coverage.statement_hits("", Position::new(6, 17)), 0);
+
+    let uncovered = report
+        .uncovered_statements()
+        .map(|span| span.start().line().expect("line"))
+        .collect::<Vec<_>>();
+    assert_eq!(uncovered, [6, 10, 11]);
+
+    let uncovered = report
+        .uncovered_branches()
+        .map(|span| span.start().line().expect("line"))
+        .collect::<Vec<_>>();
+    assert_eq!(uncovered, [5, 10, 11, 12]);
+
+    assert_eq!(report.branches().len(), 6);
+    assert_eq!(report.branches().iter().filter(|b| b.hits > 0).count(), 2);
+
+    // Coverage accumulates across runs
+    engine.start_coverage();
+
+    for x in 0..5 {
+        let mut scope = Scope::new();
+        scope.push("x", x as INT);
+        engine.run_ast_with_scope(&mut scope, &ast)?;
+    }
+
+    let report = engine.take_coverage().expect("coverage").report(&ast);
+
+    assert_eq!(report.uncovered_statements().count(), 1);
+    assert_eq!(report.uncovered_branches().count(), 1);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_coverage_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            fn sign(x) {
+                if x < 0 { return -1; }
+                if x > 0 { return 1; }
+                0
+            }
+            sign(42) + sign(-42)
+        ",
+    )?;
+
+    engine.start_coverage();
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 0);
+
+    let report = engine.take_coverage().expect("coverage").report(&ast);
+
+    assert_eq!(
+        report
+            .uncovered_statements()
+            .map(|span| span.start().line().expect("line"))
+            .collect::<Vec<_>>(),
+        [5]
+    );
+
+    // The missing `else` block of the second `if` statement
+    assert_eq!(report.uncovered_branches().count(), 1);
+
+    Ok(())
+}
+
+#[cfg(feature = "bytecode")]
+#[test]
+fn test_coverage_bytecode() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            let y = 0;
+            if x > 0 {
+                y = 1;
+            } else {
+                y = 2;
+            }
+            y
+        ",
+    )?;
+    let bytecode = engine.compile_bytecode(&ast);
+
+    engine.start_coverage();
+
+    let mut scope = Scope::new();
+    scope.push("x", 1 as INT);
+    assert_eq!(
+        engine.eval_bytecode_with_scope::<INT>(&mut scope, &bytecode)?,
+        1
+    );
+
+    let coverage = engine.take_coverage().expect("coverage");
+    let uncovered = coverage
+        .report(&ast)
+        .uncovered_statements()
+        .map(|span| span.start().line().expect("line"))
+        .collect::<Vec<_>>();
+    assert_eq!(uncovered, [6]);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_coverage_resumable() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.start_coverage();
+
+    let mut engine = rhai::Shared::new(engine);
+
+    let ast = engine.compile(
+        "
+            let x = 0;
+            for n in 0..100 {
+                x += n;
+            }
+            x
+        ",
+    )?;
+
+    // Coverage cannot be tracked by an evaluation suspended in between statements
+    let mut eval = engine
+        .clone()
+        .eval_ast_resumable::<INT>(Scope::new(), ast.clone());
+
+    match eval.resume(10) {
+        std::task::Poll::Ready(Err(err)) => {
+            assert!(matches!(*err, EvalAltResult::ErrorSystem(..)))
+        }
+        _ => panic!("evaluation should fail"),
+    }
+    drop(eval);
+
+    let coverage = rhai::Shared::get_mut(&mut engine)
+        .expect("engine")
+        .take_coverage()
+        .expect("coverage");
+    assert!(coverage.report(&ast).uncovered_statements().count() > 0);
+
+    Ok(())
+}
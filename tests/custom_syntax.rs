@@ -343,3 +343,96 @@ fn test_custom_syntax_raw2() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_custom_syntax_typed_expr() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax(
+        ["repeat", "$expr:string$", "for", "$expr:int$"],
+        false,
+        |context, inputs| {
+            let s = context.eval_expression_tree(&inputs[0])?.into_string()?;
+            let n = context.eval_expression_tree(&inputs[1])?.as_int()?;
+            Ok(s.repeat(n as usize).into())
+        },
+    )?;
+
+    assert_eq!(engine.eval::<String>(r#"repeat "ab" for 3"#)?, "ababab");
+    assert_eq!(
+        engine.eval::<String>(r#"let s = "x"; let n = 2; repeat s + "y" for n + 1"#)?,
+        "xyxyxy"
+    );
+
+    let err = engine
+        .compile(r#"repeat "ab" for "3""#)
+        .expect_err("should error");
+    assert_eq!(
+        err.err_type(),
+        &ParseErrorType::MismatchedType(
+            "an integer expression".to_string(),
+            "a string".to_string()
+        )
+    );
+    assert_eq!(err.position(), Position::new(1, 17));
+
+    assert!(matches!(
+        engine
+            .compile("repeat 42 for 3")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::MismatchedType(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_syntax_macro() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_custom_syntax_macro(["unless", "$expr$", "$block$"], "if !($0) { $1 }")?;
+    engine.register_custom_syntax_macro(
+        ["swap", "$ident$", "$ident$"],
+        "{ let t = $0; $0 = $1; $1 = t; }",
+    )?;
+    engine.register_custom_syntax_macro(["twice", "$expr$"], "$0 * 2")?;
+    engine.register_custom_syntax_macro(["bad", "$expr$"], "$1 + 1")?;
+    engine.register_custom_syntax_macro(["oops", "$expr$"], "$0 + no_such_var")?;
+
+    assert_eq!(
+        engine.eval::<INT>("let x = 1; unless x > 10 { x + 41 }")?,
+        42
+    );
+    engine.eval::<()>("let x = 11; unless x > 10 { x + 41 }")?;
+    assert_eq!(
+        engine.eval::<INT>("let a = 1; let b = 2; swap a b; a * 10 + b")?,
+        21
+    );
+    assert_eq!(engine.eval::<INT>("let x = 20; twice x + 1")?, 42);
+    assert_eq!(engine.eval::<INT>("twice twice 10")?, 40);
+
+    assert!(matches!(
+        engine
+            .compile("bad 42")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::MissingSymbol(..)
+    ));
+
+    // Errors in the template are reported at the macro call site
+    assert_eq!(
+        engine
+            .compile("let x = 1;\n  bad x")
+            .expect_err("should error")
+            .position(),
+        Position::new(2, 3)
+    );
+    assert!(matches!(
+        *engine.run("let x = 1;\n  oops x").expect_err("should error"),
+        EvalAltResult::ErrorVariableNotFound(ref name, pos)
+            if name == "no_such_var" && pos == Position::new(2, 3)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "dap")]
+#![cfg(not(feature = "no_position"))]
+use rhai::debugger::dap::{channel, DebugAdapter, StreamTransport, Transport};
+use rhai::{Engine, EvalAltResult, INT};
+use serde_json::{json, Value};
+
+#[test]
+fn test_dap() -> Result<(), Box<EvalAltResult>> {
+    let (server, mut client) = channel();
+
+    let mut engine = Engine::new();
+    engine.register_debug_adapter(DebugAdapter::new(server).with_main_source("/test.rhai"));
+
+    // Requests are only read when needed, so they can all be sent up-front
+    let requests = [
+        ("initialize", json!({ "adapterID": "rhai" })),
+        ("launch", json!({})),
+        (
+            "setBreakpoints",
+            json!({
+                "source": { "path": "/test.rhai" },
+                "breakpoints": [{ "line": 4, "condition": "x >= 1" }, { "line": 5, "condition": "x +" }]
+            }),
+        ),
+        ("configurationDone", json!({})),
+        ("stackTrace", json!({ "threadId": 1 })),
+        ("variables", json!({ "variablesReference": 1 })),
+        ("evaluate", json!({ "expression": "x * 2", "frameId": 0 })),
+        ("pause", json!({ "threadId": 1 })),
+        ("continue", json!({ "threadId": 1 })),
+    ];
+
+    for (seq, (command, arguments)) in requests.iter().enumerate() {
+        client.send(json!({
+            "seq": seq + 1,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        }));
+    }
+
+    let result = engine.eval::<INT>(
+        "
+            let x = 0;
+            for i in 0..3 {
+                let list = [x, i];
+                x += i;
+            }
+            x
+        ",
+    )?;
+
+    assert_eq!(result, 3);
+
+    drop(engine);
+
+    let mut messages = Vec::new();
+    while let Some(message) = client.receive() {
+        messages.push(message);
+    }
+
+    let find = |kind: &str, name: &str| -> Vec<&Value> {
+        let key = if kind == "event" { "event" } else { "command" };
+        messages
+            .iter()
+            .filter(|m| m["type"] == kind && m[key] == name)
+            .collect()
+    };
+
+    assert_eq!(find("event", "initialized").len(), 1);
+    assert!(
+        find("response", "initialize")[0]["body"]["supportsConditionalBreakpoints"]
+            .as_bool()
+            .unwrap()
+    );
+
+    let breakpoints = &find("response", "setBreakpoints")[0]["body"]["breakpoints"];
+    assert_eq!(breakpoints[0]["verified"], true);
+    assert_eq!(breakpoints[1]["verified"], false);
+
+    let stopped = find("event", "stopped");
+    assert_eq!(stopped.len(), 1);
+    assert_eq!(stopped[0]["body"]["reason"], "breakpoint");
+
+    let frames = &find("response", "stackTrace")[0]["body"]["stackFrames"];
+    assert_eq!(frames[0]["line"], 4);
+    assert_eq!(frames[0]["source"]["path"], "/test.rhai");
+
+    let variables = find("response", "variables");
+    let locals = variables[0]["body"]["variables"].as_array().unwrap();
+    let locals: Vec<_> = locals
+        .iter()
+        .map(|v| (v["name"].as_str().unwrap(), v["value"].as_str().unwrap()))
+        .collect();
+    assert_eq!(locals, [("x", "1"), ("i", "2")]);
+
+    assert_eq!(find("response", "evaluate")[0]["body"]["result"], "2");
+    assert_eq!(find("response", "pause")[0]["success"], false);
+    assert_eq!(find("response", "continue")[0]["success"], true);
+    assert_eq!(find("event", "terminated").len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_dap_stream_message_size() {
+    let message = r#"{"seq":1}"#;
+    let input = format!("Content-Length: {}\r\n\r\n{}", message.len(), message);
+    let mut transport = StreamTransport::new(input.as_bytes(), Vec::new());
+    assert_eq!(transport.receive(), Some(json!({ "seq": 1 })));
+
+    let input = format!("Content-Length: {}\r\n\r\n", usize::MAX);
+    let mut transport = StreamTransport::new(input.as_bytes(), Vec::new());
+    assert_eq!(transport.receive(), None);
+}
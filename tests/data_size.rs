@@ -55,6 +55,13 @@ fn test_max_string_size() -> Result<(), Box<EvalAltResult>> {
         EvalAltResult::ErrorDataTooLarge(..)
     ));
 
+    assert!(matches!(
+        *engine
+            .run(r#"let x = "hello"; x *= 3;"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
     engine.set_max_string_size(0);
 
     assert_eq!(
@@ -352,3 +359,206 @@ fn test_max_map_size() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_max_interpolation_pieces() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_interpolation_pieces(3);
+
+    assert_eq!(
+        *engine
+            .compile("let x = 42; `a${x}b${x}c`")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::LiteralTooLarge("Number of interpolated string segments".to_string(), 3)
+    );
+
+    assert_eq!(engine.eval::<String>("let x = 42; `a${x}b`")?, "a42b");
+
+    engine.set_max_string_size(10);
+
+    assert!(matches!(
+        *engine
+            .run(r#"let x = "hello"; `${x}, ${x}`"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
+    engine.set_max_interpolation_pieces(0);
+    engine.set_max_string_size(0);
+
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "hello"; `a${x}b${x}c`"#)?,
+        "ahellobhelloc"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_max_script_size() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_script_size(20);
+
+    assert_eq!(engine.eval::<INT>("let x = 42; x + 1")?, 43);
+    assert_eq!(
+        *engine
+            .compile("let x = 42; let y = x + 1; y * 2")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::ScriptTooLarge(20)
+    );
+    assert_eq!(
+        *engine
+            .compile_stream("let x = 42; let y = x + 1; y * 2".chars())
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::ScriptTooLarge(20)
+    );
+    // Reading stops at the limit, even in the middle of whitespace or a comment
+    assert_eq!(
+        *engine
+            .compile_stream("let x = 42; //".chars().chain(std::iter::repeat(' ')))
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::ScriptTooLarge(20)
+    );
+
+    engine.set_max_script_size(0);
+    engine.set_max_tokens(5);
+
+    assert_eq!(engine.eval::<INT>("(40 + 2)")?, 42);
+    assert_eq!(
+        *engine
+            .compile("let x = 42; x")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::TooManyTokens(5)
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_max_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_functions(2).set_max_fn_params(2);
+
+    engine.compile("fn foo(x, y) { x + y } fn bar() { 0 }")?;
+
+    assert_eq!(
+        *engine
+            .compile("fn foo(x, y) { x + y } fn bar() { 0 } fn baz() { 1 }")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::TooManyFunctions(2)
+    );
+    assert_eq!(
+        *engine
+            .compile("fn foo(x, y) { x + y } let f = |x| x; let g = |x| x;")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::TooManyFunctions(2)
+    );
+    assert_eq!(
+        *engine
+            .compile("fn foo(x, y, z) { x + y + z }")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::TooManyParameters("foo".to_string(), 2)
+    );
+    assert_eq!(
+        *engine
+            .compile("let f = |x, y, z| x + y + z;")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::TooManyParameters(String::new(), 2)
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_deep_clone_with_limit() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let value = engine.eval::<rhai::Dynamic>(r#"[1, [2, 3], #{a: "hello", b: [4]}]"#)?;
+
+    // 3 items + 2 items + 2 properties + 5 characters + 1 item
+    let copy = value.deep_clone_with_limit(13)?;
+    assert_eq!(copy.to_string(), value.to_string());
+
+    assert!(matches!(
+        *value.deep_clone_with_limit(12).expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_size_in_tuples() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_string_size(10);
+
+    #[cfg(not(feature = "no_index"))]
+    engine.set_max_array_size(10);
+
+    #[cfg(not(feature = "no_object"))]
+    engine.set_max_map_size(10);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(
+            engine.eval::<INT>("let a = [1,2,3,4]; let t = (a, a); t.len()")?,
+            2
+        );
+
+        assert!(matches!(
+            *engine
+                .run("let a = [1,2,3,4,5,6,7,8]; let t = (a, 42); [t, t, t, t]")
+                .expect_err("should error"),
+            EvalAltResult::ErrorDataTooLarge(..)
+        ));
+
+        assert!(matches!(
+            *engine
+                .run("let a = [1,2,3,4,5,6,7,8]; let t = (a, a);")
+                .expect_err("should error"),
+            EvalAltResult::ErrorDataTooLarge(..)
+        ));
+
+        assert!(matches!(
+            *engine
+                .run("let t = (1, 2); loop { t = (t, t); }")
+                .expect_err("should error"),
+            EvalAltResult::ErrorDataTooLarge(..)
+        ));
+    }
+
+    assert!(matches!(
+        *engine
+            .run(r#"let s = "hello"; let t = (s, s, s)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    let x = #{a:1,b:2,c:3,d:4,e:5,f:6};
+                    let t = (x, 0);
+                    #{ a: t, b: t }
+                "
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
+    Ok(())
+}
@@ -83,3 +83,278 @@ fn test_debugger_state() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_debugger_trace() -> Result<(), Box<EvalAltResult>> {
+    use rhai::debugger::{DebuggerCommand, DebuggerEvent, Trace};
+    use std::sync::{Arc, Mutex};
+
+    let recorded = Arc::new(Mutex::new(None::<Trace>));
+    let recorded2 = recorded.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        |_| Dynamic::UNIT,
+        move |mut context, event, _, _, _| {
+            let debugger = &mut context.global_runtime_state_mut().debugger;
+
+            match event {
+                DebuggerEvent::Start => debugger.start_trace(),
+                DebuggerEvent::End => *recorded2.lock().unwrap() = debugger.take_trace(),
+                _ => (),
+            }
+
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    engine.run("let x = 1; x += 41; let y = x * 2; y")?;
+
+    let trace = recorded.lock().unwrap().take().unwrap();
+    assert_eq!(trace.len(), 4);
+
+    let mut replay = trace.replay();
+    assert!(replay.current().is_none());
+
+    assert_eq!(replay.seek(3).unwrap().changes.len(), 1);
+    assert_eq!(replay.get("x").unwrap().as_int().unwrap(), 42);
+    assert_eq!(replay.get("y").unwrap().as_int().unwrap(), 84);
+
+    replay.step_back().unwrap();
+    assert_eq!(replay.index(), Some(2));
+    assert_eq!(replay.get("x").unwrap().as_int().unwrap(), 42);
+    assert!(replay.get("y").is_none());
+
+    replay.step_back().unwrap();
+    let scope = replay.scope();
+    assert_eq!(scope.len(), 1);
+    assert_eq!(scope.get_value::<INT>("x").unwrap(), 1);
+
+    assert!(replay.step_back().is_some());
+    assert!(replay.step_back().is_none());
+    assert!(replay.scope().is_empty());
+
+    #[derive(Debug, Clone)]
+    struct Foo;
+
+    engine.register_fn("foo", || Foo);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        engine.run("let a = [1, 2]; a[1] = 3; a.push(4); let f = foo(); a")?;
+
+        let trace = recorded.lock().unwrap().take().unwrap();
+        let mut replay = trace.replay();
+        assert!(replay.seek(1).unwrap().changes.len() == 1);
+        assert!(replay.seek(2).unwrap().changes.len() == 2);
+        assert!(replay.seek(3).unwrap().changes.len() == 1);
+        assert_eq!(
+            replay.get("a").unwrap().clone().into_array().unwrap().len(),
+            3
+        );
+
+        replay.seek(1).unwrap();
+        let a = replay.get("a").unwrap().clone().into_array().unwrap();
+        assert_eq!(a[1].as_int().unwrap(), 2);
+        assert_eq!(a.len(), 2);
+    }
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine.run(
+            "
+                fn f(n) { let m = n * 2; m }
+                let x = f(1) + f(2);
+                for i in 0..2 { x += i; }
+                x
+            ",
+        )?;
+
+        let trace = recorded.lock().unwrap().take().unwrap();
+        let mut replay = trace.replay();
+        let mut values = Vec::new();
+
+        while let Some(step) = replay.step_forward() {
+            values.push((step.level, replay.get("n").map(|v| v.as_int().unwrap())));
+        }
+        assert!(values.contains(&(1, Some(1))));
+        assert!(values.contains(&(1, Some(2))));
+        assert_eq!(replay.get("x").unwrap().as_int().unwrap(), 7);
+        assert!(replay.get("n").is_none());
+
+        // Step back into the loop and the function calls
+        replay.step_back().unwrap();
+        assert_eq!(replay.get("x").unwrap().as_int().unwrap(), 6);
+        assert_eq!(replay.get("i").unwrap().as_int().unwrap(), 1);
+
+        while replay.current().unwrap().level == 0 {
+            replay.step_back().unwrap();
+        }
+        assert_eq!(replay.get("n").unwrap().as_int().unwrap(), 2);
+        assert!(replay.get("x").is_none());
+
+        replay.seek(0).unwrap();
+        assert!(replay.scope().is_empty());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_debugger_conditional_break_points() -> Result<(), Box<EvalAltResult>> {
+    use rhai::debugger::{BreakPoint, DebuggerCommand, DebuggerEvent, HitCondition};
+    use std::sync::{Arc, Mutex};
+
+    let stops = Arc::new(Mutex::new(Vec::<(usize, INT)>::new()));
+    let stops2 = stops.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        |_| Dynamic::UNIT,
+        move |mut context, event, _, _, _| {
+            let engine = context.engine();
+            let debugger = &mut context.global_runtime_state_mut().debugger;
+
+            match event {
+                DebuggerEvent::Start => {
+                    debugger
+                        .break_points_mut()
+                        .push(BreakPoint::AtFunctionName {
+                            name: "print".into(),
+                            enabled: true,
+                        });
+                    debugger
+                        .break_points_mut()
+                        .push(BreakPoint::AtFunctionName {
+                            name: "debug".into(),
+                            enabled: true,
+                        });
+                    debugger.set_break_point_condition(engine, 0, "x % 2 == 0")?;
+                    debugger.set_break_point_hit_condition(0, HitCondition::AtLeast(2));
+                    debugger.set_break_point_hit_condition(1, HitCondition::Multiple(3));
+                }
+                DebuggerEvent::BreakPoint(n) => {
+                    let x = context.scope().get_value::<INT>("x").unwrap();
+                    stops2.lock().unwrap().push((n, x));
+                }
+                DebuggerEvent::End => {
+                    assert_eq!(debugger.break_point_condition(0), Some("x % 2 == 0"));
+                    assert_eq!(debugger.break_point_hits(0), 5);
+                    assert_eq!(debugger.break_point_hits(1), 10);
+
+                    debugger.remove_break_point(0);
+                    assert_eq!(debugger.break_point_condition(0), None);
+                    assert_eq!(
+                        debugger.break_point_hit_condition(0),
+                        Some(HitCondition::Multiple(3))
+                    );
+                }
+                _ => (),
+            }
+
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    engine.run("for x in 0..10 { print(x); debug(x); }")?;
+
+    assert_eq!(
+        *stops.lock().unwrap(),
+        [(0, 2), (1, 2), (0, 4), (1, 5), (0, 6), (0, 8), (1, 8)]
+    );
+
+    engine.register_debugger(
+        |_| Dynamic::UNIT,
+        |mut context, event, _, _, _| {
+            if let DebuggerEvent::Start = event {
+                let engine = context.engine();
+                let debugger = &mut context.global_runtime_state_mut().debugger;
+
+                debugger
+                    .break_points_mut()
+                    .push(BreakPoint::AtFunctionName {
+                        name: "print".into(),
+                        enabled: true,
+                    });
+                debugger.set_break_point_condition(engine, 0, "x + 1")?;
+            }
+
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    assert!(matches!(
+        *engine
+            .run("let x = 1; print(x);")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_debugger_watches() -> Result<(), Box<EvalAltResult>> {
+    use rhai::debugger::{BreakPoint, DebuggerCommand, DebuggerEvent};
+    use std::sync::{Arc, Mutex};
+
+    let stops = Arc::new(Mutex::new(Vec::<(INT, String)>::new()));
+    let stops2 = stops.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_debugger(
+        |_| Dynamic::UNIT,
+        move |mut context, event, _, _, _| {
+            let engine = context.engine();
+            let debugger = &mut context.global_runtime_state_mut().debugger;
+
+            match event {
+                DebuggerEvent::Start => {
+                    debugger
+                        .break_points_mut()
+                        .push(BreakPoint::AtVariableChange {
+                            name: "x".into(),
+                            enabled: true,
+                        });
+                    debugger.add_watch(engine, "x * 2")?;
+                    debugger.add_watch(engine, "y")?;
+                }
+                DebuggerEvent::BreakPoint(..) => {
+                    let watches = debugger.watches();
+                    let x = watches[0].value().unwrap().unwrap().as_int().unwrap();
+                    let y = match watches[1].value().unwrap() {
+                        Ok(value) => value.to_string(),
+                        Err(err) => err.to_string(),
+                    };
+                    stops2.lock().unwrap().push((x, y));
+                }
+                _ => (),
+            }
+
+            Ok(DebuggerCommand::Continue)
+        },
+    );
+
+    engine.run(
+        "
+            let x = 1;
+            x = 1;
+            let y = 0;
+            x = 2;
+            y = 42;
+            x += 1;
+            y = 0;
+        ",
+    )?;
+
+    let stops = stops.lock().unwrap();
+
+    assert_eq!(stops.len(), 2);
+    assert_eq!(stops[0], (4, "0".to_string()));
+    assert_eq!(stops[1], (6, "42".to_string()));
+
+    Ok(())
+}
@@ -0,0 +1,118 @@
+#![cfg(not(feature = "no_position"))]
+use rhai::{Diagnostic, Engine, ParseErrorType, Severity};
+
+#[test]
+fn test_diagnostics_parse() {
+    let engine = Engine::new();
+
+    let (ast, diagnostics) = engine.compile_with_diagnostics(
+        "let a = 1 + ;\n\
+         if a > 0 { let b = 2 let c = 3; }\n\
+         let d = 4;\n\
+         a + d +",
+    );
+
+    assert!(ast.is_none());
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|d| d.span().start().line().unwrap())
+            .collect::<Vec<_>>(),
+        [1, 2, 4]
+    );
+    assert!(diagnostics.iter().all(Diagnostic::is_error));
+    assert_eq!(diagnostics[1].code(), "P0003");
+    assert_eq!(diagnostics[1].help(), Some("insert ';' here"));
+
+    let (ast, diagnostics) = engine.compile_with_diagnostics("let x = 42; x");
+    assert!(ast.is_some());
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn test_diagnostics_recovery() {
+    let engine = Engine::new();
+
+    let errors = engine
+        .compile_with_recovery(
+            "let a = 1;\n\
+             if a > 0 { let b = ; a += 1; let c = 2 let d = 3; }\n\
+             while a < 10 { if a > 5 { a += ; } a += 1; }\n\
+             a + +",
+        )
+        .unwrap_err();
+
+    assert_eq!(
+        errors
+            .iter()
+            .map(|err| err.position().line().unwrap())
+            .collect::<Vec<_>>(),
+        [2, 2, 3, 4]
+    );
+    assert!(matches!(
+        errors[1].err_type(),
+        ParseErrorType::MissingToken(..)
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let errors = engine
+            .compile_with_recovery(
+                "fn foo(x) { let y = x +; y }\n\
+                 fn bar(x) { x * }\n\
+                 foo(1) + bar(2)",
+            )
+            .unwrap_err();
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|err| err.position().line().unwrap())
+                .collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+
+    assert!(engine.compile_with_recovery("let x = 42; x").is_ok());
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_diagnostics_warnings() {
+    let engine = Engine::new();
+
+    let (ast, diagnostics) = engine.compile_with_diagnostics("fn foo(x) { let y = x; } foo(1)");
+
+    assert!(ast.is_some());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity(), Severity::Warning);
+    assert_eq!(diagnostics[0].code(), "W0001");
+
+    #[cfg(not(feature = "no_optimize"))]
+    {
+        let (ast, diagnostics) =
+            engine.compile_with_diagnostics("switch x { 1 if false => 1, 2 => 2 }");
+
+        assert!(ast.is_some());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), "W0002");
+        #[cfg(not(feature = "no_position"))]
+        assert_eq!(diagnostics[0].span().start(), rhai::Position::new(1, 17));
+    }
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_diagnostics_eval() {
+    let engine = Engine::new();
+
+    let err = engine.run("fn foo(x) { bar(x) }\nfoo(42);").unwrap_err();
+    let diagnostic = Diagnostic::from(&*err);
+
+    assert_eq!(diagnostic.code(), "E0007");
+    assert_eq!(diagnostic.span().start().line(), Some(1));
+    assert_eq!(diagnostic.labels().len(), 1);
+    assert_eq!(diagnostic.labels()[0].message(), "in call to function foo");
+    assert_eq!(diagnostic.labels()[0].span().start().line(), Some(2));
+    assert!(diagnostic.help().is_some());
+}
@@ -0,0 +1,146 @@
+#![cfg(not(feature = "no_object"))]
+use rhai::{CheckpointStore, Durable, Engine, EvalAltResult, MemoryCheckpointStore, Scope, INT};
+
+#[cfg(not(feature = "no_optimize"))]
+use rhai::OptimizationLevel;
+
+#[test]
+fn test_durable() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let store = MemoryCheckpointStore::new();
+
+    let ast = engine.compile(
+        r#"
+            let total = 0;
+            const STEP = 10;
+            total += STEP;
+            checkpoint("first");
+            total += STEP * input;
+            checkpoint();
+            total + 1
+        "#,
+    )?;
+
+    let status = engine.eval_durable("job", &mut Scope::new(), &ast, &store)?;
+    assert!(matches!(status, Durable::Suspended(label) if label.as_str() == "first"));
+
+    let checkpoint = store.load("job")?.unwrap();
+    assert_eq!(checkpoint.label(), "first");
+    assert_eq!(
+        checkpoint.get_variable("total").unwrap().as_int().unwrap(),
+        10
+    );
+
+    // Resume with a new engine and new input
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+    scope.push("input", 5 as INT);
+
+    let status = engine.eval_durable("job", &mut scope, &ast, &store)?;
+    assert!(matches!(status, Durable::Suspended(label) if label.is_empty()));
+    assert_eq!(scope.get_value::<INT>("total"), Some(60));
+    assert_eq!(scope.is_constant("STEP"), Some(true));
+
+    let status = engine.eval_durable("job", &mut Scope::new(), &ast, &store)?;
+    assert!(matches!(status, Durable::Completed(value) if value.as_int().unwrap() == 61));
+    assert!(store.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_durable_shadowing() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let store = MemoryCheckpointStore::new();
+
+    let ast = engine.compile(
+        r#"
+            let y = x + 1;
+            let x = "hello";
+            checkpoint();
+            `${x}:${y}`
+        "#,
+    )?;
+
+    let mut scope = Scope::new();
+    scope.push("x", 1 as INT);
+
+    assert!(engine
+        .eval_durable("job", &mut scope, &ast, &store)?
+        .is_suspended());
+
+    let checkpoint = store.load("job")?.unwrap();
+    assert_eq!(
+        checkpoint
+            .iter_variables()
+            .map(|(name, ..)| name)
+            .collect::<Vec<_>>(),
+        ["x", "y", "x"]
+    );
+
+    // The checkpoint wins over variables of the same names passed in by the host
+    let mut scope = Scope::new();
+    scope.push("y", 42 as INT);
+
+    let status = engine.eval_durable("job", &mut scope, &ast, &store)?;
+    assert!(
+        matches!(status, Durable::Completed(value) if value.clone().into_string().unwrap() == "hello:2")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_durable_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let store = MemoryCheckpointStore::new();
+
+    // Checkpoints must be top-level statements
+    let ast = engine.compile("let x = 1; if x > 0 { checkpoint(); } x")?;
+    assert!(engine
+        .eval_durable("bad", &mut Scope::new(), &ast, &store)
+        .is_err());
+
+    // The checkpoint must match the script
+    let ast = engine.compile(r#"checkpoint("a"); 1"#)?;
+    assert!(engine
+        .eval_durable("job", &mut Scope::new(), &ast, &store)?
+        .is_suspended());
+
+    let ast = engine.compile(r#"let x = 1; checkpoint("b"); 1"#)?;
+    assert!(engine
+        .eval_durable("job", &mut Scope::new(), &ast, &store)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_optimize"))]
+fn test_durable_without_optimization() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_optimization_level(OptimizationLevel::None);
+    let store = MemoryCheckpointStore::new();
+
+    let ast = engine.compile(
+        r#"
+            let x = 40;
+            checkpoint("middle");
+            x + 2
+        "#,
+    )?;
+
+    let status = engine.eval_durable("job", &mut Scope::new(), &ast, &store)?;
+    assert!(matches!(status, Durable::Suspended(label) if label.as_str() == "middle"));
+
+    let status = engine.eval_durable("job", &mut Scope::new(), &ast, &store)?;
+    assert!(matches!(status, Durable::Completed(value) if value.as_int().unwrap() == 42));
+
+    // Checkpoints must still be top-level statements
+    let ast = engine.compile("let x = 1; if x > 0 { checkpoint(); } x")?;
+    assert!(engine
+        .eval_durable("bad", &mut Scope::new(), &ast, &store)
+        .is_err());
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+#![cfg(not(feature = "no_module"))]
+use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+
+#[test]
+fn test_enums() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = "
+        enum Color { Red, Green, Blue }
+
+        let c = Color::Green;
+
+        switch c {
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Blue => 3,
+        }
+    ";
+
+    assert_eq!(engine.eval::<INT>(script)?, 2);
+
+    assert!(engine.eval::<bool>(
+        r#"
+            enum Color { Red, Green, Blue }
+            Color::is_variant(Color::Red) && !Color::is_variant("Pink") && !Color::is_variant(42)
+        "#
+    )?);
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<String>(
+            "enum Color { Red, Green, Blue, } Color::Blue + Color::variants().len()"
+        )?,
+        "Blue3"
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<String>(
+            "
+                fn paint() { Color::Red }
+                enum Color { Red, Green, Blue }
+                paint()
+            "
+        )?,
+        "Red"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_enums_errors() {
+    let engine = Engine::new();
+
+    assert!(matches!(
+        engine
+            .compile("enum Color { Red, Green } Color::Grene")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::VariableUndefined(v) if v == "Color::Grene"
+    ));
+
+    assert!(matches!(
+        engine
+            .compile("enum Color { Red, Red }")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::DuplicatedVariable(v) if v == "Red"
+    ));
+
+    assert!(matches!(
+        engine
+            .compile("if true { enum Color { Red } }")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::WrongEnumDefinition
+    ));
+}
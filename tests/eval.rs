@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, LexError, ParseErrorType, Scope, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, LexError, ParseErrorType, Scope, TypedEvalError, INT};
 
 #[test]
 fn test_eval() -> Result<(), Box<EvalAltResult>> {
@@ -176,3 +176,43 @@ fn test_eval_disabled() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_eval_typed() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval_typed::<INT>("40 + 2")?, 42);
+    assert_eq!(engine.eval_typed::<String>(r#""hello""#)?, "hello");
+    assert!(engine.eval_typed::<Dynamic>("true")?.as_bool().unwrap());
+
+    match engine.eval_typed::<bool>("40 + 2") {
+        Err(TypedEvalError::MismatchOutputType(expected, actual, value)) => {
+            assert_eq!(expected, "bool");
+            assert_eq!(actual, engine.map_type_name(std::any::type_name::<INT>()));
+            assert_eq!(value.as_int().unwrap(), 42);
+        }
+        r => panic!("{:?}", r),
+    }
+
+    let mut scope = Scope::new();
+    scope.push("x", 40 as INT);
+
+    match engine.eval_expression_typed_with_scope::<String>(&mut scope, "x + 2") {
+        Err(TypedEvalError::MismatchOutputType(.., value)) => {
+            assert_eq!(value.as_int().unwrap(), 42)
+        }
+        r => panic!("{:?}", r),
+    }
+
+    match engine.eval_typed::<INT>("x") {
+        Err(TypedEvalError::Eval(err)) => {
+            assert!(matches!(*err, EvalAltResult::ErrorVariableNotFound(..)))
+        }
+        r => panic!("{:?}", r),
+    }
+
+    let err: Box<EvalAltResult> = engine.eval_typed::<bool>("42").unwrap_err().into();
+    assert!(matches!(*err, EvalAltResult::ErrorMismatchOutputType(..)));
+
+    Ok(())
+}
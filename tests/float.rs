@@ -92,3 +92,47 @@ fn test_float_func() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "decimal")]
+fn test_float_decimal_policy() -> Result<(), Box<EvalAltResult>> {
+    use rhai::DecimalFloatPolicy;
+    use rust_decimal::Decimal;
+    use std::convert::TryFrom;
+
+    let mut engine = Engine::new();
+
+    assert!(matches!(
+        *engine
+            .eval::<Decimal>("let x = to_decimal(1); x + 0.5")
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(..)
+    ));
+
+    engine.set_decimal_float_policy(DecimalFloatPolicy::ToDecimal);
+
+    assert_eq!(
+        engine.eval::<Decimal>("let x = to_decimal(1); x + 0.5")?,
+        Decimal::new(15, 1)
+    );
+    assert!(engine.eval::<bool>("let x = to_decimal(1); 0.5 < x")?);
+
+    engine.set_decimal_float_policy(DecimalFloatPolicy::ToFloat);
+
+    assert!((engine.eval::<FLOAT>("let x = to_decimal(1); x * 1.5")? - 1.5).abs() < EPSILON);
+    assert!(engine.eval::<bool>("let x = to_decimal(1); x == 1.0")?);
+
+    // Registered overloads take precedence over the policy, even under Fast Operators mode
+    engine.register_fn("+", |x: Decimal, y: FLOAT| {
+        x * Decimal::try_from(y).unwrap()
+    });
+
+    assert!(engine.fast_operators());
+    assert_eq!(
+        engine.eval::<Decimal>("let x = to_decimal(3); x + 0.5")?,
+        Decimal::new(15, 1)
+    );
+    assert!((engine.eval::<FLOAT>("let x = to_decimal(3); 0.5 + x")? - 3.5).abs() < EPSILON);
+
+    Ok(())
+}
@@ -142,6 +142,40 @@ fn test_fn_ptr_call() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_fn_ptr_checked() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.register_fn("add", |x: INT, y: INT| x + y);
+
+    let ast = engine.compile("fn foo(x) { x } fn foo(x, y, z) { x + y + z }")?;
+
+    let fn_ptr = FnPtr::try_new_checked("foo", 3, &engine, &ast)?;
+    assert_eq!(
+        fn_ptr.call::<INT>(&engine, &ast, (1 as INT, 2 as INT, 3 as INT))?,
+        6
+    );
+
+    let fn_ptr = FnPtr::try_new_checked("add", 2, &engine, &ast)?;
+    assert_eq!(
+        fn_ptr.call::<INT>(&engine, &ast, (40 as INT, 2 as INT))?,
+        42
+    );
+
+    assert!(matches!(
+        *FnPtr::try_new_checked("foo", 2, &engine, &ast).expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, ..) if f == "foo (2 parameters; found: 1, 3)"
+    ));
+    assert!(matches!(
+        *FnPtr::try_new_checked("bar", 1, &engine, &ast).expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(f, ..) if f == "bar"
+    ));
+    assert!(FnPtr::try_new_checked("1 + 2", 0, &engine, &ast).is_err());
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_closure"))]
 fn test_fn_ptr_make_closure() -> Result<(), Box<EvalAltResult>> {
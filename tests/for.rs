@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, Module, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, Module, INT};
 
 #[cfg(not(feature = "no_float"))]
 use rhai::FLOAT;
@@ -372,6 +372,72 @@ fn test_for_module_iterator() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[derive(Debug, Clone)]
+struct MyGrid {
+    width: INT,
+    cells: Vec<INT>,
+}
+
+#[test]
+fn test_for_iterator_fn() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_type::<MyGrid>()
+        .register_fn("new_grid", || MyGrid {
+            width: 2,
+            cells: vec![1, 2, 3, 4],
+        })
+        .register_iterator_fn(|grid: MyGrid| Box::new(grid.cells.into_iter().map(Dynamic::from)));
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let sum = 0;
+                for (x, i) in new_grid() { sum += x * i; }
+                sum
+            "
+        )?,
+        20
+    );
+
+    engine.register_indexed_iterator_fn(|grid: MyGrid| {
+        let width = grid.width;
+
+        Box::new(
+            grid.cells
+                .into_iter()
+                .enumerate()
+                .map(move |(i, x)| ((i as INT / width).into(), x.into())),
+        )
+    });
+
+    // Iterators of index/value pairs take precedence
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let sum = 0;
+                for (x, row) in new_grid() { sum += x * row; }
+                sum
+            "
+        )?,
+        7
+    );
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let sum = 0;
+                for x in new_grid() { sum += x; }
+                sum
+            "
+        )?,
+        10
+    );
+
+    Ok(())
+}
+
 #[test]
 #[cfg(not(feature = "no_index"))]
 #[cfg(not(feature = "no_closure"))]
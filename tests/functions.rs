@@ -1,5 +1,8 @@
 #![cfg(not(feature = "no_function"))]
-use rhai::{Engine, EvalAltResult, FnNamespace, Module, NativeCallContext, Shared, INT};
+use rhai::{
+    Engine, EvalAltResult, EvalSession, FnNamespace, FnVisibility, Module, NativeCallContext,
+    ParseErrorType, Scope, Shared, INT,
+};
 
 #[cfg(not(feature = "no_object"))]
 #[test]
@@ -223,3 +226,333 @@ fn test_functions_bang() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_functions_replace() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut ast = engine.compile(
+        "
+            fn foo(x) { x + 1 }
+            fn foo() { 0 }
+            fn bar(x) { foo(x) * 2 }
+            bar(20)
+        ",
+    )?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    let patch = engine.compile("fn foo(x) { x - 1 }")?;
+
+    assert!(!ast.replace_fn("foo", 2, &patch));
+    assert!(!ast.replace_fn("baz", 1, &patch));
+    assert!(ast.replace_fn("foo", 1, &patch));
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 38);
+    assert_eq!(ast.iter_functions().count(), 3);
+
+    // Sessions discard functions cached before the patch
+    let session = &mut EvalSession::new(&engine);
+    let scope = &mut Scope::new();
+
+    assert_eq!(
+        engine.call_fn_with_session::<INT>(session, scope, &ast, "bar", (20 as INT,))?,
+        38
+    );
+
+    let patch = engine.compile("fn foo(x) { x }")?;
+    assert!(ast.replace_fn("foo", 1, &patch));
+
+    assert_eq!(
+        engine.call_fn_with_session::<INT>(session, scope, &ast, "bar", (20 as INT,))?,
+        40
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_replace_running() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            fn foo(x) { x + 1 }
+            let a = foo(1);
+            patch();
+            let b = foo(1);
+            a * 100 + b
+        ",
+    )?;
+    let patch = engine.compile("fn foo(x) { x * 10 }")?;
+
+    // Clones share replacements with the original
+    let clone = ast.clone();
+    let original = std::sync::Arc::new(std::sync::Mutex::new(ast));
+    let ast = original.clone();
+
+    engine.register_fn("patch", move || {
+        original.lock().unwrap().replace_fn("foo", 1, &patch);
+    });
+
+    // The evaluation running on the clone picks up the new version of 'foo'
+    assert_eq!(engine.eval_ast::<INT>(&clone)?, 210);
+    assert_eq!(engine.eval_ast::<INT>(&clone)?, 1010);
+    let ast = ast.lock().unwrap().clone();
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1010);
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_retain_used() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut ast = engine.compile(
+        r#"
+            fn init() { let f = Fn("by_ptr"); call(f) + apply(|x| by_closure(x)) }
+            fn apply(f) { call(f, 1) }
+            fn by_ptr() { 1 }
+            fn by_closure(x) { x + 1 }
+            fn by_closure(x, y) { x + y }
+            fn from_global() { 40 }
+            fn unused() { unused_too() }
+            fn unused_too() { 0 }
+            from_global()
+        "#,
+    )?;
+
+    ast.retain_functions_used_by(["init"]);
+
+    let mut names = ast
+        .iter_functions()
+        .map(|f| f.name)
+        .filter(|name| !name.starts_with("anon$"))
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+
+    assert_eq!(
+        names,
+        [
+            "apply",
+            "by_closure",
+            "by_closure",
+            "by_ptr",
+            "from_global",
+            "init"
+        ]
+    );
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 40);
+    assert_eq!(
+        engine.call_fn::<INT>(&mut Scope::new(), &ast, "init", ())?,
+        3
+    );
+
+    ast.retain_functions_used_by(None::<&str>);
+
+    assert_eq!(ast.iter_functions().count(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_sources() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile_sources(&[
+        ("main.rhai", "let x = double(20); x + one()"),
+        ("util.rhai", "fn double(x) { x * 2 } fn one() { 1 }"),
+        ("more.rhai", "fn double(x, y) { x * y }"),
+    ])?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 41);
+    assert_eq!(ast.iter_functions().count(), 3);
+
+    assert!(matches!(
+        *engine.compile_sources(&[
+            ("a.rhai", "fn foo(x) { x }"),
+            ("b.rhai", "fn foo(y) { y + 1 }"),
+        ]).unwrap_err(),
+        EvalAltResult::ErrorInModule(ref name, ref err, ..)
+            if name == "b.rhai"
+            && matches!(**err, EvalAltResult::ErrorParsing(ParseErrorType::FnDuplicatedDefinition(ref f, 1), ..) if f == "foo")
+    ));
+
+    assert!(matches!(
+        *engine.compile_sources(&[("a.rhai", "let x = 1;"), ("b.rhai", "let = 2;")]).unwrap_err(),
+        EvalAltResult::ErrorInModule(ref name, ref err, ..)
+            if name == "b.rhai" && matches!(**err, EvalAltResult::ErrorParsing(..))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_visibility() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut ast = engine.compile(
+        "
+            fn entry(x) { helper(x) * 2 }
+            fn helper(x) { x + 1 }
+            fn call_entry() { entry(1) }
+        ",
+    )?;
+
+    assert_eq!(ast.fn_visibility("entry", 1), Some(FnVisibility::All));
+    assert_eq!(ast.fn_visibility("entry", 2), None);
+
+    assert!(ast.set_fn_visibility("entry", 1, FnVisibility::HostOnly));
+    assert!(ast.set_fn_visibility("helper", 1, FnVisibility::ScriptOnly));
+    assert!(!ast.set_fn_visibility("unknown", 0, FnVisibility::HostOnly));
+
+    let scope = &mut Scope::new();
+
+    assert_eq!(
+        engine.call_fn::<INT>(scope, &ast, "entry", (20 as INT,))?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .call_fn::<INT>(scope, &ast, "helper", (20 as INT,))
+            .unwrap_err(),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+    assert!(matches!(
+        *engine.call_fn::<INT>(scope, &ast, "call_entry", ()).unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(.., ref err, _)
+            if matches!(**err, EvalAltResult::ErrorFunctionNotFound(ref f, ..) if f.starts_with("entry"))
+    ));
+    assert!(matches!(
+        *engine
+            .eval_ast::<INT>(&ast.merge(&engine.compile("Fn(\"entry\").call(1)")?))
+            .unwrap_err(),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+
+    // Visibility survives hot-patching
+    assert!(ast.replace_fn("entry", 1, &engine.compile("fn entry(x) { x }")?));
+    assert_eq!(ast.fn_visibility("entry", 1), Some(FnVisibility::HostOnly));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_functions_call_stack() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("fn f() { call_stack().len() } f()")?, 0);
+
+    engine.set_track_call_stack(true);
+
+    engine.register_fn("depth", |ctx: NativeCallContext| {
+        ctx.call_stack().len() as INT
+    });
+
+    assert_eq!(engine.eval::<INT>("call_stack().len()")?, 0);
+    assert_eq!(engine.eval::<INT>("depth()")?, 0);
+
+    let script = "
+        fn inner() { call_stack() }
+        fn outer(x) { inner() }
+        outer(1)
+    ";
+
+    let stack = engine.eval::<rhai::Array>(script)?;
+    assert_eq!(stack.len(), 2);
+
+    let outer = stack[0].clone().cast::<rhai::Map>();
+    assert_eq!(outer["fn_name"].clone().into_string().unwrap(), "outer");
+    assert_eq!(outer["line"].as_int().unwrap(), 4);
+    assert!(!outer.contains_key("source"));
+
+    let inner = stack[1].clone().cast::<rhai::Map>();
+    assert_eq!(inner["fn_name"].clone().into_string().unwrap(), "inner");
+    assert_eq!(inner["line"].as_int().unwrap(), 3);
+    assert_eq!(inner["position"].as_int().unwrap(), 23);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn f(n) { if n > 0 { f(n - 1) } else { depth() } }
+                f(3) + call_stack().len()
+            "
+        )?,
+        4
+    );
+
+    // The call stack is unwound after errors
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn bad() { throw 42; }
+                try { bad() } catch { }
+                depth()
+            "
+        )?,
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_functions_tail_calls() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let script = "
+        fn sum(n, acc) { if n == 0 { acc } else { sum(n - 1, acc + n) } }
+        sum(10000, 0)
+    ";
+
+    #[cfg(not(feature = "unchecked"))]
+    assert!(matches!(
+        *engine.eval::<INT>(script).unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(..) | EvalAltResult::ErrorStackOverflow(..)
+    ));
+
+    engine.set_optimize_tail_calls(true);
+
+    assert_eq!(engine.eval::<INT>(script)?, 50005000);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn count(n, acc) {
+                    if n == 0 { return acc; }
+                    let x = n % 2;
+                    return count(n - 1, acc + x);
+                }
+                count(10000, 0)
+            "
+        )?,
+        5000
+    );
+
+    // Calls that are not in tail position still recurse
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn fib(n) { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } }
+                fib(7)
+            "
+        )?,
+        13
+    );
+
+    // Calls in try blocks are not in tail position
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn g(n) { if n < 0 { throw 42; } try { return g(n - 1); } catch { return n; } }
+                g(3)
+            "
+        )?,
+        0
+    );
+
+    Ok(())
+}
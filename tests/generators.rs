@@ -0,0 +1,204 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+
+#[test]
+fn test_generators() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn numbers(n) {
+                    let x = 0;
+                    while x < n {
+                        x += 1;
+                        yield x;
+                    }
+                }
+
+                let sum = 0;
+                for (x, i) in numbers(4) {
+                    sum += x * 10 + i;
+                }
+                sum
+            "
+        )?,
+        106
+    );
+
+    // The body only runs on demand
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn naturals() {
+                    let n = 0;
+                    loop {
+                        n += 1;
+                        yield n;
+                    }
+                }
+
+                let total = 0;
+                for n in naturals() {
+                    if n > 5 { break; }
+                    total += n;
+                }
+                total
+            "
+        )?,
+        15
+    );
+
+    // Nested loops, branches and generators
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                fn pairs(n) {
+                    for x in 0..n {
+                        if x % 2 == 0 {
+                            for y in 0..x {
+                                yield x * 10 + y;
+                            }
+                        } else {
+                            yield x;
+                        }
+                    }
+                    return;
+                    yield 999;
+                }
+                fn labels(g) {
+                    for x in g {
+                        yield "<" + x + ">";
+                    }
+                }
+
+                let s = "";
+                for x in labels(pairs(5)) { s += x; }
+                s
+            "#
+        )?,
+        "<1><20><21><3><40><41><42><43>"
+    );
+
+    // Local variables survive suspension
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn counter(start) {
+                    let x = start;
+                    do {
+                        { let y = x * 2; yield y; }
+                        x += 1;
+                    } until x > start + 2;
+                }
+
+                let r = 0;
+                for v in counter(10) { r = r * 100 + v; }
+                r
+            "
+        )?,
+        202224
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_generators_collect() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<rhai::Array>(
+                "
+                    fn squares(n) {
+                        for x in 1..=n {
+                            yield x * x;
+                        }
+                    }
+                    squares(4).collect()
+                "
+            )?
+            .into_iter()
+            .map(|v| v.as_int().unwrap())
+            .collect::<Vec<_>>(),
+        [1, 4, 9, 16]
+    );
+
+    assert_eq!(
+        engine
+            .eval::<rhai::Array>(
+                "
+                    fn naturals() {
+                        let n = 0;
+                        loop { n += 1; yield n; }
+                    }
+                    let gen = naturals();
+                    let a = gen.take(3);
+                    let b = gen;
+                    a + b.take(2)
+                "
+            )?
+            .into_iter()
+            .map(|v| v.as_int().unwrap())
+            .collect::<Vec<_>>(),
+        [1, 2, 3, 4, 5]
+    );
+
+    // Finished generators yield nothing more
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn one() { yield 1; }
+                let g = one();
+                g.collect();
+                g.collect().len()
+            "
+        )?,
+        0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_generators_errors() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        *engine.compile("yield 42;").unwrap_err().err_type(),
+        ParseErrorType::WrongYield
+    );
+    assert_eq!(
+        *engine
+            .compile("fn foo() { try { yield 1; } catch { } }")
+            .unwrap_err()
+            .err_type(),
+        ParseErrorType::WrongYield
+    );
+    assert_eq!(
+        *engine
+            .compile("fn foo() { let x = { yield 1; 2 }; }")
+            .unwrap_err()
+            .err_type(),
+        ParseErrorType::WrongYield
+    );
+
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    fn fail() {
+                        yield 1;
+                        throw 42;
+                    }
+                    for x in fail() { }
+                "
+            )
+            .unwrap_err(),
+        EvalAltResult::ErrorInFunctionCall(..)
+    ));
+
+    Ok(())
+}
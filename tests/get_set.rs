@@ -406,3 +406,80 @@ fn test_get_set_elvis() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_get_set_indexer_fallback() -> Result<(), Box<EvalAltResult>> {
+    type MyMap = std::collections::BTreeMap<String, INT>;
+
+    #[derive(Debug, Clone, Default)]
+    struct TestStruct {
+        a: INT,
+    }
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<MyMap>("MyMap")
+        .register_fn("new_map", || MyMap::new())
+        .register_indexer_get(|map: &mut MyMap, index: &str| {
+            map.get(index).cloned().unwrap_or_default()
+        })
+        .register_indexer_set(|map: &mut MyMap, index: &str, value: INT| {
+            map.insert(index.to_string(), value);
+        })
+        .register_type_with_name::<TestStruct>("TestStruct")
+        .register_fn("new_ts", TestStruct::default)
+        .register_get_set(
+            "a",
+            |t: &mut TestStruct| t.a,
+            |t: &mut TestStruct, value: INT| t.a = value,
+        );
+
+    // Properties without getters or setters are repeatedly accessed via the indexer
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let m = new_map();
+                m.a = 0;
+                for i in 0..10 { m.a += i; }
+                m.a
+            "
+        )?,
+        45
+    );
+
+    // The same property with getters and setters on another type
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let m = new_map();
+                let t = new_ts();
+                m.a = 1;
+                for i in 0..5 {
+                    t.a += m.a;
+                    m.a += 1;
+                }
+                t.a + m.a
+            "
+        )?,
+        21
+    );
+
+    // Without an indexer either, the error is still about the missing getter
+    assert!(matches!(
+        *engine
+            .run(
+                "
+                    let t = new_ts();
+                    for i in 0..2 {
+                        if i == 0 { try { t.b } catch {} } else { t.b }
+                    }
+                "
+            )
+            .expect_err("should error"),
+        EvalAltResult::ErrorDotExpr(..)
+    ));
+
+    Ok(())
+}
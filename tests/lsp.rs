@@ -0,0 +1,120 @@
+#![cfg(feature = "lsp")]
+#![cfg(not(feature = "no_position"))]
+use rhai::lsp::{analyze, CompletionKind, SymbolKind, TokenKind};
+use rhai::{Engine, Position};
+
+#[test]
+fn test_lsp_tokens() {
+    let engine = Engine::new();
+
+    let analysis = analyze(&engine, "let x = 42; // answer\nx + \"hello\"");
+
+    assert_eq!(
+        analysis
+            .tokens()
+            .iter()
+            .map(|t| t.kind())
+            .collect::<Vec<_>>(),
+        [
+            TokenKind::Keyword,
+            TokenKind::Identifier,
+            TokenKind::Symbol,
+            TokenKind::Number,
+            TokenKind::Symbol,
+            TokenKind::Comment,
+            TokenKind::Identifier,
+            TokenKind::Symbol,
+            TokenKind::String,
+        ]
+    );
+
+    let token = analysis.token_at(Position::new(2, 1)).unwrap();
+    assert_eq!(token.text(), "x");
+    assert_eq!(token.span().start(), Position::new(2, 1));
+    assert!(analysis.diagnostics().is_empty());
+}
+
+#[test]
+fn test_lsp_symbols() {
+    let engine = Engine::new();
+
+    let analysis = analyze(
+        &engine,
+        "const LIMIT = 10;\n\
+         let sum = 0;\n\
+         for i in 0..LIMIT { let sq = i * i; sum += sq; }\n\
+         sum",
+    );
+
+    assert_eq!(
+        analysis
+            .symbols()
+            .iter()
+            .map(|s| (s.name(), s.kind()))
+            .collect::<Vec<_>>(),
+        [
+            ("LIMIT", SymbolKind::Constant),
+            ("sum", SymbolKind::Variable),
+            ("i", SymbolKind::LoopVariable),
+            ("sq", SymbolKind::Variable),
+        ]
+    );
+
+    // `sq` is only visible within the loop body
+    let inside = Position::new(3, 40);
+    let outside = Position::new(4, 1);
+    assert!(analysis.visible_symbols(inside).any(|s| s.name() == "sq"));
+    assert!(!analysis.visible_symbols(outside).any(|s| s.name() == "sq"));
+
+    let symbol = analysis.definition_at(outside).unwrap();
+    assert_eq!(symbol.name(), "sum");
+    assert_eq!(symbol.position(), Position::new(2, 5));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let analysis = analyze(&engine, "fn area(w, h) { w * h }\narea(2, 3)");
+
+        let symbol = analysis.definition_at(Position::new(2, 1)).unwrap();
+        assert_eq!(symbol.kind(), SymbolKind::Function);
+        assert_eq!(symbol.position(), Position::new(1, 4));
+
+        let symbol = analysis.definition_at(Position::new(1, 21)).unwrap();
+        assert_eq!(symbol.kind(), SymbolKind::Parameter);
+        assert_eq!(symbol.position(), Position::new(1, 12));
+    }
+}
+
+#[test]
+fn test_lsp_completions() {
+    let mut engine = Engine::new();
+    engine.register_fn("total_cost", |x: i64| x * 2);
+    engine.disable_symbol("throw");
+
+    let analysis = analyze(&engine, "let total = 1;\nlet t = to");
+
+    assert_eq!(analysis.diagnostics().len(), 0);
+
+    let completions = analysis.completions(&engine, Position::new(2, 11));
+    let labels: Vec<_> = completions.iter().map(|c| c.label()).collect();
+    assert!(labels.contains(&"total"));
+    assert!(labels.contains(&"total_cost"));
+    assert!(labels.contains(&"to_string"));
+    assert!(labels.iter().all(|label| label.starts_with("to")));
+
+    let cost = completions
+        .iter()
+        .find(|c| c.label() == "total_cost")
+        .unwrap();
+    assert_eq!(cost.kind(), CompletionKind::Function);
+    assert!(cost.detail().unwrap().starts_with("total_cost("));
+
+    let analysis = analyze(&engine, "let t = ");
+    let labels: Vec<_> = analysis
+        .completions(&engine, Position::new(1, 9))
+        .iter()
+        .filter(|c| c.kind() == CompletionKind::Keyword)
+        .map(|c| c.label().to_string())
+        .collect();
+    assert!(labels.contains(&"let".to_string()));
+    assert!(!labels.contains(&"throw".to_string()));
+}
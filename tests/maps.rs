@@ -326,3 +326,80 @@ fn test_map_oop() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_map_scope() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let mut scope = Scope::new();
+    scope.push("x", 1 as INT);
+    scope.push("x", 40 as INT);
+    scope.push_constant("y", 2 as INT);
+
+    let (map, metadata) = scope.to_map_with_metadata();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["x"].as_int().unwrap(), 40);
+    assert!(metadata.constants.contains("y"));
+    assert!(!metadata.constants.contains("x"));
+
+    let mut scope = Scope::from_map_with_metadata(map.clone(), &metadata);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x + y")?, 42);
+    assert!(engine.run_with_scope(&mut scope, "y = 0").is_err());
+
+    let mut scope = Scope::from_map(map);
+    engine.run_with_scope(&mut scope, "y = 0; x += 2")?;
+    assert_eq!(scope.to_map()["x"].as_int().unwrap(), 42);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_map_merge_resolver() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let map = engine.eval::<Map>(
+        "
+            let m = #{a: 1, b: 2};
+            let n = #{a: 41, c: 3};
+            m.merge(n, |key, v1, v2| if key == \"a\" { v1 + v2 } else { v2 })
+        ",
+    )?;
+    assert_eq!(map["a"].as_int().unwrap(), 42);
+    assert_eq!(map["b"].as_int().unwrap(), 2);
+    assert_eq!(map["c"].as_int().unwrap(), 3);
+
+    let map = engine.eval::<Map>(
+        "
+            let m = #{a: 1, b: #{x: 1, y: 2}};
+            let n = #{a: 42, b: #{y: 0, z: 3}};
+            m.deep_merge(n)
+        ",
+    )?;
+    assert_eq!(map["a"].as_int().unwrap(), 42);
+    let b = map["b"].read_lock::<Map>().unwrap();
+    assert_eq!(b.len(), 3);
+    assert_eq!(b["x"].as_int().unwrap(), 1);
+    assert_eq!(b["y"].as_int().unwrap(), 0);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let m = #{a: #{b: #{c: 40}}};
+                let n = #{a: #{b: #{c: 2}}};
+                let r = m.deep_merge(n, |key, v1, v2| v1 + v2);
+                r.a.b.c
+            "
+        )?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<Map>("#{a: 1}.merge(#{a: 2}, |k, v1, v2| throw k)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorInFunctionCall(..)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "metadata")]
+#![cfg(not(feature = "no_module"))]
+use rhai::{Engine, Module, Shared, INT};
+
+#[derive(Debug, Clone)]
+struct Point {
+    x: INT,
+}
+
+#[derive(Debug, Clone)]
+struct Color;
+
+fn make_engine() -> Engine {
+    let mut engine = Engine::new();
+
+    engine.register_type_with_name_and_comments::<Color, _>("Color", ["/// A color."]);
+
+    let mut inner = Module::new();
+    inner.set_custom_type_with_comments::<Point, _>(
+        "Point",
+        [
+            "/// A point.",
+            "///",
+            "/// ```",
+            "/// let p = geo::shapes::origin();",
+            "/// ```",
+        ],
+    );
+    inner.set_var("ORIGIN", Point { x: 0 });
+    inner.set_native_fn("origin", || Ok(Point { x: 0 }));
+
+    let mut geo = Module::new();
+    geo.set_sub_module("shapes", inner);
+
+    engine.register_static_module("geo", Shared::new(geo));
+    engine.register_fn("get_x", |p: &mut Point| p.x);
+
+    engine
+}
+
+#[test]
+fn test_metadata_custom_types() -> serde_json::Result<()> {
+    let engine = make_engine();
+
+    let json: serde_json::Value = serde_json::from_str(&engine.gen_fn_metadata_to_json(false)?)?;
+
+    let global_types = json["customTypes"].as_array().unwrap();
+    assert_eq!(global_types.len(), 1);
+    assert_eq!(global_types[0]["name"], "Color");
+    assert_eq!(global_types[0]["docComments"][0], "/// A color.");
+
+    let shapes = &json["modules"]["geo"]["modules"]["shapes"];
+
+    let types = shapes["customTypes"].as_array().unwrap();
+    assert_eq!(types.len(), 1);
+    assert_eq!(types[0]["name"], "geo::shapes::Point");
+    assert_eq!(types[0]["typeName"], std::any::type_name::<Point>());
+    assert_eq!(types[0]["docComments"].as_array().unwrap().len(), 5);
+
+    let constants = shapes["constants"].as_array().unwrap();
+    assert_eq!(constants[0]["name"], "ORIGIN");
+    assert_eq!(constants[0]["type"], "geo::shapes::Point");
+
+    // Functions registered with the engine refer to the qualified type name
+    let get_x = json["functions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == "get_x")
+        .unwrap();
+    assert_eq!(get_x["params"][0]["type"], "&mut geo::shapes::Point");
+
+    // The display name for scripts is not qualified
+    assert_eq!(
+        engine
+            .eval::<String>("type_of(geo::shapes::origin())")
+            .unwrap(),
+        "Point"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "internals")]
+#[test]
+fn test_definitions_custom_types() {
+    let engine = make_engine();
+
+    let definitions = engine.definitions().include_standard_packages(false);
+
+    assert!(definitions
+        .static_module()
+        .contains("/// A color.\ntype Color;"));
+    assert!(definitions
+        .static_module()
+        .contains("fn get_x(_: geo::shapes::Point) -> int;"));
+
+    let (name, geo) = definitions.modules().next().unwrap();
+    assert_eq!(name, "geo");
+    assert!(geo.contains(
+        "/// A point.\n///\n/// ```\n/// let p = geo::shapes::origin();\n/// ```\ntype Point;"
+    ));
+    assert!(geo.contains("const ORIGIN: geo::shapes::Point;"));
+}
+
+#[test]
+fn test_metadata_plugin_custom_types() {
+    use rhai::plugin::*;
+
+    #[export_module]
+    mod geo_module {
+        /// A point on a plane.
+        pub type Point = super::Point;
+
+        pub type Color = super::Color;
+    }
+
+    let module = exported_module!(geo_module);
+
+    let comments = module
+        .get_custom_type_comments(std::any::type_name::<Point>())
+        .unwrap();
+    assert_eq!(comments.len(), 1);
+    assert_eq!(&*comments[0], "/// A point on a plane.");
+
+    assert!(module
+        .get_custom_type_comments(std::any::type_name::<Color>())
+        .unwrap()
+        .is_empty());
+}
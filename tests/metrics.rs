@@ -0,0 +1,52 @@
+use rhai::{Engine, EvalAltResult, MetricsSink, INT};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Default)]
+struct Metrics(Arc<Mutex<BTreeMap<String, u64>>>);
+
+impl MetricsSink for Metrics {
+    fn increment_counter(
+        &self,
+        name: &'static str,
+        label: Option<(&'static str, &str)>,
+        value: u64,
+    ) {
+        let key = match label {
+            Some((label, label_value)) => format!("{name}{{{label}={label_value}}}"),
+            None => name.to_string(),
+        };
+        *self.0.lock().unwrap().entry(key).or_default() += value;
+    }
+    fn set_gauge(&self, name: &'static str, value: u64) {
+        self.0.lock().unwrap().insert(name.to_string(), value);
+    }
+}
+
+#[test]
+fn test_metrics() -> Result<(), Box<EvalAltResult>> {
+    let metrics = Metrics::default();
+    let mut engine = Engine::new();
+    engine.set_metrics_sink(metrics.clone());
+
+    assert_eq!(
+        engine.eval::<INT>("let x = 1; let y = 2; let z = -3; x + y + abs(z)")?,
+        6
+    );
+    assert!(engine.run("let x = 1; x + y").is_err());
+    assert!(engine.eval::<INT>("throw 42").is_err());
+
+    let metrics = metrics.0.lock().unwrap();
+
+    assert_eq!(metrics["rhai_evaluations_total"], 3);
+    assert_eq!(metrics["rhai_errors_total{kind=ErrorVariableNotFound}"], 1);
+    assert_eq!(metrics["rhai_errors_total{kind=ErrorRuntime}"], 1);
+    // Operations are not counted under `unchecked`
+    #[cfg(not(feature = "unchecked"))]
+    assert!(metrics["rhai_operations_total"] > 0);
+    assert!(metrics["rhai_fn_cache_misses_total"] > 0);
+    assert!(metrics.contains_key("rhai_scope_size_peak"));
+    assert!(metrics.contains_key("rhai_interned_strings"));
+
+    Ok(())
+}
@@ -610,6 +610,49 @@ fn test_module_file() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[cfg(not(feature = "no_std"))]
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn test_module_invalidate() -> Result<(), Box<EvalAltResult>> {
+    use rhai::module_resolvers::FileModuleResolver;
+    use std::sync::{Arc, RwLock};
+
+    let dir = std::env::temp_dir().join("rhai_test_module_invalidate");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("config.rhai"), "export const VERSION = 1;").unwrap();
+
+    let invalidated = Arc::new(RwLock::new(Vec::new()));
+    let log = invalidated.clone();
+
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(FileModuleResolver::new_with_path(&dir))
+        .on_module_invalidated(move |path| log.write().unwrap().push(path.to_string()));
+
+    let ast = engine.compile(r#"import "config" as config; config::VERSION"#)?;
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1);
+
+    std::fs::write(dir.join("config.rhai"), "export const VERSION = 2;").unwrap();
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 1);
+    assert!(engine.invalidate_module("config"));
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 2);
+    assert!(!engine.invalidate_module("hello"));
+    assert_eq!(*invalidated.read().unwrap(), ["config", "hello"]);
+
+    let mut ast = ast;
+    let mut module = Module::new();
+    module.set_var("VERSION", 42 as INT);
+    assert!(ast.patch_module("config", module).is_none());
+
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    Ok(())
+}
+
 #[cfg(not(feature = "no_function"))]
 #[test]
 fn test_module_environ() -> Result<(), Box<EvalAltResult>> {
@@ -679,3 +722,360 @@ fn test_module_dynamic() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_module_call_qualified() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let mut sub_module = Module::new();
+    sub_module.set_native_fn("add", |x: INT, y: INT| Ok(x + y));
+
+    let mut module = Module::new();
+    module.set_native_fn("inc", |x: INT| Ok(x + 1));
+    module.set_sub_module("math", sub_module);
+
+    engine.register_static_module("calc", module.into());
+
+    assert_eq!(
+        engine.eval::<INT>(r#"call_qualified("calc::inc", [41])"#)?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"call_qualified("calc::math::add", [40, 2])"#)?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                let table = ["calc::inc", "calc::math::add"];
+                call_qualified(table[0], [1]) + call_qualified(table[1], [20, 20])
+            "#
+        )?,
+        42
+    );
+
+    let mut resolver = StaticModuleResolver::new();
+    let mut module = Module::new();
+    module.set_native_fn("double", |x: INT| Ok(x * 2));
+    resolver.insert("hello", module);
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(r#"import "hello" as h; call_qualified("h::double", [21])"#)?,
+        42
+    );
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "hello" as h;
+                fn foo(x) { call_qualified("h::double", [x]) }
+                foo(21)
+            "#
+        )?,
+        42
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"call_qualified("calc::inc", [1, 2])"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"call_qualified("nowhere::inc", [1])"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorModuleNotFound(..)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<INT>(r#"call_qualified("inc", [1])"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    assert!(matches!(
+        engine
+            .compile("fn call_qualified(x) { x }")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::Reserved(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_module_constants_static() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let mut module = Module::new();
+    module.set_var("FOO", 1 as INT);
+    module.set_var("BAR", 2 as INT);
+
+    let mut sub_module = Module::new();
+    sub_module.set_var("BAZ", 3 as INT);
+    module.set_sub_module("inner", sub_module);
+
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("m", module.clone());
+    engine.set_module_resolver(resolver);
+
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                import "m" as m;
+
+                let x = 2;
+
+                switch x {
+                    m::FOO => 10,
+                    m::BAR | m::inner::BAZ => 42,
+                    _ => 0
+                }
+            "#
+        )?,
+        42
+    );
+
+    engine.register_static_module("n", module.into());
+
+    assert_eq!(
+        engine.eval::<INT>("switch 3 { n::FOO => 10, n::inner::BAZ => 42, _ => 0 }")?,
+        42
+    );
+
+    // Imports that cannot be resolved statically are looked up at run-time
+    assert!(matches!(
+        *engine
+            .compile(
+                r#"
+                    let path = "m";
+                    import path as m;
+                    switch 1 { m::FOO => 10, _ => 0 }
+                "#
+            )
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::ExprExpected(..)
+    ));
+    assert_eq!(
+        engine.eval::<INT>(r#"let path = "m"; import path as m; m::FOO + m::inner::BAZ"#)?,
+        4
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_module_static_lazy() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let mut engine = Engine::new();
+
+    let counter = count.clone();
+    engine.register_static_module_lazy("calc", move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let mut module = Module::new();
+        module.set_var("ANSWER", 42 as INT);
+        module.set_native_fn("inc", |x: INT| Ok(x + 1));
+        module
+    });
+
+    assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    assert_eq!(engine.eval::<INT>("calc::inc(41)")?, 42);
+    assert_eq!(engine.eval::<INT>("calc::ANSWER")?, 42);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    engine.set_strict_variables(true);
+    assert_eq!(engine.eval::<INT>("calc::inc(calc::ANSWER)")?, 43);
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[cfg(feature = "url_resolver")]
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn test_module_url_resolver() {
+    use rhai::module_resolvers::UrlModuleResolver;
+
+    let mut resolver = UrlModuleResolver::new();
+    resolver
+        .allow("https://example.com/scripts/")
+        .set_base_url("https://example.com/scripts");
+
+    assert_eq!(
+        resolver.get_url("hello.rhai"),
+        "https://example.com/scripts/hello.rhai"
+    );
+    assert_eq!(
+        resolver.get_url("https://example.org/hello.rhai"),
+        "https://example.org/hello.rhai"
+    );
+    assert_eq!(
+        resolver.get_url("lib/../hello.rhai"),
+        "https://example.com/scripts/hello.rhai"
+    );
+    assert_eq!(
+        resolver.get_url("../secret.rhai"),
+        "https://example.com/secret.rhai"
+    );
+    assert!(resolver.is_allowed("https://example.com/scripts/hello.rhai"));
+    assert!(resolver.is_allowed("HTTPS://Example.COM:443/scripts/./hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.com.evil.org/scripts/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.com@evil.org/scripts/hello.rhai"));
+    assert!(!resolver.is_allowed("https://evil.org\\@example.com/scripts/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.com:8443/scripts/hello.rhai"));
+    assert!(!resolver.is_allowed("http://example.com/scripts/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts2/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts/../secret.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts/%2e%2e/secret.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts/..\\secret.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts/\t../\t../secret.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts/..\n/secret.rhai"));
+    assert!(!resolver.is_allowed("https://example.com/scripts/..%2Fsecret.rhai"));
+    assert!(!resolver.is_allowed("example.com/scripts/hello.rhai"));
+
+    // An allowed URL without a trailing slash still only allows whole path segments
+    resolver.allow("https://example.net/allowed");
+    assert!(resolver.is_allowed("https://example.net/allowed"));
+    assert!(resolver.is_allowed("https://example.net/allowed/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.net/allowed-not/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.net.evil.org/allowed/hello.rhai"));
+    assert!(!resolver.is_allowed("https://example.net/allowed/../secret.rhai"));
+
+    // Relative paths cannot climb out of the base URL, even into allowed URL's
+    resolver.allow("https://example.com/");
+    assert!(resolver.is_allowed("https://example.com/secret.rhai"));
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    let err = engine
+        .run(r#"import "https://example.org/hello.rhai" as hello;"#)
+        .expect_err("should error");
+
+    assert!(matches!(*err, EvalAltResult::ErrorInModule(..)));
+    assert!(err
+        .to_string()
+        .contains("Cannot fetch https://example.org/hello.rhai: URL is not allowed"));
+
+    let err = engine
+        .run(r#"import "../secret.rhai" as secret;"#)
+        .expect_err("should error");
+
+    assert!(err
+        .to_string()
+        .contains("Cannot fetch https://example.com/secret.rhai: URL is not allowed"));
+
+    // Tabs and line breaks (which URL parsers strip) cannot be used to climb out of the base URL
+    for path in [
+        r"\t../\t../secret.rhai",
+        r"..\t/secret.rhai",
+        r".\n./secret.rhai",
+    ] {
+        let err = engine
+            .run(&format!(r#"import "{path}" as secret;"#))
+            .expect_err("should error");
+
+        assert!(err.to_string().contains("URL is not allowed"), "{}", err);
+    }
+}
+
+#[cfg(feature = "url_resolver")]
+#[cfg(not(target_family = "wasm"))]
+#[test]
+fn test_module_url_resolver_redirects() {
+    use rhai::module_resolvers::UrlModuleResolver;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let secret_fetched = Arc::new(AtomicBool::new(false));
+    let fetched = secret_fetched.clone();
+
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match stream.read(&mut buf) {
+                    Ok(0) | Err(..) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+
+            let request = String::from_utf8_lossy(&request);
+            let path = request.split(' ').nth(1).unwrap_or("");
+
+            let (status, location, body) = match path {
+                "/scripts/old.rhai" => ("302 Found", "new.rhai".to_string(), ""),
+                "/scripts/new.rhai" => ("200 OK", String::new(), "export const x = 42;"),
+                "/scripts/evil.rhai" => (
+                    "302 Found",
+                    format!("http://localhost:{port}/secret.rhai"),
+                    "",
+                ),
+                _ => {
+                    fetched.store(true, Ordering::SeqCst);
+                    ("200 OK", String::new(), "export const x = 0;")
+                }
+            };
+
+            let location = if location.is_empty() {
+                location
+            } else {
+                format!("Location: {location}\r\n")
+            };
+
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\n{location}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+        }
+    });
+
+    let mut resolver = UrlModuleResolver::new();
+    resolver.allow(format!("http://127.0.0.1:{port}/scripts/"));
+
+    let mut engine = Engine::new();
+    engine.set_module_resolver(resolver);
+
+    // Redirects within the allow-list are followed
+    assert_eq!(
+        engine
+            .eval::<INT>(&format!(
+                r#"import "http://127.0.0.1:{port}/scripts/old.rhai" as m; m::x"#
+            ))
+            .unwrap(),
+        42
+    );
+
+    // Redirects out of the allow-list are not
+    let err = engine
+        .run(&format!(
+            r#"import "http://127.0.0.1:{port}/scripts/evil.rhai" as m;"#
+        ))
+        .expect_err("should error");
+
+    assert!(err.to_string().contains(&format!(
+        "redirected to http://localhost:{port}/secret.rhai, which is not allowed"
+    )));
+    assert!(!secret_fetched.load(Ordering::SeqCst));
+}
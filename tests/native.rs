@@ -1,4 +1,6 @@
-use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, NativeCallContext, INT};
+use rhai::{
+    Dynamic, Engine, EvalAltResult, ImmutableString, NativeCallContext, NativeCallLog, Scope, INT,
+};
 use std::any::TypeId;
 
 #[cfg(not(feature = "no_module"))]
@@ -97,3 +99,357 @@ fn test_native_overload() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_native_call_replay() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    let counter2 = counter.clone();
+
+    let mut engine = Engine::new();
+
+    engine.register_fn("next", move || {
+        (counter2.fetch_add(1, Ordering::Relaxed) + 1) as INT
+    });
+
+    engine.register_fn("fail", |x: INT| -> Result<INT, Box<EvalAltResult>> {
+        Err(EvalAltResult::ErrorArithmetic(format!("bad {x}"), rhai::Position::NONE).into())
+    });
+
+    let script = "let x = next(); x * 10 + next()";
+    let ast = engine.compile(script)?;
+    let scope = &mut Scope::new();
+
+    let mut log = NativeCallLog::new();
+    assert_eq!(
+        engine.eval_ast_with_native_call_log::<INT>(&mut log, scope, &ast)?,
+        12
+    );
+    assert!(!log.is_replaying());
+    assert_eq!(log.len(), 2);
+    assert_eq!(log.records()[0].name, "next");
+
+    let mut replayed = NativeCallLog::from_records(log.records().iter().cloned());
+    assert_eq!(
+        engine.eval_ast_with_native_call_log::<INT>(&mut replayed, scope, &ast)?,
+        12
+    );
+    assert_eq!(counter.load(Ordering::Relaxed), 2);
+    assert!(replayed.is_replaying());
+    assert_eq!(replayed.num_replayed(), 2);
+
+    let mut replayed = NativeCallLog::from_records(log.records().iter().cloned());
+    let ast2 = engine.compile("abs(next())")?;
+    assert!(matches!(
+        *engine
+            .eval_ast_with_native_call_log::<INT>(&mut replayed, scope, &ast2)
+            .expect_err("should error"),
+        EvalAltResult::ErrorSystem(..)
+    ));
+
+    // Errors are replayed as originally raised
+    let ast = engine.compile("fail(42)")?;
+    let mut log = NativeCallLog::new();
+    let err = engine
+        .eval_ast_with_native_call_log::<INT>(&mut log, scope, &ast)
+        .expect_err("should error");
+    let mut replayed = NativeCallLog::from_records(log.records().iter().cloned());
+    let err2 = engine
+        .eval_ast_with_native_call_log::<INT>(&mut replayed, scope, &ast)
+        .expect_err("should error");
+    assert!(matches!(*err2, EvalAltResult::ErrorArithmetic(..)));
+    assert_eq!(err.to_string(), err2.to_string());
+
+    // The log only applies to its own evaluation
+    assert_eq!(engine.eval::<INT>(script)?, 34);
+
+    Ok(())
+}
+
+#[test]
+fn test_native_missing_function() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.on_missing_function(|name, arg_types, pos| match name {
+        "double" if arg_types == ["i64"] || arg_types == ["i32"] => {
+            Ok(Some(rhai::plugin::CallableFunction::from_native_fn(
+                |_, args| Ok((args[0].as_int().unwrap() * 2).into()),
+            )))
+        }
+        "bump" => Ok(Some(rhai::plugin::CallableFunction::from_native_method(
+            |_, args| {
+                let x = args[0].as_int().unwrap();
+                *args[0] = (x + 1).into();
+                Ok(Dynamic::UNIT)
+            },
+        ))),
+        "forbidden" => Err(EvalAltResult::ErrorRuntime(name.into(), pos).into()),
+        _ => Ok(None),
+    });
+
+    assert_eq!(engine.eval::<INT>("double(21)")?, 42);
+    assert_eq!(engine.eval::<INT>("let x = 41; x.bump(); x")?, 42);
+
+    assert!(matches!(
+        *engine.run("double(\"x\")").expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+    assert!(matches!(
+        *engine.run("forbidden()").expect_err("should error"),
+        EvalAltResult::ErrorRuntime(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_native_context_data() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone)]
+    struct Request {
+        user: String,
+        limit: INT,
+    }
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("user", |context: NativeCallContext| {
+            context
+                .data::<Request>()
+                .map_or_else(String::new, |r| r.user.clone())
+        })
+        .register_fn("limit", |context: NativeCallContext| {
+            context.data::<Request>().map_or(0, |r| r.limit)
+        })
+        .register_fn("level", |context: NativeCallContext| {
+            context.data::<INT>().copied().unwrap_or(-1)
+        });
+
+    let request = Request {
+        user: "alice".to_string(),
+        limit: 42,
+    };
+
+    assert_eq!(
+        engine.eval_with_context_data::<String>(r#"user() + ":" + limit()"#, request.clone())?,
+        "alice:42"
+    );
+    assert_eq!(
+        engine.eval_with_context_data::<INT>("level()", request)?,
+        -1
+    );
+    assert_eq!(engine.eval::<String>("user()")?, "");
+
+    engine.set_default_tag(3 as INT);
+
+    assert_eq!(engine.eval::<INT>("level()")?, 3);
+    assert_eq!(
+        engine.eval_with_context_data::<INT>("level()", 5 as INT)?,
+        5
+    );
+
+    // Context data does not touch the tag and is kept as is
+    engine.register_fn("name", |context: NativeCallContext| {
+        context.data::<String>().cloned().unwrap_or_default()
+    });
+    engine.register_fn("tag", |context: NativeCallContext| {
+        context.tag().map_or(-1, |t| t.as_int().unwrap_or(-1))
+    });
+
+    assert_eq!(
+        engine.eval_with_context_data::<String>("name()", "bob".to_string())?,
+        "bob"
+    );
+    assert_eq!(
+        engine.eval_with_context_data::<INT>("tag()", "bob".to_string())?,
+        3
+    );
+
+    let ast = engine.compile("level() + x")?;
+    let mut scope = rhai::Scope::new();
+    scope.push("x", 10 as INT);
+
+    assert_eq!(
+        engine.eval_ast_with_context_data::<INT>(&mut scope, &ast, 7 as INT)?,
+        17
+    );
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_native_global_state() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::{Arc, RwLock};
+
+    let mut engine = Engine::new();
+
+    engine.register_fn("ops", |context: NativeCallContext| {
+        context
+            .global_state()
+            .map_or(-1, |state| state.num_operations() as INT)
+    });
+    engine.register_fn("src", |context: NativeCallContext| {
+        context
+            .global_state()
+            .and_then(|state| state.source())
+            .unwrap_or("none")
+            .to_string()
+    });
+
+    assert!(engine.eval::<INT>("let x = 1; x + ops()")? > 1);
+
+    let mut ast = engine.compile("src()")?;
+    assert_eq!(engine.eval_ast::<String>(&ast)?, "none");
+    ast.set_source("hello");
+    assert_eq!(engine.eval_ast::<String>(&ast)?, "hello");
+
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+
+    engine.on_eval_end(move |state| logger.write().unwrap().push(state.num_operations()));
+
+    engine.run("let x = 40; x + 2;")?;
+    engine.eval::<INT>("40 + 2")?;
+    assert!(engine.eval::<INT>("40 + true").is_err());
+
+    let log = log.read().unwrap();
+    assert_eq!(log.len(), 3);
+    assert!(log.iter().all(|&ops| ops > 0));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+#[test]
+fn test_native_borrowed_args() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("checksum", |seed: INT, data: &[u8]| {
+            data.iter().fold(seed, |sum, &b| sum + b as INT)
+        })
+        .register_fn("count", |x: INT, items: &rhai::Array| {
+            items.iter().filter(|v| v.as_int() == Ok(x)).count() as INT
+        })
+        .register_fn("first_of", |_: INT, items: &[Dynamic]| items[0].clone())
+        .register_fn("has", |map: rhai::Map, key: &ImmutableString| {
+            map.contains_key(key.as_str())
+        })
+        .register_fn("size", |_: INT, map: &rhai::Map| map.len() as INT);
+
+    assert_eq!(
+        engine.eval::<INT>("let b = blob(3, 0x10); checksum(1, b) + checksum(0, b)")?,
+        97
+    );
+    assert_eq!(engine.eval::<INT>("let a = [1, 2, 1]; count(1, a)")?, 2);
+    assert_eq!(engine.eval::<INT>("first_of(0, [42, 0])")?, 42);
+    assert!(engine.eval::<bool>(r#"has(#{a: 1}, "a")"#)?);
+    assert_eq!(engine.eval::<INT>("size(0, #{a: 1, b: 2})")?, 2);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_native_borrowed_args_no_clone() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("addr", |_: INT, data: &[u8]| data.as_ptr() as INT)
+        .register_fn("sum", |x: INT, data: &[u8]| {
+            data.iter().fold(x, |sum, &b| sum + b as INT)
+        })
+        .register_fn("take", |_: INT, data: rhai::Blob| data.len() as INT);
+
+    let mut scope = Scope::new();
+    scope.push("b", vec![1_u8; 1000]);
+    let addr = scope
+        .get("b")
+        .unwrap()
+        .read_lock::<rhai::Blob>()
+        .unwrap()
+        .as_ptr() as INT;
+
+    // Variables are lent to functions that borrow them
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "addr(0, b)")?,
+        addr
+    );
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "let x = 1; sum(x, b)")?,
+        1001
+    );
+
+    // Functions taking arguments by value get copies
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "take(0, b) + take(0, b)")?,
+        2000
+    );
+
+    // Errors in other arguments put lent variables back
+    assert!(engine
+        .eval_with_scope::<INT>(&mut scope, "sum(0, b, no_such_var)")
+        .is_err());
+    assert!(engine
+        .eval_with_scope::<INT>(&mut scope, "sum(no_such_var, b)")
+        .is_err());
+
+    assert_eq!(scope.get_value::<rhai::Blob>("b").unwrap().len(), 1000);
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "addr(0, b)")?,
+        addr
+    );
+
+    // Script-defined functions get copies
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut scope,
+            "fn grow(x, data) { data.push(x); data.len() } grow(1, b) + b.len()"
+        )?,
+        2001
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "debugging")]
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_native_borrowed_args_debugger() -> Result<(), Box<EvalAltResult>> {
+    use std::sync::{Arc, Mutex};
+
+    let mut engine = Engine::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+
+    engine
+        .register_fn("sum", |x: INT, data: &[u8], y: INT| {
+            data.iter().fold(x + y, |sum, &b| sum + b as INT)
+        })
+        .register_debugger(
+            |_| Dynamic::UNIT,
+            move |context, _, _, _, _| {
+                if let Some(b) = context.scope().get("b") {
+                    log.lock().unwrap().push(b.type_name());
+                }
+                Ok(rhai::debugger::DebuggerCommand::StepInto)
+            },
+        );
+
+    assert_eq!(
+        engine.eval::<INT>("let b = blob(3, 1); let y = 1; sum(0, b, y)")?,
+        4
+    );
+
+    // Lent variables are never seen missing from the scope while arguments are evaluated
+    let seen = seen.lock().unwrap();
+    assert!(!seen.is_empty());
+    assert!(seen.iter().all(|&typ| typ == "blob"));
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+#![cfg(not(feature = "no_position"))]
+use rhai::{Engine, EvalAltResult, NodeId, Position};
+
+#[test]
+fn test_node_ids() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            let x = 40;
+            let y = x + 2;
+            print(y);
+        ",
+    )?;
+
+    let ids = ast.node_ids();
+    assert!(!ids.is_empty());
+
+    // All nodes have distinct ID's
+    let mut unique = ids.iter().map(|(id, ..)| *id).collect::<Vec<_>>();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), ids.len());
+
+    let id_y = ast.node_id_at(Position::new(3, 13)).unwrap();
+    assert_eq!(ast.node_position(id_y), Some(Position::new(3, 13)));
+
+    let id_print = ast.node_id_at(Position::new(4, 13)).unwrap();
+
+    // Insert statements of a different kind, and shift positions
+    let ast = engine.compile(
+        "
+            // a comment
+
+            let x = 40;
+            x += 1;
+            x -= 1;
+                let y = x + 2;
+            print(y);
+        ",
+    )?;
+
+    assert_eq!(ast.node_position(id_y), Some(Position::new(7, 17)));
+    assert_eq!(ast.node_position(id_print), Some(Position::new(8, 13)));
+
+    // Insert a statement of the same kind before
+    let ast = engine.compile(
+        "
+            let x = 40;
+            print(x);
+            let y = x + 2;
+            print(y);
+        ",
+    )?;
+
+    assert_eq!(ast.node_position(id_y), Some(Position::new(4, 13)));
+    assert_ne!(ast.node_position(id_print), Some(Position::new(5, 13)));
+
+    // Raw values round-trip
+    assert_eq!(NodeId::from_u64(id_y.as_u64()), id_y);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_node_ids_functions() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            fn foo(x) {
+                let y = x * 2;
+                y + 1
+            }
+            foo(42)
+        ",
+    )?;
+
+    let id = ast.node_id_at(Position::new(3, 17)).unwrap();
+
+    // Functions are identified by name and number of parameters, not order
+    let ast = engine.compile(
+        "
+            fn bar() { 0 }
+
+            fn foo(x) {
+                let y = x * 2;
+                y + 1
+            }
+            foo(42)
+        ",
+    )?;
+
+    assert_eq!(ast.node_position(id), Some(Position::new(5, 17)));
+
+    let ast = engine.compile("fn foo(x, z) { let y = x * 2; y + z }")?;
+
+    assert_eq!(ast.node_position(id), None);
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 #![cfg(not(feature = "unchecked"))]
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{Engine, EvalAltResult, FuelCosts, Limits, INT};
+use std::num::{NonZeroU64, NonZeroUsize};
 
 #[test]
 fn test_max_operations() -> Result<(), Box<EvalAltResult>> {
@@ -168,3 +169,258 @@ fn test_max_operations_progress() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_max_operations_progress_sampled() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+
+    let samples = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = samples.clone();
+
+    engine.on_progress_sampled(10, move |info| {
+        log.lock()
+            .unwrap()
+            .push((info.num_operations, info.fn_name.to_string()));
+        if info.num_operations < 500 {
+            None
+        } else {
+            Some((42 as INT).into())
+        }
+    });
+
+    #[cfg(not(feature = "no_function"))]
+    let script = "fn foo() { for x in 0..100 {} } foo(); for x in 0..500 {}";
+    #[cfg(feature = "no_function")]
+    let script = "for x in 0..500 {}";
+
+    assert!(matches!(
+        *engine.run(script).expect_err("should error"),
+        EvalAltResult::ErrorTerminated(x, ..) if x.as_int()? == 42
+    ));
+
+    let samples = samples.lock().unwrap();
+
+    assert!(samples.iter().all(|(n, ..)| n % 10 == 0));
+    assert_eq!(samples.last().unwrap().0, 500);
+    #[cfg(not(feature = "no_function"))]
+    assert!(samples.iter().any(|(.., f)| f == "foo"));
+    assert!(samples.iter().any(|(.., f)| f.is_empty()));
+
+    Ok(())
+}
+
+#[test]
+fn test_max_operations_context() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_operations(500);
+
+    let err = engine
+        .run(
+            r#"
+                let s = "hello";
+                let x = 0;
+                while true {
+                    x += 1;
+                }
+            "#,
+        )
+        .expect_err("should error");
+
+    let ctx = err.limit_context().expect("limit context");
+    assert_eq!(ctx.fn_name, "");
+    assert_eq!(ctx.loop_pos.line(), Some(4));
+    assert_eq!(ctx.variables, vec![("s".to_string(), 5)]);
+
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_index"))]
+    {
+        let err = engine
+            .run(
+                r#"
+                    fn foo(x) {
+                        let a = [1, 2, 3];
+                        loop { x += 1; }
+                    }
+                    let s = "hello, world!";
+                    for i in 0..10 { foo(i); }
+                "#,
+            )
+            .expect_err("should error");
+
+        let ctx = err.limit_context().expect("limit context");
+        assert_eq!(ctx.fn_name, "foo");
+        assert_eq!(ctx.loop_pos.line(), Some(4));
+        assert_eq!(ctx.variables, vec![("a".to_string(), 3)]);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_fuel_metering() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+    engine.set_fuel_limit(10);
+
+    engine.set_fuel_costs(FuelCosts {
+        operation: 0,
+        loop_iteration: 1,
+        ..FuelCosts::new()
+    });
+
+    engine.run("for x in 0..10 {}")?;
+
+    assert!(matches!(
+        *engine.run("for x in 0..11 {}").expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+    assert!(matches!(
+        *engine
+            .run("let x = 0; while x < 20 { x += 1; }")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+
+    engine.set_fuel_costs(FuelCosts {
+        operation: 0,
+        string_concat: 3,
+        ..FuelCosts::new()
+    });
+
+    engine.run(r#"let s = "a"; s += "b"; s + "c""#)?;
+
+    assert!(matches!(
+        *engine
+            .run(r#"let s = "a"; s += "b"; s += "c"; s += "d"; s + "e""#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine.set_fuel_costs(FuelCosts {
+            operation: 0,
+            function_call: 5,
+            ..FuelCosts::new()
+        });
+
+        engine.run("fn foo() { 1 } foo(); abs(-1);")?;
+
+        assert!(matches!(
+            *engine
+                .run("fn foo() { 1 } foo(); foo(); foo();")
+                .expect_err("should error"),
+            EvalAltResult::ErrorTooManyOperations(..)
+        ));
+    }
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        engine.set_fuel_costs(FuelCosts {
+            operation: 0,
+            array_alloc: 4,
+            ..FuelCosts::new()
+        });
+
+        engine.run("[1, [2, 3]]")?;
+
+        assert!(matches!(
+            *engine.run("[1, [2, [3]]]").expect_err("should error"),
+            EvalAltResult::ErrorTooManyOperations(..)
+        ));
+    }
+
+    #[cfg(feature = "internals")]
+    {
+        engine.set_fuel_costs(FuelCosts::new());
+        engine.set_fuel_limit(100);
+        engine.register_fn("fuel", |ctx: rhai::NativeCallContext| {
+            ctx.global_runtime_state()
+                .and_then(|g| g.fuel_remaining())
+                .unwrap_or(0) as INT
+        });
+
+        let first = engine.eval::<INT>("fuel()")?;
+        assert_eq!(engine.eval::<INT>("fuel()")?, first);
+        assert!(engine.eval::<INT>("let x = 1; x += 1; fuel()")? < first);
+    }
+
+    engine.set_fuel_limit(0);
+
+    engine.run("for x in 0..10000 {}")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_max_operations_nested() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    #[cfg(not(feature = "no_optimize"))]
+    engine.set_optimization_level(rhai::OptimizationLevel::None);
+    engine.set_max_operations(1000);
+    engine.set_nested_limits(Limits {
+        max_operations: NonZeroU64::new(50),
+        max_string_size: NonZeroUsize::new(10),
+        #[cfg(not(feature = "no_function"))]
+        max_call_stack_depth: 1,
+        ..Limits::new()
+    });
+
+    engine.run("let x = 0; for n in 0..100 { x += n; }")?;
+    engine.run("let x = 0; for n in 0..10 { x += n; } `x = ${x}`")?;
+
+    assert!(matches!(
+        *engine
+            .run("`x = ${ let x = 0; for n in 0..100 { x += n; } x }`")
+            .expect_err("should error"),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+
+    // The budget is per nested evaluation, not per script
+    engine.run("for n in 0..10 { let s = `${ for x in 0..10 {} n }`; }")?;
+
+    assert!(matches!(
+        *engine
+            .run(r#"let s = "hello, world!"; `${s}`"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        engine.run("fn foo(x) { x } `${foo(1)}`")?;
+
+        assert!(matches!(
+            *engine
+                .run("fn foo(x) { x } fn bar(x) { foo(x) } bar(1); `${bar(1)}`")
+                .expect_err("should error"),
+            EvalAltResult::ErrorStackOverflow(..)
+        ));
+    }
+
+    #[cfg(not(feature = "no_custom_syntax"))]
+    {
+        engine.register_custom_syntax(["eval_nested", "$expr$"], false, |context, inputs| {
+            context.eval_expression_tree(&inputs[0])
+        })?;
+
+        engine.run("eval_nested { for x in 0..10 {} }")?;
+
+        assert!(matches!(
+            *engine
+                .run("eval_nested { for x in 0..100 {} }")
+                .expect_err("should error"),
+            EvalAltResult::ErrorTooManyOperations(..)
+        ));
+    }
+
+    engine.clear_nested_limits();
+    assert!(engine.nested_limits().is_none());
+
+    engine.run("`x = ${ let x = 0; for n in 0..100 { x += n; } x }`")?;
+
+    Ok(())
+}
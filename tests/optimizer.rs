@@ -163,3 +163,112 @@ fn test_optimizer_scope() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_optimizer_compile_stats() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let (ast, stats) = engine.compile_with_stats("let x = 40; x + 2")?;
+    assert_eq!(stats.tokens, 8);
+    assert_eq!(stats.functions, 0);
+    assert!(stats.nodes > 0);
+    assert!(stats.optimizer_passes > 0);
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 42);
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let (.., stats) = engine.compile_with_stats("fn foo(x) { |y| x + y } foo(1)")?;
+        assert_eq!(stats.functions, 2);
+    }
+
+    engine.set_optimization_level(OptimizationLevel::None);
+
+    let (.., stats) = engine.compile_with_stats("let x = 40; x + 2")?;
+    assert_eq!(stats.optimizer_passes, 0);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_optimizer_switch_guards() -> Result<(), Box<EvalAltResult>> {
+    use rhai::ParseWarning;
+    use std::sync::{Arc, RwLock};
+
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+
+    let mut engine = Engine::new();
+
+    let log = warnings.clone();
+    engine.on_parse_warning(move |warning, _| log.write().unwrap().push(warning));
+
+    let ast = engine.compile(
+        "
+            fn is_enabled(feature) { feature == 2 || feature == 3 }
+            fn mask(n) { let m = 1; m << n }
+            fn noisy(x) { print(x); true }
+
+            switch x {
+                1 if is_enabled(1) => 10,
+                1 if mask(2) == 4 => 11,
+                2 if is_enabled(2) => 20,
+                2 => 21,
+                3 if noisy(3) => 30,
+                _ => 0
+            }
+        ",
+    )?;
+
+    assert_eq!(
+        *warnings.read().unwrap(),
+        vec![
+            ParseWarning::UnreachableSwitchCase,
+            ParseWarning::UnreachableSwitchCase
+        ]
+    );
+
+    // Calls to pure functions are evaluated at compile time
+    let ast_text = format!("{ast:?}");
+    assert!(!ast_text.contains(r#"name: "is_enabled""#));
+    assert!(!ast_text.contains(r#"name: "mask""#));
+    assert!(ast_text.contains(r#"name: "noisy""#));
+
+    for (x, expected) in [(1, 11), (2, 20), (3, 30), (4, 0)] {
+        let mut scope = Scope::new();
+        scope.push("x", x as INT);
+        assert_eq!(
+            engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?,
+            expected
+        );
+    }
+
+    // Functions using operators overloaded by the application are not pure
+    let calls = Arc::new(RwLock::new(0));
+    let counter = calls.clone();
+    engine.set_fast_operators(false);
+    engine.register_fn("<<", move |x: INT, y: INT| {
+        *counter.write().unwrap() += 1;
+        x * (1 << y)
+    });
+
+    let ast = engine.compile(
+        "
+            fn mask(n) { let m = 1; m << n }
+
+            switch x {
+                1 if mask(2) == 4 => 11,
+                _ => 0
+            }
+        ",
+    )?;
+
+    assert!(format!("{ast:?}").contains(r#"name: "mask""#));
+    assert_eq!(*calls.read().unwrap(), 0);
+
+    let mut scope = Scope::new();
+    scope.push("x", 1 as INT);
+    assert_eq!(engine.eval_ast_with_scope::<INT>(&mut scope, &ast)?, 11);
+    assert_eq!(*calls.read().unwrap(), 1);
+
+    Ok(())
+}
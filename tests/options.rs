@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, Scope, INT};
+use rhai::{Engine, EvalAltResult, ParseErrorType, Position, Scope, INT};
 
 #[test]
 fn test_options_allow() -> Result<(), Box<EvalAltResult>> {
@@ -128,3 +128,126 @@ fn test_options_strict_var() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_options_shadowing_same_scope() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_allow_shadowing_in_same_scope(false);
+
+    engine.compile("let x = 42; { let x = 123; }")?;
+    engine.compile("{ let x = 42; } let x = 123;")?;
+
+    let err = engine
+        .compile("let x = 42; let y = 0; let x = 123;")
+        .expect_err("should error");
+    assert_eq!(
+        err.err_type(),
+        &ParseErrorType::VariableExists("x".to_string())
+    );
+    assert_eq!(err.1, Position::new(1, 28));
+
+    assert!(engine
+        .compile("let x = 42; { let x = 0; let x = 123; }")
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_options_strict_const() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.compile("const x = [1, 2, 3]; x[0] = 42;")?;
+
+    engine.set_strict_constants(true);
+
+    let err = engine
+        .compile("const x = [1, 2, 3]; x[0] = 42;")
+        .expect_err("should error");
+    assert_eq!(
+        err.err_type(),
+        &ParseErrorType::AssignmentToConstant("x".to_string())
+    );
+    assert_eq!(err.1, Position::new(1, 22));
+
+    engine.compile("let x = [1, 2, 3]; x[0] = 42;")?;
+    assert!(engine.compile("const x = #{a: 1}; x.a += 1;").is_err());
+    #[cfg(not(feature = "no_closure"))]
+    {
+        engine.compile("let x = 1; let f = || x = 2;")?;
+        assert!(engine.compile("const x = 1; let f = || x = 2;").is_err());
+        assert!(engine
+            .compile("const x = [1]; let f = || { || x[0] = 2 };")
+            .is_err());
+    }
+
+    let mut scope = Scope::new();
+    scope.push_constant("x", 42 as INT);
+
+    assert!(engine.compile_with_scope(&scope, "x += 1;").is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_options_explicit_return() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.compile("fn foo(x) { x + 1 }")?;
+
+    engine.set_require_explicit_return(true);
+
+    let err = engine
+        .compile("fn foo(x) {\n    x + 1\n}")
+        .expect_err("should error");
+    assert_eq!(
+        err.err_type(),
+        &ParseErrorType::FnImplicitReturn("foo".to_string())
+    );
+    assert_eq!(err.1, Position::new(2, 5));
+
+    assert!(engine
+        .compile("fn foo(x) { if x > 0 { return 1; } else { 0 } }")
+        .is_err());
+    engine.compile("fn foo(x) { return x + 1; }")?;
+    engine.compile("fn foo(x) { if x > 0 { return 1; } else { return 0; } }")?;
+    engine.compile("fn foo(x) { print(x) }")?;
+    engine.compile("fn foo(x) { x.bar() }")?;
+    engine.compile("let x = 42; x")?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_options_parse_warnings() -> Result<(), Box<EvalAltResult>> {
+    use rhai::ParseWarning;
+    use std::sync::{Arc, RwLock};
+
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+
+    let mut engine = Engine::new();
+
+    let log = warnings.clone();
+    engine.on_parse_warning(move |warning, pos| log.write().unwrap().push((warning, pos)));
+
+    engine.compile("fn foo(x) { x + 1 } fn bar(x) { x = 1; } fn baz() { let y = 42; }")?;
+
+    assert_eq!(
+        *warnings.read().unwrap(),
+        vec![
+            (
+                ParseWarning::FnValueDiscarded("bar".to_string()),
+                Position::new(1, 35)
+            ),
+            (
+                ParseWarning::FnValueDiscarded("baz".to_string()),
+                Position::new(1, 53)
+            ),
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+#![cfg(not(feature = "no_object"))]
+use rhai::{Engine, EvalAltResult, OrderedMap, INT};
+
+#[test]
+fn test_ordered_maps() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("len(ordered_map())")?, 0);
+    assert_eq!(
+        engine.eval::<INT>("let m = ordered_map(); m.z = 1; m.a = 2; m.get_by_index(0)")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>(r#"let m = ordered_map(); m["z"] = 1; m.a = 2; m.get_by_index(-1)"#)?,
+        2
+    );
+    assert_eq!(
+        engine.eval::<String>("let m = ordered_map(); m.z = 1; m.a = 2; m.key_by_index(1)")?,
+        "a"
+    );
+    assert_eq!(
+        engine
+            .eval::<INT>("let m = ordered_map(); m.z = 1; m.a = 2; m.z = 3; m.index_of(\"z\")")?,
+        0
+    );
+    assert!(engine.eval::<bool>(r#"let m = ordered_map(); m.x = 1; "x" in m"#)?);
+    assert!(engine.eval::<bool>("let m = ordered_map(#{x: 1}); m.get_by_index(1) == ()")?);
+    assert_eq!(
+        engine.eval::<String>("let m = ordered_map(); m.b = 1; m.a = [2]; m.to_string()")?,
+        r#"#{"b": 1, "a": [2]}"#
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let m = ordered_map();
+                m.c = 1;
+                m.a = 2;
+                m.b = 3;
+                m.remove("a");
+                m.a = 4;
+                let s = "";
+                for k in m.keys() { s += k; }
+                for v in m.values() { s += v; }
+                s
+            "#
+        )?,
+        "cba134"
+    );
+
+    let m = engine.eval::<OrderedMap>("let m = ordered_map(); m.y = 1; m.x = 2; m")?;
+    assert_eq!(m.keys().collect::<Vec<_>>(), ["y", "x"]);
+    assert_eq!(m.get_by_index(1).unwrap().1.as_int().unwrap(), 2);
+    assert_eq!(m.index_of("y"), Some(0));
+
+    Ok(())
+}
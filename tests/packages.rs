@@ -55,3 +55,118 @@ fn test_packages_with_script() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_packages_with_config() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Default)]
+    struct MyConfig {
+        enable_mul: bool,
+    }
+
+    def_package! {
+        /// My configurable package.
+        MyPackage(m, config: MyConfig) {
+            m.set_native_fn("add", |x: INT, y: INT| Ok(x + y));
+
+            if config.enable_mul {
+                m.set_native_fn("mul", |x: INT, y: INT| Ok(x * y));
+            }
+        }
+    }
+
+    let mut engine = Engine::new();
+    MyPackage::new().register_into_engine(&mut engine);
+
+    assert_eq!(engine.eval::<INT>("add(40, 2)")?, 42);
+    assert!(engine.eval::<INT>("mul(21, 2)").is_err());
+
+    let mut engine = Engine::new();
+    let pkg = MyPackage::with_config(MyConfig { enable_mul: true });
+    pkg.register_into_engine(&mut engine);
+
+    assert_eq!(engine.eval::<INT>("mul(21, 2)")?, 42);
+
+    #[cfg(feature = "metadata")]
+    assert_eq!(pkg.as_shared_module().doc(), "//! My configurable package.");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "no_pkg_math")]
+fn test_packages_excluded() -> Result<(), Box<EvalAltResult>> {
+    use rhai::packages::BasicMathPackage;
+
+    let mut engine = Engine::new();
+
+    assert!(engine.eval::<INT>(r#"parse_int("42")"#).is_err());
+
+    BasicMathPackage::new().register_into_engine(&mut engine);
+
+    assert_eq!(engine.eval::<INT>(r#"parse_int("42")"#)?, 42);
+
+    Ok(())
+}
+
+#[test]
+fn test_packages_minimal() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new_minimal();
+
+    assert_eq!(engine.eval::<INT>("let x = 40; x + 2")?, 42);
+    assert!(engine.eval::<bool>("42 > 0")?);
+    assert!(engine.eval::<INT>(r#""hello".len()"#).is_err());
+
+    let info = Engine::build_info();
+
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert!(info.has_package("ArithmeticPackage"));
+    assert_eq!(
+        info.has_package("BasicMathPackage"),
+        cfg!(not(feature = "no_pkg_math"))
+    );
+    assert_eq!(info.has_feature("metadata"), cfg!(feature = "metadata"));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_object"))]
+#[cfg(not(feature = "no_index"))]
+#[test]
+fn test_packages_verify() {
+    #[derive(Debug, Clone)]
+    struct TestStruct(INT);
+
+    let mut engine = Engine::new();
+
+    assert!(engine.verify().is_ok());
+
+    let mut module = Module::new();
+    module.set_id("my_module");
+    module.set_native_fn("hello", |x: INT| Ok(x + 1));
+
+    engine
+        .register_global_module(module.into())
+        .register_fn("hello", |x: INT| x + 2)
+        .register_fn("==", |x: INT, y: INT| x != y)
+        .register_type::<TestStruct>()
+        .register_set("x", |t: &mut TestStruct, x: INT| t.0 = x)
+        .register_get("y", |t: &mut TestStruct| t.0)
+        .register_indexer_get(|t: &mut TestStruct, _: String| t.0);
+
+    let report = engine.verify();
+    let mut issues = report
+        .iter()
+        .map(|issue| issue.to_string())
+        .collect::<Vec<_>>();
+    issues.sort();
+
+    assert_eq!(issues.len(), 4);
+    assert!(issues[0].starts_with("Function hello"));
+    assert!(
+        issues[0].ends_with("in my_module is shadowed by the same function in global namespace")
+    );
+    assert!(issues[1].starts_with("Operator function =="));
+    assert!(issues[2].starts_with("Property 'x' has a setter"));
+    assert!(issues[3].starts_with("Property 'y' has a getter"));
+    assert!(issues[3].ends_with("conflicts with a string indexer"));
+}
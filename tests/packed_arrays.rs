@@ -0,0 +1,102 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Array, Engine, EvalAltResult, IntArray, INT};
+
+#[cfg(not(feature = "no_float"))]
+use rhai::{FloatArray, FLOAT};
+
+#[test]
+fn test_packed_int_arrays() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("int_array().len")?, 0);
+    assert_eq!(
+        engine.eval::<IntArray>("int_array(3, 42)")?.into_vec(),
+        vec![42, 42, 42]
+    );
+    assert_eq!(
+        engine.eval::<IntArray>("int_array([1, 2, 3])")?.into_vec(),
+        vec![1, 2, 3]
+    );
+    assert_eq!(
+        engine.eval::<String>("let a = int_array([1, 2, 3]); a.to_string()")?,
+        "[1, 2, 3]"
+    );
+    assert_eq!(
+        engine.eval::<Array>("int_array([1, 2]).to_array()")?.len(),
+        2
+    );
+
+    assert_eq!(
+        engine.eval::<INT>("let a = int_array(5); a[1] = 40; a[-1] = 2; a[1] + a[4]")?,
+        42
+    );
+    assert_eq!(
+        engine.eval::<INT>("let a = int_array(); a.push(1); a.push(2); a.pop() + a.len")?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>("let s = 0; for x in int_array([1, 2, 3]) { s += x; } s")?,
+        6
+    );
+
+    assert_eq!(
+        engine
+            .eval::<IntArray>("int_array([1, 2, 3]) * int_array([4, 5, 6]) + 1")?
+            .into_vec(),
+        vec![5, 11, 19]
+    );
+    assert_eq!(engine.eval::<INT>("int_array([4, 1, 9]).sum()")?, 14);
+    assert_eq!(engine.eval::<INT>("int_array([4, 1, 9]).min()")?, 1);
+    assert_eq!(engine.eval::<INT>("int_array([4, 1, 9]).max()")?, 9);
+    assert_eq!(
+        engine
+            .eval::<IntArray>("int_array([1, 2, 3]).map(|x, i| x * 10 + i)")?
+            .into_vec(),
+        vec![10, 21, 32]
+    );
+    assert_eq!(
+        engine.eval::<INT>("int_array([1, 2, 3]).reduce(|sum, x| sum + x * x, 0)")?,
+        14
+    );
+    assert!(engine.eval::<bool>("int_array([1, 2]) == int_array([1, 2])")?);
+
+    assert!(engine.eval::<IntArray>("int_array([1, 'x'])").is_err());
+    assert!(engine.eval::<INT>("int_array(2)[2]").is_err());
+    assert!(engine
+        .eval::<IntArray>("int_array([1, 2]) + int_array([1])")
+        .is_err());
+    assert!(engine
+        .eval::<IntArray>(r#"int_array([1, 2]).map(|x| "x")"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_float"))]
+#[test]
+fn test_packed_float_arrays() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine
+            .eval::<FloatArray>("float_array([1, 2.5])")?
+            .into_vec(),
+        vec![1.0, 2.5]
+    );
+    assert_eq!(
+        engine.eval::<FLOAT>("(float_array([1.0, 2.0]) / 4.0).sum()")?,
+        0.75
+    );
+    assert_eq!(
+        engine.eval::<FLOAT>("int_array([1, 2, 3]).to_float_array().max()")?,
+        3.0
+    );
+    assert_eq!(
+        engine
+            .eval::<IntArray>("float_array(2, 2.9).to_int_array()")?
+            .into_vec(),
+        vec![2, 2]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "profiling")]
+use rhai::{Engine, EvalAltResult, INT};
+
+#[test]
+fn test_profiling() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    assert!(engine.take_profile().is_none());
+
+    engine.start_profiling();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                let x = 0;
+                for i in 0..5 {
+                    x += i;
+                }
+                x
+            "
+        )?,
+        10
+    );
+    assert_eq!(engine.eval::<INT>("40 + 2")?, 42);
+
+    let profile = engine.take_profile().expect("profiling");
+    assert!(engine.take_profile().is_none());
+
+    #[cfg(not(feature = "no_position"))]
+    {
+        let (_, pos, stmt) = profile
+            .statements()
+            .find(|(.., p)| p.hits == 5)
+            .expect("statement in loop");
+        assert_eq!(pos.line(), Some(4));
+
+        #[cfg(not(feature = "unchecked"))]
+        assert!(stmt.operations >= 5);
+
+        assert_eq!(
+            profile.statements().filter(|(.., p)| p.hits == 1).count(),
+            4
+        );
+    }
+
+    assert_eq!(profile.functions().count(), 0);
+    assert!(profile.to_folded_stacks().starts_with("<script> "));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_function"))]
+#[test]
+fn test_profiling_functions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.start_profiling();
+
+    engine.run(
+        "
+            fn inner(x) { x * 2 }
+            fn outer(x) { inner(x) + inner(x + 1) }
+
+            for i in 0..3 { outer(i); }
+            inner(0);
+        ",
+    )?;
+
+    let profile = engine.take_profile().expect("profiling");
+
+    let outer = profile.function("outer").expect("outer called");
+    let inner = profile.function("inner").expect("inner called");
+    assert_eq!(outer.calls, 3);
+    assert_eq!(inner.calls, 7);
+    assert!(outer.total_time >= outer.self_time);
+
+    #[cfg(not(feature = "unchecked"))]
+    assert!(outer.operations > inner.operations);
+
+    let stacks = profile.to_folded_stacks();
+    let stacks = stacks
+        .lines()
+        .map(|line| line.rsplit_once(' ').expect("stack and time").0)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stacks,
+        [
+            "<script>",
+            "<script>;inner",
+            "<script>;outer",
+            "<script>;outer;inner"
+        ]
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "bytecode")]
+#[cfg(not(feature = "no_position"))]
+#[test]
+fn test_profiling_bytecode() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let ast = engine.compile("let x = 1; let y = 2; x += y; x")?;
+    let bytecode = engine.compile_bytecode(&ast);
+
+    engine.start_profiling();
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 3);
+    let ast_profile = engine.take_profile().expect("profiling");
+
+    engine.start_profiling();
+    assert_eq!(engine.eval_bytecode::<INT>(&bytecode)?, 3);
+    let bytecode_profile = engine.take_profile().expect("profiling");
+
+    assert_eq!(ast_profile.statements().count(), 4);
+    assert_eq!(bytecode_profile.statements().count(), 4);
+
+    Ok(())
+}
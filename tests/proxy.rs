@@ -0,0 +1,69 @@
+#![cfg(not(feature = "no_object"))]
+use rhai::{
+    Dynamic, Engine, EvalAltResult, ImmutableString, Position, Proxy, ProxyHandler, Scope, INT,
+};
+
+struct Api;
+
+impl ProxyHandler for Api {
+    fn get_property(
+        &self,
+        path: &[ImmutableString],
+    ) -> Result<Option<Dynamic>, Box<EvalAltResult>> {
+        match path.last().map(|s| s.as_str()) {
+            Some("count") => Ok(Some((path.len() as INT).into())),
+            _ => Ok(None),
+        }
+    }
+    fn call_method(
+        &self,
+        path: &[ImmutableString],
+        args: &mut [&mut Dynamic],
+    ) -> Result<Dynamic, Box<EvalAltResult>> {
+        let path: Vec<_> = path.iter().map(|s| s.as_str()).collect();
+
+        match path.as_slice() {
+            ["users", "add"] => Ok((args[0].as_int().unwrap() + args[1].as_int().unwrap()).into()),
+            _ => Err(EvalAltResult::ErrorFunctionNotFound(path.join("."), Position::NONE).into()),
+        }
+    }
+}
+
+#[test]
+fn test_proxy() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+    let mut scope = Scope::new();
+
+    scope.push("api", Proxy::new(Api));
+
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "api.users.add(40, 2)")?,
+        42
+    );
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "api.count")?, 1);
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut scope, "let u = api.users; u.posts.count")?,
+        3
+    );
+    assert_eq!(
+        engine.eval_with_scope::<String>(&mut scope, "type_of(api.users)")?,
+        "proxy"
+    );
+    assert_eq!(
+        engine
+            .eval_with_scope::<Proxy>(&mut scope, "api.users.posts")?
+            .path()
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>(),
+        ["users", "posts"]
+    );
+    assert!(matches!(
+        *engine
+            .run_with_scope(&mut scope, "api.users.remove(1)")
+            .expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(ref s, ..) if s == "users.remove"
+    ));
+
+    Ok(())
+}
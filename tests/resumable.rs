@@ -0,0 +1,221 @@
+#![cfg(not(feature = "unchecked"))]
+#![cfg(not(feature = "no_function"))]
+use rhai::{Engine, EvalAltResult, Scope, Shared, INT};
+use std::task::Poll;
+
+#[test]
+fn test_resumable() -> Result<(), Box<EvalAltResult>> {
+    let engine = Shared::new(Engine::new());
+
+    let mut scope = Scope::new();
+    scope.push("total", 0 as INT);
+
+    let mut eval = engine.clone().eval_resumable::<INT>(
+        scope,
+        "
+            fn add(x, n) { x + n }
+
+            for n in 1..=100 { total = add(total, n); }
+            total * 2
+        ",
+    )?;
+
+    let mut steps = 0;
+    let mut last = 0;
+
+    let result = loop {
+        match eval.resume(10) {
+            Poll::Ready(result) => break result?,
+            Poll::Pending => {
+                steps += 1;
+                assert!(eval.num_operations() > last);
+                last = eval.num_operations();
+            }
+        }
+    };
+
+    assert_eq!(result, 10100);
+    assert!(steps > 10);
+    assert!(eval.is_done());
+    assert_eq!(
+        eval.into_scope().unwrap().get_value::<INT>("total"),
+        Some(5050)
+    );
+
+    // Nested blocks and loops are suspended and resumed in place, and the value of the last
+    // statement is kept
+    let mut eval = engine.clone().eval_resumable::<INT>(
+        Scope::new(),
+        "
+            let x = 0;
+            let i = 0;
+            while i < 10 {
+                i += 1;
+                if i % 2 == 0 {
+                    let j = 0;
+                    loop { j += 1; if j >= i { break; } }
+                    x += j;
+                }
+            }
+            if x > 0 { let y = x; y * 10 } else { 0 }
+        ",
+    )?;
+
+    let mut steps = 0;
+
+    let result = loop {
+        match eval.resume(1) {
+            Poll::Ready(result) => break result?,
+            Poll::Pending => steps += 1,
+        }
+    };
+
+    assert_eq!(result, 300);
+    assert!(steps > 50);
+    let scope = eval.scope().unwrap();
+    assert_eq!(scope.get_value::<INT>("x"), Some(30));
+    assert!(!scope.contains("j"));
+    assert!(!scope.contains("y"));
+
+    // Dropping the handle cancels the evaluation
+    let mut eval = engine.eval_resumable::<()>(Scope::new(), "loop {}")?;
+    assert!(eval.resume(100).is_pending());
+    assert!(eval.resume(100).is_pending());
+    assert!(!eval.is_done());
+    drop(eval);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "sync"))]
+#[test]
+fn test_resumable_same_thread() -> Result<(), Box<EvalAltResult>> {
+    use std::{cell::Cell, rc::Rc};
+
+    // Native functions need not be `Send` because the script runs on the calling thread
+    let counter = Rc::new(Cell::new(0 as INT));
+    let mut engine = Engine::new();
+    let c = counter.clone();
+    engine.register_fn("tick", move || c.set(c.get() + 1));
+
+    let mut eval =
+        Shared::new(engine).eval_resumable::<()>(Scope::new(), "for n in 0..100 { tick(); }")?;
+
+    assert!(eval.resume(20).is_pending());
+    let ticks = counter.get();
+    assert!(ticks > 0 && ticks < 100);
+
+    while eval.resume(20).is_pending() {
+        assert!(counter.get() >= ticks);
+    }
+    assert_eq!(counter.get(), 100);
+
+    Ok(())
+}
+
+#[test]
+fn test_resumable_fn_calls() -> Result<(), Box<EvalAltResult>> {
+    let engine = Shared::new(Engine::new());
+
+    // Calls to script-defined functions are suspended half-way
+    let mut eval = engine.clone().eval_resumable::<INT>(
+        Scope::new(),
+        "
+            fn double(n) {
+                let y = n;
+                y * 2
+            }
+            fn main() {
+                let x = 0;
+                for n in 0..100 { x += double(n); }
+                let z = double(x);
+                return z;
+            }
+            main()
+        ",
+    )?;
+
+    let mut steps = 0;
+
+    let result = loop {
+        match eval.resume(10) {
+            Poll::Ready(result) => break result?,
+            Poll::Pending => steps += 1,
+        }
+    };
+
+    assert_eq!(result, 19800);
+    assert!(steps > 50);
+
+    // Recursive calls always make progress, even with the smallest budget
+    let mut eval = engine.clone().eval_resumable::<INT>(
+        Scope::new(),
+        "
+            fn fib(n) {
+                if n < 2 { return n; }
+                let a = fib(n - 1);
+                let b = fib(n - 2);
+                a + b
+            }
+            let r = fib(6);
+            r
+        ",
+    )?;
+
+    let mut steps = 0;
+
+    let result = loop {
+        match eval.resume(1) {
+            Poll::Ready(result) => break result?,
+            Poll::Pending => steps += 1,
+        }
+    };
+
+    assert_eq!(result, 8);
+    assert!(steps > 50);
+    assert_eq!(eval.scope().unwrap().get_value::<INT>("r"), Some(8));
+
+    // Errors in suspended calls are reported as in normal evaluations
+    let mut eval = engine.eval_resumable::<INT>(
+        Scope::new(),
+        "
+            fn foo(x) {
+                let y = x + 1;
+                y.bar()
+            }
+            foo(41)
+        ",
+    )?;
+
+    let err = loop {
+        match eval.resume(1) {
+            Poll::Ready(result) => break result.expect_err("error"),
+            Poll::Pending => (),
+        }
+    };
+
+    assert!(
+        matches!(*err, EvalAltResult::ErrorInFunctionCall(ref f, ..) if f == "foo"),
+        "{}",
+        err
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "profiling")]
+#[test]
+fn test_resumable_hooks() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.start_profiling();
+
+    let mut eval = Shared::new(engine).eval_resumable::<INT>(Scope::new(), "40 + 2")?;
+
+    assert!(matches!(
+        eval.resume(100),
+        Poll::Ready(Err(err)) if matches!(*err, EvalAltResult::ErrorSystem(..))
+    ));
+    assert!(eval.is_done());
+
+    Ok(())
+}
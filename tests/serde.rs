@@ -2,7 +2,7 @@
 
 use rhai::{
     serde::{from_dynamic, to_dynamic},
-    Dynamic, Engine, EvalAltResult, ImmutableString, INT,
+    Dynamic, Engine, EvalAltResult, FnPtr, ImmutableString, INT,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -823,3 +823,226 @@ fn test_serde_json_borrowed_string() {
     println!("value: {:?}", value);
     let _: Dynamic = serde_json::from_value(value).unwrap();
 }
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_serde_fn_ptr() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let ast = engine.compile(
+        "
+            fn add(x, y) { x + y }
+            Fn(\"add\").curry(40)
+        ",
+    )?;
+
+    let fn_ptr = engine.eval_ast::<FnPtr>(&ast)?;
+
+    let json = serde_json::to_string(&fn_ptr).unwrap();
+    assert_eq!(json, r#"{"name":"add","curry":[40]}"#);
+
+    let fn_ptr: FnPtr = serde_json::from_str(&json).unwrap();
+    let fn_ptr = fn_ptr.rehydrate(&engine, &ast)?;
+    assert_eq!(fn_ptr.fn_name(), "add");
+    assert_eq!(fn_ptr.call::<INT>(&engine, &ast, (2 as INT,))?, 42);
+
+    let fn_ptr: FnPtr = serde_json::from_str(r#""add""#).unwrap();
+    assert!(!fn_ptr.is_curried());
+    fn_ptr.rehydrate(&engine, &ast)?;
+
+    let fn_ptr: FnPtr = serde_json::from_str(r#""len""#).unwrap();
+    fn_ptr.rehydrate(&engine, &ast)?;
+
+    let fn_ptr: FnPtr = serde_json::from_str(r#"{"name":"missing","curry":[]}"#).unwrap();
+    assert!(matches!(
+        *fn_ptr.rehydrate(&engine, &ast).expect_err("should error"),
+        EvalAltResult::ErrorFunctionNotFound(..)
+    ));
+
+    let fn_ptr: FnPtr = serde_json::from_str(r#"{"name":"add","curry":[1,2,3]}"#).unwrap();
+    assert!(fn_ptr.rehydrate(&engine, &ast).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_object"))]
+fn test_serde_scope() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{Map, Scope, ScopeMetadata};
+
+    let engine = Engine::new();
+
+    let mut scope = Scope::new();
+    scope.push("x", 40 as INT);
+    scope.push_constant("y", 2 as INT);
+
+    let (map, metadata) = scope.to_map_with_metadata();
+    let json = serde_json::to_string(&map).unwrap();
+    let metadata_json = serde_json::to_string(&metadata).unwrap();
+
+    let map: Map = serde_json::from_str(&json).unwrap();
+    let metadata: ScopeMetadata = serde_json::from_str(&metadata_json).unwrap();
+
+    let mut scope = Scope::from_map_with_metadata(map, &metadata);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "x + y")?, 42);
+    assert_eq!(scope.is_constant("y"), Some(true));
+
+    let config: Map = serde_json::from_str(r#"{ "a": 40, "b": 2 }"#).unwrap();
+    let mut scope = Scope::from_map(config);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "a + b")?, 42);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_serde_de_borrowed() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Payload<'a> {
+        name: &'a str,
+        data: &'a [u8],
+        tags: Vec<&'a str>,
+    }
+
+    let engine = Engine::new();
+
+    let value = engine.eval::<Dynamic>(
+        r#"
+            let data = blob(3, 0x42);
+            #{ name: "hello", data: data, tags: ["a", "b"] }
+        "#,
+    )?;
+
+    let payload: Payload = from_dynamic(&value)?;
+
+    assert_eq!(
+        payload,
+        Payload {
+            name: "hello",
+            data: &[0x42, 0x42, 0x42],
+            tags: vec!["a", "b"],
+        }
+    );
+
+    let map = value.read_lock::<Map>().unwrap();
+    let name = map["name"].read_lock::<ImmutableString>().unwrap();
+    assert_eq!(payload.name.as_ptr(), name.as_ptr());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_closure"))]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_position"))]
+fn test_serde_portable_fn_ptr() -> Result<(), Box<EvalAltResult>> {
+    use rhai::serde::PortableFnPtr;
+
+    let engine = Engine::new();
+
+    let script = r#"
+        fn scale(x) { x * 10 }
+
+        let base = [1, 2];
+        let step = |x| x + 1;
+        let label = "total";
+
+        |y| {
+            let total = scale(base.reduce(|s, x| s + x, 0)) + step.call(y);
+            `${label}: ${total}`
+        }
+    "#;
+
+    let ast = engine.compile(script)?;
+    let closure = engine.eval_ast::<FnPtr>(&ast)?;
+
+    let portable = PortableFnPtr::new(&engine, script, &closure)?;
+    let json = serde_json::to_string(&portable).unwrap();
+
+    let portable: PortableFnPtr = serde_json::from_str(&json).unwrap();
+    let (ast, closure) = portable.restore(&engine)?;
+
+    assert_eq!(
+        closure.call::<String>(&engine, &ast, (1 as INT,))?,
+        "total: 32"
+    );
+
+    let fn_ptr = FnPtr::new("scale")?;
+    let portable = PortableFnPtr::new(&engine, script, &fn_ptr)?;
+    let (ast, fn_ptr) = portable.restore(&engine)?;
+    assert_eq!(fn_ptr.call::<INT>(&engine, &ast, (4 as INT,))?, 40);
+
+    let portable = PortableFnPtr::new(&engine, "fn foo() {}", &fn_ptr)?;
+    assert!(portable.restore(&engine).is_err());
+
+    let mut fn_ptr = FnPtr::new("scale")?;
+    fn_ptr.add_curry(Dynamic::from(std::time::Duration::from_secs(1)));
+    assert!(PortableFnPtr::new(&engine, script, &fn_ptr).is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_module"))]
+#[cfg(not(feature = "no_function"))]
+#[cfg(not(feature = "no_index"))]
+fn test_serde_snapshot() -> Result<(), Box<EvalAltResult>> {
+    use rhai::{module_resolvers::StaticModuleResolver, InterpreterState, Module, Snapshot};
+
+    let mut engine = Engine::new();
+
+    let mut module = Module::new();
+    module.set_var("answer", 42 as INT);
+    let mut resolver = StaticModuleResolver::new();
+    resolver.insert("hello", module);
+    engine.set_module_resolver(resolver);
+
+    let mut state = InterpreterState::new(&engine);
+    state.scope_mut().push("x", 1 as INT);
+
+    engine.eval_with_state::<()>(
+        &mut state,
+        r#"
+            import "hello" as h;
+            fn init() {}
+            const LIMIT = 10;
+            let x = 'x';
+            let data = [blob(2, 0x42), #{ a: 1 }];
+        "#,
+    )?;
+
+    // Imported modules are kept across evaluations
+    assert_eq!(
+        engine.eval_with_state::<INT>(&mut state, "h::answer + LIMIT")?,
+        52
+    );
+    assert_eq!(state.iter_imports().collect::<Vec<_>>(), ["h"]);
+
+    let json = serde_json::to_string(&state.snapshot(&engine)?).unwrap();
+    let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+    let mut state = InterpreterState::restore(&engine, snapshot)?;
+
+    assert_eq!(state.scope().len(), 4);
+    assert_eq!(state.scope().is_constant("LIMIT"), Some(true));
+    assert_eq!(state.scope().get_value::<char>("x"), Some('x'));
+    assert_eq!(
+        engine.eval_with_state::<INT>(
+            &mut state,
+            r#"
+                fn limit() { global::LIMIT }
+                h::answer + limit() + data[0].len() + data[1].a
+            "#
+        )?,
+        55
+    );
+
+    #[derive(Debug, Clone)]
+    struct Foo;
+
+    state.scope_mut().push("foo", Foo);
+    assert!(state.snapshot(&engine).is_err());
+
+    Ok(())
+}
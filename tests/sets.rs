@@ -0,0 +1,109 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Array, Engine, EvalAltResult, Set, INT};
+
+#[test]
+fn test_sets() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("len(set())")?, 0);
+    assert_eq!(engine.eval::<INT>("len(set([1, 2, 2, 3, 3, 3]))")?, 3);
+    assert!(engine.eval::<bool>("let s = set([1, 2, 3]); 2 in s")?);
+    assert!(!engine.eval::<bool>("let s = set([1, 2, 3]); 42 in s")?);
+    assert!(engine.eval::<bool>(r#"let s = set(["a", 'b', [1, 2]]); [1, 2] in s"#)?);
+    assert!(engine.eval::<bool>("let s = set([1, 2, 3]); s == set([3, 2, 1])")?);
+    assert!(engine.eval::<bool>("let s = set([1, 2, 3]); s != set([1, 2])")?);
+
+    assert_eq!(
+        engine.eval::<INT>("let s = set(); for x in 0..10 { insert(s, x % 3); } len(s)")?,
+        3
+    );
+    assert_eq!(
+        engine.eval::<INT>("let total = 0; for x in set([1, 2, 2, 3]) { total += x; } total")?,
+        6
+    );
+
+    let s = engine.eval::<Set>("set([1, 2, 3])")?;
+    assert_eq!(s.len(), 3);
+    assert!(s.contains(&(2 as INT).into()));
+
+    #[cfg(not(feature = "no_object"))]
+    {
+        assert_eq!(
+            engine
+                .eval::<Array>(
+                    "
+                    let s = set();
+                    s.insert(1);
+                    s.insert(2);
+                    s.insert(3);
+                    s.remove(2);
+                    let a = s.to_array();
+                    a.sort();
+                    a
+                "
+                )?
+                .into_iter()
+                .map(|v| v.as_int().unwrap())
+                .collect::<Vec<_>>(),
+            [1, 3]
+        );
+        assert_eq!(
+            engine.eval::<INT>("set([1, 2, 3]).union(set([3, 4, 5])).len")?,
+            5
+        );
+        assert_eq!(
+            engine.eval::<INT>("set([1, 2, 3]).intersection(set([3, 4, 5])).to_array()[0]")?,
+            3
+        );
+        assert_eq!(
+            engine.eval::<INT>("set([1, 2, 3]).difference(set([3, 4, 5])).len")?,
+            2
+        );
+        assert!(engine.eval::<bool>("set([1, 2]).is_subset(set([1, 2, 3]))")?);
+        assert!(!engine.eval::<bool>("[1, 2, 2].to_set().insert(2)")?);
+    }
+
+    assert!(matches!(
+        *engine
+            .run("set([1, 2, Fn(\"foo\")])")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_sets_custom_types() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone)]
+    struct Foo;
+
+    let mut engine = Engine::new();
+    engine.register_fn("foo", || Foo);
+
+    assert!(matches!(
+        *engine.run("set([foo()])").expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+    assert!(matches!(
+        *engine.run("set([[1, [foo()]]])").expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+    assert!(!engine.eval::<bool>("let s = set([1, 2]); [foo()] in s")?);
+    assert!(!engine.eval::<bool>("set([1]).remove([2, foo()])")?);
+
+    #[cfg(not(feature = "no_object"))]
+    assert!(matches!(
+        *engine
+            .run("set([#{ a: foo() }])")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+
+    assert_eq!(
+        engine.eval::<INT>("switch [foo()] { 1 => 1, _ => 42 }")?,
+        42
+    );
+
+    Ok(())
+}
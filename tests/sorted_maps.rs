@@ -0,0 +1,85 @@
+#![cfg(not(feature = "no_index"))]
+use rhai::{Dynamic, Engine, EvalAltResult, SortedMap, INT};
+
+#[test]
+fn test_sorted_maps() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<INT>("len(sorted_map())")?, 0);
+    assert_eq!(
+        engine.eval::<INT>("let m = sorted_map(); m[3] = 1; m[1] = 2; m[2] = 3; m.first_key")?,
+        1
+    );
+    assert_eq!(
+        engine.eval::<INT>("let m = sorted_map(); m[3] = 1; m[1] = 2; m[2] = 3; m[3]")?,
+        1
+    );
+    assert!(engine.eval::<bool>(r#"let m = sorted_map(); m["x"] = 1; "x" in m"#)?);
+    assert!(!engine.eval::<bool>("let m = sorted_map(); m[1] = 1; 2 in m")?);
+    assert!(engine.eval::<bool>("let m = sorted_map(); m.get(42) == ()")?);
+    assert_eq!(
+        engine
+            .eval::<String>(r#"let m = sorted_map(); m[2] = "b"; m['a'] = [1]; m.to_string()"#)?,
+        r#"#{2: "b", 'a': [1]}"#
+    );
+    assert_eq!(
+        engine.eval::<INT>(
+            "let m = sorted_map(); m[1..3] = 1; m[0..5] = 2; m[0..=5] = 3; m.values()[0]"
+        )?,
+        2
+    );
+
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let m = sorted_map();
+                m.set(3, "c");
+                m.set(1, "a");
+                m.set(2.5, "b");
+                m.set(1.0, "x");
+                m.remove(3);
+                let s = "";
+                for v in m.values() { s += v; }
+                s
+            "#
+        )?,
+        "xb"
+    );
+
+    #[cfg(not(feature = "no_std"))]
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let events = sorted_map();
+                let now = timestamp();
+
+                events[now + 10.0] = "c";
+                events[now + 1.0] = "a";
+                events[now + 5.0] = "b";
+
+                let s = "";
+                for v in events.range(now, now + 6.0).values() { s += v; }
+                for v in events.range(now + 2.0, ()).values() { s += v; }
+                s
+            "#
+        )?,
+        "abbc"
+    );
+
+    assert!(matches!(
+        *engine
+            .run("let m = sorted_map(); m[[1, 2]] = 1;")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+
+    let m = engine.eval::<SortedMap>(r#"let m = sorted_map(); m["y"] = 1; m["x"] = 2; m"#)?;
+    assert_eq!(m.len(), 2);
+    assert_eq!(m.first().unwrap().0.clone().into_string().unwrap(), "x");
+    assert_eq!(m.get(&Dynamic::from("y")).unwrap().as_int().unwrap(), 1);
+    assert!(!SortedMap::is_valid_key(&Dynamic::UNIT));
+
+    Ok(())
+}
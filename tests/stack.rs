@@ -1,5 +1,5 @@
 #![cfg(not(feature = "unchecked"))]
-use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+use rhai::{Engine, EvalAltResult, NativeCallContext, ParseErrorType, INT};
 
 #[test]
 #[cfg(not(feature = "no_function"))]
@@ -35,6 +35,30 @@ fn test_stack_overflow_fn_calls() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+#[cfg(not(feature = "no_function"))]
+fn test_stack_remaining_call_depth() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.set_max_call_levels(10);
+    engine.register_fn("depth_left", |ctx: NativeCallContext| {
+        ctx.remaining_call_depth() as INT
+    });
+
+    assert_eq!(engine.eval::<INT>("depth_left()")?, 9);
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn foo(n) { if n == 0 { depth_left() } else { foo(n - 1) } }
+                depth_left() - foo(3)
+            "
+        )?,
+        4
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_stack_overflow_parsing() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();
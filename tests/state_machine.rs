@@ -0,0 +1,127 @@
+#![cfg(not(feature = "no_custom_syntax"))]
+use rhai::packages::{Package, StateMachineConfig, StateMachinePackage};
+use rhai::{Engine, EvalAltResult, ParseErrorType, StateMachine};
+use std::sync::{Arc, RwLock};
+
+const DOOR: &str = "
+    machine Door {
+        state Open { on close => Closed }
+        state Closed { on open => Open; on lock => Locked, }
+        state Locked { on unlock => Closed }
+    }
+";
+
+#[test]
+fn test_state_machine() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    StateMachinePackage::new().register_into_engine(&mut engine);
+
+    assert_eq!(
+        engine.eval::<String>(&format!(
+            r#"
+                {DOOR}
+                let ok = fire(Door, "close") && fire(Door, "lock") && !fire(Door, "open");
+                if ok {{ state(Door) }} else {{ "" }}
+            "#
+        ))?,
+        "Locked"
+    );
+
+    assert!(engine.eval::<bool>(&format!(
+        r#"
+            {DOOR}
+            let d = Door;
+            fire(d, "close");
+            state(Door) == "Open" && state(d) == "Closed" && can_fire(d, "lock")
+        "#
+    ))?);
+
+    let door = engine.eval::<StateMachine>(&format!("{DOOR} fire(Door, \"close\"); Door"))?;
+    assert_eq!(door.name(), "Door");
+    assert_eq!(door.state(), "Closed");
+    assert_eq!(door.events().collect::<Vec<_>>(), ["lock", "open"]);
+    assert_eq!(door.target("lock"), Some("Locked"));
+
+    Ok(())
+}
+
+#[test]
+fn test_state_machine_hooks() -> Result<(), Box<EvalAltResult>> {
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let enter_log = log.clone();
+    let exit_log = log.clone();
+
+    let config = StateMachineConfig::new()
+        .on_enter(move |machine, state| {
+            enter_log
+                .write()
+                .unwrap()
+                .push(format!("enter {machine}.{state}"));
+            Ok(())
+        })
+        .on_exit(move |machine, state| {
+            if state == "Locked" {
+                return Err("the door is locked".into());
+            }
+            exit_log
+                .write()
+                .unwrap()
+                .push(format!("exit {machine}.{state}"));
+            Ok(())
+        });
+
+    let mut engine = Engine::new();
+    StateMachinePackage::with_config(config).register_into_engine(&mut engine);
+
+    engine.run(&format!(
+        r#"
+            {DOOR}
+            fire(Door, "close");
+            fire(Door, "open");
+            fire(Door, "lock");
+        "#
+    ))?;
+
+    assert_eq!(
+        *log.read().unwrap(),
+        [
+            "exit Door.Open",
+            "enter Door.Closed",
+            "exit Door.Closed",
+            "enter Door.Open"
+        ]
+    );
+
+    // An error in the exit hook leaves the state unchanged
+    assert!(engine
+        .run(&format!(
+            r#"
+                {DOOR}
+                fire(Door, "close");
+                fire(Door, "lock");
+                fire(Door, "unlock");
+            "#
+        ))
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_state_machine_errors() {
+    let mut engine = Engine::new();
+    StateMachinePackage::new().register_into_engine(&mut engine);
+
+    for script in [
+        "machine M { }",
+        "machine M { state A { on tick => B } }",
+        "machine M { state A { } state A { } }",
+        "machine M { state A { on tick => A; on tick => A } }",
+        "machine M { state A { tick => A } }",
+    ] {
+        assert!(matches!(
+            engine.compile(script).unwrap_err().err_type(),
+            ParseErrorType::BadInput(..)
+        ));
+    }
+}
@@ -100,6 +100,38 @@ fn test_string_dynamic() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_string_pool() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    let script = r#"let tenant = "a rather long tenant configuration string";"#;
+
+    let constant = |ast: &rhai::AST| -> ImmutableString {
+        let (.., value) = ast.iter_literal_variables(false, true).next().unwrap();
+        value.into_immutable_string().unwrap()
+    };
+
+    let ast1 = engine.compile(script)?;
+    let ast2 = engine.compile(script)?;
+    assert!(!constant(&ast1).ptr_eq(&constant(&ast2)));
+
+    engine.set_strings_pool_capacity(100);
+    assert_eq!(engine.strings_pool_capacity(), 100);
+
+    let ast1 = engine.compile(script)?;
+    let ast2 = engine.compile(script)?;
+    assert!(constant(&ast1).ptr_eq(&constant(&ast2)));
+    engine.run_ast(&ast2)?;
+
+    engine.set_strings_pool_capacity(0);
+    assert_eq!(engine.strings_pool_capacity(), 0);
+
+    let ast3 = engine.compile(script)?;
+    assert!(!constant(&ast1).ptr_eq(&constant(&ast3)));
+
+    Ok(())
+}
+
 #[test]
 fn test_string_mut() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();
@@ -326,6 +358,44 @@ fn test_string_split() -> Result<(), Box<EvalAltResult>> {
         )?,
         2
     );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "a,b,c,d"; x.splitn(",", 2)[1]"#)?,
+        "b,c,d"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "a,b,c,d"; x.rsplit(',')[0]"#)?,
+        "d"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"let x = "a,b,c,d"; x.rsplitn(",", 2)[1]"#)?,
+        "a,b,c"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_string_lines_glob() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let result = "";
+                for line in "hello\r\n\nworld!\n".lines() {
+                    result += `[${line}]`;
+                }
+                result
+            "#
+        )?,
+        "[hello][][world!]"
+    );
+
+    assert!(engine.eval::<bool>(r#""report-2022.txt".matches_glob("*.txt")"#)?);
+    assert!(engine.eval::<bool>(r#""report-2022.txt".matches_glob("report-20??.*")"#)?);
+    assert!(engine.eval::<bool>(r#""x5".matches_glob("x[0-9]")"#)?);
+    assert!(!engine.eval::<bool>(r#""xa".matches_glob("x[!a-z]")"#)?);
+    assert!(!engine.eval::<bool>(r#""report.txt.bak".matches_glob("*.txt")"#)?);
 
     Ok(())
 }
@@ -416,3 +486,145 @@ Undeniable logic:
 
     Ok(())
 }
+
+#[test]
+fn test_string_interpolated_arena() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine.with_arena(true);
+    assert!(engine.has_arena());
+
+    #[cfg(not(feature = "no_function"))]
+    let script = "
+        fn sum(a, b, c, d, e, f, g) { a + b + c + d + e + f + g }
+        let x = 40;
+        `hello ${x + 2} ${sum(1, 2, 3, 4, 5, 6, 7)} ${[x]} worlds!`
+    ";
+    #[cfg(feature = "no_function")]
+    let script = "
+        let x = 40;
+        `hello ${x + 2} 28 ${[x]} worlds!`
+    ";
+
+    #[cfg(not(feature = "no_index"))]
+    for _ in 0..10 {
+        assert_eq!(engine.eval::<String>(script)?, "hello 42 28 [40] worlds!");
+    }
+
+    for _ in 0..10 {
+        assert_eq!(
+            engine.eval::<String>(r#"let x = "!"; `${x}${`${x}`}${x.len}`"#)?,
+            "!!1"
+        );
+    }
+
+    engine.with_arena(false);
+    assert!(!engine.has_arena());
+
+    Ok(())
+}
+
+#[test]
+fn test_string_format_spec() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    #[cfg(not(feature = "no_float"))]
+    assert_eq!(
+        engine.eval::<String>(r#"format("{}: {:>8.2}|", "total", 3.14159)"#)?,
+        "total:     3.14|"
+    );
+
+    assert_eq!(
+        engine.eval::<String>(r#"format("[{:<5}][{:^7}][{:*>6}]", "ab", "abc", 42)"#)?,
+        "[ab   ][  abc  ][****42]"
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"format("{:05}|{:4}|{:4}", -42, 7, "x")"#)?,
+        "-0042|   7|x   "
+    );
+    assert_eq!(
+        engine.eval::<String>(r#"format("{1}{0}{{}}{:.3}", "hello", "!")"#)?,
+        "!hello{}hel"
+    );
+    assert_eq!(engine.eval::<String>(r#"format("{:?}", "hi")"#)?, r#""hi""#);
+    assert_eq!(
+        engine.eval::<String>(r#"format("no values")"#)?,
+        "no values"
+    );
+
+    assert!(engine.eval::<String>(r#"format("{", 1)"#).is_err());
+    assert!(engine.eval::<String>(r#"format("}", 1)"#).is_err());
+    assert!(engine.eval::<String>(r#"format("{} {}", 1)"#).is_err());
+    assert!(engine.eval::<String>(r#"format("{:x}", 1)"#).is_err());
+    assert!(engine.eval::<String>(r#"format("{:.70000}", 1)"#).is_err());
+    assert!(engine
+        .eval::<String>(r#"format("{:.99999999999999999999}", 1)"#)
+        .is_err());
+
+    #[cfg(not(feature = "no_float"))]
+    assert!(engine
+        .eval::<String>(r#"format("{:.70000}", 1.0)"#)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "unchecked"))]
+#[cfg(not(feature = "no_float"))]
+fn test_string_format_spec_limits() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_max_string_size(10);
+
+    assert_eq!(engine.eval::<String>(r#"format("{:.5}", 1.0)"#)?, "1.00000");
+    assert_eq!(
+        engine.eval::<String>(r#"format("{:.20}", "hello")"#)?,
+        "hello"
+    );
+
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"format("{:.20}", 1.0)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+    assert!(matches!(
+        *engine
+            .eval::<String>(r#"format("{:20}", 1.0)"#)
+            .expect_err("should error"),
+        EvalAltResult::ErrorDataTooLarge(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_string_interner() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .set_max_interned_strings(100)
+        .set_max_interned_string_len(8)
+        .intern_strings(["hello", "world", "too long to intern"]);
+
+    let stats = engine.interner_stats();
+    assert_eq!(stats.capacity, 100);
+    assert_eq!(stats.max_string_len, 8);
+
+    let len = stats.len;
+    let hits = stats.hits;
+
+    engine.intern_strings(["hello"]);
+
+    let stats = engine.interner_stats();
+    assert_eq!(stats.len, len);
+    assert_eq!(stats.hits, hits + 1);
+
+    engine.set_max_interned_strings(0);
+    assert_eq!(engine.interner_stats().len, 0);
+
+    engine.intern_strings(["hello"]);
+    assert_eq!(engine.interner_stats().len, 0);
+
+    Ok(())
+}
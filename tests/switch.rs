@@ -352,3 +352,83 @@ fn test_switch_ranges() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_switch_patterns() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Clone)]
+    struct TestStruct(INT);
+
+    let mut engine = Engine::new();
+    engine
+        .register_type_with_name::<TestStruct>("TestStruct")
+        .register_fn("new_ts", TestStruct)
+        .register_fn("value", |x: &mut TestStruct| x.0);
+
+    let script = "
+        fn test(x) {
+            switch x {
+                42 => 0,
+                is string => x.len,
+                is TestStruct if x.value() > 100 => 100,
+                is TestStruct => x.value(),
+                case [a, _, c] => a + c,
+                case [a, b] if a > b => a - b,
+                case { x, y } => x * y,
+                case #{ z } => -z,
+                _ => -1
+            }
+        }
+    ";
+
+    #[cfg(not(feature = "no_function"))]
+    {
+        let ast = engine.compile(script)?;
+        let test = |x: &str| engine.eval_ast::<INT>(&ast.merge(&engine.compile(x).unwrap()));
+
+        assert_eq!(test("test(42)")?, 0);
+        assert_eq!(test("test(\"hello\")")?, 5);
+        assert_eq!(test("test(new_ts(7))")?, 7);
+        assert_eq!(test("test(new_ts(700))")?, 100);
+        assert_eq!(test("test(1)")?, -1);
+        #[cfg(not(feature = "no_index"))]
+        {
+            assert_eq!(test("test([1, 2, 3])")?, 4);
+            assert_eq!(test("test([5, 2])")?, 3);
+            assert_eq!(test("test([2, 5])")?, -1);
+            assert_eq!(test("test([1, 2, 3, 4])")?, -1);
+        }
+        #[cfg(not(feature = "no_object"))]
+        {
+            assert_eq!(test("test(#{ x: 6, y: 7, w: 1 })")?, 42);
+            assert_eq!(test("test(#{ x: 6, z: 7 })")?, -7);
+            assert_eq!(test("test(#{ x: 6 })")?, -1);
+        }
+    }
+
+    // Pattern variables shadow and do not leak
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                const a = 1;
+                let r = switch [40, 2] {
+                    case [a, b] => a + b,
+                    _ => 0
+                };
+                r + a
+            "
+        )?,
+        43
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(matches!(
+        engine
+            .compile("switch x { case [a, a] => 0 }")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::DuplicatedVariable(..)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+#![cfg(not(feature = "no_function"))]
+use rhai::packages::{Package, TasksPackage};
+use rhai::{Engine, EvalAltResult, INT};
+use std::sync::{Arc, RwLock};
+
+fn make_engine() -> (Engine, Arc<RwLock<Vec<String>>>) {
+    let mut engine = Engine::new();
+    engine.register_global_module(TasksPackage::new().as_shared_module());
+
+    let log = Arc::new(RwLock::new(Vec::new()));
+    let logger = log.clone();
+    engine.on_print(move |s| logger.write().unwrap().push(s.to_string()));
+
+    (engine, log)
+}
+
+#[test]
+fn test_tasks() -> Result<(), Box<EvalAltResult>> {
+    let (engine, log) = make_engine();
+
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn add(x, y) { x + y }
+                let t1 = spawn(|| 40);
+                let t2 = spawn(curry(curry(Fn(\"add\"), 1), 1));
+                join(t1) + join(t2)
+            "
+        )?,
+        42
+    );
+
+    assert!(engine.eval::<bool>("let t = spawn(|| 42); join(t); is_done(t)")?);
+
+    // Pending tasks are run before the evaluation returns
+    engine.run(
+        r#"
+            spawn(|| print("task"));
+            print("main");
+        "#,
+    )?;
+    assert_eq!(*log.read().unwrap(), ["main", "task"]);
+
+    // Errors are raised again when joined
+    assert!(engine.run("let t = spawn(|| throw 42); join(t);").is_err());
+    assert!(engine
+        .run("let t = spawn(|| 42); t = spawn(|| join(t)); join(t);")
+        .is_err());
+
+    // Errors keep their type when joined more than once
+    assert!(matches!(
+        engine
+            .run("let t = spawn(|| [1][5]); try { join(t) } catch {} join(t);")
+            .unwrap_err()
+            .unwrap_inner(),
+        EvalAltResult::ErrorArrayBounds(1, 5, ..)
+    ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "unchecked"))]
+#[test]
+fn test_tasks_scheduling() -> Result<(), Box<EvalAltResult>> {
+    let (engine, log) = make_engine();
+
+    // Tasks are run in between statements without being joined
+    assert!(engine.eval::<bool>(
+        r#"
+            let t = spawn(|| print("task"));
+            let n = 0;
+            while !is_done(t) { n += 1; }
+            print("main");
+            n > 0
+        "#
+    )?);
+    assert_eq!(*log.read().unwrap(), ["task", "main"]);
+
+    // Unfinished tasks take turns in time slices
+    log.write().unwrap().clear();
+    engine.run(
+        r#"
+            fn work(name) {
+                let n = 0;
+                for i in 0..3 {
+                    print(name + i);
+                    for j in 0..100 { n += j; }
+                }
+            }
+            let a = spawn(Fn("work").curry("a"));
+            let b = spawn(Fn("work").curry("b"));
+            while !is_done(a) || !is_done(b) {}
+        "#,
+    )?;
+    let order = log.read().unwrap().clone();
+    assert_eq!(order.len(), 6);
+    let pos = |s: &str| order.iter().position(|x| x == s).unwrap();
+    assert!(pos("b0") < pos("a2"));
+    assert!(pos("a0") < pos("b2"));
+
+    // A task waiting for another task does not block it
+    assert_eq!(
+        engine.eval::<INT>(
+            r#"
+                fn sum() {
+                    let n = 0;
+                    for i in 0..100 { n += i; }
+                    n
+                }
+                fn wait(t) {
+                    while !is_done(t) {}
+                    join(t) + 1
+                }
+                let a = spawn(Fn("sum"));
+                let b = spawn(Fn("wait").curry(a));
+                join(b)
+            "#
+        )?,
+        4951
+    );
+
+    let mut engine = engine;
+    engine.set_max_operations(1000);
+
+    assert!(matches!(
+        *engine.run("spawn(|| { loop {} }); loop {}").unwrap_err(),
+        EvalAltResult::ErrorTooManyOperations(..)
+    ));
+
+    Ok(())
+}
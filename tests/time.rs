@@ -107,3 +107,35 @@ fn test_timestamp_op() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_timestamp_switch_sort() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<String>(
+            r#"
+                let start = timestamp() - 90;
+
+                switch timestamp() - start {
+                    0..60 => "minute",
+                    60..3600 => "hour",
+                    _ => "later"
+                }
+            "#
+        )?,
+        "hour"
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert!(engine.eval::<bool>(
+        "
+            let t = timestamp();
+            let x = [t + 3, t + 1, t + 2];
+            x.sort();
+            x[0] == t + 1 && x[2] == t + 3
+        "
+    )?);
+
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use rhai::{Engine, EvalAltResult, ParseErrorType, INT};
+use rhai::{Engine, EvalAltResult, Module, ParseErrorType, Shared, INT};
+
+#[cfg(not(feature = "no_custom_syntax"))]
+use rhai::CustomOperatorKind;
 
 #[test]
 fn test_tokens_disabled() {
@@ -30,6 +33,30 @@ fn test_tokens_disabled() {
     ));
 }
 
+#[cfg(not(feature = "no_position"))]
+#[test]
+fn test_tokens_error_span() {
+    use rhai::{Position, Span};
+
+    let engine = Engine::new();
+
+    let err = engine
+        .compile("let x = 42;\nlet y = x === 1;")
+        .expect_err("should error");
+
+    assert_eq!(err.position(), Position::new(2, 11));
+    assert_eq!(
+        err.span(),
+        Span::new(Position::new(2, 11), Position::new(2, 13))
+    );
+    assert!(err.to_string().ends_with("(line 2, position 11-13)"));
+
+    let err = engine.compile("let x = ;").expect_err("should error");
+
+    assert_eq!(err.span(), Span::new(Position::new(1, 9), Position::NONE));
+    assert!(err.to_string().ends_with("(line 1, position 9)"));
+}
+
 #[cfg(not(feature = "no_custom_syntax"))]
 #[test]
 fn test_tokens_custom_operator_identifiers() -> Result<(), Box<EvalAltResult>> {
@@ -85,6 +112,97 @@ fn test_tokens_custom_operator_symbol() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[cfg(not(feature = "no_custom_syntax"))]
+#[test]
+fn test_tokens_custom_operator_kinds() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    // Right-binding binary operator
+    engine
+        .register_custom_operator_with_precedence("pow", 170, CustomOperatorKind::BinaryRight)
+        .unwrap();
+    engine.register_fn("pow", |x: INT, y: INT| x.pow(y as u32));
+
+    assert_eq!(engine.eval_expression::<INT>("2 pow 3 pow 2")?, 512);
+    assert_eq!(engine.eval_expression::<INT>("1 + 2 pow 3 * 2")?, 65);
+
+    // Prefix operator
+    engine
+        .register_custom_operator_with_precedence("~", 200, CustomOperatorKind::Prefix)
+        .unwrap();
+    engine.register_fn("~", |x: INT| !x);
+
+    assert_eq!(engine.eval_expression::<INT>("~0 + 1")?, 0);
+    assert_eq!(engine.eval_expression::<INT>("~~42")?, 42);
+    assert_eq!(engine.eval_expression::<INT>("3 * ~1")?, -6);
+    assert!(engine.compile_expression("1 ~ 2").is_err());
+
+    // Low-precedence prefix operator applies to the rest of the expression
+    engine
+        .register_custom_operator_with_precedence("neg", 10, CustomOperatorKind::Prefix)
+        .unwrap();
+    engine.register_fn("neg", |x: INT| -x);
+
+    assert_eq!(engine.eval_expression::<INT>("neg 1 + 2")?, -3);
+
+    // Postfix operator
+    engine
+        .register_custom_operator_with_precedence("percent", 190, CustomOperatorKind::Postfix)
+        .unwrap();
+    engine.register_fn("percent", |x: INT| x * 10);
+
+    assert_eq!(engine.eval_expression::<INT>("1 + 2 percent * 3")?, 61);
+    assert_eq!(
+        engine.eval_expression::<INT>("(1 + 2) percent percent")?,
+        300
+    );
+
+    // Re-registering as a binary operator
+    engine.register_custom_operator("percent", 160).unwrap();
+    engine.register_fn("percent", |x: INT, y: INT| x * y / 100);
+
+    assert_eq!(engine.eval_expression::<INT>("50 percent 200")?, 100);
+
+    Ok(())
+}
+
+#[cfg(not(feature = "no_custom_syntax"))]
+#[test]
+fn test_tokens_custom_operator_module() -> Result<(), Box<EvalAltResult>> {
+    let mut module = Module::new();
+
+    assert!(module
+        .set_custom_operator("#", 0, |x: INT, y: INT| Ok(x + y))
+        .is_err());
+
+    module
+        .set_custom_operator("#", 160, |x: INT, y: INT| Ok((x * y) - (x + y)))
+        .unwrap();
+    module
+        .set_custom_operator("foo", 160, |x: INT, y: INT| Ok(x - y))
+        .unwrap();
+
+    let module: Shared<Module> = module.into();
+
+    let mut engine = Engine::new();
+    assert!(engine.compile("1 # 2").is_err());
+
+    engine.register_global_module(module.clone());
+
+    assert_eq!(engine.eval_expression::<INT>("1 + 2 * 3 # 4 - 5 / 6")?, 15);
+    assert_eq!(engine.eval_expression::<INT>("10 foo 3 * 2")?, 4);
+
+    #[cfg(not(feature = "no_module"))]
+    {
+        let mut engine = Engine::new();
+        engine.register_static_module("ops", module);
+
+        assert_eq!(engine.eval_expression::<INT>("1 + 2 * 3 # 4 - 5 / 6")?, 15);
+    }
+
+    Ok(())
+}
+
 #[test]
 fn test_tokens_unicode_xid_ident() -> Result<(), Box<EvalAltResult>> {
     let engine = Engine::new();
@@ -110,3 +228,30 @@ fn test_tokens_unicode_xid_ident() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_tokens_stream() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let script = "let x = 0; for n in 0..10 { x += n; } x";
+
+    let ast = engine.compile_stream(script.chars())?;
+    assert_eq!(engine.eval_ast::<INT>(&ast)?, 45);
+
+    #[cfg(not(feature = "no_std"))]
+    {
+        let ast = engine.compile_from_reader(r#"let s = "héllo ✓"; s.len()"#.as_bytes())?;
+        assert_eq!(engine.eval_ast::<INT>(&ast)?, 7);
+
+        let mut bytes = r#""héllo""#.as_bytes().to_vec();
+        bytes.insert(3, 0xff);
+        assert!(matches!(
+            *engine
+                .compile_from_reader(&bytes[..])
+                .expect_err("should error"),
+            EvalAltResult::ErrorSystem(..)
+        ));
+    }
+
+    Ok(())
+}
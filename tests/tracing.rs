@@ -0,0 +1,69 @@
+#![cfg(feature = "tracing")]
+use rhai::{Engine, EvalAltResult, INT};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Subscriber that records the names of all spans and the function names of all events.
+#[derive(Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+struct FunctionVisitor(Option<String>);
+
+impl Visit for FunctionVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "function" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "rhai"
+    }
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut log = self.0.lock().unwrap();
+        log.push(span.metadata().name().to_string());
+        Id::from_u64(log.len() as u64)
+    }
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FunctionVisitor(None);
+        event.record(&mut visitor);
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("slow {}", visitor.0.unwrap_or_default()));
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn test_tracing() -> Result<(), Box<EvalAltResult>> {
+    let recorder = Recorder::default();
+    let mut engine = Engine::new();
+    engine.set_slow_fn_threshold(Duration::ZERO);
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        engine.eval::<INT>("40 + 2")?;
+
+        #[cfg(not(feature = "no_function"))]
+        engine.eval::<INT>("fn foo(x) { x + 1 } foo(41)")?;
+
+        Ok::<_, Box<EvalAltResult>>(())
+    })?;
+
+    let log = recorder.0.lock().unwrap();
+
+    assert_eq!(log[0], "eval");
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(log[1..], ["eval", "call", "slow foo"]);
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use rhai::{Dynamic, Engine, EvalAltResult, ParseErrorType, Scope, Tuple, INT};
+
+#[test]
+fn test_tuples() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(engine.eval::<String>("type_of((1, 2))")?, "tuple");
+    assert_eq!(engine.eval::<INT>("len((1, 'x', true))")?, 3);
+    assert_eq!(engine.eval::<INT>("len((42,))")?, 1);
+    assert_eq!(engine.eval::<INT>("(42)")?, 42);
+    assert_eq!(
+        engine.eval::<String>(r#"to_string((1, "a"))"#)?,
+        r#"(1, "a")"#
+    );
+    assert_eq!(engine.eval::<String>("`${(42,)}`")?, "(42,)");
+    assert!(engine.eval::<bool>("(1, 2) == (1, 2)")?);
+    assert!(engine.eval::<bool>("(1, 2) != (1, 2, 3)")?);
+    assert_eq!(
+        engine.eval::<INT>("let sum = 0; for x in (1, 2, 3) { sum += x } sum")?,
+        6
+    );
+
+    let t = engine.eval::<Tuple>("let x = 40; (x + 2, `${x}`)")?;
+    assert_eq!(t.len(), 2);
+    assert_eq!(t[0].as_int().unwrap(), 42);
+    assert_eq!(t[1].clone().into_string().unwrap(), "40");
+
+    let d = Dynamic::from(t.clone());
+    assert!(d.is::<Tuple>());
+    assert_eq!(d.type_name(), "tuple");
+    assert_eq!(d.read_lock::<Tuple>().unwrap().len(), 2);
+    assert_eq!(d.cast::<Tuple>()[0].as_int().unwrap(), 42);
+
+    let mut scope = Scope::new();
+    scope.push("t", t);
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "len(t)")?, 2);
+    assert!(engine.eval_with_scope::<bool>(&mut scope, r#"t == (42, "40")"#)?);
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        assert_eq!(engine.eval::<INT>("let t = (1, 2, 3); t[1]")?, 2);
+        assert_eq!(engine.eval::<INT>("let t = (1, 2, 3); t[-1]")?, 3);
+        assert_eq!(
+            engine.eval::<INT>("let t = (1, 2, 3); t[0] = 42; t[0]")?,
+            42
+        );
+        assert_eq!(engine.eval::<INT>("len(to_array((1, 2, 3)))")?, 3);
+        assert!(matches!(
+            *engine
+                .run("let t = (1, 2); t[2]")
+                .expect_err("should error"),
+            EvalAltResult::ErrorArrayBounds(2, 2, ..)
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_tuples_destructure() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    assert_eq!(
+        engine.eval::<INT>("let (a, b, c) = (1, 2, 3); a * 100 + b * 10 + c")?,
+        123
+    );
+    assert_eq!(
+        engine.eval::<INT>("let (a, _, c) = (1, 2, 3); a * 10 + c")?,
+        13
+    );
+    assert_eq!(
+        engine.eval::<INT>("let a = 1; let (a, b) = (a + 1, a + 2); a * 10 + b")?,
+        23
+    );
+    assert_eq!(engine.eval::<INT>("const (a, b) = (1, 2); a + b")?, 3);
+
+    #[cfg(not(feature = "no_function"))]
+    assert_eq!(
+        engine.eval::<INT>(
+            "
+                fn div_mod(a, b) { (a / b, a % b) }
+
+                let (q, r) = div_mod(17, 5);
+                q * 10 + r
+            "
+        )?,
+        32
+    );
+
+    assert_eq!(
+        engine.eval::<INT>("let a = 1; let b = 2; (a, b) = (b, a); a * 10 + b")?,
+        21
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = [0, 0]; let y = 0; (x[1], y) = (1, 2); x[1] * 10 + y")?,
+        12
+    );
+
+    #[cfg(not(feature = "no_object"))]
+    assert_eq!(
+        engine.eval::<INT>("let x = #{a: 0}; (x.a, x.b) = (1, 2); x.a * 10 + x.b")?,
+        12
+    );
+
+    assert!(matches!(
+        *engine
+            .run("let (a, b) = (1, 2, 3);")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+    assert!(matches!(
+        *engine.run("let (a, b) = 42;").expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+    assert!(matches!(
+        *engine
+            .run("let a = 1; let b = 2; (a, b) = (1,);")
+            .expect_err("should error"),
+        EvalAltResult::ErrorMismatchDataType(..)
+    ));
+
+    let mut scope = Scope::new();
+    scope.push_constant("c", 1 as INT);
+    assert!(matches!(
+        *engine
+            .run_with_scope(&mut scope, "let a = 0; (a, c) = (1, 2);")
+            .expect_err("should error"),
+        EvalAltResult::ErrorAssignmentToConstant(c, ..) if c == "c"
+    ));
+
+    assert!(matches!(
+        engine
+            .compile("let (a, a) = (1, 2);")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::DuplicatedVariable(..)
+    ));
+    assert!(matches!(
+        engine
+            .compile("const (a, b) = (1, 2); a = 3;")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::AssignmentToConstant(..)
+    ));
+    assert!(matches!(
+        engine
+            .compile("let a = 1; let b = 2; (a, b) += (1, 2);")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::AssignmentToInvalidLHS(..)
+    ));
+    assert!(matches!(
+        engine
+            .compile("let a = 1; (a, 2) = (1, 2);")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::AssignmentToConstant(..)
+    ));
+
+    Ok(())
+}
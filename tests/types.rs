@@ -1,4 +1,4 @@
-use rhai::{Engine, EvalAltResult, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, INT};
 
 #[test]
 fn test_type_of() -> Result<(), Box<EvalAltResult>> {
@@ -57,3 +57,123 @@ fn test_type_of() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_type_names() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone)]
+    struct TestStruct;
+
+    #[derive(Clone)]
+    struct Unregistered;
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<TestStruct>("Hello")
+        .register_fn("new_ts", || TestStruct)
+        .register_fn("new_list", || vec![TestStruct])
+        .register_fn("new_other", || Unregistered);
+
+    // Paths are never shown
+    assert_eq!(
+        engine.eval::<String>("type_of(new_other())")?,
+        "Unregistered"
+    );
+    assert_eq!(engine.eval::<String>("type_of(new_list())")?, "Vec<Hello>");
+    assert_eq!(
+        engine.map_type_name(std::any::type_name::<
+            std::collections::BTreeMap<String, Vec<(TestStruct, Unregistered)>>,
+        >()),
+        "BTreeMap<string, Vec<(Hello, Unregistered)>>"
+    );
+    assert_eq!(
+        engine.map_type_name(std::any::type_name::<&mut [Option<TestStruct>; 2]>()),
+        "&mut [Option<Hello>; 2]"
+    );
+
+    assert!(matches!(
+        *engine.eval::<Vec<TestStruct>>("new_other()").unwrap_err(),
+        EvalAltResult::ErrorMismatchOutputType(need, actual, ..)
+            if need == "Vec<Hello>" && actual == "Unregistered"
+    ));
+
+    // Display names map back to type names
+    assert_eq!(
+        engine.find_type_name("Hello"),
+        Some(std::any::type_name::<TestStruct>())
+    );
+    assert_eq!(engine.find_type_name("Unregistered"), None);
+
+    // Types in switch patterns are matched by display names
+    let script = r#"
+        switch new_list() {
+            is Hello => 1,
+            is "alloc::vec::Vec<Hello>" => 2,
+            _ => 3
+        }
+    "#;
+    assert_eq!(engine.eval::<INT>(script)?, 2);
+    assert_eq!(
+        engine.eval::<INT>(&script.replace("new_list", "new_ts"))?,
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_try_cast_result() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new();
+
+    let value = engine.eval::<Dynamic>(r#""hello""#)?;
+    let value = value.try_cast_result::<INT>().unwrap_err();
+    assert_eq!(engine.map_type_name(value.type_name()), "string");
+    assert_eq!(value.try_cast_result::<String>().unwrap(), "hello");
+
+    let value = engine.eval::<Dynamic>("42")?;
+    assert_eq!(
+        value.clone().try_cast_result::<Dynamic>().unwrap().as_int(),
+        Ok(42)
+    );
+    assert_eq!(value.try_cast_result::<INT>().unwrap(), 42);
+
+    Ok(())
+}
+
+#[test]
+#[cfg(not(feature = "no_index"))]
+#[cfg(not(feature = "no_object"))]
+fn test_container_conversions() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+
+    engine
+        .register_fn("find", |x: INT| -> Dynamic {
+            if x > 0 {
+                Some(x).into()
+            } else {
+                None::<INT>.into()
+            }
+        })
+        .register_fn("parse", |s: &str| -> Dynamic {
+            s.parse::<INT>().map_err(|e| e.to_string()).into()
+        })
+        .register_fn("pair", |x: INT| -> Dynamic {
+            (x, x.to_string(), true).into()
+        });
+
+    assert_eq!(engine.eval::<INT>("find(42)")?, 42);
+    assert!(engine.eval::<bool>("find(0) == ()")?);
+    assert_eq!(engine.eval::<INT>(r#"parse("42").ok"#)?, 42);
+    assert!(engine.eval::<bool>(r#"let r = parse("x"); "err" in r && !("ok" in r)"#)?);
+    assert_eq!(
+        engine.eval::<String>("let p = pair(1); `${p.len()} ${p[1]} ${p[2]}`")?,
+        "3 1 true"
+    );
+
+    let mut map = std::collections::HashMap::new();
+    map.insert("a".to_string(), 1 as INT);
+    let value: Dynamic = map.into();
+    assert!(value.is::<rhai::Map>());
+
+    Ok(())
+}
@@ -13,3 +13,54 @@ fn test_unary_minus() -> Result<(), Box<EvalAltResult>> {
 
     Ok(())
 }
+
+#[test]
+fn test_unary_ops_builtin() -> Result<(), Box<EvalAltResult>> {
+    let engine = Engine::new_raw();
+
+    assert_eq!(engine.eval::<INT>("let x = 5; -x")?, -5);
+    assert_eq!(engine.eval::<INT>("let x = 5; +x")?, 5);
+    assert!(engine.eval::<bool>("let x = false; !x")?);
+
+    #[cfg(not(feature = "unchecked"))]
+    assert!(matches!(
+        *engine
+            .eval::<INT>(&format!("let x = {}; x -= 1; -x", INT::MIN + 1))
+            .expect_err("should error"),
+        EvalAltResult::ErrorArithmetic(..)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_unary_ops_custom_type() -> Result<(), Box<EvalAltResult>> {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Point(INT, INT);
+
+    let mut engine = Engine::new();
+
+    engine
+        .register_type_with_name::<Point>("Point")
+        .register_fn("point", |x: INT, y: INT| Point(x, y))
+        .register_fn("-", |p: Point| Point(-p.0, -p.1))
+        .register_fn("!", |p: Point| Point(p.1, p.0));
+
+    assert_eq!(
+        engine.eval::<Point>("let p = point(1, 2); -p")?,
+        Point(-1, -2)
+    );
+    assert_eq!(
+        engine.eval::<Point>("let p = point(1, 2); !-p")?,
+        Point(-2, -1)
+    );
+
+    engine.set_fast_operators(false);
+
+    assert_eq!(
+        engine.eval::<Point>("let p = point(1, 2); -p")?,
+        Point(-1, -2)
+    );
+
+    Ok(())
+}
@@ -153,6 +153,79 @@ fn test_scope_eval() -> Result<(), Box<EvalAltResult>> {
     Ok(())
 }
 
+#[test]
+fn test_scope_frozen() -> Result<(), Box<EvalAltResult>> {
+    let mut engine = Engine::new();
+    engine.set_strict_constants(true);
+
+    let mut base = Scope::new();
+    base.push_constant("A", 40 as INT);
+    base.push("b", 2 as INT);
+    let base = base.freeze();
+
+    let mut scope = Scope::with_base(&base);
+
+    assert_eq!(engine.eval_with_scope::<INT>(&mut scope, "A + b")?, 42);
+    assert_eq!(scope.len(), 0);
+
+    assert!(matches!(
+        *engine
+            .compile_with_scope(&scope, "b = 1")
+            .expect_err("should error")
+            .err_type(),
+        ParseErrorType::AssignmentToConstant(..)
+    ));
+
+    engine.run_with_scope(&mut scope, "let b = b * 10; b += 1;")?;
+    assert_eq!(scope.len(), 1);
+    assert_eq!(scope.get_value::<INT>("b").expect("b should exist"), 21);
+    assert_eq!(base.get_value::<INT>("b").expect("b should exist"), 2);
+
+    #[cfg(not(feature = "no_function"))]
+    #[cfg(not(feature = "no_closure"))]
+    assert_eq!(
+        engine.eval_with_scope::<INT>(
+            &mut Scope::with_base(&base),
+            "let f = |x| x + A; call(f, 2)"
+        )?,
+        42
+    );
+
+    #[cfg(not(feature = "no_index"))]
+    {
+        let mut base = Scope::new();
+        base.push("list", vec![rhai::Dynamic::from(1 as INT)]);
+        let base = base.freeze();
+
+        assert_eq!(
+            engine.eval_with_scope::<INT>(
+                &mut Scope::with_base(&base),
+                "let c = list; c.push(2); c.len() + list.len() + list[0]"
+            )?,
+            4
+        );
+        #[cfg(not(feature = "no_closure"))]
+        assert!(engine
+            .run_with_scope(&mut Scope::with_base(&base), "list.push(2);")
+            .is_err());
+        assert_eq!(
+            base.get_value::<rhai::Array>("list")
+                .expect("list should exist")
+                .len(),
+            1
+        );
+    }
+
+    let frozen = scope.freeze();
+    assert_eq!(frozen.len(), 3);
+    assert_eq!(
+        engine.eval_with_scope::<INT>(&mut Scope::with_base(&frozen), "A + b")?,
+        61
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_var_resolver() -> Result<(), Box<EvalAltResult>> {
     let mut engine = Engine::new();